@@ -0,0 +1,195 @@
+//! Quick actions offered in a result's context menu.
+//!
+//! Which actions a result gets depends on its `file_type` and extension;
+//! `lib.rs` routes a picked action to the matching launcher function.
+
+use crate::db::FileEntry;
+use serde::Serialize;
+
+/// An action that can be run on an indexed result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickAction {
+    Open,
+    RunAsAdmin,
+    OpenFileLocation,
+    OpenTargetLocation,
+    OpenInTerminal,
+    OpenWith,
+    ExtractHere,
+    CopyPath,
+    CopyFile,
+    Properties,
+    Delete,
+}
+
+/// An action as sent to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionItem {
+    pub id: &'static str,
+    pub label: &'static str,
+    /// The frontend must confirm before running it (see `run_action`).
+    pub needs_confirm: bool,
+}
+
+impl QuickAction {
+    const ALL: [QuickAction; 11] = [
+        QuickAction::Open,
+        QuickAction::RunAsAdmin,
+        QuickAction::OpenFileLocation,
+        QuickAction::OpenTargetLocation,
+        QuickAction::OpenInTerminal,
+        QuickAction::OpenWith,
+        QuickAction::ExtractHere,
+        QuickAction::CopyPath,
+        QuickAction::CopyFile,
+        QuickAction::Properties,
+        QuickAction::Delete,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            QuickAction::Open => "open",
+            QuickAction::RunAsAdmin => "run_as_admin",
+            QuickAction::OpenFileLocation => "open_file_location",
+            QuickAction::OpenTargetLocation => "open_target_location",
+            QuickAction::OpenInTerminal => "open_in_terminal",
+            QuickAction::OpenWith => "open_with",
+            QuickAction::ExtractHere => "extract_here",
+            QuickAction::CopyPath => "copy_path",
+            QuickAction::CopyFile => "copy_file",
+            QuickAction::Properties => "properties",
+            QuickAction::Delete => "delete",
+        }
+    }
+
+    /// Parse an action id returned by `get_actions`.
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.id() == id)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuickAction::Open => "Open",
+            QuickAction::RunAsAdmin => "Run as administrator",
+            QuickAction::OpenFileLocation => "Open file location",
+            QuickAction::OpenTargetLocation => "Open target location",
+            QuickAction::OpenInTerminal => "Open in terminal",
+            QuickAction::OpenWith => "Open with…",
+            QuickAction::ExtractHere => "Extract here",
+            QuickAction::CopyPath => "Copy path",
+            QuickAction::CopyFile => "Copy file",
+            QuickAction::Properties => "Properties",
+            QuickAction::Delete => "Move to Recycle Bin",
+        }
+    }
+
+    /// Actions that lose data can't run without confirmation.
+    pub fn needs_confirm(self) -> bool {
+        self == QuickAction::Delete
+    }
+
+    pub fn item(self) -> ActionItem {
+        ActionItem {
+            id: self.id(),
+            label: self.label(),
+            needs_confirm: self.needs_confirm(),
+        }
+    }
+}
+
+/// Extensions the shell can run as administrator.
+fn can_elevate(extension: &str) -> bool {
+    matches!(extension, "exe" | "msi" | "bat" | "cmd" | "com" | "lnk")
+}
+
+/// The actions offered for `entry`, in menu order.
+pub fn actions_for(entry: &FileEntry) -> Vec<QuickAction> {
+    let extension = entry.extension.to_lowercase();
+    let mut actions = vec![QuickAction::Open];
+
+    if can_elevate(&extension) {
+        actions.push(QuickAction::RunAsAdmin);
+    }
+    match entry.file_type.as_str() {
+        "folder" => actions.push(QuickAction::OpenInTerminal),
+        // Internet shortcuts point at a URL, not a file
+        "shortcut" if extension == "lnk" => actions.push(QuickAction::OpenTargetLocation),
+        "app" | "shortcut" => {}
+        _ => actions.push(QuickAction::OpenWith),
+    }
+    if crate::archive::is_archive(&extension) {
+        actions.push(QuickAction::ExtractHere);
+    }
+    actions.extend([
+        QuickAction::OpenFileLocation,
+        QuickAction::CopyPath,
+        QuickAction::CopyFile,
+        QuickAction::Properties,
+        QuickAction::Delete,
+    ]);
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(filename: &str, file_type: &str) -> FileEntry {
+        FileEntry {
+            id: 1,
+            filename: filename.to_string(),
+            filepath: format!(r"C:\Users\me\{}", filename),
+            extension: filename.rsplit_once('.').map(|(_, ext)| ext.to_string()).unwrap_or_default(),
+            file_size: 0,
+            modified_at: 0,
+            file_type: file_type.to_string(),
+            click_count: 0,
+            last_accessed: 0,
+            icon_path: None,
+            child_count: None,
+            display_name: String::new(),
+        }
+    }
+
+    fn has(entry: &FileEntry, action: QuickAction) -> bool {
+        actions_for(entry).contains(&action)
+    }
+
+    #[test]
+    fn test_actions_depend_on_file_type() {
+        let app = entry("Code.EXE", "app");
+        assert!(has(&app, QuickAction::RunAsAdmin));
+        assert!(has(&app, QuickAction::OpenFileLocation));
+        assert!(!has(&app, QuickAction::OpenWith));
+
+        let shortcut = entry("Spotify.lnk", "shortcut");
+        assert!(has(&shortcut, QuickAction::RunAsAdmin));
+        assert!(has(&shortcut, QuickAction::OpenTargetLocation));
+
+        let internet_shortcut = entry("Docs.url", "shortcut");
+        assert!(!has(&internet_shortcut, QuickAction::RunAsAdmin));
+        assert!(!has(&internet_shortcut, QuickAction::OpenTargetLocation));
+
+        let folder = entry("Projects", "folder");
+        assert!(has(&folder, QuickAction::OpenInTerminal));
+        assert!(!has(&folder, QuickAction::RunAsAdmin));
+
+        let document = entry("report.pdf", "document");
+        assert!(has(&document, QuickAction::CopyPath));
+        assert!(has(&document, QuickAction::OpenWith));
+        assert!(!has(&document, QuickAction::RunAsAdmin));
+        assert!(!has(&document, QuickAction::OpenInTerminal));
+        assert!(!has(&document, QuickAction::ExtractHere));
+        assert!(has(&entry("photos.ZIP", "other"), QuickAction::ExtractHere));
+    }
+
+    #[test]
+    fn test_action_ids_round_trip() {
+        for action in QuickAction::ALL {
+            assert_eq!(QuickAction::from_id(action.id()), Some(action));
+        }
+        assert_eq!(QuickAction::from_id("format_disk"), None);
+        assert!(QuickAction::Delete.item().needs_confirm);
+        assert!(!QuickAction::CopyPath.item().needs_confirm);
+    }
+}
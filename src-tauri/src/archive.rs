@@ -0,0 +1,326 @@
+//! "Extract here" for archive results.
+//!
+//! An archive is extracted into a new folder named after it. Zip files are
+//! read with the `zip` crate, entry by entry, reporting progress as they go;
+//! other formats are handed to 7-Zip when `7z.exe` is installed.
+
+use log::{info, warn};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Error returned by [`extract_archive`] when extracting would replace
+/// existing files. The frontend can ask and retry with `overwrite`.
+pub const WOULD_OVERWRITE: &str = "would_overwrite";
+
+/// Extensions offered "Extract here". Anything but zip needs 7-Zip.
+const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "7z", "rar", "tar", "gz", "tgz", "bz2", "xz"];
+
+/// Minimum time between progress reports while extracting.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far an extraction has got, sent as `extract-progress` events.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtractProgress {
+    pub archive: String,
+    pub entries_done: usize,
+    pub entries_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Whether files with this extension can be extracted.
+pub fn is_archive(extension: &str) -> bool {
+    ARCHIVE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+/// Extract `filepath` into a new folder named after it, inside `destination`
+/// (the archive's own folder by default), and return that folder. Fails with
+/// [`WOULD_OVERWRITE`] before writing anything if a file would be replaced,
+/// unless `overwrite` is set.
+pub fn extract_archive(
+    filepath: &str,
+    destination: Option<&str>,
+    overwrite: bool,
+    on_progress: impl FnMut(&ExtractProgress),
+) -> Result<PathBuf, String> {
+    let archive = Path::new(filepath);
+    if !archive.is_file() {
+        return Err(format!("File not found: {}", filepath));
+    }
+    let parent = match destination {
+        Some(dir) => PathBuf::from(dir),
+        None => archive.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    if !parent.is_dir() {
+        return Err(format!("Destination folder not found: {}", parent.display()));
+    }
+    let target = parent.join(folder_name(archive));
+
+    let extension = archive
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    info!("Extracting {} into {}", filepath, target.display());
+    if extension == "zip" {
+        extract_zip(archive, &target, overwrite, on_progress)?;
+    } else {
+        extract_with_7zip(archive, &target, overwrite, on_progress)?;
+    }
+    Ok(target)
+}
+
+/// Folder name for an archive's contents: its name without the archive
+/// extensions (`logs.tar.gz` → `logs`).
+fn folder_name(archive: &Path) -> String {
+    let mut name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    while let Some((stem, extension)) = name.rsplit_once('.') {
+        if stem.is_empty() || !is_archive(extension) {
+            break;
+        }
+        name.truncate(stem.len());
+    }
+    name
+}
+
+/// The relative path an entry should be written to, or `None` if its name
+/// could escape the target folder: `..` components, absolute paths, drive
+/// letters or alternate data streams. Both slash styles are separators, as
+/// malicious archives use backslashes to get past checks for `/`.
+fn safe_entry_path(name: &str) -> Option<PathBuf> {
+    if name.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            _ if part.contains(':') => return None,
+            _ => {}
+        }
+        // Only plain names may remain once split (no prefixes or roots)
+        if !matches!(Path::new(part).components().next(), Some(Component::Normal(_))) {
+            return None;
+        }
+        path.push(part);
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// Throttles progress reports to one per [`PROGRESS_INTERVAL`].
+struct ProgressReporter<F: FnMut(&ExtractProgress)> {
+    progress: ExtractProgress,
+    last_report: Option<Instant>,
+    on_progress: F,
+}
+
+impl<F: FnMut(&ExtractProgress)> ProgressReporter<F> {
+    fn report(&mut self, force: bool) {
+        if force || self.last_report.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL) {
+            (self.on_progress)(&self.progress);
+            self.last_report = Some(Instant::now());
+        }
+    }
+}
+
+fn extract_zip(
+    archive_path: &Path,
+    target: &Path,
+    overwrite: bool,
+    on_progress: impl FnMut(&ExtractProgress),
+) -> Result<(), String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open '{}': {}", archive_path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))
+        .map_err(|e| format!("'{}' is not a readable zip file: {}", archive_path.display(), e))?;
+
+    // Check every entry before writing anything, so a bad entry can't leave
+    // a half-extracted folder behind
+    let mut plan = Vec::with_capacity(archive.len());
+    let mut bytes_total = 0;
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(|e| e.to_string())?;
+        let relative = safe_entry_path(entry.name())
+            .ok_or_else(|| format!("Refusing to extract: unsafe path '{}' in the archive", entry.name()))?;
+        if entry.is_symlink() {
+            warn!("Skipping symbolic link '{}' in {}", entry.name(), archive_path.display());
+            continue;
+        }
+        let out = target.join(relative);
+        if !entry.is_dir() {
+            if !overwrite && out.exists() {
+                return Err(WOULD_OVERWRITE.to_string());
+            }
+            bytes_total += entry.size();
+        }
+        plan.push((index, out, entry.is_dir()));
+    }
+
+    let mut reporter = ProgressReporter {
+        progress: ExtractProgress {
+            archive: archive_path.to_string_lossy().to_string(),
+            entries_done: 0,
+            entries_total: plan.len(),
+            bytes_done: 0,
+            bytes_total,
+        },
+        last_report: None,
+        on_progress,
+    };
+    let mut buffer = vec![0u8; 64 * 1024];
+    for (index, out, is_dir) in plan {
+        if is_dir {
+            std::fs::create_dir_all(&out).map_err(|e| format!("Failed to create '{}': {}", out.display(), e))?;
+        } else {
+            if let Some(parent) = out.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+            }
+            let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+            let mut output = OpenOptions::new()
+                .write(true)
+                .create(overwrite)
+                .truncate(overwrite)
+                .create_new(!overwrite)
+                .open(&out)
+                .map_err(|e| format!("Failed to create '{}': {}", out.display(), e))?;
+            loop {
+                let read = entry
+                    .read(&mut buffer)
+                    .map_err(|e| format!("Failed to extract '{}': {}", out.display(), e))?;
+                if read == 0 {
+                    break;
+                }
+                output
+                    .write_all(&buffer[..read])
+                    .map_err(|e| format!("Failed to write '{}': {}", out.display(), e))?;
+                reporter.progress.bytes_done += read as u64;
+                reporter.report(false);
+            }
+        }
+        reporter.progress.entries_done += 1;
+        reporter.report(false);
+    }
+    reporter.report(true);
+    Ok(())
+}
+
+/// 7-Zip's command-line program, on PATH or in its default install folder.
+fn find_7zip() -> Option<PathBuf> {
+    crate::launcher::find_on_path("7z.exe").or_else(|| {
+        let installed = PathBuf::from(std::env::var_os("ProgramFiles")?).join("7-Zip").join("7z.exe");
+        installed.is_file().then_some(installed)
+    })
+}
+
+/// Extract with 7-Zip, which drops `..` from entry paths itself. It can't be
+/// asked beforehand what would be overwritten, so without `overwrite` an
+/// existing, non-empty target folder is refused.
+fn extract_with_7zip(
+    archive: &Path,
+    target: &Path,
+    overwrite: bool,
+    mut on_progress: impl FnMut(&ExtractProgress),
+) -> Result<(), String> {
+    let extension = archive.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    let seven_zip = find_7zip().ok_or_else(|| format!("Extracting .{} files needs 7-Zip installed", extension))?;
+    let target_has_files = std::fs::read_dir(target).is_ok_and(|mut entries| entries.next().is_some());
+    if !overwrite && target_has_files {
+        return Err(WOULD_OVERWRITE.to_string());
+    }
+
+    let output = crate::launcher::no_window(&mut Command::new(&seven_zip))
+        .arg("x")
+        .arg(archive)
+        .arg(format!("-o{}", target.display()))
+        .arg(if overwrite { "-aoa" } else { "-aos" })
+        .arg("-y")
+        .output()
+        .map_err(|e| format!("Failed to run 7-Zip: {}", e))?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("7-Zip couldn't extract '{}': {}", archive.display(), message));
+    }
+
+    let bytes = std::fs::metadata(archive).map(|m| m.len()).unwrap_or(0);
+    on_progress(&ExtractProgress {
+        archive: archive.to_string_lossy().to_string(),
+        entries_done: 1,
+        entries_total: 1,
+        bytes_done: bytes,
+        bytes_total: bytes,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_safe_entry_path_rejects_traversal() {
+        assert_eq!(safe_entry_path("docs/readme.txt"), Some(PathBuf::from("docs").join("readme.txt")));
+        assert_eq!(safe_entry_path("./a//b.txt"), Some(PathBuf::from("a").join("b.txt")));
+        assert_eq!(safe_entry_path(r"..\..\evil.exe"), None);
+        assert_eq!(safe_entry_path("docs/../../evil.exe"), None);
+        assert_eq!(safe_entry_path("/etc/passwd"), None);
+        assert_eq!(safe_entry_path(r"\Windows\evil.dll"), None);
+        assert_eq!(safe_entry_path(r"C:\evil.exe"), None);
+        assert_eq!(safe_entry_path("notes.txt:hidden"), None);
+        assert_eq!(safe_entry_path("./"), None);
+    }
+
+    #[test]
+    fn test_folder_name() {
+        assert_eq!(folder_name(Path::new("photos.zip")), "photos");
+        assert_eq!(folder_name(Path::new("logs.tar.gz")), "logs");
+        assert_eq!(folder_name(Path::new("v1.2.zip")), "v1.2");
+        assert_eq!(folder_name(Path::new(".zip")), ".zip");
+    }
+
+    #[test]
+    fn test_extract_zip_refuses_overwrite_and_traversal() {
+        let dir = std::env::temp_dir().join(format!("ancheck-extract-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("bundle.zip");
+        write_zip(&archive, &[("a.txt", "alpha"), ("sub/b.txt", "beta")]);
+
+        let mut reports = Vec::new();
+        let folder = extract_archive(&archive.to_string_lossy(), None, false, |p| reports.push(p.clone())).unwrap();
+        assert_eq!(folder, dir.join("bundle"));
+        assert_eq!(std::fs::read_to_string(folder.join("sub").join("b.txt")).unwrap(), "beta");
+        let last = reports.last().unwrap();
+        assert_eq!((last.entries_done, last.bytes_done), (2, 9));
+
+        // A second run would replace the files
+        let again = extract_archive(&archive.to_string_lossy(), None, false, |_| {});
+        assert_eq!(again, Err(WOULD_OVERWRITE.to_string()));
+        std::fs::write(folder.join("a.txt"), "changed").unwrap();
+        extract_archive(&archive.to_string_lossy(), None, true, |_| {}).unwrap();
+        assert_eq!(std::fs::read_to_string(folder.join("a.txt")).unwrap(), "alpha");
+
+        let evil = dir.join("evil.zip");
+        write_zip(&evil, &[("fine.txt", "ok"), (r"..\..\evil.exe", "MZ")]);
+        let result = extract_archive(&evil.to_string_lossy(), None, false, |_| {});
+        assert!(result.unwrap_err().contains("unsafe path"));
+        assert!(!dir.join("evil").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Starting with Windows through the current user's `Run` registry key.
+//!
+//! The entry runs this executable with [`HIDDEN_ARG`], so a start at sign-in
+//! goes straight to the tray without showing the window. A portable copy
+//! also passes [`PORTABLE_ARG`], so it starts on its own data either way.
+
+use crate::paths::{AppPaths, PORTABLE_ARG};
+use std::path::Path;
+
+/// Command-line flag that starts the app with its window hidden.
+pub const HIDDEN_ARG: &str = "--hidden";
+
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+const VALUE_NAME: &str = "AnCheck";
+
+/// The `Run` value that starts `exe` hidden.
+fn command_line(exe: &Path, portable: bool) -> String {
+    let command = format!("\"{}\" {}", exe.display(), HIDDEN_ARG);
+    if portable {
+        format!("{} {}", command, PORTABLE_ARG)
+    } else {
+        command
+    }
+}
+
+fn current_command_line() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find the app's executable: {}", e))?;
+    Ok(command_line(&exe, AppPaths::current().is_portable()))
+}
+
+pub fn is_enabled() -> Result<bool, String> {
+    Ok(read_run_value()?.is_some())
+}
+
+pub fn enable() -> Result<(), String> {
+    write_run_value(&current_command_line()?)
+}
+
+pub fn disable() -> Result<(), String> {
+    delete_run_value()
+}
+
+/// If autostart is on but the executable it starts is gone (the app was
+/// moved, or an old install removed), point it at this one. An entry for
+/// another copy that still exists, such as the installed app while a dev
+/// build runs, is left alone. Returns whether the entry was rewritten.
+pub fn repair() -> Result<bool, String> {
+    let Some(value) = read_run_value()? else {
+        return Ok(false);
+    };
+    let expected = current_command_line()?;
+    if !needs_repair(&value, &expected, |exe| exe.exists()) {
+        return Ok(false);
+    }
+    write_run_value(&expected)?;
+    Ok(true)
+}
+
+/// Whether the `Run` value `value` should be replaced with `expected`.
+fn needs_repair(value: &str, expected: &str, exists: impl Fn(&Path) -> bool) -> bool {
+    // Paths on Windows are case-insensitive
+    if value.trim().eq_ignore_ascii_case(expected) {
+        return false;
+    }
+    recorded_exe(value).is_none_or(|exe| !exists(Path::new(exe)))
+}
+
+/// The executable a `Run` value starts: its quoted first part, or up to the
+/// first space when it isn't quoted.
+fn recorded_exe(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let exe = match value.strip_prefix('"') {
+        Some(rest) => rest.split('"').next(),
+        None => value.split_whitespace().next(),
+    };
+    exe.filter(|exe| !exe.is_empty())
+}
+
+#[cfg(windows)]
+fn read_run_value() -> Result<Option<String>, String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    let key = HSTRING::from(RUN_KEY);
+    let name = HSTRING::from(VALUE_NAME);
+    let mut size = 0u32;
+    // SAFETY: the strings outlive the call; with no buffer only the size is written.
+    let status = unsafe { RegGetValueW(HKEY_CURRENT_USER, &key, &name, RRF_RT_REG_SZ, None, None, Some(&mut size)) };
+    if status == ERROR_FILE_NOT_FOUND {
+        return Ok(None);
+    }
+    status.ok().map_err(|e| format!("Failed to read autostart entry: {}", e))?;
+
+    let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+    // SAFETY: `buffer` holds `size` bytes, as the first call asked for.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &name,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+    };
+    status.ok().map_err(|e| format!("Failed to read autostart entry: {}", e))?;
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Ok(Some(String::from_utf16_lossy(&buffer[..len])))
+}
+
+#[cfg(windows)]
+fn write_run_value(value: &str) -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let key = HSTRING::from(RUN_KEY);
+    let name = HSTRING::from(VALUE_NAME);
+    let data: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: `data` is a null-terminated UTF-16 string of the given byte length.
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &name,
+            REG_SZ.0,
+            Some(data.as_ptr().cast()),
+            (data.len() * 2) as u32,
+        )
+    }
+    .ok()
+    .map_err(|e| format!("Failed to enable autostart: {}", e))
+}
+
+#[cfg(windows)]
+fn delete_run_value() -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{RegDeleteKeyValueW, HKEY_CURRENT_USER};
+
+    let key = HSTRING::from(RUN_KEY);
+    let name = HSTRING::from(VALUE_NAME);
+    // SAFETY: the strings outlive the call.
+    let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, &key, &name) };
+    if status == ERROR_FILE_NOT_FOUND {
+        return Ok(());
+    }
+    status.ok().map_err(|e| format!("Failed to disable autostart: {}", e))
+}
+
+#[cfg(not(windows))]
+fn read_run_value() -> Result<Option<String>, String> {
+    Ok(None)
+}
+
+#[cfg(not(windows))]
+fn write_run_value(_value: &str) -> Result<(), String> {
+    Err("Autostart is only supported on Windows".to_string())
+}
+
+#[cfg(not(windows))]
+fn delete_run_value() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line_quotes_path() {
+        let exe = Path::new(r"C:\Program Files\AnCheck\ancheck.exe");
+        assert_eq!(command_line(exe, false), r#""C:\Program Files\AnCheck\ancheck.exe" --hidden"#);
+        let exe = Path::new(r"E:\AnCheck\ancheck.exe");
+        assert_eq!(command_line(exe, true), r#""E:\AnCheck\ancheck.exe" --hidden --portable"#);
+    }
+
+    #[test]
+    fn test_repair_only_replaces_a_missing_executable() {
+        let installed = r#""C:\Program Files\AnCheck\ancheck.exe" --hidden"#;
+        let dev = r#""C:\src\win-light\target\debug\ancheck.exe" --hidden"#;
+        assert_eq!(recorded_exe(installed), Some(r"C:\Program Files\AnCheck\ancheck.exe"));
+        assert_eq!(recorded_exe(r"C:\AnCheck\ancheck.exe --hidden"), Some(r"C:\AnCheck\ancheck.exe"));
+        assert_eq!(recorded_exe("  "), None);
+
+        // A dev build leaves the installed app's entry alone
+        assert!(!needs_repair(installed, dev, |_| true));
+        // ...unless the installed app is gone
+        assert!(needs_repair(installed, dev, |_| false));
+        // The same entry needs nothing, whatever the case of its path
+        assert!(!needs_repair(&installed.to_uppercase(), installed, |_| false));
+        assert!(needs_repair("", installed, |_| true));
+    }
+}
@@ -0,0 +1,247 @@
+//! Performance benchmark for catching regressions in search and indexing:
+//! a synthetic corpus is written to a temporary database, then a fixed
+//! workload of exact, prefix, short, fuzzy and filtered queries runs against it.
+//!
+//! Run from the `run_benchmark` command, `ancheck --bench`, or as a
+//! guardrail with `cargo test --release bench_guardrail -- --ignored`.
+
+use crate::db::Database;
+use crate::indexer::{classify_file, FileRow};
+use crate::searcher::{self, FilenameSnapshot, SearchOptions};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Corpus size when none is given.
+pub const DEFAULT_ROWS: usize = 100_000;
+pub const MAX_ROWS: usize = 2_000_000;
+/// Times each workload query runs.
+const ITERATIONS: usize = 20;
+/// Rows per write transaction, as the indexer batches them.
+const BATCH_SIZE: usize = 500;
+
+const WORDS: &[&str] = &[
+    "report", "setup", "photo", "invoice", "project", "backup", "notes", "draft", "studio", "player", "driver",
+    "update", "config", "manual", "budget", "slides",
+];
+const EXTENSIONS: &[&str] = &["pdf", "docx", "xlsx", "png", "txt", "exe", "lnk", "rs"];
+const FOLDERS: &[&str] = &["Documents", "Downloads", "Desktop", "Pictures", "Projects"];
+
+/// The queries run against the corpus, by kind.
+const WORKLOAD: &[(&str, &str)] = &[
+    ("exact", "invoice budget 4242.pdf"),
+    ("exact", "setup"),
+    ("prefix", "phot"),
+    ("prefix", "proj"),
+    ("short", "p"),
+    ("short", "re"),
+    ("fuzzy", "invbdg"),
+    ("fuzzy", "phtupd"),
+    ("filtered", "report ext:pdf"),
+    ("filtered", "notes in:downloads -type:app"),
+];
+
+/// Latencies in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryTiming {
+    pub kind: &'static str,
+    pub query: &'static str,
+    pub results: usize,
+    pub timings: Percentiles,
+}
+
+/// Result of `run_benchmark` and `ancheck --bench`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub rows: usize,
+    /// Time to write the corpus to the database.
+    pub index_ms: f64,
+    pub index_rows_per_sec: f64,
+    /// Time to load the in-memory search snapshot.
+    pub snapshot_ms: f64,
+    pub queries: Vec<QueryTiming>,
+    /// Over every query of the workload.
+    pub overall: Percentiles,
+}
+
+/// `count` rows spread over [`FOLDERS`], named from pairs of [`WORDS`] so
+/// every word matches many files, as in a real index.
+pub fn synthetic_rows(count: usize) -> Vec<FileRow> {
+    (0..count)
+        .map(|n| {
+            let extension = EXTENSIONS[n % EXTENSIONS.len()];
+            let filename = format!(
+                "{} {} {}.{}",
+                WORDS[n % WORDS.len()],
+                WORDS[(n / WORDS.len()) % WORDS.len()],
+                n,
+                extension
+            );
+            let filepath = format!(r"C:\Users\bench\{}\{}", FOLDERS[(n / 7) % FOLDERS.len()], filename);
+            let file_type = classify_file(extension, &filepath);
+            let size = (n as i64 % 5000) * 1024;
+            let modified = 1_700_000_000 + n as i64 * 60;
+            (filename, filepath, extension.to_string(), size, modified, file_type)
+        })
+        .collect()
+}
+
+/// Benchmark `rows` synthetic entries in a temporary database, deleted
+/// afterwards.
+pub fn run(rows: usize) -> Result<BenchReport, String> {
+    if !(1..=MAX_ROWS).contains(&rows) {
+        return Err(format!("The benchmark takes between 1 and {} rows", MAX_ROWS));
+    }
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let run = RUNS.fetch_add(1, Ordering::Relaxed);
+    let path = std::env::temp_dir().join(format!("ancheck-bench-{}-{}.db", std::process::id(), run));
+    remove_database(&path);
+    let report = run_at(&path, rows);
+    remove_database(&path);
+    report
+}
+
+fn run_at(path: &Path, rows: usize) -> Result<BenchReport, String> {
+    let db = Arc::new(Database::open(path).map_err(|e| format!("Failed to create the database: {}", e))?);
+    let corpus = synthetic_rows(rows);
+
+    let start = Instant::now();
+    for batch in corpus.chunks(BATCH_SIZE) {
+        db.upsert_files_batch(batch)
+            .map_err(|e| format!("Failed to write the corpus: {}", e))?;
+    }
+    let index_time = start.elapsed();
+
+    let snapshot = FilenameSnapshot::default();
+    let start = Instant::now();
+    snapshot.refresh(&db)?;
+    let snapshot_time = start.elapsed();
+
+    let options = SearchOptions::default();
+    let mut all = Vec::with_capacity(WORKLOAD.len() * ITERATIONS);
+    let mut queries = Vec::with_capacity(WORKLOAD.len());
+    for &(kind, query) in WORKLOAD {
+        let mut samples = Vec::with_capacity(ITERATIONS);
+        let mut results = 0;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            let response = searcher::search(&db, &snapshot, query, &options, &|| false)?;
+            samples.push(start.elapsed());
+            results = response.results.len();
+        }
+        all.extend_from_slice(&samples);
+        queries.push(QueryTiming {
+            kind,
+            query,
+            results,
+            timings: percentiles(&mut samples),
+        });
+    }
+
+    Ok(BenchReport {
+        rows,
+        index_ms: millis(index_time),
+        index_rows_per_sec: rows as f64 / index_time.as_secs_f64().max(f64::EPSILON),
+        snapshot_ms: millis(snapshot_time),
+        queries,
+        overall: percentiles(&mut all),
+    })
+}
+
+/// Nearest-rank percentiles of `samples`, which get sorted.
+fn percentiles(samples: &mut [Duration]) -> Percentiles {
+    samples.sort();
+    let rank = |p: f64| {
+        let index = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+        samples.get(index.saturating_sub(1)).copied().map_or(0.0, millis)
+    };
+    Percentiles {
+        p50_ms: rank(50.0),
+        p95_ms: rank(95.0),
+        p99_ms: rank(99.0),
+        max_ms: rank(100.0),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn remove_database(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(file));
+    }
+}
+
+/// The report as a table for the terminal.
+pub fn format_report(report: &BenchReport) -> String {
+    let mut lines = vec![
+        format!(
+            "{} rows indexed in {:.0} ms ({:.0} rows/s), snapshot loaded in {:.0} ms",
+            report.rows, report.index_ms, report.index_rows_per_sec, report.snapshot_ms
+        ),
+        String::new(),
+        format!("{:<9} {:<30} {:>7} {:>9} {:>9} {:>9}", "kind", "query", "results", "p50 ms", "p95 ms", "p99 ms"),
+    ];
+    for query in &report.queries {
+        lines.push(format!(
+            "{:<9} {:<30} {:>7} {:>9.2} {:>9.2} {:>9.2}",
+            query.kind, query.query, query.results, query.timings.p50_ms, query.timings.p95_ms, query.timings.p99_ms
+        ));
+    }
+    lines.push(format!(
+        "{:<9} {:<30} {:>7} {:>9.2} {:>9.2} {:>9.2}",
+        "overall", "", "", report.overall.p50_ms, report.overall.p95_ms, report.overall.p99_ms
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_benchmark_runs_the_workload() {
+        let report = run(2_000).unwrap();
+        assert_eq!(report.rows, 2_000);
+        assert_eq!(report.queries.len(), WORKLOAD.len());
+        for query in &report.queries {
+            assert!(query.results > 0, "{} found nothing", query.query);
+            assert!(query.timings.p50_ms <= query.timings.p95_ms);
+        }
+        assert!(report.index_rows_per_sec > 0.0);
+        assert!(run(0).is_err());
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+        let p = percentiles(&mut samples);
+        assert_eq!((p.p50_ms, p.p95_ms, p.p99_ms, p.max_ms), (50.0, 95.0, 99.0, 100.0));
+        assert_eq!(percentiles(&mut []).max_ms, 0.0);
+    }
+
+    /// Fails when searching 100k rows gets slow. The limit leaves room for
+    /// a single slow core (p95 was about 90 ms on one); timings only mean
+    /// something in release builds: run with
+    /// `cargo test --release bench_guardrail -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_guardrail() {
+        let report = run(DEFAULT_ROWS).unwrap();
+        println!("{}", format_report(&report));
+        assert!(report.overall.p95_ms < 150.0, "search p95 {:.1} ms at 100k rows", report.overall.p95_ms);
+    }
+}
@@ -0,0 +1,352 @@
+//! Browser bookmarks from Chrome, Edge and Firefox, indexed by
+//! [`BookmarksProvider`] so a tool that only lives as a bookmark can be
+//! found by its title.
+//!
+//! Chrome and Edge keep each profile's bookmarks in a `Bookmarks` JSON file
+//! they replace on save, so it's read in place. Firefox keeps them in
+//! `places.sqlite`, locked while it runs, so that is copied to a temporary
+//! folder first. A URL bookmarked in several browsers is indexed once.
+
+use crate::db::Database;
+use crate::indexer::{NewEntry, Provider, ProviderReport};
+use log::warn;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// The `file_type` of indexed bookmarks.
+pub const BOOKMARK_FILE_TYPE: &str = "bookmark";
+
+const BOOKMARK_BROWSER_KEY: &str = "bookmark_browser";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+}
+
+impl Browser {
+    /// In the order their bookmarks are read; the first to have a URL is
+    /// its source.
+    pub const ALL: [Browser; 3] = [Browser::Chrome, Browser::Edge, Browser::Firefox];
+
+    /// Where the browser is installed, if it is.
+    pub fn executable(self) -> Option<PathBuf> {
+        let (roots, path): (&[&str], &str) = match self {
+            Browser::Chrome => (
+                &["ProgramFiles", "ProgramFiles(x86)", "LOCALAPPDATA"],
+                r"Google\Chrome\Application\chrome.exe",
+            ),
+            Browser::Edge => (&["ProgramFiles(x86)", "ProgramFiles"], r"Microsoft\Edge\Application\msedge.exe"),
+            Browser::Firefox => (&["ProgramFiles", "ProgramFiles(x86)"], r"Mozilla Firefox\firefox.exe"),
+        };
+        roots
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(|root| PathBuf::from(root).join(path))
+            .find(|exe| exe.exists())
+    }
+
+    /// Every bookmarks file of every profile.
+    fn bookmark_files(self) -> Vec<PathBuf> {
+        let profiles_dir = match self {
+            Browser::Chrome => dirs::data_local_dir().map(|dir| dir.join(r"Google\Chrome\User Data")),
+            Browser::Edge => dirs::data_local_dir().map(|dir| dir.join(r"Microsoft\Edge\User Data")),
+            Browser::Firefox => dirs::data_dir().map(|dir| dir.join(r"Mozilla\Firefox\Profiles")),
+        };
+        let file_name = match self {
+            Browser::Firefox => "places.sqlite",
+            Browser::Chrome | Browser::Edge => "Bookmarks",
+        };
+        let Some(Ok(profiles)) = profiles_dir.map(std::fs::read_dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = profiles
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join(file_name))
+            .filter(|file| file.is_file())
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Title and URL of each bookmark in `file`.
+    fn read(self, file: &Path) -> Result<Vec<(String, String)>, String> {
+        match self {
+            Browser::Firefox => read_firefox_bookmarks(file),
+            Browser::Chrome | Browser::Edge => {
+                let json = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+                parse_chromium_bookmarks(&json)
+            }
+        }
+    }
+}
+
+/// Which browser opens a bookmark.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookmarkBrowser {
+    /// The default browser.
+    #[default]
+    Default,
+    /// The browser the bookmark came from, if it's still installed.
+    Source,
+}
+
+impl BookmarkBrowser {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BookmarkBrowser::Default => "default",
+            BookmarkBrowser::Source => "source",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "default" => Some(BookmarkBrowser::Default),
+            "source" => Some(BookmarkBrowser::Source),
+            _ => None,
+        }
+    }
+}
+
+pub fn bookmark_browser(db: &Database) -> BookmarkBrowser {
+    db.get_meta(BOOKMARK_BROWSER_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| BookmarkBrowser::parse(&value))
+        .unwrap_or_default()
+}
+
+pub fn set_bookmark_browser(db: &Database, browser: BookmarkBrowser) -> Result<(), String> {
+    db.set_meta(BOOKMARK_BROWSER_KEY, browser.as_str())
+        .map_err(|e| format!("Failed to save bookmark browser: {}", e))
+}
+
+/// The browser each URL indexed by the last complete run came from.
+static SOURCES: Mutex<Option<HashMap<String, Browser>>> = Mutex::new(None);
+
+/// The executable to open `url` with under the `bookmark_browser` setting:
+/// the browser it was bookmarked in, or `None` for the default browser.
+pub fn browser_for(db: &Database, url: &str) -> Option<PathBuf> {
+    if bookmark_browser(db) != BookmarkBrowser::Source {
+        return None;
+    }
+    let browser = *SOURCES.lock().unwrap().as_ref()?.get(url)?;
+    browser.executable()
+}
+
+/// Indexes the bookmarks of every Chrome, Edge and Firefox profile.
+pub struct BookmarksProvider;
+
+impl BookmarksProvider {
+    pub const NAME: &'static str = "bookmarks";
+}
+
+impl Provider for BookmarksProvider {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn owned_file_type(&self) -> Option<&'static str> {
+        Some(BOOKMARK_FILE_TYPE)
+    }
+
+    fn collect(&self, sink: &mut dyn FnMut(NewEntry), cancel: &AtomicBool) -> Result<ProviderReport, String> {
+        let files: Vec<(Browser, PathBuf)> = Browser::ALL
+            .into_iter()
+            .flat_map(|browser| browser.bookmark_files().into_iter().map(move |file| (browser, file)))
+            .collect();
+        let (sources, report) = collect_bookmarks(&files, sink, cancel);
+        if !cancel.load(Ordering::Relaxed) {
+            *SOURCES.lock().unwrap() = Some(sources);
+        }
+        Ok(report)
+    }
+}
+
+/// Pass the bookmarks in `files` to `sink`, each URL once, and return the
+/// browser each came from. A file that can't be read is skipped.
+fn collect_bookmarks(
+    files: &[(Browser, PathBuf)],
+    sink: &mut dyn FnMut(NewEntry),
+    cancel: &AtomicBool,
+) -> (HashMap<String, Browser>, ProviderReport) {
+    let mut report = ProviderReport::default();
+    let mut sources = HashMap::new();
+    for (browser, file) in files {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let bookmarks = match browser.read(file) {
+            Ok(bookmarks) => bookmarks,
+            Err(e) => {
+                warn!("Failed to read bookmarks from '{}': {}", file.display(), e);
+                report.skipped += 1;
+                continue;
+            }
+        };
+        for (title, url) in bookmarks {
+            if sources.contains_key(&url) {
+                continue;
+            }
+            sources.insert(url.clone(), *browser);
+            sink(NewEntry {
+                filename: title,
+                filepath: url,
+                extension: String::new(),
+                file_size: 0,
+                modified_at: 0,
+                file_type: BOOKMARK_FILE_TYPE.to_string(),
+                child_count: None,
+            });
+        }
+    }
+    (sources, report)
+}
+
+/// Title and URL of each web bookmark in a Chrome or Edge `Bookmarks` file,
+/// folders included.
+fn parse_chromium_bookmarks(json: &str) -> Result<Vec<(String, String)>, String> {
+    fn walk(node: &Value, bookmarks: &mut Vec<(String, String)>) {
+        match node["type"].as_str() {
+            Some("url") => {
+                if let (Some(name), Some(url)) = (node["name"].as_str(), node["url"].as_str()) {
+                    push_bookmark(bookmarks, name, url);
+                }
+            }
+            Some("folder") => {
+                for child in node["children"].as_array().into_iter().flatten() {
+                    walk(child, bookmarks);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let file: Value = serde_json::from_str(json).map_err(|e| format!("Invalid bookmarks file: {}", e))?;
+    let roots = file["roots"].as_object().ok_or("Invalid bookmarks file: no roots")?;
+    let mut bookmarks = Vec::new();
+    for root in roots.values() {
+        walk(root, &mut bookmarks);
+    }
+    Ok(bookmarks)
+}
+
+/// Title and URL of each web bookmark in a Firefox `places.sqlite`, read
+/// from a copy so Firefox's lock doesn't get in the way.
+fn read_firefox_bookmarks(places: &Path) -> Result<Vec<(String, String)>, String> {
+    let dir = std::env::temp_dir().join(format!("ancheck-places-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let copy = dir.join("places.sqlite");
+    let result = (|| {
+        std::fs::copy(places, &copy).map_err(|e| format!("Failed to copy: {}", e))?;
+        // Recent changes may still be in the write-ahead log
+        let mut wal = places.as_os_str().to_owned();
+        wal.push("-wal");
+        if Path::new(&wal).exists() {
+            std::fs::copy(&wal, dir.join("places.sqlite-wal")).map_err(|e| format!("Failed to copy: {}", e))?;
+        }
+        let conn = Connection::open(&copy).map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT b.title, p.url FROM moz_bookmarks b JOIN moz_places p ON p.id = b.fk
+                 WHERE b.type = 1 ORDER BY b.id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| e.to_string())?;
+        let mut bookmarks = Vec::new();
+        for (title, url) in rows.filter_map(|r| r.ok()) {
+            push_bookmark(&mut bookmarks, title.as_deref().unwrap_or_default(), &url);
+        }
+        Ok(bookmarks)
+    })();
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+/// Add a bookmark if it's a web page; an untitled one goes by its URL.
+fn push_bookmark(bookmarks: &mut Vec<(String, String)>, title: &str, url: &str) {
+    if !crate::launcher::is_web_url(url) {
+        return;
+    }
+    let title = title.trim();
+    let title = if title.is_empty() { url } else { title };
+    bookmarks.push((title.to_string(), url.to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_BOOKMARKS: &str = r#"{
+        "checksum": "0",
+        "roots": {
+            "bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+                {"type": "url", "name": "Deploy dashboard", "url": "https://deploy.internal/"},
+                {"type": "folder", "name": "Tools", "children": [
+                    {"type": "url", "name": "  ", "url": "https://wiki.internal/"},
+                    {"type": "url", "name": "Script", "url": "javascript:void(0)"}
+                ]}
+            ]},
+            "other": {"type": "folder", "name": "Other", "children": []}
+        },
+        "version": 1
+    }"#;
+
+    #[test]
+    fn test_parse_chromium_bookmarks() {
+        assert_eq!(
+            parse_chromium_bookmarks(CHROME_BOOKMARKS).unwrap(),
+            [
+                ("Deploy dashboard".to_string(), "https://deploy.internal/".to_string()),
+                ("https://wiki.internal/".to_string(), "https://wiki.internal/".to_string()),
+            ]
+        );
+        assert!(parse_chromium_bookmarks("{").is_err());
+        assert!(parse_chromium_bookmarks("{}").is_err());
+    }
+
+    #[test]
+    fn test_collect_reads_each_browser_and_dedupes_urls() {
+        let dir = std::env::temp_dir().join(format!("ancheck-bookmarks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let chrome = dir.join("Bookmarks");
+        std::fs::write(&chrome, CHROME_BOOKMARKS).unwrap();
+        let places = dir.join("places.sqlite");
+        {
+            let conn = Connection::open(&places).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT NOT NULL);
+                 CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, title TEXT);
+                 INSERT INTO moz_places VALUES (1, 'https://deploy.internal/'), (2, 'https://tickets.internal/');
+                 INSERT INTO moz_bookmarks VALUES (1, 2, NULL, 'Menu'), (2, 1, 1, 'Deploy (Firefox)'),
+                                                  (3, 1, 2, 'Tickets');",
+            )
+            .unwrap();
+        }
+
+        let files = [
+            (Browser::Chrome, chrome),
+            (Browser::Edge, dir.join("missing")),
+            (Browser::Firefox, places),
+        ];
+        let mut entries = Vec::new();
+        let (sources, report) = collect_bookmarks(&files, &mut |entry| entries.push(entry), &AtomicBool::new(false));
+        let titles: Vec<&str> = entries.iter().map(|entry| entry.filename.as_str()).collect();
+        assert_eq!(titles, ["Deploy dashboard", "https://wiki.internal/", "Tickets"]);
+        assert!(entries.iter().all(|entry| entry.file_type == BOOKMARK_FILE_TYPE));
+        assert_eq!(sources.get("https://deploy.internal/"), Some(&Browser::Chrome));
+        assert_eq!(sources.get("https://tickets.internal/"), Some(&Browser::Firefox));
+        assert_eq!(report.skipped, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! Forgetting what AnCheck has stored, for privacy or before uninstalling.
+//!
+//! Usage data and the index are cleared in place (see
+//! [`Database::clear_usage_data`] and [`Database::clear_index`]). Deleting
+//! everything else can't happen from the running app, which holds the
+//! database and log open; [`spawn_after_exit`] starts the executable again
+//! with [`UNINSTALL_CLEANUP_ARG`], which waits for the app to quit and then
+//! deletes its folders. An uninstaller runs the same flag directly.
+
+use crate::db::WriterLock;
+use crate::paths::{AppPaths, PORTABLE_ARG};
+use crate::{autostart, deeplink};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Command-line flag that deletes all of AnCheck's data and registrations.
+pub const UNINSTALL_CLEANUP_ARG: &str = "--uninstall-cleanup";
+/// How long the cleanup waits for the app to quit.
+const QUIT_WAIT: Duration = Duration::from_secs(10);
+const QUIT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// What [`remove_everything`] did.
+#[derive(Debug, Default)]
+pub struct CleanupReport {
+    pub removed: Vec<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+/// Start the cleanup in a new process that outlives this one. The caller
+/// should quit right after.
+pub fn spawn_after_exit(paths: &AppPaths) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find the app's executable: {}", e))?;
+    let mut command = std::process::Command::new(exe);
+    command.arg(UNINSTALL_CLEANUP_ARG);
+    if paths.is_portable() {
+        command.arg(PORTABLE_ARG);
+    }
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start the cleanup: {}", e))
+}
+
+/// Wait up to [`QUIT_WAIT`] for the app to release the index at `db_path`.
+/// Returns false if it's still running.
+pub fn wait_for_app_to_quit(db_path: &Path) -> Result<bool, String> {
+    let deadline = Instant::now() + QUIT_WAIT;
+    loop {
+        if WriterLock::try_acquire(db_path)?.is_some() {
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        std::thread::sleep(QUIT_POLL_INTERVAL);
+    }
+}
+
+/// Remove the autostart entry and the `ancheck://` registration, then
+/// delete `folders`. Keeps going past failures, which are reported.
+pub fn remove_everything(folders: &[PathBuf]) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    for result in [autostart::disable(), deeplink::unregister()] {
+        if let Err(e) = result {
+            report.errors.push(e);
+        }
+    }
+    delete_folders(folders, &mut report);
+    report
+}
+
+fn delete_folders(folders: &[PathBuf], report: &mut CleanupReport) {
+    for folder in folders.iter().filter(|folder| folder.exists()) {
+        match std::fs::remove_dir_all(folder) {
+            Ok(()) => report.removed.push(folder.clone()),
+            Err(e) => report.errors.push(format!("Failed to delete {}: {}", folder.display(), e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, NewSnippet, UsageCleared};
+
+    #[test]
+    fn test_clear_usage_data_and_index() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        db.upsert_file("report.pdf", r"C:\Docs\report.pdf", "pdf", 10, 1, "document").unwrap();
+        db.upsert_file("notes.txt", r"C:\Docs\notes.txt", "txt", 10, 1, "document").unwrap();
+        db.record_click(r"C:\Docs\report.pdf").unwrap();
+        db.record_query_pick("rep", r"C:\Docs\report.pdf").unwrap();
+        db.record_context_pick("slack.exe", r"C:\Docs\report.pdf").unwrap();
+        let snippet = NewSnippet {
+            name: "Address".to_string(),
+            keywords: Vec::new(),
+            body: "1 Main St".to_string(),
+        };
+        let id = db.add_snippet(&snippet).unwrap();
+        db.record_snippet_use(id).unwrap();
+        db.add_clipboard_entry("copied", 5).unwrap();
+
+        let cleared = db.clear_usage_data().unwrap();
+        assert_eq!(
+            cleared,
+            UsageCleared {
+                launches: 1,
+                query_picks: 1,
+                context_picks: 1,
+                snippets: 1,
+                clipboard_entries: 1
+            }
+        );
+        assert!(db.recently_accessed(10).unwrap().is_empty());
+        assert!(db.query_picks_for("rep").unwrap().is_empty());
+        assert_eq!(db.snippet(id).unwrap().unwrap().use_count, 0);
+        assert_eq!(db.clear_usage_data().unwrap(), UsageCleared::default());
+
+        assert_eq!(db.clear_index().unwrap(), 2);
+        assert_eq!(db.file_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delete_folders() {
+        let root = std::env::temp_dir().join(format!("ancheck-cleanup-{}", std::process::id()));
+        let data = root.join("data");
+        std::fs::create_dir_all(data.join("logs")).unwrap();
+        std::fs::write(data.join("logs").join("ancheck.log"), "log").unwrap();
+
+        let mut report = CleanupReport::default();
+        delete_folders(&[data.clone(), root.join("never-created")], &mut report);
+        assert_eq!(report.removed, [data.as_path()]);
+        assert!(report.errors.is_empty());
+        assert!(!data.exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
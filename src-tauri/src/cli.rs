@@ -0,0 +1,374 @@
+//! Scripting the launcher from a terminal without opening its window:
+//! `ancheck --query "invoice 2024" --limit 5 --json` prints matching files
+//! and `ancheck --rebuild-index` rebuilds the index from scratch once, like
+//! the tray's "Rebuild index". `lib.rs` calls [`main`] before starting
+//! Tauri.
+//!
+//! Queries open the database read-only, so they work next to a running app.
+//! A rebuild writes, so it takes the [`WriterLock`] first and refuses to run
+//! while the app, or another rebuild, holds it. `--uninstall-cleanup`
+//! waits for the app to quit before deleting its data (see `cleanup`).
+//! `--bench` runs the performance benchmark on a throwaway database (see
+//! `bench`).
+
+use crate::bench;
+use crate::cleanup::{self, UNINSTALL_CLEANUP_ARG};
+use crate::db::{Database, WriterLock};
+use crate::indexer::{self, IndexStatus};
+use crate::paths::{AppPaths, PORTABLE_ARG};
+use crate::searcher::{self, FilenameSnapshot, SearchOptions};
+use std::path::Path;
+use std::sync::Arc;
+
+pub const EXIT_OK: i32 = 0;
+/// A query matched nothing, as with grep.
+pub const EXIT_NO_RESULTS: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_FAILED: i32 = 3;
+/// `--rebuild-index` while another process is writing the index.
+pub const EXIT_BUSY: i32 = 4;
+
+const USAGE: &str = "Usage:
+  ancheck --query <text> [--limit <n>] [--json]   Print matching files, one path per line
+  ancheck --rebuild-index                         Rebuild the index once (not while AnCheck is running)
+  ancheck --uninstall-cleanup                     Delete the index, settings and logs, e.g. before uninstalling
+  ancheck --bench [--rows <n>] [--json]           Time indexing and searches on <n> synthetic files (default 100000)
+  ancheck --help                                  Show this help
+
+Add --portable to use the index in the data folder next to ancheck.exe.
+
+Exit codes: 0 ok, 1 no results, 2 bad arguments, 3 failed, 4 AnCheck is running";
+
+#[derive(Debug, PartialEq)]
+pub enum CliCommand {
+    Query {
+        query: String,
+        limit: Option<usize>,
+        json: bool,
+    },
+    RebuildIndex,
+    UninstallCleanup,
+    Bench {
+        rows: usize,
+        json: bool,
+    },
+    Help,
+}
+
+/// Run the command in `args` (without the program name) if there is one
+/// and return the exit code, or `None` to start the app normally.
+pub fn main(args: &[String], db_path: &Path) -> Option<i32> {
+    let command = match parse_args(args) {
+        Ok(command) => command?,
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("{}\n\n{}", e, USAGE);
+            return Some(EXIT_USAGE);
+        }
+    };
+    attach_parent_console();
+    Some(match command {
+        CliCommand::Help => {
+            println!("{}", USAGE);
+            EXIT_OK
+        }
+        CliCommand::Query { query, limit, json } => run_query(db_path, &query, limit, json),
+        CliCommand::RebuildIndex => rebuild_index(db_path),
+        CliCommand::UninstallCleanup => uninstall_cleanup(db_path),
+        CliCommand::Bench { rows, json } => run_bench(rows, json),
+    })
+}
+
+/// The command in `args`, `Ok(None)` if they hold no CLI flags (the app's
+/// own flags such as `--hidden` are left alone), or a usage error.
+pub fn parse_args(args: &[String]) -> Result<Option<CliCommand>, String> {
+    let mut query = None;
+    let mut limit = None;
+    let mut json = false;
+    let mut rebuild = false;
+    let mut uninstall = false;
+    let mut bench = false;
+    let mut rows = None;
+    let mut help = false;
+    let mut unknown = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match flag {
+            "--query" => query = Some(value()?),
+            "--limit" => {
+                let value = value()?;
+                let n = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("--limit needs a positive number, not '{}'", value))?;
+                limit = Some(n);
+            }
+            "--rows" => {
+                let value = value()?;
+                let n = value
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=bench::MAX_ROWS).contains(n))
+                    .ok_or_else(|| format!("--rows needs a number from 1 to {}, not '{}'", bench::MAX_ROWS, value))?;
+                rows = Some(n);
+            }
+            "--json" => json = true,
+            "--bench" => bench = true,
+            "--rebuild-index" => rebuild = true,
+            UNINSTALL_CLEANUP_ARG => uninstall = true,
+            "--help" | "-h" => help = true,
+            // Picks the database, which AppPaths already did
+            PORTABLE_ARG => {}
+            _ => unknown.push(arg),
+        }
+    }
+
+    if query.is_none() && limit.is_none() && !json && !rebuild && !uninstall && !bench && rows.is_none() && !help {
+        return Ok(None);
+    }
+    if help {
+        return Ok(Some(CliCommand::Help));
+    }
+    if let Some(arg) = unknown.first() {
+        return Err(format!("Unknown argument '{}'", arg));
+    }
+    if uninstall {
+        if query.is_some() || limit.is_some() || json || rebuild {
+            return Err("--uninstall-cleanup can't be combined with other commands".to_string());
+        }
+        return Ok(Some(CliCommand::UninstallCleanup));
+    }
+    if bench {
+        if query.is_some() || limit.is_some() || rebuild {
+            return Err("--bench can't be combined with other commands".to_string());
+        }
+        let rows = rows.unwrap_or(bench::DEFAULT_ROWS);
+        return Ok(Some(CliCommand::Bench { rows, json }));
+    }
+    if rows.is_some() {
+        return Err("--rows only applies to --bench".to_string());
+    }
+    match (query, rebuild) {
+        (Some(_), true) => Err("--query and --rebuild-index can't be combined".to_string()),
+        (Some(query), false) => Ok(Some(CliCommand::Query { query, limit, json })),
+        (None, true) if limit.is_some() || json => Err("--limit and --json only apply to --query".to_string()),
+        (None, true) => Ok(Some(CliCommand::RebuildIndex)),
+        (None, false) => Err("--limit and --json need --query".to_string()),
+    }
+}
+
+fn run_query(db_path: &Path, query: &str, limit: Option<usize>, json: bool) -> i32 {
+    if !db_path.exists() {
+        eprintln!("No index at {}; start AnCheck once to build it", db_path.display());
+        return EXIT_FAILED;
+    }
+    let db = match Database::open_read_only(db_path) {
+        Ok(db) => Arc::new(db),
+        Err(e) => {
+            eprintln!("Failed to open the index: {}", e);
+            return EXIT_FAILED;
+        }
+    };
+    let options = SearchOptions {
+        max_results: limit.map(searcher::clamp_result_limit).unwrap_or_else(|| searcher::default_result_limit(&db)),
+        min_score: searcher::default_min_score(&db),
+        hide_empty_files: searcher::hide_empty_files(&db),
+        large_file_bytes: searcher::large_file_bytes(searcher::large_file_mb(&db)),
+        ..SearchOptions::default()
+    };
+    let response = match searcher::search(&db, &FilenameSnapshot::default(), query, &options, &|| false) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Search failed: {}", e);
+            return EXIT_FAILED;
+        }
+    };
+    for error in &response.filter_errors {
+        eprintln!("Ignored filter: {}", error);
+    }
+
+    if json {
+        match serde_json::to_string_pretty(&response.results) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                eprintln!("Failed to format results: {}", e);
+                return EXIT_FAILED;
+            }
+        }
+    } else {
+        for result in &response.results {
+            println!("{}", result.filepath);
+        }
+    }
+    if response.results.is_empty() {
+        EXIT_NO_RESULTS
+    } else {
+        EXIT_OK
+    }
+}
+
+fn rebuild_index(db_path: &Path) -> i32 {
+    let _lock = match WriterLock::try_acquire(db_path) {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            eprintln!("AnCheck is running; rebuild the index from its tray menu instead");
+            return EXIT_BUSY;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILED;
+        }
+    };
+    let db = match Database::open(db_path) {
+        Ok(db) => Arc::new(db),
+        Err(e) => {
+            eprintln!("Failed to open the index: {}", e);
+            return EXIT_FAILED;
+        }
+    };
+    match indexer::full_index(&db, &IndexStatus::default(), true) {
+        Ok(indexed) => {
+            println!("Indexed {} files", indexed);
+            EXIT_OK
+        }
+        Err(e) => {
+            eprintln!("Index failed: {}", e);
+            EXIT_FAILED
+        }
+    }
+}
+
+fn run_bench(rows: usize, json: bool) -> i32 {
+    let report = match bench::run(rows) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            return EXIT_FAILED;
+        }
+    };
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                eprintln!("Failed to format the report: {}", e);
+                return EXIT_FAILED;
+            }
+        }
+    } else {
+        println!("{}", bench::format_report(&report));
+    }
+    EXIT_OK
+}
+
+fn uninstall_cleanup(db_path: &Path) -> i32 {
+    match cleanup::wait_for_app_to_quit(db_path) {
+        Ok(true) => {}
+        Ok(false) => {
+            eprintln!("AnCheck is still running; quit it from its tray menu first");
+            return EXIT_BUSY;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILED;
+        }
+    }
+    let report = cleanup::remove_everything(&AppPaths::current().all_dirs());
+    for folder in &report.removed {
+        println!("Deleted {}", folder.display());
+    }
+    for error in &report.errors {
+        eprintln!("{}", error);
+    }
+    if report.errors.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_FAILED
+    }
+}
+
+/// Release builds are GUI programs with no console of their own; print to
+/// the terminal that started us, if any.
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: no arguments beyond a constant; fails harmlessly if there's
+    // no parent console or one is already attached.
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<CliCommand>, String> {
+        parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(parse(&["--hidden"]), Ok(None));
+        assert_eq!(
+            parse(&["--query", "invoice 2024", "--limit=5", "--json"]),
+            Ok(Some(CliCommand::Query {
+                query: "invoice 2024".to_string(),
+                limit: Some(5),
+                json: true
+            }))
+        );
+        assert_eq!(parse(&["--rebuild-index"]), Ok(Some(CliCommand::RebuildIndex)));
+        assert_eq!(parse(&["--portable", "--rebuild-index"]), Ok(Some(CliCommand::RebuildIndex)));
+        assert_eq!(parse(&["--help", "--bogus"]), Ok(Some(CliCommand::Help)));
+
+        assert!(parse(&["--query"]).is_err());
+        assert!(parse(&["--query", "x", "--limit", "0"]).is_err());
+        assert!(parse(&["--query", "x", "--rebuild-index"]).is_err());
+        assert!(parse(&["--json"]).is_err());
+        assert!(parse(&["--rebuild-index", "--bogus"]).is_err());
+        assert_eq!(parse(&["--uninstall-cleanup"]), Ok(Some(CliCommand::UninstallCleanup)));
+        assert!(parse(&["--uninstall-cleanup", "--rebuild-index"]).is_err());
+        assert_eq!(
+            parse(&["--bench", "--rows=5000", "--json"]),
+            Ok(Some(CliCommand::Bench { rows: 5000, json: true }))
+        );
+        assert_eq!(
+            parse(&["--bench"]),
+            Ok(Some(CliCommand::Bench {
+                rows: bench::DEFAULT_ROWS,
+                json: false
+            }))
+        );
+        assert!(parse(&["--rows", "10"]).is_err());
+        assert!(parse(&["--bench", "--rebuild-index"]).is_err());
+    }
+
+    #[test]
+    fn test_writer_lock_is_exclusive() {
+        let dir = std::env::temp_dir().join(format!("ancheck-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("index.db");
+
+        let lock = WriterLock::try_acquire(&db_path).unwrap();
+        assert!(lock.is_some());
+        assert!(WriterLock::try_acquire(&db_path).unwrap().is_none());
+        drop(lock);
+        assert!(WriterLock::try_acquire(&db_path).unwrap().is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+use log::{info, warn};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many times to retry opening the clipboard when another process holds it.
+const OPEN_ATTEMPTS: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_millis(20);
+
+/// Process-wide clipboard handle.
+///
+/// The `arboard::Clipboard` is created lazily and then kept alive for the
+/// lifetime of the app, so copied text stays available even after the
+/// launcher window hides immediately after copying.
+#[derive(Default)]
+pub struct ClipboardHolder {
+    inner: Mutex<Option<arboard::Clipboard>>,
+}
+
+impl ClipboardHolder {
+    /// Put `text` on the system clipboard, retrying briefly if the clipboard
+    /// is locked by another application.
+    pub fn set_text(&self, text: &str) -> Result<(), String> {
+        let text = normalize_newlines(text);
+        self.write(|clipboard| clipboard.set_text(text.as_str()))?;
+        info!("Copied {} chars to clipboard", text.chars().count());
+        Ok(())
+    }
+
+    /// Put files on the clipboard (`CF_HDROP` on Windows), so they can be
+    /// pasted into Explorer or attached to an email.
+    pub fn set_files(&self, paths: &[&Path]) -> Result<(), String> {
+        self.write(|clipboard| clipboard.set().file_list(paths))?;
+        info!("Copied {} file(s) to clipboard", paths.len());
+        Ok(())
+    }
+
+    /// The text on the clipboard, or None if it holds none or is locked.
+    pub fn get_text(&self) -> Option<String> {
+        let mut guard = self.inner.lock().ok()?;
+        if guard.is_none() {
+            *guard = arboard::Clipboard::new().ok();
+        }
+        match guard.as_mut()?.get_text() {
+            Ok(text) => Some(text),
+            Err(arboard::Error::ContentNotAvailable) => None,
+            Err(e) => {
+                warn!("Clipboard read failed: {}", e);
+                // Drop the handle so the next read reopens it
+                *guard = None;
+                None
+            }
+        }
+    }
+
+    /// Run `write` against the clipboard, reopening it and retrying if it fails.
+    fn write(&self, write: impl Fn(&mut arboard::Clipboard) -> Result<(), arboard::Error>) -> Result<(), String> {
+        let mut guard = self
+            .inner
+            .lock()
+            .map_err(|_| "Clipboard lock poisoned".to_string())?;
+
+        let mut last_error = String::new();
+        for attempt in 1..=OPEN_ATTEMPTS {
+            if guard.is_none() {
+                match arboard::Clipboard::new() {
+                    Ok(clipboard) => *guard = Some(clipboard),
+                    Err(e) => {
+                        last_error = e.to_string();
+                        warn!("Clipboard unavailable (attempt {}): {}", attempt, e);
+                        std::thread::sleep(RETRY_DELAY);
+                        continue;
+                    }
+                }
+            }
+
+            let clipboard = guard.as_mut().expect("clipboard initialized above");
+            match write(clipboard) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    last_error = e.to_string();
+                    warn!("Clipboard write failed (attempt {}): {}", attempt, e);
+                    // Drop the handle so the next attempt reopens it
+                    *guard = None;
+                    std::thread::sleep(RETRY_DELAY);
+                }
+            }
+        }
+
+        Err(format!("Failed to copy to clipboard: {}", last_error))
+    }
+}
+
+/// Windows expects CRLF line endings in clipboard text; other platforms get
+/// the text unchanged.
+fn normalize_newlines(text: &str) -> String {
+    if cfg!(windows) {
+        text.replace("\r\n", "\n").replace('\n', "\r\n")
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,154 @@
+//! Opt-in clipboard history: while enabled, text copied anywhere is kept
+//! (up to [`MAX_CLIPBOARD_ENTRIES`](crate::db::MAX_CLIPBOARD_ENTRIES)) and
+//! found with a `clip ` or `>clipboard` query. Picking an entry copies it
+//! back.
+//!
+//! Off by default, since copied text includes passwords. Text a password
+//! manager marks as not for clipboard monitors is skipped; anything else
+//! can be deleted one entry at a time, or all at once with the
+//! "Clear clipboard history" command.
+//!
+//! The clipboard is polled rather than watched with a listener window; on
+//! Windows the clipboard sequence number makes an unchanged poll free.
+
+use crate::clipboard::ClipboardHolder;
+use crate::db::Database;
+use log::{debug, info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Copied texts longer than this aren't kept.
+pub const MAX_ENTRY_CHARS: usize = 10_000;
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+const ENABLED_KEY: &str = "clipboard_history";
+
+/// Whether clipboard history is on. Off unless the user turned it on.
+pub fn enabled(db: &Database) -> bool {
+    db.get_meta(ENABLED_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+pub fn set_enabled(db: &Database, enabled: bool) -> Result<(), String> {
+    db.set_meta(ENABLED_KEY, &enabled.to_string())
+        .map_err(|e| format!("Failed to save clipboard history setting: {}", e))
+}
+
+/// Keep `text` in the history. Blank and oversized texts are skipped;
+/// returns whether it was kept.
+pub fn record(db: &Database, text: &str) -> Result<bool, String> {
+    if text.trim().is_empty() {
+        return Ok(false);
+    }
+    if text.chars().count() > MAX_ENTRY_CHARS {
+        debug!("Not keeping a {}-char clipboard text", text.chars().count());
+        return Ok(false);
+    }
+    db.add_clipboard_entry(text, chrono::Utc::now().timestamp())
+        .map_err(|e| format!("Failed to save clipboard entry: {}", e))?;
+    Ok(true)
+}
+
+/// Poll the clipboard on a background thread, recording new text while
+/// `is_enabled` says so.
+pub fn watch(db: Arc<Database>, clipboard: Arc<ClipboardHolder>, is_enabled: impl Fn() -> bool + Send + 'static) {
+    std::thread::spawn(move || {
+        info!("Clipboard history watcher started");
+        let mut last_sequence = None;
+        let mut last_text: Option<String> = None;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            if !is_enabled() {
+                last_sequence = None;
+                last_text = None;
+                continue;
+            }
+            let sequence = sequence_number();
+            if sequence.is_some() && sequence == last_sequence {
+                continue;
+            }
+            last_sequence = sequence;
+            if excluded_by_source() {
+                continue;
+            }
+            let Some(text) = clipboard.get_text() else { continue };
+            if last_text.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+            if let Err(e) = record(&db, &text) {
+                warn!("{}", e);
+            }
+            last_text = Some(text);
+        }
+    });
+}
+
+/// Changes whenever anything is copied, or None where unavailable.
+#[cfg(windows)]
+pub(crate) fn sequence_number() -> Option<u32> {
+    use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+    // SAFETY: no arguments; returns 0 without window station access.
+    Some(unsafe { GetClipboardSequenceNumber() }).filter(|&n| n != 0)
+}
+
+#[cfg(not(windows))]
+pub(crate) fn sequence_number() -> Option<u32> {
+    None
+}
+
+/// Whether the copying app asked clipboard monitors and history to leave
+/// this content alone, as password managers do.
+#[cfg(windows)]
+fn excluded_by_source() -> bool {
+    use windows::core::w;
+    use windows::Win32::System::DataExchange::{IsClipboardFormatAvailable, RegisterClipboardFormatW};
+
+    // SAFETY: registering a format name only returns its id, and checking
+    // a format doesn't open the clipboard.
+    unsafe {
+        [
+            w!("ExcludeClipboardContentFromMonitorProcessing"),
+            // Set to 0 to opt out of history; it's rarely set otherwise
+            w!("CanIncludeInClipboardHistory"),
+        ]
+        .into_iter()
+        .map(|name| RegisterClipboardFormatW(name))
+        .any(|format| format != 0 && IsClipboardFormatAvailable(format).is_ok())
+    }
+}
+
+#[cfg(not(windows))]
+fn excluded_by_source() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MAX_CLIPBOARD_ENTRIES;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_history_is_off_by_default_and_capped() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        assert!(!enabled(&db));
+        set_enabled(&db, true).unwrap();
+        assert!(enabled(&db));
+
+        assert!(!record(&db, "  \n").unwrap());
+        assert!(!record(&db, &"x".repeat(MAX_ENTRY_CHARS + 1)).unwrap());
+        for i in 0..MAX_CLIPBOARD_ENTRIES + 5 {
+            db.add_clipboard_entry(&format!("entry {}", i), i).unwrap();
+        }
+        let entries = db.clipboard_entries().unwrap();
+        assert_eq!(entries.len() as i64, MAX_CLIPBOARD_ENTRIES);
+        assert_eq!(entries[0].text, format!("entry {}", MAX_CLIPBOARD_ENTRIES + 4));
+
+        // Copying a kept text again moves it to the top
+        db.add_clipboard_entry("entry 100", MAX_CLIPBOARD_ENTRIES + 10).unwrap();
+        let entries = db.clipboard_entries().unwrap();
+        assert_eq!((entries[0].text.as_str(), entries.len() as i64), ("entry 100", MAX_CLIPBOARD_ENTRIES));
+
+        assert!(db.remove_clipboard_entry(entries[0].id).unwrap());
+        assert_eq!(db.clipboard_entry_text(entries[0].id).unwrap(), None);
+        assert_eq!(db.clear_clipboard_history().unwrap() as i64, MAX_CLIPBOARD_ENTRIES - 1);
+    }
+}
@@ -0,0 +1,117 @@
+//! Entries the user adds by hand: a URL ("Standup notes" opening a Notion
+//! page), a command ("Deploy script" running `powershell -File deploy.ps1`)
+//! or a file or folder outside the indexed directories.
+//!
+//! They are rows in the `files` table marked `custom`, so search finds them
+//! like any file, while index passes never overwrite or remove them. A
+//! command runs whatever it says when picked, so adding or editing one
+//! needs the user's confirmation, recorded as `confirmed_at`.
+
+use crate::db::NewCustomEntry;
+use crate::env_vars;
+use crate::indexer;
+use crate::launcher::{self, EntryKind};
+use crate::system_actions::CONFIRMATION_REQUIRED;
+use std::path::Path;
+
+/// Validate a custom entry from the frontend. A command fails with
+/// [`CONFIRMATION_REQUIRED`] unless `confirmed`.
+pub fn prepare(name: &str, target: &str, kind: EntryKind, confirmed: bool) -> Result<NewCustomEntry, String> {
+    let name = name.trim();
+    let target = target.trim();
+    if name.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    if target.is_empty() {
+        return Err("Target cannot be empty".to_string());
+    }
+
+    let (file_type, confirmed_at) = match kind {
+        EntryKind::Url => {
+            if !launcher::is_web_url(target) {
+                return Err(format!("'{}' is not an http:// or https:// address", target));
+            }
+            ("url".to_string(), 0)
+        }
+        EntryKind::Command => {
+            if !confirmed {
+                return Err(CONFIRMATION_REQUIRED.to_string());
+            }
+            ("command".to_string(), chrono::Utc::now().timestamp())
+        }
+        EntryKind::File => {
+            let expanded = env_vars::expand_env_vars(target);
+            let path = Path::new(expanded.as_ref());
+            if !path.exists() {
+                return Err(format!("File not found: {}", target));
+            }
+            let extension = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (indexer::classify_file(&extension, &expanded), 0)
+        }
+    };
+    Ok(NewCustomEntry {
+        name: name.to_string(),
+        target: target.to_string(),
+        kind,
+        file_type,
+        confirmed_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_prepare_validates_kinds() {
+        let url = prepare(" Standup notes ", "https://notion.so/standup", EntryKind::Url, false).unwrap();
+        assert_eq!((url.name.as_str(), url.file_type.as_str()), ("Standup notes", "url"));
+        assert!(prepare("Notes", "notion.so/standup", EntryKind::Url, false).is_err());
+
+        let command = "powershell -File deploy.ps1";
+        assert_eq!(
+            prepare("Deploy script", command, EntryKind::Command, false),
+            Err(CONFIRMATION_REQUIRED.to_string())
+        );
+        let deploy = prepare("Deploy script", command, EntryKind::Command, true).unwrap();
+        assert!(deploy.confirmed_at > 0);
+
+        let missing = std::env::temp_dir().join("ancheck-custom-missing");
+        assert!(prepare("Gone", &missing.to_string_lossy(), EntryKind::File, false).is_err());
+        let folder = prepare("Temp", &std::env::temp_dir().to_string_lossy(), EntryKind::File, false).unwrap();
+        assert_eq!(folder.file_type, "folder");
+        assert!(prepare("  ", command, EntryKind::Command, true).is_err());
+    }
+
+    #[test]
+    fn test_custom_entries_survive_index_passes() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let url = prepare("Standup notes", "https://notion.so/standup", EntryKind::Url, false).unwrap();
+        let id = db.add_custom_entry(&url).unwrap().unwrap();
+        assert_eq!(db.add_custom_entry(&url).unwrap(), None);
+        let deploy = prepare("Deploy script", "powershell -File deploy.ps1", EntryKind::Command, true).unwrap();
+        db.add_custom_entry(&deploy).unwrap().unwrap();
+
+        // Neither target exists as a file, and the crawler can't rename them
+        assert_eq!(db.remove_missing_files(&[]).unwrap(), 0);
+        assert_eq!(db.remove_path("https://notion.so/standup").unwrap(), 0);
+        db.upsert_file("standup", "https://notion.so/standup", "", 0, 0, "other").unwrap();
+        let entries = db.custom_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].name, "Standup notes");
+        assert_eq!(entries[1].kind, EntryKind::Url);
+        assert_eq!(db.entry_kind("powershell -File deploy.ps1").unwrap(), (EntryKind::Command, true));
+        assert_eq!(db.entry_kind(r"C:\not\indexed.txt").unwrap(), (EntryKind::File, false));
+
+        let renamed = prepare("Standup", "https://notion.so/standup-2", EntryKind::Url, false).unwrap();
+        assert!(db.update_custom_entry(id, &renamed).unwrap());
+        assert!(db.remove_custom_entry(id).unwrap());
+        assert!(!db.remove_custom_entry(id).unwrap());
+        assert_eq!(db.custom_entries().unwrap().len(), 1);
+    }
+}
@@ -1,7 +1,13 @@
-use rusqlite::{params, Connection, Result as SqlResult};
+use crate::launcher::{ConsoleMode, EntryKind};
+use crate::searcher::{fold_text, BigramSignature, QueryFilters};
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, Result as SqlResult, ToSql};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, MutexGuard};
+use std::thread::JoinHandle;
 
 /// Represents a single indexed file entry stored in SQLite.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,49 +22,371 @@ pub struct FileEntry {
     pub click_count: i64,
     pub last_accessed: i64,
     pub icon_path: Option<String>,
+    /// Items directly inside a folder as of its last full index, capped at
+    /// `indexer::CHILD_COUNT_CAP`; `None` for files and uncounted folders.
+    pub child_count: Option<i64>,
+    /// The product name of an app or shortcut, searched and shown in place
+    /// of the filename (see `display_names`); empty when it has none.
+    pub display_name: String,
 }
 
+/// Minimal per-file data kept in memory for the fuzzy search stage.
+/// Strings are boxed (no spare capacity) and `file_type` points at a static
+/// category name, keeping a large snapshot's footprint bounded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactEntry {
+    pub id: i64,
+    pub filename: Box<str>,
+    pub filename_folded: Box<str>,
+    pub filepath: Box<str>,
+    pub file_size: i64,
+    pub file_type: &'static str,
+    pub click_count: i64,
+    pub last_accessed: i64,
+    pub modified_at: i64,
+    /// See [`FileEntry::child_count`].
+    pub child_count: Option<i64>,
+    /// See [`FileEntry::display_name`].
+    pub display_name: Box<str>,
+    pub display_name_folded: Box<str>,
+    /// Bigrams of both folded names, for the fuzzy stage's pre-filter.
+    pub bigrams: BigramSignature,
+}
+
+/// A remembered launch of `filepath` from the launcher, opened while an
+/// app (its context) had the focus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextPick {
+    pub filepath: String,
+    pub pick_count: i64,
+    pub last_used: i64,
+}
+
+/// A remembered choice: `filepath` was launched after typing `query` (folded).
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPick {
+    pub query: String,
+    pub filepath: String,
+    pub pick_count: i64,
+    pub last_used: i64,
+}
+
+/// A user-added entry: a URL, a command, or a file or folder outside the
+/// indexed directories.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CustomEntry {
+    pub id: i64,
+    pub name: String,
+    pub target: String,
+    pub kind: EntryKind,
+    /// When the user confirmed a command entry (Unix seconds); 0 if never.
+    pub confirmed_at: i64,
+}
+
+/// Fields for adding or editing a [`CustomEntry`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewCustomEntry {
+    pub name: String,
+    pub target: String,
+    pub kind: EntryKind,
+    pub file_type: String,
+    pub confirmed_at: i64,
+}
+
+/// A text snippet, found by name or keyword and copied (or pasted) when
+/// picked.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    pub id: i64,
+    pub name: String,
+    /// Extra words the snippet is found by, lowercase.
+    pub keywords: Vec<String>,
+    pub body: String,
+    pub use_count: i64,
+}
+
+/// Fields for adding or editing a [`Snippet`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewSnippet {
+    pub name: String,
+    pub keywords: Vec<String>,
+    pub body: String,
+}
+
+/// A text copied while clipboard history was on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClipboardEntry {
+    pub id: i64,
+    pub text: String,
+    /// When it was last copied (Unix seconds).
+    pub copied_at: i64,
+}
+
+/// What `clear_usage_data` forgot.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageCleared {
+    /// Files and custom entries whose launch count and time were reset.
+    pub launches: usize,
+    /// Remembered picks for a query.
+    pub query_picks: usize,
+    /// Remembered launches from an app's context.
+    pub context_picks: usize,
+    /// Snippets whose use count was reset.
+    pub snippets: usize,
+    pub clipboard_entries: usize,
+}
+
+/// Most clipboard history entries kept; the oldest are dropped beyond this.
+pub const MAX_CLIPBOARD_ENTRIES: i64 = 200;
+
+/// Most query picks kept; the least recently used are dropped beyond this.
+const MAX_QUERY_PICKS: i64 = 2000;
+
+/// How long launches stay in the launch log: long enough to cover this
+/// month and last week for the usage leaderboard.
+const LAUNCH_LOG_RETENTION_SECS: i64 = 62 * 86_400;
+
+/// Most context picks kept, like [`MAX_QUERY_PICKS`].
+const MAX_CONTEXT_PICKS: i64 = 1000;
+
+/// Map a stored file_type onto its static category name.
+fn intern_file_type(file_type: &str) -> &'static str {
+    match file_type {
+        "app" => "app",
+        "shortcut" => "shortcut",
+        "folder" => "folder",
+        "document" => "document",
+        "image" => "image",
+        "code" => "code",
+        "url" => "url",
+        "command" => "command",
+        "bookmark" => "bookmark",
+        _ => "other",
+    }
+}
+
+/// An exclusive lock on `<database>.lock`, held by whichever process writes
+/// the index: the app, or a `--rebuild-index` run. Released when dropped
+/// or when the process exits, even if it crashed.
+pub struct WriterLock(std::fs::File);
+
+impl WriterLock {
+    /// Take the lock for the database at `db_path`, or `Ok(None)` if
+    /// another process holds it.
+    pub fn try_acquire(db_path: &Path) -> Result<Option<WriterLock>, String> {
+        let mut lock_path = db_path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| format!("Failed to open index lock file: {}", e))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(WriterLock(file))),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(e)) => Err(format!("Failed to lock the index: {}", e)),
+        }
+    }
+}
+
+/// A write run on the writer thread with its connection.
+type WriteJob = Box<dyn FnOnce(&mut Connection) + Send>;
+
+/// Read connections kept open; a read takes whichever is free.
+const READ_CONNECTIONS: usize = 2;
+
 /// Thread-safe database wrapper.
+///
+/// Every write goes through a channel to one writer thread that owns the
+/// only connection allowed to write, so writes are serialized without
+/// callers holding a lock while they run. Public write methods enqueue a
+/// job and wait for its result. Reads use their own connections and, with
+/// WAL, never wait for a write, even a long batch.
 pub struct Database {
-    conn: Mutex<Connection>,
+    writer: Mutex<Option<Sender<WriteJob>>>,
+    writer_thread: Mutex<Option<JoinHandle<()>>>,
+    readers: Vec<Mutex<Connection>>,
+    /// Bumped after every write that changes which files search can see or
+    /// how they rank, so cached views of the table know they're stale.
+    files_generation: AtomicU64,
 }
 
 impl Database {
     /// Open or create the SQLite database at the given path.
-    pub fn open(db_path: &PathBuf) -> SqlResult<Self> {
-        let conn = Connection::open(db_path)?;
+    pub fn open(db_path: &Path) -> SqlResult<Self> {
+        Self::open_with_journal(db_path, "WAL", "NORMAL")
+    }
+
+    /// Like [`Database::open`], with a rollback journal instead of WAL, for
+    /// a database left in a synced folder: a sync client copying the `-wal`
+    /// file out of step with the database corrupts it. Reads then wait for
+    /// writes, and every commit is synced to disk, which outside WAL is what
+    /// keeps a power cut from corrupting it too.
+    pub fn open_without_wal(db_path: &Path) -> SqlResult<Self> {
+        Self::open_with_journal(db_path, "DELETE", "FULL")
+    }
+
+    fn open_with_journal(db_path: &Path, journal_mode: &str, synchronous: &str) -> SqlResult<Self> {
+        let path = connection_path(db_path);
+        let conn = Connection::open(&path)?;
 
         // Performance tunings for search-heavy workload
-        conn.execute_batch(
-            "PRAGMA journal_mode = WAL;
-             PRAGMA synchronous = NORMAL;
+        conn.execute_batch(&format!(
+            "PRAGMA journal_mode = {};
+             PRAGMA synchronous = {};
              PRAGMA cache_size = -64000;
              PRAGMA temp_store = MEMORY;
              PRAGMA mmap_size = 268435456;",
-        )?;
+            journal_mode, synchronous
+        ))?;
+        Self::create_tables(&conn)?;
+
+        let readers = (0..READ_CONNECTIONS)
+            .map(|_| {
+                let reader = Connection::open(&path)?;
+                // read_uncommitted only matters for a shared in-memory
+                // database, where readers would otherwise lock tables
+                reader.execute_batch(
+                    "PRAGMA cache_size = -16000;
+                     PRAGMA temp_store = MEMORY;
+                     PRAGMA mmap_size = 268435456;
+                     PRAGMA read_uncommitted = true;",
+                )?;
+                Ok(Mutex::new(reader))
+            })
+            .collect::<SqlResult<_>>()?;
+        Ok(Self::with_connections(conn, readers))
+    }
+
+    /// Open an existing database for reading only, e.g. for a CLI query
+    /// while the app is running. Tables are neither created nor migrated,
+    /// and writes fail.
+    pub fn open_read_only(db_path: &Path) -> SqlResult<Self> {
+        let open = || -> SqlResult<Connection> {
+            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            conn.execute_batch(
+                "PRAGMA cache_size = -16000;
+                 PRAGMA temp_store = MEMORY;",
+            )?;
+            Ok(conn)
+        };
+        Ok(Self::with_connections(open()?, vec![Mutex::new(open()?)]))
+    }
+
+    /// Start the writer thread on `writer`.
+    fn with_connections(writer: Connection, readers: Vec<Mutex<Connection>>) -> Self {
+        let (sender, jobs) = mpsc::channel::<WriteJob>();
+        let thread = std::thread::Builder::new()
+            .name("db-writer".to_string())
+            .spawn(move || {
+                let mut conn = writer;
+                for job in jobs {
+                    job(&mut conn);
+                }
+            })
+            .expect("Failed to start the database writer thread");
+        Database {
+            writer: Mutex::new(Some(sender)),
+            writer_thread: Mutex::new(Some(thread)),
+            readers,
+            files_generation: AtomicU64::new(0),
+        }
+    }
 
-        let db = Database {
-            conn: Mutex::new(conn),
+    /// Run `job` on the writer thread, after every write queued before it,
+    /// and wait for its result.
+    fn write<T: Send + 'static>(
+        &self,
+        job: impl FnOnce(&mut Connection) -> SqlResult<T> + Send + 'static,
+    ) -> SqlResult<T> {
+        let (reply, result) = mpsc::sync_channel(1);
+        let job: WriteJob = Box::new(move |conn| {
+            let _ = reply.send(job(conn));
+        });
+        let sent = match self.writer.lock().unwrap().as_ref() {
+            Some(sender) => sender.send(job).is_ok(),
+            None => false,
         };
-        db.create_tables()?;
-        Ok(db)
+        if !sent {
+            return Err(writer_stopped());
+        }
+        // A job that panicked took the writer thread down with it
+        result.recv().unwrap_or_else(|_| Err(writer_stopped()))
+    }
+
+    /// [`Database::write`] for a job that adds, removes, hides or renames
+    /// files; bumps [`Database::files_generation`] once it's done.
+    fn write_files<T: Send + 'static>(
+        &self,
+        job: impl FnOnce(&mut Connection) -> SqlResult<T> + Send + 'static,
+    ) -> SqlResult<T> {
+        let result = self.write(job);
+        self.files_generation.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+
+    /// Counts writes to the files table made through this handle. Two equal
+    /// readings mean nothing search-visible changed in between; a count
+    /// can't tell, since hiding one file and adding another keeps it.
+    pub fn files_generation(&self) -> u64 {
+        self.files_generation.load(Ordering::SeqCst)
+    }
+
+    /// A connection for reading: a free one if there is one, otherwise
+    /// whichever frees up first.
+    fn reader(&self) -> MutexGuard<'_, Connection> {
+        self.readers
+            .iter()
+            .find_map(|reader| reader.try_lock().ok())
+            .unwrap_or_else(|| self.readers[0].lock().unwrap())
+    }
+
+    /// Finish every queued write and stop the writer thread; later writes
+    /// fail. Called on app exit so nothing queued is lost, and on drop.
+    pub fn shutdown(&self) {
+        // The thread drains the channel, then exits once it's closed
+        self.writer.lock().unwrap().take();
+        if let Some(thread) = self.writer_thread.lock().unwrap().take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Write a consistent copy of the database, including whatever is still
+    /// in the WAL, to a new file at `dest`.
+    pub fn copy_to(&self, dest: &Path) -> SqlResult<()> {
+        let conn = self.reader();
+        conn.execute("VACUUM INTO ?1", [dest.to_string_lossy()])?;
+        Ok(())
     }
 
     /// Create tables and indexes if they don't already exist.
-    fn create_tables(&self) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
+    fn create_tables(conn: &Connection) -> SqlResult<()> {
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS files (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 filename TEXT NOT NULL,
+                filename_folded TEXT NOT NULL DEFAULT '',
                 filepath TEXT NOT NULL UNIQUE,
+                filepath_folded TEXT NOT NULL DEFAULT '',
                 extension TEXT NOT NULL DEFAULT '',
                 file_size INTEGER NOT NULL DEFAULT 0,
                 modified_at INTEGER NOT NULL DEFAULT 0,
                 file_type TEXT NOT NULL DEFAULT 'other',
                 click_count INTEGER NOT NULL DEFAULT 0,
                 last_accessed INTEGER NOT NULL DEFAULT 0,
-                icon_path TEXT
+                icon_path TEXT,
+                pinned_at INTEGER NOT NULL DEFAULT 0,
+                hidden INTEGER NOT NULL DEFAULT 0,
+                console_mode TEXT NOT NULL DEFAULT 'auto',
+                entry_kind TEXT NOT NULL DEFAULT 'file',
+                custom INTEGER NOT NULL DEFAULT 0,
+                confirmed_at INTEGER NOT NULL DEFAULT 0,
+                alternate_path TEXT NOT NULL DEFAULT '',
+                child_count INTEGER,
+                display_name TEXT NOT NULL DEFAULT '',
+                display_name_folded TEXT NOT NULL DEFAULT '',
+                display_name_mtime INTEGER NOT NULL DEFAULT -1
             );
 
             CREATE INDEX IF NOT EXISTS idx_filename ON files(filename);
@@ -71,12 +399,120 @@ impl Database {
             CREATE TABLE IF NOT EXISTS index_meta (
                 key TEXT PRIMARY KEY,
                 value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS query_picks (
+                query TEXT NOT NULL,
+                filepath TEXT NOT NULL,
+                pick_count INTEGER NOT NULL DEFAULT 0,
+                last_used INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (query, filepath)
+            );
+
+            CREATE TABLE IF NOT EXISTS context_picks (
+                context_app TEXT NOT NULL,
+                filepath TEXT NOT NULL,
+                pick_count INTEGER NOT NULL DEFAULT 0,
+                last_used INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (context_app, filepath)
+            );
+
+            CREATE TABLE IF NOT EXISTS snippets (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                keywords TEXT NOT NULL DEFAULT '',
+                body TEXT NOT NULL,
+                use_count INTEGER NOT NULL DEFAULT 0,
+                last_used INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS clipboard_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                text TEXT NOT NULL UNIQUE,
+                copied_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS launch_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                filepath TEXT NOT NULL,
+                launched_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_launch_log_launched_at ON launch_log(launched_at);
+
+            CREATE TABLE IF NOT EXISTS quick_slots (
+                slot INTEGER PRIMARY KEY,
+                filepath TEXT NOT NULL
             );",
         )?;
+        Self::migrate_folded_column(conn, "filename_folded", "filename")?;
+        Self::migrate_folded_column(conn, "filepath_folded", "filepath")?;
+        Self::add_column_if_missing(conn, "pinned_at", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::add_column_if_missing(conn, "hidden", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::add_column_if_missing(conn, "console_mode", "TEXT NOT NULL DEFAULT 'auto'")?;
+        Self::add_column_if_missing(conn, "entry_kind", "TEXT NOT NULL DEFAULT 'file'")?;
+        Self::add_column_if_missing(conn, "custom", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::add_column_if_missing(conn, "confirmed_at", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::add_column_if_missing(conn, "alternate_path", "TEXT NOT NULL DEFAULT ''")?;
+        Self::add_column_if_missing(conn, "child_count", "INTEGER")?;
+        Self::add_column_if_missing(conn, "display_name", "TEXT NOT NULL DEFAULT ''")?;
+        Self::add_column_if_missing(conn, "display_name_folded", "TEXT NOT NULL DEFAULT ''")?;
+        Self::add_column_if_missing(conn, "display_name_mtime", "INTEGER NOT NULL DEFAULT -1")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_filename_folded ON files(filename_folded);
+             CREATE INDEX IF NOT EXISTS idx_filepath_folded ON files(filepath_folded);
+             CREATE INDEX IF NOT EXISTS idx_display_name_folded ON files(display_name_folded);
+             CREATE INDEX IF NOT EXISTS idx_alternate_path ON files(alternate_path);",
+        )?;
+        Ok(())
+    }
+
+    /// Add the `column` holding [`fold_text`] of `source` and backfill it on
+    /// databases created before it existed (`filename_folded` before
+    /// diacritic-insensitive matching, `filepath_folded` before path matches
+    /// used it).
+    fn migrate_folded_column(conn: &Connection, column: &str, source: &str) -> SqlResult<()> {
+        let has_column: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = ?1",
+            params![column],
+            |row| row.get(0),
+        )?;
+        if has_column {
+            return Ok(());
+        }
+
+        conn.execute_batch(&format!("ALTER TABLE files ADD COLUMN {} TEXT NOT NULL DEFAULT '';", column))?;
+        let rows: Vec<(i64, String)> = conn
+            .prepare(&format!("SELECT id, {} FROM files", source))?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(&format!("UPDATE files SET {} = ?1 WHERE id = ?2", column))?;
+            for (id, value) in &rows {
+                stmt.execute(params![fold_text(value), id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Add a column to `files` on databases created before it existed.
+    fn add_column_if_missing(conn: &Connection, column: &str, definition: &str) -> SqlResult<()> {
+        let has_column: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = ?1",
+            params![column],
+            |row| row.get(0),
+        )?;
+        if !has_column {
+            conn.execute_batch(&format!("ALTER TABLE files ADD COLUMN {} {};", column, definition))?;
+        }
         Ok(())
     }
 
-    /// Insert or update a file entry (upsert based on filepath).
+    /// Insert or update a file entry (upsert based on filepath). A path
+    /// merged into another entry as its `alternate_path` isn't added back.
     pub fn upsert_file(
         &self,
         filename: &str,
@@ -86,64 +522,137 @@ impl Database {
         modified_at: i64,
         file_type: &str,
     ) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO files (filename, filepath, extension, file_size, modified_at, file_type)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-             ON CONFLICT(filepath) DO UPDATE SET
-                filename = excluded.filename,
-                file_size = excluded.file_size,
-                modified_at = excluded.modified_at,
-                file_type = excluded.file_type",
-            params![filename, filepath, extension, file_size, modified_at, file_type],
-        )?;
-        Ok(())
+        let entry = (
+            filename.to_string(),
+            filepath.to_string(),
+            extension.to_string(),
+            file_size,
+            modified_at,
+            file_type.to_string(),
+        );
+        self.upsert_files_batch(&[entry])
     }
 
     /// Batch insert/upsert multiple file entries in a single transaction.
     pub fn upsert_files_batch(&self, entries: &[(String, String, String, i64, i64, String)]) -> SqlResult<()> {
-        let mut conn = self.conn.lock().unwrap();
-        let tx = conn.transaction()?;
-        {
-            let mut stmt = tx.prepare_cached(
-                "INSERT INTO files (filename, filepath, extension, file_size, modified_at, file_type)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-                 ON CONFLICT(filepath) DO UPDATE SET
-                    filename = excluded.filename,
-                    file_size = excluded.file_size,
-                    modified_at = excluded.modified_at,
-                    file_type = excluded.file_type",
-            )?;
-            for (filename, filepath, extension, file_size, modified_at, file_type) in entries {
-                stmt.execute(params![filename, filepath, extension, file_size, modified_at, file_type])?;
+        let entries = entries.to_vec();
+        self.write_files(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO files (filename, filename_folded, filepath, extension, file_size, modified_at, file_type,
+                                        filepath_folded)
+                     SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8
+                     WHERE NOT EXISTS (SELECT 1 FROM files WHERE alternate_path = ?3)
+                     ON CONFLICT(filepath) DO UPDATE SET
+                        filename = excluded.filename,
+                        filename_folded = excluded.filename_folded,
+                        file_size = excluded.file_size,
+                        modified_at = excluded.modified_at,
+                        file_type = excluded.file_type
+                     WHERE custom = 0",
+                )?;
+                for (filename, filepath, extension, file_size, modified_at, file_type) in &entries {
+                    stmt.execute(params![
+                        filename,
+                        fold_text(filename),
+                        filepath,
+                        extension,
+                        file_size,
+                        modified_at,
+                        file_type,
+                        fold_text(filepath)
+                    ])?;
+                }
             }
-        }
-        tx.commit()?;
-        Ok(())
+            tx.commit()
+        })
+    }
+
+    /// Record how many items are directly inside each of the given indexed
+    /// folders, in a single transaction.
+    pub fn set_child_counts(&self, counts: &[(String, i64)]) -> SqlResult<()> {
+        let counts = counts.to_vec();
+        self.write_files(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached("UPDATE files SET child_count = ?2 WHERE filepath = ?1")?;
+                for (filepath, count) in &counts {
+                    stmt.execute(params![filepath, count])?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Indexed shortcuts and executables whose display name wasn't read
+    /// since they last changed, as (filepath, modified_at).
+    pub fn display_names_to_read(&self) -> SqlResult<Vec<(String, i64)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT filepath, modified_at FROM files
+             WHERE custom = 0 AND lower(extension) IN ('lnk', 'exe') AND display_name_mtime != modified_at",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    /// Record the display names read for entries, as (filepath, name,
+    /// modified_at it was read at); an empty name clears it. In a single
+    /// transaction.
+    pub fn set_display_names(&self, names: &[(String, String, i64)]) -> SqlResult<()> {
+        let names = names.to_vec();
+        self.write_files(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "UPDATE files SET display_name = ?2, display_name_folded = ?3, display_name_mtime = ?4
+                     WHERE filepath = ?1",
+                )?;
+                for (filepath, name, modified_at) in &names {
+                    stmt.execute(params![filepath, name, fold_text(name), modified_at])?;
+                }
+            }
+            tx.commit()
+        })
     }
 
     /// Search files using SQL LIKE for prefix/substring matching.
-    /// Returns up to `limit` results sorted by relevance.
-    pub fn search_files(&self, query: &str, limit: usize) -> SqlResult<Vec<FileEntry>> {
-        let conn = self.conn.lock().unwrap();
-        let like_pattern = format!("%{}%", query.replace('%', "\\%").replace('_', "\\_"));
-        let prefix_pattern = format!("{}%", query.replace('%', "\\%").replace('_', "\\_"));
+    /// `query` must already be folded (see [`fold_text`]); it is compared against
+    /// the stored `filename_folded` column.
+    /// Returns up to `limit` results sorted by relevance, restricted by `filters`.
+    pub fn search_files(&self, query: &str, filters: &QueryFilters, limit: usize) -> SqlResult<Vec<FileEntry>> {
+        let conn = self.reader();
+        let like_pattern = format!("%{}%", escape_like(query));
+        let prefix_pattern = format!("{}%", escape_like(query));
+
+        let mut values: Vec<Box<dyn ToSql>> = vec![
+            Box::new(query.to_string()),
+            Box::new(prefix_pattern),
+            Box::new(like_pattern),
+            Box::new(limit as i64),
+        ];
+        let filter_sql = filter_clauses(filters, &mut values);
 
         // Union query: exact matches first, then prefix, then substring,
         // all boosted by click_count and recency.
-        let sql = "
+        let sql = format!(
+            "
             SELECT id, filename, filepath, extension, file_size, modified_at,
-                   file_type, click_count, last_accessed, icon_path,
+                   file_type, click_count, last_accessed, icon_path, child_count, display_name,
                    CASE
-                       WHEN LOWER(filename) = LOWER(?1) THEN 100
-                       WHEN LOWER(filename) LIKE LOWER(?2) ESCAPE '\\' THEN 75
-                       WHEN LOWER(filename) LIKE LOWER(?3) ESCAPE '\\' THEN 50
-                       WHEN LOWER(filepath) LIKE LOWER(?3) ESCAPE '\\' THEN 25
+                       WHEN filename_folded = ?1 OR display_name_folded = ?1 THEN 100
+                       WHEN filename_folded LIKE ?2 ESCAPE '\\' OR display_name_folded LIKE ?2 ESCAPE '\\' THEN 75
+                       WHEN filename_folded LIKE ?3 ESCAPE '\\' OR display_name_folded LIKE ?3 ESCAPE '\\' THEN 50
+                       WHEN filepath_folded LIKE ?3 ESCAPE '\\' THEN 25
                        ELSE 0
                    END AS match_score
             FROM files
-            WHERE LOWER(filename) LIKE LOWER(?3) ESCAPE '\\'
-               OR LOWER(filepath) LIKE LOWER(?3) ESCAPE '\\'
+            WHERE (filename_folded LIKE ?3 ESCAPE '\\'
+               OR display_name_folded LIKE ?3 ESCAPE '\\'
+               OR filepath_folded LIKE ?3 ESCAPE '\\')
+               AND hidden = 0
+               {}
             ORDER BY
                 match_score DESC,
                 CASE file_type
@@ -157,10 +666,12 @@ impl Database {
                 last_accessed DESC,
                 modified_at DESC
             LIMIT ?4
-        ";
+        ",
+            filter_sql
+        );
 
-        let mut stmt = conn.prepare(sql)?;
-        let rows = stmt.query_map(params![query, prefix_pattern, like_pattern, limit as i64], |row| {
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
             Ok(FileEntry {
                 id: row.get(0)?,
                 filename: row.get(1)?,
@@ -172,6 +683,8 @@ impl Database {
                 click_count: row.get(7)?,
                 last_accessed: row.get(8)?,
                 icon_path: row.get(9)?,
+                child_count: row.get(10)?,
+                display_name: row.get(11)?,
             })
         })?;
 
@@ -184,56 +697,945 @@ impl Database {
         Ok(results)
     }
 
+    /// Candidates for a one- or two-char query, where a substring pattern
+    /// would match much of the index: filenames and display names starting
+    /// with `query` (index range scans) plus entries launched or picked for
+    /// a query before that contain it. Ranked like
+    /// [`Database::search_files`].
+    pub fn search_files_short(&self, query: &str, filters: &QueryFilters, limit: usize) -> SqlResult<Vec<FileEntry>> {
+        let conn = self.reader();
+        // Every string starting with `query` sorts between it and this bound
+        let prefix_end = format!("{}\u{10FFFF}", query);
+        let like_pattern = format!("%{}%", escape_like(query));
+
+        let mut values: Vec<Box<dyn ToSql>> = vec![
+            Box::new(query.to_string()),
+            Box::new(prefix_end),
+            Box::new(like_pattern),
+            Box::new(limit as i64),
+        ];
+        let filter_sql = filter_clauses(filters, &mut values);
+
+        let sql = format!(
+            "
+            SELECT id, filename, filepath, extension, file_size, modified_at,
+                   file_type, click_count, last_accessed, icon_path, child_count, display_name,
+                   CASE
+                       WHEN filename_folded = ?1 OR display_name_folded = ?1 THEN 100
+                       WHEN filename_folded >= ?1 AND filename_folded < ?2 THEN 75
+                       WHEN display_name_folded >= ?1 AND display_name_folded < ?2 THEN 75
+                       ELSE 50
+                   END AS match_score
+            FROM files
+            WHERE ((filename_folded >= ?1 AND filename_folded < ?2)
+               OR (display_name_folded >= ?1 AND display_name_folded < ?2)
+               OR ((click_count > 0 OR filepath IN (SELECT filepath FROM query_picks))
+                   AND filename_folded LIKE ?3 ESCAPE '\\'))
+               AND hidden = 0
+               {}
+            ORDER BY
+                match_score DESC,
+                CASE file_type
+                    WHEN 'app' THEN 5
+                    WHEN 'shortcut' THEN 4
+                    WHEN 'document' THEN 3
+                    WHEN 'folder' THEN 2
+                    ELSE 1
+                END DESC,
+                click_count DESC,
+                last_accessed DESC,
+                modified_at DESC
+            LIMIT ?4
+        ",
+            filter_sql
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(FileEntry {
+                id: row.get(0)?,
+                filename: row.get(1)?,
+                filepath: row.get(2)?,
+                extension: row.get(3)?,
+                file_size: row.get(4)?,
+                modified_at: row.get(5)?,
+                file_type: row.get(6)?,
+                click_count: row.get(7)?,
+                last_accessed: row.get(8)?,
+                icon_path: row.get(9)?,
+                child_count: row.get(10)?,
+                display_name: row.get(11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Candidates for a query containing `*` / `?` wildcards: filenames the
+    /// pattern matches anywhere (see [`wildcard_glob`]). `query` must already
+    /// be folded. Ranked like [`Database::search_files`] without the match
+    /// tiers, which the searcher works out from the pattern itself.
+    pub fn search_files_glob(&self, query: &str, filters: &QueryFilters, limit: usize) -> SqlResult<Vec<FileEntry>> {
+        let conn = self.reader();
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(wildcard_glob(query)), Box::new(limit as i64)];
+        let filter_sql = filter_clauses(filters, &mut values);
+
+        let sql = format!(
+            "
+            SELECT id, filename, filepath, extension, file_size, modified_at,
+                   file_type, click_count, last_accessed, icon_path, child_count, display_name
+            FROM files
+            WHERE filename_folded GLOB ?1
+               AND hidden = 0
+               {}
+            ORDER BY
+                CASE file_type
+                    WHEN 'app' THEN 5
+                    WHEN 'shortcut' THEN 4
+                    WHEN 'document' THEN 3
+                    WHEN 'folder' THEN 2
+                    ELSE 1
+                END DESC,
+                click_count DESC,
+                last_accessed DESC,
+                modified_at DESC
+            LIMIT ?2
+        ",
+            filter_sql
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(FileEntry {
+                id: row.get(0)?,
+                filename: row.get(1)?,
+                filepath: row.get(2)?,
+                extension: row.get(3)?,
+                file_size: row.get(4)?,
+                modified_at: row.get(5)?,
+                file_type: row.get(6)?,
+                click_count: row.get(7)?,
+                last_accessed: row.get(8)?,
+                icon_path: row.get(9)?,
+                child_count: row.get(10)?,
+                display_name: row.get(11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
     /// Increment the click count and update last_accessed time for a file.
     pub fn record_click(&self, filepath: &str) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
+        self.record_click_at(filepath, chrono::Utc::now().timestamp())
+    }
+
+    /// [`Database::record_click`] at `now` (Unix seconds). The launch is also
+    /// added to the launch log, and log rows older than
+    /// [`LAUNCH_LOG_RETENTION_SECS`] are dropped.
+    pub fn record_click_at(&self, filepath: &str, now: i64) -> SqlResult<()> {
+        let filepath = filepath.to_string();
+        self.write(move |conn| {
+            let tx = conn.transaction()?;
+            let updated = tx.execute(
+                "UPDATE files SET click_count = click_count + 1, last_accessed = ?1 WHERE filepath = ?2",
+                params![now, filepath],
+            )?;
+            if updated > 0 {
+                tx.execute(
+                    "INSERT INTO launch_log (filepath, launched_at) VALUES (?1, ?2)",
+                    params![filepath, now],
+                )?;
+            }
+            tx.execute(
+                "DELETE FROM launch_log WHERE launched_at < ?1",
+                params![now - LAUNCH_LOG_RETENTION_SECS],
+            )?;
+            tx.commit()
+        })
+    }
+
+    /// Non-hidden entries by launch count, most launched first, with their
+    /// counts. With no `window` every launch counts (`click_count`); with
+    /// `(since, until)` only launches in the log from `since` up to (not
+    /// including) `until` do. Ties go to the most recently launched.
+    pub fn most_launched(&self, window: Option<(i64, i64)>, limit: i64) -> SqlResult<Vec<(FileEntry, i64)>> {
+        let counted = match window {
+            None => "SELECT filepath, click_count AS launches, last_accessed AS last_launch
+                     FROM files WHERE click_count > 0",
+            Some(_) => "SELECT filepath, COUNT(*) AS launches, MAX(launched_at) AS last_launch FROM launch_log
+                        WHERE launched_at >= ?2 AND launched_at < ?3 GROUP BY filepath",
+        };
+        let conn = self.reader();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT f.id, f.filename, f.filepath, f.extension, f.file_size, f.modified_at,
+                    f.file_type, f.click_count, f.last_accessed, f.icon_path, f.child_count, f.display_name, c.launches
+             FROM ({}) AS c JOIN files f ON f.filepath = c.filepath
+             WHERE f.hidden = 0
+             ORDER BY c.launches DESC, c.last_launch DESC, f.filename ASC
+             LIMIT ?1",
+            counted
+        ))?;
+        let to_row = |row: &rusqlite::Row| {
+            Ok((
+                FileEntry {
+                    id: row.get(0)?,
+                    filename: row.get(1)?,
+                    filepath: row.get(2)?,
+                    extension: row.get(3)?,
+                    file_size: row.get(4)?,
+                    modified_at: row.get(5)?,
+                    file_type: row.get(6)?,
+                    click_count: row.get(7)?,
+                    last_accessed: row.get(8)?,
+                    icon_path: row.get(9)?,
+                    child_count: row.get(10)?,
+                    display_name: row.get(11)?,
+                },
+                row.get(12)?,
+            ))
+        };
+        let rows = match window {
+            None => stmt.query_map(params![limit], to_row)?.filter_map(|r| r.ok()).collect(),
+            Some((since, until)) => stmt
+                .query_map(params![limit, since, until], to_row)?
+                .filter_map(|r| r.ok())
+                .collect(),
+        };
+        Ok(rows)
+    }
+
+    /// Remember that `filepath` was launched for `query` (already folded),
+    /// trimming the table to the most recently used picks.
+    pub fn record_query_pick(&self, query: &str, filepath: &str) -> SqlResult<()> {
+        let (query, filepath) = (query.to_string(), filepath.to_string());
+        let now = chrono::Utc::now().timestamp();
+        self.write(move |conn| {
+            conn.execute(
+                "INSERT INTO query_picks (query, filepath, pick_count, last_used) VALUES (?1, ?2, 1, ?3)
+                 ON CONFLICT(query, filepath) DO UPDATE SET pick_count = pick_count + 1, last_used = excluded.last_used",
+                params![query, filepath, now],
+            )?;
+            conn.execute(
+                "DELETE FROM query_picks WHERE rowid NOT IN
+                    (SELECT rowid FROM query_picks ORDER BY last_used DESC LIMIT ?1)",
+                params![MAX_QUERY_PICKS],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Remember that `filepath` was launched while `context_app` (a
+    /// lowercase executable name) was the app in front of the launcher,
+    /// trimming the table to the most recently used picks.
+    pub fn record_context_pick(&self, context_app: &str, filepath: &str) -> SqlResult<()> {
+        let (context_app, filepath) = (context_app.to_string(), filepath.to_string());
         let now = chrono::Utc::now().timestamp();
-        conn.execute(
-            "UPDATE files SET click_count = click_count + 1, last_accessed = ?1 WHERE filepath = ?2",
-            params![now, filepath],
+        self.write(move |conn| {
+            conn.execute(
+                "INSERT INTO context_picks (context_app, filepath, pick_count, last_used) VALUES (?1, ?2, 1, ?3)
+                 ON CONFLICT(context_app, filepath) DO UPDATE SET
+                    pick_count = pick_count + 1, last_used = excluded.last_used",
+                params![context_app, filepath, now],
+            )?;
+            conn.execute(
+                "DELETE FROM context_picks WHERE rowid NOT IN
+                    (SELECT rowid FROM context_picks ORDER BY last_used DESC LIMIT ?1)",
+                params![MAX_CONTEXT_PICKS],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Launches recorded from `context_app`.
+    pub fn context_picks_for(&self, context_app: &str) -> SqlResult<Vec<ContextPick>> {
+        let conn = self.reader();
+        let mut stmt =
+            conn.prepare("SELECT filepath, pick_count, last_used FROM context_picks WHERE context_app = ?1")?;
+        let rows = stmt.query_map(params![context_app], |row| {
+            Ok(ContextPick {
+                filepath: row.get(0)?,
+                pick_count: row.get(1)?,
+                last_used: row.get(2)?,
+            })
+        })?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// Picks recorded for `query` (folded), for a prefix of it, or for a
+    /// longer query it is a prefix of.
+    pub fn query_picks_for(&self, query: &str) -> SqlResult<Vec<QueryPick>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT query, filepath, pick_count, last_used FROM query_picks
+             WHERE substr(?1, 1, length(query)) = query OR substr(query, 1, length(?1)) = ?1",
         )?;
-        Ok(())
+        let rows = stmt.query_map(params![query], |row| {
+            Ok(QueryPick {
+                query: row.get(0)?,
+                filepath: row.get(1)?,
+                pick_count: row.get(2)?,
+                last_used: row.get(3)?,
+            })
+        })?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
     }
 
-    /// Remove entries whose files no longer exist on disk.
-    pub fn remove_missing_files(&self) -> SqlResult<usize> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT filepath FROM files")?;
-        let paths: Vec<String> = stmt
-            .query_map([], |row| row.get(0))?
+    /// Pin or unpin a file for the empty-query view. Pinned files are listed
+    /// in the order they were pinned.
+    pub fn set_pinned(&self, filepath: &str, pinned: bool) -> SqlResult<()> {
+        let filepath = filepath.to_string();
+        let pinned_at = if pinned { chrono::Utc::now().timestamp_millis() } else { 0 };
+        self.write(move |conn| {
+            conn.execute(
+                "UPDATE files SET pinned_at = ?1 WHERE filepath = ?2",
+                params![pinned_at, filepath],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Hide a file from search results and the empty-query view, or show it again.
+    pub fn set_hidden(&self, filepath: &str, hidden: bool) -> SqlResult<()> {
+        let filepath = filepath.to_string();
+        self.write_files(move |conn| {
+            conn.execute(
+                "UPDATE files SET hidden = ?1 WHERE filepath = ?2",
+                params![hidden, filepath],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// The stored path of the indexed entry at `filepath`, compared
+    /// case-insensitively, or `None` if it isn't indexed.
+    pub fn indexed_filepath(&self, filepath: &str) -> SqlResult<Option<String>> {
+        let conn = self.reader();
+        let result = conn.query_row(
+            "SELECT filepath FROM files
+             WHERE filepath = ?1 COLLATE NOCASE OR (alternate_path = ?1 COLLATE NOCASE AND alternate_path != '')
+             LIMIT 1",
+            params![filepath],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(stored) => Ok(Some(stored)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// How launching `filepath` handles a console window; `Auto` for files
+    /// that aren't indexed.
+    pub fn console_mode(&self, filepath: &str) -> SqlResult<ConsoleMode> {
+        let conn = self.reader();
+        let result = conn.query_row(
+            "SELECT console_mode FROM files WHERE filepath = ?1",
+            params![filepath],
+            |row| row.get::<_, String>(0),
+        );
+        match result {
+            Ok(mode) => Ok(ConsoleMode::parse(&mode)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(ConsoleMode::Auto),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Store how launching `filepath` handles a console window. Returns false
+    /// if the file isn't indexed.
+    pub fn set_console_mode(&self, filepath: &str, mode: ConsoleMode) -> SqlResult<bool> {
+        let filepath = filepath.to_string();
+        self.write(move |conn| {
+            let updated = conn.execute(
+                "UPDATE files SET console_mode = ?1 WHERE filepath = ?2",
+                params![mode.as_str(), filepath],
+            )?;
+            Ok(updated > 0)
+        })
+    }
+
+    /// How to launch the entry at `filepath`: its kind, and whether a
+    /// command entry was confirmed. Files that aren't indexed are `File`.
+    pub fn entry_kind(&self, filepath: &str) -> SqlResult<(EntryKind, bool)> {
+        let conn = self.reader();
+        let result = conn.query_row(
+            "SELECT entry_kind, confirmed_at > 0 FROM files WHERE filepath = ?1",
+            params![filepath],
+            |row| Ok((EntryKind::parse(&row.get::<_, String>(0)?), row.get(1)?)),
+        );
+        match result {
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok((EntryKind::File, false)),
+            other => other,
+        }
+    }
+
+    /// Add a custom entry. Returns its id, or `None` if an entry with this
+    /// target already exists.
+    pub fn add_custom_entry(&self, entry: &NewCustomEntry) -> SqlResult<Option<i64>> {
+        let entry = entry.clone();
+        self.write_files(move |conn| {
+            let inserted = conn.execute(
+                "INSERT INTO files (filename, filename_folded, filepath, file_type, entry_kind, custom, confirmed_at,
+                                    filepath_folded)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7)
+                 ON CONFLICT(filepath) DO NOTHING",
+                params![
+                    entry.name,
+                    fold_text(&entry.name),
+                    entry.target,
+                    entry.file_type,
+                    entry.kind.as_str(),
+                    entry.confirmed_at,
+                    fold_text(&entry.target)
+                ],
+            )?;
+            Ok((inserted > 0).then(|| conn.last_insert_rowid()))
+        })
+    }
+
+    /// Replace a custom entry's fields, keeping its usage stats. Returns
+    /// false if `id` isn't a custom entry.
+    pub fn update_custom_entry(&self, id: i64, entry: &NewCustomEntry) -> SqlResult<bool> {
+        let entry = entry.clone();
+        self.write_files(move |conn| {
+            let updated = conn.execute(
+                "UPDATE files SET filename = ?1, filename_folded = ?2, filepath = ?3, file_type = ?4,
+                        entry_kind = ?5, confirmed_at = ?6, filepath_folded = ?8
+                 WHERE id = ?7 AND custom = 1",
+                params![
+                    entry.name,
+                    fold_text(&entry.name),
+                    entry.target,
+                    entry.file_type,
+                    entry.kind.as_str(),
+                    entry.confirmed_at,
+                    id,
+                    fold_text(&entry.target)
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+    }
+
+    /// Remove a custom entry. Returns false if `id` isn't one.
+    pub fn remove_custom_entry(&self, id: i64) -> SqlResult<bool> {
+        self.write_files(move |conn| Ok(conn.execute("DELETE FROM files WHERE id = ?1 AND custom = 1", params![id])? > 0))
+    }
+
+    /// All custom entries, by name.
+    pub fn custom_entries(&self) -> SqlResult<Vec<CustomEntry>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, filename, filepath, entry_kind, confirmed_at FROM files
+             WHERE custom = 1 ORDER BY filename COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(CustomEntry {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                target: row.get(2)?,
+                kind: EntryKind::parse(&row.get::<_, String>(3)?),
+                confirmed_at: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// All snippets, by name.
+    pub fn snippets(&self) -> SqlResult<Vec<Snippet>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, keywords, body, use_count FROM snippets ORDER BY name COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([], Self::snippet_from_row)?;
+        rows.collect()
+    }
+
+    /// The snippet with `id`, if it exists.
+    pub fn snippet(&self, id: i64) -> SqlResult<Option<Snippet>> {
+        let conn = self.reader();
+        let result = conn.query_row(
+            "SELECT id, name, keywords, body, use_count FROM snippets WHERE id = ?1",
+            params![id],
+            Self::snippet_from_row,
+        );
+        match result {
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            other => other.map(Some),
+        }
+    }
+
+    fn snippet_from_row(row: &rusqlite::Row) -> SqlResult<Snippet> {
+        let keywords: String = row.get(2)?;
+        Ok(Snippet {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            keywords: keywords.split_whitespace().map(str::to_string).collect(),
+            body: row.get(3)?,
+            use_count: row.get(4)?,
+        })
+    }
+
+    /// Add a snippet and return its id.
+    pub fn add_snippet(&self, snippet: &NewSnippet) -> SqlResult<i64> {
+        let snippet = snippet.clone();
+        self.write(move |conn| {
+            conn.execute(
+                "INSERT INTO snippets (name, keywords, body) VALUES (?1, ?2, ?3)",
+                params![snippet.name, snippet.keywords.join(" "), snippet.body],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Replace a snippet's name, keywords and body. Returns false if there
+    /// is no snippet `id`.
+    pub fn update_snippet(&self, id: i64, snippet: &NewSnippet) -> SqlResult<bool> {
+        let snippet = snippet.clone();
+        self.write(move |conn| {
+            let updated = conn.execute(
+                "UPDATE snippets SET name = ?1, keywords = ?2, body = ?3 WHERE id = ?4",
+                params![snippet.name, snippet.keywords.join(" "), snippet.body, id],
+            )?;
+            Ok(updated > 0)
+        })
+    }
+
+    /// Remove a snippet. Returns false if there is no snippet `id`.
+    pub fn remove_snippet(&self, id: i64) -> SqlResult<bool> {
+        self.write(move |conn| Ok(conn.execute("DELETE FROM snippets WHERE id = ?1", params![id])? > 0))
+    }
+
+    /// Count a use of snippet `id`.
+    pub fn record_snippet_use(&self, id: i64) -> SqlResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.write(move |conn| {
+            conn.execute(
+                "UPDATE snippets SET use_count = use_count + 1, last_used = ?1 WHERE id = ?2",
+                params![now, id],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Add a copied text to the clipboard history, or move it to the top if
+    /// it's already there, and drop the oldest entries beyond the cap.
+    pub fn add_clipboard_entry(&self, text: &str, copied_at: i64) -> SqlResult<()> {
+        let text = text.to_string();
+        self.write(move |conn| {
+            conn.execute(
+                "INSERT INTO clipboard_history (text, copied_at) VALUES (?1, ?2)
+                 ON CONFLICT(text) DO UPDATE SET copied_at = excluded.copied_at",
+                params![text, copied_at],
+            )?;
+            conn.execute(
+                "DELETE FROM clipboard_history WHERE id NOT IN
+                    (SELECT id FROM clipboard_history ORDER BY copied_at DESC, id DESC LIMIT ?1)",
+                params![MAX_CLIPBOARD_ENTRIES],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Clipboard history, most recently copied first.
+    pub fn clipboard_entries(&self) -> SqlResult<Vec<ClipboardEntry>> {
+        let conn = self.reader();
+        let mut stmt =
+            conn.prepare("SELECT id, text, copied_at FROM clipboard_history ORDER BY copied_at DESC, id DESC")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ClipboardEntry {
+                id: row.get(0)?,
+                text: row.get(1)?,
+                copied_at: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// The text of clipboard history entry `id`, if it exists.
+    pub fn clipboard_entry_text(&self, id: i64) -> SqlResult<Option<String>> {
+        let conn = self.reader();
+        let result = conn.query_row(
+            "SELECT text FROM clipboard_history WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        );
+        match result {
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            other => other.map(Some),
+        }
+    }
+
+    /// Remove one clipboard history entry. Returns false if there is no entry `id`.
+    pub fn remove_clipboard_entry(&self, id: i64) -> SqlResult<bool> {
+        self.write(move |conn| Ok(conn.execute("DELETE FROM clipboard_history WHERE id = ?1", params![id])? > 0))
+    }
+
+    /// Remove all clipboard history. Returns how many entries there were.
+    pub fn clear_clipboard_history(&self) -> SqlResult<usize> {
+        self.write(|conn| conn.execute("DELETE FROM clipboard_history", []))
+    }
+
+    /// Forget what was launched, picked and copied, and when. Pins, hidden
+    /// files and the entries themselves stay.
+    pub fn clear_usage_data(&self) -> SqlResult<UsageCleared> {
+        self.write_files(|conn| {
+            let tx = conn.transaction()?;
+            let cleared = UsageCleared {
+                launches: tx.execute(
+                    "UPDATE files SET click_count = 0, last_accessed = 0 WHERE click_count > 0 OR last_accessed > 0",
+                    [],
+                )?,
+                query_picks: tx.execute("DELETE FROM query_picks", [])?,
+                context_picks: tx.execute("DELETE FROM context_picks", [])?,
+                snippets: tx.execute(
+                    "UPDATE snippets SET use_count = 0, last_used = 0 WHERE use_count > 0 OR last_used > 0",
+                    [],
+                )?,
+                clipboard_entries: tx.execute("DELETE FROM clipboard_history", [])?,
+            };
+            tx.execute("DELETE FROM launch_log", [])?;
+            tx.commit()?;
+            Ok(cleared)
+        })
+    }
+
+    /// Remove every indexed file, keeping custom entries, ahead of a
+    /// rebuild from scratch. Returns how many were removed.
+    pub fn clear_index(&self) -> SqlResult<usize> {
+        self.write_files(|conn| conn.execute("DELETE FROM files WHERE custom = 0", []))
+    }
+
+    /// Quick slot bindings in slot order, as (slot, filepath, name), the
+    /// name being the entry's display name or filename when it's indexed.
+    pub fn quick_slots(&self) -> SqlResult<Vec<(u8, String, Option<String>)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT q.slot, q.filepath, COALESCE(NULLIF(f.display_name, ''), f.filename)
+             FROM quick_slots q LEFT JOIN files f ON f.filepath = q.filepath
+             ORDER BY q.slot",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect()
+    }
+
+    /// Make `slots` the quick slot bindings, in a single transaction.
+    pub fn replace_quick_slots(&self, slots: &BTreeMap<u8, String>) -> SqlResult<()> {
+        let slots = slots.clone();
+        self.write(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM quick_slots", [])?;
+            {
+                let mut stmt = tx.prepare_cached("INSERT INTO quick_slots (slot, filepath) VALUES (?1, ?2)")?;
+                for (slot, filepath) in &slots {
+                    stmt.execute(params![slot, filepath])?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Pinned, non-hidden files in the order they were pinned.
+    pub fn pinned_entries(&self) -> SqlResult<Vec<FileEntry>> {
+        self.entries_where("pinned_at > 0 AND hidden = 0 ORDER BY pinned_at ASC", -1)
+    }
+
+    /// The `limit` most recently launched non-hidden files.
+    pub fn recently_accessed(&self, limit: usize) -> SqlResult<Vec<FileEntry>> {
+        self.entries_where(
+            "last_accessed > 0 AND hidden = 0 ORDER BY last_accessed DESC",
+            limit as i64,
+        )
+    }
+
+    /// The entry launched most recently, hidden or not.
+    pub fn last_launched(&self) -> SqlResult<Option<FileEntry>> {
+        let entries = self.entries_where("last_accessed > 0 ORDER BY last_accessed DESC", 1)?;
+        Ok(entries.into_iter().next())
+    }
+
+    /// The `limit` most frequently launched non-hidden files.
+    pub fn top_clicked(&self, limit: usize) -> SqlResult<Vec<FileEntry>> {
+        self.entries_where(
+            "click_count > 0 AND hidden = 0 ORDER BY click_count DESC, last_accessed DESC",
+            limit as i64,
+        )
+    }
+
+    /// Full entries matching a fixed `WHERE ... ORDER BY` clause (a negative
+    /// `limit` means no limit).
+    fn entries_where(&self, clause: &str, limit: i64) -> SqlResult<Vec<FileEntry>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, filename, filepath, extension, file_size, modified_at,
+                    file_type, click_count, last_accessed, icon_path, child_count, display_name
+             FROM files WHERE {} LIMIT ?1",
+            clause
+        ))?;
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(FileEntry {
+                id: row.get(0)?,
+                filename: row.get(1)?,
+                filepath: row.get(2)?,
+                extension: row.get(3)?,
+                file_size: row.get(4)?,
+                modified_at: row.get(5)?,
+                file_type: row.get(6)?,
+                click_count: row.get(7)?,
+                last_accessed: row.get(8)?,
+                icon_path: row.get(9)?,
+                child_count: row.get(10)?,
+                display_name: row.get(11)?,
+            })
+        })?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// Remove entries whose files no longer exist on disk. An entry whose
+    /// file is gone but whose `alternate_path` is still there moves to the
+    /// alternate instead, keeping its usage; one whose alternate is gone
+    /// forgets it. Entries under `keep_under` are left alone.
+    pub fn remove_missing_files(&self, keep_under: &[PathBuf]) -> SqlResult<usize> {
+        // Custom entries stay; a URL or command was never a file. Nor is a
+        // bookmark, which its provider removes once it's gone.
+        let paths: Vec<(String, String)> = self
+            .reader()
+            .prepare("SELECT filepath, alternate_path FROM files WHERE custom = 0 AND file_type != ?1")?
+            .query_map(params![crate::bookmarks::BOOKMARK_FILE_TYPE], |row| Ok((row.get(0)?, row.get(1)?)))?
             .filter_map(|r| r.ok())
             .collect();
+        let exists = |path: &str| Path::new(crate::env_vars::expand_env_vars(path).as_ref()).exists();
+
+        // Check the disk first, so writes aren't queued behind it
+        let mut forget_alternate = Vec::new();
+        let mut use_alternate = Vec::new();
+        let mut missing = Vec::new();
+        for (path, alternate) in paths {
+            if keep_under.iter().any(|root| Path::new(&path).starts_with(root)) {
+                continue;
+            }
+            let alternate_exists = !alternate.is_empty() && exists(&alternate);
+            if exists(&path) {
+                if !alternate.is_empty() && !alternate_exists {
+                    forget_alternate.push(path);
+                }
+            } else if alternate_exists {
+                use_alternate.push((path, alternate));
+            } else {
+                missing.push(path);
+            }
+        }
 
-        let mut removed = 0usize;
-        for path in &paths {
-            if !std::path::Path::new(path).exists() {
-                conn.execute("DELETE FROM files WHERE filepath = ?1", params![path])?;
-                removed += 1;
+        self.write_files(move |conn| {
+            let tx = conn.transaction()?;
+            for path in &forget_alternate {
+                tx.execute("UPDATE files SET alternate_path = '' WHERE filepath = ?1", params![path])?;
+            }
+            for (path, alternate) in &use_alternate {
+                tx.execute(
+                    "UPDATE files SET filepath = alternate_path, filepath_folded = ?2, alternate_path = ''
+                     WHERE filepath = ?1",
+                    params![path, fold_text(alternate)],
+                )?;
+            }
+            let mut removed = 0usize;
+            for path in &missing {
+                removed += tx.execute("DELETE FROM files WHERE filepath = ?1", params![path])?;
+            }
+            tx.commit()?;
+            Ok(removed)
+        })
+    }
+
+    /// Remove indexed entries of `file_type` whose path isn't in `keep`.
+    /// Returns how many were removed.
+    pub fn remove_file_type_except(&self, file_type: &str, keep: &HashSet<String>) -> SqlResult<usize> {
+        let paths: Vec<String> = self
+            .reader()
+            .prepare("SELECT filepath FROM files WHERE file_type = ?1 AND custom = 0")?
+            .query_map(params![file_type], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .filter(|path| !keep.contains(path))
+            .collect();
+        self.write_files(move |conn| {
+            let tx = conn.transaction()?;
+            let mut removed = 0usize;
+            {
+                let mut stmt = tx.prepare("DELETE FROM files WHERE filepath = ?1")?;
+                for path in &paths {
+                    removed += stmt.execute(params![path])?;
+                }
             }
+            tx.commit()?;
+            Ok(removed)
+        })
+    }
+
+    /// Indexed files (not folders) under `dir`, for spotting duplicates
+    /// between two directories.
+    pub fn entries_under(&self, dir: &str) -> SqlResult<Vec<FileEntry>> {
+        let conn = self.reader();
+        let prefix = format!("{}{}", dir.trim_end_matches(['\\', '/']), std::path::MAIN_SEPARATOR);
+        let mut stmt = conn.prepare(
+            "SELECT id, filename, filepath, extension, file_size, modified_at,
+                    file_type, click_count, last_accessed, icon_path, child_count, display_name
+             FROM files WHERE custom = 0 AND file_type != 'folder' AND substr(filepath, 1, ?1) = ?2",
+        )?;
+        let rows = stmt.query_map(params![prefix.chars().count() as i64, prefix], |row| {
+            Ok(FileEntry {
+                id: row.get(0)?,
+                filename: row.get(1)?,
+                filepath: row.get(2)?,
+                extension: row.get(3)?,
+                file_size: row.get(4)?,
+                modified_at: row.get(5)?,
+                file_type: row.get(6)?,
+                click_count: row.get(7)?,
+                last_accessed: row.get(8)?,
+                icon_path: row.get(9)?,
+                child_count: row.get(10)?,
+                display_name: row.get(11)?,
+            })
+        })?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// Fold the entry for `duplicate` into the one for `keep`: launch counts
+    /// and remembered picks add up, the later launch time and an earlier pin
+    /// win, and `duplicate` is recorded as `keep`'s alternate path so later
+    /// index passes don't add it back. Returns false if either is missing.
+    pub fn merge_duplicate(&self, keep: &str, duplicate: &str) -> SqlResult<bool> {
+        if keep == duplicate {
+            return Ok(false);
         }
-        Ok(removed)
+        let (keep, duplicate) = (keep.to_string(), duplicate.to_string());
+        self.write_files(move |conn| {
+            let tx = conn.transaction()?;
+            let merged = tx.execute(
+                "UPDATE files SET
+                    click_count = files.click_count + dup.click_count,
+                    last_accessed = max(files.last_accessed, dup.last_accessed),
+                    pinned_at = CASE WHEN files.pinned_at = 0 THEN dup.pinned_at
+                                     WHEN dup.pinned_at = 0 THEN files.pinned_at
+                                     ELSE min(files.pinned_at, dup.pinned_at) END,
+                    alternate_path = dup.filepath
+                 FROM (SELECT filepath, click_count, last_accessed, pinned_at FROM files WHERE filepath = ?2) AS dup
+                 WHERE files.filepath = ?1",
+                params![keep, duplicate],
+            )?;
+            if merged == 0 {
+                return Ok(false);
+            }
+            tx.execute("DELETE FROM files WHERE filepath = ?1", params![duplicate])?;
+            tx.execute(
+                "INSERT INTO query_picks (query, filepath, pick_count, last_used)
+                 SELECT query, ?1, pick_count, last_used FROM query_picks WHERE filepath = ?2
+                 ON CONFLICT(query, filepath) DO UPDATE SET
+                    pick_count = pick_count + excluded.pick_count,
+                    last_used = max(last_used, excluded.last_used)",
+                params![keep, duplicate],
+            )?;
+            tx.execute("DELETE FROM query_picks WHERE filepath = ?1", params![duplicate])?;
+            tx.execute(
+                "INSERT INTO context_picks (context_app, filepath, pick_count, last_used)
+                 SELECT context_app, ?1, pick_count, last_used FROM context_picks WHERE filepath = ?2
+                 ON CONFLICT(context_app, filepath) DO UPDATE SET
+                    pick_count = pick_count + excluded.pick_count,
+                    last_used = max(last_used, excluded.last_used)",
+                params![keep, duplicate],
+            )?;
+            tx.execute("DELETE FROM context_picks WHERE filepath = ?1", params![duplicate])?;
+            tx.execute("UPDATE launch_log SET filepath = ?1 WHERE filepath = ?2", params![keep, duplicate])?;
+            tx.commit()?;
+            Ok(true)
+        })
+    }
+
+    /// Remove the entry for `filepath` and, for a directory, every entry
+    /// inside it. Returns how many rows were removed.
+    pub fn remove_path(&self, filepath: &str) -> SqlResult<usize> {
+        let filepath = filepath.to_string();
+        let prefix = format!("{}{}", filepath.trim_end_matches(['\\', '/']), std::path::MAIN_SEPARATOR);
+        self.write_files(move |conn| {
+            conn.execute(
+                "DELETE FROM files WHERE custom = 0 AND (filepath = ?1 OR substr(filepath, 1, ?2) = ?3)",
+                params![filepath, prefix.chars().count() as i64, prefix],
+            )
+        })
+    }
+
+    /// Remove files (not folders) with any of `extensions`, given lowercase
+    /// without the dot. Returns how many rows were removed.
+    pub fn remove_extensions(&self, extensions: &[String]) -> SqlResult<usize> {
+        let extensions = extensions.to_vec();
+        self.write_files(move |conn| {
+            let mut removed = 0usize;
+            for extension in &extensions {
+                removed += conn.execute(
+                    "DELETE FROM files WHERE lower(extension) = ?1 AND file_type != 'folder' AND custom = 0",
+                    params![extension],
+                )?;
+            }
+            Ok(removed)
+        })
     }
 
     /// Get the total number of indexed files.
     pub fn file_count(&self) -> SqlResult<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
     }
 
     /// Set a metadata key/value pair.
     pub fn set_meta(&self, key: &str, value: &str) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO index_meta (key, value) VALUES (?1, ?2)
-             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
-            params![key, value],
-        )?;
-        Ok(())
+        let (key, value) = (key.to_string(), value.to_string());
+        self.write(move |conn| {
+            conn.execute(
+                "INSERT INTO index_meta (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// The number of indexed files and the time of the latest full or
+    /// incremental index, if any, in Unix seconds. `None` right away when
+    /// every read connection is in use, for callers that mustn't wait.
+    pub fn try_index_freshness(&self) -> Option<SqlResult<(i64, Option<i64>)>> {
+        let conn = self.readers.iter().find_map(|reader| reader.try_lock().ok())?;
+        let freshness = conn
+            .query_row("SELECT COUNT(*) FROM files WHERE hidden = 0", [], |row| row.get(0))
+            .and_then(|count| {
+                let indexed_at = conn.query_row(
+                    "SELECT MAX(CAST(value AS INTEGER)) FROM index_meta
+                     WHERE key IN ('last_full_index', 'last_incremental_index')",
+                    [],
+                    |row| row.get(0),
+                )?;
+                Ok((count, indexed_at))
+            });
+        Some(freshness)
+    }
+
+    /// Every metadata key starting with `prefix`, with the prefix cut off.
+    pub fn meta_keys_with_prefix(&self, prefix: &str) -> SqlResult<Vec<String>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare("SELECT key FROM index_meta WHERE key LIKE ?1 ESCAPE '\\'")?;
+        let keys = stmt.query_map(params![format!("{}%", escape_like(prefix))], |row| row.get::<_, String>(0))?;
+        keys.map(|key| key.map(|key| key[prefix.len()..].to_string())).collect()
+    }
+
+    /// Remove every metadata key starting with `prefix`.
+    pub fn remove_meta_with_prefix(&self, prefix: &str) -> SqlResult<()> {
+        let pattern = format!("{}%", escape_like(prefix));
+        self.write(move |conn| {
+            conn.execute("DELETE FROM index_meta WHERE key LIKE ?1 ESCAPE '\\'", params![pattern])?;
+            Ok(())
+        })
     }
 
     /// Get a metadata value by key.
     pub fn get_meta(&self, key: &str) -> SqlResult<Option<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare("SELECT value FROM index_meta WHERE key = ?1")?;
         let result = stmt.query_row(params![key], |row| row.get(0));
         match result {
@@ -243,38 +1645,42 @@ impl Database {
         }
     }
 
-    /// Get all file entries (for fuzzy matching in memory).
-    pub fn get_all_filenames(&self) -> SqlResult<Vec<(i64, String, String, String, i64, i64, i64)>> {
-        let conn = self.conn.lock().unwrap();
+    /// Load every non-hidden file as a [`CompactEntry`] (for fuzzy matching in memory).
+    pub fn load_compact_entries(&self) -> SqlResult<Vec<CompactEntry>> {
+        let conn = self.reader();
         let mut stmt = conn.prepare(
-            "SELECT id, filename, filepath, file_type, click_count, last_accessed, modified_at FROM files"
+            "SELECT id, filename, filename_folded, filepath, file_size, file_type, click_count, last_accessed,
+                    modified_at, child_count, display_name, display_name_folded
+             FROM files WHERE hidden = 0",
         )?;
         let rows = stmt.query_map([], |row| {
-            Ok((
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                row.get(3)?,
-                row.get(4)?,
-                row.get(5)?,
-                row.get(6)?,
-            ))
+            let filename_folded: String = row.get(2)?;
+            let display_name_folded: String = row.get(11)?;
+            Ok(CompactEntry {
+                id: row.get(0)?,
+                filename: row.get::<_, String>(1)?.into_boxed_str(),
+                bigrams: BigramSignature::of(&[&filename_folded, &display_name_folded]),
+                filename_folded: filename_folded.into_boxed_str(),
+                filepath: row.get::<_, String>(3)?.into_boxed_str(),
+                file_size: row.get(4)?,
+                file_type: intern_file_type(&row.get::<_, String>(5)?),
+                click_count: row.get(6)?,
+                last_accessed: row.get(7)?,
+                modified_at: row.get(8)?,
+                child_count: row.get(9)?,
+                display_name: row.get::<_, String>(10)?.into_boxed_str(),
+                display_name_folded: display_name_folded.into_boxed_str(),
+            })
         })?;
-        let mut result = Vec::new();
-        for row in rows {
-            if let Ok(entry) = row {
-                result.push(entry);
-            }
-        }
-        Ok(result)
+        Ok(rows.filter_map(|r| r.ok()).collect())
     }
 
     /// Get a single file entry by id.
     pub fn get_file_by_id(&self, id: i64) -> SqlResult<Option<FileEntry>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             "SELECT id, filename, filepath, extension, file_size, modified_at,
-                    file_type, click_count, last_accessed, icon_path
+                    file_type, click_count, last_accessed, icon_path, child_count, display_name
              FROM files WHERE id = ?1",
         )?;
         let result = stmt.query_row(params![id], |row| {
@@ -289,6 +1695,8 @@ impl Database {
                 click_count: row.get(7)?,
                 last_accessed: row.get(8)?,
                 icon_path: row.get(9)?,
+                child_count: row.get(10)?,
+                display_name: row.get(11)?,
             })
         });
         match result {
@@ -298,3 +1706,197 @@ impl Database {
         }
     }
 }
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// What to open for `db_path`. Separate connections to `:memory:` would
+/// each get their own empty database, so the reader and writer connections
+/// share a uniquely named in-memory one instead.
+fn connection_path(db_path: &Path) -> PathBuf {
+    static MEMORY_DATABASES: AtomicUsize = AtomicUsize::new(0);
+    if db_path != Path::new(":memory:") {
+        return db_path.to_path_buf();
+    }
+    let n = MEMORY_DATABASES.fetch_add(1, Ordering::Relaxed);
+    PathBuf::from(format!("file:ancheck-memory-{}-{}?mode=memory&cache=shared", std::process::id(), n))
+}
+
+/// The error for a write made after [`Database::shutdown`], or after the
+/// writer thread died.
+fn writer_stopped() -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+        Some("The database writer has stopped".to_string()),
+    )
+}
+
+/// Escape `%`, `_` and the escape character itself for use in a LIKE pattern.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Escape `*`, `?` and `[` for use in a GLOB pattern.
+fn escape_glob(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '*' | '?' | '[' => format!("[{}]", c),
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+/// GLOB pattern for a wildcard query, matching anywhere in the name. `*` and
+/// `?` pass through: Windows forbids both in filenames, so they are never
+/// literal. `[` is GLOB's only other special character and gets escaped.
+fn wildcard_glob(query: &str) -> String {
+    format!("*{}*", query.replace('[', "[[]"))
+}
+
+/// Translate parsed query filters into extra `AND ...` WHERE clauses,
+/// appending their bound values to `values`.
+fn filter_clauses(filters: &QueryFilters, values: &mut Vec<Box<dyn ToSql>>) -> String {
+    let mut sql = String::new();
+
+    push_in_clause(&mut sql, values, "LOWER(extension)", &filters.extensions, false);
+    push_in_clause(&mut sql, values, "LOWER(extension)", &filters.exclude_extensions, true);
+    push_in_clause(&mut sql, values, "file_type", &filters.file_types, false);
+    push_in_clause(&mut sql, values, "file_type", &filters.exclude_file_types, true);
+
+    if !filters.locations.is_empty() {
+        let alternatives: Vec<String> = filters
+            .locations
+            .iter()
+            .map(|location| {
+                values.push(Box::new(location_pattern(location)));
+                format!("filepath_folded LIKE ?{} ESCAPE '\\'", values.len())
+            })
+            .collect();
+        sql.push_str(&format!(" AND ({})", alternatives.join(" OR ")));
+    }
+    for location in &filters.exclude_locations {
+        values.push(Box::new(location_pattern(location)));
+        sql.push_str(&format!(" AND filepath_folded NOT LIKE ?{} ESCAPE '\\'", values.len()));
+    }
+
+    // GLOB is case-sensitive; word boundaries are checked afterwards in Rust
+    for term in &filters.required_terms {
+        values.push(Box::new(format!("*{}*", escape_glob(&term.text))));
+        sql.push_str(&format!(" AND filename GLOB ?{}", values.len()));
+    }
+
+    let bounds = [
+        ("file_size >=", filters.min_size),
+        ("file_size <=", filters.max_size),
+        ("modified_at >=", filters.modified_after),
+        ("modified_at <", filters.modified_before),
+    ];
+    for (comparison, bound) in bounds {
+        if let Some(bound) = bound {
+            values.push(Box::new(bound));
+            sql.push_str(&format!(" AND {} ?{}", comparison, values.len()));
+        }
+    }
+
+    sql
+}
+
+/// Append `AND column [NOT] IN (...)` for a non-empty list of values.
+fn push_in_clause(
+    sql: &mut String,
+    values: &mut Vec<Box<dyn ToSql>>,
+    column: &str,
+    items: &[String],
+    negate: bool,
+) {
+    if items.is_empty() {
+        return;
+    }
+    let placeholders: Vec<String> = items
+        .iter()
+        .map(|item| {
+            values.push(Box::new(item.clone()));
+            format!("?{}", values.len())
+        })
+        .collect();
+    sql.push_str(&format!(
+        " AND {} {}IN ({})",
+        column,
+        if negate { "NOT " } else { "" },
+        placeholders.join(", ")
+    ));
+}
+
+/// LIKE pattern for an `in:` filter: absolute paths match as a prefix,
+/// anything else as a substring of the full path.
+fn location_pattern(location: &str) -> String {
+    let location = fold_text(location);
+    if QueryFilters::is_absolute_location(&location) {
+        format!("{}%", escape_like(&location))
+    } else {
+        format!("%{}%", escape_like(&location))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Queue `job` on the writer thread without waiting for it.
+    fn enqueue(db: &Database, job: impl FnOnce(&mut Connection) + Send + 'static) {
+        db.writer.lock().unwrap().as_ref().unwrap().send(Box::new(job)).unwrap();
+    }
+
+    #[test]
+    fn test_shutdown_drains_queued_writes() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+
+        // Hold the writer until shutdown has started, so the writes below
+        // are still queued when it closes the channel
+        let (release, gate) = mpsc::channel::<()>();
+        enqueue(&db, move |_| {
+            let _ = gate.recv();
+        });
+        for i in 0..20 {
+            enqueue(&db, move |conn| {
+                conn.execute(
+                    "INSERT INTO index_meta (key, value) VALUES (?1, 'queued')",
+                    params![format!("key{}", i)],
+                )
+                .unwrap();
+            });
+        }
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            let _ = release.send(());
+        });
+        db.shutdown();
+
+        for i in 0..20 {
+            assert_eq!(db.get_meta(&format!("key{}", i)).unwrap().as_deref(), Some("queued"));
+        }
+    }
+
+    #[test]
+    fn test_write_after_shutdown_fails() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        db.set_meta("before", "1").unwrap();
+        db.shutdown();
+
+        let err = db.set_meta("after", "1").unwrap_err();
+        assert!(err.to_string().contains("writer has stopped"), "{}", err);
+        assert_eq!(db.get_meta("after").unwrap(), None);
+        assert_eq!(db.get_meta("before").unwrap().as_deref(), Some("1"));
+
+        // A second shutdown, as on drop, is a no-op
+        db.shutdown();
+    }
+}
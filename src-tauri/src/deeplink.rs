@@ -0,0 +1,262 @@
+//! The `ancheck://` URL scheme, so other tools can drive the launcher:
+//! `ancheck://show`, `ancheck://search?q=vpn` and
+//! `ancheck://launch?path=C:%5CTools%5Cvpn.exe`.
+//!
+//! Windows starts a new process for every link opened. The first instance
+//! listens on a loopback port, written to [`PORT_FILE`] in the data folder;
+//! a later one hands its link over with [`forward_to_running`] and exits.
+//! `launch` only starts files that are in the index (see
+//! [`indexed_target`]), so a web page can't use a link to run an arbitrary
+//! program.
+
+use crate::db::Database;
+use log::{info, warn};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const SCHEME: &str = "ancheck";
+/// Holds the running instance's port, next to the database.
+const PORT_FILE: &str = "instance.port";
+/// The first line the running instance sends, so a stale port file that
+/// now points at some other program is recognised.
+const GREETING: &str = "ancheck";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    /// Toggle the window.
+    Show,
+    /// Show the window with the query box filled in.
+    Search(String),
+    /// Launch a file, which must be in the index.
+    Launch(String),
+}
+
+/// Parse an `ancheck://` link. Query values are percent-decoded, and `+`
+/// stands for a space, so a literal plus must be sent as `%2B`.
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = url::Url::parse(link.trim()).map_err(|e| format!("Invalid link '{}': {}", link, e))?;
+    if !url.scheme().eq_ignore_ascii_case(SCHEME) {
+        return Err(format!("Not an {}:// link: '{}'", SCHEME, link));
+    }
+    // `ancheck://search?q=` has the action as its host; `ancheck:search?q=` as its path
+    let action = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_ascii_lowercase();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    match action.as_str() {
+        "show" => Ok(DeepLink::Show),
+        "search" => Ok(DeepLink::Search(param("q").unwrap_or_default())),
+        "launch" => param("path")
+            .filter(|path| !path.trim().is_empty())
+            .map(DeepLink::Launch)
+            .ok_or_else(|| "Launch link needs a path".to_string()),
+        _ => Err(format!("Unknown link action '{}'", action)),
+    }
+}
+
+/// The `ancheck:` link among the command-line arguments, as Windows passes
+/// it when the scheme is opened.
+pub fn link_from_args(args: &[String]) -> Option<String> {
+    let prefix = format!("{}:", SCHEME);
+    args.iter()
+        .find(|arg| arg.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(&prefix)))
+        .cloned()
+}
+
+/// The path to launch for a `launch` link: the indexed entry matching
+/// `path` (case-insensitively, as Windows paths are), never `path` itself.
+pub fn indexed_target(db: &Database, path: &str) -> Result<String, String> {
+    let path = path.trim();
+    db.indexed_filepath(path)
+        .map_err(|e| format!("Failed to look up '{}': {}", path, e))?
+        .ok_or_else(|| format!("Refusing to launch '{}': it is not in the index", path))
+}
+
+fn port_file(data_dir: &Path) -> PathBuf {
+    data_dir.join(PORT_FILE)
+}
+
+/// Send `link` to an already running instance, or with `None` just check
+/// for one. Returns whether an instance took it.
+pub fn forward_to_running(data_dir: &Path, link: Option<&str>) -> bool {
+    let Some(port) = std::fs::read_to_string(port_file(data_dir))
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok())
+    else {
+        return false;
+    };
+    let address = (Ipv4Addr::LOCALHOST, port).into();
+    let Ok(mut stream) = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+    let mut greeting = String::new();
+    if BufReader::new(&stream).read_line(&mut greeting).is_err() || greeting.trim_end() != GREETING {
+        return false;
+    }
+    writeln!(stream, "{}", link.unwrap_or_default()).is_ok()
+}
+
+/// Accept links from later instances on a background thread, calling
+/// `on_link` for each. Records the port for [`forward_to_running`].
+pub fn serve(data_dir: &Path, on_link: impl Fn(String) + Send + 'static) -> Result<(), String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(|e| format!("Failed to listen for {}:// links: {}", SCHEME, e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    std::fs::write(port_file(data_dir), port.to_string())
+        .map_err(|e| format!("Failed to record the instance port: {}", e))?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+            if writeln!(stream, "{}", GREETING).is_err() {
+                continue;
+            }
+            let mut link = String::new();
+            match BufReader::new(&stream).read_line(&mut link) {
+                Ok(_) if !link.trim().is_empty() => {
+                    info!("Received link from another instance");
+                    on_link(link.trim().to_string());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to read forwarded link: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Register the scheme for the current user, pointing at this executable.
+/// Rewritten on every start, so it follows the app if it moves.
+#[cfg(windows)]
+pub fn register() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find the app's executable: {}", e))?;
+    let key = format!(r"Software\Classes\{}", SCHEME);
+    set_registry_string(&key, None, "URL:AnCheck")?;
+    set_registry_string(&key, Some("URL Protocol"), "")?;
+    set_registry_string(
+        &format!(r"{}\shell\open\command", key),
+        None,
+        &format!("\"{}\" \"%1\"", exe.display()),
+    )
+}
+
+/// Remove the scheme's registration, before an uninstall.
+#[cfg(windows)]
+pub fn unregister() -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{RegDeleteTreeW, HKEY_CURRENT_USER};
+
+    let key = HSTRING::from(format!(r"Software\Classes\{}", SCHEME));
+    // SAFETY: the string outlives the call.
+    let status = unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, &key) };
+    if status == ERROR_FILE_NOT_FOUND {
+        return Ok(());
+    }
+    status
+        .ok()
+        .map_err(|e| format!("Failed to unregister {}:// links: {}", SCHEME, e))
+}
+
+#[cfg(windows)]
+fn set_registry_string(key: &str, name: Option<&str>, value: &str) -> Result<(), String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let key = HSTRING::from(key);
+    let name = name.map(HSTRING::from);
+    let name_ptr = name.as_ref().map_or(PCWSTR::null(), |name| PCWSTR(name.as_ptr()));
+    let data: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: the strings outlive the call and `data` is a null-terminated
+    // UTF-16 string of the given byte length.
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            name_ptr,
+            REG_SZ.0,
+            Some(data.as_ptr().cast()),
+            (data.len() * 2) as u32,
+        )
+    }
+    .ok()
+    .map_err(|e| format!("Failed to register {}:// links: {}", SCHEME, e))
+}
+
+#[cfg(not(windows))]
+pub fn register() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn unregister() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_links() {
+        assert_eq!(parse("ancheck://show"), Ok(DeepLink::Show));
+        assert_eq!(parse("AnCheck://Show/"), Ok(DeepLink::Show));
+        assert_eq!(parse("ancheck://search?q=vpn"), Ok(DeepLink::Search("vpn".to_string())));
+        assert_eq!(
+            parse("ancheck://search?q=q3%20budget+%C3%A9t%C3%A9%2B"),
+            Ok(DeepLink::Search("q3 budget été+".to_string()))
+        );
+        assert_eq!(parse("ancheck:search"), Ok(DeepLink::Search(String::new())));
+        assert_eq!(
+            parse(r"ancheck://launch?path=C:%5CProgram%20Files%5CVPN%5Cvpn.exe"),
+            Ok(DeepLink::Launch(r"C:\Program Files\VPN\vpn.exe".to_string()))
+        );
+
+        assert!(parse("ancheck://launch").is_err());
+        assert!(parse("ancheck://launch?path=%20").is_err());
+        assert!(parse("ancheck://delete?path=x").is_err());
+        assert!(parse("https://show").is_err());
+        assert!(parse("not a link").is_err());
+    }
+
+    #[test]
+    fn test_launch_only_indexed_paths() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let indexed = r"C:\Program Files\VPN\vpn.exe";
+        db.upsert_files_batch(&[(
+            "vpn.exe".to_string(),
+            indexed.to_string(),
+            "exe".to_string(),
+            0,
+            0,
+            "app".to_string(),
+        )])
+        .unwrap();
+
+        assert_eq!(indexed_target(&db, indexed).unwrap(), indexed);
+        // The stored path is what gets launched
+        assert_eq!(indexed_target(&db, r"c:\program files\vpn\VPN.EXE").unwrap(), indexed);
+        assert!(indexed_target(&db, r"C:\Windows\System32\cmd.exe").is_err());
+        assert!(indexed_target(&db, r"C:\Program Files\VPN\..\..\Windows\System32\cmd.exe").is_err());
+        assert!(indexed_target(&db, "").is_err());
+    }
+
+    #[test]
+    fn test_link_from_args() {
+        let args = ["--hidden".to_string(), "ancheck://search?q=x".to_string()];
+        assert_eq!(link_from_args(&args).as_deref(), Some("ancheck://search?q=x"));
+        assert_eq!(link_from_args(&["ancheck".to_string()]), None);
+    }
+}
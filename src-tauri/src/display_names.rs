@@ -0,0 +1,167 @@
+//! Display names for apps: "Visual Studio Code" for `Code.exe`, "Adobe
+//! Photoshop 2024" for `Photoshop.exe`. They're read after each full index
+//! from a shortcut's comment or the version resource of the program it (or
+//! an `.exe` result itself) points at, and searched and shown in place of
+//! the filename. Names that say nothing about the app, like "Setup
+//! Application", are dropped so the filename stays.
+//!
+//! Reading them takes a COM call or a version resource per entry, so only
+//! entries changed since their name was last read are read again.
+
+use crate::db::Database;
+use crate::launcher;
+use log::{debug, info};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Longer names are descriptions rather than names.
+const MAX_NAME_CHARS: usize = 60;
+/// Names with more words than this are sentences.
+const MAX_NAME_WORDS: usize = 6;
+/// Names that fit any program, folded.
+const GENERIC_NAMES: &[&str] = &[
+    "app",
+    "application",
+    "setup",
+    "setup application",
+    "setup launcher",
+    "installer",
+    "install",
+    "uninstall",
+    "uninstaller",
+    "launcher",
+    "application launcher",
+    "program",
+    "executable",
+    "main",
+    "shortcut",
+    "microsoft windows operating system",
+    "microsoft® windows® operating system",
+];
+/// Names read per write to the index.
+const BATCH_SIZE: usize = 200;
+
+/// Read display names for every shortcut and executable that changed since
+/// its name was last read, stopping early once `cancel` is set. Returns how
+/// many entries got a name.
+pub fn update_display_names(db: &Database, cancel: &AtomicBool) -> Result<usize, String> {
+    let pending = db
+        .display_names_to_read()
+        .map_err(|e| format!("Failed to list display names to read: {}", e))?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+    let mut named = 0usize;
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    for chunk in pending.chunks(BATCH_SIZE) {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        for (filepath, modified_at) in chunk {
+            let name = display_name(Path::new(filepath)).unwrap_or_default();
+            named += usize::from(!name.is_empty());
+            batch.push((filepath.clone(), name, *modified_at));
+        }
+        db.set_display_names(&batch)
+            .map_err(|e| format!("Failed to save display names: {}", e))?;
+        batch.clear();
+    }
+    info!("Read display names for {} entries, {} named", pending.len(), named);
+    Ok(named)
+}
+
+/// The name to show for a shortcut or executable, if it has a useful one:
+/// a shortcut's comment, else the product name of its target program; for
+/// an `.exe`, its product name, else its file description.
+pub fn display_name(path: &Path) -> Option<String> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let filename = path.file_name()?.to_string_lossy();
+    let program = match extension.as_str() {
+        "lnk" => {
+            let description = launcher::shortcut_description(path)
+                .map_err(|e| debug!("No shortcut comment for '{}': {}", path.display(), e))
+                .ok()
+                .flatten();
+            if let Some(name) = description.filter(|name| is_useful_name(name, &filename)) {
+                return Some(name);
+            }
+            launcher::shortcut_target(path).ok()?
+        }
+        "exe" => path.to_path_buf(),
+        _ => return None,
+    };
+    if !program.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("exe")) {
+        return None;
+    }
+    let version = launcher::version_info(&program)
+        .map_err(|e| debug!("No version info for '{}': {}", program.display(), e))
+        .ok()?;
+    [version.product_name, version.file_description]
+        .into_iter()
+        .flatten()
+        .find(|name| is_useful_name(name, &filename))
+}
+
+/// Whether `name` tells the app of `filename` apart better than the
+/// filename does: not blank, generic, a sentence, or the filename again.
+fn is_useful_name(name: &str, filename: &str) -> bool {
+    let name = name.trim();
+    let folded = crate::searcher::fold_text(name);
+    let stem = Path::new(filename)
+        .file_stem()
+        .map(|stem| crate::searcher::fold_text(&stem.to_string_lossy()))
+        .unwrap_or_default();
+    !name.is_empty()
+        && name.chars().count() <= MAX_NAME_CHARS
+        && name.split_whitespace().count() <= MAX_NAME_WORDS
+        && !name.ends_with('.')
+        && name.chars().any(char::is_alphanumeric)
+        && !GENERIC_NAMES.contains(&folded.as_str())
+        && folded != stem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::searcher::QueryFilters;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_useful_names() {
+        assert!(is_useful_name("Visual Studio Code", "Code.exe"));
+        assert!(is_useful_name("Adobe Photoshop 2024", "Photoshop.exe"));
+        assert!(!is_useful_name("Setup Application", "setup.exe"));
+        assert!(!is_useful_name("Microsoft® Windows® Operating System", "notepad.exe"));
+        assert!(!is_useful_name("   ", "tool.exe"));
+        assert!(!is_useful_name("---", "tool.exe"));
+        // The filename again adds nothing
+        assert!(!is_useful_name("code", "Code.exe"));
+        assert!(!is_useful_name("Opens the editor to write and edit your documents.", "Editor.lnk"));
+        assert!(!is_useful_name("A very long product name that keeps going on and on and on forever", "a.exe"));
+    }
+
+    #[test]
+    fn test_display_names_are_read_once_per_change() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        db.upsert_file("Code.exe", r"C:\Apps\Code.exe", "exe", 1, 10, "application").unwrap();
+        db.upsert_file("notes.txt", r"C:\notes.txt", "txt", 1, 10, "document").unwrap();
+        let pending = db.display_names_to_read().unwrap();
+        assert_eq!(pending, vec![(r"C:\Apps\Code.exe".to_string(), 10)]);
+
+        db.set_display_names(&[(r"C:\Apps\Code.exe".to_string(), "Visual Studio Code".to_string(), 10)])
+            .unwrap();
+        assert!(db.display_names_to_read().unwrap().is_empty());
+        let found = |query| db.search_files(query, &QueryFilters::default(), 10).unwrap();
+        let hits = found("visual studio");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].display_name, "Visual Studio Code");
+
+        // Re-indexing keeps the name, and a changed file is read again
+        db.upsert_file("Code.exe", r"C:\Apps\Code.exe", "exe", 1, 20, "application").unwrap();
+        assert_eq!(db.display_names_to_read().unwrap().len(), 1);
+        assert_eq!(found("visual studio code")[0].filename, "Code.exe");
+    }
+}
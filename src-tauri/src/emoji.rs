@@ -0,0 +1,601 @@
+//! Emoji table for the `:` / `emoji ` search provider.
+//!
+//! Generated from Unicode character names (lowercased) plus hand-picked
+//! keywords; a compact set of commonly used emoji rather than the full
+//! Unicode list. Skin-tone variants are not included.
+
+/// An emoji with its Unicode name and space-separated search keywords.
+pub struct Emoji {
+    pub emoji: &'static str,
+    pub name: &'static str,
+    pub keywords: &'static str,
+}
+
+pub const EMOJI: &[Emoji] = &[
+    Emoji { emoji: "😀", name: "grinning face", keywords: "smile happy grin" },
+    Emoji { emoji: "😃", name: "smiling face with open mouth", keywords: "smile happy" },
+    Emoji { emoji: "😄", name: "smiling face with open mouth and smiling eyes", keywords: "smile happy laugh" },
+    Emoji { emoji: "😁", name: "grinning face with smiling eyes", keywords: "grin teeth" },
+    Emoji { emoji: "😆", name: "smiling face with open mouth and tightly-closed eyes", keywords: "laugh lol" },
+    Emoji { emoji: "😅", name: "smiling face with open mouth and cold sweat", keywords: "relief" },
+    Emoji { emoji: "🤣", name: "rolling on the floor laughing", keywords: "rofl lol laugh" },
+    Emoji { emoji: "😂", name: "face with tears of joy", keywords: "lol laugh cry" },
+    Emoji { emoji: "🙂", name: "slightly smiling face", keywords: "smile" },
+    Emoji { emoji: "🙃", name: "upside-down face", keywords: "upside down sarcasm" },
+    Emoji { emoji: "😉", name: "winking face", keywords: "wink" },
+    Emoji { emoji: "😊", name: "smiling face with smiling eyes", keywords: "blush smile happy" },
+    Emoji { emoji: "😇", name: "smiling face with halo", keywords: "angel innocent" },
+    Emoji { emoji: "🥰", name: "smiling face with smiling eyes and three hearts", keywords: "love adore" },
+    Emoji { emoji: "😍", name: "smiling face with heart-shaped eyes", keywords: "love heart crush" },
+    Emoji { emoji: "🤩", name: "grinning face with star eyes", keywords: "struck wow" },
+    Emoji { emoji: "😘", name: "face throwing a kiss", keywords: "love" },
+    Emoji { emoji: "😋", name: "face savouring delicious food", keywords: "yum tasty" },
+    Emoji { emoji: "😛", name: "face with stuck-out tongue", keywords: "" },
+    Emoji { emoji: "😜", name: "face with stuck-out tongue and winking eye", keywords: "wink crazy" },
+    Emoji { emoji: "🤪", name: "grinning face with one large and one small eye", keywords: "crazy zany goofy" },
+    Emoji { emoji: "🤑", name: "money-mouth face", keywords: "money rich" },
+    Emoji { emoji: "🤗", name: "hugging face", keywords: "hug" },
+    Emoji { emoji: "🤭", name: "smiling face with smiling eyes and hand covering mouth", keywords: "oops giggle" },
+    Emoji { emoji: "🤫", name: "face with finger covering closed lips", keywords: "shush quiet secret" },
+    Emoji { emoji: "🤔", name: "thinking face", keywords: "think hmm wonder" },
+    Emoji { emoji: "🤐", name: "zipper-mouth face", keywords: "zip secret" },
+    Emoji { emoji: "🤨", name: "face with one eyebrow raised", keywords: "skeptical suspicious" },
+    Emoji { emoji: "😐", name: "neutral face", keywords: "meh" },
+    Emoji { emoji: "😑", name: "expressionless face", keywords: "meh" },
+    Emoji { emoji: "😶", name: "face without mouth", keywords: "speechless silent" },
+    Emoji { emoji: "😏", name: "smirking face", keywords: "smirk" },
+    Emoji { emoji: "😒", name: "unamused face", keywords: "meh" },
+    Emoji { emoji: "🙄", name: "face with rolling eyes", keywords: "eye roll whatever" },
+    Emoji { emoji: "😬", name: "grimacing face", keywords: "grimace awkward" },
+    Emoji { emoji: "😌", name: "relieved face", keywords: "calm" },
+    Emoji { emoji: "😔", name: "pensive face", keywords: "sad" },
+    Emoji { emoji: "😪", name: "sleepy face", keywords: "tired" },
+    Emoji { emoji: "🤤", name: "drooling face", keywords: "drool" },
+    Emoji { emoji: "😴", name: "sleeping face", keywords: "sleep tired zzz" },
+    Emoji { emoji: "😷", name: "face with medical mask", keywords: "sick ill" },
+    Emoji { emoji: "🤒", name: "face with thermometer", keywords: "sick fever ill" },
+    Emoji { emoji: "🤕", name: "face with head-bandage", keywords: "hurt injured" },
+    Emoji { emoji: "🤢", name: "nauseated face", keywords: "nausea sick gross" },
+    Emoji { emoji: "🤮", name: "face with open mouth vomiting", keywords: "vomit sick gross" },
+    Emoji { emoji: "🥵", name: "overheated face", keywords: "hot heat sweating" },
+    Emoji { emoji: "🥶", name: "freezing face", keywords: "cold" },
+    Emoji { emoji: "🥴", name: "face with uneven eyes and wavy mouth", keywords: "woozy drunk dizzy" },
+    Emoji { emoji: "😵", name: "dizzy face", keywords: "dead" },
+    Emoji { emoji: "🤯", name: "shocked face with exploding head", keywords: "mind blown" },
+    Emoji { emoji: "🤠", name: "face with cowboy hat", keywords: "" },
+    Emoji { emoji: "🥳", name: "face with party horn and party hat", keywords: "celebrate birthday" },
+    Emoji { emoji: "😎", name: "smiling face with sunglasses", keywords: "cool" },
+    Emoji { emoji: "🤓", name: "nerd face", keywords: "geek" },
+    Emoji { emoji: "🧐", name: "face with monocle", keywords: "curious" },
+    Emoji { emoji: "😕", name: "confused face", keywords: "" },
+    Emoji { emoji: "😟", name: "worried face", keywords: "" },
+    Emoji { emoji: "🙁", name: "slightly frowning face", keywords: "frown sad" },
+    Emoji { emoji: "😮", name: "face with open mouth", keywords: "surprised wow" },
+    Emoji { emoji: "😯", name: "hushed face", keywords: "surprised" },
+    Emoji { emoji: "😲", name: "astonished face", keywords: "shocked" },
+    Emoji { emoji: "😳", name: "flushed face", keywords: "embarrassed" },
+    Emoji { emoji: "🥺", name: "face with pleading eyes", keywords: "puppy please" },
+    Emoji { emoji: "😦", name: "frowning face with open mouth", keywords: "frown" },
+    Emoji { emoji: "😧", name: "anguished face", keywords: "" },
+    Emoji { emoji: "😨", name: "fearful face", keywords: "scared" },
+    Emoji { emoji: "😰", name: "face with open mouth and cold sweat", keywords: "anxious" },
+    Emoji { emoji: "😥", name: "disappointed but relieved face", keywords: "sad" },
+    Emoji { emoji: "😢", name: "crying face", keywords: "cry sad tear" },
+    Emoji { emoji: "😭", name: "loudly crying face", keywords: "sob cry sad" },
+    Emoji { emoji: "😱", name: "face screaming in fear", keywords: "scream scared horror" },
+    Emoji { emoji: "😖", name: "confounded face", keywords: "" },
+    Emoji { emoji: "😣", name: "persevering face", keywords: "persevere" },
+    Emoji { emoji: "😞", name: "disappointed face", keywords: "sad" },
+    Emoji { emoji: "😓", name: "face with cold sweat", keywords: "downcast" },
+    Emoji { emoji: "😩", name: "weary face", keywords: "tired" },
+    Emoji { emoji: "😫", name: "tired face", keywords: "exhausted" },
+    Emoji { emoji: "🥱", name: "yawning face", keywords: "yawn bored tired" },
+    Emoji { emoji: "😤", name: "face with look of triumph", keywords: "angry huff" },
+    Emoji { emoji: "😡", name: "pouting face", keywords: "angry mad rage" },
+    Emoji { emoji: "😠", name: "angry face", keywords: "mad" },
+    Emoji { emoji: "🤬", name: "serious face with symbols covering mouth", keywords: "swear curse angry" },
+    Emoji { emoji: "😈", name: "smiling face with horns", keywords: "devil evil smile" },
+    Emoji { emoji: "👿", name: "imp", keywords: "devil angry" },
+    Emoji { emoji: "💀", name: "skull", keywords: "dead" },
+    Emoji { emoji: "💩", name: "pile of poo", keywords: "poop" },
+    Emoji { emoji: "🤡", name: "clown face", keywords: "" },
+    Emoji { emoji: "👻", name: "ghost", keywords: "halloween" },
+    Emoji { emoji: "👽", name: "extraterrestrial alien", keywords: "ufo" },
+    Emoji { emoji: "🤖", name: "robot face", keywords: "bot" },
+    Emoji { emoji: "😺", name: "smiling cat face with open mouth", keywords: "smile" },
+    Emoji { emoji: "❤️", name: "heavy black heart", keywords: "love red" },
+    Emoji { emoji: "🧡", name: "orange heart", keywords: "" },
+    Emoji { emoji: "💛", name: "yellow heart", keywords: "" },
+    Emoji { emoji: "💚", name: "green heart", keywords: "" },
+    Emoji { emoji: "💙", name: "blue heart", keywords: "" },
+    Emoji { emoji: "💜", name: "purple heart", keywords: "" },
+    Emoji { emoji: "🖤", name: "black heart", keywords: "" },
+    Emoji { emoji: "🤍", name: "white heart", keywords: "" },
+    Emoji { emoji: "💔", name: "broken heart", keywords: "heartbreak" },
+    Emoji { emoji: "💕", name: "two hearts", keywords: "love" },
+    Emoji { emoji: "💯", name: "hundred points symbol", keywords: "perfect score" },
+    Emoji { emoji: "💢", name: "anger symbol", keywords: "" },
+    Emoji { emoji: "💥", name: "collision symbol", keywords: "boom explosion" },
+    Emoji { emoji: "💫", name: "dizzy symbol", keywords: "star" },
+    Emoji { emoji: "💦", name: "splashing sweat symbol", keywords: "water drops" },
+    Emoji { emoji: "💨", name: "dash symbol", keywords: "fast wind" },
+    Emoji { emoji: "💬", name: "speech balloon", keywords: "chat message" },
+    Emoji { emoji: "💭", name: "thought balloon", keywords: "bubble" },
+    Emoji { emoji: "💤", name: "sleeping symbol", keywords: "sleep zzz" },
+    Emoji { emoji: "👋", name: "waving hand sign", keywords: "wave hello hi bye" },
+    Emoji { emoji: "🤚", name: "raised back of hand", keywords: "" },
+    Emoji { emoji: "✋", name: "raised hand", keywords: "stop high five" },
+    Emoji { emoji: "🖖", name: "raised hand with part between middle and ring fingers", keywords: "vulcan spock" },
+    Emoji { emoji: "👌", name: "ok hand sign", keywords: "okay perfect" },
+    Emoji { emoji: "🤌", name: "pinched fingers", keywords: "italian" },
+    Emoji { emoji: "✌️", name: "victory hand", keywords: "peace" },
+    Emoji { emoji: "🤞", name: "hand with index and middle fingers crossed", keywords: "luck" },
+    Emoji { emoji: "🤟", name: "i love you hand sign", keywords: "" },
+    Emoji { emoji: "🤘", name: "sign of the horns", keywords: "rock metal" },
+    Emoji { emoji: "🤙", name: "call me hand", keywords: "shaka" },
+    Emoji { emoji: "👈", name: "white left pointing backhand index", keywords: "point" },
+    Emoji { emoji: "👉", name: "white right pointing backhand index", keywords: "point" },
+    Emoji { emoji: "👆", name: "white up pointing backhand index", keywords: "point" },
+    Emoji { emoji: "👇", name: "white down pointing backhand index", keywords: "point" },
+    Emoji { emoji: "👍", name: "thumbs up sign", keywords: "like yes approve +1" },
+    Emoji { emoji: "👎", name: "thumbs down sign", keywords: "dislike no -1" },
+    Emoji { emoji: "✊", name: "raised fist", keywords: "" },
+    Emoji { emoji: "👊", name: "fisted hand sign", keywords: "punch fist bump" },
+    Emoji { emoji: "👏", name: "clapping hands sign", keywords: "clap applause bravo" },
+    Emoji { emoji: "🙌", name: "person raising both hands in celebration", keywords: "hooray raised celebrate" },
+    Emoji { emoji: "👐", name: "open hands sign", keywords: "" },
+    Emoji { emoji: "🤲", name: "palms up together", keywords: "" },
+    Emoji { emoji: "🤝", name: "handshake", keywords: "deal agreement" },
+    Emoji { emoji: "🙏", name: "person with folded hands", keywords: "pray please thanks namaste" },
+    Emoji { emoji: "✍️", name: "writing hand", keywords: "write" },
+    Emoji { emoji: "💪", name: "flexed biceps", keywords: "muscle strong flex" },
+    Emoji { emoji: "🧠", name: "brain", keywords: "smart" },
+    Emoji { emoji: "👀", name: "eyes", keywords: "look see" },
+    Emoji { emoji: "👁️", name: "eye", keywords: "" },
+    Emoji { emoji: "👶", name: "baby", keywords: "" },
+    Emoji { emoji: "🧒", name: "child", keywords: "kid" },
+    Emoji { emoji: "👦", name: "boy", keywords: "" },
+    Emoji { emoji: "👧", name: "girl", keywords: "" },
+    Emoji { emoji: "🧑", name: "adult", keywords: "person" },
+    Emoji { emoji: "👨", name: "man", keywords: "" },
+    Emoji { emoji: "👩", name: "woman", keywords: "" },
+    Emoji { emoji: "🧓", name: "older adult", keywords: "person" },
+    Emoji { emoji: "👴", name: "older man", keywords: "old grandpa" },
+    Emoji { emoji: "👵", name: "older woman", keywords: "old grandma" },
+    Emoji { emoji: "👮", name: "police officer", keywords: "cop" },
+    Emoji { emoji: "🕵️", name: "sleuth or spy", keywords: "detective" },
+    Emoji { emoji: "💂", name: "guardsman", keywords: "guard" },
+    Emoji { emoji: "👷", name: "construction worker", keywords: "builder" },
+    Emoji { emoji: "🤴", name: "prince", keywords: "" },
+    Emoji { emoji: "👸", name: "princess", keywords: "" },
+    Emoji { emoji: "👰", name: "bride with veil", keywords: "wedding" },
+    Emoji { emoji: "🤵", name: "man in tuxedo", keywords: "groom" },
+    Emoji { emoji: "🤰", name: "pregnant woman", keywords: "" },
+    Emoji { emoji: "🎅", name: "father christmas", keywords: "santa" },
+    Emoji { emoji: "🧙", name: "mage", keywords: "wizard magic" },
+    Emoji { emoji: "🧚", name: "fairy", keywords: "" },
+    Emoji { emoji: "🧛", name: "vampire", keywords: "" },
+    Emoji { emoji: "🧟", name: "zombie", keywords: "" },
+    Emoji { emoji: "🙋", name: "happy person raising one hand", keywords: "raise hi" },
+    Emoji { emoji: "🙅", name: "face with no good gesture", keywords: "" },
+    Emoji { emoji: "🙆", name: "face with ok gesture", keywords: "" },
+    Emoji { emoji: "🤷", name: "shrug", keywords: "whatever idk" },
+    Emoji { emoji: "🤦", name: "face palm", keywords: "facepalm" },
+    Emoji { emoji: "🙇", name: "person bowing deeply", keywords: "bow sorry" },
+    Emoji { emoji: "💃", name: "dancer", keywords: "dance" },
+    Emoji { emoji: "🕺", name: "man dancing", keywords: "dance disco" },
+    Emoji { emoji: "🏃", name: "runner", keywords: "run running" },
+    Emoji { emoji: "🚶", name: "pedestrian", keywords: "walk walking" },
+    Emoji { emoji: "🐶", name: "dog face", keywords: "puppy" },
+    Emoji { emoji: "🐱", name: "cat face", keywords: "kitten" },
+    Emoji { emoji: "🐭", name: "mouse face", keywords: "" },
+    Emoji { emoji: "🐹", name: "hamster face", keywords: "" },
+    Emoji { emoji: "🐰", name: "rabbit face", keywords: "bunny" },
+    Emoji { emoji: "🦊", name: "fox face", keywords: "" },
+    Emoji { emoji: "🐻", name: "bear face", keywords: "" },
+    Emoji { emoji: "🐼", name: "panda face", keywords: "" },
+    Emoji { emoji: "🐨", name: "koala", keywords: "" },
+    Emoji { emoji: "🐯", name: "tiger face", keywords: "" },
+    Emoji { emoji: "🦁", name: "lion face", keywords: "" },
+    Emoji { emoji: "🐮", name: "cow face", keywords: "" },
+    Emoji { emoji: "🐷", name: "pig face", keywords: "" },
+    Emoji { emoji: "🐸", name: "frog face", keywords: "" },
+    Emoji { emoji: "🐵", name: "monkey face", keywords: "" },
+    Emoji { emoji: "🙈", name: "see-no-evil monkey", keywords: "see no evil" },
+    Emoji { emoji: "🐔", name: "chicken", keywords: "" },
+    Emoji { emoji: "🐧", name: "penguin", keywords: "" },
+    Emoji { emoji: "🐦", name: "bird", keywords: "" },
+    Emoji { emoji: "🐤", name: "baby chick", keywords: "" },
+    Emoji { emoji: "🦆", name: "duck", keywords: "" },
+    Emoji { emoji: "🦅", name: "eagle", keywords: "" },
+    Emoji { emoji: "🦉", name: "owl", keywords: "" },
+    Emoji { emoji: "🦇", name: "bat", keywords: "" },
+    Emoji { emoji: "🐺", name: "wolf face", keywords: "" },
+    Emoji { emoji: "🐴", name: "horse face", keywords: "" },
+    Emoji { emoji: "🦄", name: "unicorn face", keywords: "" },
+    Emoji { emoji: "🐝", name: "honeybee", keywords: "bee" },
+    Emoji { emoji: "🐛", name: "bug", keywords: "caterpillar" },
+    Emoji { emoji: "🦋", name: "butterfly", keywords: "" },
+    Emoji { emoji: "🐌", name: "snail", keywords: "slow" },
+    Emoji { emoji: "🐞", name: "lady beetle", keywords: "ladybug bug" },
+    Emoji { emoji: "🐜", name: "ant", keywords: "" },
+    Emoji { emoji: "🕷️", name: "spider", keywords: "" },
+    Emoji { emoji: "🐢", name: "turtle", keywords: "slow" },
+    Emoji { emoji: "🐍", name: "snake", keywords: "python" },
+    Emoji { emoji: "🦖", name: "t-rex", keywords: "dinosaur" },
+    Emoji { emoji: "🐙", name: "octopus", keywords: "" },
+    Emoji { emoji: "🦑", name: "squid", keywords: "" },
+    Emoji { emoji: "🦀", name: "crab", keywords: "rust" },
+    Emoji { emoji: "🐠", name: "tropical fish", keywords: "" },
+    Emoji { emoji: "🐟", name: "fish", keywords: "" },
+    Emoji { emoji: "🐬", name: "dolphin", keywords: "" },
+    Emoji { emoji: "🐳", name: "spouting whale", keywords: "" },
+    Emoji { emoji: "🦈", name: "shark", keywords: "" },
+    Emoji { emoji: "🐊", name: "crocodile", keywords: "" },
+    Emoji { emoji: "🐘", name: "elephant", keywords: "" },
+    Emoji { emoji: "🦒", name: "giraffe face", keywords: "" },
+    Emoji { emoji: "🐪", name: "dromedary camel", keywords: "" },
+    Emoji { emoji: "🐑", name: "sheep", keywords: "" },
+    Emoji { emoji: "🐐", name: "goat", keywords: "" },
+    Emoji { emoji: "🦌", name: "deer", keywords: "" },
+    Emoji { emoji: "🐕", name: "dog", keywords: "" },
+    Emoji { emoji: "🐈", name: "cat", keywords: "" },
+    Emoji { emoji: "🐓", name: "rooster", keywords: "" },
+    Emoji { emoji: "🦃", name: "turkey", keywords: "" },
+    Emoji { emoji: "🦜", name: "parrot", keywords: "" },
+    Emoji { emoji: "🐿️", name: "chipmunk", keywords: "squirrel" },
+    Emoji { emoji: "💐", name: "bouquet", keywords: "flowers" },
+    Emoji { emoji: "🌸", name: "cherry blossom", keywords: "flower spring" },
+    Emoji { emoji: "🌹", name: "rose", keywords: "flower" },
+    Emoji { emoji: "🌺", name: "hibiscus", keywords: "flower" },
+    Emoji { emoji: "🌻", name: "sunflower", keywords: "flower" },
+    Emoji { emoji: "🌼", name: "blossom", keywords: "flower" },
+    Emoji { emoji: "🌷", name: "tulip", keywords: "flower" },
+    Emoji { emoji: "🌱", name: "seedling", keywords: "sprout plant" },
+    Emoji { emoji: "🌲", name: "evergreen tree", keywords: "pine" },
+    Emoji { emoji: "🌳", name: "deciduous tree", keywords: "" },
+    Emoji { emoji: "🌴", name: "palm tree", keywords: "beach" },
+    Emoji { emoji: "🌵", name: "cactus", keywords: "desert" },
+    Emoji { emoji: "🍀", name: "four leaf clover", keywords: "luck" },
+    Emoji { emoji: "🍁", name: "maple leaf", keywords: "autumn fall canada" },
+    Emoji { emoji: "🍂", name: "fallen leaf", keywords: "leaves autumn fall" },
+    Emoji { emoji: "🍄", name: "mushroom", keywords: "" },
+    Emoji { emoji: "🌍", name: "earth globe europe-africa", keywords: "world europe africa" },
+    Emoji { emoji: "🌎", name: "earth globe americas", keywords: "world" },
+    Emoji { emoji: "🌏", name: "earth globe asia-australia", keywords: "world asia" },
+    Emoji { emoji: "🌙", name: "crescent moon", keywords: "night" },
+    Emoji { emoji: "🌕", name: "full moon symbol", keywords: "" },
+    Emoji { emoji: "⭐", name: "white medium star", keywords: "" },
+    Emoji { emoji: "🌟", name: "glowing star", keywords: "sparkle" },
+    Emoji { emoji: "✨", name: "sparkles", keywords: "magic shiny" },
+    Emoji { emoji: "⚡", name: "high voltage sign", keywords: "lightning zap electric" },
+    Emoji { emoji: "🔥", name: "fire", keywords: "hot flame lit" },
+    Emoji { emoji: "🌈", name: "rainbow", keywords: "" },
+    Emoji { emoji: "☀️", name: "black sun with rays", keywords: "sunny weather" },
+    Emoji { emoji: "🌤️", name: "white sun with small cloud", keywords: "weather" },
+    Emoji { emoji: "⛅", name: "sun behind cloud", keywords: "partly cloudy weather" },
+    Emoji { emoji: "☁️", name: "cloud", keywords: "weather" },
+    Emoji { emoji: "🌧️", name: "cloud with rain", keywords: "weather" },
+    Emoji { emoji: "⛈️", name: "thunder cloud and rain", keywords: "storm weather" },
+    Emoji { emoji: "❄️", name: "snowflake", keywords: "snow cold winter" },
+    Emoji { emoji: "☃️", name: "snowman", keywords: "winter" },
+    Emoji { emoji: "⛄", name: "snowman without snow", keywords: "winter" },
+    Emoji { emoji: "🌊", name: "water wave", keywords: "ocean sea" },
+    Emoji { emoji: "💧", name: "droplet", keywords: "water" },
+    Emoji { emoji: "☔", name: "umbrella with rain drops", keywords: "" },
+    Emoji { emoji: "🍏", name: "green apple", keywords: "fruit" },
+    Emoji { emoji: "🍎", name: "red apple", keywords: "fruit" },
+    Emoji { emoji: "🍐", name: "pear", keywords: "fruit" },
+    Emoji { emoji: "🍊", name: "tangerine", keywords: "orange fruit" },
+    Emoji { emoji: "🍋", name: "lemon", keywords: "fruit" },
+    Emoji { emoji: "🍌", name: "banana", keywords: "fruit" },
+    Emoji { emoji: "🍉", name: "watermelon", keywords: "fruit" },
+    Emoji { emoji: "🍇", name: "grapes", keywords: "fruit" },
+    Emoji { emoji: "🍓", name: "strawberry", keywords: "fruit" },
+    Emoji { emoji: "🍒", name: "cherries", keywords: "fruit" },
+    Emoji { emoji: "🍑", name: "peach", keywords: "fruit" },
+    Emoji { emoji: "🥭", name: "mango", keywords: "fruit" },
+    Emoji { emoji: "🍍", name: "pineapple", keywords: "fruit" },
+    Emoji { emoji: "🥥", name: "coconut", keywords: "" },
+    Emoji { emoji: "🥝", name: "kiwifruit", keywords: "kiwi fruit" },
+    Emoji { emoji: "🍅", name: "tomato", keywords: "" },
+    Emoji { emoji: "🥑", name: "avocado", keywords: "" },
+    Emoji { emoji: "🍆", name: "aubergine", keywords: "eggplant" },
+    Emoji { emoji: "🥔", name: "potato", keywords: "" },
+    Emoji { emoji: "🥕", name: "carrot", keywords: "" },
+    Emoji { emoji: "🌽", name: "ear of maize", keywords: "corn" },
+    Emoji { emoji: "🌶️", name: "hot pepper", keywords: "chili spicy" },
+    Emoji { emoji: "🥦", name: "broccoli", keywords: "" },
+    Emoji { emoji: "🧄", name: "garlic", keywords: "" },
+    Emoji { emoji: "🧅", name: "onion", keywords: "" },
+    Emoji { emoji: "🥜", name: "peanuts", keywords: "" },
+    Emoji { emoji: "🍞", name: "bread", keywords: "" },
+    Emoji { emoji: "🥐", name: "croissant", keywords: "" },
+    Emoji { emoji: "🥨", name: "pretzel", keywords: "" },
+    Emoji { emoji: "🧀", name: "cheese wedge", keywords: "" },
+    Emoji { emoji: "🥚", name: "egg", keywords: "" },
+    Emoji { emoji: "🍳", name: "cooking", keywords: "fried egg" },
+    Emoji { emoji: "🥓", name: "bacon", keywords: "" },
+    Emoji { emoji: "🥞", name: "pancakes", keywords: "" },
+    Emoji { emoji: "🍗", name: "poultry leg", keywords: "chicken" },
+    Emoji { emoji: "🍖", name: "meat on bone", keywords: "" },
+    Emoji { emoji: "🍔", name: "hamburger", keywords: "burger" },
+    Emoji { emoji: "🍟", name: "french fries", keywords: "" },
+    Emoji { emoji: "🍕", name: "slice of pizza", keywords: "" },
+    Emoji { emoji: "🌭", name: "hot dog", keywords: "" },
+    Emoji { emoji: "🥪", name: "sandwich", keywords: "" },
+    Emoji { emoji: "🌮", name: "taco", keywords: "" },
+    Emoji { emoji: "🌯", name: "burrito", keywords: "" },
+    Emoji { emoji: "🥗", name: "green salad", keywords: "" },
+    Emoji { emoji: "🍿", name: "popcorn", keywords: "movie" },
+    Emoji { emoji: "🍱", name: "bento box", keywords: "" },
+    Emoji { emoji: "🍣", name: "sushi", keywords: "" },
+    Emoji { emoji: "🍜", name: "steaming bowl", keywords: "ramen noodles" },
+    Emoji { emoji: "🍝", name: "spaghetti", keywords: "pasta" },
+    Emoji { emoji: "🍦", name: "soft ice cream", keywords: "" },
+    Emoji { emoji: "🍩", name: "doughnut", keywords: "donut" },
+    Emoji { emoji: "🍪", name: "cookie", keywords: "" },
+    Emoji { emoji: "🎂", name: "birthday cake", keywords: "" },
+    Emoji { emoji: "🍰", name: "shortcake", keywords: "cake" },
+    Emoji { emoji: "🧁", name: "cupcake", keywords: "" },
+    Emoji { emoji: "🍫", name: "chocolate bar", keywords: "" },
+    Emoji { emoji: "🍬", name: "candy", keywords: "" },
+    Emoji { emoji: "🍭", name: "lollipop", keywords: "" },
+    Emoji { emoji: "☕", name: "hot beverage", keywords: "coffee tea" },
+    Emoji { emoji: "🍵", name: "teacup without handle", keywords: "tea" },
+    Emoji { emoji: "🍺", name: "beer mug", keywords: "" },
+    Emoji { emoji: "🍻", name: "clinking beer mugs", keywords: "beers cheers" },
+    Emoji { emoji: "🍷", name: "wine glass", keywords: "" },
+    Emoji { emoji: "🥂", name: "clinking glasses", keywords: "cheers toast champagne" },
+    Emoji { emoji: "🍸", name: "cocktail glass", keywords: "martini" },
+    Emoji { emoji: "🥤", name: "cup with straw", keywords: "soda drink" },
+    Emoji { emoji: "⚽", name: "soccer ball", keywords: "football" },
+    Emoji { emoji: "🏀", name: "basketball and hoop", keywords: "" },
+    Emoji { emoji: "🏈", name: "american football", keywords: "" },
+    Emoji { emoji: "⚾", name: "baseball", keywords: "" },
+    Emoji { emoji: "🎾", name: "tennis racquet and ball", keywords: "" },
+    Emoji { emoji: "🏐", name: "volleyball", keywords: "" },
+    Emoji { emoji: "🎱", name: "billiards", keywords: "pool 8 ball" },
+    Emoji { emoji: "🏓", name: "table tennis paddle and ball", keywords: "ping pong" },
+    Emoji { emoji: "🥊", name: "boxing glove", keywords: "" },
+    Emoji { emoji: "🏆", name: "trophy", keywords: "winner award" },
+    Emoji { emoji: "🥇", name: "first place medal", keywords: "gold" },
+    Emoji { emoji: "🥈", name: "second place medal", keywords: "silver" },
+    Emoji { emoji: "🥉", name: "third place medal", keywords: "bronze" },
+    Emoji { emoji: "🎯", name: "direct hit", keywords: "target bullseye dart goal" },
+    Emoji { emoji: "🎮", name: "video game", keywords: "controller gaming" },
+    Emoji { emoji: "🕹️", name: "joystick", keywords: "game" },
+    Emoji { emoji: "🎲", name: "game die", keywords: "dice" },
+    Emoji { emoji: "🧩", name: "jigsaw puzzle piece", keywords: "" },
+    Emoji { emoji: "♟️", name: "black chess pawn", keywords: "" },
+    Emoji { emoji: "🎨", name: "artist palette", keywords: "art paint" },
+    Emoji { emoji: "🎬", name: "clapper board", keywords: "movie film" },
+    Emoji { emoji: "🎤", name: "microphone", keywords: "karaoke sing" },
+    Emoji { emoji: "🎧", name: "headphone", keywords: "headphones music" },
+    Emoji { emoji: "🎵", name: "musical note", keywords: "music" },
+    Emoji { emoji: "🎶", name: "multiple musical notes", keywords: "music" },
+    Emoji { emoji: "🎹", name: "musical keyboard", keywords: "piano music" },
+    Emoji { emoji: "🎸", name: "guitar", keywords: "music" },
+    Emoji { emoji: "🥁", name: "drum with drumsticks", keywords: "music" },
+    Emoji { emoji: "🎉", name: "party popper", keywords: "tada celebrate congrats" },
+    Emoji { emoji: "🎊", name: "confetti ball", keywords: "celebrate" },
+    Emoji { emoji: "🎈", name: "balloon", keywords: "party" },
+    Emoji { emoji: "🎁", name: "wrapped present", keywords: "gift birthday" },
+    Emoji { emoji: "🎄", name: "christmas tree", keywords: "xmas" },
+    Emoji { emoji: "🎃", name: "jack-o-lantern", keywords: "jack o lantern pumpkin halloween" },
+    Emoji { emoji: "🎆", name: "fireworks", keywords: "celebrate" },
+    Emoji { emoji: "🚗", name: "automobile", keywords: "car" },
+    Emoji { emoji: "🚕", name: "taxi", keywords: "cab" },
+    Emoji { emoji: "🚌", name: "bus", keywords: "" },
+    Emoji { emoji: "🚑", name: "ambulance", keywords: "" },
+    Emoji { emoji: "🚒", name: "fire engine", keywords: "truck" },
+    Emoji { emoji: "🚓", name: "police car", keywords: "" },
+    Emoji { emoji: "🚚", name: "delivery truck", keywords: "" },
+    Emoji { emoji: "🚜", name: "tractor", keywords: "" },
+    Emoji { emoji: "🏍️", name: "racing motorcycle", keywords: "" },
+    Emoji { emoji: "🚲", name: "bicycle", keywords: "bike" },
+    Emoji { emoji: "🛴", name: "scooter", keywords: "" },
+    Emoji { emoji: "🚂", name: "steam locomotive", keywords: "train" },
+    Emoji { emoji: "🚆", name: "train", keywords: "" },
+    Emoji { emoji: "🚇", name: "metro", keywords: "subway" },
+    Emoji { emoji: "✈️", name: "airplane", keywords: "plane flight travel" },
+    Emoji { emoji: "🛫", name: "airplane departure", keywords: "takeoff flight" },
+    Emoji { emoji: "🛬", name: "airplane arriving", keywords: "arrival landing flight" },
+    Emoji { emoji: "🚀", name: "rocket", keywords: "launch space ship" },
+    Emoji { emoji: "🛸", name: "flying saucer", keywords: "ufo" },
+    Emoji { emoji: "🚁", name: "helicopter", keywords: "" },
+    Emoji { emoji: "⛵", name: "sailboat", keywords: "boat" },
+    Emoji { emoji: "🚢", name: "ship", keywords: "cruise" },
+    Emoji { emoji: "⚓", name: "anchor", keywords: "" },
+    Emoji { emoji: "🗺️", name: "world map", keywords: "" },
+    Emoji { emoji: "🏠", name: "house building", keywords: "home" },
+    Emoji { emoji: "🏡", name: "house with garden", keywords: "home" },
+    Emoji { emoji: "🏢", name: "office building", keywords: "work" },
+    Emoji { emoji: "🏥", name: "hospital", keywords: "" },
+    Emoji { emoji: "🏦", name: "bank", keywords: "" },
+    Emoji { emoji: "🏫", name: "school", keywords: "" },
+    Emoji { emoji: "🏰", name: "european castle", keywords: "" },
+    Emoji { emoji: "⛪", name: "church", keywords: "" },
+    Emoji { emoji: "🗽", name: "statue of liberty", keywords: "" },
+    Emoji { emoji: "🗼", name: "tokyo tower", keywords: "" },
+    Emoji { emoji: "⛺", name: "tent", keywords: "camping" },
+    Emoji { emoji: "🌋", name: "volcano", keywords: "" },
+    Emoji { emoji: "🏖️", name: "beach with umbrella", keywords: "vacation" },
+    Emoji { emoji: "🏝️", name: "desert island", keywords: "" },
+    Emoji { emoji: "⌚", name: "watch", keywords: "time" },
+    Emoji { emoji: "📱", name: "mobile phone", keywords: "smartphone" },
+    Emoji { emoji: "💻", name: "personal computer", keywords: "laptop" },
+    Emoji { emoji: "⌨️", name: "keyboard", keywords: "" },
+    Emoji { emoji: "🖥️", name: "desktop computer", keywords: "monitor" },
+    Emoji { emoji: "🖨️", name: "printer", keywords: "" },
+    Emoji { emoji: "🖱️", name: "three button mouse", keywords: "computer" },
+    Emoji { emoji: "💾", name: "floppy disk", keywords: "save" },
+    Emoji { emoji: "💿", name: "optical disc", keywords: "cd" },
+    Emoji { emoji: "📷", name: "camera", keywords: "photo" },
+    Emoji { emoji: "📹", name: "video camera", keywords: "" },
+    Emoji { emoji: "🎥", name: "movie camera", keywords: "film" },
+    Emoji { emoji: "📺", name: "television", keywords: "tv" },
+    Emoji { emoji: "📻", name: "radio", keywords: "" },
+    Emoji { emoji: "🔋", name: "battery", keywords: "" },
+    Emoji { emoji: "🔌", name: "electric plug", keywords: "" },
+    Emoji { emoji: "💡", name: "electric light bulb", keywords: "idea" },
+    Emoji { emoji: "🔦", name: "electric torch", keywords: "flashlight" },
+    Emoji { emoji: "🕯️", name: "candle", keywords: "" },
+    Emoji { emoji: "💸", name: "money with wings", keywords: "flying spend" },
+    Emoji { emoji: "💵", name: "banknote with dollar sign", keywords: "money cash" },
+    Emoji { emoji: "💰", name: "money bag", keywords: "rich" },
+    Emoji { emoji: "💳", name: "credit card", keywords: "payment" },
+    Emoji { emoji: "💎", name: "gem stone", keywords: "diamond" },
+    Emoji { emoji: "🔧", name: "wrench", keywords: "tool fix" },
+    Emoji { emoji: "🔨", name: "hammer", keywords: "tool" },
+    Emoji { emoji: "🛠️", name: "hammer and wrench", keywords: "tools" },
+    Emoji { emoji: "⚙️", name: "gear", keywords: "settings cog" },
+    Emoji { emoji: "🔩", name: "nut and bolt", keywords: "" },
+    Emoji { emoji: "🧰", name: "toolbox", keywords: "" },
+    Emoji { emoji: "🧲", name: "magnet", keywords: "" },
+    Emoji { emoji: "🔬", name: "microscope", keywords: "science" },
+    Emoji { emoji: "🔭", name: "telescope", keywords: "" },
+    Emoji { emoji: "📡", name: "satellite antenna", keywords: "" },
+    Emoji { emoji: "💉", name: "syringe", keywords: "vaccine shot" },
+    Emoji { emoji: "💊", name: "pill", keywords: "medicine" },
+    Emoji { emoji: "🩹", name: "adhesive bandage", keywords: "" },
+    Emoji { emoji: "🚪", name: "door", keywords: "" },
+    Emoji { emoji: "🛏️", name: "bed", keywords: "sleep" },
+    Emoji { emoji: "🛋️", name: "couch and lamp", keywords: "sofa" },
+    Emoji { emoji: "🚽", name: "toilet", keywords: "" },
+    Emoji { emoji: "🚿", name: "shower", keywords: "" },
+    Emoji { emoji: "🛁", name: "bathtub", keywords: "bath" },
+    Emoji { emoji: "🧹", name: "broom", keywords: "clean" },
+    Emoji { emoji: "🧺", name: "basket", keywords: "laundry" },
+    Emoji { emoji: "🧻", name: "roll of paper", keywords: "toilet" },
+    Emoji { emoji: "🧼", name: "bar of soap", keywords: "" },
+    Emoji { emoji: "🛒", name: "shopping trolley", keywords: "cart" },
+    Emoji { emoji: "🚬", name: "smoking symbol", keywords: "cigarette" },
+    Emoji { emoji: "🔑", name: "key", keywords: "password" },
+    Emoji { emoji: "🗝️", name: "old key", keywords: "" },
+    Emoji { emoji: "🔒", name: "lock", keywords: "locked secure" },
+    Emoji { emoji: "🔓", name: "open lock", keywords: "unlocked" },
+    Emoji { emoji: "🔔", name: "bell", keywords: "notification" },
+    Emoji { emoji: "🔕", name: "bell with cancellation stroke", keywords: "mute silent" },
+    Emoji { emoji: "📢", name: "public address loudspeaker", keywords: "announcement" },
+    Emoji { emoji: "📣", name: "cheering megaphone", keywords: "" },
+    Emoji { emoji: "✉️", name: "envelope", keywords: "email mail" },
+    Emoji { emoji: "📧", name: "e-mail symbol", keywords: "email" },
+    Emoji { emoji: "📨", name: "incoming envelope", keywords: "mail" },
+    Emoji { emoji: "📩", name: "envelope with downwards arrow above", keywords: "mail" },
+    Emoji { emoji: "📤", name: "outbox tray", keywords: "sent" },
+    Emoji { emoji: "📥", name: "inbox tray", keywords: "" },
+    Emoji { emoji: "📦", name: "package", keywords: "box parcel shipping" },
+    Emoji { emoji: "📫", name: "closed mailbox with raised flag", keywords: "" },
+    Emoji { emoji: "📝", name: "memo", keywords: "note write" },
+    Emoji { emoji: "📄", name: "page facing up", keywords: "document file" },
+    Emoji { emoji: "📃", name: "page with curl", keywords: "document" },
+    Emoji { emoji: "📑", name: "bookmark tabs", keywords: "" },
+    Emoji { emoji: "📊", name: "bar chart", keywords: "graph stats" },
+    Emoji { emoji: "📈", name: "chart with upwards trend", keywords: "increasing growth up" },
+    Emoji { emoji: "📉", name: "chart with downwards trend", keywords: "decreasing down" },
+    Emoji { emoji: "📋", name: "clipboard", keywords: "" },
+    Emoji { emoji: "📅", name: "calendar", keywords: "date" },
+    Emoji { emoji: "📆", name: "tear-off calendar", keywords: "" },
+    Emoji { emoji: "🗓️", name: "spiral calendar pad", keywords: "" },
+    Emoji { emoji: "📁", name: "file folder", keywords: "directory" },
+    Emoji { emoji: "📂", name: "open file folder", keywords: "directory" },
+    Emoji { emoji: "🗂️", name: "card index dividers", keywords: "" },
+    Emoji { emoji: "🗒️", name: "spiral note pad", keywords: "notepad" },
+    Emoji { emoji: "📌", name: "pushpin", keywords: "pin" },
+    Emoji { emoji: "📍", name: "round pushpin", keywords: "location pin" },
+    Emoji { emoji: "📎", name: "paperclip", keywords: "attachment" },
+    Emoji { emoji: "🖇️", name: "linked paperclips", keywords: "" },
+    Emoji { emoji: "📏", name: "straight ruler", keywords: "" },
+    Emoji { emoji: "📐", name: "triangular ruler", keywords: "" },
+    Emoji { emoji: "✂️", name: "black scissors", keywords: "cut" },
+    Emoji { emoji: "🗑️", name: "wastebasket", keywords: "trash delete bin" },
+    Emoji { emoji: "🗃️", name: "card file box", keywords: "" },
+    Emoji { emoji: "🔍", name: "left-pointing magnifying glass", keywords: "search zoom find" },
+    Emoji { emoji: "🔎", name: "right-pointing magnifying glass", keywords: "search zoom find" },
+    Emoji { emoji: "🔗", name: "link symbol", keywords: "chain url" },
+    Emoji { emoji: "📚", name: "books", keywords: "library study" },
+    Emoji { emoji: "📖", name: "open book", keywords: "read" },
+    Emoji { emoji: "📓", name: "notebook", keywords: "" },
+    Emoji { emoji: "✏️", name: "pencil", keywords: "write edit" },
+    Emoji { emoji: "🖊️", name: "lower left ballpoint pen", keywords: "" },
+    Emoji { emoji: "🖌️", name: "lower left paintbrush", keywords: "" },
+    Emoji { emoji: "🖍️", name: "lower left crayon", keywords: "" },
+    Emoji { emoji: "⏰", name: "alarm clock", keywords: "wake" },
+    Emoji { emoji: "⏱️", name: "stopwatch", keywords: "timer" },
+    Emoji { emoji: "⏳", name: "hourglass with flowing sand", keywords: "wait loading time" },
+    Emoji { emoji: "⌛", name: "hourglass", keywords: "done time" },
+    Emoji { emoji: "🌐", name: "globe with meridians", keywords: "internet web" },
+    Emoji { emoji: "🏳️", name: "waving white flag", keywords: "surrender" },
+    Emoji { emoji: "🏴", name: "waving black flag", keywords: "" },
+    Emoji { emoji: "🚩", name: "triangular flag on post", keywords: "red warning" },
+    Emoji { emoji: "🏁", name: "chequered flag", keywords: "checkered finish race" },
+    Emoji { emoji: "🏳️‍🌈", name: "rainbow flag", keywords: "pride" },
+    Emoji { emoji: "✅", name: "white heavy check mark", keywords: "done yes ok complete" },
+    Emoji { emoji: "☑️", name: "ballot box with check", keywords: "checkbox" },
+    Emoji { emoji: "✔️", name: "heavy check mark", keywords: "done" },
+    Emoji { emoji: "❌", name: "cross mark", keywords: "no wrong cancel x" },
+    Emoji { emoji: "❎", name: "negative squared cross mark", keywords: "button" },
+    Emoji { emoji: "➕", name: "heavy plus sign", keywords: "add" },
+    Emoji { emoji: "➖", name: "heavy minus sign", keywords: "subtract" },
+    Emoji { emoji: "➗", name: "heavy division sign", keywords: "divide" },
+    Emoji { emoji: "✖️", name: "heavy multiplication x", keywords: "multiply times" },
+    Emoji { emoji: "❓", name: "black question mark ornament", keywords: "" },
+    Emoji { emoji: "❔", name: "white question mark ornament", keywords: "" },
+    Emoji { emoji: "❗", name: "heavy exclamation mark symbol", keywords: "warning important" },
+    Emoji { emoji: "❕", name: "white exclamation mark ornament", keywords: "" },
+    Emoji { emoji: "‼️", name: "double exclamation mark", keywords: "" },
+    Emoji { emoji: "⁉️", name: "exclamation question mark", keywords: "" },
+    Emoji { emoji: "⚠️", name: "warning sign", keywords: "caution alert" },
+    Emoji { emoji: "🚫", name: "no entry sign", keywords: "prohibited forbidden" },
+    Emoji { emoji: "⛔", name: "no entry", keywords: "stop" },
+    Emoji { emoji: "🛑", name: "octagonal sign", keywords: "stop" },
+    Emoji { emoji: "♻️", name: "black universal recycling symbol", keywords: "recycle" },
+    Emoji { emoji: "🔄", name: "anticlockwise downwards and upwards open circle arrows", keywords: "refresh reload sync" },
+    Emoji { emoji: "🔁", name: "clockwise rightwards and leftwards open circle arrows", keywords: "repeat loop" },
+    Emoji { emoji: "🔀", name: "twisted rightwards arrows", keywords: "shuffle random" },
+    Emoji { emoji: "▶️", name: "black right-pointing triangle", keywords: "play" },
+    Emoji { emoji: "⏸️", name: "double vertical bar", keywords: "pause" },
+    Emoji { emoji: "⏹️", name: "black square for stop", keywords: "" },
+    Emoji { emoji: "⏺️", name: "black circle for record", keywords: "" },
+    Emoji { emoji: "⏭️", name: "black right-pointing double triangle with vertical bar", keywords: "next track skip" },
+    Emoji { emoji: "⏮️", name: "black left-pointing double triangle with vertical bar", keywords: "previous track" },
+    Emoji { emoji: "🔊", name: "speaker with three sound waves", keywords: "loud volume" },
+    Emoji { emoji: "🔇", name: "speaker with cancellation stroke", keywords: "muted mute silent" },
+    Emoji { emoji: "🔅", name: "low brightness symbol", keywords: "dim" },
+    Emoji { emoji: "🔆", name: "high brightness symbol", keywords: "bright" },
+    Emoji { emoji: "📶", name: "antenna with bars", keywords: "signal wifi" },
+    Emoji { emoji: "🆗", name: "squared ok", keywords: "button" },
+    Emoji { emoji: "🆕", name: "squared new", keywords: "" },
+    Emoji { emoji: "🆓", name: "squared free", keywords: "" },
+    Emoji { emoji: "🆒", name: "squared cool", keywords: "" },
+    Emoji { emoji: "🆘", name: "squared sos", keywords: "help emergency" },
+    Emoji { emoji: "🔴", name: "large red circle", keywords: "" },
+    Emoji { emoji: "🟠", name: "large orange circle", keywords: "" },
+    Emoji { emoji: "🟡", name: "large yellow circle", keywords: "" },
+    Emoji { emoji: "🟢", name: "large green circle", keywords: "" },
+    Emoji { emoji: "🔵", name: "large blue circle", keywords: "" },
+    Emoji { emoji: "🟣", name: "large purple circle", keywords: "" },
+    Emoji { emoji: "⚫", name: "medium black circle", keywords: "" },
+    Emoji { emoji: "⚪", name: "medium white circle", keywords: "" },
+    Emoji { emoji: "🟥", name: "large red square", keywords: "" },
+    Emoji { emoji: "🟩", name: "large green square", keywords: "" },
+    Emoji { emoji: "🟦", name: "large blue square", keywords: "" },
+    Emoji { emoji: "⬛", name: "black large square", keywords: "" },
+    Emoji { emoji: "⬜", name: "white large square", keywords: "" },
+    Emoji { emoji: "🔶", name: "large orange diamond", keywords: "" },
+    Emoji { emoji: "🔷", name: "large blue diamond", keywords: "" },
+    Emoji { emoji: "➡️", name: "black rightwards arrow", keywords: "right" },
+    Emoji { emoji: "⬅️", name: "leftwards black arrow", keywords: "left" },
+    Emoji { emoji: "⬆️", name: "upwards black arrow", keywords: "up" },
+    Emoji { emoji: "⬇️", name: "downwards black arrow", keywords: "down" },
+    Emoji { emoji: "↩️", name: "leftwards arrow with hook", keywords: "return back" },
+    Emoji { emoji: "🔝", name: "top with upwards arrow above", keywords: "" },
+    Emoji { emoji: "🔜", name: "soon with rightwards arrow above", keywords: "" },
+    Emoji { emoji: "ℹ️", name: "information source", keywords: "info" },
+    Emoji { emoji: "©️", name: "copyright sign", keywords: "" },
+    Emoji { emoji: "®️", name: "registered sign", keywords: "" },
+    Emoji { emoji: "™️", name: "trade mark sign", keywords: "trademark tm" },
+    Emoji { emoji: "#️⃣", name: "keycap number sign", keywords: "hash" },
+    Emoji { emoji: "🔢", name: "input symbol for numbers", keywords: "1234" },
+];
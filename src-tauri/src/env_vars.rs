@@ -0,0 +1,90 @@
+//! `%VAR%` expansion for stored paths and command lines, shared by the
+//! launcher, custom entries and the index's missing-file check.
+
+use std::borrow::Cow;
+
+/// Most rounds of expansion for variables whose values contain variables.
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+/// Expand `%VAR%` references in a path from the environment, as stored by
+/// registry and custom entries (`%LOCALAPPDATA%\Programs\app.exe`).
+/// Unknown variables and stray percent signs are left as they are.
+pub fn expand_env_vars(path: &str) -> Cow<'_, str> {
+    expand_vars_with(path, |name| std::env::var(name).ok())
+}
+
+/// [`expand_env_vars`] with variables looked up through `lookup`. Values
+/// that themselves contain variables are expanded too, up to
+/// [`MAX_EXPANSION_DEPTH`] levels deep.
+fn expand_vars_with(input: &str, lookup: impl Fn(&str) -> Option<String>) -> Cow<'_, str> {
+    if !input.contains('%') {
+        return Cow::Borrowed(input);
+    }
+    let mut expanded = input.to_string();
+    for _ in 0..MAX_EXPANSION_DEPTH {
+        let next = substitute_vars(&expanded, &lookup);
+        if next == expanded {
+            break;
+        }
+        expanded = next;
+    }
+    Cow::Owned(expanded)
+}
+
+/// One round of substitution. When the text between two percent signs isn't
+/// a known variable, the first sign is kept literally and the second may
+/// still open a variable (`100% of %USERPROFILE%`).
+fn substitute_vars(input: &str, lookup: &impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('%')
+            .filter(|&end| end > 0)
+            .and_then(|end| Some((lookup(&after[..end])?, end)));
+        match value {
+            Some((value, end)) => {
+                out.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('%');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_vars() {
+        let vars = std::collections::HashMap::from([
+            ("LOCALAPPDATA", r"C:\Users\me\AppData\Local".to_string()),
+            ("APPS", r"%LOCALAPPDATA%\Programs".to_string()),
+            ("SELF", "%SELF%x".to_string()),
+        ]);
+        let expand = |input: &str| expand_vars_with(input, |name| vars.get(name).cloned()).into_owned();
+
+        assert_eq!(expand(r"%LOCALAPPDATA%\app.exe"), r"C:\Users\me\AppData\Local\app.exe");
+        assert_eq!(
+            expand("%LOCALAPPDATA%;%LOCALAPPDATA%"),
+            r"C:\Users\me\AppData\Local;C:\Users\me\AppData\Local"
+        );
+        // Nested: a value referring to another variable
+        assert_eq!(expand(r"%APPS%\code.exe"), r"C:\Users\me\AppData\Local\Programs\code.exe");
+        // Unknown variables and stray signs stay literal
+        assert_eq!(expand(r"%NOPE%\app.exe"), r"%NOPE%\app.exe");
+        assert_eq!(expand(r"C:\100%% done\%LOCALAPPDATA"), r"C:\100%% done\%LOCALAPPDATA");
+        assert_eq!(expand(r"C:\50% off\%LOCALAPPDATA%"), r"C:\50% off\C:\Users\me\AppData\Local");
+        // Self-reference stops after a bounded number of rounds
+        assert!(expand("%SELF%").starts_with("%SELF%x"));
+        assert!(matches!(expand_vars_with(r"C:\plain", |_| None), Cow::Borrowed(_)));
+    }
+}
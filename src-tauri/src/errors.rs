@@ -0,0 +1,105 @@
+//! Errors the user should know about, beyond a failed command's return
+//! value: `lib.rs` sends each one to the frontend as an `app-error` event,
+//! and the last [`MAX_RECENT_ERRORS`] are kept for `get_recent_errors`.
+
+use log::{error, warn};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+pub const MAX_RECENT_ERRORS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// One item or one attempt failed; the next try may well work.
+    Transient,
+    /// A feature stopped working until something is fixed or retried.
+    Error,
+    /// The app can't work properly, e.g. the database is corrupt.
+    Fatal,
+}
+
+impl Severity {
+    /// `default`, raised to `Fatal` if `message` reports a corrupt or
+    /// unreadable database.
+    pub fn for_message(message: &str, default: Severity) -> Severity {
+        const FATAL: &[&str] = &["database disk image is malformed", "file is not a database", "disk I/O error"];
+        if FATAL.iter().any(|pattern| message.contains(pattern)) {
+            Severity::Fatal
+        } else {
+            default
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppError {
+    /// The part of the app that failed, e.g. "indexer" or "hotkeys".
+    pub source: &'static str,
+    pub message: String,
+    pub severity: Severity,
+    /// Unix seconds.
+    pub timestamp: i64,
+}
+
+/// The most recent errors, oldest dropped first.
+#[derive(Default)]
+pub struct ErrorLog {
+    recent: Mutex<VecDeque<AppError>>,
+}
+
+impl ErrorLog {
+    /// Log an error and remember it. Returns it for the caller to emit.
+    pub fn record(&self, source: &'static str, severity: Severity, message: impl Into<String>) -> AppError {
+        let message = message.into();
+        match severity {
+            Severity::Transient => warn!("[{}] {}", source, message),
+            Severity::Error | Severity::Fatal => error!("[{}] {}", source, message),
+        }
+        let app_error = AppError {
+            source,
+            message,
+            severity,
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == MAX_RECENT_ERRORS {
+            recent.pop_front();
+        }
+        recent.push_back(app_error.clone());
+        app_error
+    }
+
+    /// Recent errors, newest first.
+    pub fn recent(&self) -> Vec<AppError> {
+        self.recent.lock().unwrap().iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_log_keeps_most_recent() {
+        let log = ErrorLog::default();
+        for i in 0..MAX_RECENT_ERRORS + 5 {
+            log.record("indexer", Severity::Transient, format!("error {}", i));
+        }
+        let recent = log.recent();
+        assert_eq!(recent.len(), MAX_RECENT_ERRORS);
+        assert_eq!(recent[0].message, format!("error {}", MAX_RECENT_ERRORS + 4));
+        assert_eq!(recent.last().unwrap().message, "error 5");
+    }
+
+    #[test]
+    fn test_corrupt_database_is_fatal() {
+        assert_eq!(
+            Severity::for_message("Remove missing failed: database disk image is malformed", Severity::Error),
+            Severity::Fatal
+        );
+        assert_eq!(Severity::for_message("Permission denied", Severity::Transient), Severity::Transient);
+    }
+}
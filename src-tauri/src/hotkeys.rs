@@ -0,0 +1,324 @@
+//! Activation hotkeys: the persisted list, and double-tap gestures on a bare
+//! modifier ("Double Ctrl").
+//!
+//! Regular shortcuts such as "Alt+Space" are registered with the
+//! global-shortcut plugin in `lib.rs`. The plugin can't bind a modifier on
+//! its own, so double-taps are detected here from a low-level keyboard hook
+//! (Windows only).
+
+use crate::db::Database;
+use log::{error, warn};
+use serde::Serialize;
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub const DEFAULT_HOTKEY: &str = "Ctrl+Space";
+const HOTKEYS_KEY: &str = "hotkeys";
+
+/// Most time between the two taps of a double-tap, in milliseconds.
+const DOUBLE_TAP_WINDOW_MS: u32 = 400;
+
+/// The persisted activation hotkeys, primary first. Defaults to
+/// [`DEFAULT_HOTKEY`] alone.
+pub fn stored_hotkeys(db: &Database) -> Vec<String> {
+    let stored = db.get_meta(HOTKEYS_KEY).ok().flatten();
+    let hotkeys: Vec<String> = match stored.as_deref().map(serde_json::from_str::<Vec<String>>) {
+        Some(Ok(hotkeys)) => hotkeys.into_iter().filter(|h| !h.trim().is_empty()).collect(),
+        Some(Err(e)) => {
+            warn!("Ignoring invalid hotkey list: {}", e);
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+    if hotkeys.is_empty() {
+        vec![DEFAULT_HOTKEY.to_string()]
+    } else {
+        hotkeys
+    }
+}
+
+pub fn save_hotkeys(db: &Database, hotkeys: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string(hotkeys).map_err(|e| e.to_string())?;
+    db.set_meta(HOTKEYS_KEY, &json)
+        .map_err(|e| format!("Failed to save hotkeys: {}", e))
+}
+
+/// Trim a hotkey list and drop blanks and duplicates. Fails if nothing is left.
+pub fn normalize_hotkeys(list: &[String]) -> Result<Vec<String>, String> {
+    let mut hotkeys: Vec<String> = Vec::new();
+    for hotkey in list.iter().map(|h| h.trim()).filter(|h| !h.is_empty()) {
+        if !hotkeys.iter().any(|h| h == hotkey) {
+            hotkeys.push(hotkey.to_string());
+        }
+    }
+    if hotkeys.is_empty() {
+        return Err("At least one hotkey is required".to_string());
+    }
+    Ok(hotkeys)
+}
+
+/// Whether one hotkey of a list could be registered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HotkeyStatus {
+    pub hotkey: String,
+    pub registered: bool,
+    pub error: Option<String>,
+}
+
+impl HotkeyStatus {
+    pub fn registered(hotkey: &str) -> Self {
+        HotkeyStatus {
+            hotkey: hotkey.to_string(),
+            registered: true,
+            error: None,
+        }
+    }
+
+    pub fn failed(hotkey: &str, error: String) -> Self {
+        HotkeyStatus {
+            hotkey: hotkey.to_string(),
+            registered: false,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+}
+
+impl Modifier {
+    /// Whether virtual-key code `vk` is this modifier, left, right or either.
+    fn matches(self, vk: u32) -> bool {
+        match self {
+            Modifier::Ctrl => matches!(vk, 0x11 | 0xA2 | 0xA3),
+            Modifier::Alt => matches!(vk, 0x12 | 0xA4 | 0xA5),
+            Modifier::Shift => matches!(vk, 0x10 | 0xA0 | 0xA1),
+        }
+    }
+}
+
+/// Parse a double-tap gesture: "Double Ctrl", "DoubleCtrl" or "Double+Ctrl"
+/// (any case; "Control" also works). Anything else is a regular shortcut.
+pub fn parse_double_tap(hotkey: &str) -> Option<Modifier> {
+    let lower = hotkey.trim().to_ascii_lowercase();
+    let key = lower.strip_prefix("double")?.trim_start_matches([' ', '+', '-']);
+    match key {
+        "ctrl" | "control" => Some(Modifier::Ctrl),
+        "alt" => Some(Modifier::Alt),
+        "shift" => Some(Modifier::Shift),
+        _ => None,
+    }
+}
+
+/// Detects two quick taps of one modifier from raw key events.
+///
+/// A tap is a press and release with no other key in between, held for no
+/// longer than the double-tap window, so holding Ctrl or using it in a
+/// chord (Ctrl+C) never counts. Key repeat while held is ignored.
+#[derive(Debug)]
+pub struct DoubleTapDetector {
+    modifier: Modifier,
+    pressed_at: Option<u32>,
+    last_tap: Option<u32>,
+}
+
+impl DoubleTapDetector {
+    pub fn new(modifier: Modifier) -> Self {
+        DoubleTapDetector {
+            modifier,
+            pressed_at: None,
+            last_tap: None,
+        }
+    }
+
+    /// Feed a key event; `time` is in milliseconds (it may wrap). Returns
+    /// true when this event completes a double-tap.
+    pub fn key_event(&mut self, vk: u32, down: bool, time: u32) -> bool {
+        if !self.modifier.matches(vk) {
+            if down {
+                self.pressed_at = None;
+                self.last_tap = None;
+            }
+            return false;
+        }
+        if down {
+            if self.pressed_at.is_none() {
+                self.pressed_at = Some(time);
+            }
+            return false;
+        }
+
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return false;
+        };
+        if time.wrapping_sub(pressed_at) > DOUBLE_TAP_WINDOW_MS {
+            self.last_tap = None;
+            return false;
+        }
+        match self.last_tap.take() {
+            Some(previous) if time.wrapping_sub(previous) <= DOUBLE_TAP_WINDOW_MS => true,
+            _ => {
+                self.last_tap = Some(time);
+                false
+            }
+        }
+    }
+}
+
+type Trigger = Arc<dyn Fn() + Send + Sync>;
+
+/// What the keyboard hook watches for; empty when no double-tap is set.
+static DOUBLE_TAPS: Mutex<Vec<DoubleTapDetector>> = Mutex::new(Vec::new());
+static ON_DOUBLE_TAP: Mutex<Option<Trigger>> = Mutex::new(None);
+/// Whether the hook was installed. It's installed on first use and stays
+/// for the life of the process; with no detectors it only passes keys on.
+static HOOK: OnceLock<Result<(), String>> = OnceLock::new();
+
+/// Watch for double-taps of `modifiers`, calling `on_trigger` (on a new
+/// thread) for each. An empty list turns detection off.
+pub fn set_double_taps(modifiers: &[Modifier], on_trigger: Trigger) -> Result<(), String> {
+    if !modifiers.is_empty() {
+        HOOK.get_or_init(install_hook).clone()?;
+    }
+    *ON_DOUBLE_TAP.lock().unwrap() = Some(on_trigger);
+    *DOUBLE_TAPS.lock().unwrap() = modifiers.iter().map(|&m| DoubleTapDetector::new(m)).collect();
+    Ok(())
+}
+
+/// Pass a key event from the hook to every detector.
+fn on_key_event(vk: u32, down: bool, time: u32) {
+    let mut triggered = false;
+    for detector in DOUBLE_TAPS.lock().unwrap().iter_mut() {
+        triggered |= detector.key_event(vk, down, time);
+    }
+    if !triggered {
+        return;
+    }
+    // The hook must return quickly or Windows drops it
+    if let Some(on_trigger) = ON_DOUBLE_TAP.lock().unwrap().clone() {
+        if let Err(e) = std::thread::Builder::new()
+            .name("double-tap".to_string())
+            .spawn(move || on_trigger())
+        {
+            error!("Failed to handle double-tap: {}", e);
+        }
+    }
+}
+
+/// Start a thread that installs a low-level keyboard hook and pumps its
+/// messages. Waits for the hook to be installed.
+#[cfg(windows)]
+fn install_hook() -> Result<(), String> {
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, SetWindowsHookExW, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, MSG,
+        WH_KEYBOARD_LL, WM_KEYDOWN, WM_SYSKEYDOWN,
+    };
+
+    unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 {
+            let event = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+            let down = matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+            on_key_event(event.vkCode, down, event.time);
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+
+    let (installed, result) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("keyboard-hook".to_string())
+        .spawn(move || unsafe {
+            match SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_proc), HINSTANCE::default(), 0) {
+                Ok(_) => {
+                    let _ = installed.send(Ok(()));
+                    let mut msg = MSG::default();
+                    while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {}
+                }
+                Err(e) => {
+                    let _ = installed.send(Err(format!("Failed to install keyboard hook: {}", e)));
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start keyboard hook: {}", e))?;
+    result
+        .recv()
+        .map_err(|_| "Keyboard hook thread exited".to_string())?
+}
+
+#[cfg(not(windows))]
+fn install_hook() -> Result<(), String> {
+    Err("Double-tap hotkeys are only supported on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const CTRL: u32 = 0xA2;
+    const C: u32 = 0x43;
+
+    /// Feed (vk, down, time) events and return the times that triggered.
+    fn triggers(events: &[(u32, bool, u32)]) -> Vec<u32> {
+        let mut detector = DoubleTapDetector::new(Modifier::Ctrl);
+        events
+            .iter()
+            .filter(|&&(vk, down, time)| detector.key_event(vk, down, time))
+            .map(|&(_, _, time)| time)
+            .collect()
+    }
+
+    #[test]
+    fn test_double_tap_detection() {
+        // Two quick taps
+        assert_eq!(triggers(&[(CTRL, true, 0), (CTRL, false, 80), (CTRL, true, 200), (CTRL, false, 280)]), [280]);
+        // Right Ctrl counts too, and key repeat is ignored
+        assert_eq!(
+            triggers(&[(0xA3, true, 0), (0xA3, true, 30), (0xA3, false, 60), (CTRL, true, 150), (CTRL, false, 200)]),
+            [200]
+        );
+        // Too slow
+        assert!(triggers(&[(CTRL, true, 0), (CTRL, false, 80), (CTRL, true, 600), (CTRL, false, 680)]).is_empty());
+        // Held, then tapped
+        assert!(triggers(&[(CTRL, true, 0), (CTRL, false, 900), (CTRL, true, 1000), (CTRL, false, 1050)]).is_empty());
+        // Ctrl+C in between
+        assert!(triggers(&[(CTRL, true, 0), (CTRL, false, 80), (CTRL, true, 150), (C, true, 170), (C, false, 200), (CTRL, false, 220)])
+            .is_empty());
+        // A third tap starts over rather than triggering again
+        assert_eq!(
+            triggers(&[(CTRL, true, 0), (CTRL, false, 50), (CTRL, true, 100), (CTRL, false, 150), (CTRL, true, 200), (CTRL, false, 250)]),
+            [150]
+        );
+        // The millisecond clock wrapping
+        assert_eq!(
+            triggers(&[(CTRL, true, u32::MAX - 100), (CTRL, false, u32::MAX - 50), (CTRL, true, 20), (CTRL, false, 60)]),
+            [60]
+        );
+    }
+
+    #[test]
+    fn test_parse_double_tap() {
+        assert_eq!(parse_double_tap("Double Ctrl"), Some(Modifier::Ctrl));
+        assert_eq!(parse_double_tap("doublecontrol"), Some(Modifier::Ctrl));
+        assert_eq!(parse_double_tap("Double+Shift"), Some(Modifier::Shift));
+        assert_eq!(parse_double_tap("Double Alt"), Some(Modifier::Alt));
+        assert_eq!(parse_double_tap("Alt+Space"), None);
+        assert_eq!(parse_double_tap("Double Space"), None);
+    }
+
+    #[test]
+    fn test_stored_hotkeys() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        assert_eq!(stored_hotkeys(&db), [DEFAULT_HOTKEY]);
+
+        let hotkeys = ["Ctrl+Space".to_string(), "Double Ctrl".to_string()];
+        save_hotkeys(&db, &hotkeys).unwrap();
+        assert_eq!(stored_hotkeys(&db), hotkeys);
+
+        db.set_meta(HOTKEYS_KEY, "not json").unwrap();
+        assert_eq!(stored_hotkeys(&db), [DEFAULT_HOTKEY]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Translations of what the backend shows by itself: the tray menu and
+//! tooltip, notifications, and the launch errors the frontend displays.
+//!
+//! Tables are embedded per [`Locale`] and keyed by dotted names; a key
+//! missing from a locale falls back to English. Placeholders are written
+//! `{name}` and filled in by [`t_with`].
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+    Hi,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::De, Locale::Es, Locale::Hi];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Es => "es",
+            Locale::Hi => "hi",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Locale::ALL.into_iter().find(|locale| locale.as_str() == value)
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+            Locale::Es => ES,
+            Locale::Hi => HI,
+        }
+    }
+}
+
+/// Index of the current locale in [`Locale::ALL`].
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn current() -> Locale {
+    Locale::ALL[CURRENT.load(Ordering::Relaxed) as usize]
+}
+
+/// Use `locale` for everything translated from now on.
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL.iter().position(|&l| l == locale).unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+/// `key` in the current locale.
+pub fn t(key: &'static str) -> &'static str {
+    translate(current(), key)
+}
+
+/// `key` in the current locale with its `{name}` placeholders filled in.
+pub fn t_with(key: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    fill(t(key), args)
+}
+
+/// `key` in `locale`, else in English, else the key itself so a typo shows
+/// up instead of an empty label.
+fn translate(locale: Locale, key: &'static str) -> &'static str {
+    let lookup = |table: &'static [(&'static str, &'static str)]| {
+        table.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+    };
+    lookup(locale.table())
+        .or_else(|| lookup(EN))
+        .unwrap_or(key)
+}
+
+/// `count` with its thousands grouped the current locale's way, e.g.
+/// "182,340" in English and "182.340" in German.
+pub fn format_count(count: i64) -> String {
+    format_count_in(current(), count)
+}
+
+fn format_count_in(locale: Locale, count: i64) -> String {
+    let separator = match locale {
+        Locale::De | Locale::Es => '.',
+        Locale::En | Locale::Hi => ',',
+    };
+    let digits = count.unsigned_abs().to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if count < 0 {
+        grouped.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+fn fill(text: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    args.iter()
+        .fold(text.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), &value.to_string()))
+}
+
+const EN: &[(&str, &str)] = &[
+    ("tray.show", "Show Launcher ({hotkey})"),
+    ("tray.rebuild", "Rebuild Index"),
+    ("tray.autostart", "Start with Windows"),
+    ("tray.logs", "Open Logs"),
+    ("tray.updates", "Check for Updates"),
+    ("tray.exit", "Exit"),
+    ("tray.tooltip", "AnCheck - Quick Launcher"),
+    ("tray.index_status", "{count} files · indexed {when}"),
+    ("tray.not_indexed", "{count} files · not indexed yet"),
+    ("notify.index_failed", "Indexing failed"),
+    ("notify.files_removed", "{count} files left the index"),
+    ("notify.files_removed_body", "They no longer exist where they were indexed. Was a drive disconnected?"),
+    ("notify.slow_index", "Indexing took a while"),
+    ("notify.slow_index_body", "The background index pass took {minutes} minutes for {count} files"),
+    ("notify.relaunched", "Opened {name}"),
+    ("notify.relaunch_failed", "Couldn't open the last item"),
+    ("notify.quick_slot_empty", "Quick slot {slot} is empty"),
+    ("notify.quick_slot_empty_body", "Nothing is bound to {hotkey} yet"),
+    ("notify.quick_slot_failed", "Couldn't open quick slot {slot}"),
+    ("notify.synced_db", "The index is in a {client} folder"),
+    ("notify.synced_db_body", "Syncing can corrupt it. Move AnCheck's data folder out of {client} or exclude it from syncing."),
+    ("error.file_not_found", "File not found: {file}"),
+    ("error.permission_denied", "Permission denied: '{file}'. This file may require administrator privileges."),
+    ("error.no_association", "No application is associated with '{file}'"),
+    ("error.in_use", "'{file}' is in use by another program"),
+    ("error.bad_format", "'{file}' is not a valid Windows program"),
+    ("error.out_of_memory", "Not enough memory to open '{file}'"),
+    ("error.open_failed", "Failed to open '{file}' (error {code})"),
+    ("error.elevation_cancelled", "Cancelled: administrator permission was not granted"),
+    ("error.delete_denied", "Permission denied: can't delete '{file}'"),
+    ("error.delete_in_use", "'{file}' is in use by another program; close it and try again"),
+    ("error.recycle_failed", "Failed to move '{file}' to the Recycle Bin (error {code})"),
+    ("error.not_deleted", "Cancelled: '{file}' was not deleted"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("tray.show", "Launcher anzeigen ({hotkey})"),
+    ("tray.rebuild", "Index neu aufbauen"),
+    ("tray.autostart", "Mit Windows starten"),
+    ("tray.logs", "Protokolle öffnen"),
+    ("tray.updates", "Nach Updates suchen"),
+    ("tray.exit", "Beenden"),
+    ("tray.tooltip", "AnCheck - Schnellstarter"),
+    ("tray.index_status", "{count} Dateien · indiziert {when}"),
+    ("tray.not_indexed", "{count} Dateien · noch nicht indiziert"),
+    ("notify.index_failed", "Indizierung fehlgeschlagen"),
+    ("notify.files_removed", "{count} Dateien wurden aus dem Index entfernt"),
+    ("notify.files_removed_body", "Sie existieren nicht mehr am indizierten Ort. Wurde ein Laufwerk getrennt?"),
+    ("notify.slow_index", "Die Indizierung hat lange gedauert"),
+    ("notify.slow_index_body", "Der Indexdurchlauf im Hintergrund dauerte {minutes} Minuten für {count} Dateien"),
+    ("notify.relaunched", "{name} geöffnet"),
+    ("notify.relaunch_failed", "Das letzte Element konnte nicht geöffnet werden"),
+    ("notify.quick_slot_empty", "Schnellplatz {slot} ist leer"),
+    ("notify.quick_slot_empty_body", "{hotkey} ist noch nichts zugewiesen"),
+    ("notify.quick_slot_failed", "Schnellplatz {slot} konnte nicht geöffnet werden"),
+    ("notify.synced_db", "Der Index liegt in einem {client}-Ordner"),
+    ("notify.synced_db_body", "Die Synchronisierung kann ihn beschädigen. Verschiebe den Datenordner von AnCheck aus {client} oder schließe ihn von der Synchronisierung aus."),
+    ("error.file_not_found", "Datei nicht gefunden: {file}"),
+    ("error.permission_denied", "Zugriff verweigert: '{file}'. Diese Datei erfordert möglicherweise Administratorrechte."),
+    ("error.no_association", "Keine Anwendung ist mit '{file}' verknüpft"),
+    ("error.in_use", "'{file}' wird von einem anderen Programm verwendet"),
+    ("error.bad_format", "'{file}' ist kein gültiges Windows-Programm"),
+    ("error.out_of_memory", "Nicht genügend Arbeitsspeicher, um '{file}' zu öffnen"),
+    ("error.open_failed", "'{file}' konnte nicht geöffnet werden (Fehler {code})"),
+    ("error.elevation_cancelled", "Abgebrochen: Administratorrechte wurden nicht erteilt"),
+    ("error.delete_denied", "Zugriff verweigert: '{file}' kann nicht gelöscht werden"),
+    ("error.delete_in_use", "'{file}' wird von einem anderen Programm verwendet; schließen Sie es und versuchen Sie es erneut"),
+    ("error.recycle_failed", "'{file}' konnte nicht in den Papierkorb verschoben werden (Fehler {code})"),
+    ("error.not_deleted", "Abgebrochen: '{file}' wurde nicht gelöscht"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("tray.show", "Mostrar lanzador ({hotkey})"),
+    ("tray.rebuild", "Reconstruir índice"),
+    ("tray.autostart", "Iniciar con Windows"),
+    ("tray.logs", "Abrir registros"),
+    ("tray.updates", "Buscar actualizaciones"),
+    ("tray.exit", "Salir"),
+    ("tray.tooltip", "AnCheck - Lanzador rápido"),
+    ("tray.index_status", "{count} archivos · indexado {when}"),
+    ("tray.not_indexed", "{count} archivos · aún sin indexar"),
+    ("notify.index_failed", "Error al indexar"),
+    ("notify.files_removed", "{count} archivos salieron del índice"),
+    ("notify.files_removed_body", "Ya no existen donde se indexaron. ¿Se desconectó una unidad?"),
+    ("notify.slow_index", "La indexación tardó bastante"),
+    ("notify.slow_index_body", "La indexación en segundo plano tardó {minutes} minutos para {count} archivos"),
+    ("notify.relaunched", "Se abrió {name}"),
+    ("notify.relaunch_failed", "No se pudo abrir el último elemento"),
+    ("notify.quick_slot_empty", "La ranura rápida {slot} está vacía"),
+    ("notify.quick_slot_empty_body", "Todavía no hay nada asignado a {hotkey}"),
+    ("notify.quick_slot_failed", "No se pudo abrir la ranura rápida {slot}"),
+    ("notify.synced_db", "El índice está en una carpeta de {client}"),
+    ("notify.synced_db_body", "La sincronización puede dañarlo. Mueve la carpeta de datos de AnCheck fuera de {client} o exclúyela de la sincronización."),
+    ("error.file_not_found", "Archivo no encontrado: {file}"),
+    ("error.permission_denied", "Permiso denegado: '{file}'. Es posible que este archivo requiera privilegios de administrador."),
+    ("error.no_association", "No hay ninguna aplicación asociada con '{file}'"),
+    ("error.in_use", "'{file}' está en uso por otro programa"),
+    ("error.bad_format", "'{file}' no es un programa de Windows válido"),
+    ("error.out_of_memory", "No hay memoria suficiente para abrir '{file}'"),
+    ("error.open_failed", "No se pudo abrir '{file}' (error {code})"),
+    ("error.elevation_cancelled", "Cancelado: no se concedió el permiso de administrador"),
+    ("error.delete_denied", "Permiso denegado: no se puede eliminar '{file}'"),
+    ("error.delete_in_use", "'{file}' está en uso por otro programa; ciérrelo e inténtelo de nuevo"),
+    ("error.recycle_failed", "No se pudo mover '{file}' a la Papelera de reciclaje (error {code})"),
+    ("error.not_deleted", "Cancelado: '{file}' no se eliminó"),
+];
+
+const HI: &[(&str, &str)] = &[
+    ("tray.show", "लॉन्चर दिखाएँ ({hotkey})"),
+    ("tray.rebuild", "इंडेक्स फिर से बनाएँ"),
+    ("tray.autostart", "Windows के साथ शुरू करें"),
+    ("tray.logs", "लॉग खोलें"),
+    ("tray.updates", "अपडेट की जाँच करें"),
+    ("tray.exit", "बाहर निकलें"),
+    ("tray.tooltip", "AnCheck - क्विक लॉन्चर"),
+    ("tray.index_status", "{count} फ़ाइलें · इंडेक्स किया गया {when}"),
+    ("tray.not_indexed", "{count} फ़ाइलें · अभी इंडेक्स नहीं हुआ"),
+    ("notify.index_failed", "इंडेक्सिंग विफल रही"),
+    ("notify.files_removed", "{count} फ़ाइलें इंडेक्स से हटा दी गईं"),
+    ("notify.files_removed_body", "वे अब वहाँ मौजूद नहीं हैं जहाँ उन्हें इंडेक्स किया गया था। क्या कोई ड्राइव हटाई गई?"),
+    ("notify.slow_index", "इंडेक्सिंग में काफ़ी समय लगा"),
+    ("notify.slow_index_body", "बैकग्राउंड इंडेक्सिंग में {count} फ़ाइलों के लिए {minutes} मिनट लगे"),
+    ("notify.relaunched", "{name} खोला गया"),
+    ("notify.relaunch_failed", "पिछला आइटम नहीं खुल सका"),
+    ("notify.quick_slot_empty", "क्विक स्लॉट {slot} खाली है"),
+    ("notify.quick_slot_empty_body", "{hotkey} पर अभी कुछ नहीं जोड़ा गया है"),
+    ("notify.quick_slot_failed", "क्विक स्लॉट {slot} नहीं खुल सका"),
+    ("notify.synced_db", "इंडेक्स एक {client} फ़ोल्डर में है"),
+    ("notify.synced_db_body", "सिंक करने से यह खराब हो सकता है। AnCheck का डेटा फ़ोल्डर {client} से बाहर ले जाएँ या उसे सिंक से बाहर रखें।"),
+    ("error.file_not_found", "फ़ाइल नहीं मिली: {file}"),
+    ("error.permission_denied", "अनुमति नहीं है: '{file}'। इस फ़ाइल के लिए व्यवस्थापक अधिकार चाहिए हो सकते हैं।"),
+    ("error.no_association", "'{file}' से कोई ऐप्लिकेशन जुड़ा नहीं है"),
+    ("error.in_use", "'{file}' किसी दूसरे प्रोग्राम द्वारा उपयोग में है"),
+    ("error.bad_format", "'{file}' एक मान्य Windows प्रोग्राम नहीं है"),
+    ("error.out_of_memory", "'{file}' खोलने के लिए पर्याप्त मेमोरी नहीं है"),
+    ("error.open_failed", "'{file}' नहीं खुल सकी (त्रुटि {code})"),
+    ("error.elevation_cancelled", "रद्द किया गया: व्यवस्थापक अनुमति नहीं दी गई"),
+    ("error.delete_denied", "अनुमति नहीं है: '{file}' को हटाया नहीं जा सकता"),
+    ("error.delete_in_use", "'{file}' किसी दूसरे प्रोग्राम द्वारा उपयोग में है; उसे बंद करके फिर से कोशिश करें"),
+    ("error.recycle_failed", "'{file}' को रीसायकल बिन में नहीं ले जाया जा सका (त्रुटि {code})"),
+    ("error.not_deleted", "रद्द किया गया: '{file}' को हटाया नहीं गया"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_every_locale_has_every_key() {
+        for locale in Locale::ALL {
+            let table = locale.table();
+            for (key, english) in EN {
+                let text = table.iter().find(|(k, _)| k == key).map(|(_, text)| *text);
+                let text = text.unwrap_or_else(|| panic!("{} is missing {}", locale.as_str(), key));
+                assert_eq!(placeholders(text), placeholders(english), "{} {}", locale.as_str(), key);
+            }
+            for (key, _) in table {
+                assert!(EN.iter().any(|(k, _)| k == key), "{} has unknown key {}", locale.as_str(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_falls_back_to_english() {
+        assert_eq!(translate(Locale::De, "tray.exit"), "Beenden");
+        assert_eq!(translate(Locale::Hi, "no.such.key"), "no.such.key");
+        assert_eq!(
+            fill(translate(Locale::Es, "error.open_failed"), &[("file", &"a.txt"), ("code", &31)]),
+            "No se pudo abrir 'a.txt' (error 31)"
+        );
+        assert_eq!(Locale::parse("hi"), Some(Locale::Hi));
+        assert_eq!(Locale::parse("fr"), None);
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count_in(Locale::En, 182_340), "182,340");
+        assert_eq!(format_count_in(Locale::De, 1_234_567), "1.234.567");
+        assert_eq!(format_count_in(Locale::En, 999), "999");
+        assert_eq!(format_count_in(Locale::En, -4_000), "-4,000");
+        assert_eq!(format_count_in(Locale::Es, 0), "0");
+    }
+}
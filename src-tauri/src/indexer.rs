@@ -1,12 +1,17 @@
+use crate::bookmarks::BookmarksProvider;
 use crate::db::Database;
+use crate::system_state::SkipReason;
 use log::{error, info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Instant, SystemTime};
 use walkdir::WalkDir;
 
 /// Determines the file_type category from extension and path context.
-fn classify_file(extension: &str, filepath: &str) -> String {
+pub(crate) fn classify_file(extension: &str, filepath: &str) -> String {
     let ext_lower = extension.to_lowercase();
     let path_lower = filepath.to_lowercase();
 
@@ -60,41 +65,160 @@ fn classify_file(extension: &str, filepath: &str) -> String {
     "other".to_string()
 }
 
-/// Collects all directories that should be indexed.
-fn get_index_directories() -> Vec<PathBuf> {
-    let mut dirs = Vec::new();
+/// Collects all directories that should be indexed: the ones of
+/// [`index_root_candidates`] that exist.
+pub fn get_index_directories(db: &Database) -> Vec<PathBuf> {
+    existing_directories(index_root_candidates(db))
+}
 
-    // User profile directories
-    if let Some(home) = dirs::home_dir() {
-        dirs.push(home.join("Desktop"));
-        dirs.push(home.join("Documents"));
-        dirs.push(home.join("Downloads"));
-    }
+/// The `index_roots` setting, or the default directories when it's empty,
+/// each once, whether or not they exist right now.
+pub fn index_root_candidates(db: &Database) -> Vec<PathBuf> {
+    let roots = crate::settings::index_roots(db);
+    let mut dirs = if roots.is_empty() {
+        default_directory_candidates()
+    } else {
+        roots.into_iter().map(PathBuf::from).collect()
+    };
+    let mut seen = HashSet::new();
+    dirs.retain(|d| seen.insert(d.clone()));
+    dirs
+}
 
-    // Start Menu (both user and system)
-    if let Some(data) = dirs::data_dir() {
-        // %APPDATA%\Microsoft\Windows\Start Menu
-        dirs.push(data.join("Microsoft").join("Windows").join("Start Menu"));
-    }
-    // System-wide Start Menu
-    let system_start_menu = PathBuf::from(r"C:\ProgramData\Microsoft\Windows\Start Menu");
-    if system_start_menu.exists() {
-        dirs.push(system_start_menu);
-    }
+/// The user folders and the app directories, indexed unless other roots
+/// were chosen.
+pub fn default_index_directories() -> Vec<PathBuf> {
+    existing_directories(default_directory_candidates())
+}
 
-    // Program Files
+fn default_directory_candidates() -> Vec<PathBuf> {
+    let mut dirs = user_folders();
+    dirs.extend(app_directory_candidates());
+    dirs
+}
+
+/// Where installed apps are found: both Start Menus and Program Files.
+pub fn app_directories() -> Vec<PathBuf> {
+    existing_directories(app_directory_candidates())
+}
+
+fn app_directory_candidates() -> Vec<PathBuf> {
+    let mut dirs = start_menu_candidates();
     if let Ok(pf) = std::env::var("ProgramFiles") {
         dirs.push(PathBuf::from(pf));
     }
     if let Ok(pf86) = std::env::var("ProgramFiles(x86)") {
         dirs.push(PathBuf::from(pf86));
     }
+    dirs
+}
+
+/// `dirs` that actually exist, each once.
+pub fn existing_directories(mut dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    dirs.retain(|d| d.exists() && seen.insert(d.clone()));
+    dirs
+}
+
+/// The user's Desktop, Documents and Downloads where Windows says they are.
+/// `dirs` asks the Known Folder API (`SHGetKnownFolderPath`), which follows
+/// folder redirection, OneDrive backup and moved or localized folders; if
+/// that fails, the folder of the usual name in the profile is used.
+pub fn user_folders() -> Vec<PathBuf> {
+    let mut folders = document_folders();
+    folders.extend(user_folder(dirs::download_dir(), "Downloads"));
+    folders
+}
 
-    // Only keep directories that actually exist
+/// The user's Desktop and Documents (see [`user_folders`]).
+pub fn document_folders() -> Vec<PathBuf> {
+    [
+        user_folder(dirs::desktop_dir(), "Desktop"),
+        user_folder(dirs::document_dir(), "Documents"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// `known`, or else the folder called `name` in the profile.
+fn user_folder(known: Option<PathBuf>, name: &str) -> Option<PathBuf> {
+    known.or_else(|| dirs::home_dir().map(|home| home.join(name)))
+}
+
+/// The per-user and the system-wide Start Menu, in that order, where they
+/// exist.
+pub fn start_menu_directories() -> Vec<PathBuf> {
+    let mut dirs = start_menu_candidates();
     dirs.retain(|d| d.exists());
     dirs
 }
 
+fn start_menu_candidates() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(data) = dirs::data_dir() {
+        // %APPDATA%\Microsoft\Windows\Start Menu
+        dirs.push(data.join("Microsoft").join("Windows").join("Start Menu"));
+    }
+    dirs.push(PathBuf::from(r"C:\ProgramData\Microsoft\Windows\Start Menu"));
+    dirs
+}
+
+/// Merge entries that are the same app in both Start Menus into one, so
+/// its launches aren't split between two results. Returns how many pairs
+/// were merged. Also cleans up pairs indexed before merging existed.
+pub fn merge_start_menu_duplicates(db: &Database) -> Result<usize, String> {
+    let dirs = start_menu_directories();
+    let [user, system] = dirs.as_slice() else {
+        return Ok(0);
+    };
+    merge_duplicates_between(db, user, system)
+}
+
+/// Merge each entry under `user` into its duplicate under `system`, or the
+/// other way round when the user copy was launched more often.
+fn merge_duplicates_between(db: &Database, user: &Path, system: &Path) -> Result<usize, String> {
+    let entries_under = |dir: &Path| {
+        db.entries_under(&dir.to_string_lossy())
+            .map_err(|e| format!("Failed to read '{}': {}", dir.display(), e))
+    };
+    let mut system_entries: HashMap<String, _> = entries_under(system)?
+        .into_iter()
+        .map(|entry| (duplicate_key(&entry), entry))
+        .collect();
+
+    let mut merged = 0usize;
+    for entry in entries_under(user)? {
+        let Some(other) = system_entries.remove(&duplicate_key(&entry)) else {
+            continue;
+        };
+        let (keep, duplicate) = if entry.click_count > other.click_count {
+            (entry, other)
+        } else {
+            (other, entry)
+        };
+        if db
+            .merge_duplicate(&keep.filepath, &duplicate.filepath)
+            .map_err(|e| format!("Failed to merge '{}': {}", duplicate.filepath, e))?
+        {
+            info!("Merged duplicate Start Menu entry {} into {}", duplicate.filepath, keep.filepath);
+            merged += 1;
+        }
+    }
+    Ok(merged)
+}
+
+/// What two copies of one Start Menu entry share: the file a shortcut
+/// points at or, when that can't be read, the file name and size.
+fn duplicate_key(entry: &crate::db::FileEntry) -> String {
+    if entry.extension.eq_ignore_ascii_case("lnk") {
+        if let Ok(target) = crate::launcher::shortcut_target(Path::new(&entry.filepath)) {
+            return format!("target:{}", target.to_string_lossy().to_lowercase());
+        }
+    }
+    format!("name:{}:{}", entry.filename.to_lowercase(), entry.file_size)
+}
+
 /// Maximum directory depth to prevent scanning deeply nested node_modules etc.
 const MAX_DEPTH: usize = 6;
 
@@ -120,38 +244,417 @@ fn should_skip_dir(name: &str) -> bool {
     SKIP_DIRS.iter().any(|&skip| lower == skip)
 }
 
-/// Performs a full index scan of all configured directories.
-/// Returns the number of files indexed.
-pub fn full_index(db: &Arc<Database>) -> Result<usize, String> {
-    let directories = get_index_directories();
-    info!("Starting full index of {} directories", directories.len());
+/// Whether a row's file has one of the `excluded` extensions (see
+/// `settings::excluded_extensions`). Folders are never excluded.
+fn is_excluded(entry: &NewEntry, excluded: &[String]) -> bool {
+    entry.file_type != "folder"
+        && !entry.extension.is_empty()
+        && excluded.iter().any(|ext| ext.eq_ignore_ascii_case(&entry.extension))
+}
 
-    let mut total_indexed = 0usize;
-    let mut batch: Vec<(String, String, String, i64, i64, String)> = Vec::with_capacity(1000);
+/// A row for `Database::upsert_files_batch`: filename, path, extension,
+/// size, modification time and file type.
+pub(crate) type FileRow = (String, String, String, i64, i64, String);
+
+fn new_entry(path: &Path, metadata: &std::fs::Metadata) -> Option<NewEntry> {
+    let filepath = path.to_string_lossy().to_string();
+    let filename = path.file_name()?.to_string_lossy().to_string();
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let file_size = if metadata.is_file() {
+        metadata.len() as i64
+    } else {
+        0
+    };
+
+    let modified_at = unix_seconds(metadata.modified()).unwrap_or(0);
+
+    let file_type = classify_file(&extension, &filepath);
+    Some(NewEntry {
+        filename,
+        filepath,
+        extension,
+        file_size,
+        modified_at,
+        file_type,
+        child_count: None,
+    })
+}
+
+/// When a file was created, last written and last read, in Unix seconds;
+/// each is `None` where the filesystem doesn't keep it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct FileTimes {
+    pub created_at: Option<i64>,
+    pub modified_at: Option<i64>,
+    pub accessed_at: Option<i64>,
+}
+
+pub fn file_times(metadata: &std::fs::Metadata) -> FileTimes {
+    FileTimes {
+        created_at: unix_seconds(metadata.created()),
+        modified_at: unix_seconds(metadata.modified()),
+        accessed_at: unix_seconds(metadata.accessed()),
+    }
+}
+
+fn unix_seconds(time: std::io::Result<SystemTime>) -> Option<i64> {
+    let since_epoch = time.ok()?.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_secs() as i64)
+}
+
+/// Index `root` and everything under it right away, e.g. a freshly
+/// extracted archive, if it lies inside an indexed directory (otherwise the
+/// indexer would never refresh it). Returns the number of entries upserted.
+pub fn index_tree(db: &Database, root: &Path) -> Result<usize, String> {
+    let Some(base) = get_index_directories(db).into_iter().find(|dir| root.starts_with(dir)) else {
+        return Ok(0);
+    };
+    let depth_left = MAX_DEPTH.saturating_sub(root.strip_prefix(&base).map_or(0, |p| p.components().count()));
+    let excluded = crate::settings::excluded_extensions(db);
+
+    let rows: Vec<FileRow> = WalkDir::new(root)
+        .max_depth(depth_left)
+        .into_iter()
+        .filter_entry(should_walk)
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| new_entry(entry.path(), &entry.metadata().ok()?))
+        .filter(|entry| !is_excluded(entry, &excluded))
+        .map(FileRow::from)
+        .collect();
+    db.upsert_files_batch(&rows)
+        .map_err(|e| format!("Failed to index '{}': {}", root.display(), e))?;
+    Ok(rows.len())
+}
+
+/// Whether the walk should visit `entry`: hidden and skipped directories
+/// are left out along with everything inside them.
+fn should_walk(entry: &walkdir::DirEntry) -> bool {
+    !(entry.file_type().is_dir() && is_skipped_dir_name(entry.file_name()))
+}
+
+/// A hidden (dot) or skipped directory name (see [`SKIP_DIRS`]).
+fn is_skipped_dir_name(name: &std::ffi::OsStr) -> bool {
+    name.to_str().is_some_and(|name| name.starts_with('.') || should_skip_dir(name))
+}
+
+/// How often [`HotFolders::check`] is meant to run.
+pub const HOT_FOLDER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Desktop and Downloads, where new files usually land, watched between
+/// index passes so a file downloaded a moment ago is already searchable.
+/// Only the top level of each is read, and only when the folder changed,
+/// so a quiet check costs one `metadata` call per folder.
+pub struct HotFolders {
+    folders: Vec<HotFolder>,
+}
+
+struct HotFolder {
+    path: PathBuf,
+    /// The folder's modification time at the last listing, which moves
+    /// whenever an entry is added, removed or renamed.
+    modified: Option<SystemTime>,
+    /// Modification time of each entry at the last listing; `None` before
+    /// the first one.
+    entries: Option<HashMap<std::ffi::OsString, Option<SystemTime>>>,
+}
+
+impl Default for HotFolders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HotFolders {
+    pub fn new() -> Self {
+        let folders = [
+            user_folder(dirs::desktop_dir(), "Desktop"),
+            user_folder(dirs::download_dir(), "Downloads"),
+        ];
+        Self::with_folders(folders.into_iter().flatten().collect())
+    }
+
+    fn with_folders(paths: Vec<PathBuf>) -> Self {
+        let folders = paths
+            .into_iter()
+            .map(|path| HotFolder {
+                path,
+                modified: None,
+                entries: None,
+            })
+            .collect();
+        Self { folders }
+    }
+
+    /// Index what was added to or changed in the folders since the last
+    /// check, and drop what was removed. The first check only takes stock,
+    /// leaving what's already there to the index passes. Folders outside
+    /// `roots` (the `index_roots` setting; empty for the defaults) are
+    /// left alone. Returns how many rows were written or removed.
+    pub fn check(&mut self, db: &Database, roots: &[String]) -> usize {
+        let mut changed = 0;
+        for folder in &mut self.folders {
+            if !roots.is_empty() && !roots.iter().any(|root| folder.path.starts_with(root)) {
+                continue;
+            }
+            let modified = std::fs::metadata(&folder.path).and_then(|m| m.modified()).ok();
+            // A second change within the filesystem's timestamp resolution
+            // wouldn't move the time again, so a fresh one is always listed
+            let just_modified = modified
+                .and_then(|m| m.elapsed().ok())
+                .is_some_and(|age| age < HOT_FOLDER_INTERVAL * 2);
+            if folder.entries.is_some() && modified == folder.modified && !just_modified {
+                continue;
+            }
+            folder.modified = modified;
+            match folder.list(db) {
+                Ok(count) => changed += count,
+                Err(e) => warn!("Failed to check '{}': {}", folder.path.display(), e),
+            }
+        }
+        changed
+    }
+}
+
+impl HotFolder {
+    fn list(&mut self, db: &Database) -> Result<usize, String> {
+        let read = std::fs::read_dir(&self.path).map_err(|e| e.to_string())?;
+        let excluded = crate::settings::excluded_extensions(db);
+        let mut current = HashMap::new();
+        let mut rows: Vec<FileRow> = Vec::new();
+        for entry in read.filter_map(|entry| entry.ok()) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let name = entry.file_name();
+            if metadata.is_dir() && is_skipped_dir_name(&name) {
+                continue;
+            }
+            let modified = metadata.modified().ok();
+            let seen = self.entries.as_ref().map(|entries| entries.get(&name));
+            if matches!(seen, Some(previous) if previous != Some(&modified)) {
+                if let Some(new) = new_entry(&entry.path(), &metadata).filter(|new| !is_excluded(new, &excluded)) {
+                    rows.push(new.into());
+                }
+            }
+            current.insert(name, modified);
+        }
+
+        let mut changed = rows.len();
+        if let Some(previous) = self.entries.take() {
+            db.upsert_files_batch(&rows).map_err(|e| e.to_string())?;
+            for name in previous.keys().filter(|name| !current.contains_key(*name)) {
+                let path = self.path.join(name);
+                changed += db.remove_path(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+            }
+        }
+        self.entries = Some(current);
+        Ok(changed)
+    }
+}
+
+/// An entry found by a [`Provider`], on its way into the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewEntry {
+    pub filename: String,
+    pub filepath: String,
+    /// Without the dot; empty if there is none.
+    pub extension: String,
+    pub file_size: i64,
+    /// Unix seconds.
+    pub modified_at: i64,
+    pub file_type: String,
+    /// See [`crate::db::FileEntry::child_count`]. `None` leaves the indexed
+    /// count as it is.
+    pub child_count: Option<i64>,
+}
+
+impl From<NewEntry> for FileRow {
+    fn from(entry: NewEntry) -> Self {
+        (entry.filename, entry.filepath, entry.extension, entry.file_size, entry.modified_at, entry.file_type)
+    }
+}
+
+/// A source of index entries. `full_index` runs each enabled provider in
+/// turn and writes what it hands over in batches, leaving out excluded
+/// extensions, so a provider only has to find entries.
+pub trait Provider {
+    /// Short stable name, used for the provider's flag in the
+    /// `index_providers` setting and in the index summary.
+    fn name(&self) -> &'static str;
+
+    /// The `file_type` of entries only this provider adds, if any. After a
+    /// run that wasn't cancelled, indexed entries of that type it didn't
+    /// pass on are removed, and all of them are while it's turned off.
+    /// Entries that aren't files need this, since the check for missing
+    /// files leaves them alone.
+    fn owned_file_type(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Where the provider reads from, listed in its [`ProviderSummary`] so
+    /// users can check what was scanned.
+    fn sources(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Parts the provider's work splits into, each collected on its own by
+    /// [`Provider::collect_unit`]. A pass records every unit it finishes, so
+    /// the next one can pick up where an interrupted pass stopped. Empty
+    /// (the default) for a provider that always runs in one go.
+    fn units(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Pass every entry to `sink`, stopping early once `cancel` is set.
+    /// Entries passed before an error are still indexed.
+    fn collect(&self, sink: &mut dyn FnMut(NewEntry), cancel: &AtomicBool) -> Result<ProviderReport, String>;
+
+    /// Like [`Provider::collect`], for one of [`Provider::units`] only.
+    fn collect_unit(
+        &self,
+        _unit: &str,
+        sink: &mut dyn FnMut(NewEntry),
+        cancel: &AtomicBool,
+    ) -> Result<ProviderReport, String> {
+        self.collect(sink, cancel)
+    }
+}
+
+/// What a provider says about its run besides the entries themselves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ProviderReport {
+    /// Items it came across but couldn't read, e.g. a folder it has no
+    /// access to.
+    pub skipped: usize,
+}
+
+/// How one provider did in an index pass.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderSummary {
+    pub name: &'static str,
+    pub entries: usize,
+    pub skipped: usize,
+    /// Entries of its own type it no longer found (see
+    /// [`Provider::owned_file_type`]), removed from the index.
+    pub removed: usize,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// See [`Provider::sources`].
+    pub sources: Vec<String>,
+}
+
+/// Name of the provider that walks the index directories.
+pub const FILESYSTEM_PROVIDER: &str = "filesystem";
+
+/// Name of every provider, in the order they run.
+pub const PROVIDER_NAMES: &[&str] = &[FilesystemProvider::NAME, BookmarksProvider::NAME];
+
+/// Every provider, in the order they run, the filesystem one walking
+/// `roots`; `status` follows the root being walked.
+fn providers(roots: Vec<PathBuf>, status: &IndexStatus) -> Vec<Box<dyn Provider + '_>> {
+    vec![
+        Box::new(FilesystemProvider {
+            roots,
+            status,
+        }),
+        Box::new(BookmarksProvider),
+    ]
+}
+
+/// Walks the index roots (see [`get_index_directories`]). Each root is
+/// split into units: the root with the files directly in it, and every
+/// folder in it that the walk goes into. Folders are passed on once the
+/// walk has counted what's directly inside them.
+struct FilesystemProvider<'a> {
+    roots: Vec<PathBuf>,
+    status: &'a IndexStatus,
+}
+
+impl FilesystemProvider<'_> {
+    const NAME: &'static str = FILESYSTEM_PROVIDER;
+}
+
+impl Provider for FilesystemProvider<'_> {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn sources(&self) -> Vec<String> {
+        self.roots.iter().map(|root| root.display().to_string()).collect()
+    }
+
+    fn units(&self) -> Vec<String> {
+        let mut units = Vec::new();
+        for root in &self.roots {
+            units.push(root.to_string_lossy().to_string());
+            let folders = WalkDir::new(root)
+                .min_depth(1)
+                .max_depth(1)
+                .follow_links(true)
+                .into_iter()
+                .filter_entry(should_walk)
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_dir());
+            units.extend(folders.map(|entry| entry.path().to_string_lossy().to_string()));
+        }
+        units
+    }
+
+    fn collect(&self, sink: &mut dyn FnMut(NewEntry), cancel: &AtomicBool) -> Result<ProviderReport, String> {
+        let mut report = ProviderReport::default();
+        for unit in self.units() {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            report.skipped += self.collect_unit(&unit, sink, cancel)?.skipped;
+        }
+        self.status.set_root(None);
+        Ok(report)
+    }
 
-    for dir in &directories {
-        info!("Indexing directory: {}", dir.display());
+    fn collect_unit(
+        &self,
+        unit: &str,
+        sink: &mut dyn FnMut(NewEntry),
+        cancel: &AtomicBool,
+    ) -> Result<ProviderReport, String> {
+        let mut report = ProviderReport::default();
+        let dir = Path::new(unit);
+        self.status.set_root(Some(dir));
 
+        // A root's own unit stops at its direct children and counts the
+        // folders among them without passing them on: they're walked as
+        // units of their own
+        let is_root = self.roots.iter().any(|root| root == dir);
+        if is_root {
+            info!("Indexing directory: {}", dir.display());
+        }
+        let max_depth = if is_root { 1 } else { MAX_DEPTH - 1 };
         let walker = WalkDir::new(dir)
-            .max_depth(MAX_DEPTH)
+            .max_depth(max_depth)
             .follow_links(true)
             .into_iter()
-            .filter_entry(|entry| {
-                // Skip hidden/system directories
-                if entry.file_type().is_dir() {
-                    if let Some(name) = entry.file_name().to_str() {
-                        if name.starts_with('.') || should_skip_dir(name) {
-                            return false;
-                        }
-                    }
-                }
-                true
-            });
+            .filter_entry(should_walk);
 
+        let mut open = OpenFolders::default();
         for entry in walker {
+            if cancel.load(Ordering::Relaxed) {
+                open.abandon(sink);
+                return Ok(report);
+            }
             let entry = match entry {
                 Ok(e) => e,
                 Err(e) => {
+                    report.skipped += 1;
+                    // A folder that can't be listed has no count to go by
+                    if let Some(path) = e.path() {
+                        open.uncount(path);
+                    }
                     // Permission denied, inaccessible files, or broken symlinks - skip silently
                     if let Some(io_err) = e.io_error() {
                         let kind = io_err.kind();
@@ -173,83 +676,1147 @@ pub fn full_index(db: &Arc<Database>) -> Result<usize, String> {
                 }
             };
 
-            let path = entry.path();
-            let filepath = path.to_string_lossy().to_string();
-
-            let filename = match path.file_name() {
-                Some(name) => name.to_string_lossy().to_string(),
-                None => continue,
+            open.visit(entry.depth(), sink);
+            let Some(new_entry) = entry.metadata().ok().and_then(|m| new_entry(entry.path(), &m)) else {
+                continue;
             };
+            if !entry.file_type().is_dir() {
+                sink(new_entry);
+            } else if entry.depth() < max_depth {
+                open.open(entry.depth(), new_entry);
+            } else if !is_root {
+                // At the depth limit, so its contents go uncounted
+                sink(new_entry);
+            }
+        }
+        open.close_from(0, sink);
+        Ok(report)
+    }
+}
+
+/// Largest child count recorded for a folder; the walk still visits
+/// everything in bigger ones.
+pub const CHILD_COUNT_CAP: i64 = 10_000;
+
+/// Folders the walk is inside of, innermost last. Each is held back from
+/// the sink until the walk leaves it, so it goes in with its child count.
+#[derive(Default)]
+struct OpenFolders {
+    folders: Vec<(usize, NewEntry)>,
+}
+
+impl OpenFolders {
+    fn open(&mut self, depth: usize, mut folder: NewEntry) {
+        folder.child_count = Some(0);
+        self.folders.push((depth, folder));
+    }
+
+    /// Count an entry at `depth` in its folder, after passing on the
+    /// folders the walk has left.
+    fn visit(&mut self, depth: usize, sink: &mut dyn FnMut(NewEntry)) {
+        self.close_from(depth, sink);
+        if let Some((parent_depth, parent)) = self.folders.last_mut() {
+            if *parent_depth + 1 == depth {
+                if let Some(count) = &mut parent.child_count {
+                    *count = (*count + 1).min(CHILD_COUNT_CAP);
+                }
+            }
+        }
+    }
+
+    /// Drop the count of `path` if it's an open folder.
+    fn uncount(&mut self, path: &Path) {
+        for (_, folder) in &mut self.folders {
+            if Path::new(&folder.filepath) == path {
+                folder.child_count = None;
+            }
+        }
+    }
+
+    /// Pass on every folder at `depth` or deeper.
+    fn close_from(&mut self, depth: usize, sink: &mut dyn FnMut(NewEntry)) {
+        while self.folders.last().is_some_and(|(folder_depth, _)| *folder_depth >= depth) {
+            if let Some((_, folder)) = self.folders.pop() {
+                sink(folder);
+            }
+        }
+    }
+
+    /// Pass on every open folder without a count, for a walk cut short.
+    fn abandon(&mut self, sink: &mut dyn FnMut(NewEntry)) {
+        for (_, folder) in &mut self.folders {
+            folder.child_count = None;
+        }
+        self.close_from(0, sink);
+    }
+}
+
+/// Entries written to the index at a time.
+const BATCH_SIZE: usize = 500;
+
+/// Prefix of the `index_meta` keys recording the units (see
+/// [`Provider::units`]) finished by a full index pass that hasn't completed
+/// yet, one `index_checkpoint:provider:unit` row per unit, so recording one
+/// doesn't rewrite the others.
+const CHECKPOINT_PREFIX: &str = "index_checkpoint:";
+
+fn load_checkpoint(db: &Database) -> HashSet<String> {
+    db.meta_keys_with_prefix(CHECKPOINT_PREFIX)
+        .map(|keys| keys.into_iter().collect())
+        .unwrap_or_default()
+}
+
+fn record_checkpoint(db: &Database, unit_key: &str) {
+    if let Err(e) = db.set_meta(&format!("{}{}", CHECKPOINT_PREFIX, unit_key), "") {
+        warn!("Failed to save the index checkpoint: {}", e);
+    }
+}
+
+fn clear_checkpoint(db: &Database) {
+    // Without the colon, this also drops the single JSON row older
+    // versions kept under `index_checkpoint`
+    if let Err(e) = db.remove_meta_with_prefix(CHECKPOINT_PREFIX.trim_end_matches(':')) {
+        warn!("Failed to clear the index checkpoint: {}", e);
+    }
+}
+
+/// `index_meta` key holding every index root a pass has scanned, as a
+/// JSON object mapping it to whether it's stale: scanned before, but
+/// missing or unreadable since.
+const ROOT_HEALTH_KEY: &str = "index_root_health";
+
+/// What a pass found at an index root.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "error")]
+pub enum RootStatus {
+    Scanned,
+    /// It doesn't exist.
+    SkippedMissing,
+    /// It's there but couldn't be listed, e.g. while a virus scanner holds
+    /// it or for lack of permission.
+    Error(String),
+}
 
-            let extension = path
-                .extension()
-                .map(|e| e.to_string_lossy().to_string())
-                .unwrap_or_default();
+/// How one index root fared in a pass.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RootCoverage {
+    pub root: String,
+    pub status: RootStatus,
+    /// Scanned by an earlier pass but not by this one. Its entries are
+    /// kept, rather than removed as missing, until a pass scans it again.
+    pub stale: bool,
+    /// Went stale in this pass, so it's reported once.
+    pub newly_stale: bool,
+}
 
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
+/// Check that each index root (see [`index_root_candidates`]) can be
+/// listed, before a pass walks them.
+pub fn check_roots(db: &Database) -> Vec<RootCoverage> {
+    let health = load_root_health(db);
+    index_root_candidates(db)
+        .into_iter()
+        .map(|root| {
+            let status = match std::fs::read_dir(&root) {
+                Ok(_) => RootStatus::Scanned,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => RootStatus::SkippedMissing,
+                Err(e) => RootStatus::Error(e.to_string()),
             };
+            let root = root.to_string_lossy().to_string();
+            let known = health.get(&root).copied();
+            let stale = status != RootStatus::Scanned && known.is_some();
+            RootCoverage {
+                newly_stale: stale && known == Some(false),
+                root,
+                status,
+                stale,
+            }
+        })
+        .collect()
+}
+
+fn load_root_health(db: &Database) -> BTreeMap<String, bool> {
+    db.get_meta(ROOT_HEALTH_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
 
-            let file_size = if metadata.is_file() {
-                metadata.len() as i64
-            } else {
-                0
+/// Record the roots a pass covered. A scanned root is only cleared of
+/// being stale once the pass `completed`; roots no longer indexed are
+/// forgotten.
+fn save_root_health(db: &Database, coverage: &[RootCoverage], completed: bool) {
+    let previous = load_root_health(db);
+    let health: BTreeMap<&str, bool> = coverage
+        .iter()
+        .filter_map(|root| {
+            let stale = match root.status {
+                RootStatus::Scanned if completed => false,
+                RootStatus::Scanned => *previous.get(&root.root)?,
+                _ => root.stale.then_some(true)?,
             };
+            Some((root.root.as_str(), stale))
+        })
+        .collect();
+    let json = serde_json::to_string(&health).unwrap_or_else(|_| "{}".to_string());
+    if let Err(e) = db.set_meta(ROOT_HEALTH_KEY, &json) {
+        warn!("Failed to save the index root status: {}", e);
+    }
+}
 
-            let modified_at = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0);
+/// Performs a full index pass with every provider enabled in settings,
+/// reporting progress to `status`. Returns the number of entries indexed.
+///
+/// If the last pass was interrupted, the units it finished are skipped,
+/// unless `force` is set to start over. Roots that can't be listed right
+/// now are left out and recorded as such (see [`check_roots`]).
+pub fn full_index(db: &Arc<Database>, status: &IndexStatus, force: bool) -> Result<usize, String> {
+    let enabled = crate::settings::index_providers(db);
+    let coverage = check_roots(db);
+    for root in coverage.iter().filter(|root| root.newly_stale) {
+        warn!("Index root '{}' can't be scanned ({:?}); keeping its entries", root.root, root.status);
+    }
+    let roots = coverage
+        .iter()
+        .filter(|root| root.status == RootStatus::Scanned)
+        .map(|root| PathBuf::from(&root.root))
+        .collect();
+    let (providers, disabled): (Vec<_>, Vec<_>) = providers(roots, status)
+        .into_iter()
+        .partition(|provider| enabled.get(provider.name()).copied().unwrap_or(true));
+    for file_type in disabled.iter().filter_map(|provider| provider.owned_file_type()) {
+        match db.remove_file_type_except(file_type, &HashSet::new()) {
+            Ok(0) => {}
+            Ok(removed) => info!("Removed {} {} entries of a disabled provider", removed, file_type),
+            Err(e) => warn!("Failed to remove {} entries: {}", file_type, e),
+        }
+    }
+    let result = run_providers(db, status, &providers, force);
+    if providers.iter().any(|provider| provider.name() == FILESYSTEM_PROVIDER) {
+        save_root_health(db, &coverage, result.is_ok());
+        status.set_roots(coverage);
+    }
+    result
+}
 
-            let file_type = classify_file(&extension, &filepath);
+/// Run `providers` one after another, writing their entries to the index
+/// and a [`ProviderSummary`] for each to `status`. A provider that fails
+/// doesn't stop the others. Units finished by an interrupted earlier pass
+/// are skipped unless `force` is set.
+fn run_providers(
+    db: &Database,
+    status: &IndexStatus,
+    providers: &[Box<dyn Provider + '_>],
+    force: bool,
+) -> Result<usize, String> {
+    info!("Starting full index with {} providers", providers.len());
+    let excluded = crate::settings::excluded_extensions(db);
+    status.set_phase(IndexPhase::Scanning);
 
-            batch.push((filename, filepath, extension, file_size, modified_at, file_type));
+    if force {
+        clear_checkpoint(db);
+    }
+    let checkpoint = load_checkpoint(db);
+    if !checkpoint.is_empty() {
+        info!("Resuming the last full index: {} units already done", checkpoint.len());
+    }
 
-            // Flush batch every 500 entries
-            if batch.len() >= 500 {
-                if let Err(e) = db.upsert_files_batch(&batch) {
-                    error!("Failed to upsert batch: {}", e);
+    let mut total_indexed = 0usize;
+    for provider in providers {
+        let started = Instant::now();
+        let owned_file_type = provider.owned_file_type();
+        let mut pending = PendingRows {
+            db,
+            status,
+            excluded: &excluded,
+            batch: Vec::with_capacity(BATCH_SIZE),
+            child_counts: Vec::new(),
+            entries: 0,
+            seen: owned_file_type.map(|_| HashSet::new()),
+        };
+        let units = provider.units();
+        let mut resumed = false;
+        let result = if units.is_empty() {
+            provider.collect(&mut |entry| pending.push(entry), &status.cancel)
+        } else {
+            let (mut report, mut error) = (ProviderReport::default(), None);
+            for unit in units {
+                let key = format!("{}:{}", provider.name(), unit);
+                if checkpoint.contains(&key) {
+                    resumed = true;
+                    continue;
+                }
+                if status.cancel.load(Ordering::Relaxed) {
+                    break;
                 }
-                total_indexed += batch.len();
-                batch.clear();
+                match provider.collect_unit(&unit, &mut |entry| pending.push(entry), &status.cancel) {
+                    Ok(unit_report) => report.skipped += unit_report.skipped,
+                    Err(e) => error = Some(e),
+                }
+                // The unit's rows go in before it's recorded as done
+                pending.flush();
+                if error.is_some() {
+                    break;
+                }
+                if !status.cancel.load(Ordering::Relaxed) {
+                    record_checkpoint(db, &key);
+                }
+            }
+            error.map_or(Ok(report), Err)
+        };
+        pending.flush();
+        status.set_root(None);
+        let (entries, seen) = (pending.entries, pending.seen);
+        total_indexed += entries;
+
+        // Entries in units skipped on resume weren't seen, so cleanup waits
+        // for a pass that covers them all
+        let mut removed = 0usize;
+        let complete = result.is_ok() && !resumed && !status.cancel.load(Ordering::Relaxed);
+        if let (Some(file_type), Some(seen)) = (owned_file_type.filter(|_| complete), seen) {
+            match db.remove_file_type_except(file_type, &seen) {
+                Ok(count) => removed = count,
+                Err(e) => warn!("Failed to remove stale {} entries: {}", file_type, e),
             }
         }
-    }
 
-    // Flush remaining entries
-    if !batch.is_empty() {
-        if let Err(e) = db.upsert_files_batch(&batch) {
-            error!("Failed to upsert final batch: {}", e);
+        let summary = ProviderSummary {
+            name: provider.name(),
+            entries,
+            skipped: result.as_ref().map_or(0, |report| report.skipped),
+            removed,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: result.err(),
+            sources: provider.sources(),
+        };
+        match &summary.error {
+            Some(e) => warn!("Provider {} failed after {} entries: {}", summary.name, entries, e),
+            None => info!(
+                "Provider {}: {} entries, {} skipped, {} removed, in {} ms",
+                summary.name, entries, summary.skipped, removed, summary.duration_ms
+            ),
         }
-        total_indexed += batch.len();
+        status.add_provider(summary);
+    }
+
+    if status.cancel.load(Ordering::Relaxed) {
+        return Err("Indexing was cancelled".to_string());
+    }
+
+    match merge_start_menu_duplicates(db) {
+        Ok(merged) if merged > 0 => info!("Merged {} duplicate Start Menu entries", merged),
+        Ok(_) => {}
+        Err(e) => warn!("Failed to merge duplicate Start Menu entries: {}", e),
+    }
+    if let Err(e) = crate::display_names::update_display_names(db, &status.cancel) {
+        warn!("{}", e);
     }
 
     // Record indexing time
     let now = chrono::Utc::now().timestamp().to_string();
     let _ = db.set_meta("last_full_index", &now);
+    clear_checkpoint(db);
 
     info!("Full index complete: {} files indexed", total_indexed);
     Ok(total_indexed)
 }
 
+/// Rows a provider has passed on in the current pass, written to the index
+/// [`BATCH_SIZE`] at a time.
+struct PendingRows<'a> {
+    db: &'a Database,
+    status: &'a IndexStatus,
+    excluded: &'a [String],
+    batch: Vec<FileRow>,
+    /// Folder paths and child counts (see [`OpenFolders`]), written along
+    /// with `batch`.
+    child_counts: Vec<(String, i64)>,
+    entries: usize,
+    /// Paths passed on, kept for providers with an owned file type.
+    seen: Option<HashSet<String>>,
+}
+
+impl PendingRows<'_> {
+    fn push(&mut self, entry: NewEntry) {
+        if is_excluded(&entry, self.excluded) {
+            return;
+        }
+        if let Some(seen) = &mut self.seen {
+            seen.insert(entry.filepath.clone());
+        }
+        if let Some(count) = entry.child_count {
+            self.child_counts.push((entry.filepath.clone(), count));
+        }
+        self.batch.push(entry.into());
+        self.entries += 1;
+        self.status.add_files(1);
+        if self.batch.len() >= BATCH_SIZE {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        write_batch(self.db, &mut self.batch);
+        if self.child_counts.is_empty() {
+            return;
+        }
+        if let Err(e) = self.db.set_child_counts(&self.child_counts) {
+            error!("Failed to record folder child counts: {}", e);
+        }
+        self.child_counts.clear();
+    }
+}
+
+/// Write `batch` to the index and empty it. A failed write is logged, and
+/// the pass carries on.
+fn write_batch(db: &Database, batch: &mut Vec<FileRow>) {
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = db.upsert_files_batch(batch) {
+        error!("Failed to upsert batch: {}", e);
+    }
+    batch.clear();
+}
+
 /// Perform an incremental re-index: remove missing files and re-scan directories.
-pub fn incremental_index(db: &Arc<Database>) -> Result<(usize, usize), String> {
+pub fn incremental_index(db: &Arc<Database>, status: &IndexStatus) -> Result<(usize, usize), String> {
     info!("Starting incremental index...");
+    status.set_phase(IndexPhase::RemovingMissing);
 
-    // Remove files that no longer exist
-    let removed = db.remove_missing_files().map_err(|e| format!("Remove missing failed: {}", e))?;
+    // Remove files that no longer exist, except under roots that went
+    // missing or unreadable: more likely they're back soon than gone
+    let stale: Vec<PathBuf> = check_roots(db)
+        .into_iter()
+        .filter(|root| root.stale)
+        .map(|root| PathBuf::from(root.root))
+        .collect();
+    let removed = db
+        .remove_missing_files(&stale)
+        .map_err(|e| format!("Remove missing failed: {}", e))?;
     if removed > 0 {
         info!("Removed {} missing files from index", removed);
     }
 
     // Re-scan and upsert
-    let indexed = full_index(db)?;
+    let indexed = full_index(db, status, false)?;
 
     let now = chrono::Utc::now().timestamp().to_string();
     let _ = db.set_meta("last_incremental_index", &now);
 
     Ok((indexed, removed))
 }
+
+/// Holds the "indexing in progress" flag while alive and clears it when
+/// dropped, so an early return or a panicking index pass can't leave it set.
+pub struct IndexingGuard<'a>(&'a AtomicBool);
+
+impl<'a> IndexingGuard<'a> {
+    /// Set `flag`, or `None` if it was already set by a pass still running.
+    pub fn acquire(flag: &'a AtomicBool) -> Option<Self> {
+        if flag.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(IndexingGuard(flag))
+        }
+    }
+}
+
+impl Drop for IndexingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// What started an index pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexTrigger {
+    /// The initial index when the app starts.
+    Startup,
+    /// A rebuild asked for from the UI, tray or `>rebuild`.
+    Manual,
+    /// The periodic incremental pass.
+    Background,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexPhase {
+    Idle,
+    /// Dropping entries for files that no longer exist.
+    RemovingMissing,
+    /// Walking the index roots.
+    Scanning,
+    /// Reloading the in-memory filename snapshot.
+    Finishing,
+}
+
+/// How the last finished index pass went.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexRunSummary {
+    pub trigger: IndexTrigger,
+    /// Unix seconds.
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub files_indexed: usize,
+    pub files_removed: usize,
+    pub error: Option<String>,
+    /// Each provider that ran, in order.
+    pub providers: Vec<ProviderSummary>,
+    /// Each index root and whether it was scanned; empty when the
+    /// filesystem provider didn't run.
+    pub roots: Vec<RootCoverage>,
+}
+
+/// A background pass that was put off, and why.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct IndexSkip {
+    pub reason: SkipReason,
+    /// Unix seconds.
+    pub skipped_at: i64,
+}
+
+/// Result of `get_indexing_status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexingStatus {
+    pub running: bool,
+    pub phase: IndexPhase,
+    pub trigger: Option<IndexTrigger>,
+    pub files_processed: usize,
+    /// The index root being walked.
+    pub current_root: Option<String>,
+    /// Unix seconds.
+    pub started_at: Option<i64>,
+    /// Rough seconds left, assuming this pass sees as many files as the
+    /// last one did.
+    pub eta_hint: Option<u64>,
+    pub last_run: Option<IndexRunSummary>,
+    /// Set while background passes are being put off, explaining why the
+    /// index may be stale; cleared when a pass starts.
+    pub skipped: Option<IndexSkip>,
+}
+
+struct CurrentRun {
+    trigger: IndexTrigger,
+    phase: IndexPhase,
+    current_root: Option<String>,
+    started_at: i64,
+    started: Instant,
+    providers: Vec<ProviderSummary>,
+    roots: Vec<RootCoverage>,
+}
+
+#[derive(Default)]
+struct RunState {
+    current: Option<CurrentRun>,
+    last_run: Option<IndexRunSummary>,
+    skipped: Option<IndexSkip>,
+}
+
+/// Progress of the running index pass, updated by `full_index` as it goes,
+/// and the summary of the last one.
+#[derive(Default)]
+pub struct IndexStatus {
+    /// Bumped for every file, so kept out of the lock.
+    files_processed: AtomicUsize,
+    /// Set to stop the running pass early; cleared when the next begins.
+    cancel: AtomicBool,
+    run: RwLock<RunState>,
+}
+
+impl IndexStatus {
+    /// Start tracking a pass. Callers hold the `IndexingGuard`, so passes
+    /// never overlap.
+    pub fn begin(&self, trigger: IndexTrigger) {
+        self.files_processed.store(0, Ordering::Relaxed);
+        self.cancel.store(false, Ordering::Relaxed);
+        let mut run = self.run.write().unwrap();
+        run.skipped = None;
+        run.current = Some(CurrentRun {
+            trigger,
+            phase: IndexPhase::Scanning,
+            current_root: None,
+            started_at: chrono::Utc::now().timestamp(),
+            started: Instant::now(),
+            providers: Vec::new(),
+            roots: Vec::new(),
+        });
+    }
+
+    /// Ask the running pass to stop after the entry it's on, e.g. because
+    /// the app is exiting. The entries indexed so far are kept.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Record that a background pass was put off for `reason`.
+    pub fn skip(&self, reason: SkipReason) {
+        self.run.write().unwrap().skipped = Some(IndexSkip {
+            reason,
+            skipped_at: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    pub fn set_phase(&self, phase: IndexPhase) {
+        if let Some(current) = &mut self.run.write().unwrap().current {
+            current.phase = phase;
+        }
+    }
+
+    fn set_root(&self, root: Option<&Path>) {
+        if let Some(current) = &mut self.run.write().unwrap().current {
+            current.current_root = root.map(|root| root.to_string_lossy().to_string());
+        }
+    }
+
+    fn add_files(&self, count: usize) {
+        self.files_processed.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn add_provider(&self, summary: ProviderSummary) {
+        if let Some(current) = &mut self.run.write().unwrap().current {
+            current.providers.push(summary);
+        }
+    }
+
+    fn set_roots(&self, roots: Vec<RootCoverage>) {
+        if let Some(current) = &mut self.run.write().unwrap().current {
+            current.roots = roots;
+        }
+    }
+
+    /// End the pass and keep its summary; back to idle.
+    pub fn finish(&self, result: Result<(usize, usize), String>) {
+        let mut run = self.run.write().unwrap();
+        let Some(current) = run.current.take() else {
+            return;
+        };
+        let (files_indexed, files_removed, error) = match result {
+            Ok((indexed, removed)) => (indexed, removed, None),
+            Err(e) => (self.files_processed.load(Ordering::Relaxed), 0, Some(e)),
+        };
+        run.last_run = Some(IndexRunSummary {
+            trigger: current.trigger,
+            started_at: current.started_at,
+            finished_at: chrono::Utc::now().timestamp(),
+            files_indexed,
+            files_removed,
+            error,
+            providers: current.providers,
+            roots: current.roots,
+        });
+    }
+
+    pub fn snapshot(&self) -> IndexingStatus {
+        let run = self.run.read().unwrap();
+        let last_run = run.last_run.clone();
+        let skipped = run.skipped;
+        let Some(current) = &run.current else {
+            return IndexingStatus {
+                running: false,
+                phase: IndexPhase::Idle,
+                trigger: None,
+                files_processed: 0,
+                current_root: None,
+                started_at: None,
+                eta_hint: None,
+                last_run,
+                skipped,
+            };
+        };
+        let files_processed = self.files_processed.load(Ordering::Relaxed);
+        let eta_hint = last_run.as_ref().and_then(|last| {
+            eta_seconds(files_processed, last.files_indexed, current.started.elapsed().as_secs())
+        });
+        IndexingStatus {
+            running: true,
+            phase: current.phase,
+            trigger: Some(current.trigger),
+            files_processed,
+            current_root: current.current_root.clone(),
+            started_at: Some(current.started_at),
+            eta_hint,
+            last_run,
+            skipped,
+        }
+    }
+}
+
+/// Seconds left to reach `expected` files at the rate so far, or `None`
+/// when there's no rate yet or the estimate has already been passed.
+fn eta_seconds(processed: usize, expected: usize, elapsed_secs: u64) -> Option<u64> {
+    if processed == 0 || processed >= expected {
+        return None;
+    }
+    Some(elapsed_secs * (expected - processed) as u64 / processed as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexing_guard_clears_flag_after_panic() {
+        let flag = AtomicBool::new(false);
+
+        // The way commands run a pass: the panic surfaces as a join error
+        let rebuild = || -> Result<usize, String> {
+            let _guard = IndexingGuard::acquire(&flag).ok_or("Indexing is already in progress")?;
+            std::thread::spawn(|| -> usize { panic!("weird path") })
+                .join()
+                .map_err(|_| "Index task failed".to_string())
+        };
+        assert!(rebuild().is_err());
+        assert!(!flag.load(Ordering::SeqCst));
+
+        // A panic while the guard itself unwinds
+        let unwound = std::panic::catch_unwind(|| {
+            let _guard = IndexingGuard::acquire(&flag).unwrap();
+            panic!("weird path");
+        });
+        assert!(unwound.is_err());
+        assert!(!flag.load(Ordering::SeqCst));
+
+        let guard = IndexingGuard::acquire(&flag).unwrap();
+        assert!(IndexingGuard::acquire(&flag).is_none());
+        drop(guard);
+        assert!(IndexingGuard::acquire(&flag).is_some());
+    }
+
+    #[test]
+    fn test_index_status_lifecycle() {
+        let status = IndexStatus::default();
+        assert!(!status.snapshot().running);
+        assert_eq!(status.snapshot().last_run, None);
+
+        status.skip(SkipReason::OnBattery);
+        assert_eq!(status.snapshot().skipped.map(|skip| skip.reason), Some(SkipReason::OnBattery));
+
+        status.begin(IndexTrigger::Background);
+        status.set_phase(IndexPhase::RemovingMissing);
+        status.set_root(Some(Path::new(r"C:\Program Files")));
+        status.add_files(3);
+        let running = status.snapshot();
+        assert!(running.running);
+        assert_eq!(running.phase, IndexPhase::RemovingMissing);
+        assert_eq!(running.trigger, Some(IndexTrigger::Background));
+        assert_eq!(running.files_processed, 3);
+        assert_eq!(running.current_root.as_deref(), Some(r"C:\Program Files"));
+        assert_eq!(running.eta_hint, None);
+        assert_eq!(running.skipped, None);
+
+        status.finish(Ok((3, 1)));
+        let idle = status.snapshot();
+        assert!(!idle.running);
+        assert_eq!(idle.phase, IndexPhase::Idle);
+        assert_eq!(idle.files_processed, 0);
+        let last = idle.last_run.unwrap();
+        assert_eq!((last.trigger, last.files_indexed, last.files_removed), (IndexTrigger::Background, 3, 1));
+        assert_eq!(last.error, None);
+
+        // A failed pass keeps what it got through
+        status.begin(IndexTrigger::Manual);
+        status.add_files(2);
+        status.finish(Err("Index task failed".to_string()));
+        let last = status.snapshot().last_run.unwrap();
+        assert_eq!(last.files_indexed, 2);
+        assert_eq!(last.error.as_deref(), Some("Index task failed"));
+    }
+
+    #[test]
+    fn test_eta_seconds() {
+        assert_eq!(eta_seconds(1000, 4000, 10), Some(30));
+        assert_eq!(eta_seconds(0, 4000, 10), None);
+        assert_eq!(eta_seconds(5000, 4000, 10), None);
+    }
+
+    struct MockProvider {
+        name: &'static str,
+        files: &'static [&'static str],
+        error: Option<&'static str>,
+        owned: Option<&'static str>,
+    }
+
+    impl Provider for MockProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn owned_file_type(&self) -> Option<&'static str> {
+            self.owned
+        }
+
+        fn collect(&self, sink: &mut dyn FnMut(NewEntry), cancel: &AtomicBool) -> Result<ProviderReport, String> {
+            for file in self.files {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let path = Path::new(file);
+                let extension = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+                sink(NewEntry {
+                    filename: path.file_name().unwrap().to_string_lossy().to_string(),
+                    filepath: file.to_string(),
+                    file_type: self.owned.map_or_else(|| classify_file(&extension, file), str::to_string),
+                    extension,
+                    file_size: 1,
+                    modified_at: 0,
+                    child_count: None,
+                });
+            }
+            match self.error {
+                Some(e) => Err(e.to_string()),
+                None => Ok(ProviderReport { skipped: 2 }),
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_providers_reports_each_provider() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let settings = crate::settings::Settings::load(&db);
+        let patch = crate::settings::SettingsPatch {
+            excluded_extensions: Some(vec!["tmp".to_string()]),
+            ..Default::default()
+        };
+        settings.with_patch(patch).unwrap().save(&db).unwrap();
+        let status = IndexStatus::default();
+        let providers: Vec<Box<dyn Provider>> = vec![
+            Box::new(MockProvider {
+                name: "apps",
+                files: &[r"C:\Apps\editor.exe", r"C:\Apps\scratch.tmp"],
+                error: None,
+                owned: None,
+            }),
+            Box::new(MockProvider {
+                name: "bookmarks",
+                files: &[r"C:\Bookmarks\news.url"],
+                error: Some("profile is locked"),
+                owned: None,
+            }),
+        ];
+
+        // A failing provider keeps what it found and doesn't stop the pass
+        status.begin(IndexTrigger::Manual);
+        assert_eq!(run_providers(&db, &status, &providers, false).unwrap(), 2);
+        assert_eq!(db.file_count().unwrap(), 2);
+        status.finish(Ok((2, 0)));
+        let summaries = status.snapshot().last_run.unwrap().providers;
+        assert_eq!(summaries.len(), 2);
+        assert_eq!((summaries[0].name, summaries[0].entries, summaries[0].skipped), ("apps", 1, 2));
+        assert_eq!(summaries[0].error, None);
+        assert_eq!((summaries[1].name, summaries[1].entries), ("bookmarks", 1));
+        assert_eq!(summaries[1].error.as_deref(), Some("profile is locked"));
+
+        // A cancelled pass stops early and doesn't count as a full index
+        status.begin(IndexTrigger::Manual);
+        status.cancel();
+        assert!(run_providers(&db, &status, &providers, false).is_err());
+        status.finish(Err("Indexing was cancelled".to_string()));
+        let summaries = status.snapshot().last_run.unwrap().providers;
+        assert!(summaries.iter().all(|summary| summary.entries == 0));
+    }
+
+    #[test]
+    fn test_provider_removes_its_entries_that_are_gone() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let status = IndexStatus::default();
+        let run = |files: &'static [&'static str]| {
+            let provider: Box<dyn Provider> = Box::new(MockProvider {
+                name: "links",
+                files,
+                error: None,
+                owned: Some(crate::bookmarks::BOOKMARK_FILE_TYPE),
+            });
+            run_providers(&db, &status, &[provider], false).unwrap();
+        };
+        run(&["https://a.example/", "https://b.example/"]);
+        db.record_click("https://a.example/").unwrap();
+        run(&["https://a.example/"]);
+
+        let entries = db.load_compact_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((&*entries[0].filepath, entries[0].click_count), ("https://a.example/", 1));
+        // Bookmarks aren't files, so the missing-file check leaves them be
+        assert_eq!(db.remove_missing_files(&[]).unwrap(), 0);
+    }
+
+    /// Cancels the pass when it reaches unit number `after`.
+    struct InterruptingProvider<'a> {
+        inner: FilesystemProvider<'a>,
+        after: usize,
+        started: std::cell::Cell<usize>,
+    }
+
+    impl Provider for InterruptingProvider<'_> {
+        fn name(&self) -> &'static str {
+            self.inner.name()
+        }
+
+        fn units(&self) -> Vec<String> {
+            self.inner.units()
+        }
+
+        fn collect(&self, sink: &mut dyn FnMut(NewEntry), cancel: &AtomicBool) -> Result<ProviderReport, String> {
+            self.inner.collect(sink, cancel)
+        }
+
+        fn collect_unit(
+            &self,
+            unit: &str,
+            sink: &mut dyn FnMut(NewEntry),
+            cancel: &AtomicBool,
+        ) -> Result<ProviderReport, String> {
+            self.started.set(self.started.get() + 1);
+            if self.started.get() > self.after {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            self.inner.collect_unit(unit, sink, cancel)
+        }
+    }
+
+    #[test]
+    fn test_folder_child_counts() {
+        let root = std::env::temp_dir().join(format!("ancheck-child-counts-{}", std::process::id()));
+        for dir in ["full/inner", "empty", ".hidden"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["full/a.txt", "full/b.txt", ".hidden/c.txt"] {
+            std::fs::write(root.join(file), file).unwrap();
+        }
+        let db = Database::open(Path::new(":memory:")).unwrap();
+        let status = IndexStatus::default();
+        let provider: Box<dyn Provider> = Box::new(FilesystemProvider {
+            roots: vec![root.clone()],
+            status: &status,
+        });
+        run_providers(&db, &status, &[provider], false).unwrap();
+
+        let entries = db.load_compact_entries().unwrap();
+        let count = |path: &Path| {
+            let path = path.to_string_lossy();
+            entries.iter().find(|e| *e.filepath == *path).unwrap().child_count
+        };
+        // Hidden folders aren't walked, so they don't count either
+        assert_eq!(count(&root), Some(2));
+        assert_eq!(count(&root.join("full")), Some(3));
+        assert_eq!(count(&root.join("full/inner")), Some(0));
+        assert_eq!(count(&root.join("empty")), Some(0));
+        assert_eq!(count(&root.join("full/a.txt")), None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_hot_folders_pick_up_new_files() {
+        let root = std::env::temp_dir().join(format!("ancheck-hot-folders-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("old.txt"), "old").unwrap();
+        let db = Database::open(Path::new(":memory:")).unwrap();
+        let mut hot = HotFolders::with_folders(vec![root.clone()]);
+        let indexed = |db: &Database| {
+            let mut names: Vec<String> =
+                db.load_compact_entries().unwrap().iter().map(|e| e.filename.to_string()).collect();
+            names.sort();
+            names
+        };
+
+        // The first check only takes stock
+        assert_eq!(hot.check(&db, &[]), 0);
+        assert!(indexed(&db).is_empty());
+
+        std::fs::write(root.join("invoice.pdf.part"), "partial").unwrap();
+        // Not watched while it isn't an index root
+        let elsewhere = [std::env::temp_dir().join("ancheck-elsewhere").to_string_lossy().to_string()];
+        assert_eq!(hot.check(&db, &elsewhere), 0);
+        assert_eq!(hot.check(&db, &[root.to_string_lossy().to_string()]), 1);
+        assert_eq!(indexed(&db), ["invoice.pdf.part"]);
+
+        // A finished download is renamed into place
+        std::fs::rename(root.join("invoice.pdf.part"), root.join("invoice.pdf")).unwrap();
+        std::fs::create_dir(root.join(".cache")).unwrap();
+        assert_eq!(hot.check(&db, &[]), 2);
+        assert_eq!(indexed(&db), ["invoice.pdf"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_vanished_root_keeps_its_entries() {
+        let root = std::env::temp_dir().join(format!("ancheck-vanishing-{}", std::process::id()));
+        let moved = root.with_extension("away");
+        let never = root.with_extension("never");
+        std::fs::create_dir_all(root.join("Tools")).unwrap();
+        std::fs::write(root.join("Tools").join("tool.exe"), "tool").unwrap();
+        let db = Arc::new(Database::open(Path::new(":memory:")).unwrap());
+        db.set_meta("index_providers", r#"{"bookmarks": false}"#).unwrap();
+        db.set_meta("index_roots", &serde_json::to_string(&[&root, &never]).unwrap())
+            .unwrap();
+        let status = IndexStatus::default();
+        let pass = || {
+            status.begin(IndexTrigger::Manual);
+            let result = incremental_index(&db, &status);
+            status.finish(result);
+            status.snapshot().last_run.unwrap().roots
+        };
+
+        let roots = pass();
+        assert_eq!(roots[0].status, RootStatus::Scanned);
+        // Never scanned, so there's nothing to keep
+        assert_eq!(roots[1].status, RootStatus::SkippedMissing);
+        assert!(!roots[1].stale);
+        let indexed = db.file_count().unwrap();
+        assert!(indexed >= 2);
+
+        // Gone for a while: its entries stay, and it's reported once
+        std::fs::rename(&root, &moved).unwrap();
+        let roots = pass();
+        assert_eq!(roots[0].status, RootStatus::SkippedMissing);
+        assert!(roots[0].stale && roots[0].newly_stale);
+        assert_eq!(db.file_count().unwrap(), indexed);
+        let roots = pass();
+        assert!(roots[0].stale && !roots[0].newly_stale);
+        assert_eq!(db.file_count().unwrap(), indexed);
+
+        // Back again, it's scanned and no longer stale
+        std::fs::rename(&moved, &root).unwrap();
+        let roots = pass();
+        assert_eq!(roots[0].status, RootStatus::Scanned);
+        assert!(!roots[0].stale);
+        assert_eq!(load_root_health(&db).get(&roots[0].root), Some(&false));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_interrupted_full_index_resumes() {
+        let root = std::env::temp_dir().join(format!("ancheck-resume-{}", std::process::id()));
+        for (dir, file) in [("", "notes.txt"), ("one", "a.txt"), ("one/deep", "b.txt"), ("two", "c.txt"), ("three", "d.txt")] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join(file), file).unwrap();
+        }
+        let status = IndexStatus::default();
+        let filesystem = || FilesystemProvider {
+            roots: vec![root.clone()],
+            status: &status,
+        };
+        let interrupted = |after| -> Vec<Box<dyn Provider + '_>> {
+            vec![Box::new(InterruptingProvider {
+                inner: filesystem(),
+                after,
+                started: Default::default(),
+            })]
+        };
+        let paths = |db: &Database| {
+            let mut paths: Vec<String> =
+                db.load_compact_entries().unwrap().iter().map(|e| e.filepath.to_string()).collect();
+            paths.sort();
+            paths
+        };
+        let last_entries = || {
+            status.finish(Ok((0, 0)));
+            status.snapshot().last_run.unwrap().providers[0].entries
+        };
+
+        // The root itself plus its three folders
+        assert_eq!(filesystem().units().len(), 4);
+        let expected = Database::open(Path::new(":memory:")).unwrap();
+        status.begin(IndexTrigger::Manual);
+        run_providers(&expected, &status, &[Box::new(filesystem())], false).unwrap();
+        assert_eq!(paths(&expected).len(), 10);
+
+        // Two units are recorded before the interruption, and the next pass
+        // only walks the other two
+        let db = Database::open(Path::new(":memory:")).unwrap();
+        status.begin(IndexTrigger::Manual);
+        assert!(run_providers(&db, &status, &interrupted(2), false).is_err());
+        assert_eq!(load_checkpoint(&db).len(), 2);
+        assert!(paths(&db).len() < 10);
+        db.set_meta("index_checkpoint", "[]").unwrap();
+        status.begin(IndexTrigger::Manual);
+        run_providers(&db, &status, &[Box::new(filesystem())], false).unwrap();
+        assert!(last_entries() < 10);
+        assert_eq!(paths(&db), paths(&expected));
+        assert!(load_checkpoint(&db).is_empty());
+        // The old single-row checkpoint goes with the new rows
+        assert_eq!(db.get_meta("index_checkpoint").unwrap(), None);
+
+        // Forcing a full pass walks everything again
+        status.begin(IndexTrigger::Manual);
+        assert!(run_providers(&db, &status, &interrupted(1), false).is_err());
+        status.begin(IndexTrigger::Manual);
+        run_providers(&db, &status, &[Box::new(filesystem())], true).unwrap();
+        assert_eq!(last_entries(), 10);
+        assert_eq!(paths(&db), paths(&expected));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_provider_names() {
+        let status = IndexStatus::default();
+        let names: Vec<&str> = providers(Vec::new(), &status).iter().map(|provider| provider.name()).collect();
+        assert_eq!(names, PROVIDER_NAMES);
+    }
+
+    #[test]
+    fn test_merge_start_menu_duplicates() {
+        let dir = std::env::temp_dir().join(format!("ancheck-start-menu-{}", std::process::id()));
+        let (user, system) = (dir.join("user"), dir.join("system"));
+        std::fs::create_dir_all(&user).unwrap();
+        std::fs::create_dir_all(&system).unwrap();
+        std::fs::write(user.join("Editor.lnk"), "editor").unwrap();
+        std::fs::write(system.join("Editor.lnk"), "editor").unwrap();
+        std::fs::write(system.join("Player.lnk"), "player").unwrap();
+        std::fs::write(user.join("Player.lnk"), "other player").unwrap();
+
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let index = |db: &Database| {
+            index_tree_rows(db, &user);
+            index_tree_rows(db, &system);
+        };
+        index(&db);
+        let path = |root: &Path, name: &str| root.join(name).to_string_lossy().to_string();
+        let (user_editor, system_editor) = (path(&user, "Editor.lnk"), path(&system, "Editor.lnk"));
+        db.record_click(&user_editor).unwrap();
+        db.record_click(&user_editor).unwrap();
+        db.record_click(&system_editor).unwrap();
+        db.record_query_pick("edi", &system_editor).unwrap();
+
+        // Same name and size merge into the more launched copy; a different size doesn't
+        assert_eq!(merge_duplicates_between(&db, &user, &system).unwrap(), 1);
+        let entries = db.load_compact_entries().unwrap();
+        assert_eq!(entries.len(), 3);
+        let editor = entries.iter().find(|e| &*e.filename == "Editor.lnk").unwrap();
+        assert_eq!((&*editor.filepath, editor.click_count), (user_editor.as_str(), 3));
+        assert_eq!(db.query_picks_for("edi").unwrap()[0].filepath, user_editor);
+        assert_eq!(db.indexed_filepath(&system_editor).unwrap(), Some(user_editor.clone()));
+
+        // Later passes don't bring the duplicate back
+        index(&db);
+        assert_eq!(merge_duplicates_between(&db, &user, &system).unwrap(), 0);
+        assert_eq!(db.load_compact_entries().unwrap().len(), 3);
+
+        // Removing the kept copy moves the entry to the other one
+        std::fs::remove_file(&user_editor).unwrap();
+        assert_eq!(db.remove_missing_files(&[]).unwrap(), 0);
+        index(&db);
+        let entries = db.load_compact_entries().unwrap();
+        assert_eq!(entries.len(), 3);
+        let editor = entries.iter().find(|e| &*e.filename == "Editor.lnk").unwrap();
+        assert_eq!((&*editor.filepath, editor.click_count), (system_editor.as_str(), 3));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn index_tree_rows(db: &Database, root: &Path) {
+        let rows: Vec<FileRow> = WalkDir::new(root)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| new_entry(entry.path(), &entry.metadata().ok()?))
+            .map(FileRow::from)
+            .collect();
+        db.upsert_files_batch(&rows).unwrap();
+    }
+}
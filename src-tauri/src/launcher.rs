@@ -1,14 +1,39 @@
+use crate::db::Database;
+use crate::env_vars::expand_env_vars;
+use crate::i18n::t_with;
+use crate::indexer::{self, FileTimes};
+use crate::processes::{self, ProcessCache};
+use crate::settings_pages;
 use log::{error, info, warn};
-use std::path::Path;
-use std::process::Command;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 /// Launch a file or application at the given path using the Windows shell.
 /// Handles .exe, .lnk, directories, and documents.
-pub fn launch(filepath: &str) -> Result<(), String> {
+pub fn launch(filepath: &str, options: &LaunchOptions) -> Result<(), String> {
+    match options.kind {
+        EntryKind::Url => return open_url(filepath),
+        EntryKind::Command => return run_command(filepath, options),
+        EntryKind::File => {}
+    }
+    if is_web_url(filepath) {
+        return match &options.browser {
+            Some(browser) => open_url_in(filepath, browser),
+            None => open_url(filepath),
+        };
+    }
+    if settings_pages::is_settings_target(filepath) {
+        return open_settings_page(filepath);
+    }
+
+    let expanded = expand_env_vars(filepath);
+    let filepath = expanded.as_ref();
     let path = Path::new(filepath);
 
     if !path.exists() {
-        return Err(format!("File not found: {}", filepath));
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
     }
 
     let extension = path
@@ -20,25 +45,220 @@ pub fn launch(filepath: &str) -> Result<(), String> {
 
     match extension.as_str() {
         // Direct execution for .exe files
-        "exe" => launch_exe(filepath),
+        "exe" => launch_exe(filepath, &[], None, options),
         // Resolve and launch .lnk shortcuts
         "lnk" => launch_shortcut(filepath),
-        // Open directories in Explorer
-        "" if path.is_dir() => open_in_explorer(filepath),
+        // Open directories in the file manager
+        "" if path.is_dir() => open_folder(filepath, options.file_manager.as_deref()),
         // Everything else: open with default handler via ShellExecute
         _ => shell_open(filepath),
     }
 }
 
-/// Launch an .exe file directly.
-fn launch_exe(filepath: &str) -> Result<(), String> {
-    let parent = Path::new(filepath)
-        .parent()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| ".".to_string());
+/// Pause between launches in [`launch_each`]; Explorer mishandles
+/// ShellExecute calls that arrive at the same moment.
+const LAUNCH_STAGGER: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// The result of launching one of several files.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LaunchOutcome {
+    pub filepath: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Launch each of `filepaths` in order with `launch`, pausing
+/// [`LAUNCH_STAGGER`] between them. A failure doesn't stop the rest.
+pub fn launch_each(
+    filepaths: &[String],
+    mut launch: impl FnMut(&str) -> Result<(), String>,
+) -> Vec<LaunchOutcome> {
+    filepaths
+        .iter()
+        .enumerate()
+        .map(|(i, filepath)| {
+            if i > 0 {
+                std::thread::sleep(LAUNCH_STAGGER);
+            }
+            let error = launch(filepath).err();
+            if let Some(e) = &error {
+                warn!("Failed to launch '{}' from a multi-selection: {}", filepath, e);
+            }
+            LaunchOutcome {
+                filepath: filepath.clone(),
+                ok: error.is_none(),
+                error,
+            }
+        })
+        .collect()
+}
+
+/// Update usage stats after launching `filepath`. A successful launch records
+/// the click (and the query it was picked for, and the app the launcher was
+/// opened from); a failed one records nothing, and if the file is gone its
+/// stale index rows are removed. Returns true when rows were removed.
+pub fn record_launch_outcome(
+    db: &Database,
+    filepath: &str,
+    query: Option<&str>,
+    context_app: Option<&str>,
+    result: &Result<(), String>,
+) -> bool {
+    if result.is_ok() {
+        if let Err(e) = db.record_click(filepath) {
+            error!("Failed to record click: {}", e);
+        }
+        if let Some(key) = query.and_then(crate::searcher::pick_key) {
+            if let Err(e) = db.record_query_pick(&key, filepath) {
+                error!("Failed to record query pick: {}", e);
+            }
+        }
+        if let Some(context_app) = context_app {
+            if let Err(e) = db.record_context_pick(context_app, filepath) {
+                error!("Failed to record context pick: {}", e);
+            }
+        }
+        return false;
+    }
+    if is_web_url(filepath)
+        || settings_pages::is_settings_target(filepath)
+        || Path::new(expand_env_vars(filepath).as_ref()).exists()
+    {
+        return false;
+    }
+
+    match db.remove_path(filepath) {
+        Ok(removed) => {
+            info!("Removed {} stale index entries for missing: {}", removed, filepath);
+            removed > 0
+        }
+        Err(e) => {
+            error!("Failed to remove stale entry '{}': {}", filepath, e);
+            false
+        }
+    }
+}
+
+/// Whether a launch target is an http(s) URL rather than a path.
+pub(crate) fn is_web_url(target: &str) -> bool {
+    let lower = target.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
 
-    Command::new(filepath)
-        .current_dir(&parent)
+/// Open a URL in the default browser.
+fn open_url(url: &str) -> Result<(), String> {
+    // `cmd /C start` would treat '&' in query strings as a command separator,
+    // so hand the URL to the protocol handler directly.
+    Command::new("rundll32")
+        .args(["url.dll,FileProtocolHandler", url])
+        .spawn()
+        .map_err(|e| {
+            error!("Failed to open URL '{}': {}", url, e);
+            format!("Failed to open '{}': {}", url, e)
+        })?;
+
+    info!("Opened URL: {}", url);
+    Ok(())
+}
+
+/// Open a URL in the browser at `browser`.
+fn open_url_in(url: &str, browser: &Path) -> Result<(), String> {
+    Command::new(browser).arg(url).spawn().map_err(|e| {
+        error!("Failed to open URL '{}' in {}: {}", url, browser.display(), e);
+        format!("Failed to open '{}': {}", url, e)
+    })?;
+
+    info!("Opened URL in {}: {}", browser.display(), url);
+    Ok(())
+}
+
+/// Open a Windows Settings page (an `ms-settings:` URI, which the protocol
+/// handler takes like a web link) or a Control Panel applet by its
+/// canonical name.
+fn open_settings_page(target: &str) -> Result<(), String> {
+    let Some(applet) = settings_pages::control_panel_name(target) else {
+        return open_url(target);
+    };
+    Command::new("control.exe").args(["/name", applet]).spawn().map_err(|e| {
+        error!("Failed to open Control Panel applet '{}': {}", applet, e);
+        format!("Failed to open '{}': {}", applet, e)
+    })?;
+    info!("Opened Control Panel applet: {}", applet);
+    Ok(())
+}
+
+/// Launch `filepath` with command-line arguments (`code .`) and an optional
+/// working directory, which defaults to the file's folder.
+///
+/// An `.exe` gets each argument passed separately, so embedded quotes and
+/// trailing backslashes survive. Anything else, including `.lnk` shortcuts,
+/// falls back to ShellExecute with the arguments quoted into one parameter
+/// string; for a shortcut the shell appends them to its resolved target's
+/// own arguments.
+pub fn launch_with_args(
+    filepath: &str,
+    args: &[String],
+    cwd: Option<&str>,
+    options: &LaunchOptions,
+) -> Result<(), String> {
+    // Custom URL and command entries take no extra arguments
+    if (args.is_empty() && cwd.is_none()) || options.kind != EntryKind::File {
+        return launch(filepath, options);
+    }
+
+    let expanded = expand_env_vars(filepath);
+    let filepath = expanded.as_ref();
+    let path = Path::new(filepath);
+    if !path.exists() {
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+    if path.is_dir() {
+        return Err(format!("Can't pass arguments to a folder: {}", filepath));
+    }
+    if let Some(dir) = cwd {
+        if !Path::new(dir).is_dir() {
+            return Err(format!("Working directory not found: {}", dir));
+        }
+    }
+
+    info!("Launching with args: {} {:?} (cwd: {:?})", filepath, args, cwd);
+    let is_exe = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("exe"));
+    if is_exe {
+        launch_exe(filepath, args, cwd, options)
+    } else {
+        let parameters = join_arguments(args);
+        let directory = cwd.map(str::to_string).unwrap_or_else(|| parent_dir(filepath));
+        shell_execute("open", filepath, Some(parameters.as_str()).filter(|p| !p.is_empty()), &directory)
+    }
+}
+
+/// Launch an .exe file directly, in `cwd` or else the file's own folder.
+///
+/// A console program gets a console of its own with [`ConsoleMode::Auto`],
+/// never the launcher's, so `cmd.exe` or `python.exe` open ready for input.
+/// [`ConsoleMode::Hidden`] runs it without a window (and without stdin, so it
+/// can't wait for input nobody can give), and [`ConsoleMode::Terminal`] in
+/// the preferred terminal, which stays open after it exits.
+fn launch_exe(filepath: &str, args: &[String], cwd: Option<&str>, options: &LaunchOptions) -> Result<(), String> {
+    let console = options.console;
+    let directory = cwd.map(str::to_string).unwrap_or_else(|| parent_dir(filepath));
+    if console == ConsoleMode::Terminal {
+        return launch_in_console(filepath, args, &directory, &options.terminal);
+    }
+
+    let mut command = Command::new(filepath);
+    command.args(args).current_dir(&directory);
+    if is_console_exe(filepath) {
+        if console == ConsoleMode::Hidden {
+            no_window(&mut command).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        } else {
+            new_console(&mut command);
+        }
+    }
+    command
         .spawn()
         .map_err(|e| {
             if e.kind() == std::io::ErrorKind::PermissionDenied {
@@ -55,12 +275,297 @@ fn launch_exe(filepath: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Open a .lnk shortcut using the Windows shell.
+/// Settings that affect how a file is launched, read before launching it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchOptions {
+    pub console: ConsoleMode,
+    /// Template from [`file_manager_command`] used to open folders.
+    pub file_manager: Option<String>,
+    /// What the entry's path is; a custom entry may hold a URL or a command.
+    pub kind: EntryKind,
+    /// Whether the user confirmed a [`EntryKind::Command`] entry when adding it.
+    pub command_confirmed: bool,
+    /// Browser to open a web URL in instead of the default one, e.g. the
+    /// one a bookmark came from.
+    pub browser: Option<PathBuf>,
+    /// Where [`ConsoleMode::Terminal`] runs a console program.
+    pub terminal: TerminalPreference,
+}
+
+/// What an entry's `filepath` holds. Indexed entries are always files;
+/// custom entries (see `custom_entries`) can be any kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    #[default]
+    File,
+    /// Opened in the default browser.
+    Url,
+    /// A command line, e.g. `powershell -File deploy.ps1`.
+    Command,
+}
+
+impl EntryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntryKind::File => "file",
+            EntryKind::Url => "url",
+            EntryKind::Command => "command",
+        }
+    }
+
+    /// Parse a stored kind; anything unrecognized is `File`.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "url" => EntryKind::Url,
+            "command" => EntryKind::Command,
+            _ => EntryKind::File,
+        }
+    }
+}
+
+/// Run a custom entry's command line from the user's home folder. The
+/// program is looked up on PATH unless given as a path, and its arguments
+/// are passed as with [`launch_with_args`].
+fn run_command(command_line: &str, options: &LaunchOptions) -> Result<(), String> {
+    if !options.command_confirmed {
+        return Err(format!("Command '{}' was never confirmed; edit the entry to confirm it", command_line));
+    }
+    let mut parts = split_command_line(&expand_env_vars(command_line)).into_iter();
+    let program = parts.next().ok_or_else(|| "Command cannot be empty".to_string())?;
+    let args: Vec<String> = parts.collect();
+    let program = if program.contains(['\\', '/']) {
+        PathBuf::from(&program)
+    } else {
+        find_on_path(&program)
+            .or_else(|| find_on_path(&format!("{}.exe", program)))
+            .ok_or_else(|| format!("Program not found on PATH: {}", program))?
+    };
+    let home = dirs::home_dir().map(|dir| dir.to_string_lossy().to_string());
+
+    info!("Running command: {}", command_line);
+    let file_options = LaunchOptions {
+        kind: EntryKind::File,
+        ..options.clone()
+    };
+    launch_with_args(&program.to_string_lossy(), &args, home.as_deref(), &file_options)
+}
+
+/// How a console program's window is handled when it is launched; stored
+/// per entry (see `Database::set_console_mode`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleMode {
+    /// Run console programs in a console window of their own that closes
+    /// when they exit; GUI programs are unaffected.
+    #[default]
+    Auto,
+    /// Run console programs without a window, for background tools whose
+    /// console would only flash up.
+    Hidden,
+    /// Run in a console window that stays open after the program exits,
+    /// for tools like `ffmpeg.exe` whose output is the point.
+    Terminal,
+}
+
+impl ConsoleMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConsoleMode::Auto => "auto",
+            ConsoleMode::Hidden => "hidden",
+            ConsoleMode::Terminal => "terminal",
+        }
+    }
+
+    /// Parse a stored mode; anything unrecognized is `Auto`.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "hidden" => ConsoleMode::Hidden,
+            "terminal" => ConsoleMode::Terminal,
+            _ => ConsoleMode::Auto,
+        }
+    }
+}
+
+/// Keep a helper program (`shutdown.exe`, ...) from flashing a console window.
+#[cfg(windows)]
+pub(crate) fn no_window(command: &mut Command) -> &mut Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    command.creation_flags(CREATE_NO_WINDOW)
+}
+
+#[cfg(not(windows))]
+pub(crate) fn no_window(command: &mut Command) -> &mut Command {
+    command
+}
+
+/// Give a console program a new console instead of the launcher's own,
+/// which it would inherit when AnCheck was started from a terminal.
+#[cfg(windows)]
+fn new_console(command: &mut Command) -> &mut Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+    command.creation_flags(CREATE_NEW_CONSOLE)
+}
+
+#[cfg(not(windows))]
+fn new_console(command: &mut Command) -> &mut Command {
+    command
+}
+
+/// Bytes read from the start of an .exe to find its PE header.
+const PE_HEADER_BYTES: u64 = 4096;
+const IMAGE_SUBSYSTEM_WINDOWS_CUI: u16 = 3;
+
+/// Whether `filepath` is a console-subsystem executable.
+fn is_console_exe(filepath: &str) -> bool {
+    let mut header = Vec::new();
+    std::fs::File::open(filepath)
+        .and_then(|file| file.take(PE_HEADER_BYTES).read_to_end(&mut header))
+        .is_ok()
+        && pe_subsystem(&header) == Some(IMAGE_SUBSYSTEM_WINDOWS_CUI)
+}
+
+/// The `Subsystem` field of a PE image's optional header.
+fn pe_subsystem(header: &[u8]) -> Option<u16> {
+    if header.get(..2)? != b"MZ" {
+        return None;
+    }
+    let pe = u32::from_le_bytes(header.get(0x3C..0x40)?.try_into().ok()?) as usize;
+    if header.get(pe..pe.checked_add(4)?)? != b"PE\0\0" {
+        return None;
+    }
+    // After the signature and the 20-byte COFF header; the offset is the
+    // same in PE32 and PE32+ optional headers
+    let at = pe + 4 + 20 + 68;
+    Some(u16::from_le_bytes(header.get(at..at + 2)?.try_into().ok()?))
+}
+
+/// Command line for `cmd /K`: cmd strips the outer quotes of the command it
+/// is given, so the whole line is quoted once more.
+fn console_command_line(filepath: &str, args: &[String]) -> String {
+    let mut line = quote_argument(filepath);
+    if !args.is_empty() {
+        line.push(' ');
+        line.push_str(&join_arguments(args));
+    }
+    format!("\"{}\"", line)
+}
+
+/// Run a console program in the preferred terminal (see
+/// [`console_launch`]), in a window that stays open once the program exits.
+#[cfg(windows)]
+fn launch_in_console(
+    filepath: &str,
+    args: &[String],
+    directory: &str,
+    preference: &TerminalPreference,
+) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+    let has_wt = find_on_path("wt.exe").is_some();
+    let launch = console_launch(preference, filepath, args, directory, has_wt);
+    let mut command = Command::new(&launch.program);
+    command.args(&launch.args);
+    if let Some(tail) = &launch.raw_tail {
+        command.raw_arg(tail);
+    }
+    command
+        .current_dir(directory)
+        .creation_flags(CREATE_NEW_CONSOLE)
+        .spawn()
+        .map_err(|e| format!("Failed to open a terminal for '{}': {}", filepath, e))?;
+
+    info!("Launched in terminal ({}): {}", launch.program, filepath);
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn launch_in_console(
+    filepath: &str,
+    _args: &[String],
+    _directory: &str,
+    _preference: &TerminalPreference,
+) -> Result<(), String> {
+    Err(format!("Can't open a terminal for '{}': not supported on this platform", filepath))
+}
+
+/// A console program started through a terminal, see [`console_launch`].
+#[derive(Debug, PartialEq)]
+struct ConsoleLaunch {
+    program: String,
+    args: Vec<String>,
+    /// Passed after `args` as is, since cmd quotes its `/K` line in its own
+    /// way (see [`console_command_line`]).
+    raw_tail: Option<String>,
+}
+
+/// How to run `filepath` in the preferred terminal and keep it open after the
+/// program exits: Windows Terminal and cmd through `cmd /K`, PowerShell
+/// with `-NoExit`. A custom command line only says how to open the
+/// terminal, not how to run something in it, so that case uses cmd.
+fn console_launch(
+    preference: &TerminalPreference,
+    filepath: &str,
+    args: &[String],
+    dir: &str,
+    has_windows_terminal: bool,
+) -> ConsoleLaunch {
+    match preference {
+        TerminalPreference::Auto if has_windows_terminal => windows_terminal_console(filepath, args, dir),
+        TerminalPreference::WindowsTerminal => windows_terminal_console(filepath, args, dir),
+        TerminalPreference::Auto | TerminalPreference::PowerShell => {
+            let mut script = format!("& {}", powershell_quote(filepath));
+            for arg in args {
+                script.push(' ');
+                script.push_str(&powershell_quote(arg));
+            }
+            ConsoleLaunch {
+                program: "powershell.exe".to_string(),
+                args: vec!["-NoExit".to_string(), "-Command".to_string(), script],
+                raw_tail: None,
+            }
+        }
+        TerminalPreference::Cmd | TerminalPreference::Custom { .. } => ConsoleLaunch {
+            program: "cmd.exe".to_string(),
+            args: vec!["/K".to_string()],
+            raw_tail: Some(console_command_line(filepath, args)),
+        },
+    }
+}
+
+/// `wt.exe -d <dir> cmd.exe /K <line>`. Windows Terminal reads `;` as a
+/// separator between its own commands, so ones in the line are escaped.
+fn windows_terminal_console(filepath: &str, args: &[String], dir: &str) -> ConsoleLaunch {
+    ConsoleLaunch {
+        program: "wt.exe".to_string(),
+        args: ["-d", dir, "cmd.exe", "/K"].iter().map(|a| a.to_string()).collect(),
+        raw_tail: Some(console_command_line(filepath, args).replace(';', "\\;")),
+    }
+}
+
+/// A PowerShell single-quoted string, in which only `'` needs doubling.
+fn powershell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Open a .lnk shortcut using the Windows shell, which resolves its target.
 fn launch_shortcut(filepath: &str) -> Result<(), String> {
-    // Use cmd /c start to handle .lnk files properly
     shell_open(filepath)
 }
 
+/// Open a directory with the configured file manager, or Explorer.
+fn open_folder(filepath: &str, file_manager: Option<&str>) -> Result<(), String> {
+    if try_file_manager(file_manager, filepath, filepath) {
+        return Ok(());
+    }
+    open_in_explorer(filepath)
+}
+
 /// Open a directory in Windows Explorer.
 fn open_in_explorer(filepath: &str) -> Result<(), String> {
     Command::new("explorer.exe")
@@ -72,42 +577,1199 @@ fn open_in_explorer(filepath: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Use Windows ShellExecute (via cmd start) to open a file with its default handler.
+/// Open a file with its default handler through ShellExecuteW.
+/// This handles .lnk, .url, documents, and any registered file types. The path
+/// goes to the shell as-is, so `&`, `^`, `%` and parentheses need no escaping.
 fn shell_open(filepath: &str) -> Result<(), String> {
-    // Use PowerShell's Start-Process for reliable ShellExecute behavior.
-    // This handles .lnk, .url, documents, and any registered file types.
-    Command::new("cmd")
-        .args(["/C", "start", "", filepath])
+    shell_execute("open", filepath, None, &parent_dir(filepath))
+}
+
+fn parent_dir(filepath: &str) -> String {
+    Path::new(filepath)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// The program and parameters to run elevated for `filepath`: `.msi`
+/// packages go through msiexec, everything else is run directly.
+fn elevated_command(filepath: &str) -> (String, Option<String>) {
+    let is_msi = Path::new(filepath)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("msi"));
+    if is_msi {
+        let parameters = format!("/i {}", quote_argument(filepath));
+        ("msiexec.exe".to_string(), Some(parameters))
+    } else {
+        (filepath.to_string(), None)
+    }
+}
+
+/// Quote one command-line argument so the program's argv parser (the
+/// `CommandLineToArgvW` rules) reads it back unchanged. Backslashes only
+/// need doubling before a quote, including the closing one.
+fn quote_argument(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                backslashes = 0;
+            }
+        }
+        if c != '\\' {
+            quoted.push(c);
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Quote and join arguments into a single command line.
+fn join_arguments(args: &[String]) -> String {
+    args.iter().map(|a| quote_argument(a)).collect::<Vec<_>>().join(" ")
+}
+
+// ShellExecute return values at or below 32 (shellapi.h / winerror.h)
+const SE_ERR_OUT_OF_MEMORY: isize = 0;
+const SE_ERR_FNF: isize = 2;
+const SE_ERR_PNF: isize = 3;
+const SE_ERR_ACCESSDENIED: isize = 5;
+const SE_ERR_OOM: isize = 8;
+const SE_ERR_BAD_FORMAT: isize = 11;
+const SE_ERR_SHARE: isize = 26;
+const SE_ERR_ASSOCINCOMPLETE: isize = 27;
+const SE_ERR_NOASSOC: isize = 31;
+
+/// User-facing message for a failed ShellExecute return code.
+fn shell_error_message(code: isize, file: &str) -> String {
+    let key = match code {
+        SE_ERR_FNF | SE_ERR_PNF => "error.file_not_found",
+        SE_ERR_ACCESSDENIED => "error.permission_denied",
+        SE_ERR_NOASSOC | SE_ERR_ASSOCINCOMPLETE => "error.no_association",
+        SE_ERR_SHARE => "error.in_use",
+        SE_ERR_BAD_FORMAT => "error.bad_format",
+        SE_ERR_OUT_OF_MEMORY | SE_ERR_OOM => "error.out_of_memory",
+        _ => "error.open_failed",
+    };
+    t_with(key, &[("file", &file), ("code", &code)])
+}
+
+/// Launch a file with administrator rights through the `runas` verb, so the
+/// UAC prompt appears. `.msi` packages are installed by an elevated msiexec.
+pub fn launch_elevated(filepath: &str) -> Result<(), String> {
+    let expanded = expand_env_vars(filepath);
+    let filepath = expanded.as_ref();
+    let path = Path::new(filepath);
+    if !path.exists() {
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+
+    info!("Launching elevated: {}", filepath);
+    let (program, parameters) = elevated_command(filepath);
+    shell_execute("runas", &program, parameters.as_deref(), &parent_dir(filepath))
+}
+
+/// Run `verb` on `file` through ShellExecuteW, with no intermediate shell.
+/// A declined UAC prompt comes back as a short message rather than an error;
+/// other failures are described by [`shell_error_message`].
+#[cfg(windows)]
+pub(crate) fn shell_execute(verb: &str, file: &str, parameters: Option<&str>, directory: &str) -> Result<(), String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::{GetLastError, ERROR_CANCELLED, HWND};
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let verb = HSTRING::from(verb);
+    let wide_file = HSTRING::from(file);
+    let parameters = parameters.map(HSTRING::from);
+    let directory = HSTRING::from(directory);
+
+    // SAFETY: every string outlives the call and is null-terminated by HSTRING.
+    let (code, last_error) = unsafe {
+        let instance = ShellExecuteW(
+            HWND::default(),
+            &verb,
+            &wide_file,
+            parameters.as_ref().map_or(PCWSTR::null(), |p| PCWSTR(p.as_ptr())),
+            &directory,
+            SW_SHOWNORMAL,
+        );
+        (instance.0 as isize, GetLastError())
+    };
+
+    // Values above 32 mean success
+    if code > 32 {
+        info!("Shell executed ({}): {}", verb, file);
+        return Ok(());
+    }
+    if last_error == ERROR_CANCELLED {
+        info!("Elevation cancelled for: {}", file);
+        return Err(crate::i18n::t("error.elevation_cancelled").to_string());
+    }
+    error!("ShellExecute ({}) failed for '{}' with code {}", verb, file, code);
+    Err(shell_error_message(code, file))
+}
+
+#[cfg(not(windows))]
+pub(crate) fn shell_execute(_verb: &str, file: &str, _parameters: Option<&str>, _directory: &str) -> Result<(), String> {
+    Err(format!("Failed to open '{}': the Windows shell is not available", file))
+}
+
+/// Move a file or directory to the Recycle Bin, so it can be restored.
+pub fn move_to_recycle_bin(filepath: &str) -> Result<(), String> {
+    if !Path::new(filepath).exists() {
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+    recycle(filepath)?;
+    info!("Moved to Recycle Bin: {}", filepath);
+    Ok(())
+}
+
+/// SHFileOperationW with `FOF_ALLOWUNDO`, which recycles instead of deleting.
+/// No progress or error dialogs are shown; the result code is turned into a
+/// message instead. Explorer still warns before permanently deleting from a
+/// drive that has no Recycle Bin.
+#[cfg(windows)]
+fn recycle(filepath: &str) -> Result<(), String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::UI::Shell::{
+        SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT,
+        FOF_WANTNUKEWARNING, FO_DELETE, SHFILEOPSTRUCTW,
+    };
+
+    // pFrom is a list of paths, so it ends with an extra null
+    let from: Vec<u16> = filepath.encode_utf16().chain([0, 0]).collect();
+    let flags = FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT | FOF_WANTNUKEWARNING;
+    let mut operation = SHFILEOPSTRUCTW {
+        wFunc: FO_DELETE,
+        pFrom: PCWSTR(from.as_ptr()),
+        fFlags: flags.0 as u16,
+        ..Default::default()
+    };
+
+    // SAFETY: `from` outlives the call and is double-null-terminated.
+    let code = unsafe { SHFileOperationW(&mut operation) };
+    if code != 0 {
+        error!("SHFileOperation failed for '{}' with code {:#x}", filepath, code);
+        return Err(recycle_error_message(code, filepath));
+    }
+    if operation.fAnyOperationsAborted.as_bool() {
+        return Err(t_with("error.not_deleted", &[("file", &filepath)]));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn recycle(filepath: &str) -> Result<(), String> {
+    Err(format!("Can't recycle '{}': the Recycle Bin is only available on Windows", filepath))
+}
+
+/// User-facing message for a failed SHFileOperation result, which is either
+/// a Win32 error or one of the legacy `DE_*` codes.
+fn recycle_error_message(code: i32, filepath: &str) -> String {
+    const ERROR_FILE_NOT_FOUND: i32 = 2;
+    const ERROR_PATH_NOT_FOUND: i32 = 3;
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    const DE_ACCESSDENIEDSRC: i32 = 0x78;
+    const DE_INVALIDFILES: i32 = 0x7C;
+
+    let key = match code {
+        ERROR_ACCESS_DENIED | DE_ACCESSDENIEDSRC => "error.delete_denied",
+        ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION => "error.delete_in_use",
+        ERROR_FILE_NOT_FOUND | ERROR_PATH_NOT_FOUND | DE_INVALIDFILES => "error.file_not_found",
+        _ => "error.recycle_failed",
+    };
+    t_with(key, &[("file", &filepath), ("code", &format!("{:#x}", code))])
+}
+
+/// Which terminal "Open in terminal here" starts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TerminalPreference {
+    /// Windows Terminal if `wt.exe` is on PATH, otherwise PowerShell.
+    #[default]
+    Auto,
+    WindowsTerminal,
+    PowerShell,
+    Cmd,
+    /// A command line where `{dir}` is replaced with the directory, e.g.
+    /// `alacritty --working-directory {dir}`.
+    Custom { command: String },
+}
+
+const TERMINAL_KEY: &str = "terminal_preference";
+
+/// The persisted terminal preference, or [`TerminalPreference::Auto`] if unset.
+pub fn terminal_preference(db: &Database) -> TerminalPreference {
+    db.get_meta(TERMINAL_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the terminal preference. A custom command must not be empty.
+pub fn set_terminal_preference(db: &Database, preference: &TerminalPreference) -> Result<(), String> {
+    if let TerminalPreference::Custom { command } = preference {
+        if split_command_line(command).is_empty() {
+            return Err("Terminal command cannot be empty".to_string());
+        }
+    }
+    let json = serde_json::to_string(preference).map_err(|e| e.to_string())?;
+    db.set_meta(TERMINAL_KEY, &json)
+        .map_err(|e| format!("Failed to save terminal preference: {}", e))
+}
+
+/// Open a terminal in `filepath`, or in its folder if it is a file.
+pub fn open_terminal_at(filepath: &str, preference: &TerminalPreference) -> Result<(), String> {
+    let path = Path::new(filepath);
+    if !path.exists() {
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+    let dir = if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        containing_folder(path)
+    };
+
+    let has_wt = find_on_path("wt.exe").is_some();
+    let (program, args) = terminal_command(preference, &dir.to_string_lossy(), has_wt)?;
+    // Starting in the directory (rather than a `cd` in the shell's own
+    // syntax) keeps spaces and non-ASCII characters out of any quoting
+    Command::new(&program)
+        .args(&args)
+        .current_dir(&dir)
         .spawn()
         .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                warn!("Permission denied opening '{}', attempting elevated launch", filepath);
-                format!(
-                    "Permission denied: '{}'. This file may require administrator privileges.",
-                    filepath
-                )
-            } else {
-                error!("Failed to shell open '{}': {}", filepath, e);
-                format!("Failed to open '{}': {}", filepath, e)
-            }
+            error!("Failed to start terminal {} {:?}: {}", program, args, e);
+            format!("Failed to open terminal '{}': {}", program, e)
         })?;
 
-    info!("Shell opened: {}", filepath);
+    info!("Opened terminal ({}) in: {}", program, dir.display());
     Ok(())
 }
 
-/// Open the containing folder of a file in Explorer, with the file selected.
-pub fn open_containing_folder(filepath: &str) -> Result<(), String> {
+/// The program and arguments for a terminal in `dir`. Each starts in the
+/// directory as its working directory; Windows Terminal also needs `-d`,
+/// since its profiles may set their own starting directory.
+fn terminal_command(
+    preference: &TerminalPreference,
+    dir: &str,
+    has_windows_terminal: bool,
+) -> Result<(String, Vec<String>), String> {
+    let command = |program: &str, args: &[&str]| {
+        (program.to_string(), args.iter().map(|a| a.to_string()).collect())
+    };
+    Ok(match preference {
+        TerminalPreference::Auto if has_windows_terminal => command("wt.exe", &["-d", dir]),
+        TerminalPreference::WindowsTerminal => command("wt.exe", &["-d", dir]),
+        TerminalPreference::Auto | TerminalPreference::PowerShell => command("powershell.exe", &["-NoExit"]),
+        TerminalPreference::Cmd => command("cmd.exe", &["/K"]),
+        TerminalPreference::Custom { command } => {
+            // Substituted after splitting, so a directory with spaces stays one argument
+            let mut parts = split_command_line(command)
+                .into_iter()
+                .map(|part| part.replace("{dir}", dir));
+            let program = parts
+                .next()
+                .ok_or_else(|| "Terminal command cannot be empty".to_string())?;
+            (program, parts.collect())
+        }
+    })
+}
+
+/// Split a command line on whitespace; double quotes group words (and are removed).
+fn split_command_line(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_part = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_part = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_part {
+                    parts.push(std::mem::take(&mut current));
+                    has_part = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_part = true;
+            }
+        }
+    }
+    if has_part {
+        parts.push(current);
+    }
+    parts
+}
+
+/// First `program` found in a PATH directory.
+pub(crate) fn find_on_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Show the Windows "Open with" chooser for a file. It runs in its own
+/// rundll32 process, so it stays open after the launcher window hides.
+pub fn open_with_dialog(filepath: &str) -> Result<(), String> {
     let path = Path::new(filepath);
     if !path.exists() {
-        return Err(format!("File not found: {}", filepath));
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+    if path.is_dir() {
+        return Err(format!("Can't choose an app for a folder: {}", filepath));
     }
 
-    Command::new("explorer.exe")
-        .args(["/select,", filepath])
+    spawn_open_with(filepath).map_err(|e| {
+        error!("Failed to show Open With for '{}': {}", filepath, e);
+        format!("Failed to show Open With for '{}': {}", filepath, e)
+    })?;
+    info!("Showed Open With for: {}", filepath);
+    Ok(())
+}
+
+#[cfg(windows)]
+fn spawn_open_with(filepath: &str) -> std::io::Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    // OpenAs_RunDLL takes the rest of the command line verbatim as the path,
+    // so it must not be quoted like a normal argument
+    Command::new("rundll32.exe")
+        .raw_arg(format!("shell32.dll,OpenAs_RunDLL {}", filepath))
         .spawn()
-        .map_err(|e| format!("Failed to open containing folder: {}", e))?;
+        .map(|_| ())
+}
+
+#[cfg(not(windows))]
+fn spawn_open_with(_filepath: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the Open With dialog is only available on Windows",
+    ))
+}
+
+/// How long the properties thread keeps pumping messages after the sheet is
+/// requested. The shell creates the sheet on a thread of its own, but the
+/// requesting thread has to stay alive (with a message loop) until it has.
+const PROPERTIES_GRACE: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Show the standard Properties sheet for a file or folder.
+///
+/// The request is made from a dedicated thread so the sheet doesn't depend on
+/// the calling task or the launcher window, which hides right after.
+pub fn show_file_properties(filepath: &str) -> Result<(), String> {
+    if !Path::new(filepath).exists() {
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+
+    let (done, result) = std::sync::mpsc::channel();
+    let path = filepath.to_string();
+    std::thread::Builder::new()
+        .name("properties-sheet".to_string())
+        .spawn(move || request_properties(&path, done))
+        .map_err(|e| format!("Failed to show properties: {}", e))?;
+    result
+        .recv()
+        .map_err(|_| "Failed to show properties: the request was dropped".to_string())??;
+
+    info!("Showed properties for: {}", filepath);
+    Ok(())
+}
+
+/// ShellExecuteExW with the "properties" verb, reporting the outcome on `done`
+/// and then pumping messages for [`PROPERTIES_GRACE`].
+#[cfg(windows)]
+fn request_properties(filepath: &str, done: std::sync::mpsc::Sender<Result<(), String>>) {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+    use windows::Win32::UI::Shell::{
+        ShellExecuteExW, SEE_MASK_FLAG_NO_UI, SEE_MASK_INVOKEIDLIST, SHELLEXECUTEINFOW,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE, SW_SHOW,
+    };
+
+    let verb = HSTRING::from("properties");
+    let file = HSTRING::from(filepath);
+    // SAFETY: the strings outlive the call; COM is only uninitialized if
+    // this thread initialized it.
+    unsafe {
+        let com = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let mut info = SHELLEXECUTEINFOW {
+            cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+            fMask: SEE_MASK_INVOKEIDLIST | SEE_MASK_FLAG_NO_UI,
+            lpVerb: PCWSTR(verb.as_ptr()),
+            lpFile: PCWSTR(file.as_ptr()),
+            nShow: SW_SHOW.0,
+            ..Default::default()
+        };
+        // On failure hInstApp holds the same SE_ERR_* code ShellExecuteW returns
+        let result = ShellExecuteExW(&mut info).map_err(|e| {
+            error!("Properties failed for '{}': {}", filepath, e);
+            shell_error_message(info.hInstApp.0 as isize, filepath)
+        });
+        let shown = result.is_ok();
+        let _ = done.send(result);
 
+        if shown {
+            let deadline = std::time::Instant::now() + PROPERTIES_GRACE;
+            let mut msg = MSG::default();
+            while std::time::Instant::now() < deadline {
+                while PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(15));
+            }
+        }
+        if com.is_ok() {
+            CoUninitialize();
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn request_properties(filepath: &str, done: std::sync::mpsc::Sender<Result<(), String>>) {
+    let _ = done.send(Err(format!(
+        "Can't show properties for '{}': the Windows shell is not available",
+        filepath
+    )));
+}
+
+/// Open the containing folder of a file in Explorer, with the file selected.
+/// A directory is selected inside its parent too, rather than opened. If
+/// Explorer can't select the item, the parent folder is opened instead.
+///
+/// With a `file_manager` template, that file manager is opened at the
+/// containing folder instead, falling back to Explorer if it can't be started.
+pub fn open_containing_folder(filepath: &str, file_manager: Option<&str>) -> Result<(), String> {
+    let expanded = expand_env_vars(filepath);
+    let filepath = expanded.as_ref();
+    let path = Path::new(filepath);
+    if !path.exists() {
+        return Err(t_with("error.file_not_found", &[("file", &filepath)]));
+    }
+    if try_file_manager(file_manager, &containing_folder(path).to_string_lossy(), filepath) {
+        info!("Opened containing folder for: {}", filepath);
+        return Ok(());
+    }
+
+    // The fallback goes through the shell too, since Explorer's own command
+    // line splits on commas
+    reveal(path, select_in_explorer, |folder| shell_open(&folder.to_string_lossy()))?;
     info!("Opened containing folder for: {}", filepath);
     Ok(())
 }
+
+/// Open the folder a .lnk shortcut points into, with its target selected
+/// ("Open target location").
+pub fn open_shortcut_target_location(filepath: &str, file_manager: Option<&str>) -> Result<(), String> {
+    let target = shortcut_target(Path::new(filepath))?;
+    open_containing_folder(&target.to_string_lossy(), file_manager)
+}
+
+const FILE_MANAGER_KEY: &str = "file_manager_command";
+
+/// The persisted file manager template, or `None` to use Explorer.
+pub fn file_manager_command(db: &Database) -> Option<String> {
+    db.get_meta(FILE_MANAGER_KEY)
+        .ok()
+        .flatten()
+        .filter(|command| !command.trim().is_empty())
+}
+
+/// Persist the file manager template: a command line where `{dir}` is
+/// replaced with the folder to open and `{file}` with the file to select,
+/// e.g. `"C:\totalcmd\TOTALCMD64.EXE" /O /T "{dir}"`. Many file managers
+/// can't select a file, so `{file}` may be ignored by them; when a folder
+/// itself is opened, `{file}` is that folder. An empty template restores
+/// Explorer.
+pub fn set_file_manager_command(db: &Database, command: &str) -> Result<(), String> {
+    db.set_meta(FILE_MANAGER_KEY, command.trim())
+        .map_err(|e| format!("Failed to save file manager: {}", e))
+}
+
+/// Run the file manager `template` for `dir` and `file`. Returns false,
+/// after logging why, if there is no template or it couldn't be started,
+/// so the caller falls back to Explorer.
+fn try_file_manager(template: Option<&str>, dir: &str, file: &str) -> bool {
+    let Some(template) = template else {
+        return false;
+    };
+    match run_file_manager(template, dir, file) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("{}; using Explorer instead", e);
+            false
+        }
+    }
+}
+
+fn run_file_manager(template: &str, dir: &str, file: &str) -> Result<(), String> {
+    let (program, args) = file_manager_invocation(template, dir, file)
+        .ok_or_else(|| "File manager command is empty".to_string())?;
+    if !program_exists(&program) {
+        return Err(format!("File manager not found: {}", program));
+    }
+    Command::new(&program)
+        .args(&args)
+        .spawn()
+        .map_err(|e| format!("Failed to start file manager '{}': {}", program, e))?;
+
+    info!("Opened {} in file manager: {}", dir, program);
+    Ok(())
+}
+
+/// Program and arguments from a file manager template. Placeholders are
+/// substituted after splitting, so paths with spaces stay one argument.
+fn file_manager_invocation(template: &str, dir: &str, file: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = split_command_line(template)
+        .into_iter()
+        .map(|part| part.replace("{dir}", dir).replace("{file}", file));
+    let program = parts.next()?;
+    Some((program, parts.collect()))
+}
+
+/// Whether `program` is an existing file, or a bare name found on PATH.
+fn program_exists(program: &str) -> bool {
+    let path = Path::new(program);
+    if path.components().count() > 1 || path.is_absolute() {
+        return path.is_file();
+    }
+    find_on_path(program).is_some() || find_on_path(&format!("{}.exe", program)).is_some()
+}
+
+/// Folder to open when `path` can't be selected: its parent, or the path
+/// itself for a drive root.
+fn containing_folder(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Select `path` with `select`, falling back to opening its containing
+/// folder with `open`. A drive root has nothing to be selected in, so it is
+/// opened directly.
+fn reveal(
+    path: &Path,
+    select: impl FnOnce(&Path) -> Result<(), String>,
+    open: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    let folder = containing_folder(path);
+    if folder == path {
+        return open(&folder);
+    }
+    select(path).or_else(|e| {
+        warn!("Couldn't select '{}' in Explorer ({}), opening its folder", path.display(), e);
+        open(&folder)
+    })
+}
+
+/// Open Explorer at `item`'s parent with `item` selected, through
+/// SHOpenFolderAndSelectItems. The path is parsed into an item ID list rather
+/// than passed on a command line, so commas and unusual characters survive.
+///
+/// The API needs COM initialized on the calling thread. It is initialized
+/// single-threaded here and released afterwards, unless the thread already
+/// joined another apartment (`RPC_E_CHANGED_MODE`), which still works.
+#[cfg(windows)]
+fn select_in_explorer(item: &Path) -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+    use windows::Win32::UI::Shell::{ILCreateFromPathW, ILFree, SHOpenFolderAndSelectItems};
+
+    let wide = HSTRING::from(item);
+    // SAFETY: the path string outlives ILCreateFromPathW, the ID list is
+    // freed exactly once, and COM is only uninitialized if we initialized it.
+    unsafe {
+        let com = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let pidl = ILCreateFromPathW(&wide);
+        let result = if pidl.is_null() {
+            Err(format!("Couldn't resolve '{}'", item.display()))
+        } else {
+            // With no child items, the ID list itself is the item to select
+            let selected = SHOpenFolderAndSelectItems(pidl, None, 0).map_err(|e| e.to_string());
+            ILFree(Some(pidl));
+            selected
+        };
+        if com.is_ok() {
+            CoUninitialize();
+        }
+        result
+    }
+}
+
+#[cfg(not(windows))]
+fn select_in_explorer(item: &Path) -> Result<(), String> {
+    Err(format!("Can't select '{}': Explorer is not available", item.display()))
+}
+
+/// Load a .lnk shortcut into IShellLinkW and read from it with `read`,
+/// which fills `buffer`. Returns the nul-terminated text `read` left there.
+#[cfg(windows)]
+fn read_shortcut(
+    shortcut: &Path,
+    read: impl FnOnce(&windows::Win32::UI::Shell::IShellLinkW, &mut [u16]) -> windows::core::Result<()>,
+) -> Result<String, String> {
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, IPersistFile, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED, STGM_READ,
+    };
+    use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
+
+    let wide = HSTRING::from(shortcut);
+    let mut buffer = vec![0u16; 32768];
+    // SAFETY: the path string and buffer outlive the calls using them, and
+    // COM is only uninitialized if we initialized it.
+    let result = unsafe {
+        let com = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let result = (|| -> windows::core::Result<()> {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            link.cast::<IPersistFile>()?.Load(&wide, STGM_READ)?;
+            read(&link, buffer.as_mut_slice())
+        })();
+        if com.is_ok() {
+            CoUninitialize();
+        }
+        result
+    };
+    result.map_err(|e| format!("Couldn't read shortcut '{}': {}", shortcut.display(), e))?;
+
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Ok(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// Read the file a .lnk shortcut points at through IShellLinkW, without
+/// resolving (and possibly searching for) a moved target. Shortcuts to shell
+/// items such as Control Panel pages have no file target.
+#[cfg(windows)]
+pub(crate) fn shortcut_target(shortcut: &Path) -> Result<PathBuf, String> {
+    use windows::Win32::UI::Shell::SLGP_UNCPRIORITY;
+
+    // SAFETY: the find-data pointer may be null.
+    let target = read_shortcut(shortcut, |link, buffer| unsafe {
+        link.GetPath(buffer, std::ptr::null_mut(), SLGP_UNCPRIORITY.0 as u32)
+    })?;
+    if target.is_empty() {
+        return Err(format!("Shortcut '{}' has no file target", shortcut.display()));
+    }
+    Ok(PathBuf::from(target))
+}
+
+#[cfg(not(windows))]
+pub(crate) fn shortcut_target(shortcut: &Path) -> Result<PathBuf, String> {
+    Err(format!("Can't read shortcut '{}': not supported on this platform", shortcut.display()))
+}
+
+/// The comment of a .lnk shortcut (Explorer's "Comment" field), which app
+/// installers often set to the product's name; `None` when it has none.
+#[cfg(windows)]
+pub(crate) fn shortcut_description(shortcut: &Path) -> Result<Option<String>, String> {
+    // SAFETY: the buffer is as long as the call is told.
+    let description = read_shortcut(shortcut, |link, buffer| unsafe { link.GetDescription(buffer) })?;
+    let description = description.trim();
+    Ok((!description.is_empty()).then(|| description.to_string()))
+}
+
+#[cfg(not(windows))]
+pub(crate) fn shortcut_description(shortcut: &Path) -> Result<Option<String>, String> {
+    Err(format!("Can't read shortcut '{}': not supported on this platform", shortcut.display()))
+}
+
+/// What the detail pane shows about a result beyond its search row. Each
+/// field is read on its own, so one that can't be (access denied, a
+/// shortcut to nowhere) is left out without failing the rest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FileDetails {
+    pub filepath: String,
+    /// Exact size in bytes; `None` for folders.
+    pub size: Option<u64>,
+    #[serde(flatten)]
+    pub times: FileTimes,
+    /// The file a `.lnk` shortcut points at.
+    pub target_path: Option<String>,
+    /// Version resource of a program, or of a shortcut's target program.
+    pub version: Option<VersionInfo>,
+    /// Whether the program, or a shortcut's target, is running; `None` for
+    /// anything that isn't a program.
+    pub is_running: Option<bool>,
+}
+
+/// Strings from a program's version resource, as Explorer's Details tab
+/// shows them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VersionInfo {
+    pub product_name: Option<String>,
+    pub product_version: Option<String>,
+    pub file_description: Option<String>,
+    pub file_version: Option<String>,
+    pub company_name: Option<String>,
+}
+
+/// Gather [`FileDetails`] for `filepath`. Slow enough (a COM call for
+/// shortcuts, reading version resources, a process snapshot) that it's only
+/// done when a result is selected, never while searching.
+pub fn file_details(filepath: &str, processes: &ProcessCache) -> Result<FileDetails, String> {
+    let path = Path::new(filepath);
+    let metadata = std::fs::metadata(path).map_err(|_| t_with("error.file_not_found", &[("file", &filepath)]))?;
+    let target = match extension_of(path).as_str() {
+        "lnk" => shortcut_target(path)
+            .map_err(|e| warn!("No target for details of '{}': {}", filepath, e))
+            .ok(),
+        _ => None,
+    };
+    let program = program_path(path, target.as_deref());
+    let version = program.and_then(|program| {
+        version_info(program)
+            .map_err(|e| info!("No version info for '{}': {}", program.display(), e))
+            .ok()
+    });
+    let is_running = program.and_then(|program| program.file_name()).map(|exe_name| {
+        !processes::list_matching_processes(&processes.get(), &exe_name.to_string_lossy()).is_empty()
+    });
+
+    Ok(FileDetails {
+        filepath: filepath.to_string(),
+        size: metadata.is_file().then_some(metadata.len()),
+        times: indexer::file_times(&metadata),
+        target_path: target.map(|target| target.to_string_lossy().to_string()),
+        version,
+        is_running,
+    })
+}
+
+fn extension_of(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// The program behind a result: the file itself if it's an `.exe`, or the
+/// `.exe` a shortcut points at.
+fn program_path<'a>(path: &'a Path, shortcut_target: Option<&'a Path>) -> Option<&'a Path> {
+    let program = shortcut_target.unwrap_or(path);
+    (extension_of(program) == "exe").then_some(program)
+}
+
+/// Read the version resource of `program`, using its first language (or
+/// US English with Unicode, which nearly every program has).
+#[cfg(windows)]
+pub(crate) fn version_info(program: &Path) -> Result<VersionInfo, String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW};
+
+    let wide = HSTRING::from(program);
+    // SAFETY: the path string outlives the call.
+    let size = unsafe { GetFileVersionInfoSizeW(&wide, None) };
+    if size == 0 {
+        return Err("the file has no version resource".to_string());
+    }
+    let mut block = vec![0u8; size as usize];
+    // SAFETY: `block` is `size` bytes long, as the call is told.
+    unsafe { GetFileVersionInfoW(&wide, 0, size, block.as_mut_ptr().cast()) }.map_err(|e| e.to_string())?;
+
+    // Points into `block` for `len` units of the value's type
+    let query = |sub_block: &str| -> Option<(*const std::ffi::c_void, usize)> {
+        let mut value = std::ptr::null_mut();
+        let mut len = 0u32;
+        // SAFETY: `block` holds the resource read above and outlives the
+        // returned pointer's use below.
+        let found = unsafe { VerQueryValueW(block.as_ptr().cast(), &HSTRING::from(sub_block), &mut value, &mut len) };
+        (found.as_bool() && !value.is_null() && len > 0).then_some((value as *const _, len as usize))
+    };
+    let (language, code_page) = query(r"\VarFileInfo\Translation")
+        .filter(|&(_, len)| len >= 4)
+        // SAFETY: the translation table is pairs of u16, at least one of them.
+        .map(|(value, _)| unsafe { (*(value as *const u16), *(value as *const u16).add(1)) })
+        .unwrap_or((0x0409, 1200));
+    let string = |name: &str| {
+        let (value, len) = query(&format!(r"\StringFileInfo\{:04x}{:04x}\{}", language, code_page, name))?;
+        // SAFETY: string values are `len` UTF-16 units, the last one a nul.
+        let units = unsafe { std::slice::from_raw_parts(value as *const u16, len) };
+        let text = String::from_utf16_lossy(units);
+        let text = text.trim_end_matches('\0').trim();
+        (!text.is_empty()).then(|| text.to_string())
+    };
+
+    Ok(VersionInfo {
+        product_name: string("ProductName"),
+        product_version: string("ProductVersion"),
+        file_description: string("FileDescription"),
+        file_version: string("FileVersion"),
+        company_name: string("CompanyName"),
+    })
+}
+
+#[cfg(not(windows))]
+pub(crate) fn version_info(_program: &Path) -> Result<VersionInfo, String> {
+    Err("version resources are only read on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db(paths: &[&Path]) -> Database {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let rows: Vec<_> = paths
+            .iter()
+            .map(|p| {
+                let name = p.file_name().unwrap().to_string_lossy().to_string();
+                (name, p.to_string_lossy().to_string(), "txt".to_string(), 0, 0, "document".to_string())
+            })
+            .collect();
+        db.upsert_files_batch(&rows).unwrap();
+        db
+    }
+
+    fn click_count(db: &Database, filepath: &str) -> Option<i64> {
+        db.load_compact_entries()
+            .unwrap()
+            .into_iter()
+            .find(|e| &*e.filepath == filepath)
+            .map(|e| e.click_count)
+    }
+
+    #[test]
+    fn test_launch_records_click_only_on_success() {
+        let dir = std::env::temp_dir().join(format!("ancheck-launch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("present.txt");
+        std::fs::write(&file, "x").unwrap();
+        let filepath = file.to_string_lossy().to_string();
+        let db = temp_db(&[&file]);
+
+        assert!(!record_launch_outcome(&db, &filepath, Some("pres"), Some("slack.exe"), &Ok(())));
+        assert_eq!(click_count(&db, &filepath), Some(1));
+        assert_eq!(db.query_picks_for("pres").unwrap().len(), 1);
+        assert_eq!(db.context_picks_for("slack.exe").unwrap()[0].pick_count, 1);
+
+        // A failure for a file that still exists (e.g. access denied) changes nothing
+        let denied = Err("Permission denied".to_string());
+        assert!(!record_launch_outcome(&db, &filepath, Some("pres"), Some("slack.exe"), &denied));
+        assert_eq!(click_count(&db, &filepath), Some(1));
+        assert_eq!(db.context_picks_for("slack.exe").unwrap()[0].pick_count, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_launch_of_missing_file_removes_entry() {
+        let missing = std::env::temp_dir().join("ancheck-launch-missing").join("gone.txt");
+        let filepath = missing.to_string_lossy().to_string();
+        let db = temp_db(&[&missing]);
+
+        let result = Err(format!("File not found: {}", filepath));
+        assert!(record_launch_outcome(&db, &filepath, None, None, &result));
+        assert_eq!(click_count(&db, &filepath), None);
+        // Nothing left to remove the second time
+        assert!(!record_launch_outcome(&db, &filepath, None, None, &result));
+    }
+
+    #[test]
+    fn test_stale_check_expands_variables() {
+        let dir = std::env::temp_dir().join(format!("ancheck-expand-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tool.exe"), "x").unwrap();
+        let var = format!("ANCHECK_TEST_DIR_{}", std::process::id());
+        std::env::set_var(&var, &dir);
+
+        let stored = format!("%{}%{}tool.exe", var, std::path::MAIN_SEPARATOR);
+        let db = temp_db(&[Path::new(&stored)]);
+        let denied = Err("Permission denied".to_string());
+        assert!(!record_launch_outcome(&db, &stored, None, None, &denied));
+        assert_eq!(db.remove_missing_files(&[]).unwrap(), 0);
+        assert_eq!(click_count(&db, &stored), Some(0));
+
+        std::env::remove_var(&var);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_console_launch_follows_terminal_preference() {
+        let (exe, dir) = (r"C:\Tools\ffmpeg.exe", r"C:\Videos");
+        let args = vec!["-i".to_string(), "it's; here.mp4".to_string()];
+        let strings = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        let cmd = console_launch(&TerminalPreference::Cmd, exe, &args, dir, true);
+        assert_eq!(cmd.program, "cmd.exe");
+        assert_eq!(cmd.args, strings(&["/K"]));
+        assert_eq!(cmd.raw_tail, Some(console_command_line(exe, &args)));
+        // A custom terminal can't be told what to run
+        let custom = TerminalPreference::Custom {
+            command: "alacritty --working-directory {dir}".to_string(),
+        };
+        assert_eq!(console_launch(&custom, exe, &args, dir, true), cmd);
+
+        let wt = console_launch(&TerminalPreference::Auto, exe, &args, dir, true);
+        assert_eq!(wt.program, "wt.exe");
+        assert_eq!(wt.args, strings(&["-d", dir, "cmd.exe", "/K"]));
+        assert_eq!(wt.raw_tail.unwrap(), r#""C:\Tools\ffmpeg.exe -i "it's\; here.mp4"""#);
+        assert_eq!(
+            console_launch(&TerminalPreference::WindowsTerminal, exe, &[], dir, false).program,
+            "wt.exe"
+        );
+
+        let powershell = console_launch(&TerminalPreference::Auto, exe, &args, dir, false);
+        assert_eq!(powershell.program, "powershell.exe");
+        assert_eq!(
+            powershell.args,
+            strings(&["-NoExit", "-Command", r"& 'C:\Tools\ffmpeg.exe' '-i' 'it''s; here.mp4'"])
+        );
+        assert_eq!(powershell.raw_tail, None);
+    }
+
+    #[test]
+    fn test_pe_subsystem() {
+        let mut header = vec![0u8; 512];
+        header[..2].copy_from_slice(b"MZ");
+        header[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        header[0x80..0x84].copy_from_slice(b"PE\0\0");
+        header[0x80 + 92..0x80 + 94].copy_from_slice(&IMAGE_SUBSYSTEM_WINDOWS_CUI.to_le_bytes());
+        assert_eq!(pe_subsystem(&header), Some(IMAGE_SUBSYSTEM_WINDOWS_CUI));
+
+        // GUI subsystem
+        header[0x80 + 92] = 2;
+        assert_eq!(pe_subsystem(&header), Some(2));
+        // Header offset pointing past what was read, and not an executable at all
+        header[0x3C..0x40].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(pe_subsystem(&header), None);
+        assert_eq!(pe_subsystem(b"#!/bin/sh"), None);
+    }
+
+    #[test]
+    fn test_console_command_line_and_mode() {
+        assert_eq!(console_command_line(r"C:\Tools\ffmpeg.exe", &[]), r#""C:\Tools\ffmpeg.exe""#);
+        assert_eq!(
+            console_command_line(r"C:\Program Files\ffmpeg.exe", &["-i".to_string(), "my clip.mp4".to_string()]),
+            r#"""C:\Program Files\ffmpeg.exe" -i "my clip.mp4"""#
+        );
+        assert_eq!(ConsoleMode::parse(ConsoleMode::Terminal.as_str()), ConsoleMode::Terminal);
+        assert_eq!(ConsoleMode::parse(ConsoleMode::Hidden.as_str()), ConsoleMode::Hidden);
+        assert_eq!(ConsoleMode::parse("bogus"), ConsoleMode::Auto);
+    }
+
+    #[test]
+    fn test_launch_each_continues_after_failure() {
+        let filepaths: Vec<String> = ["a.txt", "b.txt", "c.txt"].iter().map(|p| p.to_string()).collect();
+        let mut attempted = Vec::new();
+        let outcomes = launch_each(&filepaths, |filepath| {
+            attempted.push(filepath.to_string());
+            if filepath == "b.txt" {
+                Err("File not found: b.txt".to_string())
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(attempted, filepaths);
+        let ok: Vec<bool> = outcomes.iter().map(|o| o.ok).collect();
+        assert_eq!(ok, vec![true, false, true]);
+        assert_eq!(outcomes[1].error.as_deref(), Some("File not found: b.txt"));
+        assert_eq!(outcomes[0].error, None);
+    }
+
+    #[test]
+    fn test_file_manager_invocation() {
+        let template = r#""C:\totalcmd\TOTALCMD64.EXE" /O /T "{dir}""#;
+        let (program, args) =
+            file_manager_invocation(template, r"C:\My Files", r"C:\My Files\a.txt").unwrap();
+        assert_eq!(program, r"C:\totalcmd\TOTALCMD64.EXE");
+        assert_eq!(args, vec!["/O", "/T", r"C:\My Files"]);
+
+        let (_, args) = file_manager_invocation("dopus.exe /select {file}", r"C:\Docs", r"C:\Docs\b c.txt").unwrap();
+        assert_eq!(args, vec!["/select", r"C:\Docs\b c.txt"]);
+        assert_eq!(file_manager_invocation("   ", "a", "b"), None);
+    }
+
+    #[test]
+    fn test_missing_file_manager_falls_back() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        assert_eq!(file_manager_command(&db), None);
+        set_file_manager_command(&db, r#""C:\nowhere\fm.exe" "{dir}""#).unwrap();
+        let template = file_manager_command(&db);
+        assert!(template.is_some());
+        assert!(!try_file_manager(template.as_deref(), "dir", "file"));
+        assert!(!try_file_manager(None, "dir", "file"));
+
+        set_file_manager_command(&db, "  ").unwrap();
+        assert_eq!(file_manager_command(&db), None);
+    }
+
+    #[test]
+    fn test_quote_argument() {
+        assert_eq!(quote_argument(r"C:\Setup\app.msi"), r"C:\Setup\app.msi");
+        assert_eq!(quote_argument(""), r#""""#);
+        // Shell metacharacters only need quoting for the space
+        assert_eq!(quote_argument(r"C:\Tom & Jerry\100% (1).msi"), r#""C:\Tom & Jerry\100% (1).msi""#);
+        assert_eq!(quote_argument("a^b&c"), "a^b&c");
+        assert_eq!(quote_argument(r#"say "hi""#), r#""say \"hi\"""#);
+        // Backslashes are doubled only before a quote
+        assert_eq!(quote_argument(r"C:\My Dir\"), r#""C:\My Dir\\""#);
+        assert_eq!(quote_argument(r#"a b\"c"#), r#""a b\\\"c""#);
+    }
+
+    #[test]
+    fn test_join_arguments() {
+        let args = [".".to_string(), r"C:\My Projects\".to_string(), r#"--title="a b""#.to_string()];
+        assert_eq!(join_arguments(&args), r#". "C:\My Projects\\" "--title=\"a b\"""#);
+        assert_eq!(join_arguments(&[]), "");
+    }
+
+    #[test]
+    fn test_elevated_command() {
+        assert_eq!(elevated_command(r"C:\Tools\Tom & Jerry.exe"), (r"C:\Tools\Tom & Jerry.exe".to_string(), None));
+        assert_eq!(
+            elevated_command(r"C:\Setup\My App.MSI"),
+            ("msiexec.exe".to_string(), Some(r#"/i "C:\Setup\My App.MSI""#.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_reveal_selects_files_and_directories() {
+        let dir = std::env::temp_dir().join("ancheck, reveal test");
+        for item in [dir.join("a, b.txt"), dir.clone()] {
+            let mut selected = None;
+            reveal(
+                &item,
+                |p| {
+                    selected = Some(p.to_path_buf());
+                    Ok(())
+                },
+                |_| panic!("should not fall back"),
+            )
+            .unwrap();
+            assert_eq!(selected, Some(item));
+        }
+    }
+
+    #[test]
+    fn test_reveal_falls_back_to_parent() {
+        let dir = std::env::temp_dir().join("ancheck reveal test");
+        let file = dir.join("notes.txt");
+        let mut opened = None;
+        let result = reveal(
+            &file,
+            |_| Err("no PIDL".to_string()),
+            |p| {
+                opened = Some(p.to_path_buf());
+                Ok(())
+            },
+        );
+        assert!(result.is_ok());
+        assert_eq!(opened, Some(dir));
+
+        // Both failing reports the fallback's error
+        let result = reveal(&file, |_| Err("no PIDL".to_string()), |_| Err("no explorer".to_string()));
+        assert_eq!(result, Err("no explorer".to_string()));
+    }
+
+    #[test]
+    fn test_reveal_opens_drive_root() {
+        let root = Path::new(std::path::MAIN_SEPARATOR_STR);
+        let mut opened = None;
+        reveal(root, |_| panic!("a root can't be selected"), |p| {
+            opened = Some(p.to_path_buf());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(opened.as_deref(), Some(root));
+    }
+
+    #[test]
+    fn test_recycle_error_message() {
+        assert_eq!(
+            recycle_error_message(32, "a.docx"),
+            "'a.docx' is in use by another program; close it and try again"
+        );
+        assert_eq!(recycle_error_message(0x78, "a.docx"), "Permission denied: can't delete 'a.docx'");
+        assert_eq!(recycle_error_message(0x10000, "a.docx"), "Failed to move 'a.docx' to the Recycle Bin (error 0x10000)");
+    }
+
+    #[test]
+    fn test_terminal_command() {
+        let dir = r"C:\Users\Zoë\My Projects";
+        let args = |v: &[&str]| v.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        let auto = TerminalPreference::Auto;
+        assert_eq!(terminal_command(&auto, dir, true).unwrap(), ("wt.exe".to_string(), args(&["-d", dir])));
+        assert_eq!(terminal_command(&auto, dir, false).unwrap(), ("powershell.exe".to_string(), args(&["-NoExit"])));
+        assert_eq!(terminal_command(&TerminalPreference::Cmd, dir, true).unwrap(), ("cmd.exe".to_string(), args(&["/K"])));
+
+        let custom = TerminalPreference::Custom {
+            command: r#""C:\Program Files\Alacritty\alacritty.exe" --working-directory {dir}"#.to_string(),
+        };
+        assert_eq!(
+            terminal_command(&custom, dir, true).unwrap(),
+            (r"C:\Program Files\Alacritty\alacritty.exe".to_string(), args(&["--working-directory", dir]))
+        );
+        let empty = TerminalPreference::Custom { command: "  ".to_string() };
+        assert!(terminal_command(&empty, dir, true).is_err());
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(split_command_line(r#"wt  -d "" {dir}"#), vec!["wt", "-d", "", "{dir}"]);
+        assert_eq!(split_command_line(r#"a"b c"d"#), vec!["ab cd"]);
+        assert!(split_command_line("   ").is_empty());
+    }
+
+    #[test]
+    fn test_shell_error_message() {
+        assert_eq!(shell_error_message(2, "a.txt"), "File not found: a.txt");
+        assert!(shell_error_message(5, "a.txt").starts_with("Permission denied"));
+        assert_eq!(shell_error_message(31, "a.xyz"), "No application is associated with 'a.xyz'");
+        assert_eq!(shell_error_message(42, "a.txt"), "Failed to open 'a.txt' (error 42)");
+    }
+
+    #[test]
+    fn test_file_details() {
+        let dir = std::env::temp_dir().join(format!("ancheck-details-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, "twelve bytes").unwrap();
+        let processes = ProcessCache::default();
+
+        let details = file_details(&file.to_string_lossy(), &processes).unwrap();
+        assert_eq!(details.size, Some(12));
+        assert!(details.times.modified_at.is_some());
+        assert_eq!((details.target_path, details.version, details.is_running), (None, None, None));
+        assert_eq!(file_details(&dir.to_string_lossy(), &processes).unwrap().size, None);
+        assert!(file_details(&dir.join("gone.txt").to_string_lossy(), &processes).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_program_path() {
+        let exe = Path::new(r"C:\Apps\Tool.EXE");
+        assert_eq!(program_path(exe, None), Some(exe));
+        let shortcut = Path::new(r"C:\Start Menu\Tool.lnk");
+        assert_eq!(program_path(shortcut, Some(exe)), Some(exe));
+        assert_eq!(program_path(shortcut, Some(Path::new(r"C:\Docs\a.pdf"))), None);
+        assert_eq!(program_path(shortcut, None), None);
+    }
+}
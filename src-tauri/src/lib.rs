@@ -1,108 +1,1939 @@
+mod actions;
+mod archive;
+mod bookmarks;
+mod autostart;
+mod bench;
+mod cleanup;
+mod cli;
+mod clipboard;
+mod clipboard_history;
+mod custom_entries;
 mod db;
+mod deeplink;
+mod display_names;
+mod emoji;
+mod env_vars;
+mod errors;
+mod hotkeys;
+mod i18n;
 mod indexer;
 mod launcher;
+mod logging;
+mod notifications;
+mod onboarding;
+mod open_windows;
+mod paths;
+mod placement;
+mod preview;
+mod processes;
+mod quick_slots;
+mod search_sessions;
 mod searcher;
+mod selection;
+mod settings;
+mod settings_pages;
+mod snippets;
+mod system_actions;
+mod system_state;
+mod thumbnails;
+mod updates;
+mod usage;
 
-use db::Database;
-use log::{error, info};
-use searcher::SearchResult;
+use actions::{ActionItem, QuickAction};
+use bench::BenchReport;
+use clipboard::ClipboardHolder;
+use db::{CustomEntry, Database, Snippet, UsageCleared, WriterLock};
+use deeplink::DeepLink;
+use errors::{AppError, ErrorLog, Severity};
+use hotkeys::HotkeyStatus;
+use i18n::Locale;
+use indexer::{IndexPhase, IndexStatus, IndexTrigger, IndexingGuard, IndexingStatus};
+use launcher::{ConsoleMode, EntryKind, FileDetails, LaunchOptions, LaunchOutcome, TerminalPreference};
+use log::{debug, error, info, warn};
+use notifications::NotifyPolicy;
+use onboarding::{IndexScope, Onboarding, OnboardingState};
+use open_windows::WindowList;
+use paths::{AppPaths, DbLocation};
+use placement::{Bounds, GeometryTracker, WindowPlacement};
+use preview::FilePreview;
+use thumbnails::{Thumbnail, Thumbnails};
+use processes::ProcessCache;
+use quick_slots::QuickSlot;
+use search_sessions::{SearchSessions, SessionResults};
+use searcher::{FilenameSnapshot, PageCache, SearchOptions, SearchPage, SearchResponse, WebSearchTemplate};
+use serde::Serialize;
+use settings::{Settings, SettingsPatch};
+use system_state::BackgroundPolicy;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
-    tray::TrayIconBuilder,
+    menu::{CheckMenuItem, CheckMenuItemBuilder, Menu, MenuBuilder, MenuItem, MenuItemBuilder},
+    tray::{TrayIcon, TrayIconBuilder},
     AppHandle, Emitter, Manager,
 };
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use updates::{UpdateInfo, UpdateState};
+use usage::{LeaderboardEntry, UsagePeriod};
 
 /// Application state shared across all Tauri commands.
 pub struct AppState {
     pub db: Arc<Database>,
+    pub snapshot: Arc<FilenameSnapshot>,
+    pub page_cache: Arc<PageCache>,
     pub indexing: std::sync::atomic::AtomicBool,
+    /// Progress of the running index pass, for `get_indexing_status`.
+    pub index_status: Arc<IndexStatus>,
+    /// Incremented by every `search` call; older calls see they were superseded.
+    pub search_generation: Arc<std::sync::atomic::AtomicU64>,
+    /// Search-as-you-type sessions, for `update_query`.
+    pub search_sessions: Arc<SearchSessions>,
+    /// Kept alive for the whole session so copied text outlives the window hiding.
+    pub clipboard: Arc<ClipboardHolder>,
+    /// Recent process list for marking app results as running.
+    pub processes: Arc<ProcessCache>,
+    /// Other apps' windows, listed when the launcher opens.
+    pub windows: Arc<WindowList>,
+    /// Loaded at startup; changed only through `apply_settings`, which also saves.
+    pub settings: Arc<RwLock<Settings>>,
+    /// Held by `apply_settings` from reading the settings to storing the
+    /// patched ones, so concurrent updates can't drop each other's changes.
+    pub settings_update: tokio::sync::Mutex<()>,
+    /// Wakes the background indexer to pick up a new interval.
+    pub index_wakeup: Arc<tokio::sync::Notify>,
+    /// Holds the initial index back until the user picks what to index.
+    pub onboarding: Arc<Onboarding>,
+    /// Errors reported through `report_error`, for `get_recent_errors`.
+    pub errors: Arc<ErrorLog>,
+    /// The update found by the last check.
+    pub updates: Arc<UpdateState>,
+    /// While set, the window stays open when it loses focus.
+    pub pinned: std::sync::atomic::AtomicBool,
+    /// When the window was last shown, see [`SHOW_FOCUS_GRACE`].
+    pub shown_at: std::sync::Mutex<Option<std::time::Instant>>,
+    /// Tells the user's moves and resizes from the launcher's own.
+    pub geometry: Arc<GeometryTracker>,
+    /// Result thumbnails, for `get_thumbnail`.
+    pub thumbnails: Arc<Thumbnails>,
+    /// The tray icon once it's set up, for [`refresh_tray_status`].
+    pub tray: std::sync::Mutex<Option<TrayIcon>>,
 }
 
-/// Get the database file path in the app data directory.
-fn get_db_path() -> PathBuf {
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("AnCheck");
-    std::fs::create_dir_all(&path).ok();
-    path.push("ancheck_index.db");
-    path
+/// How long the window must stay put after a move or resize before its
+/// geometry is saved; dragging sends a stream of moves.
+const GEOMETRY_SAVE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Focus lost this soon after showing the window doesn't hide it; some
+/// machines report a one-frame focus loss right after `show()`.
+const SHOW_FOCUS_GRACE: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Result of `update_settings`.
+#[derive(Serialize)]
+struct SettingsUpdate {
+    settings: Settings,
+    /// Registration result for each hotkey, when the update set hotkeys.
+    hotkeys: Option<Vec<HotkeyStatus>>,
+    /// Registration result for the relaunch hotkey, when the update set one.
+    relaunch_hotkey: Option<HotkeyStatus>,
+    /// Registration result for the selection hotkey, when the update set one.
+    selection_hotkey: Option<HotkeyStatus>,
+    /// Registration result for each quick slot hotkey, when the update set
+    /// quick slots.
+    quick_slot_hotkeys: Option<Vec<HotkeyStatus>>,
+}
+
+/// Id of the tray icon, to rebuild its menu when the locale changes.
+const TRAY_ID: &str = "main";
+
+/// The tray's "Show Launcher" item, relabelled when the primary hotkey
+/// changes and replaced when the menu is rebuilt.
+struct TrayShowItem(std::sync::Mutex<MenuItem<tauri::Wry>>);
+
+/// The tray's "Start with Windows" item, kept in sync with the registry.
+struct TrayAutostartItem(std::sync::Mutex<CheckMenuItem<tauri::Wry>>);
+
+/// The tray's disabled line with the index size and age, see
+/// [`refresh_tray_status`].
+struct TrayStatusItem(std::sync::Mutex<MenuItem<tauri::Wry>>);
+
+/// How often the tray's index status is refreshed besides after each pass,
+/// so "indexed 12 minutes ago" keeps counting.
+const TRAY_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Query from the `ancheck://search` link the app was started with.
+struct StartupQuery(std::sync::Mutex<Option<String>>);
+
+/// Plugin shortcuts currently registered to show the launcher.
+#[derive(Default)]
+struct ActiveShortcuts(std::sync::Mutex<Vec<Shortcut>>);
+
+/// Plugin shortcuts currently registered for the hotkeys that act without
+/// toggling the launcher.
+#[derive(Default)]
+struct ActionShortcuts {
+    relaunch: std::sync::Mutex<Option<Shortcut>>,
+    selection: std::sync::Mutex<Option<Shortcut>>,
+}
+
+/// Registration result for each quick slot hotkey, from the last attempt.
+#[derive(Default)]
+struct QuickSlotShortcuts(std::sync::Mutex<Vec<HotkeyStatus>>);
+
+type ShortcutHandler = fn(&AppHandle, &Shortcut, ShortcutEvent);
+
+fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    hotkey
+        .parse()
+        .map_err(|e| format!("Invalid shortcut '{}': {}", hotkey, e))
+}
+
+fn show_item_label(hotkey: &str) -> String {
+    i18n::t_with("tray.show", &[("hotkey", &hotkey)])
+}
+
+/// Log what's needed to triage a report from the log file alone.
+fn log_startup_info(db: &Database, db_path: &std::path::Path) {
+    info!("AnCheck {} starting", env!("CARGO_PKG_VERSION"));
+    info!("Database path: {}", db_path.display());
+    if AppPaths::current().is_portable() {
+        info!("Running in portable mode");
+    }
+    for root in indexer::get_index_directories(db) {
+        info!("Index root: {}", root.display());
+    }
+    match db.file_count() {
+        Ok(count) => info!("Indexed files: {}", count),
+        Err(e) => error!("Failed to count indexed files: {}", e),
+    }
+    for key in ["last_full_index", "last_incremental_index"] {
+        let when = db
+            .get_meta(key)
+            .ok()
+            .flatten()
+            .and_then(|secs| secs.parse().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map_or_else(|| "never".to_string(), |time| time.to_rfc3339());
+        info!("{}: {}", key, when);
+    }
+}
+
+/// Log and remember an error the user should see, and send it to the
+/// frontend as an `app-error` event.
+fn report_error(app: &AppHandle, source: &'static str, severity: Severity, message: impl Into<String>) {
+    let error = app.state::<AppState>().errors.record(source, severity, message);
+    let _ = app.emit("app-error", error);
+}
+
+/// Report a failed index pass; a corrupt database makes it fatal.
+fn report_index_error(app: &AppHandle, what: &str, e: &str) {
+    report_error(app, "indexer", Severity::for_message(e, Severity::Error), format!("{} failed: {}", what, e));
+}
+
+/// Report the index roots that the last pass found missing or unreadable
+/// after earlier ones scanned them. Their entries are kept until a pass
+/// scans them again, so each is only reported when it goes stale.
+fn report_stale_roots(app: &AppHandle) {
+    let Some(last_run) = app.state::<AppState>().index_status.snapshot().last_run else {
+        return;
+    };
+    for root in last_run.roots.iter().filter(|root| root.newly_stale) {
+        let message = format!(
+            "Index folder '{}' is missing or can't be read; its entries are kept until it's back",
+            root.root
+        );
+        report_error(app, "indexer", Severity::Error, message);
+    }
+}
+
+/// Rebuild the fuzzy-search snapshot after an index pass changed the table.
+fn refresh_snapshot(db: &Database, snapshot: &FilenameSnapshot) {
+    match snapshot.refresh(db) {
+        Ok(count) => info!("Search snapshot refreshed: {} entries", count),
+        Err(e) => error!("Failed to refresh search snapshot: {}", e),
+    }
 }
 
 // ────────────────────── Tauri Commands ──────────────────────
 
-/// Perform a search query and return ranked results.
+/// Perform a search query and return ranked results along with any parsed filters.
+/// `limit` and `fuzzy` default to the `result_limit` and `enable_fuzzy`
+/// settings; `explain` attaches a score breakdown to every result for
+/// debugging rankings, and `metrics` adds stage timings as `meta`.
+/// Fails with `"superseded"` if a newer search started before this one finished.
+#[tauri::command]
+async fn search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+    fuzzy: Option<bool>,
+    explain: Option<bool>,
+    metrics: Option<bool>,
+) -> Result<SearchResponse, String> {
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let process_cache = state.processes.clone();
+    let mut options = search_options(&state);
+    if let Some(limit) = limit {
+        options.max_results = searcher::clamp_result_limit(limit);
+    }
+    options.fuzzy = fuzzy.unwrap_or(options.fuzzy);
+    options.explain = explain.unwrap_or(false);
+    options.metrics = metrics.unwrap_or(false);
+    let latest = state.search_generation.clone();
+    let generation = latest.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+    tokio::task::spawn_blocking(move || {
+        options.min_score = searcher::default_min_score(&db);
+        let is_superseded = || latest.load(std::sync::atomic::Ordering::SeqCst) != generation;
+        let mut response = searcher::search(&db, &snapshot, &query, &options, &is_superseded)?;
+        processes::annotate_running(&mut response.results, &process_cache);
+        Ok(response)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+}
+
+/// Start a search-as-you-type session and return its id for `update_query`.
+#[tauri::command]
+async fn start_search_session(state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    Ok(state.search_sessions.start())
+}
+
+/// Search for `query` in session `session_id` and emit the results as
+/// `search-results`. Typing on from the previous query only re-checks the
+/// entries that could still match it (see `search_sessions`). An update
+/// superseded by a newer one for the same session emits nothing.
+#[tauri::command]
+async fn update_query(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    session_id: u64,
+    query: String,
+) -> Result<(), String> {
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let sessions = state.search_sessions.clone();
+    let process_cache = state.processes.clone();
+    let mut options = search_options(&state);
+    let response = tokio::task::spawn_blocking(move || {
+        options.min_score = searcher::default_min_score(&db);
+        let mut response = sessions.search(session_id, &db, &snapshot, &query, &options)?;
+        if let Some(response) = &mut response {
+            processes::annotate_running(&mut response.results, &process_cache);
+        }
+        Ok::<_, String>(response.map(|response| SessionResults {
+            session_id,
+            query,
+            response,
+        }))
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))??;
+    if let Some(results) = response {
+        let _ = app.emit("search-results", &results);
+    }
+    Ok(())
+}
+
+/// Search options from the settings, shared by `search` and
+/// `update_query`. The minimum score is read from the database, so callers
+/// fill it in off the async runtime.
+fn search_options(state: &AppState) -> SearchOptions {
+    let open_windows = state.windows.get();
+    let context_app = launch_context(state);
+    let settings = state.settings.read().unwrap();
+    SearchOptions {
+        max_results: settings.result_limit,
+        open_windows,
+        fuzzy: settings.enable_fuzzy,
+        deep_path: settings.deep_path_search,
+        group_caps: settings.group_results.then_some(settings.group_caps),
+        context_app,
+        hide_empty_files: settings.hide_empty_files,
+        large_file_bytes: searcher::large_file_bytes(settings.large_file_mb),
+        ..SearchOptions::default()
+    }
+}
+
+/// The app the launcher was last opened from, whose launches are recorded
+/// and boosted, or `None` when context boosting is off.
+fn launch_context(state: &AppState) -> Option<String> {
+    if !state.settings.read().unwrap().context_boost {
+        return None;
+    }
+    state.windows.previous_app()
+}
+
+/// Fetch one page (zero-based) of results for a query, reusing the ranked
+/// list cached for that query until the index changes.
+#[tauri::command]
+async fn search_page(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    page: usize,
+    page_size: usize,
+) -> Result<SearchPage, String> {
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let page_cache = state.page_cache.clone();
+    let process_cache = state.processes.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut page = page_cache.page(&db, &snapshot, &query, page, page_size)?;
+        processes::annotate_running(&mut page.results, &process_cache);
+        Ok(page)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+}
+
+/// Get the default number of results returned by `search`.
+#[tauri::command]
+async fn get_result_limit(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    Ok(state.settings.read().unwrap().result_limit)
+}
+
+/// Persist the default number of results returned by `search`.
+/// Returns the stored value after clamping.
+#[tauri::command]
+async fn set_result_limit(state: tauri::State<'_, AppState>, app: AppHandle, limit: usize) -> Result<usize, String> {
+    let patch = SettingsPatch {
+        result_limit: Some(searcher::clamp_result_limit(limit)),
+        ..Default::default()
+    };
+    Ok(apply_settings(&state, &app, patch).await?.settings.result_limit)
+}
+
+/// Get the minimum base match score a result needs to be shown.
 #[tauri::command]
-async fn search(state: tauri::State<'_, AppState>, query: String) -> Result<Vec<SearchResult>, String> {
+async fn get_min_score(state: tauri::State<'_, AppState>) -> Result<f64, String> {
     let db = state.db.clone();
-    tokio::task::spawn_blocking(move || searcher::search(&db, &query, 15))
+    tokio::task::spawn_blocking(move || searcher::default_min_score(&db))
         .await
-        .map_err(|e| format!("Search task failed: {}", e))?
+        .map_err(|e| format!("Task failed: {}", e))
 }
 
-/// Evaluate a math expression. Returns None-equivalent empty string if not a math expression.
+/// Persist the minimum base match score. Returns the stored value after clamping.
 #[tauri::command]
-async fn eval_math(query: String) -> Result<Option<String>, String> {
-    Ok(searcher::evaluate_math(&query))
+async fn set_min_score(state: tauri::State<'_, AppState>, min_score: f64) -> Result<f64, String> {
+    let db = state.db.clone();
+    let page_cache = state.page_cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let stored = searcher::set_default_min_score(&db, min_score)?;
+        page_cache.clear();
+        Ok(stored)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
-/// Launch a file/app at the given path and record the click.
+/// Get the web searches offered when nothing in the index matches.
+#[tauri::command]
+async fn get_web_searches(state: tauri::State<'_, AppState>) -> Result<Vec<WebSearchTemplate>, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || searcher::web_search_templates(&db))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Replace the web-search fallbacks. Each URL must contain `{query}`.
+#[tauri::command]
+async fn set_web_searches(
+    state: tauri::State<'_, AppState>,
+    templates: Vec<WebSearchTemplate>,
+) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || searcher::set_web_search_templates(&db, &templates))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Whether an empty query shows pinned, recent and frequent files.
+#[tauri::command]
+async fn get_home_view(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || searcher::home_view_enabled(&db))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Turn the empty-query pinned/recent/frequent view on or off.
+#[tauri::command]
+async fn set_home_view(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || searcher::set_home_view_enabled(&db, enabled))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Pin or unpin a file at the top of the empty-query view.
+#[tauri::command]
+async fn set_file_pinned(state: tauri::State<'_, AppState>, filepath: String, pinned: bool) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        db.set_pinned(&filepath, pinned)
+            .map_err(|e| format!("Failed to update pin: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Hide a file from search results and the empty-query view, or unhide it.
 #[tauri::command]
-async fn launch_file(state: tauri::State<'_, AppState>, filepath: String) -> Result<(), String> {
-    // Record the click for usage boosting
+async fn set_file_hidden(state: tauri::State<'_, AppState>, filepath: String, hidden: bool) -> Result<(), String> {
     let db = state.db.clone();
-    let fp = filepath.clone();
+    let page_cache = state.page_cache.clone();
     tokio::task::spawn_blocking(move || {
-        if let Err(e) = db.record_click(&fp) {
-            error!("Failed to record click: {}", e);
+        db.set_hidden(&filepath, hidden)
+            .map_err(|e| format!("Failed to update hidden state: {}", e))?;
+        page_cache.clear();
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// List the user's custom entries (see `custom_entries`).
+#[tauri::command]
+async fn get_custom_entries(state: tauri::State<'_, AppState>) -> Result<Vec<CustomEntry>, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || db.custom_entries().map_err(|e| format!("Lookup error: {}", e)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Add a searchable entry for a URL, a command or a file outside the index.
+/// A command fails with "confirmation_required" unless `confirmed`.
+/// Returns the new entry's id.
+#[tauri::command]
+async fn add_custom_entry(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    target: String,
+    kind: EntryKind,
+    confirmed: Option<bool>,
+) -> Result<i64, String> {
+    let entry = custom_entries::prepare(&name, &target, kind, confirmed.unwrap_or(false))?;
+    change_custom_entries(&state, move |db| {
+        db.add_custom_entry(&entry)
+            .map_err(|e| format!("Failed to add entry: {}", e))?
+            .ok_or_else(|| format!("An entry for '{}' already exists", entry.target))
+    })
+    .await
+}
+
+/// Replace a custom entry's name, target and kind. A command needs
+/// `confirmed` again, since its target may have changed.
+#[tauri::command]
+async fn update_custom_entry(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    name: String,
+    target: String,
+    kind: EntryKind,
+    confirmed: Option<bool>,
+) -> Result<(), String> {
+    let entry = custom_entries::prepare(&name, &target, kind, confirmed.unwrap_or(false))?;
+    change_custom_entries(&state, move |db| {
+        match db.update_custom_entry(id, &entry) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("No custom entry with id {}", id)),
+            Err(e) => Err(format!("Failed to update entry: {}", e)),
         }
     })
     .await
-    .ok();
+}
 
-    launcher::launch(&filepath)
+#[tauri::command]
+async fn remove_custom_entry(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    change_custom_entries(&state, move |db| {
+        match db.remove_custom_entry(id) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("No custom entry with id {}", id)),
+            Err(e) => Err(format!("Failed to remove entry: {}", e)),
+        }
+    })
+    .await
 }
 
-/// Open the containing folder of a file in Explorer.
+/// All text snippets, by name.
 #[tauri::command]
-async fn open_containing_folder(filepath: String) -> Result<(), String> {
-    launcher::open_containing_folder(&filepath)
+async fn get_snippets(state: tauri::State<'_, AppState>) -> Result<Vec<Snippet>, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || db.snippets().map_err(|e| format!("Failed to load snippets: {}", e)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
 }
 
-/// Trigger a full re-index of the file system.
+/// Add a text snippet found by `name` and `keywords`. Returns its id.
 #[tauri::command]
-async fn rebuild_index(
+async fn add_snippet(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    keywords: Vec<String>,
+    body: String,
+) -> Result<i64, String> {
+    let snippet = snippets::prepare(&name, &keywords, &body)?;
+    change_snippets(&state, move |db| {
+        db.add_snippet(&snippet).map_err(|e| format!("Failed to add snippet: {}", e))
+    })
+    .await
+}
+
+/// Replace a snippet's name, keywords and text.
+#[tauri::command]
+async fn update_snippet(
     state: tauri::State<'_, AppState>,
+    id: i64,
+    name: String,
+    keywords: Vec<String>,
+    body: String,
+) -> Result<(), String> {
+    let snippet = snippets::prepare(&name, &keywords, &body)?;
+    change_snippets(&state, move |db| match db.update_snippet(id, &snippet) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No snippet with id {}", id)),
+        Err(e) => Err(format!("Failed to update snippet: {}", e)),
+    })
+    .await
+}
+
+/// Delete a snippet.
+#[tauri::command]
+async fn remove_snippet(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    change_snippets(&state, move |db| match db.remove_snippet(id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No snippet with id {}", id)),
+        Err(e) => Err(format!("Failed to remove snippet: {}", e)),
+    })
+    .await
+}
+
+/// Run `change` and drop cached result pages, which may list snippets.
+async fn change_snippets<T: Send + 'static>(
+    state: &AppState,
+    change: impl FnOnce(&Database) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let db = state.db.clone();
+    let page_cache = state.page_cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = change(&db)?;
+        page_cache.clear();
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Copy a snippet's text to the clipboard. With the auto-paste setting on,
+/// also hide the launcher and paste it into the window that had the focus
+/// before the launcher opened.
+#[tauri::command]
+async fn use_snippet(app: AppHandle, state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.clone();
+    let clipboard = state.clipboard.clone();
+    let windows = state.windows.clone();
+    let auto_paste = state.settings.read().unwrap().auto_paste_snippets;
+    tokio::task::spawn_blocking(move || {
+        let snippet = db
+            .snippet(id)
+            .map_err(|e| format!("Failed to load snippet: {}", e))?
+            .ok_or_else(|| format!("No snippet with id {}", id))?;
+        clipboard.set_text(&snippet.body)?;
+        if let Err(e) = db.record_snippet_use(id) {
+            warn!("Failed to record snippet use: {}", e);
+        }
+        if auto_paste {
+            paste_to_previous_window(&app, &windows)?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Hide the launcher and paste the clipboard into the window that had the
+/// focus before it opened.
+fn paste_to_previous_window(app: &AppHandle, windows: &WindowList) -> Result<(), String> {
+    let target = windows
+        .previous_foreground()
+        .ok_or("No window to paste into; the text is on the clipboard")?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    snippets::paste_into(target)
+}
+
+/// Copy a clipboard history entry back to the clipboard, pasting it like a
+/// snippet when auto-paste is on.
+#[tauri::command]
+async fn use_clipboard_entry(app: AppHandle, state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.clone();
+    let clipboard = state.clipboard.clone();
+    let windows = state.windows.clone();
+    let auto_paste = state.settings.read().unwrap().auto_paste_snippets;
+    tokio::task::spawn_blocking(move || {
+        let text = db
+            .clipboard_entry_text(id)
+            .map_err(|e| format!("Failed to load clipboard entry: {}", e))?
+            .ok_or_else(|| format!("No clipboard entry with id {}", id))?;
+        clipboard.set_text(&text)?;
+        if auto_paste {
+            paste_to_previous_window(&app, &windows)?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Forget one clipboard history entry, e.g. a copied password.
+#[tauri::command]
+async fn remove_clipboard_entry(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || match db.remove_clipboard_entry(id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No clipboard entry with id {}", id)),
+        Err(e) => Err(format!("Failed to remove clipboard entry: {}", e)),
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Forget all clipboard history. Returns how many entries were removed.
+#[tauri::command]
+async fn clear_clipboard_history(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let removed = db
+            .clear_clipboard_history()
+            .map_err(|e| format!("Failed to clear clipboard history: {}", e))?;
+        info!("Cleared {} clipboard history entries", removed);
+        Ok(removed)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Forget launch counts and times, query picks, snippet use and clipboard
+/// history; emits `usage-data-cleared`. Fails with "confirmation_required"
+/// unless `confirm` is true.
+#[tauri::command]
+async fn clear_usage_data(
     app: AppHandle,
-) -> Result<usize, String> {
-    let is_indexing = &state.indexing;
+    state: tauri::State<'_, AppState>,
+    confirm: Option<bool>,
+) -> Result<UsageCleared, String> {
+    if !confirm.unwrap_or(false) {
+        return Err(system_actions::CONFIRMATION_REQUIRED.to_string());
+    }
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let page_cache = state.page_cache.clone();
+    let cleared = tokio::task::spawn_blocking(move || {
+        let cleared = db
+            .clear_usage_data()
+            .map_err(|e| format!("Failed to clear usage data: {}", e))?;
+        refresh_snapshot(&db, &snapshot);
+        page_cache.clear();
+        Ok::<_, String>(cleared)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+    info!("Cleared usage data: {:?}", cleared);
+    let _ = app.emit("usage-data-cleared", &cleared);
+    Ok(cleared)
+}
 
-    // Prevent concurrent indexing
-    if is_indexing.swap(true, std::sync::atomic::Ordering::SeqCst) {
-        return Err("Indexing is already in progress".to_string());
+/// The `limit` most-launched entries for the stats page, over all time
+/// (the default), this month or this week, each with its change in rank
+/// from last week to this week.
+#[tauri::command]
+async fn get_usage_leaderboard(
+    state: tauri::State<'_, AppState>,
+    limit: usize,
+    period: Option<UsagePeriod>,
+) -> Result<Vec<LeaderboardEntry>, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || usage::leaderboard(&db, limit, period.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Delete every indexed file (custom entries stay) and index from scratch;
+/// emits `index-reset` with the number removed. Fails with
+/// "confirmation_required" unless `confirm` is true.
+///
+/// The indexing flag is held from before the clear until the rebuild ends,
+/// so no other pass can write into the table while it's being reset.
+#[tauri::command]
+async fn reset_index(app: AppHandle, confirm: Option<bool>) -> Result<usize, String> {
+    if !confirm.unwrap_or(false) {
+        return Err(system_actions::CONFIRMATION_REQUIRED.to_string());
+    }
+    let (cleared_tx, cleared_rx) = tokio::sync::oneshot::channel();
+    let task_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = task_app;
+        let state = app.state::<AppState>();
+        let Some(guard) = IndexingGuard::acquire(&state.indexing) else {
+            let _ = cleared_tx.send(Err("Indexing is in progress; try again when it's done".to_string()));
+            return;
+        };
+        let db = state.db.clone();
+        let snapshot = state.snapshot.clone();
+        let page_cache = state.page_cache.clone();
+        let cleared = tokio::task::spawn_blocking(move || {
+            let removed = db.clear_index().map_err(|e| format!("Failed to reset the index: {}", e))?;
+            refresh_snapshot(&db, &snapshot);
+            page_cache.clear();
+            Ok::<_, String>(removed)
+        })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+        .and_then(|result| result);
+        let Ok(removed) = cleared else {
+            let _ = cleared_tx.send(cleared);
+            return;
+        };
+        info!("Reset the index: removed {} files", removed);
+        let _ = app.emit("index-reset", removed);
+        let _ = cleared_tx.send(Ok(removed));
+        match run_full_index(&app, guard, IndexTrigger::Manual).await {
+            Ok(count) => info!("Index rebuilt after reset: {} files", count),
+            Err(e) => report_index_error(&app, "Index rebuild", &e),
+        }
+    });
+    cleared_rx.await.map_err(|_| "Reset task failed".to_string())?
+}
+
+/// Delete everything AnCheck stored (index, settings, logs, webview cache)
+/// and its autostart and link registrations, then quit. The files are open
+/// in this process, so a new one deletes them once it has exited (see
+/// `cleanup`). Returns the folders being deleted and emits them as
+/// `uninstall-cleanup`. Fails with "confirmation_required" unless `confirm`
+/// is true.
+#[tauri::command]
+async fn uninstall_cleanup(app: AppHandle, confirm: Option<bool>) -> Result<Vec<String>, String> {
+    if !confirm.unwrap_or(false) {
+        return Err(system_actions::CONFIRMATION_REQUIRED.to_string());
     }
+    let paths = AppPaths::current();
+    let folders: Vec<String> = paths
+        .all_dirs()
+        .iter()
+        .filter(|folder| folder.exists())
+        .map(|folder| folder.to_string_lossy().to_string())
+        .collect();
+    cleanup::spawn_after_exit(paths)?;
+    info!("Quitting to delete {:?}", folders);
+    let _ = app.emit("uninstall-cleanup", &folders);
+    // Let the reply reach the frontend first
+    let exit_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        exit_handle.exit(0);
+    });
+    Ok(folders)
+}
 
+/// Run `change` and refresh what search sees.
+async fn change_custom_entries<T: Send + 'static>(
+    state: &AppState,
+    change: impl FnOnce(&Database) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
     let db = state.db.clone();
-    let result = tokio::task::spawn_blocking(move || indexer::full_index(&db))
+    let snapshot = state.snapshot.clone();
+    let page_cache = state.page_cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = change(&db)?;
+        refresh_snapshot(&db, &snapshot);
+        page_cache.clear();
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Evaluate an inline answer (math, number base, unit conversion, color code or date calculation). Returns None otherwise.
+#[tauri::command]
+async fn eval_math(query: String) -> Result<Option<String>, String> {
+    Ok(searcher::evaluate_inline(&query))
+}
+
+/// Copy text (e.g. an inline answer) to the system clipboard.
+#[tauri::command]
+async fn copy_to_clipboard(state: tauri::State<'_, AppState>, text: String) -> Result<(), String> {
+    let clipboard = state.clipboard.clone();
+    tokio::task::spawn_blocking(move || clipboard.set_text(&text))
         .await
-        .map_err(|e| format!("Index task failed: {}", e))?;
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Copy a file's full path to the clipboard as text.
+#[tauri::command]
+async fn copy_path_to_clipboard(state: tauri::State<'_, AppState>, filepath: String) -> Result<(), String> {
+    let clipboard = state.clipboard.clone();
+    tokio::task::spawn_blocking(move || {
+        ensure_exists(&filepath)?;
+        clipboard.set_text(&filepath)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Copy the file itself to the clipboard, to paste into Explorer or an email.
+#[tauri::command]
+async fn copy_file_to_clipboard(state: tauri::State<'_, AppState>, filepath: String) -> Result<(), String> {
+    let clipboard = state.clipboard.clone();
+    tokio::task::spawn_blocking(move || {
+        ensure_exists(&filepath)?;
+        clipboard.set_files(&[std::path::Path::new(&filepath)])
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+fn ensure_exists(filepath: &str) -> Result<(), String> {
+    if std::path::Path::new(filepath).exists() {
+        Ok(())
+    } else {
+        Err(i18n::t_with("error.file_not_found", &[("file", &filepath)]))
+    }
+}
 
-    is_indexing.store(false, std::sync::atomic::Ordering::SeqCst);
+/// Launch a file/app at the given path and record the click.
+/// `query` is what was typed when the result was picked, so future searches
+/// for it can rank this file higher.
+#[tauri::command]
+async fn launch_file(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    filepath: String,
+    query: Option<String>,
+) -> Result<(), String> {
+    launch_and_record(&state, &app, filepath, query, launcher::launch).await
+}
+
+/// Launch a file/app with command-line arguments and an optional working
+/// directory (`code .`), recording the click like `launch_file`.
+#[tauri::command]
+async fn launch_file_with_args(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    filepath: String,
+    args: Vec<String>,
+    cwd: Option<String>,
+    query: Option<String>,
+) -> Result<(), String> {
+    launch_and_record(&state, &app, filepath, query, move |path, options| {
+        launcher::launch_with_args(path, &args, cwd.as_deref(), options)
+    })
+    .await
+}
+
+/// Launch a file/app as administrator (UAC prompt) and record the click.
+/// Fails with a "Cancelled: ..." message if the user declines the prompt.
+#[tauri::command]
+async fn launch_file_elevated(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    filepath: String,
+    query: Option<String>,
+) -> Result<(), String> {
+    launch_and_record(&state, &app, filepath, query, |path, _| launcher::launch_elevated(path)).await
+}
+
+/// Launch whatever was launched last again, returning its path. With
+/// nothing launched yet, shows the launcher and returns `None`.
+#[tauri::command]
+async fn relaunch_last(state: tauri::State<'_, AppState>, app: AppHandle) -> Result<Option<String>, String> {
+    let entry = relaunch_last_entry(&state, &app).await?;
+    if entry.is_none() {
+        show_window(&app);
+    }
+    Ok(entry.map(|entry| entry.filepath))
+}
+
+/// Launch the most recently launched entry, if there is one.
+async fn relaunch_last_entry(state: &AppState, app: &AppHandle) -> Result<Option<db::FileEntry>, String> {
+    let db = state.db.clone();
+    let last = tokio::task::spawn_blocking(move || db.last_launched())
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+        .map_err(|e| format!("Lookup error: {}", e))?;
+    let Some(entry) = last else {
+        return Ok(None);
+    };
+    info!("Relaunching {}", entry.filepath);
+    launch_and_record(state, app, entry.filepath.clone(), None, launcher::launch).await?;
+    Ok(Some(entry))
+}
+
+/// Run `launch` with the entry's launch options, then record the click for
+/// usage boosting only if it succeeded. If it failed because the file is
+/// gone, the stale entry is dropped from the index and "stale-entry-removed"
+/// is emitted with its path.
+async fn launch_and_record(
+    state: &AppState,
+    app: &AppHandle,
+    filepath: String,
+    query: Option<String>,
+    launch: impl FnOnce(&str, &LaunchOptions) -> Result<(), String> + Send + 'static,
+) -> Result<(), String> {
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let context_app = launch_context(state);
+    let (result, removed) = tokio::task::spawn_blocking(move || {
+        let result = launch(&filepath, &launch_options(&db, &filepath));
+        let removed = launcher::record_launch_outcome(
+            &db,
+            &filepath,
+            query.as_deref(),
+            context_app.as_deref(),
+            &result,
+        );
+        if removed {
+            refresh_snapshot(&db, &snapshot);
+        }
+        (result, removed.then_some(filepath))
+    })
+    .await
+    .map_err(|e| format!("Launch task failed: {}", e))?;
+
+    if let Some(filepath) = removed {
+        let _ = app.emit("stale-entry-removed", filepath);
+    }
+    result
+}
+
+/// Get how launching an indexed .exe handles its console window.
+#[tauri::command]
+async fn get_console_mode(state: tauri::State<'_, AppState>, filepath: String) -> Result<ConsoleMode, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || db.console_mode(&filepath).map_err(|e| format!("Lookup error: {}", e)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Set how launching an indexed .exe handles its console window: "auto"
+/// gives console programs a console of their own, "hidden" runs them
+/// without one, "terminal" keeps their output open.
+#[tauri::command]
+async fn set_console_mode(state: tauri::State<'_, AppState>, filepath: String, mode: ConsoleMode) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let updated = db
+            .set_console_mode(&filepath, mode)
+            .map_err(|e| format!("Failed to save console mode: {}", e))?;
+        if updated {
+            Ok(())
+        } else {
+            Err(format!("Not in the index: {}", filepath))
+        }
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Launch several files one after another (a multi-selection), recording
+/// clicks for the ones that launched. One failure doesn't stop the rest;
+/// each file's outcome is returned. Missing files are dropped from the
+/// index as in `launch_file`.
+#[tauri::command]
+async fn launch_files(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    filepaths: Vec<String>,
+    query: Option<String>,
+) -> Result<Vec<LaunchOutcome>, String> {
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let context_app = launch_context(&state);
+    let (outcomes, removed) = tokio::task::spawn_blocking(move || {
+        let mut removed = Vec::new();
+        let outcomes = launcher::launch_each(&filepaths, |filepath| {
+            let result = launcher::launch(filepath, &launch_options(&db, filepath));
+            if launcher::record_launch_outcome(&db, filepath, query.as_deref(), context_app.as_deref(), &result) {
+                removed.push(filepath.to_string());
+            }
+            result
+        });
+        if !removed.is_empty() {
+            refresh_snapshot(&db, &snapshot);
+        }
+        (outcomes, removed)
+    })
+    .await
+    .map_err(|e| format!("Launch task failed: {}", e))?;
+
+    for filepath in removed {
+        let _ = app.emit("stale-entry-removed", filepath);
+    }
+    Ok(outcomes)
+}
+
+/// The stored settings for launching `filepath`.
+fn launch_options(db: &Database, filepath: &str) -> LaunchOptions {
+    let console = db.console_mode(filepath).unwrap_or_else(|e| {
+        error!("Failed to read console mode for '{}': {}", filepath, e);
+        ConsoleMode::Auto
+    });
+    let (kind, command_confirmed) = db.entry_kind(filepath).unwrap_or_else(|e| {
+        error!("Failed to read entry kind for '{}': {}", filepath, e);
+        (EntryKind::File, false)
+    });
+    LaunchOptions {
+        console,
+        file_manager: launcher::file_manager_command(db),
+        kind,
+        command_confirmed,
+        browser: bookmarks::browser_for(db, filepath),
+        terminal: launcher::terminal_preference(db),
+    }
+}
+
+/// Open the containing folder of a file in Explorer, or in the configured
+/// file manager (see `set_file_manager`).
+#[tauri::command]
+async fn open_containing_folder(state: tauri::State<'_, AppState>, filepath: String) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        launcher::open_containing_folder(&filepath, launcher::file_manager_command(&db).as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Get the file manager command template; empty when Explorer is used.
+#[tauri::command]
+async fn get_file_manager(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(state.settings.read().unwrap().file_manager.clone())
+}
+
+/// Set the file manager used to open folders, as a command template with
+/// `{dir}` and `{file}` placeholders. An empty template restores Explorer.
+#[tauri::command]
+async fn set_file_manager(state: tauri::State<'_, AppState>, app: AppHandle, command: String) -> Result<(), String> {
+    let patch = SettingsPatch {
+        file_manager: Some(command),
+        ..Default::default()
+    };
+    apply_settings(&state, &app, patch).await.map(|_| ())
+}
+
+/// Trigger a full re-index of the file system, starting over even if the
+/// last pass was interrupted.
+#[tauri::command]
+async fn rebuild_index(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    // Prevent concurrent indexing
+    let guard = IndexingGuard::acquire(&state.indexing)
+        .ok_or_else(|| "Indexing is already in progress".to_string())?;
+    run_full_index(&app, guard, IndexTrigger::Manual).await
+}
+
+/// Run a launcher command picked from a `>` query (see
+/// `searcher::LAUNCHER_COMMANDS`). Returns a message to show in place of
+/// hiding the window, if the command has one. Commands that delete data
+/// fail with "confirmation_required" unless `confirmed`.
+#[tauri::command]
+async fn run_launcher_command(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    action_id: String,
+    confirmed: Option<bool>,
+) -> Result<Option<String>, String> {
+    let command = searcher::find_launcher_command(&action_id)
+        .ok_or_else(|| format!("No such command: {}", action_id))?;
+    info!("Running launcher command: {}", command.id);
+
+    match command.id {
+        "rebuild" => {
+            if state.indexing.load(std::sync::atomic::Ordering::SeqCst) {
+                return Ok(Some("Indexing is already in progress".to_string()));
+            }
+            spawn_rebuild(&app);
+            Ok(Some("Rebuilding index…".to_string()))
+        }
+        "index_stats" => {
+            let db = state.db.clone();
+            tokio::task::spawn_blocking(move || {
+                let count = db.file_count().map_err(|e| format!("Count error: {}", e))?;
+                let last_index = db
+                    .get_meta("last_full_index")
+                    .ok()
+                    .flatten()
+                    .and_then(|value| value.parse::<i64>().ok())
+                    .map(|ts| searcher::format_relative_time(ts, chrono::Utc::now().timestamp()))
+                    .unwrap_or_else(|| "never".to_string());
+                Ok(Some(format!("{} files indexed · last full index {}", count, last_index)))
+            })
+            .await
+            .map_err(|e| format!("Task failed: {}", e))?
+        }
+        "settings" => {
+            let _ = app.emit("open-settings", ());
+            Ok(None)
+        }
+        "check_updates" => {
+            let info = updates::check(&app, &state.updates).await?;
+            Ok(Some(updates::describe(&info)))
+        }
+        "install_update" => {
+            updates::install(&app, &state.updates).await?;
+            Ok(None)
+        }
+        "clear_clipboard" => {
+            let removed = clear_clipboard_history(state).await?;
+            Ok(Some(format!("Cleared {} clipboard history entries", removed)))
+        }
+        "clear_usage" => {
+            let cleared = clear_usage_data(app, state, confirmed).await?;
+            Ok(Some(format!(
+                "Forgot {} launched files, {} search picks and {} clipboard entries",
+                cleared.launches, cleared.query_picks, cleared.clipboard_entries
+            )))
+        }
+        "reset_index" => {
+            let removed = reset_index(app, confirmed).await?;
+            Ok(Some(format!("Removed {} files; indexing from scratch…", removed)))
+        }
+        "quit" => {
+            state.index_status.cancel();
+            app.exit(0);
+            Ok(None)
+        }
+        other => Err(format!("Command '{}' has no handler", other)),
+    }
+}
+
+/// Run a power/session action picked from the results (lock, sleep,
+/// shutdown, ...). Destructive actions fail with "confirmation_required"
+/// unless `confirmed` is true, so the frontend can ask first.
+#[tauri::command]
+async fn run_system_action(action: String, confirmed: Option<bool>) -> Result<(), String> {
+    let action = system_actions::SystemAction::from_id(&action)
+        .ok_or_else(|| format!("Unknown system action: {}", action))?;
+    tokio::task::spawn_blocking(move || system_actions::run(action, confirmed.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Preview a file for the details pane: the start of a text file (at most
+/// `max_bytes`), a thumbnail of an image, or `{ kind: "none" }`.
+#[tauri::command]
+async fn get_file_preview(filepath: String, max_bytes: Option<u64>) -> Result<FilePreview, String> {
+    tokio::task::spawn_blocking(move || preview::file_preview(&filepath, max_bytes))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Thumbnail of image or video result `id` for the result list, at most
+/// `max_px` pixels on its longest side, or `{ kind: "unavailable" }` when
+/// there's none to show. Never fails, so a missing thumbnail is no error.
+#[tauri::command]
+async fn get_thumbnail(state: tauri::State<'_, AppState>, id: i64, max_px: u32) -> Result<Thumbnail, String> {
+    let Ok(entry) = indexed_file(state.db.clone(), id).await else {
+        return Ok(Thumbnail::Unavailable);
+    };
+    if !thumbnails::supports(&entry.file_type, &entry.extension) {
+        return Ok(Thumbnail::Unavailable);
+    }
+    Ok(state.thumbnails.get(entry.filepath, max_px).await)
+}
+
+/// Details of result `id` for the details pane: exact size, times, a
+/// shortcut's target, a program's version and whether it's running.
+#[tauri::command]
+async fn get_file_details(state: tauri::State<'_, AppState>, id: i64) -> Result<FileDetails, String> {
+    let db = state.db.clone();
+    let process_cache = state.processes.clone();
+    tokio::task::spawn_blocking(move || {
+        let entry = db
+            .get_file_by_id(id)
+            .map_err(|e| format!("Lookup error: {}", e))?
+            .ok_or_else(|| format!("No indexed file with id {}", id))?;
+        launcher::file_details(&entry.filepath, &process_cache)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Show the Windows "Open with" app chooser for a file.
+#[tauri::command]
+async fn open_with_dialog(filepath: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || launcher::open_with_dialog(&filepath))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Show the Windows Properties sheet for a file or folder.
+#[tauri::command]
+async fn show_file_properties(filepath: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || launcher::show_file_properties(&filepath))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Open a terminal in a folder, or in a file's folder, using the configured
+/// terminal (see `set_terminal`).
+#[tauri::command]
+async fn open_terminal_at(state: tauri::State<'_, AppState>, filepath: String) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let preference = launcher::terminal_preference(&db);
+        launcher::open_terminal_at(&filepath, &preference)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Get the terminal used by `open_terminal_at`.
+#[tauri::command]
+async fn get_terminal(state: tauri::State<'_, AppState>) -> Result<TerminalPreference, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || launcher::terminal_preference(&db))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Set the terminal used by `open_terminal_at`; `custom` takes a command
+/// with a `{dir}` placeholder.
+#[tauri::command]
+async fn set_terminal(state: tauri::State<'_, AppState>, preference: TerminalPreference) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || launcher::set_terminal_preference(&db, &preference))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Move a result's file or folder to the Recycle Bin and drop it from the
+/// index. `target` is a result id or a path. Fails with
+/// "confirmation_required" unless `confirm` is true.
+#[tauri::command]
+async fn delete_file(state: tauri::State<'_, AppState>, target: String, confirm: Option<bool>) -> Result<(), String> {
+    if !confirm.unwrap_or(false) {
+        return Err(system_actions::CONFIRMATION_REQUIRED.to_string());
+    }
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    tokio::task::spawn_blocking(move || {
+        let filepath = match target.parse::<i64>() {
+            Ok(id) => db
+                .get_file_by_id(id)
+                .map_err(|e| format!("Lookup error: {}", e))?
+                .ok_or_else(|| format!("No indexed file with id {}", id))?
+                .filepath,
+            Err(_) => target,
+        };
+        recycle_and_forget(&db, &snapshot, &filepath)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Extract an archive into a new folder named after it, inside `destination`
+/// (default: the archive's folder), and return the folder's path. Emits
+/// `extract-progress` while extracting. Fails with "would_overwrite" unless
+/// `overwrite` is true when files would be replaced.
+#[tauri::command]
+async fn extract_archive(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    filepath: String,
+    destination: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<String, String> {
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    tokio::task::spawn_blocking(move || {
+        let folder = extract_and_index(&db, &snapshot, &app, &filepath, destination.as_deref(), overwrite.unwrap_or(false))?;
+        Ok(folder.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Extract an archive (see `extract_archive`), then index the new folder
+/// right away rather than waiting for the next indexing pass.
+fn extract_and_index(
+    db: &Database,
+    snapshot: &FilenameSnapshot,
+    app: &AppHandle,
+    filepath: &str,
+    destination: Option<&str>,
+    overwrite: bool,
+) -> Result<PathBuf, String> {
+    let folder = archive::extract_archive(filepath, destination, overwrite, |progress| {
+        let _ = app.emit("extract-progress", progress);
+    })?;
+    match indexer::index_tree(db, &folder) {
+        Ok(0) => {}
+        Ok(count) => {
+            info!("Indexed {} extracted entries in {}", count, folder.display());
+            refresh_snapshot(db, snapshot);
+        }
+        Err(e) => report_error(app, "indexer", Severity::Transient, e),
+    }
+    Ok(folder)
+}
+
+/// Move `filepath` to the Recycle Bin and drop it from the index.
+fn recycle_and_forget(db: &Database, snapshot: &FilenameSnapshot, filepath: &str) -> Result<(), String> {
+    launcher::move_to_recycle_bin(filepath)?;
+    db.remove_path(filepath)
+        .map_err(|e| format!("Deleted, but failed to update the index: {}", e))?;
+    refresh_snapshot(db, snapshot);
+    Ok(())
+}
+
+/// Look up an indexed file for a quick action.
+async fn indexed_file(db: Arc<Database>, file_id: i64) -> Result<db::FileEntry, String> {
+    tokio::task::spawn_blocking(move || {
+        db.get_file_by_id(file_id)
+            .map_err(|e| format!("Lookup error: {}", e))?
+            .ok_or_else(|| format!("No indexed file with id {}", file_id))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Context menu actions for an indexed result, depending on its type.
+#[tauri::command]
+async fn get_actions(state: tauri::State<'_, AppState>, file_id: i64) -> Result<Vec<ActionItem>, String> {
+    let entry = indexed_file(state.db.clone(), file_id).await?;
+    Ok(actions::actions_for(&entry).into_iter().map(QuickAction::item).collect())
+}
+
+/// Run an action returned by `get_actions` on an indexed result. Actions
+/// with `needs_confirm` fail with "confirmation_required" unless `confirmed`
+/// is true; "extract_here" fails with "would_overwrite" unless `confirmed`
+/// is true when it would replace files.
+#[tauri::command]
+async fn run_action(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    file_id: i64,
+    action_id: String,
+    confirmed: Option<bool>,
+) -> Result<(), String> {
+    let entry = indexed_file(state.db.clone(), file_id).await?;
+    let action = QuickAction::from_id(&action_id)
+        .filter(|action| actions::actions_for(&entry).contains(action))
+        .ok_or_else(|| format!("Action '{}' isn't available for {}", action_id, entry.filename))?;
+    if action.needs_confirm() && !confirmed.unwrap_or(false) {
+        return Err(system_actions::CONFIRMATION_REQUIRED.to_string());
+    }
+    info!("Running action {} on {}", action.id(), entry.filepath);
+
+    let filepath = entry.filepath;
+    match action {
+        QuickAction::Open => return launch_and_record(&state, &app, filepath, None, launcher::launch).await,
+        QuickAction::RunAsAdmin => {
+            return launch_and_record(&state, &app, filepath, None, |path, _| launcher::launch_elevated(path)).await
+        }
+        _ => {}
+    }
+
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let clipboard = state.clipboard.clone();
+    tokio::task::spawn_blocking(move || match action {
+        QuickAction::Open | QuickAction::RunAsAdmin => unreachable!("launches are handled above"),
+        QuickAction::OpenFileLocation => {
+            launcher::open_containing_folder(&filepath, launcher::file_manager_command(&db).as_deref())
+        }
+        QuickAction::OpenTargetLocation => {
+            launcher::open_shortcut_target_location(&filepath, launcher::file_manager_command(&db).as_deref())
+        }
+        QuickAction::OpenInTerminal => {
+            launcher::open_terminal_at(&filepath, &launcher::terminal_preference(&db))
+        }
+        QuickAction::OpenWith => launcher::open_with_dialog(&filepath),
+        QuickAction::CopyPath => {
+            ensure_exists(&filepath)?;
+            clipboard.set_text(&filepath)
+        }
+        QuickAction::CopyFile => {
+            ensure_exists(&filepath)?;
+            clipboard.set_files(&[std::path::Path::new(&filepath)])
+        }
+        QuickAction::Properties => launcher::show_file_properties(&filepath),
+        QuickAction::ExtractHere => {
+            let overwrite = confirmed.unwrap_or(false);
+            extract_and_index(&db, &snapshot, &app, &filepath, None, overwrite).map(|_| ())
+        }
+        QuickAction::Delete => recycle_and_forget(&db, &snapshot, &filepath),
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// End a running process shown on an app result. Fails with
+/// "elevation_required" when access is denied; retrying with `elevated`
+/// runs taskkill as administrator.
+#[tauri::command]
+async fn kill_process(state: tauri::State<'_, AppState>, pid: u32, elevated: Option<bool>) -> Result<(), String> {
+    let process_cache = state.processes.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = processes::kill_process(pid, elevated.unwrap_or(false));
+        process_cache.invalidate();
+        result
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Ask the update server whether a newer version is available.
+#[tauri::command]
+async fn check_for_updates(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<UpdateInfo, String> {
+    updates::check(&app, &state.updates).await
+}
+
+/// Download and install the newest version, emitting
+/// `update-download-progress`, then restart into it.
+#[tauri::command]
+async fn install_update(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    updates::install(&app, &state.updates).await
+}
+
+/// Check for updates from the tray: show the launcher with the outcome, or
+/// with `update-available` when there's a new version.
+fn spawn_tray_update_check(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let result = updates::check(&app, &state.updates).await;
+        show_window(&app);
+        match result {
+            Ok(info) if info.available => {
+                let _ = app.emit("update-available", info);
+            }
+            Ok(info) => {
+                let _ = app.emit("update-status", updates::describe(&info));
+            }
+            Err(e) => {
+                let _ = app.emit("update-status", e);
+            }
+        }
+    });
+}
+
+/// Switch to an open window from a `window` result, restoring it if
+/// minimized. Its handle is the result's `filepath`.
+#[tauri::command]
+async fn focus_window(hwnd: isize) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || open_windows::focus_window(hwnd))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Get the primary hotkey that shows the launcher, e.g. "Ctrl+Space".
+#[tauri::command]
+async fn get_hotkey(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    Ok(state.settings.read().unwrap().hotkeys[0].clone())
+}
+
+/// Change the primary hotkey, e.g. to "Alt+Space"; other hotkeys are kept.
+/// The new list is saved before it is registered. If another app already
+/// owns the shortcut, the error says so and the old hotkeys are saved and
+/// registered again.
+#[tauri::command]
+async fn set_hotkey(state: tauri::State<'_, AppState>, app: AppHandle, shortcut: String) -> Result<String, String> {
+    let hotkey = shortcut.trim().to_string();
+    if hotkey.is_empty() {
+        return Err("Shortcut is empty".to_string());
+    }
+    let mut old_list = Vec::new();
+    let update = apply_settings_with(&state, &app, |current| {
+        old_list = current.hotkeys.clone();
+        let mut list = vec![hotkey.clone()];
+        list.extend(old_list.iter().skip(1).filter(|h| **h != hotkey).cloned());
+        Ok(SettingsPatch {
+            hotkeys: Some(list),
+            ..Default::default()
+        })
+    })
+    .await?;
+    let statuses = update.hotkeys.unwrap_or_default();
+    if let Some(e) = statuses.first().and_then(|status| status.error.clone()) {
+        // Save and register the old hotkeys again
+        let patch = SettingsPatch {
+            hotkeys: Some(old_list),
+            ..Default::default()
+        };
+        apply_settings(&state, &app, patch).await?;
+        return Err(e);
+    }
+    info!("Primary hotkey changed from {} to {}", old_list[0], hotkey);
+    Ok(hotkey)
+}
+
+/// Get every hotkey that shows the launcher, primary first.
+#[tauri::command]
+async fn get_hotkeys(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.settings.read().unwrap().hotkeys.clone())
+}
+
+/// Replace the hotkeys that show the launcher, e.g. `["Ctrl+Space",
+/// "Alt+Space", "Double Ctrl"]`; the first is the primary shown in the tray.
+/// Returns whether each one could be registered. The list is saved even if
+/// some failed, so those are retried on the next start.
+#[tauri::command]
+async fn set_hotkeys(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    hotkeys: Vec<String>,
+) -> Result<Vec<HotkeyStatus>, String> {
+    let patch = SettingsPatch {
+        hotkeys: Some(hotkeys),
+        ..Default::default()
+    };
+    Ok(apply_settings(&state, &app, patch).await?.hotkeys.unwrap_or_default())
+}
+
+/// Whether AnCheck starts (hidden, in the tray) when the user signs in.
+#[tauri::command]
+async fn is_autostart_enabled() -> Result<bool, String> {
+    tokio::task::spawn_blocking(autostart::is_enabled)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn enable_autostart(app: AppHandle) -> Result<(), String> {
+    set_autostart(app, true).await
+}
+
+#[tauri::command]
+async fn disable_autostart(app: AppHandle) -> Result<(), String> {
+    set_autostart(app, false).await
+}
+
+/// Turn autostart on or off and tick the tray item to match the result.
+async fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let (result, now_enabled) = tokio::task::spawn_blocking(move || {
+        let result = if enabled { autostart::enable() } else { autostart::disable() };
+        (result, autostart::is_enabled().unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+
+    if let Some(item) = app.try_state::<TrayAutostartItem>() {
+        if let Err(e) = item.0.lock().unwrap().set_checked(now_enabled) {
+            error!("Failed to update tray autostart item: {}", e);
+        }
+    }
+    result
+}
+
+/// Get the language of the tray menu, notifications and launch errors.
+#[tauri::command]
+async fn get_locale(state: tauri::State<'_, AppState>) -> Result<Locale, String> {
+    Ok(state.settings.read().unwrap().locale)
+}
+
+/// Change the backend's language; the tray menu is rebuilt in it.
+#[tauri::command]
+async fn set_locale(state: tauri::State<'_, AppState>, app: AppHandle, locale: Locale) -> Result<(), String> {
+    let patch = SettingsPatch {
+        locale: Some(locale),
+        ..Default::default()
+    };
+    apply_settings(&state, &app, patch).await.map(|_| ())
+}
+
+/// Whether to show the first-run chooser, and what each choice indexes.
+#[tauri::command]
+async fn get_onboarding_state(state: tauri::State<'_, AppState>) -> Result<OnboardingState, String> {
+    let onboarding = state.onboarding.clone();
+    tokio::task::spawn_blocking(move || onboarding.state())
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Finish first-run onboarding: index `selected_roots` (usually one of the
+/// choices from `get_onboarding_state`) and apply `options`, settings
+/// picked alongside them. The initial index starts right after.
+#[tauri::command]
+async fn complete_onboarding(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    selected_roots: Vec<String>,
+    options: Option<SettingsPatch>,
+) -> Result<SettingsUpdate, String> {
+    if !state.onboarding.is_pending() {
+        return Err("Onboarding is already done".to_string());
+    }
+    if selected_roots.iter().all(|root| root.trim().is_empty()) {
+        return Err("Pick at least one folder to index".to_string());
+    }
+    let patch = SettingsPatch {
+        index_roots: Some(selected_roots),
+        ..options.unwrap_or_default()
+    };
+    let update = apply_settings(&state, &app, patch).await?;
+    state.onboarding.finish(&state.db)?;
+    Ok(update)
+}
+
+/// Nobody picked what to index in time: index just the apps, which is
+/// quick, and let the frontend close its chooser.
+async fn finish_onboarding_with_apps(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    match state.onboarding.finish(&state.db) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => error!("{}", e),
+    }
+    info!("No index scope picked in time; indexing apps only");
+    let roots: Vec<String> = IndexScope::Apps
+        .roots()
+        .iter()
+        .map(|root| root.to_string_lossy().to_string())
+        .collect();
+    let patch = SettingsPatch {
+        index_roots: Some(roots),
+        ..Default::default()
+    };
+    match apply_settings(&state, app, patch).await {
+        Ok(update) => {
+            let _ = app.emit("onboarding-finished", &update.settings.index_roots);
+        }
+        Err(e) => report_error(app, "onboarding", Severity::Error, e),
+    }
+}
+
+/// Get all settings.
+#[tauri::command]
+async fn get_settings(state: tauri::State<'_, AppState>) -> Result<Settings, String> {
+    Ok(state.settings.read().unwrap().clone())
+}
+
+/// Change some settings; fields left out of `patch` keep their value.
+/// Hotkeys, the index interval and the result limit take effect right away,
+/// and newly excluded extensions and folders no longer indexed are dropped
+/// from the index.
+#[tauri::command]
+async fn update_settings(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    patch: SettingsPatch,
+) -> Result<SettingsUpdate, String> {
+    apply_settings(&state, &app, patch).await
+}
+
+/// Validate and save `patch`, then apply what changed to the running app.
+/// Nothing is applied, hotkeys included, if saving fails. Hotkeys in the
+/// patch are registered even if unchanged, so one that failed before is
+/// retried.
+async fn apply_settings(state: &AppState, app: &AppHandle, patch: SettingsPatch) -> Result<SettingsUpdate, String> {
+    apply_settings_with(state, app, |_| Ok(patch)).await
+}
+
+/// [`apply_settings`] with a patch made from the current settings, for
+/// changes such as adding one quick slot. No other update runs in between.
+async fn apply_settings_with(
+    state: &AppState,
+    app: &AppHandle,
+    make_patch: impl FnOnce(&Settings) -> Result<SettingsPatch, String>,
+) -> Result<SettingsUpdate, String> {
+    let _updating = state.settings_update.lock().await;
+    let current = state.settings.read().unwrap().clone();
+    let patch = make_patch(&current)?;
+    let sets_hotkeys = patch.hotkeys.is_some();
+    let sets_quick_slots = patch.quick_slots.is_some();
+    let updated = current.with_patch(patch)?;
+
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let saved = updated.clone();
+    let newly_excluded: Vec<String> = updated
+        .excluded_extensions
+        .iter()
+        .filter(|ext| !current.excluded_extensions.contains(ext))
+        .cloned()
+        .collect();
+    let roots_changed = updated.index_roots != current.index_roots;
+    tokio::task::spawn_blocking(move || {
+        let old_roots = indexer::get_index_directories(&db);
+        saved.save(&db)?;
+        if !newly_excluded.is_empty() {
+            let removed = db
+                .remove_extensions(&newly_excluded)
+                .map_err(|e| format!("Failed to remove excluded files: {}", e))?;
+            info!("Removed {} files with excluded extensions", removed);
+            refresh_snapshot(&db, &snapshot);
+        }
+        if roots_changed {
+            let new_roots = indexer::get_index_directories(&db);
+            let mut removed = 0;
+            for root in old_roots.iter().filter(|old| !new_roots.iter().any(|new| old.starts_with(new))) {
+                removed += db
+                    .remove_path(&root.to_string_lossy())
+                    .map_err(|e| format!("Failed to remove '{}' from the index: {}", root.display(), e))?;
+            }
+            if removed > 0 {
+                info!("Removed {} files in folders no longer indexed", removed);
+                refresh_snapshot(&db, &snapshot);
+            }
+        }
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+
+    let hotkey_statuses = sets_hotkeys.then(|| register_hotkeys(app, &updated.hotkeys));
+    let actions = app.state::<ActionShortcuts>();
+    let relaunch_status = (updated.relaunch_hotkey != current.relaunch_hotkey)
+        .then(|| register_action_hotkey(app, &actions.relaunch, &updated.relaunch_hotkey, on_relaunch_hotkey))
+        .flatten();
+    let selection_status = (updated.selection_hotkey != current.selection_hotkey)
+        .then(|| register_action_hotkey(app, &actions.selection, &updated.selection_hotkey, on_selection_hotkey))
+        .flatten();
+    let quick_slot_statuses = sets_quick_slots.then(|| register_quick_slot_hotkeys(app, &updated.quick_slots));
+    *state.settings.write().unwrap() = updated.clone();
+
+    if updated.hotkeys[0] != current.hotkeys[0] {
+        if let Some(item) = app.try_state::<TrayShowItem>() {
+            if let Err(e) = item.0.lock().unwrap().set_text(show_item_label(&updated.hotkeys[0])) {
+                error!("Failed to update tray label: {}", e);
+            }
+        }
+    }
+    if updated.index_interval_minutes != current.index_interval_minutes || roots_changed {
+        state.index_wakeup.notify_one();
+    }
+    if updated.enable_fuzzy != current.enable_fuzzy {
+        state.page_cache.clear();
+    }
+    if updated.log_level != current.log_level {
+        logging::set_level(updated.log_level);
+    }
+    if updated.locale != current.locale {
+        i18n::set_locale(updated.locale);
+        rebuild_tray(app);
+    }
+    if updated.pin_by_default != current.pin_by_default {
+        set_pinned(app, state, updated.pin_by_default);
+    }
+    Ok(SettingsUpdate {
+        settings: updated,
+        hotkeys: hotkey_statuses,
+        relaunch_hotkey: relaunch_status,
+        selection_hotkey: selection_status,
+        quick_slot_hotkeys: quick_slot_statuses,
+    })
+}
+
+/// Bind `filepath` to quick slot `slot`, replacing what it launched before.
+#[tauri::command]
+async fn set_quick_slot(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    slot: u8,
+    filepath: String,
+) -> Result<SettingsUpdate, String> {
+    let slot = quick_slots::check_slot(slot)?;
+    apply_settings_with(&state, &app, |current| {
+        let mut slots = current.quick_slots.clone();
+        slots.insert(slot, filepath);
+        Ok(SettingsPatch {
+            quick_slots: Some(slots),
+            ..Default::default()
+        })
+    })
+    .await
+}
+
+/// Unbind quick slot `slot`.
+#[tauri::command]
+async fn clear_quick_slot(state: tauri::State<'_, AppState>, app: AppHandle, slot: u8) -> Result<SettingsUpdate, String> {
+    let slot = quick_slots::check_slot(slot)?;
+    apply_settings_with(&state, &app, |current| {
+        let mut slots = current.quick_slots.clone();
+        slots.remove(&slot);
+        Ok(SettingsPatch {
+            quick_slots: Some(slots),
+            ..Default::default()
+        })
+    })
+    .await
+}
+
+/// Every bound quick slot, with the name of what it launches and why its
+/// hotkey couldn't be registered, if it couldn't.
+#[tauri::command]
+async fn get_quick_slots(state: tauri::State<'_, AppState>, app: AppHandle) -> Result<Vec<QuickSlot>, String> {
+    let db = state.db.clone();
+    let mut slots = tokio::task::spawn_blocking(move || quick_slots::list(&db))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    let statuses = app.state::<QuickSlotShortcuts>();
+    let statuses = statuses.0.lock().unwrap();
+    for slot in &mut slots {
+        slot.hotkey_error = statuses
+            .iter()
+            .find(|status| status.hotkey == slot.hotkey)
+            .and_then(|status| status.error.clone());
+    }
+    Ok(slots)
+}
+
+/// Time indexing and a standard query workload on `rows` synthetic files
+/// (100k by default) in a throwaway database. For checking performance;
+/// the frontend doesn't offer it.
+#[tauri::command]
+async fn run_benchmark(rows: Option<usize>) -> Result<BenchReport, String> {
+    let rows = rows.unwrap_or(bench::DEFAULT_ROWS);
+    tokio::task::spawn_blocking(move || bench::run(rows))
+        .await
+        .map_err(|e| format!("Benchmark task failed: {}", e))?
+}
+
+/// Get the total number of indexed files.
+#[tauri::command]
+async fn get_index_count(state: tauri::State<'_, AppState>) -> Result<i64, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || db.file_count().map_err(|e| format!("Count error: {}", e)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Open the folder holding the log files.
+#[tauri::command]
+async fn open_log_folder(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let options = LaunchOptions {
+            file_manager: launcher::file_manager_command(&db),
+            ..LaunchOptions::default()
+        };
+        launcher::launch(&logging::log_dir().to_string_lossy(), &options)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
 
-    // Notify frontend that indexing is complete
-    let _ = app.emit("indexing-complete", ());
+/// The query from the `ancheck://search` link the app was started with,
+/// once; later links arrive as `prefill-query` events.
+#[tauri::command]
+async fn take_startup_query(startup: tauri::State<'_, StartupQuery>) -> Result<Option<String>, String> {
+    Ok(startup.0.lock().unwrap().take())
+}
 
-    result
+/// Errors reported since startup (see `app-error`), newest first.
+#[tauri::command]
+async fn get_recent_errors(state: tauri::State<'_, AppState>) -> Result<Vec<AppError>, String> {
+    Ok(state.errors.recent())
 }
 
-/// Get the total number of indexed files.
+/// What the indexer is doing: the running pass's phase and progress, or
+/// the last pass's summary when idle.
 #[tauri::command]
-async fn get_index_count(state: tauri::State<'_, AppState>) -> Result<i64, String> {
-    let db = state.db.clone();
-    tokio::task::spawn_blocking(move || db.file_count().map_err(|e| format!("Count error: {}", e)))
-        .await
-        .map_err(|e| format!("Task failed: {}", e))?
+async fn get_indexing_status(state: tauri::State<'_, AppState>) -> Result<IndexingStatus, String> {
+    Ok(state.index_status.snapshot())
 }
 
 /// Check if indexing is currently in progress.
@@ -111,37 +1942,370 @@ async fn is_indexing(state: tauri::State<'_, AppState>) -> Result<bool, String>
     Ok(state.indexing.load(std::sync::atomic::Ordering::SeqCst))
 }
 
+#[tauri::command]
+async fn is_window_pinned(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.pinned.load(std::sync::atomic::Ordering::SeqCst))
+}
+
+/// Keep the window open when it loses focus, or stop.
+#[tauri::command]
+async fn set_window_pinned(app: AppHandle, state: tauri::State<'_, AppState>, pinned: bool) -> Result<(), String> {
+    set_pinned(&app, &state, pinned);
+    Ok(())
+}
+
+/// Set the pin and tell the frontend with a `window-pinned` event.
+fn set_pinned(app: &AppHandle, state: &AppState, pinned: bool) {
+    state.pinned.store(pinned, std::sync::atomic::Ordering::SeqCst);
+    let _ = app.emit("window-pinned", pinned);
+}
+
+/// Show the window, noting when for [`SHOW_FOCUS_GRACE`].
+fn show_and_focus(app: &AppHandle, window: &tauri::WebviewWindow) {
+    *app.state::<AppState>().shown_at.lock().unwrap() = Some(std::time::Instant::now());
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
+/// Handle the window losing focus: hide it, unless it's pinned or was only
+/// just shown, in which case the focus loss is spurious and it takes the
+/// focus back.
+fn on_focus_lost(app: &AppHandle, window: &tauri::WebviewWindow) {
+    let state = app.state::<AppState>();
+    if state.pinned.load(std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+    let just_shown = state
+        .shown_at
+        .lock()
+        .unwrap()
+        .is_some_and(|shown_at| shown_at.elapsed() < SHOW_FOCUS_GRACE);
+    if just_shown {
+        debug!("Ignoring focus loss right after showing the window");
+        let _ = window.set_focus();
+    } else {
+        let _ = window.hide();
+    }
+}
+
 // ────────────────────── App Setup ──────────────────────
 
-/// Toggle window visibility: show if hidden, hide if visible.
+/// Toggle window visibility: show if hidden, hide if visible. A pinned
+/// window left behind another one is brought back instead.
 fn toggle_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
-        if window.is_visible().unwrap_or(false) {
+        if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(true) {
             let _ = window.hide();
         } else {
-            let _ = window.show();
-            let _ = window.set_focus();
-            // Notify frontend to focus the search input
-            let _ = app.emit("focus-search", ());
+            show_window(app);
+        }
+    }
+}
+
+/// Show and focus the window on the configured monitor.
+fn show_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        // Before showing takes the focus, so a snippet can be pasted back there
+        let state = app.state::<AppState>();
+        state.windows.remember_foreground();
+        // Off the hotkey thread; searches typed meanwhile see the last list
+        let (windows, processes) = (state.windows.clone(), state.processes.clone());
+        std::thread::spawn(move || windows.refresh(&processes));
+        if let Err(e) = position_window(app, &window) {
+            error!("Failed to position window: {}", e);
+        }
+        show_and_focus(app, &window);
+        // Notify frontend to focus the search input
+        let _ = app.emit("focus-search", ());
+    }
+}
+
+/// Act on an `ancheck://` link (see `deeplink`). There's no caller to
+/// return failures to, so they're reported as `app-error` events.
+fn handle_deep_link(app: &AppHandle, link: &str) {
+    match deeplink::parse(link) {
+        Ok(DeepLink::Show) => toggle_window(app),
+        Ok(DeepLink::Search(query)) => {
+            show_window(app);
+            let _ = app.emit("prefill-query", query);
+        }
+        Ok(DeepLink::Launch(path)) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let db = app.state::<AppState>().db.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let target = deeplink::indexed_target(&db, &path)?;
+                    launcher::launch(&target, &launch_options(&db, &target))
+                })
+                .await
+                .map_err(|e| format!("Task failed: {}", e))
+                .and_then(|result| result);
+                if let Err(e) = result {
+                    report_error(&app, "deep link", Severity::Transient, e);
+                }
+            });
+        }
+        Err(e) => report_error(app, "deep link", Severity::Transient, e),
+    }
+}
+
+/// Move the window to where the user left it on this monitor layout or,
+/// if they haven't moved it, onto the monitor chosen by the
+/// `window_placement` setting (see `placement::place`).
+fn position_window(app: &AppHandle, window: &tauri::WebviewWindow) -> tauri::Result<()> {
+    let state = app.state::<AppState>();
+    let (layout, work_areas) = monitor_layout(app)?;
+    let saved = placement::saved_geometry(&state.db, &layout)
+        .and_then(|bounds| placement::clamp_to_work_areas(bounds, &work_areas));
+    let bounds = match saved {
+        Some(bounds) => bounds,
+        None => match default_bounds(app, window)? {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        },
+    };
+
+    // Move first: crossing into a monitor with another scale factor makes
+    // Windows rescale the window, which the resize then overrides
+    window.set_position(tauri::PhysicalPosition::new(bounds.x, bounds.y))?;
+    window.set_size(tauri::PhysicalSize::new(bounds.width, bounds.height))?;
+    state.geometry.set_placed(bounds);
+    Ok(())
+}
+
+/// The current monitor layout's key (see `placement::layout_key`) and its
+/// monitors' work areas.
+fn monitor_layout(app: &AppHandle) -> tauri::Result<(String, Vec<Bounds>)> {
+    let monitors = app.available_monitors()?;
+    let layout: Vec<(Bounds, f64)> = monitors
+        .iter()
+        .map(|monitor| {
+            let bounds = Bounds {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+            };
+            (bounds, monitor.scale_factor())
+        })
+        .collect();
+    let work_areas = monitors.iter().map(work_area_bounds).collect();
+    Ok((placement::layout_key(&layout), work_areas))
+}
+
+fn work_area_bounds(monitor: &tauri::Monitor) -> Bounds {
+    let area = monitor.work_area();
+    Bounds {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    }
+}
+
+/// The window's default bounds on the monitor chosen by the
+/// `window_placement` setting, sized for that monitor's scale factor.
+fn default_bounds(app: &AppHandle, window: &tauri::WebviewWindow) -> tauri::Result<Option<Bounds>> {
+    let placement = app.state::<AppState>().settings.read().unwrap().window_placement;
+    let monitor = match placement {
+        WindowPlacement::Cursor => {
+            let cursor = app.cursor_position()?;
+            app.monitor_from_point(cursor.x, cursor.y)?
+        }
+        WindowPlacement::FocusedWindow => match placement::foreground_window_center() {
+            Some((x, y)) => app.monitor_from_point(x, y)?,
+            None => None,
+        },
+        WindowPlacement::Primary => None,
+    };
+    let monitor = match monitor {
+        Some(monitor) => monitor,
+        None => match app.primary_monitor()? {
+            Some(monitor) => monitor,
+            None => return Ok(None),
+        },
+    };
+
+    // The configured size, so a window shrunk to fit a small monitor
+    // grows back on a larger one
+    let logical_size = match app.config().app.windows.iter().find(|w| w.label == window.label()) {
+        Some(config) => (config.width, config.height),
+        None => {
+            let size = window.outer_size()?.to_logical::<f64>(window.scale_factor()?);
+            (size.width, size.height)
+        }
+    };
+    Ok(Some(placement::place(work_area_bounds(&monitor), logical_size, monitor.scale_factor())))
+}
+
+/// After the user moves or resizes the window, save where it ended up once
+/// they've stopped for [`GEOMETRY_SAVE_DELAY`].
+fn on_geometry_changed(app: &AppHandle, window: &tauri::WebviewWindow) {
+    let generation = app.state::<AppState>().geometry.changed();
+    let (app, window) = (app.clone(), window.clone());
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(GEOMETRY_SAVE_DELAY).await;
+        let state = app.state::<AppState>();
+        if !state.geometry.is_latest(generation) || !window.is_visible().unwrap_or(false) {
+            return;
+        }
+        if let Err(e) = save_window_geometry(&app, &window) {
+            warn!("{}", e);
         }
+    });
+}
+
+fn save_window_geometry(app: &AppHandle, window: &tauri::WebviewWindow) -> Result<(), String> {
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let bounds = Bounds {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let state = app.state::<AppState>();
+    // Minimized, or still where the launcher put it
+    if bounds.width == 0 || bounds.height == 0 || !state.geometry.is_users(bounds) {
+        return Ok(());
     }
+    let (layout, _) = monitor_layout(app).map_err(|e| e.to_string())?;
+    placement::save_geometry(&state.db, &layout, bounds)?;
+    state.geometry.set_placed(bounds);
+    debug!("Saved window geometry {:?} for monitor layout {}", bounds, layout);
+    Ok(())
 }
 
 /// Set up the system tray icon and menu.
-fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let show_item = MenuItemBuilder::with_id("show", "Show Launcher (Ctrl+Space)").build(app)?;
-    let rebuild_item = MenuItemBuilder::with_id("rebuild", "Rebuild Index").build(app)?;
+/// The tray menu in the current locale. Its "Show Launcher", "Start with
+/// Windows" and index status items replace the managed ones, so they stay
+/// the ones updated.
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let hotkey = app.state::<AppState>().settings.read().unwrap().hotkeys[0].clone();
+    // Keeps the last status until the refresh after a rebuild
+    let status_text = app
+        .try_state::<TrayStatusItem>()
+        .and_then(|item| item.0.lock().unwrap().text().ok())
+        .unwrap_or_default();
+    let status_item = MenuItemBuilder::with_id("index_status", status_text).enabled(false).build(app)?;
+    let show_item = MenuItemBuilder::with_id("show", show_item_label(&hotkey)).build(app)?;
+    let rebuild_item = MenuItemBuilder::with_id("rebuild", i18n::t("tray.rebuild")).build(app)?;
+    let autostart_item = CheckMenuItemBuilder::with_id("autostart", i18n::t("tray.autostart"))
+        .checked(autostart::is_enabled().unwrap_or(false))
+        .build(app)?;
+    let logs_item = MenuItemBuilder::with_id("logs", i18n::t("tray.logs")).build(app)?;
+    let updates_item = MenuItemBuilder::with_id("updates", i18n::t("tray.updates")).build(app)?;
     let separator = MenuItemBuilder::with_id("sep", "────────────").enabled(false).build(app)?;
-    let exit_item = MenuItemBuilder::with_id("exit", "Exit").build(app)?;
+    let exit_item = MenuItemBuilder::with_id("exit", i18n::t("tray.exit")).build(app)?;
 
     let menu = MenuBuilder::new(app)
+        .item(&status_item)
         .item(&show_item)
         .item(&rebuild_item)
+        .item(&autostart_item)
+        .item(&logs_item)
+        .item(&updates_item)
         .item(&separator)
         .item(&exit_item)
         .build()?;
 
-    let _tray = TrayIconBuilder::new()
+    match app.try_state::<TrayShowItem>() {
+        Some(item) => *item.0.lock().unwrap() = show_item,
+        None => {
+            app.manage(TrayShowItem(std::sync::Mutex::new(show_item)));
+        }
+    }
+    match app.try_state::<TrayAutostartItem>() {
+        Some(item) => *item.0.lock().unwrap() = autostart_item,
+        None => {
+            app.manage(TrayAutostartItem(std::sync::Mutex::new(autostart_item)));
+        }
+    }
+    match app.try_state::<TrayStatusItem>() {
+        Some(item) => *item.0.lock().unwrap() = status_item,
+        None => {
+            app.manage(TrayStatusItem(std::sync::Mutex::new(status_item)));
+        }
+    }
+    Ok(menu)
+}
+
+/// Relabel the tray after a locale change.
+fn rebuild_tray(app: &AppHandle) {
+    let Some(tray) = app.state::<AppState>().tray.lock().unwrap().clone() else {
+        return;
+    };
+    if let Err(e) = build_tray_menu(app).and_then(|menu| tray.set_menu(Some(menu))) {
+        error!("Failed to rebuild the tray menu: {}", e);
+    }
+    refresh_tray_status(app);
+}
+
+/// The tray's index status line, e.g. "182,340 files · indexed 12 minutes
+/// ago", from the file count and the last index time in Unix seconds.
+fn tray_status_text(count: i64, indexed_at: Option<i64>, now: i64) -> String {
+    let count = i18n::format_count(count);
+    match indexed_at.filter(|&secs| secs > 0) {
+        Some(secs) => {
+            let when = searcher::format_relative_time(secs, now);
+            i18n::t_with("tray.index_status", &[("count", &count), ("when", &when)])
+        }
+        None => i18n::t_with("tray.not_indexed", &[("count", &count)]),
+    }
+}
+
+/// Show the index size and age in the tray tooltip and status line. The
+/// database is read off the main thread and without waiting: while it's
+/// busy the old status stays up until the next refresh.
+fn refresh_tray_status(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<AppState>().db.clone();
+        let (count, indexed_at) = match tokio::task::spawn_blocking(move || db.try_index_freshness()).await {
+            Ok(Some(Ok(freshness))) => freshness,
+            Ok(Some(Err(e))) => {
+                warn!("Failed to read the index status for the tray: {}", e);
+                return;
+            }
+            Ok(None) => {
+                debug!("Database busy, tray status left as is");
+                return;
+            }
+            Err(e) => {
+                warn!("Tray status task failed: {}", e);
+                return;
+            }
+        };
+        let text = tray_status_text(count, indexed_at, chrono::Utc::now().timestamp());
+        let tray = app.state::<AppState>().tray.lock().unwrap().clone();
+        if let Some(tray) = tray {
+            if let Err(e) = tray.set_tooltip(Some(format!("{}\n{}", i18n::t("tray.tooltip"), text))) {
+                warn!("Failed to update the tray tooltip: {}", e);
+            }
+        }
+        if let Some(item) = app.try_state::<TrayStatusItem>() {
+            if let Err(e) = item.0.lock().unwrap().set_text(&text) {
+                warn!("Failed to update the tray status: {}", e);
+            }
+        }
+    });
+}
+
+/// Refresh the tray status every [`TRAY_REFRESH_INTERVAL`].
+fn spawn_tray_refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh_tray_status(&app);
+            tokio::time::sleep(TRAY_REFRESH_INTERVAL).await;
+        }
+    });
+}
+
+fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app)?;
+
+    let tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(Image::from_path("icons/32x32.png").unwrap_or_else(|_| {
             // Fallback: use the app icon from resources
             app.default_window_icon().cloned().unwrap_or_else(|| {
@@ -150,29 +2314,34 @@ fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
             })
         }))
         .menu(&menu)
-        .tooltip("AnCheck - Quick Launcher")
+        .tooltip(i18n::t("tray.tooltip"))
         .on_menu_event(|app, event| match event.id().as_ref() {
             "show" => toggle_window(app),
-            "rebuild" => {
+            "rebuild" => spawn_rebuild(app),
+            "autostart" => {
+                // The click already toggled the tick; apply it
+                let enable = app
+                    .try_state::<TrayAutostartItem>()
+                    .and_then(|item| item.0.lock().unwrap().is_checked().ok())
+                    .unwrap_or(false);
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = set_autostart(app, enable).await {
+                        error!("{}", e);
+                    }
+                });
+            }
+            "logs" => {
                 let app = app.clone();
                 tauri::async_runtime::spawn(async move {
-                    let state = app.state::<AppState>();
-                    let db = state.db.clone();
-                    let is_indexing = &state.indexing;
-                    if !is_indexing.swap(true, std::sync::atomic::Ordering::SeqCst) {
-                        let _ = app.emit("indexing-started", ());
-                        let result = tokio::task::spawn_blocking(move || indexer::full_index(&db)).await;
-                        is_indexing.store(false, std::sync::atomic::Ordering::SeqCst);
-                        let _ = app.emit("indexing-complete", ());
-                        match result {
-                            Ok(Ok(count)) => info!("Tray rebuild: indexed {} files", count),
-                            Ok(Err(e)) => error!("Tray rebuild error: {}", e),
-                            Err(e) => error!("Tray rebuild task error: {}", e),
-                        }
+                    if let Err(e) = open_log_folder(app.state::<AppState>()).await {
+                        error!("{}", e);
                     }
                 });
             }
+            "updates" => spawn_tray_update_check(app),
             "exit" => {
+                app.state::<AppState>().index_status.cancel();
                 app.exit(0);
             }
             _ => {}
@@ -185,28 +2354,330 @@ fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
             }
         })
         .build(app)?;
+    *app.state::<AppState>().tray.lock().unwrap() = Some(tray);
+    spawn_tray_refresh(app);
 
     Ok(())
 }
 
-/// Register the global Ctrl+Space hotkey.
-fn setup_global_shortcut(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+/// Start a full re-index in the background unless one is already running,
+/// emitting `indexing-started` / `indexing-complete` around it.
+fn spawn_rebuild(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let Some(guard) = IndexingGuard::acquire(&state.indexing) else {
+            return;
+        };
+        match run_full_index(&app, guard, IndexTrigger::Manual).await {
+            Ok(count) => info!("Background rebuild: indexed {} files", count),
+            Err(e) => report_index_error(&app, "Index rebuild", &e),
+        }
+    });
+}
 
-    let shortcut: Shortcut = "Ctrl+Space".parse().map_err(|e| {
-        format!("Failed to parse shortcut: {:?}", e)
-    })?;
+/// Run a full index pass while `guard` holds the indexing flag, forced
+/// unless it's the startup one. Emits `indexing-started`, then
+/// `indexing-complete` or `indexing-failed` with the error (a panic in the
+/// indexer included), and reports roots the pass found missing. The flag
+/// is released once the pass is done.
+async fn run_full_index(app: &AppHandle, guard: IndexingGuard<'_>, trigger: IndexTrigger) -> Result<usize, String> {
+    let state = app.state::<AppState>();
+    let db = state.db.clone();
+    let snapshot = state.snapshot.clone();
+    let status = state.index_status.clone();
+    status.begin(trigger);
+    let _ = app.emit("indexing-started", ());
+    let task_status = status.clone();
+    let force = trigger != IndexTrigger::Startup;
+    let result = tokio::task::spawn_blocking(move || {
+        let result = indexer::full_index(&db, &task_status, force);
+        task_status.set_phase(IndexPhase::Finishing);
+        refresh_snapshot(&db, &snapshot);
+        result
+    })
+    .await
+    .map_err(|e| format!("Index task failed: {}", e))
+    .and_then(|result| result);
+    status.finish(result.clone().map(|count| (count, 0)));
+    drop(guard);
 
-    app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
-        if event.state == ShortcutState::Pressed {
-            toggle_window(app);
+    refresh_tray_status(app);
+    report_stale_roots(app);
+    match &result {
+        Ok(_) => {
+            let _ = app.emit("indexing-complete", ());
         }
-    }).map_err(|e| format!("Failed to register global shortcut: {}", e))?;
+        Err(e) => {
+            let _ = app.emit("indexing-failed", e);
+        }
+    }
+    result
+}
+
+/// Register the configured hotkeys (Ctrl+Space by default). Fails only if
+/// none of them could be registered.
+fn setup_global_shortcut(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ActiveShortcuts::default());
+    app.manage(ActionShortcuts::default());
+    app.manage(QuickSlotShortcuts::default());
+    let state = app.state::<AppState>();
+    let (list, relaunch, selection, slots) = {
+        let settings = state.settings.read().unwrap();
+        (
+            settings.hotkeys.clone(),
+            settings.relaunch_hotkey.clone(),
+            settings.selection_hotkey.clone(),
+            settings.quick_slots.clone(),
+        )
+    };
 
-    info!("Global shortcut Ctrl+Space registered");
+    let statuses = register_hotkeys(app, &list);
+    for status in &statuses {
+        match &status.error {
+            Some(e) => report_error(app, "hotkeys", Severity::Error, format!("Hotkey {}: {}", status.hotkey, e)),
+            None => info!("Global shortcut {} registered", status.hotkey),
+        }
+    }
+    let actions = app.state::<ActionShortcuts>();
+    let action_statuses = [
+        register_action_hotkey(app, &actions.relaunch, &relaunch, on_relaunch_hotkey),
+        register_action_hotkey(app, &actions.selection, &selection, on_selection_hotkey),
+    ];
+    let quick_slot_statuses = register_quick_slot_hotkeys(app, &slots);
+    for HotkeyStatus { hotkey, error, .. } in action_statuses.into_iter().flatten().chain(quick_slot_statuses) {
+        if let Some(e) = error {
+            report_error(app, "hotkeys", Severity::Error, format!("Hotkey {}: {}", hotkey, e));
+        }
+    }
+    if !statuses.iter().any(|status| status.registered) {
+        return Err("No hotkey could be registered".into());
+    }
     Ok(())
 }
 
+/// Register `hotkey` to run `handler`, releasing the shortcut previously
+/// held in `slot`. An empty `hotkey` only releases it and returns `None`.
+fn register_action_hotkey(
+    app: &AppHandle,
+    slot: &std::sync::Mutex<Option<Shortcut>>,
+    hotkey: &str,
+    handler: ShortcutHandler,
+) -> Option<HotkeyStatus> {
+    let global_shortcut = app.global_shortcut();
+    let mut active = slot.lock().unwrap();
+    if let Some(previous) = active.take() {
+        if let Err(e) = global_shortcut.unregister(previous) {
+            error!("Failed to unregister hotkey {}: {}", previous, e);
+        }
+    }
+    if hotkey.is_empty() {
+        return None;
+    }
+    let status = match parse_hotkey(hotkey) {
+        Err(e) => HotkeyStatus::failed(hotkey, e),
+        Ok(shortcut) => match global_shortcut.on_shortcut(shortcut, handler) {
+            Ok(()) => {
+                *active = Some(shortcut);
+                info!("Global shortcut {} registered", hotkey);
+                HotkeyStatus::registered(hotkey)
+            }
+            Err(e) => HotkeyStatus::failed(
+                hotkey,
+                format!("Couldn't register {} ({}); another app may already be using it", hotkey, e),
+            ),
+        },
+    };
+    Some(status)
+}
+
+/// Hold the hotkeys of the bound quick `slots` and release the others (see
+/// [`quick_slots`] for why unbound ones aren't held). Ones already held are
+/// kept and ones that failed before are tried again. Returns a status per
+/// bound slot; a slot that fails doesn't stop the others.
+fn register_quick_slot_hotkeys(app: &AppHandle, slots: &std::collections::BTreeMap<u8, String>) -> Vec<HotkeyStatus> {
+    let global_shortcut = app.global_shortcut();
+    for slot in (1..=quick_slots::SLOT_COUNT).filter(|slot| !slots.contains_key(slot)) {
+        let Ok(shortcut) = parse_hotkey(&quick_slots::hotkey(slot)) else {
+            continue;
+        };
+        if global_shortcut.is_registered(shortcut) {
+            if let Err(e) = global_shortcut.unregister(shortcut) {
+                error!("Failed to unregister hotkey {}: {}", shortcut, e);
+            }
+        }
+    }
+    let statuses: Vec<HotkeyStatus> = slots
+        .keys()
+        .map(|&slot| {
+            let hotkey = quick_slots::hotkey(slot);
+            match parse_hotkey(&hotkey) {
+                Err(e) => HotkeyStatus::failed(&hotkey, e),
+                Ok(shortcut) if global_shortcut.is_registered(shortcut) => HotkeyStatus::registered(&hotkey),
+                Ok(shortcut) => match global_shortcut.on_shortcut(shortcut, on_quick_slot_hotkey) {
+                    Ok(()) => {
+                        info!("Global shortcut {} registered", hotkey);
+                        HotkeyStatus::registered(&hotkey)
+                    }
+                    Err(e) => HotkeyStatus::failed(
+                        &hotkey,
+                        format!("Couldn't register {} ({}); another app may already be using it", hotkey, e),
+                    ),
+                },
+            }
+        })
+        .collect();
+    *app.state::<QuickSlotShortcuts>().0.lock().unwrap() = statuses.clone();
+    statuses
+}
+
+/// Make `list` the hotkeys that toggle the launcher, replacing the previous
+/// set. New shortcuts are registered before stale ones are released. Each
+/// hotkey gets its own status, so one conflict doesn't lose the others.
+fn register_hotkeys(app: &AppHandle, list: &[String]) -> Vec<HotkeyStatus> {
+    let global_shortcut = app.global_shortcut();
+    let mut statuses = Vec::with_capacity(list.len());
+    let mut shortcuts = Vec::new();
+    let mut double_taps = Vec::new();
+
+    for hotkey in list {
+        if let Some(modifier) = hotkeys::parse_double_tap(hotkey) {
+            double_taps.push(modifier);
+            statuses.push(HotkeyStatus::registered(hotkey));
+            continue;
+        }
+        let status = match parse_hotkey(hotkey) {
+            Err(e) => HotkeyStatus::failed(hotkey, e),
+            Ok(shortcut) if global_shortcut.is_registered(shortcut) => {
+                shortcuts.push(shortcut);
+                HotkeyStatus::registered(hotkey)
+            }
+            Ok(shortcut) => match global_shortcut.on_shortcut(shortcut, on_hotkey) {
+                Ok(()) => {
+                    shortcuts.push(shortcut);
+                    HotkeyStatus::registered(hotkey)
+                }
+                Err(e) => HotkeyStatus::failed(
+                    hotkey,
+                    format!("Couldn't register {} ({}); another app may already be using it", hotkey, e),
+                ),
+            },
+        };
+        statuses.push(status);
+    }
+
+    let active = app.state::<ActiveShortcuts>();
+    let mut active = active.0.lock().unwrap();
+    for stale in active.iter().filter(|shortcut| !shortcuts.contains(shortcut)) {
+        if let Err(e) = global_shortcut.unregister(*stale) {
+            error!("Failed to unregister hotkey {}: {}", stale, e);
+        }
+    }
+    *active = shortcuts;
+
+    let handle = app.clone();
+    if let Err(e) = hotkeys::set_double_taps(&double_taps, Arc::new(move || toggle_window(&handle))) {
+        for status in statuses.iter_mut().filter(|s| hotkeys::parse_double_tap(&s.hotkey).is_some()) {
+            status.registered = false;
+            status.error = Some(e.clone());
+        }
+    }
+    statuses
+}
+
+fn on_hotkey(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state == ShortcutState::Pressed {
+        toggle_window(app);
+    }
+}
+
+/// Relaunch the last item with no window to show what happened, so a toast
+/// says what was opened. With nothing launched yet, show the launcher.
+fn on_relaunch_hotkey(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state != ShortcutState::Pressed {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        match relaunch_last_entry(&state, &app).await {
+            Ok(Some(entry)) => {
+                let title = i18n::t_with("notify.relaunched", &[("name", &entry.filename)]);
+                notifications::show(&app, &title, &entry.filepath)
+            }
+            Ok(None) => show_window(&app),
+            Err(e) => {
+                report_error(&app, "relaunch", Severity::Error, e.clone());
+                notifications::show(&app, i18n::t("notify.relaunch_failed"), &e);
+            }
+        }
+    });
+}
+
+/// Launch what the pressed quick slot is bound to, recording the click like
+/// a launch from the results. An empty slot gets a toast saying so.
+fn on_quick_slot_hotkey(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state != ShortcutState::Pressed {
+        return;
+    }
+    let Some(slot) =
+        (1..=quick_slots::SLOT_COUNT).find(|&slot| parse_hotkey(&quick_slots::hotkey(slot)).ok() == Some(*shortcut))
+    else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let slot_name = slot.to_string();
+        let bound = state.settings.read().unwrap().quick_slots.get(&slot).cloned();
+        let Some(filepath) = bound else {
+            let title = i18n::t_with("notify.quick_slot_empty", &[("slot", &slot_name)]);
+            let body = i18n::t_with("notify.quick_slot_empty_body", &[("hotkey", &quick_slots::hotkey(slot))]);
+            notifications::show(&app, &title, &body);
+            return;
+        };
+        info!("Launching quick slot {}: {}", slot, filepath);
+        if let Err(e) = launch_and_record(&state, &app, filepath, None, launcher::launch).await {
+            report_error(&app, "quick slots", Severity::Error, e.clone());
+            let title = i18n::t_with("notify.quick_slot_failed", &[("slot", &slot_name)]);
+            notifications::show(&app, &title, &e);
+        }
+    });
+}
+
+/// Copy the selection of the app in front, then show the launcher with a
+/// `prefill-query` event carrying it. The launcher shows either way, empty
+/// when nothing was selected or the copy failed.
+fn on_selection_hotkey(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state != ShortcutState::Pressed {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let clipboard = app.state::<AppState>().clipboard.clone();
+        let capture = tokio::task::spawn_blocking(move || selection::capture(&clipboard));
+        let query = match tokio::time::timeout(selection::CAPTURE_TIMEOUT, capture).await {
+            Ok(Ok(Ok(query))) => query,
+            Ok(Ok(Err(e))) => {
+                warn!("Failed to copy the selection: {}", e);
+                None
+            }
+            Ok(Err(e)) => {
+                error!("Selection task failed: {}", e);
+                None
+            }
+            Err(_) => {
+                warn!("Copying the selection timed out; the app in front may be hung");
+                None
+            }
+        };
+        show_window(&app);
+        if let Some(query) = query {
+            let _ = app.emit("prefill-query", query);
+        }
+    });
+}
+
 /// Spawn the background incremental indexing loop.
 fn start_background_indexer(app: &AppHandle) {
     let app_handle = app.clone();
@@ -216,114 +2687,431 @@ fn start_background_indexer(app: &AppHandle) {
 
         loop {
             let state = app_handle.state::<AppState>();
-            let is_indexing = &state.indexing;
 
-            if !is_indexing.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            // Nothing is indexed before the first-run chooser is answered
+            if state.onboarding.is_pending() {
+                tokio::time::sleep(system_state::DEFER_DELAY).await;
+                continue;
+            }
+
+            // Put the pass off while the user is busy or on battery
+            let policy = {
+                let settings = state.settings.read().unwrap();
+                BackgroundPolicy {
+                    idle_threshold: std::time::Duration::from_secs(settings.index_idle_seconds),
+                    index_on_battery: settings.index_on_battery,
+                }
+            };
+            if let Some(reason) = system_state::background_skip_reason(&policy) {
+                info!("Background index put off: {}", reason.describe());
+                state.index_status.skip(reason);
+                tokio::time::sleep(system_state::DEFER_DELAY).await;
+                continue;
+            }
+
+            if let Some(guard) = IndexingGuard::acquire(&state.indexing) {
+                state.index_status.begin(IndexTrigger::Background);
                 let db = state.db.clone();
-                let result =
-                    tokio::task::spawn_blocking(move || indexer::incremental_index(&db)).await;
+                let snapshot = state.snapshot.clone();
+                let status = state.index_status.clone();
+                let started = std::time::Instant::now();
+                let result = tokio::task::spawn_blocking(move || {
+                    let result = indexer::incremental_index(&db, &status);
+                    status.set_phase(IndexPhase::Finishing);
+                    refresh_snapshot(&db, &snapshot);
+                    result
+                })
+                .await
+                .map_err(|e| format!("Index task failed: {}", e))
+                .and_then(|result| result);
+
+                state.index_status.finish(result.clone());
+                drop(guard);
 
-                is_indexing.store(false, std::sync::atomic::Ordering::SeqCst);
+                let policy = {
+                    let settings = state.settings.read().unwrap();
+                    NotifyPolicy {
+                        enabled: settings.index_notifications,
+                        slow_run: std::time::Duration::from_secs(settings.notify_slow_index_minutes * 60),
+                        removed_files: settings.notify_removed_files,
+                    }
+                };
+                if let Some((title, body)) = notifications::index_run_toast(&policy, started.elapsed(), &result) {
+                    notifications::show(&app_handle, &title, &body);
+                }
 
+                refresh_tray_status(&app_handle);
+                report_stale_roots(&app_handle);
                 match result {
-                    Ok(Ok((indexed, removed))) => {
+                    Ok((indexed, removed)) => {
                         info!(
                             "Background index: {} files indexed, {} removed",
                             indexed, removed
                         );
                     }
-                    Ok(Err(e)) => error!("Background index error: {}", e),
-                    Err(e) => error!("Background index task error: {}", e),
+                    Err(e) => {
+                        report_index_error(&app_handle, "Background index", &e);
+                        let _ = app_handle.emit("indexing-failed", e);
+                    }
+                }
+            }
+
+            // Wait for the configured interval, starting over if it changes
+            let wakeup = state.index_wakeup.clone();
+            loop {
+                let minutes = state.settings.read().unwrap().index_interval_minutes;
+                tokio::select! {
+                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(minutes * 60)) => break,
+                    _ = wakeup.notified() => {}
                 }
             }
+        }
+    });
+}
 
-            // Re-index every 5 minutes
-            tokio::time::sleep(tokio::time::Duration::from_secs(300)).await;
+/// Keep Desktop and Downloads current between index passes (see
+/// [`indexer::HotFolders`]), while the filesystem provider is on.
+fn start_hot_folder_watcher(app: &AppHandle) {
+    let app_handle = app.clone();
+    std::thread::spawn(move || {
+        let mut hot_folders = indexer::HotFolders::new();
+        loop {
+            std::thread::sleep(indexer::HOT_FOLDER_INTERVAL);
+            let state = app_handle.state::<AppState>();
+            let (enabled, roots) = {
+                let settings = state.settings.read().unwrap();
+                (
+                    settings.index_providers.get(indexer::FILESYSTEM_PROVIDER).copied().unwrap_or(true),
+                    settings.index_roots.clone(),
+                )
+            };
+            if enabled && !state.onboarding.is_pending() && hot_folders.check(&state.db, &roots) > 0 {
+                refresh_snapshot(&state.db, &state.snapshot);
+            }
         }
     });
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let paths = AppPaths::current();
+    let db_path = paths.db_path();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::main(&args, &db_path) {
+        std::process::exit(code);
+    }
 
-    let db_path = get_db_path();
-    info!("Database path: {}", db_path.display());
+    // A second launch, e.g. for an ancheck:// link, hands over to the
+    // running instance; one started at sign-in just quits
+    let start_hidden = args.iter().any(|arg| arg == autostart::HIDDEN_ARG);
+    let link = deeplink::link_from_args(&args)
+        .or_else(|| (!start_hidden).then(|| format!("{}://show", deeplink::SCHEME)));
+    if deeplink::forward_to_running(&paths.data_dir(), link.as_deref()) {
+        std::process::exit(0);
+    }
+
+    logging::init();
+    match paths::migrate_to_portable(paths) {
+        Ok(true) => info!("Copied the installed index into the portable data folder"),
+        Ok(false) => {}
+        Err(e) => error!("{}", e),
+    }
+
+    // Move the index out of a synced folder before it's opened, while the
+    // lock shows no other process has it open either
+    let db_location = match WriterLock::try_acquire(&db_path) {
+        Ok(Some(_lock)) => paths::relocate_off_sync_root(paths),
+        _ => Ok(DbLocation::of(&db_path)),
+    }
+    .unwrap_or_else(|e| {
+        error!("{}", e);
+        DbLocation::of(&db_path)
+    });
+    let db_path = paths.db_path();
+    match &db_location {
+        DbLocation::Local => {}
+        DbLocation::Moved { client, to } => info!("Moved the index out of {} to {}", client, to.display()),
+        DbLocation::Synced { client } => warn!("The index is in a {} folder; opening it without WAL", client),
+    }
 
-    let db = Database::open(&db_path).expect("Failed to open database");
+    // Held for the app's lifetime so `--rebuild-index` won't write alongside it
+    let _writer_lock = match WriterLock::try_acquire(&db_path) {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => {
+            warn!("Another AnCheck process is writing the index");
+            None
+        }
+        Err(e) => {
+            error!("{}", e);
+            None
+        }
+    };
+    let db = match &db_location {
+        DbLocation::Synced { .. } => Database::open_without_wal(&db_path),
+        _ => Database::open(&db_path),
+    }
+    .expect("Failed to open database");
     let db = Arc::new(db);
+    if let Err(e) = db.set_meta(paths::DB_LOCATION_KEY, &db_path.to_string_lossy()) {
+        warn!("Failed to record the index location: {}", e);
+    }
+    let settings = Settings::load(&db);
+    logging::set_level(settings.log_level);
+    i18n::set_locale(settings.locale);
+    log_startup_info(&db, &db_path);
+    let pin_by_default = settings.pin_by_default;
 
     let app_state = AppState {
         db: db.clone(),
+        snapshot: Arc::new(FilenameSnapshot::default()),
+        page_cache: Arc::new(PageCache::default()),
         indexing: std::sync::atomic::AtomicBool::new(false),
+        index_status: Arc::new(IndexStatus::default()),
+        search_generation: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+        search_sessions: Arc::new(SearchSessions::default()),
+        clipboard: Arc::new(ClipboardHolder::default()),
+        processes: Arc::new(ProcessCache::default()),
+        windows: Arc::new(WindowList::default()),
+        settings: Arc::new(RwLock::new(settings)),
+        settings_update: tokio::sync::Mutex::new(()),
+        index_wakeup: Arc::new(tokio::sync::Notify::new()),
+        onboarding: Arc::new(Onboarding::new(&db)),
+        errors: Arc::new(ErrorLog::default()),
+        updates: Arc::new(UpdateState::default()),
+        pinned: std::sync::atomic::AtomicBool::new(pin_by_default),
+        shown_at: std::sync::Mutex::new(None),
+        geometry: Arc::new(GeometryTracker::default()),
+        tray: std::sync::Mutex::new(None),
+        thumbnails: Arc::new(Thumbnails::new(paths.thumbnail_dir())),
     };
 
     tauri::Builder::default()
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             search,
+            start_search_session,
+            update_query,
+            search_page,
+            get_result_limit,
+            set_result_limit,
+            get_min_score,
+            set_min_score,
+            get_web_searches,
+            set_web_searches,
+            get_home_view,
+            set_home_view,
+            set_file_pinned,
+            set_file_hidden,
+            get_custom_entries,
+            add_custom_entry,
+            update_custom_entry,
+            remove_custom_entry,
+            get_snippets,
+            add_snippet,
+            update_snippet,
+            remove_snippet,
+            use_snippet,
+            use_clipboard_entry,
+            remove_clipboard_entry,
+            clear_clipboard_history,
+            clear_usage_data,
+            get_usage_leaderboard,
+            reset_index,
+            uninstall_cleanup,
             eval_math,
+            copy_to_clipboard,
+            copy_path_to_clipboard,
+            copy_file_to_clipboard,
             launch_file,
+            launch_file_elevated,
+            relaunch_last,
+            launch_file_with_args,
+            launch_files,
+            get_console_mode,
+            set_console_mode,
             open_containing_folder,
+            delete_file,
+            extract_archive,
+            get_actions,
+            run_action,
+            open_terminal_at,
+            open_with_dialog,
+            show_file_properties,
+            get_file_preview,
+            get_thumbnail,
+            get_file_details,
+            get_terminal,
+            set_terminal,
+            get_file_manager,
+            set_file_manager,
             rebuild_index,
+            run_launcher_command,
+            run_system_action,
+            kill_process,
+            check_for_updates,
+            install_update,
+            focus_window,
+            get_hotkey,
+            set_hotkey,
+            get_hotkeys,
+            set_hotkeys,
+            is_autostart_enabled,
+            enable_autostart,
+            disable_autostart,
+            get_onboarding_state,
+            complete_onboarding,
+            get_settings,
+            update_settings,
+            set_quick_slot,
+            clear_quick_slot,
+            get_quick_slots,
+            get_locale,
+            set_locale,
             get_index_count,
+            run_benchmark,
             is_indexing,
+            is_window_pinned,
+            set_window_pinned,
+            get_indexing_status,
+            get_recent_errors,
+            open_log_folder,
+            take_startup_query,
         ])
-        .setup(|app| {
+        .setup(move |app| {
             let handle = app.handle().clone();
 
+            if let DbLocation::Synced { client } = db_location {
+                let title = i18n::t_with("notify.synced_db", &[("client", &client)]);
+                let body = i18n::t_with("notify.synced_db_body", &[("client", &client)]);
+                report_error(&handle, "database", Severity::Error, format!("{} {}", title, body));
+                notifications::show(&handle, &title, &body);
+            }
+
+            // Built here rather than from the config, so a portable copy
+            // keeps the webview's cache in its own data folder too
+            if let Some(config) = app.config().app.windows.iter().find(|w| w.label == "main") {
+                let mut builder = tauri::WebviewWindowBuilder::from_config(&handle, config)?;
+                if let Some(dir) = AppPaths::current().webview_dir() {
+                    builder = builder.data_directory(dir);
+                }
+                builder.build()?;
+            }
+
+            match autostart::repair() {
+                Ok(true) => info!("Autostart entry pointed at a missing executable; updated it"),
+                Ok(false) => {}
+                Err(e) => report_error(&handle, "autostart", Severity::Transient, e),
+            }
+
+            if let Err(e) = deeplink::register() {
+                report_error(&handle, "deep link", Severity::Transient, e);
+            }
+            let link_handle = handle.clone();
+            if let Err(e) = deeplink::serve(&AppPaths::current().data_dir(), move |link| handle_deep_link(&link_handle, &link)) {
+                report_error(&handle, "deep link", Severity::Error, e);
+            }
+
             // Set up system tray
             if let Err(e) = setup_tray(&handle) {
-                error!("Failed to setup tray: {}", e);
+                report_error(&handle, "tray", Severity::Error, format!("Failed to set up tray: {}", e));
             }
 
             // Register global shortcut
             if let Err(e) = setup_global_shortcut(&handle) {
-                error!("Failed to setup global shortcut: {}", e);
+                report_error(&handle, "hotkeys", Severity::Error, e.to_string());
             }
 
-            // Hide window on focus lost
+            let args: Vec<String> = std::env::args().collect();
+            let link = deeplink::link_from_args(&args);
             if let Some(window) = app.get_webview_window("main") {
-                let win = window.clone();
-                window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::Focused(false) = event {
-                        let _ = win.hide();
+                // Started at sign-in: stay in the tray until the hotkey is
+                // pressed. Started by a link: the link decides.
+                if !args.iter().any(|arg| arg == autostart::HIDDEN_ARG) && link.is_none() {
+                    if let Err(e) = position_window(&handle, &window) {
+                        error!("Failed to position window: {}", e);
+                    }
+                    show_and_focus(&handle, &window);
+                }
+
+                let (win, focus_handle) = (window.clone(), handle.clone());
+                window.on_window_event(move |event| match event {
+                    tauri::WindowEvent::Focused(false) => on_focus_lost(&focus_handle, &win),
+                    tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                        on_geometry_changed(&focus_handle, &win)
                     }
+                    _ => {}
                 });
             }
 
-            // Run initial indexing in background
-            let db_clone = {
+            // The frontend isn't listening yet, so a search link's query
+            // waits for `take_startup_query`
+            let mut startup_query = None;
+            if let Some(link) = &link {
+                match deeplink::parse(link) {
+                    Ok(DeepLink::Search(query)) => {
+                        show_window(&handle);
+                        startup_query = Some(query);
+                    }
+                    _ => handle_deep_link(&handle, link),
+                }
+            }
+            app.manage(StartupQuery(std::sync::Mutex::new(startup_query)));
+
+            // Both follow their setting: clipboard history idles until it's
+            // turned on, update checks wait for their interval
+            {
                 let state = handle.state::<AppState>();
-                state.db.clone()
-            };
+                let settings = state.settings.clone();
+                clipboard_history::watch(state.db.clone(), state.clipboard.clone(), move || {
+                    settings.read().unwrap().clipboard_history
+                });
+                let settings = state.settings.clone();
+                updates::spawn_background_checks(handle.clone(), state.db.clone(), state.updates.clone(), move || {
+                    settings.read().unwrap().update_check_interval_days
+                });
+            }
+
+            // Run initial indexing in background
             let handle_for_index = handle.clone();
             tauri::async_runtime::spawn(async move {
                 let state = handle_for_index.state::<AppState>();
-                let is_indexing = &state.indexing;
-                is_indexing.store(true, std::sync::atomic::Ordering::SeqCst);
-                let _ = handle_for_index.emit("indexing-started", ());
-
-                let result = tokio::task::spawn_blocking(move || indexer::full_index(&db_clone)).await;
-
-                is_indexing.store(false, std::sync::atomic::Ordering::SeqCst);
-                let _ = handle_for_index.emit("indexing-complete", ());
-
-                match result {
-                    Ok(Ok(count)) => info!("Initial index complete: {} files", count),
-                    Ok(Err(e)) => error!("Initial index error: {}", e),
-                    Err(e) => error!("Initial index task error: {}", e),
+                // On first run, wait for the user to pick what to index
+                if state.onboarding.wait().await {
+                    finish_onboarding_with_apps(&handle_for_index).await;
+                }
+                // A rebuild requested this early already covers the initial index
+                let Some(guard) = IndexingGuard::acquire(&state.indexing) else {
+                    return;
+                };
+                match run_full_index(&handle_for_index, guard, IndexTrigger::Startup).await {
+                    Ok(count) => info!("Initial index complete: {} files", count),
+                    Err(e) => report_index_error(&handle_for_index, "Initial index", &e),
                 }
             });
 
             // Start background incremental indexer
             start_background_indexer(&handle);
+            start_hot_folder_watcher(&handle);
+
+            // Keep the thumbnail cache from growing without bound
+            let thumbnails = handle.state::<AppState>().thumbnails.clone();
+            tauri::async_runtime::spawn_blocking(move || match thumbnails.prune() {
+                0 => {}
+                removed => info!("Removed {} old thumbnails", removed),
+            });
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Let writes still queued reach the database before the process ends
+                app.state::<AppState>().db.shutdown();
+            }
+        });
 }
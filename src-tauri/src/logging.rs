@@ -0,0 +1,224 @@
+//! Logging to stderr and to a file in the `logs` folder of the data folder
+//! (`%LOCALAPPDATA%\AnCheck`, or next to the exe in portable mode; see
+//! `paths`), so a tray app started at sign-in, with no console, still
+//! leaves a trace.
+//!
+//! `ancheck.log` is the current file. Once it passes [`MAX_LOG_BYTES`] it
+//! becomes `ancheck.1.log`, the previous `ancheck.1.log` becomes
+//! `ancheck.2.log`, and so on; [`KEPT_LOG_FILES`] files are kept in all.
+//! The level comes from [`LogLevel`] in the settings and can be changed
+//! while running.
+
+use crate::paths::AppPaths;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// The current log plus this many minus one rotated ones.
+pub const KEPT_LOG_FILES: usize = 3;
+const LOG_STEM: &str = "ancheck";
+
+/// How much gets logged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(LogLevel::Error),
+            "warn" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            "trace" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// The folder holding the log files.
+pub fn log_dir() -> PathBuf {
+    AppPaths::current().log_dir()
+}
+
+/// Install the logger at the default level. `RUST_LOG` still narrows it
+/// per module, e.g. `RUST_LOG=tao=warn`.
+pub fn init() {
+    let dir = log_dir();
+    let file = match RotatingFile::open(&dir, MAX_LOG_BYTES) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Logging to stderr only; can't open a log file in {}: {}", dir.display(), e);
+            None
+        }
+    };
+    env_logger::Builder::new()
+        .filter_level(LevelFilter::Trace)
+        .parse_default_env()
+        .target(env_logger::Target::Pipe(Box::new(LogWriter { file })))
+        .init();
+    set_level(LogLevel::default());
+}
+
+/// Change the level of everything logged from now on.
+pub fn set_level(level: LogLevel) {
+    log::set_max_level(level.filter());
+}
+
+/// Writes each record to stderr and, if it could be opened, the log file.
+struct LogWriter {
+    file: Option<RotatingFile>,
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _ = io::stderr().write_all(buf);
+        if let Some(file) = &mut self.file {
+            // A full disk shouldn't take down whatever was logging
+            let _ = file.write_all(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `ancheck.log` in `dir`, rotated once it reaches `max_bytes`.
+struct RotatingFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let file = open_append(&log_path(dir, 0))?;
+        let len = file.metadata()?.len();
+        Ok(RotatingFile {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            file,
+            len,
+        })
+    }
+
+    /// Shift every log file up one number, dropping the oldest, and start
+    /// a fresh `ancheck.log`.
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (0..KEPT_LOG_FILES - 1).rev() {
+            let from = log_path(&self.dir, index);
+            if from.exists() {
+                // On Windows a rename doesn't replace an existing file
+                let to = log_path(&self.dir, index + 1);
+                let _ = std::fs::remove_file(&to);
+                std::fs::rename(&from, &to)?;
+            }
+        }
+        self.file = open_append(&log_path(&self.dir, 0))?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// `ancheck.log` for 0, `ancheck.<index>.log` for older files.
+fn log_path(dir: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        dir.join(format!("{}.log", LOG_STEM))
+    } else {
+        dir.join(format!("{}.{}.log", LOG_STEM, index))
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_rotation() {
+        let dir = std::env::temp_dir().join(format!("ancheck-logs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut file = RotatingFile::open(&dir, 10).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        drop(file);
+
+        let read = |index| std::fs::read_to_string(log_path(&dir, index)).ok();
+        assert_eq!(read(0).as_deref(), Some("fourth\n"));
+        assert_eq!(read(1).as_deref(), Some("third\n"));
+        assert_eq!(read(2).as_deref(), Some("second\n"));
+        assert_eq!(read(3), None);
+
+        // Reopening continues the current file
+        let mut file = RotatingFile::open(&dir, 100).unwrap();
+        file.write_all(b"fifth\n").unwrap();
+        assert_eq!(read(0).as_deref(), Some("fourth\nfifth\n"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_log_level_ids_round_trip() {
+        for level in [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace] {
+            assert_eq!(LogLevel::parse(level.as_str()), Some(level));
+        }
+        assert_eq!(LogLevel::parse("verbose"), None);
+    }
+}
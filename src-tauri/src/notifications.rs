@@ -0,0 +1,91 @@
+//! Windows toasts about background index passes, which otherwise go
+//! unnoticed: one that failed, removed a lot of files (a drive that went
+//! away?) or ran unusually long. The thresholds, and whether to notify at
+//! all, are settings; see [`NotifyPolicy`].
+//!
+//! Clicking a toast starts AnCheck from its Start Menu shortcut, which
+//! hands over to the running instance and shows the launcher, as opening
+//! it a second time always does.
+
+use crate::i18n::{t, t_with};
+use log::warn;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// When a finished background pass is worth a toast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotifyPolicy {
+    pub enabled: bool,
+    /// A pass taking at least this long is reported.
+    pub slow_run: Duration,
+    /// A pass removing at least this many files is reported.
+    pub removed_files: usize,
+}
+
+/// Title and text of the toast for a background pass that took `duration`
+/// and ended with `result` (files indexed and removed), or `None` if the
+/// policy doesn't call for one. A failure beats many removals, which beat
+/// a slow pass.
+pub fn index_run_toast(
+    policy: &NotifyPolicy,
+    duration: Duration,
+    result: &Result<(usize, usize), String>,
+) -> Option<(String, String)> {
+    if !policy.enabled {
+        return None;
+    }
+    match result {
+        Err(e) => Some((t("notify.index_failed").to_string(), e.clone())),
+        Ok((_, removed)) if *removed >= policy.removed_files => Some((
+            t_with("notify.files_removed", &[("count", removed)]),
+            t("notify.files_removed_body").to_string(),
+        )),
+        Ok((indexed, _)) if duration >= policy.slow_run => Some((
+            t("notify.slow_index").to_string(),
+            t_with(
+                "notify.slow_index_body",
+                &[("minutes", &(duration.as_secs() / 60)), ("count", indexed)],
+            ),
+        )),
+        Ok(_) => None,
+    }
+}
+
+/// Show a toast. Failures are only logged; they're never worth more noise.
+pub fn show(app: &AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        warn!("Failed to show notification '{}': {}", title, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_run_toast() {
+        let policy = NotifyPolicy {
+            enabled: true,
+            slow_run: Duration::from_secs(10 * 60),
+            removed_files: 500,
+        };
+        let quick = Duration::from_secs(30);
+        let slow = Duration::from_secs(12 * 60);
+
+        assert_eq!(index_run_toast(&policy, quick, &Ok((9000, 3))), None);
+        let (title, _) = index_run_toast(&policy, quick, &Ok((9000, 800))).unwrap();
+        assert_eq!(title, "800 files left the index");
+        let (title, body) = index_run_toast(&policy, slow, &Ok((9000, 3))).unwrap();
+        assert_eq!(title, "Indexing took a while");
+        assert!(body.contains("12 minutes"));
+        let failed = Err("Database is locked".to_string());
+        assert_eq!(
+            index_run_toast(&policy, slow, &failed),
+            Some(("Indexing failed".to_string(), "Database is locked".to_string()))
+        );
+
+        let off = NotifyPolicy { enabled: false, ..policy };
+        assert_eq!(index_run_toast(&off, slow, &failed), None);
+    }
+}
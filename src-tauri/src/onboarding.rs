@@ -0,0 +1,166 @@
+//! First-run onboarding. A fresh install doesn't index anything until the
+//! user picks what to: just apps, apps and documents, or everything (see
+//! [`IndexScope`]). The pick becomes the `index_roots` setting. If nobody
+//! picks within [`ONBOARDING_TIMEOUT`], the apps-only scope is used, so the
+//! launcher is useful after a short index instead of a long one.
+//!
+//! An install that already has an index, from before onboarding existed,
+//! counts as onboarded.
+
+use crate::db::Database;
+use crate::indexer;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Set once onboarding finished, by a pick or by the timeout.
+pub const FIRST_RUN_DONE_KEY: &str = "first_run_done";
+/// How long the initial index waits for a pick.
+pub const ONBOARDING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What the initial index covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexScope {
+    /// Start Menus and Program Files.
+    Apps,
+    /// Apps, Desktop and Documents.
+    AppsAndDocuments,
+    /// Everything indexed by default, Downloads included.
+    Everything,
+}
+
+impl IndexScope {
+    pub const ALL: [IndexScope; 3] = [IndexScope::Apps, IndexScope::AppsAndDocuments, IndexScope::Everything];
+
+    /// The folders this scope indexes on this machine.
+    pub fn roots(self) -> Vec<PathBuf> {
+        match self {
+            IndexScope::Apps => indexer::app_directories(),
+            IndexScope::AppsAndDocuments => {
+                let mut roots = indexer::document_folders();
+                roots.extend(indexer::app_directories());
+                indexer::existing_directories(roots)
+            }
+            IndexScope::Everything => indexer::default_index_directories(),
+        }
+    }
+}
+
+/// One option of the first-run chooser.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopeChoice {
+    pub scope: IndexScope,
+    /// What to pass to `complete_onboarding` for it.
+    pub roots: Vec<String>,
+}
+
+/// What the frontend needs to show the first-run chooser.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    /// Nothing has been indexed yet and no scope was picked; show the
+    /// chooser.
+    pub first_run: bool,
+    pub choices: Vec<ScopeChoice>,
+    /// Seconds until the apps-only scope is picked for the user; `None`
+    /// once onboarding is done.
+    pub seconds_left: Option<u64>,
+}
+
+/// Whether onboarding still waits for a pick, and the signal for the
+/// initial index that it's done.
+pub struct Onboarding {
+    pending: AtomicBool,
+    started: Instant,
+    done: tokio::sync::Notify,
+}
+
+impl Onboarding {
+    pub fn new(db: &Database) -> Self {
+        Onboarding {
+            pending: AtomicBool::new(is_first_run(db)),
+            started: Instant::now(),
+            done: tokio::sync::Notify::new(),
+        }
+    }
+
+    /// Still waiting for a pick: nothing should be indexed yet.
+    pub fn is_pending(&self) -> bool {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    pub fn state(&self) -> OnboardingState {
+        let pending = self.is_pending();
+        OnboardingState {
+            first_run: pending,
+            choices: IndexScope::ALL
+                .iter()
+                .map(|&scope| ScopeChoice {
+                    scope,
+                    roots: scope.roots().iter().map(|root| root.to_string_lossy().to_string()).collect(),
+                })
+                .collect(),
+            seconds_left: pending.then(|| ONBOARDING_TIMEOUT.saturating_sub(self.started.elapsed()).as_secs()),
+        }
+    }
+
+    /// Record that onboarding is done and let the initial index start.
+    /// Returns `false` if it already was, e.g. by the timeout.
+    pub fn finish(&self, db: &Database) -> Result<bool, String> {
+        if !self.pending.swap(false, Ordering::SeqCst) {
+            return Ok(false);
+        }
+        db.set_meta(FIRST_RUN_DONE_KEY, "true")
+            .map_err(|e| format!("Failed to save onboarding: {}", e))?;
+        self.done.notify_one();
+        Ok(true)
+    }
+
+    /// Wait until onboarding is done or [`ONBOARDING_TIMEOUT`] has passed
+    /// since it started. Returns `true` on the timeout with onboarding
+    /// still pending.
+    pub async fn wait(&self) -> bool {
+        if !self.is_pending() {
+            return false;
+        }
+        let left = ONBOARDING_TIMEOUT.saturating_sub(self.started.elapsed());
+        tokio::time::timeout(left, self.done.notified()).await.is_err() && self.is_pending()
+    }
+}
+
+/// No scope was picked yet and nothing was ever indexed.
+fn is_first_run(db: &Database) -> bool {
+    let meta = |key| db.get_meta(key).ok().flatten();
+    meta(FIRST_RUN_DONE_KEY).is_none() && meta("last_full_index").is_none() && db.file_count().unwrap_or(0) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_run_only_on_a_fresh_index() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let onboarding = Onboarding::new(&db);
+        assert!(onboarding.is_pending());
+        assert!(onboarding.state().first_run);
+        assert!(onboarding.finish(&db).unwrap());
+        assert!(!onboarding.finish(&db).unwrap());
+        assert!(!onboarding.state().first_run);
+        assert_eq!(onboarding.state().seconds_left, None);
+        assert!(!Onboarding::new(&db).is_pending());
+
+        // An index from before onboarding existed counts as onboarded
+        let upgraded = Database::open(&PathBuf::from(":memory:")).unwrap();
+        upgraded.upsert_file("a.txt", r"C:\a.txt", "txt", 1, 1, "document").unwrap();
+        assert!(!Onboarding::new(&upgraded).is_pending());
+    }
+
+    #[test]
+    fn test_wider_scopes_cover_narrower_ones() {
+        let [apps, documents, everything] = IndexScope::ALL.map(IndexScope::roots);
+        assert!(apps.iter().all(|root| documents.contains(root)));
+        assert!(documents.iter().all(|root| everything.contains(root)));
+    }
+}
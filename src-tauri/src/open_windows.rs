@@ -0,0 +1,237 @@
+//! Open windows of other apps, offered as search results so picking one
+//! switches to it instead of starting another instance.
+//!
+//! The list is taken when the launcher opens ([`WindowList::refresh`]), not
+//! per keystroke: windows rarely come and go while the user types. Only
+//! visible, titled, unowned top-level windows are listed, the ones Alt+Tab
+//! would show, and never the launcher's own.
+
+use crate::processes::ProcessCache;
+use std::sync::{Arc, Mutex, RwLock};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenWindow {
+    /// Window handle, passed back to [`focus_window`].
+    pub hwnd: isize,
+    pub title: String,
+    pub pid: u32,
+    /// Executable file name of the owning process, e.g. "chrome.exe";
+    /// empty if the process is gone or couldn't be read.
+    pub exe_name: String,
+}
+
+/// The windows seen when the launcher last opened.
+#[derive(Default)]
+pub struct WindowList {
+    windows: RwLock<Arc<Vec<OpenWindow>>>,
+    /// Had the focus before the launcher took it.
+    previous_foreground: Mutex<Option<isize>>,
+    /// Executable name of that window's process, lowercase.
+    previous_app: Mutex<Option<String>>,
+}
+
+impl WindowList {
+    /// The list from the last [`WindowList::refresh`].
+    pub fn get(&self) -> Arc<Vec<OpenWindow>> {
+        self.windows.read().unwrap().clone()
+    }
+
+    /// Enumerate the open windows again, naming their processes from
+    /// `processes`.
+    pub fn refresh(&self, processes: &ProcessCache) {
+        let own_pid = std::process::id();
+        let listed: Vec<(isize, String, u32)> = list_windows().into_iter().filter(|w| w.2 != own_pid).collect();
+        let running = if listed.is_empty() { Arc::default() } else { processes.get() };
+        let windows = listed
+            .into_iter()
+            .map(|(hwnd, title, pid)| OpenWindow {
+                hwnd,
+                title,
+                pid,
+                exe_name: running
+                    .iter()
+                    .find(|p| p.pid == pid)
+                    .map(|p| p.exe_name.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        *self.windows.write().unwrap() = Arc::new(windows);
+    }
+
+    /// Note the foreground window and its app, before the launcher shows
+    /// and takes the focus. If that's already the launcher, the last ones
+    /// noted are kept.
+    pub fn remember_foreground(&self) {
+        if let Some((hwnd, pid)) = foreground_window() {
+            *self.previous_foreground.lock().unwrap() = Some(hwnd);
+            *self.previous_app.lock().unwrap() = process_exe_name(pid).map(|name| name.to_lowercase());
+        }
+    }
+
+    /// The window that had the focus when the launcher last opened.
+    pub fn previous_foreground(&self) -> Option<isize> {
+        *self.previous_foreground.lock().unwrap()
+    }
+
+    /// Executable name of the app that had the focus when the launcher last
+    /// opened, lowercase (e.g. "slack.exe").
+    pub fn previous_app(&self) -> Option<String> {
+        self.previous_app.lock().unwrap().clone()
+    }
+}
+
+/// The foreground window and its process id, unless it's one of the
+/// launcher's own.
+#[cfg(windows)]
+fn foreground_window() -> Option<(isize, u32)> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    // SAFETY: plain queries; a null handle is checked before use.
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        (pid != std::process::id()).then_some((hwnd.0 as isize, pid))
+    }
+}
+
+#[cfg(not(windows))]
+fn foreground_window() -> Option<(isize, u32)> {
+    None
+}
+
+/// File name of the executable process `pid` runs, if it can be queried.
+#[cfg(windows)]
+fn process_exe_name(pid: u32) -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    // SAFETY: the handle is closed exactly once, and `len` tells the call
+    // how much of `buffer` it may write.
+    let path = unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let result = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut len);
+        let _ = CloseHandle(handle);
+        result.ok()?;
+        String::from_utf16_lossy(&buffer[..len as usize])
+    };
+    std::path::Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+#[cfg(not(windows))]
+fn process_exe_name(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Handle, title and process id of every window Alt+Tab would show.
+#[cfg(windows)]
+fn list_windows() -> Vec<(isize, String, u32)> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindow, GetWindowLongW, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+        GWL_EXSTYLE, GW_OWNER, WS_EX_TOOLWINDOW,
+    };
+
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: `lparam` is the Vec passed to EnumWindows below, which
+        // outlives the enumeration.
+        let windows = &mut *(lparam.0 as *mut Vec<(isize, String, u32)>);
+        let has_owner = GetWindow(hwnd, GW_OWNER).is_ok_and(|owner| !owner.is_invalid());
+        let is_tool = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW.0 != 0;
+        if !IsWindowVisible(hwnd).as_bool() || has_owner || is_tool {
+            return true.into();
+        }
+        // Suspended Store apps keep a visible but cloaked frame
+        let mut cloaked = 0u32;
+        let _ = DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_CLOAKED,
+            (&mut cloaked as *mut u32).cast(),
+            std::mem::size_of::<u32>() as u32,
+        );
+        if cloaked != 0 {
+            return true.into();
+        }
+        let mut buffer = [0u16; 512];
+        let len = GetWindowTextW(hwnd, &mut buffer).max(0) as usize;
+        let title = String::from_utf16_lossy(&buffer[..len]);
+        if title.trim().is_empty() {
+            return true.into();
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        windows.push((hwnd.0 as isize, title, pid));
+        true.into()
+    }
+
+    let mut windows: Vec<(isize, String, u32)> = Vec::new();
+    // SAFETY: `collect` only runs during this call, while `windows` is alive.
+    if let Err(e) = unsafe { EnumWindows(Some(collect), LPARAM(&mut windows as *mut _ as isize)) } {
+        log::warn!("Failed to enumerate windows: {}", e);
+    }
+    windows
+}
+
+#[cfg(not(windows))]
+fn list_windows() -> Vec<(isize, String, u32)> {
+    Vec::new()
+}
+
+/// Restore `hwnd` if minimized and bring it to the foreground.
+///
+/// Windows only lets the foreground process move the foreground elsewhere.
+/// The launcher usually is that process, having just been picked from, but
+/// it hides first; attaching to the foreground thread's input makes the
+/// call count as coming from there.
+#[cfg(windows)]
+pub fn focus_window(hwnd: isize) -> Result<(), String> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Threading::{AttachThreadInput, GetCurrentThreadId};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        BringWindowToTop, GetForegroundWindow, GetWindowThreadProcessId, IsIconic, IsWindow, SetForegroundWindow,
+        ShowWindow, SW_RESTORE,
+    };
+
+    let hwnd = HWND(hwnd as *mut core::ffi::c_void);
+    // SAFETY: `hwnd` is checked with IsWindow first; every thread input
+    // attached here is detached again before returning.
+    unsafe {
+        if !IsWindow(hwnd).as_bool() {
+            return Err("That window has been closed".to_string());
+        }
+        if IsIconic(hwnd).as_bool() {
+            let _ = ShowWindow(hwnd, SW_RESTORE);
+        }
+        let own_thread = GetCurrentThreadId();
+        let foreground_thread = GetWindowThreadProcessId(GetForegroundWindow(), None);
+        let attached = foreground_thread != 0
+            && foreground_thread != own_thread
+            && AttachThreadInput(own_thread, foreground_thread, true).as_bool();
+        let focused = SetForegroundWindow(hwnd).as_bool();
+        let _ = BringWindowToTop(hwnd);
+        if attached {
+            let _ = AttachThreadInput(own_thread, foreground_thread, false);
+        }
+        if !focused {
+            return Err("Windows refused to switch to that window".to_string());
+        }
+    }
+    log::info!("Switched to window {:?}", hwnd.0);
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn focus_window(hwnd: isize) -> Result<(), String> {
+    Err(format!("Can't switch to window {}: only supported on Windows", hwnd))
+}
@@ -0,0 +1,376 @@
+//! Where AnCheck keeps its files: the index database (which also holds the
+//! settings), the logs and the webview's cache. Everything written to disk
+//! gets its location from [`AppPaths::current`], so portable mode can't
+//! miss a file.
+//!
+//! Installed, that's `%LOCALAPPDATA%\AnCheck`. Portable mode, chosen by a
+//! `portable.marker` file next to the executable or the `--portable`
+//! argument, keeps it all in a `data` folder beside the executable instead,
+//! so a copy on a USB stick takes its index from machine to machine.
+//!
+//! A data folder inside a synced folder (OneDrive, Dropbox and the like)
+//! puts the database at risk: sync clients copy its `-wal` file out of step
+//! with it. At startup the database is moved to a local folder in the
+//! profile, and the data folder records where it went. A portable copy
+//! keeps its database with it instead, opened without WAL.
+
+use crate::db::Database;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Command-line flag that turns on portable mode without a marker file.
+pub const PORTABLE_ARG: &str = "--portable";
+/// File next to the executable that turns on portable mode.
+const PORTABLE_MARKER: &str = "portable.marker";
+const PORTABLE_DATA_DIR: &str = "data";
+const DB_FILE: &str = "ancheck_index.db";
+/// The `identifier` in tauri.conf.json, which names Tauri's own data folder.
+const TAURI_IDENTIFIER: &str = "com.harshal-patel.ancheck";
+/// File in the data folder holding the path the database was moved to
+/// (see [`relocate_off_sync_root`]).
+const DB_LOCATION_FILE: &str = "db_location";
+/// Folder in the profile a database is moved to out of a synced data
+/// folder. Sync clients take over folders in the profile, not the profile.
+const LOCAL_DB_DIR: &str = ".ancheck";
+/// Meta key recording where the database in use lives.
+pub const DB_LOCATION_KEY: &str = "db_location";
+/// Folder names sync clients give their roots, lowercase, and the client.
+/// A root may carry a suffix, as in "OneDrive - Contoso" or "Dropbox (Personal)".
+const SYNC_FOLDERS: &[(&str, &str)] = &[
+    ("onedrive", "OneDrive"),
+    ("dropbox", "Dropbox"),
+    ("google drive", "Google Drive"),
+    ("my drive", "Google Drive"),
+    ("iclouddrive", "iCloud Drive"),
+    ("icloud drive", "iCloud Drive"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppPaths {
+    data_dir: PathBuf,
+    portable: bool,
+}
+
+impl AppPaths {
+    /// The paths of this process, decided from its arguments and the
+    /// executable's folder on first use.
+    pub fn current() -> &'static AppPaths {
+        static CURRENT: OnceLock<AppPaths> = OnceLock::new();
+        CURRENT.get_or_init(|| {
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            let exe_dir = std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf));
+            AppPaths::resolve(&args, exe_dir.as_deref(), installed_data_dir())
+        })
+    }
+
+    fn resolve(args: &[String], exe_dir: Option<&Path>, installed_dir: PathBuf) -> AppPaths {
+        let portable_dir = exe_dir
+            .filter(|dir| args.iter().any(|arg| arg == PORTABLE_ARG) || dir.join(PORTABLE_MARKER).is_file())
+            .map(|dir| dir.join(PORTABLE_DATA_DIR));
+        match portable_dir {
+            Some(data_dir) => AppPaths { data_dir, portable: true },
+            None => AppPaths {
+                data_dir: installed_dir,
+                portable: false,
+            },
+        }
+    }
+
+    pub fn is_portable(&self) -> bool {
+        self.portable
+    }
+
+    /// The data folder, created if missing.
+    pub fn data_dir(&self) -> PathBuf {
+        std::fs::create_dir_all(&self.data_dir).ok();
+        self.data_dir.clone()
+    }
+
+    /// The database, in the data folder unless it was moved out of a
+    /// synced one.
+    pub fn db_path(&self) -> PathBuf {
+        let data_dir = self.data_dir();
+        relocated_db_path(&data_dir).unwrap_or_else(|| data_dir.join(DB_FILE))
+    }
+
+    /// The folder holding cached result thumbnails.
+    pub fn thumbnail_dir(&self) -> PathBuf {
+        self.data_dir.join("thumbnails")
+    }
+
+    /// The folder holding the log files.
+    pub fn log_dir(&self) -> PathBuf {
+        self.data_dir.join("logs")
+    }
+
+    /// Where the webview keeps its cache and storage. Installed, Tauri's
+    /// default under `%LOCALAPPDATA%` is left alone.
+    pub fn webview_dir(&self) -> Option<PathBuf> {
+        self.portable.then(|| self.data_dir.join("webview"))
+    }
+
+    /// Every folder AnCheck writes to, for cleaning up before an uninstall.
+    pub fn all_dirs(&self) -> Vec<PathBuf> {
+        let mut folders = vec![self.data_dir.clone()];
+        if let Some(folder) = relocated_db_path(&self.data_dir).and_then(|db| db.parent().map(Path::to_path_buf)) {
+            folders.push(folder);
+        }
+        if !self.portable {
+            if let Some(local) = dirs::data_local_dir() {
+                folders.push(local.join(TAURI_IDENTIFIER));
+            }
+        }
+        folders
+    }
+}
+
+fn installed_data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("AnCheck")
+}
+
+/// The database path recorded in `data_dir` by a relocation, while its
+/// folder exists. On another machine syncing the data folder it may not,
+/// and that machine gets its own local database in turn.
+fn relocated_db_path(data_dir: &Path) -> Option<PathBuf> {
+    let recorded = std::fs::read_to_string(data_dir.join(DB_LOCATION_FILE)).ok()?;
+    let path = PathBuf::from(recorded.trim());
+    path.parent().is_some_and(Path::is_dir).then_some(path)
+}
+
+/// The sync client `path` looks to be inside, going by the OneDrive folders
+/// Windows reports and the folder names sync clients use.
+pub fn sync_client(path: &Path) -> Option<&'static str> {
+    let onedrive: Vec<PathBuf> = ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .filter(|root| !root.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    sync_client_in(path, &onedrive)
+}
+
+fn sync_client_in(path: &Path, onedrive_roots: &[PathBuf]) -> Option<&'static str> {
+    if onedrive_roots.iter().any(|root| path.starts_with(root)) {
+        return Some("OneDrive");
+    }
+    path.components().find_map(|component| {
+        let name = component.as_os_str().to_str()?.to_lowercase();
+        SYNC_FOLDERS
+            .iter()
+            .find(|(root, _)| name.strip_prefix(root).is_some_and(|rest| rest.is_empty() || rest.starts_with(' ')))
+            .map(|&(_, client)| client)
+    })
+}
+
+/// Where the database ended up, from [`relocate_off_sync_root`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DbLocation {
+    /// Outside any synced folder.
+    Local,
+    /// Moved out of `client`'s folder to `to`.
+    Moved { client: &'static str, to: PathBuf },
+    /// Inside `client`'s folder with nowhere local to go, so it must be
+    /// opened without WAL (see [`Database::open_without_wal`]).
+    Synced { client: &'static str },
+}
+
+impl DbLocation {
+    /// Where `db_path` is, without moving anything.
+    pub fn of(db_path: &Path) -> DbLocation {
+        match sync_client(db_path) {
+            Some(client) => DbLocation::Synced { client },
+            None => DbLocation::Local,
+        }
+    }
+}
+
+/// Move the database out of a synced folder to one in the profile, and
+/// record the new place in the data folder for [`AppPaths::db_path`]. Only
+/// call this before the database is opened, with its [`WriterLock`] held
+/// so no other process has it open either.
+///
+/// In portable mode it stays put, reported as [`DbLocation::Synced`]: moved
+/// into this machine's profile, it would no longer travel with the copy.
+///
+/// [`WriterLock`]: crate::db::WriterLock
+pub fn relocate_off_sync_root(paths: &AppPaths) -> Result<DbLocation, String> {
+    let local_dir = dirs::home_dir()
+        .filter(|_| !paths.is_portable())
+        .map(|home| home.join(LOCAL_DB_DIR));
+    relocate_database(&paths.data_dir(), &paths.db_path(), local_dir.as_deref(), sync_client)
+}
+
+fn relocate_database(
+    data_dir: &Path,
+    db_path: &Path,
+    local_dir: Option<&Path>,
+    sync_client: impl Fn(&Path) -> Option<&'static str>,
+) -> Result<DbLocation, String> {
+    let Some(client) = sync_client(db_path) else {
+        return Ok(DbLocation::Local);
+    };
+    let Some(local_dir) = local_dir.filter(|dir| sync_client(dir).is_none()) else {
+        return Ok(DbLocation::Synced { client });
+    };
+    std::fs::create_dir_all(local_dir).map_err(|e| format!("Failed to create '{}': {}", local_dir.display(), e))?;
+
+    // The copy takes in what's still in the WAL. An existing local database
+    // (from a move whose record got lost) wins, and the synced one is kept.
+    let target = local_dir.join(DB_FILE);
+    let copied = copy_database(db_path, &target)?;
+    std::fs::write(data_dir.join(DB_LOCATION_FILE), target.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Failed to record the index's new location: {}", e))?;
+    if copied {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = db_path.as_os_str().to_owned();
+            file.push(suffix);
+            match std::fs::remove_file(&file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    log::warn!("Failed to remove the synced copy {}: {}", Path::new(&file).display(), e)
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(DbLocation::Moved { client, to: target })
+}
+
+/// On the first portable start, copy the installed index so pins, history
+/// and settings come along. Returns whether a copy was made.
+pub fn migrate_to_portable(paths: &AppPaths) -> Result<bool, String> {
+    if !paths.portable {
+        return Ok(false);
+    }
+    copy_database(&installed_data_dir().join(DB_FILE), &paths.db_path())
+}
+
+/// Copy the database at `source` to `target`, unless `target` already
+/// exists or there's nothing to copy.
+fn copy_database(source: &Path, target: &Path) -> Result<bool, String> {
+    if target.exists() || !source.is_file() {
+        return Ok(false);
+    }
+    let db = Database::open_read_only(source).map_err(|e| format!("Failed to open the installed index: {}", e))?;
+    db.copy_to(target)
+        .map_err(|e| format!("Failed to copy the installed index: {}", e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_mode_detection() {
+        let exe_dir = std::env::temp_dir().join(format!("ancheck-portable-{}", std::process::id()));
+        std::fs::create_dir_all(&exe_dir).unwrap();
+        let installed = PathBuf::from("installed");
+
+        let paths = AppPaths::resolve(&[], Some(&exe_dir), installed.clone());
+        assert_eq!((paths.data_dir.as_path(), paths.portable), (installed.as_path(), false));
+        assert_eq!(paths.webview_dir(), None);
+
+        let args = ["--hidden".to_string(), PORTABLE_ARG.to_string()];
+        let paths = AppPaths::resolve(&args, Some(&exe_dir), installed.clone());
+        assert_eq!((paths.data_dir, paths.portable), (exe_dir.join("data"), true));
+
+        std::fs::write(exe_dir.join(PORTABLE_MARKER), "").unwrap();
+        let paths = AppPaths::resolve(&[], Some(&exe_dir), installed.clone());
+        assert_eq!(paths.log_dir(), exe_dir.join("data").join("logs"));
+        assert!(!AppPaths::resolve(&args, None, installed).portable);
+
+        std::fs::remove_dir_all(&exe_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_client_detection() {
+        let onedrive = [PathBuf::from("/home/me/Work Files")];
+        let client = |path: &str| sync_client_in(Path::new(path), &onedrive);
+        assert_eq!(client("/home/me/Work Files/AppData/AnCheck"), Some("OneDrive"));
+        assert_eq!(client("/home/me/OneDrive - Contoso/AnCheck"), Some("OneDrive"));
+        assert_eq!(client("/home/me/Dropbox (Personal)/tools/data"), Some("Dropbox"));
+        assert_eq!(client("/mnt/g/My Drive/AnCheck"), Some("Google Drive"));
+        assert_eq!(client("/home/me/Dropboxes/AnCheck"), None);
+        assert_eq!(client("/home/me/AppData/Local/AnCheck"), None);
+    }
+
+    #[test]
+    fn test_relocate_database_out_of_sync_root() {
+        let root = std::env::temp_dir().join(format!("ancheck-relocate-{}", std::process::id()));
+        let data_dir = root.join("Dropbox").join("AnCheck");
+        let local_dir = root.join("local");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let paths = AppPaths {
+            data_dir: data_dir.clone(),
+            portable: true,
+        };
+        let client = |path: &Path| sync_client_in(path, &[]);
+        let synced_db = data_dir.join(DB_FILE);
+        Database::open(&synced_db).unwrap().set_meta("result_limit", "20").unwrap();
+
+        // Nowhere local to go: it stays, to be opened without WAL
+        let location = relocate_database(&data_dir, &paths.db_path(), None, client).unwrap();
+        assert_eq!(location, DbLocation::Synced { client: "Dropbox" });
+        assert_eq!(paths.db_path(), synced_db);
+
+        let location = relocate_database(&data_dir, &paths.db_path(), Some(&local_dir), client).unwrap();
+        let moved = local_dir.join(DB_FILE);
+        assert_eq!(location, DbLocation::Moved { client: "Dropbox", to: moved.clone() });
+        assert!(!synced_db.exists());
+        assert_eq!(paths.db_path(), moved);
+        let db = Database::open(&paths.db_path()).unwrap();
+        assert_eq!(db.get_meta("result_limit").unwrap().as_deref(), Some("20"));
+
+        // Once moved, later starts find it local
+        let location = relocate_database(&data_dir, &paths.db_path(), Some(&local_dir), client).unwrap();
+        assert_eq!(location, DbLocation::Local);
+
+        drop(db);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_portable_database_stays_in_sync_root() {
+        let root = std::env::temp_dir().join(format!("ancheck-portable-sync-{}", std::process::id()));
+        let data_dir = root.join("Dropbox").join("AnCheck").join("data");
+        let paths = AppPaths {
+            data_dir: data_dir.clone(),
+            portable: true,
+        };
+        let synced_db = paths.db_path();
+        Database::open(&synced_db).unwrap().set_meta("result_limit", "20").unwrap();
+
+        let location = relocate_off_sync_root(&paths).unwrap();
+        assert_eq!(location, DbLocation::Synced { client: "Dropbox" });
+        assert!(synced_db.exists());
+        assert!(!data_dir.join(DB_LOCATION_FILE).exists());
+        assert_eq!(paths.db_path(), synced_db);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_copy_database_once() {
+        let dir = std::env::temp_dir().join(format!("ancheck-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("installed.db");
+        let target = dir.join("portable.db");
+        assert!(!copy_database(&source, &target).unwrap());
+
+        Database::open(&source).unwrap().set_meta("result_limit", "20").unwrap();
+        assert!(copy_database(&source, &target).unwrap());
+        let copied = Database::open(&target).unwrap();
+        assert_eq!(copied.get_meta("result_limit").unwrap().as_deref(), Some("20"));
+
+        copied.set_meta("result_limit", "30").unwrap();
+        assert!(!copy_database(&source, &target).unwrap());
+        assert_eq!(copied.get_meta("result_limit").unwrap().as_deref(), Some("30"));
+
+        drop(copied);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
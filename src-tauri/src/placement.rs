@@ -0,0 +1,302 @@
+//! Where the launcher window appears when shown.
+//!
+//! The window goes on the monitor picked by [`WindowPlacement`], centred
+//! horizontally and a quarter of the way down the monitor's work area. Its
+//! size is the configured logical size at that monitor's scale factor, so
+//! it looks the same on a 100% and a 150% display.
+//!
+//! Once the user moves or resizes the window, that geometry is remembered
+//! for the current monitor layout instead and restored on every show. A
+//! layout is identified by a hash of its monitors, so unplugging one goes
+//! back to the default placement rather than restoring to a screen that's
+//! gone; a saved geometry is still clamped onto a visible work area.
+
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const GEOMETRY_KEY_PREFIX: &str = "window_geometry:";
+
+/// Which monitor the launcher opens on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowPlacement {
+    /// The monitor under the mouse cursor.
+    #[default]
+    Cursor,
+    /// The monitor showing the window that had focus.
+    FocusedWindow,
+    /// Always the primary monitor.
+    Primary,
+}
+
+impl WindowPlacement {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WindowPlacement::Cursor => "cursor",
+            WindowPlacement::FocusedWindow => "focused_window",
+            WindowPlacement::Primary => "primary",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cursor" => Some(WindowPlacement::Cursor),
+            "focused_window" => Some(WindowPlacement::FocusedWindow),
+            "primary" => Some(WindowPlacement::Primary),
+            _ => None,
+        }
+    }
+}
+
+/// A rectangle in physical pixels on the virtual desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where a window of `logical_size` goes on a monitor with `work_area` and
+/// `scale_factor`: centred horizontally, its top a quarter of the way down,
+/// and never larger than the work area or hanging off its bottom.
+pub fn place(work_area: Bounds, logical_size: (f64, f64), scale_factor: f64) -> Bounds {
+    let width = ((logical_size.0 * scale_factor).round() as u32).min(work_area.width);
+    let height = ((logical_size.1 * scale_factor).round() as u32).min(work_area.height);
+    let x = work_area.x + ((work_area.width - width) / 2) as i32;
+    let top = (work_area.height / 4).min(work_area.height - height);
+    Bounds {
+        x,
+        y: work_area.y + top as i32,
+        width,
+        height,
+    }
+}
+
+impl Bounds {
+    fn center(self) -> (i64, i64) {
+        (
+            self.x as i64 + self.width as i64 / 2,
+            self.y as i64 + self.height as i64 / 2,
+        )
+    }
+
+    /// Area of the part of `self` that's inside `other`.
+    fn overlap(self, other: Bounds) -> i64 {
+        let width = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64)
+            - (self.x as i64).max(other.x as i64);
+        let height = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64)
+            - (self.y as i64).max(other.y as i64);
+        width.max(0) * height.max(0)
+    }
+}
+
+/// Identifies a monitor layout by each monitor's bounds and scale factor,
+/// in any order. Stable across runs, unlike `DefaultHasher`.
+pub fn layout_key(monitors: &[(Bounds, f64)]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|(b, scale)| format!("{},{},{},{}@{}", b.x, b.y, b.width, b.height, scale))
+        .collect();
+    parts.sort();
+    // FNV-1a
+    let hash = parts.join(";").bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Move `bounds` onto the work area it overlaps most, or the nearest one
+/// if it's on none, shrinking it to fit. `None` without work areas.
+pub fn clamp_to_work_areas(bounds: Bounds, work_areas: &[Bounds]) -> Option<Bounds> {
+    let (cx, cy) = bounds.center();
+    let area = work_areas.iter().copied().max_by_key(|area| {
+        let (ax, ay) = area.center();
+        // Overlap first; the negated distance only breaks ties at zero
+        (bounds.overlap(*area), -((ax - cx).pow(2) + (ay - cy).pow(2)))
+    })?;
+    let width = bounds.width.min(area.width);
+    let height = bounds.height.min(area.height);
+    Some(Bounds {
+        x: bounds.x.clamp(area.x, area.x + (area.width - width) as i32),
+        y: bounds.y.clamp(area.y, area.y + (area.height - height) as i32),
+        width,
+        height,
+    })
+}
+
+/// The geometry the user left the window at in monitor layout `layout`.
+pub fn saved_geometry(db: &Database, layout: &str) -> Option<Bounds> {
+    let value = db.get_meta(&format!("{}{}", GEOMETRY_KEY_PREFIX, layout)).ok().flatten()?;
+    let parts: Vec<&str> = value.split(',').collect();
+    match parts.as_slice() {
+        [x, y, width, height] => Some(Bounds {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            width: width.parse().ok().filter(|&w| w > 0)?,
+            height: height.parse().ok().filter(|&h| h > 0)?,
+        }),
+        _ => None,
+    }
+}
+
+pub fn save_geometry(db: &Database, layout: &str, bounds: Bounds) -> Result<(), String> {
+    let value = format!("{},{},{},{}", bounds.x, bounds.y, bounds.width, bounds.height);
+    db.set_meta(&format!("{}{}", GEOMETRY_KEY_PREFIX, layout), &value)
+        .map_err(|e| format!("Failed to save window geometry: {}", e))
+}
+
+/// Tells the user's moves and resizes apart from the launcher's own
+/// placement, and lets a burst of them be saved once.
+#[derive(Default)]
+pub struct GeometryTracker {
+    /// Bumped by every move or resize.
+    generation: AtomicU64,
+    /// Where the launcher last put the window itself.
+    placed: Mutex<Option<Bounds>>,
+}
+
+impl GeometryTracker {
+    /// Note that the launcher put the window at `bounds`.
+    pub fn set_placed(&self, bounds: Bounds) {
+        *self.placed.lock().unwrap() = Some(bounds);
+    }
+
+    /// Note a move or resize, returning its generation for [`Self::is_latest`].
+    pub fn changed(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Whether no move or resize came after `generation`.
+    pub fn is_latest(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+
+    /// Whether the window at `bounds` was put there by the user rather
+    /// than by the launcher.
+    pub fn is_users(&self, bounds: Bounds) -> bool {
+        *self.placed.lock().unwrap() != Some(bounds)
+    }
+}
+
+/// Centre of the foreground window, in physical pixels. `None` if no
+/// window has focus.
+#[cfg(windows)]
+pub fn foreground_window_center() -> Option<(f64, f64)> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowRect};
+
+    // SAFETY: plain queries; a handle that went stale in between makes
+    // GetWindowRect fail rather than misbehave.
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        Some((
+            (rect.left as f64 + rect.right as f64) / 2.0,
+            (rect.top as f64 + rect.bottom as f64) / 2.0,
+        ))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn foreground_window_center() -> Option<(f64, f64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_on_monitor() {
+        // 1080p primary with a 48px taskbar at the bottom
+        let primary = Bounds { x: 0, y: 0, width: 1920, height: 1032 };
+        assert_eq!(
+            place(primary, (800.0, 600.0), 1.0),
+            Bounds { x: 560, y: 258, width: 800, height: 600 }
+        );
+
+        // 4K monitor at 150% to the left of the primary
+        let left = Bounds { x: -3840, y: 0, width: 3840, height: 2112 };
+        assert_eq!(
+            place(left, (800.0, 600.0), 1.5),
+            Bounds { x: -3840 + 1320, y: 528, width: 1200, height: 900 }
+        );
+
+        // Small monitor at 200%: clamped to the work area, pushed up to fit
+        let small = Bounds { x: 1920, y: 0, width: 1366, height: 728 };
+        assert_eq!(
+            place(small, (800.0, 600.0), 2.0),
+            Bounds { x: 1920, y: 0, width: 1366, height: 728 }
+        );
+        let short = Bounds { x: 0, y: 100, width: 1600, height: 700 };
+        assert_eq!(place(short, (800.0, 600.0), 1.0).y, 100 + 100);
+    }
+
+    #[test]
+    fn test_layout_key_ignores_monitor_order() {
+        let primary = (Bounds { x: 0, y: 0, width: 1920, height: 1080 }, 1.0);
+        let left = (Bounds { x: -3840, y: 0, width: 3840, height: 2160 }, 1.5);
+        assert_eq!(layout_key(&[primary, left]), layout_key(&[left, primary]));
+        assert_ne!(layout_key(&[primary, left]), layout_key(&[primary]));
+        assert_ne!(layout_key(&[primary]), layout_key(&[(primary.0, 1.25)]));
+    }
+
+    #[test]
+    fn test_clamp_to_work_areas() {
+        let primary = Bounds { x: 0, y: 0, width: 1920, height: 1032 };
+        let right = Bounds { x: 1920, y: 0, width: 1280, height: 984 };
+        let areas = [primary, right];
+
+        let inside = Bounds { x: 100, y: 100, width: 800, height: 600 };
+        assert_eq!(clamp_to_work_areas(inside, &areas), Some(inside));
+
+        // Mostly on the right monitor and hanging off its bottom
+        let straddling = Bounds { x: 1800, y: 700, width: 800, height: 600 };
+        assert_eq!(
+            clamp_to_work_areas(straddling, &areas),
+            Some(Bounds { x: 1920, y: 384, width: 800, height: 600 })
+        );
+
+        // On a monitor that's gone: onto the nearest, shrunk to fit
+        let gone = Bounds { x: 5000, y: 0, width: 1600, height: 1200 };
+        assert_eq!(
+            clamp_to_work_areas(gone, &areas),
+            Some(Bounds { x: 1920, y: 0, width: 1280, height: 984 })
+        );
+        assert_eq!(clamp_to_work_areas(gone, &[]), None);
+    }
+
+    #[test]
+    fn test_geometry_saved_per_layout() {
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        let bounds = Bounds { x: -200, y: 50, width: 900, height: 640 };
+        assert_eq!(saved_geometry(&db, "a"), None);
+        save_geometry(&db, "a", bounds).unwrap();
+        assert_eq!(saved_geometry(&db, "a"), Some(bounds));
+        assert_eq!(saved_geometry(&db, "b"), None);
+
+        let tracker = GeometryTracker::default();
+        tracker.set_placed(bounds);
+        assert!(!tracker.is_users(bounds));
+        assert!(tracker.is_users(Bounds { x: 0, ..bounds }));
+        let first = tracker.changed();
+        assert!(tracker.is_latest(first));
+        tracker.changed();
+        assert!(!tracker.is_latest(first));
+    }
+
+    #[test]
+    fn test_placement_ids_round_trip() {
+        for placement in [WindowPlacement::Cursor, WindowPlacement::FocusedWindow, WindowPlacement::Primary] {
+            assert_eq!(WindowPlacement::parse(placement.as_str()), Some(placement));
+        }
+        assert_eq!(WindowPlacement::parse("leftmost"), None);
+    }
+}
@@ -0,0 +1,278 @@
+//! File previews for the details pane: the start of a text file, or a
+//! thumbnail of an image.
+//!
+//! Reads are capped and run on a worker thread with a timeout, so a huge file
+//! or a stalled network share can't hold up the UI. Cloud-only placeholders
+//! (OneDrive "online-only" files) are never opened, since reading them would
+//! download the whole file.
+
+use base64::Engine;
+use log::warn;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+/// Text read when the caller doesn't pass `max_bytes`.
+const DEFAULT_TEXT_BYTES: u64 = 64 * 1024;
+/// Upper bound on `max_bytes`.
+const MAX_TEXT_BYTES: u64 = 1024 * 1024;
+/// Larger images get no thumbnail rather than a long decode.
+pub(crate) const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+/// Decoder memory limit, against images that claim huge dimensions.
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+/// Longest side of a thumbnail, in pixels.
+const THUMBNAIL_SIZE: u32 = 256;
+/// How long a preview may take before giving up (slow network paths).
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FilePreview {
+    Text {
+        content: String,
+        /// Only the first part of the file was read.
+        truncated: bool,
+    },
+    Image {
+        /// PNG thumbnail, at most [`THUMBNAIL_SIZE`] pixels on its longest side.
+        data_base64: String,
+        /// Dimensions of the original image.
+        width: u32,
+        height: u32,
+    },
+    None,
+}
+
+pub(crate) const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "ico"];
+
+/// Formats that are binary without looking; anything else is sniffed.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "msi", "sys", "lnk", "appx", "msix", "zip", "7z", "rar", "gz", "tar", "iso",
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "mp3", "mp4", "mkv",
+    "avi", "mov", "wav", "flac", "tiff", "psd", "db", "sqlite",
+];
+
+/// Preview `filepath`, reading at most `max_bytes` of text (default
+/// [`DEFAULT_TEXT_BYTES`], capped at [`MAX_TEXT_BYTES`]). Fails if the file
+/// is missing or reading takes longer than [`PREVIEW_TIMEOUT`].
+pub fn file_preview(filepath: &str, max_bytes: Option<u64>) -> Result<FilePreview, String> {
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_TEXT_BYTES).min(MAX_TEXT_BYTES);
+    let (done, result) = std::sync::mpsc::channel();
+    let path = filepath.to_string();
+    std::thread::Builder::new()
+        .name("file-preview".to_string())
+        .spawn(move || {
+            let _ = done.send(read_preview(Path::new(&path), max_bytes));
+        })
+        .map_err(|e| format!("Failed to preview '{}': {}", filepath, e))?;
+
+    match result.recv_timeout(PREVIEW_TIMEOUT) {
+        Ok(preview) => preview,
+        // The worker is left to finish (or stay stuck) on its own
+        Err(RecvTimeoutError::Timeout) => Err(format!("Timed out reading '{}'", filepath)),
+        Err(RecvTimeoutError::Disconnected) => Err(format!("Failed to preview '{}'", filepath)),
+    }
+}
+
+fn read_preview(path: &Path, max_bytes: u64) -> Result<FilePreview, String> {
+    let metadata = std::fs::metadata(path).map_err(|_| format!("File not found: {}", path.display()))?;
+    if !metadata.is_file() || is_cloud_placeholder(&metadata) {
+        return Ok(FilePreview::None);
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        if metadata.len() > MAX_IMAGE_BYTES {
+            return Ok(FilePreview::None);
+        }
+        let bytes = read_capped(path, MAX_IMAGE_BYTES)?;
+        return Ok(image_preview(&bytes).unwrap_or_else(|e| {
+            warn!("No thumbnail for '{}': {}", path.display(), e);
+            FilePreview::None
+        }));
+    }
+    if BINARY_EXTENSIONS.contains(&extension.as_str()) {
+        return Ok(FilePreview::None);
+    }
+
+    let bytes = read_capped(path, max_bytes)?;
+    Ok(match decode_text(&bytes) {
+        Some(content) => FilePreview::Text {
+            content,
+            truncated: metadata.len() > bytes.len() as u64,
+        },
+        None => FilePreview::None,
+    })
+}
+
+pub(crate) fn read_capped(path: &Path, cap: u64) -> Result<Vec<u8>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open '{}': {}", path.display(), e))?;
+    let mut bytes = Vec::new();
+    file.take(cap)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    Ok(bytes)
+}
+
+/// Whether the file's data lives in the cloud and opening it would download it.
+#[cfg(windows)]
+pub(crate) fn is_cloud_placeholder(metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+    metadata.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+#[cfg(not(windows))]
+pub(crate) fn is_cloud_placeholder(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Decode the start of a text file: UTF-8 or UTF-16 by BOM, otherwise UTF-8
+/// with invalid sequences replaced. A character cut off by the read cap is
+/// dropped. Returns `None` for content that looks binary (NUL bytes without
+/// a UTF-16 BOM).
+fn decode_text(bytes: &[u8]) -> Option<String> {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return Some(decode_utf8(rest));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return Some(decode_utf16(rest, u16::from_le_bytes));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return Some(decode_utf16(rest, u16::from_be_bytes));
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+    Some(decode_utf8(bytes))
+}
+
+fn decode_utf8(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        // Incomplete sequence at the very end: the cap split a character
+        Err(e) if e.error_len().is_none() => String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned(),
+        Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let mut units: Vec<u16> = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]])).collect();
+    // A high surrogate whose pair was cut off by the cap
+    if units.last().is_some_and(|&u| (0xD800..0xDC00).contains(&u)) {
+        units.pop();
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Decode an image and encode a PNG thumbnail of it.
+fn image_preview(bytes: &[u8]) -> Result<FilePreview, String> {
+    let image = decode_image(bytes)?;
+    let (width, height) = (image.width(), image.height());
+    let png = encode_png(shrink_to(image, THUMBNAIL_SIZE))?;
+
+    Ok(FilePreview::Image {
+        data_base64: base64::engine::general_purpose::STANDARD.encode(png),
+        width,
+        height,
+    })
+}
+
+/// Decode an image of any supported format, within [`MAX_DECODE_ALLOC`].
+pub(crate) fn decode_image(bytes: &[u8]) -> Result<image::DynamicImage, String> {
+    let mut limits = image::Limits::default();
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?;
+    reader.limits(limits);
+    reader.decode().map_err(|e| e.to_string())
+}
+
+/// Scale `image` down to at most `size` pixels on its longest side.
+pub(crate) fn shrink_to(image: image::DynamicImage, size: u32) -> image::DynamicImage {
+    if image.width() > size || image.height() > size {
+        image.thumbnail(size, size)
+    } else {
+        image
+    }
+}
+
+pub(crate) fn encode_png(image: image::DynamicImage) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_text_encodings() {
+        assert_eq!(decode_text(b"plain ascii").as_deref(), Some("plain ascii"));
+        assert_eq!(decode_text(b"\xEF\xBB\xBFcaf\xC3\xA9").as_deref(), Some("café"));
+        assert_eq!(decode_text(b"\xFF\xFEh\0i\0").as_deref(), Some("hi"));
+        assert_eq!(decode_text(b"\xFE\xFF\0h\0i").as_deref(), Some("hi"));
+        // Windows-1252 text falls back to lossy UTF-8
+        assert_eq!(decode_text(b"caf\xE9 au lait").as_deref(), Some("caf\u{FFFD} au lait"));
+        assert_eq!(decode_text(b"MZ\x90\0\x03\0\0\0"), None);
+    }
+
+    #[test]
+    fn test_decode_text_drops_character_split_by_cap() {
+        // "é" cut after its first byte
+        assert_eq!(decode_text(b"caf\xC3").as_deref(), Some("caf"));
+        // U+1F600 as a UTF-16LE surrogate pair, cut after the high surrogate
+        assert_eq!(decode_text(b"\xFF\xFEa\0\x3D\xD8").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_file_preview_caps_text_and_thumbnails_images() {
+        let dir = std::env::temp_dir().join(format!("ancheck-preview-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, "0123456789".repeat(100)).unwrap();
+        match file_preview(&text.to_string_lossy(), Some(16)).unwrap() {
+            FilePreview::Text { content, truncated } => {
+                assert_eq!(content, "0123456789012345");
+                assert!(truncated);
+            }
+            other => panic!("expected text, got {:?}", other),
+        }
+
+        let picture = dir.join("wide.png");
+        image::RgbImage::new(1024, 512).save(&picture).unwrap();
+        match file_preview(&picture.to_string_lossy(), None).unwrap() {
+            FilePreview::Image { data_base64, width, height } => {
+                assert_eq!((width, height), (1024, 512));
+                let png = base64::engine::general_purpose::STANDARD.decode(data_base64).unwrap();
+                let thumbnail = image::load_from_memory(&png).unwrap();
+                assert_eq!((thumbnail.width(), thumbnail.height()), (256, 128));
+            }
+            other => panic!("expected image, got {:?}", other),
+        }
+
+        let binary = dir.join("blob.bin");
+        std::fs::write(&binary, b"\x00\x01\x02\x03").unwrap();
+        assert_eq!(file_preview(&binary.to_string_lossy(), None).unwrap(), FilePreview::None);
+        assert_eq!(file_preview(&dir.to_string_lossy(), None).unwrap(), FilePreview::None);
+        assert!(file_preview(&dir.join("missing.txt").to_string_lossy(), None).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
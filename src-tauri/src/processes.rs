@@ -0,0 +1,228 @@
+//! Running processes, used to mark app results as running and to end them.
+//!
+//! Processes are matched to results by executable name only: `chrome.exe`
+//! matches any indexed `chrome.exe`, and a shortcut matches when its name is
+//! the executable's stem (`Spotify.lnk` and `Spotify.exe`). Shortcut targets
+//! are not resolved.
+
+use crate::searcher::SearchResult;
+use log::info;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Error returned by [`kill_process`] when ending the process needs
+/// administrator rights. The frontend can offer to retry elevated.
+pub const ELEVATION_REQUIRED: &str = "elevation_required";
+
+/// How long a process list is reused before taking a new snapshot.
+const CACHE_TTL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningProcess {
+    pub pid: u32,
+    /// Executable file name, e.g. "chrome.exe".
+    pub exe_name: String,
+}
+
+/// The process list from the last snapshot, so annotating results doesn't
+/// snapshot on every keystroke.
+#[derive(Default)]
+pub struct ProcessCache {
+    last: Mutex<Option<(Instant, Arc<Vec<RunningProcess>>)>>,
+}
+
+impl ProcessCache {
+    /// The current process list, at most [`CACHE_TTL`] old.
+    pub fn get(&self) -> Arc<Vec<RunningProcess>> {
+        let mut last = self.last.lock().unwrap();
+        match &*last {
+            Some((taken, processes)) if taken.elapsed() < CACHE_TTL => processes.clone(),
+            _ => {
+                let processes = Arc::new(list_processes());
+                *last = Some((Instant::now(), processes.clone()));
+                processes
+            }
+        }
+    }
+
+    /// Force the next [`ProcessCache::get`] to take a new snapshot.
+    pub fn invalidate(&self) {
+        *self.last.lock().unwrap() = None;
+    }
+}
+
+/// Pids of the processes running `exe_name` (case-insensitive).
+pub fn list_matching_processes(processes: &[RunningProcess], exe_name: &str) -> Vec<u32> {
+    processes
+        .iter()
+        .filter(|p| p.exe_name.eq_ignore_ascii_case(exe_name))
+        .map(|p| p.pid)
+        .collect()
+}
+
+/// Set `is_running` / `running_pids` on app and shortcut results. The
+/// process list is only fetched if there is such a result.
+pub fn annotate_running(results: &mut [SearchResult], cache: &ProcessCache) {
+    if !results.iter().any(|r| r.id > 0 && running_exe_name(r).is_some()) {
+        return;
+    }
+    mark_running(results, &cache.get());
+}
+
+fn mark_running(results: &mut [SearchResult], processes: &[RunningProcess]) {
+    for result in results.iter_mut().filter(|r| r.id > 0) {
+        let Some(exe_name) = running_exe_name(result) else {
+            continue;
+        };
+        result.running_pids = list_matching_processes(processes, &exe_name);
+        result.is_running = !result.running_pids.is_empty();
+    }
+}
+
+/// Executable name a result's process would have: the file name of an
+/// `.exe`, or `<stem>.exe` for a `.lnk` shortcut.
+pub(crate) fn running_exe_name(result: &SearchResult) -> Option<String> {
+    match result.extension.to_ascii_lowercase().as_str() {
+        "exe" => Some(result.filename.clone()),
+        "lnk" => {
+            let stem = result.filename.get(..result.filename.len() - ".lnk".len())?;
+            Some(format!("{}.exe", stem))
+        }
+        _ => None,
+    }
+}
+
+/// Snapshot the running processes with the Toolhelp32 API.
+#[cfg(windows)]
+fn list_processes() -> Vec<RunningProcess> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+
+    let mut processes = Vec::new();
+    // SAFETY: `dwSize` is set as the API requires and the snapshot handle is
+    // closed exactly once.
+    unsafe {
+        let snapshot = match CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
+            Ok(handle) => handle,
+            Err(e) => {
+                log::warn!("Failed to snapshot processes: {}", e);
+                return processes;
+            }
+        };
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut next = Process32FirstW(snapshot, &mut entry);
+        while next.is_ok() {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            processes.push(RunningProcess {
+                pid: entry.th32ProcessID,
+                exe_name: String::from_utf16_lossy(&entry.szExeFile[..len]),
+            });
+            next = Process32NextW(snapshot, &mut entry);
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    processes
+}
+
+#[cfg(not(windows))]
+fn list_processes() -> Vec<RunningProcess> {
+    Vec::new()
+}
+
+/// End process `pid`. Fails with [`ELEVATION_REQUIRED`] when access is
+/// denied; with `elevated` set, `taskkill` is run as administrator instead
+/// (which shows the UAC prompt).
+pub fn kill_process(pid: u32, elevated: bool) -> Result<(), String> {
+    if pid == std::process::id() {
+        return Err("Can't end AnCheck from itself".to_string());
+    }
+    if elevated {
+        let parameters = format!("/PID {} /F", pid);
+        crate::launcher::shell_execute("runas", "taskkill.exe", Some(&parameters), ".")?;
+    } else {
+        terminate(pid)?;
+    }
+    info!("Ended process {}", pid);
+    Ok(())
+}
+
+#[cfg(windows)]
+fn terminate(pid: u32) -> Result<(), String> {
+    use windows::Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+    use windows::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    let describe = |e: windows::core::Error| {
+        if e.code() == ERROR_ACCESS_DENIED.to_hresult() {
+            ELEVATION_REQUIRED.to_string()
+        } else if e.code() == ERROR_INVALID_PARAMETER.to_hresult() {
+            format!("Process {} is no longer running", pid)
+        } else {
+            format!("Failed to end process {}: {}", pid, e)
+        }
+    };
+    // SAFETY: the handle is closed exactly once, after TerminateProcess.
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, false, pid).map_err(describe)?;
+        let result = TerminateProcess(handle, 1).map_err(describe);
+        let _ = CloseHandle(handle);
+        result
+    }
+}
+
+#[cfg(not(windows))]
+fn terminate(pid: u32) -> Result<(), String> {
+    Err(format!("Can't end process {}: only supported on Windows", pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, exe_name: &str) -> RunningProcess {
+        RunningProcess {
+            pid,
+            exe_name: exe_name.to_string(),
+        }
+    }
+
+    fn result(id: i64, filename: &str) -> SearchResult {
+        let extension = filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+        serde_json::from_value(serde_json::json!({
+            "id": id, "filename": filename, "filepath": format!(r"C:\\Apps\\{}", filename),
+            "extension": extension, "file_size": 0, "modified_at": 0, "file_type": "app",
+            "click_count": 0, "last_accessed": 0, "parent_dir": "", "file_size_display": "",
+            "modified_display": "", "score": 0.0, "match_type": "exact", "matched_indices": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_list_matching_processes_ignores_case() {
+        let processes = [process(11, "chrome.exe"), process(12, "CHROME.EXE"), process(13, "code.exe")];
+        assert_eq!(list_matching_processes(&processes, "Chrome.exe"), vec![11, 12]);
+        assert!(list_matching_processes(&processes, "notepad.exe").is_empty());
+    }
+
+    #[test]
+    fn test_mark_running_apps_and_shortcuts() {
+        let processes = [process(10, "Spotify.exe"), process(11, "chrome.exe")];
+        let mut results = vec![
+            result(1, "chrome.exe"),
+            result(2, "Spotify.lnk"),
+            result(3, "Notepad.lnk"),
+            result(4, "notes.txt"),
+        ];
+        mark_running(&mut results, &processes);
+
+        let running: Vec<_> = results.iter().map(|r| (r.is_running, r.running_pids.clone())).collect();
+        assert_eq!(
+            running,
+            vec![(true, vec![11]), (true, vec![10]), (false, vec![]), (false, vec![])]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Quick slots: up to nine items bound to Ctrl+Alt+1 through Ctrl+Alt+9,
+//! which launch them without showing the launcher.
+//!
+//! Bindings live in the `quick_slots` table and are part of [`Settings`]
+//! (`crate::settings::Settings`), so they're saved and restored with the
+//! other settings. `lib.rs` holds the hotkeys of bound slots only, and
+//! retries failed ones whenever the bindings change: Ctrl+Alt is AltGr on
+//! many keyboard layouts, and holding Ctrl+Alt+2 would swallow the '@'
+//! typed with AltGr+2 for anyone not using that slot.
+
+use crate::db::Database;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Number of slots, numbered from 1.
+pub const SLOT_COUNT: u8 = 9;
+
+/// A bound slot as `get_quick_slots` lists it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuickSlot {
+    pub slot: u8,
+    pub hotkey: String,
+    pub filepath: String,
+    /// The entry's display name or filename if it's indexed, else the last
+    /// component of `filepath`.
+    pub name: String,
+    /// Whether `filepath` is in the index.
+    pub indexed: bool,
+    /// Why `hotkey` couldn't be registered, if it couldn't.
+    pub hotkey_error: Option<String>,
+}
+
+/// The hotkey that launches `slot`.
+pub fn hotkey(slot: u8) -> String {
+    format!("Ctrl+Alt+{}", slot)
+}
+
+/// Check a slot number from the frontend.
+pub fn check_slot(slot: u8) -> Result<u8, String> {
+    if (1..=SLOT_COUNT).contains(&slot) {
+        Ok(slot)
+    } else {
+        Err(format!("Quick slot must be between 1 and {}", SLOT_COUNT))
+    }
+}
+
+/// Check bindings from a settings patch: valid slots and non-blank paths,
+/// which are trimmed.
+pub fn normalize_slots(slots: &BTreeMap<u8, String>) -> Result<BTreeMap<u8, String>, String> {
+    slots
+        .iter()
+        .map(|(&slot, filepath)| {
+            let filepath = filepath.trim();
+            if filepath.is_empty() {
+                return Err(format!("Quick slot {} has no item", slot));
+            }
+            Ok((check_slot(slot)?, filepath.to_string()))
+        })
+        .collect()
+}
+
+/// The stored bindings, by slot. Unreadable ones are left out.
+pub fn bindings(db: &Database) -> BTreeMap<u8, String> {
+    db.quick_slots()
+        .map(|slots| slots.into_iter().map(|(slot, filepath, _)| (slot, filepath)).collect())
+        .unwrap_or_default()
+}
+
+/// Every bound slot, in slot order.
+pub fn list(db: &Database) -> Result<Vec<QuickSlot>, String> {
+    let slots = db
+        .quick_slots()
+        .map_err(|e| format!("Failed to load quick slots: {}", e))?;
+    Ok(slots
+        .into_iter()
+        .map(|(slot, filepath, indexed_name)| QuickSlot {
+            slot,
+            hotkey: hotkey(slot),
+            name: indexed_name.clone().unwrap_or_else(|| {
+                let last = filepath.trim_end_matches(['\\', '/']).rsplit(['\\', '/']).next();
+                last.filter(|name| !name.is_empty()).unwrap_or(&filepath).to_string()
+            }),
+            indexed: indexed_name.is_some(),
+            hotkey_error: None,
+            filepath,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_slots_are_checked() {
+        assert_eq!(check_slot(1), Ok(1));
+        assert_eq!(check_slot(9), Ok(9));
+        assert!(check_slot(0).is_err());
+        assert!(check_slot(10).is_err());
+
+        let slots = BTreeMap::from([(1, r"  C:\Apps\Code.exe ".to_string())]);
+        assert_eq!(normalize_slots(&slots).unwrap()[&1], r"C:\Apps\Code.exe");
+        assert!(normalize_slots(&BTreeMap::from([(2, " ".to_string())])).is_err());
+        assert!(normalize_slots(&BTreeMap::from([(12, r"C:\a.txt".to_string())])).is_err());
+    }
+
+    #[test]
+    fn test_list_names_indexed_and_missing_items() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        db.upsert_file("Code.exe", r"C:\Apps\Code.exe", "exe", 1, 1, "application").unwrap();
+        db.set_display_names(&[(r"C:\Apps\Code.exe".to_string(), "Visual Studio Code".to_string(), 1)])
+            .unwrap();
+        db.replace_quick_slots(&BTreeMap::from([
+            (3, r"C:\Apps\Code.exe".to_string()),
+            (1, r"D:\Games\game.exe".to_string()),
+        ]))
+        .unwrap();
+
+        let slots = list(&db).unwrap();
+        let summary: Vec<_> = slots.iter().map(|s| (s.slot, s.name.as_str(), s.indexed)).collect();
+        assert_eq!(summary, vec![(1, "game.exe", false), (3, "Visual Studio Code", true)]);
+        assert_eq!(slots[1].hotkey, "Ctrl+Alt+3");
+
+        db.replace_quick_slots(&BTreeMap::from([(2, r"C:\a.txt".to_string())])).unwrap();
+        assert_eq!(bindings(&db), BTreeMap::from([(2, r"C:\a.txt".to_string())]));
+    }
+}
@@ -0,0 +1,183 @@
+//! Search-as-you-type sessions. The frontend starts a session when the
+//! launcher opens and sends each edit of the query with `update_query`;
+//! results arrive as `search-results` events instead of one `search` call
+//! per keystroke.
+//!
+//! A session keeps the [`CandidatePool`] of its last query. Typing another
+//! char narrows that pool instead of querying SQL and scanning the whole
+//! snapshot again; deleting chars, an index pass that refreshes the
+//! snapshot, or a query the pool doesn't apply to starts over from the full
+//! snapshot. A newer update supersedes a search still running for the same
+//! session.
+
+use crate::db::Database;
+use crate::searcher::{self, CandidatePool, FilenameSnapshot, SearchOptions, SearchResponse, SEARCH_SUPERSEDED};
+use log::debug;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Most sessions kept; starting another drops the least recently used.
+const MAX_SESSIONS: usize = 8;
+/// Sessions unused for this long are dropped when another starts.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Payload of the `search-results` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionResults {
+    pub session_id: u64,
+    pub query: String,
+    pub response: SearchResponse,
+}
+
+struct Session {
+    /// Bumped by every update; a search for an older one is superseded.
+    generation: u64,
+    last_used: Instant,
+    pool: Option<Arc<CandidatePool>>,
+}
+
+/// Open search sessions by id.
+#[derive(Default)]
+pub struct SearchSessions {
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<u64, Session>>,
+}
+
+impl SearchSessions {
+    /// Open a session and return its id, dropping idle sessions and, if
+    /// there are still too many, the least recently used one.
+    pub fn start(&self) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, session| session.last_used.elapsed() < SESSION_IDLE_TIMEOUT);
+        while sessions.len() >= MAX_SESSIONS {
+            let Some(oldest) = sessions.iter().min_by_key(|(&id, s)| (s.last_used, id)).map(|(&id, _)| id) else {
+                break;
+            };
+            sessions.remove(&oldest);
+        }
+        sessions.insert(
+            id,
+            Session {
+                generation: 0,
+                last_used: Instant::now(),
+                pool: None,
+            },
+        );
+        id
+    }
+
+    /// Search for `query` in session `id`, reusing its last query's
+    /// candidates where they apply. Returns `None` when a newer update to
+    /// the session superseded this one.
+    pub fn search(
+        &self,
+        id: u64,
+        db: &Arc<Database>,
+        snapshot: &FilenameSnapshot,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Option<SearchResponse>, String> {
+        let (generation, parent) = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let session = sessions.get_mut(&id).ok_or_else(|| format!("No search session {}", id))?;
+            session.generation += 1;
+            session.last_used = Instant::now();
+            (session.generation, session.pool.clone())
+        };
+        let is_superseded = || self.generation(id) != Some(generation);
+
+        let pool = searcher::candidate_pool(db, snapshot, query, options, parent.as_deref())?.map(Arc::new);
+        if let Some(pool) = &pool {
+            debug!("Search session {}: {} candidates for '{}'", id, pool.len(), query);
+        }
+        if is_superseded() {
+            return Ok(None);
+        }
+        let response =
+            match searcher::search_with_candidates(db, snapshot, query, options, pool.as_deref(), &is_superseded) {
+                Err(e) if e == SEARCH_SUPERSEDED => return Ok(None),
+                other => other?,
+            };
+
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get_mut(&id) {
+            Some(session) if session.generation == generation => {
+                session.pool = pool;
+                Ok(Some(response))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn generation(&self, id: u64) -> Option<u64> {
+        self.sessions.lock().unwrap().get(&id).map(|session| session.generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture() -> (Arc<Database>, FilenameSnapshot) {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let rows: Vec<_> = ["Chrome.lnk", "Chromebook notes.txt", "Chess.exe", "cheatsheet.pdf"]
+            .iter()
+            .map(|name| (name.to_string(), format!(r"C:\Files\{}", name), String::new(), 10, 0, "other".to_string()))
+            .collect();
+        db.upsert_files_batch(&rows).unwrap();
+        (Arc::new(db), FilenameSnapshot::default())
+    }
+
+    fn names(response: &SearchResponse) -> Vec<&str> {
+        response.results.iter().map(|r| r.filename.as_str()).collect()
+    }
+
+    #[test]
+    fn test_typing_reuses_candidates() {
+        let (db, snapshot) = fixture();
+        let sessions = SearchSessions::default();
+        let id = sessions.start();
+        let options = SearchOptions {
+            metrics: true,
+            ..SearchOptions::default()
+        };
+        let search = |query: &str| sessions.search(id, &db, &snapshot, query, &options).unwrap().unwrap();
+
+        // Too short for a pool
+        assert!(!search("ch").meta.unwrap().reused_candidates);
+        // The session's first pool is built from the snapshot
+        let first = search("chr");
+        assert!(!first.meta.as_ref().unwrap().reused_candidates);
+        let typed = search("chrom");
+        assert!(typed.meta.as_ref().unwrap().reused_candidates);
+        let fresh = searcher::search(&db, &snapshot, "chrom", &options, &|| false).unwrap();
+        assert_eq!(names(&typed), names(&fresh));
+
+        // Deleting chars starts over from the snapshot and still finds what
+        // the longer query's pool had dropped
+        let deleted = search("che");
+        assert!(!deleted.meta.as_ref().unwrap().reused_candidates);
+        assert!(names(&deleted).contains(&"cheatsheet.pdf"));
+        assert!(names(&deleted).contains(&"Chess.exe"));
+    }
+
+    #[test]
+    fn test_unknown_and_evicted_sessions() {
+        let (db, snapshot) = fixture();
+        let sessions = SearchSessions::default();
+        let options = SearchOptions::default();
+        assert!(sessions.search(42, &db, &snapshot, "chrome", &options).is_err());
+
+        let first = sessions.start();
+        let ids: Vec<u64> = (0..MAX_SESSIONS).map(|_| sessions.start()).collect();
+        assert!(sessions.search(first, &db, &snapshot, "chrome", &options).is_err());
+        for id in ids {
+            assert!(sessions.search(id, &db, &snapshot, "chrome", &options).unwrap().is_some());
+        }
+    }
+}
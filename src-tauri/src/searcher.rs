@@ -1,14 +1,28 @@
-use crate::db::{Database, FileEntry};
+use crate::db::{CompactEntry, ContextPick, Database, FileEntry, QueryPick};
+use crate::open_windows::OpenWindow;
+use crate::settings_pages::{SETTINGS_PAGES, SETTINGS_URI_PREFIX};
+use chrono::{Datelike, Days, Months, NaiveDate};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use log::warn;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use unicode_normalization::char::{decompose_compatible, is_combining_mark};
 
 /// A search result with computed score and match metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: i64,
     pub filename: String,
+    /// Name shown in place of `filename`, see [`FileEntry::display_name`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
     pub filepath: String,
     pub extension: String,
     pub file_size: i64,
@@ -16,364 +30,6340 @@ pub struct SearchResult {
     pub file_type: String,
     pub click_count: i64,
     pub last_accessed: i64,
+    /// Folder containing the file, empty for URLs and web searches.
+    pub parent_dir: String,
+    /// Human-readable size ("4.2 MB"), empty when the size is unknown.
+    pub file_size_display: String,
+    /// Relative modification time ("3 days ago"), empty when unknown.
+    pub modified_display: String,
+    /// Items directly inside a folder result, see [`FileEntry::child_count`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child_count: Option<i64>,
     pub score: f64,
-    pub match_type: String,       // "exact", "prefix", "substring", "fuzzy", "path"
-    pub matched_indices: Vec<usize>, // character positions that matched
+    pub match_type: String,       // "exact", "prefix", "substring", "typo", "fuzzy", "path", "wildcard", "filter"
+    pub matched_indices: Vec<usize>, // char (Unicode scalar) positions in the shown name that matched
+    /// Char positions in `parent_dir` that a deep path search matched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_indices: Vec<usize>,
+    /// Score components, only present when the search was run with `explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+    /// Other locations of the same file that were folded into this result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<String>,
+    /// Empty-query section this result belongs to: "pinned", "recent" or "frequent".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Category of a grouped search's result (see [`GroupCaps`]): "apps",
+    /// "actions", "folders", "documents" or "other".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Whether an app or shortcut result's executable is running (see `processes`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_running: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub running_pids: Vec<u32>,
 }
 
-/// Performs multi-strategy search combining SQL pre-filtering with in-memory fuzzy matching.
+/// How a result's score was put together, for debugging rankings.
+/// `score` on the result is the sum of all numeric fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Matching branch that produced the base score: "exact", "exact_stem",
+    /// "prefix", "substring", "path", "typo", "fuzzy", "fuzzy_path", "deep_path",
+    /// "wildcard" or "filter".
+    pub branch: Cow<'static, str>,
+    pub match_score: f64,
+    /// Word-boundary bonus or in-word position penalty for substring matches.
+    pub position_adjustment: f64,
+    pub length_bonus: f64,
+    /// Bonus when the query ends in the entry's extension ("report.pdf"),
+    /// penalty when the name matches but the extension differs.
+    pub extension_adjustment: f64,
+    pub type_boost: f64,
+    /// Penalty for an empty or very large regular file (see
+    /// [`size_adjustment`]).
+    pub size_adjustment: f64,
+    pub click_boost: f64,
+    pub recency_boost: f64,
+    /// Boost for files the user previously launched for this query.
+    pub personal_boost: f64,
+    /// Boost for files the user launched before from the app in front of
+    /// the launcher (see [`SearchOptions::context_app`]).
+    pub context_boost: f64,
+}
+
+impl ScoreBreakdown {
+    /// The final score.
+    pub fn total(&self) -> f64 {
+        self.match_score
+            + self.position_adjustment
+            + self.length_bonus
+            + self.extension_adjustment
+            + self.type_boost
+            + self.size_adjustment
+            + self.click_boost
+            + self.recency_boost
+            + self.personal_boost
+            + self.context_boost
+    }
+
+    /// How well the text matched, before any boosts. This is what the
+    /// minimum score cutoff applies to, so heavy usage can't resurrect a
+    /// garbage match.
+    pub fn base_match(&self) -> f64 {
+        self.match_score + self.position_adjustment
+    }
+
+    /// Whether this result clears `min_score`. Filter-only queries have no
+    /// text to match, so they always pass.
+    fn passes(&self, min_score: f64) -> bool {
+        self.branch == "filter" || self.base_match() >= min_score
+    }
+
+    /// The coarse `match_type` reported on every result.
+    fn match_type(&self) -> &'static str {
+        match self.branch.as_ref() {
+            "exact" | "exact_stem" => "exact",
+            "prefix" => "prefix",
+            "substring" => "substring",
+            "path" | "fuzzy_path" | "deep_path" => "path",
+            "fuzzy" => "fuzzy",
+            "typo" => "typo",
+            "wildcard" => "wildcard",
+            "filter" => "filter",
+            _ => "none",
+        }
+    }
+}
+
+/// Per-call search settings.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub max_results: usize,
+    /// Attach a [`ScoreBreakdown`] to every result.
+    pub explain: bool,
+    /// Drop results whose [`ScoreBreakdown::base_match`] is below this.
+    pub min_score: f64,
+    /// Attach [`SearchMeta`] timings to the response.
+    pub metrics: bool,
+    /// Open windows to offer as `match_type = "window"` results.
+    pub open_windows: Arc<Vec<OpenWindow>>,
+    /// Scan the in-memory snapshot when SQL finds too few results. The
+    /// scan is the costly part of a search on a slow machine.
+    pub fuzzy: bool,
+    /// Also fuzzy-match the last folders of each path when the filename
+    /// stages find too few results, as a query containing `/` always does.
+    pub deep_path: bool,
+    /// Group results by category with these caps; `None` returns one flat
+    /// ranking.
+    pub group_caps: Option<GroupCaps>,
+    /// Executable name of the app the launcher was opened from (e.g.
+    /// "slack.exe"), whose past launches get a [`ScoreBreakdown::context_boost`].
+    pub context_app: Option<String>,
+    /// Leave out empty regular files (see [`is_empty_file`]).
+    pub hide_empty_files: bool,
+    /// Regular files above this size get a [`ScoreBreakdown::size_adjustment`];
+    /// 0 turns that off.
+    pub large_file_bytes: i64,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            max_results: DEFAULT_RESULT_LIMIT,
+            explain: false,
+            min_score: DEFAULT_MIN_SCORE,
+            metrics: false,
+            open_windows: Arc::default(),
+            fuzzy: true,
+            deep_path: false,
+            group_caps: None,
+            context_app: None,
+            hide_empty_files: false,
+            large_file_bytes: large_file_bytes(DEFAULT_LARGE_FILE_MB),
+        }
+    }
+}
+
+/// Most results of each category a grouped search returns while other
+/// categories have results too, so a common word matching dozens of
+/// documents still leaves room for the one app. Launcher actions (open
+/// windows, system actions, snippets, web searches) are never capped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroupCaps {
+    /// Apps, shortcuts and custom commands.
+    pub apps: usize,
+    pub folders: usize,
+    pub documents: usize,
+    /// Everything else, custom URLs included.
+    pub other: usize,
+}
+
+impl Default for GroupCaps {
+    fn default() -> Self {
+        GroupCaps {
+            apps: 5,
+            folders: 5,
+            documents: 5,
+            other: 5,
+        }
+    }
+}
+
+impl GroupCaps {
+    /// Every cap, with its group's name.
+    pub fn each(&self) -> [(&'static str, usize); 4] {
+        [
+            ("apps", self.apps),
+            ("folders", self.folders),
+            ("documents", self.documents),
+            ("other", self.other),
+        ]
+    }
+}
+
+/// Filters parsed from `key:value` tokens in the query, e.g. `ext:pdf`,
+/// `type:app`, `in:downloads`, or negated as `-ext:lnk`.
+/// Values are lowercased; repeated filters of the same kind are OR-ed together.
 ///
-/// Strategy:
-/// 1. SQL LIKE query for prefix/substring matches (fast, uses indexes)
-/// 2. In-memory fuzzy matching on all filenames for fuzzy results
-/// 3. Combine, deduplicate, rank, and return top results
-pub fn search(db: &Arc<Database>, query: &str, max_results: usize) -> Result<Vec<SearchResult>, String> {
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
+/// `size:` and `modified:` tokens become bounds instead; repeated ones narrow
+/// the range. Quoted phrases and `cs:` text become [`RequiredTerm`]s.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryFilters {
+    pub extensions: Vec<String>,
+    pub file_types: Vec<String>,
+    pub locations: Vec<String>,
+    pub exclude_extensions: Vec<String>,
+    pub exclude_file_types: Vec<String>,
+    pub exclude_locations: Vec<String>,
+    /// Inclusive size bounds in bytes.
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    /// Unix timestamps: `modified_after` is inclusive, `modified_before` exclusive.
+    pub modified_after: Option<i64>,
+    pub modified_before: Option<i64>,
+    /// Case-sensitive text every matching filename must contain.
+    #[serde(default)]
+    pub required_terms: Vec<RequiredTerm>,
+}
+
+/// Text a filename must contain with exactly this case: a quoted phrase
+/// (`"Q3"`, which must also stand as whole words) or an unquoted word of a
+/// `cs:` query (a plain case-sensitive substring).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequiredTerm {
+    pub text: String,
+    pub whole_word: bool,
+}
+
+impl RequiredTerm {
+    /// Char position of the first acceptable occurrence in `filename`.
+    pub fn find_in(&self, filename: &str) -> Option<usize> {
+        let is_word_char = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+        filename
+            .match_indices(self.text.as_str())
+            .map(|(byte, _)| byte)
+            .find(|&byte| {
+                !self.whole_word
+                    || (!is_word_char(filename[..byte].chars().next_back())
+                        && !is_word_char(filename[byte + self.text.len()..].chars().next()))
+            })
+            .map(|byte| filename[..byte].chars().count())
+    }
+}
+
+impl QueryFilters {
+    /// True when no filter tokens were recognized.
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+            && self.file_types.is_empty()
+            && self.locations.is_empty()
+            && self.exclude_extensions.is_empty()
+            && self.exclude_file_types.is_empty()
+            && self.exclude_locations.is_empty()
+            && self.min_size.is_none()
+            && self.max_size.is_none()
+            && self.modified_after.is_none()
+            && self.modified_before.is_none()
+            && self.required_terms.is_empty()
+    }
+
+    /// `in:` values that look like absolute paths are matched as a prefix
+    /// rather than as a substring anywhere in the path.
+    pub fn is_absolute_location(location: &str) -> bool {
+        location.contains(':') || location.starts_with('\\') || location.starts_with('/')
+    }
+
+    /// Check an in-memory candidate against the filters (mirrors the SQL clauses).
+    pub fn matches(&self, filename: &str, filepath: &str, file_type: &str) -> bool {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let filepath_folded = if self.locations.is_empty() && self.exclude_locations.is_empty() {
+            String::new()
+        } else {
+            fold_text(filepath)
+        };
+        let in_location = |location: &String| {
+            let location = fold_text(location);
+            if Self::is_absolute_location(&location) {
+                filepath_folded.starts_with(location.as_str())
+            } else {
+                filepath_folded.contains(location.as_str())
+            }
+        };
+
+        (self.extensions.is_empty() || self.extensions.contains(&extension))
+            && (self.file_types.is_empty() || self.file_types.iter().any(|t| t == file_type))
+            && (self.locations.is_empty() || self.locations.iter().any(in_location))
+            && !self.exclude_extensions.contains(&extension)
+            && !self.exclude_file_types.iter().any(|t| t == file_type)
+            && !self.exclude_locations.iter().any(in_location)
+    }
+
+    /// Check an in-memory candidate's size and modification time against the
+    /// `size:`/`modified:` bounds (mirrors the SQL clauses).
+    pub fn matches_metadata(&self, file_size: i64, modified_at: i64) -> bool {
+        self.min_size.is_none_or(|min| file_size >= min)
+            && self.max_size.is_none_or(|max| file_size <= max)
+            && self.modified_after.is_none_or(|after| modified_at >= after)
+            && self.modified_before.is_none_or(|before| modified_at < before)
+    }
+
+    /// Check a candidate's filename against the quoted / `cs:` terms.
+    /// Either name has to contain all of them.
+    pub fn matches_required_terms(&self, filename: &str, display_name: &str) -> bool {
+        let all_in = |name: &str| self.required_terms.iter().all(|term| term.find_in(name).is_some());
+        all_in(filename) || (!display_name.is_empty() && all_in(display_name))
+    }
+
+    /// Narrow the size range to `(min, max)`.
+    fn add_size_bounds(&mut self, (min, max): (Option<i64>, Option<i64>)) {
+        if let Some(min) = min {
+            self.min_size = Some(self.min_size.map_or(min, |current| current.max(min)));
+        }
+        if let Some(max) = max {
+            self.max_size = Some(self.max_size.map_or(max, |current| current.min(max)));
+        }
+    }
+
+    /// Narrow the modification window to `[after, before)`.
+    fn add_modified_bounds(&mut self, (after, before): (Option<i64>, Option<i64>)) {
+        if let Some(after) = after {
+            self.modified_after = Some(self.modified_after.map_or(after, |current| current.max(after)));
+        }
+        if let Some(before) = before {
+            self.modified_before = Some(self.modified_before.map_or(before, |current| current.min(before)));
+        }
+    }
+}
+
+/// A query split into its free-text part and its recognized filters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    pub text: String,
+    pub filters: QueryFilters,
+    /// Messages for `size:`/`modified:` tokens that couldn't be parsed.
+    pub errors: Vec<String>,
+    /// Every word of `text` belongs to a required term, so highlights should
+    /// come from the terms alone (see [`required_term_indices`]).
+    pub fully_strict: bool,
+}
+
+/// Split filter tokens (`ext:`, `type:`, `in:`, optionally prefixed with `-`)
+/// out of the query. Unknown prefixes and empty values are kept as literal text,
+/// so a file literally named "type:something" is still searchable.
+///
+/// Text in double quotes must appear in the filename as whole words with the
+/// same case; the quoted words still count as query text for ranking. A
+/// `cs:` token makes each unquoted word case-sensitive on its own
+/// (`cs:README notes`). Both become [`QueryFilters::required_terms`].
+pub fn parse_query(query: &str) -> ParsedQuery {
+    let now = chrono::Local::now();
+    parse_query_at(query, now.timestamp(), now.date_naive())
+}
+
+fn parse_query_at(query: &str, now: i64, today: NaiveDate) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    let mut text_tokens: Vec<&str> = Vec::new();
+    // Unquoted text words, which `cs:` applies to
+    let mut loose_tokens: Vec<&str> = Vec::new();
+    let mut case_sensitive = false;
+
+    // Even-numbered pieces are outside quotes; an unclosed quote runs to the end
+    for (i, piece) in query.split('"').enumerate() {
+        if i % 2 == 1 {
+            let phrase = piece.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !phrase.is_empty() {
+                text_tokens.extend(piece.split_whitespace());
+                parsed.filters.required_terms.push(RequiredTerm {
+                    text: phrase,
+                    whole_word: true,
+                });
+            }
+            continue;
+        }
+
+        for token in piece.split_whitespace() {
+            if let Some(rest) = strip_prefix_ignore_case(token, "cs:") {
+                case_sensitive = true;
+                if !rest.is_empty() {
+                    text_tokens.push(rest);
+                    loose_tokens.push(rest);
+                }
+                continue;
+            }
+            if parse_filter_token(token, &mut parsed, now, today) {
+                continue;
+            }
+            text_tokens.push(token);
+            loose_tokens.push(token);
+        }
+    }
+
+    if case_sensitive {
+        parsed.filters.required_terms.extend(loose_tokens.iter().map(|token| RequiredTerm {
+            text: token.to_string(),
+            whole_word: false,
+        }));
+    }
+    parsed.fully_strict =
+        !parsed.filters.required_terms.is_empty() && (case_sensitive || loose_tokens.is_empty());
+    parsed.text = text_tokens.join(" ");
+    parsed
+}
+
+fn strip_prefix_ignore_case<'a>(token: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = token.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &token[prefix.len()..])
+}
+
+/// Apply `token` to `parsed` if it is a `key:value` filter. Returns false for
+/// plain text, unknown keys and empty values.
+fn parse_filter_token(token: &str, parsed: &mut ParsedQuery, now: i64, today: NaiveDate) -> bool {
+    let (negated, body) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token),
+    };
+
+    let Some((key, value)) = body.split_once(':') else {
+        return false;
+    };
+    let value = value.trim_start_matches('.').to_lowercase();
+    if value.is_empty() {
+        return false;
+    }
+
+    let key = key.to_lowercase();
+    if key == "size" || key == "modified" {
+        let bounds = if negated {
+            Err(format!("'{}' can't be negated; use < or > instead", token))
+        } else if key == "size" {
+            parse_size_filter(&value).map(|b| parsed.filters.add_size_bounds(b))
+        } else {
+            parse_modified_filter(&value, now, today).map(|b| parsed.filters.add_modified_bounds(b))
+        };
+        if let Err(e) = bounds {
+            parsed.errors.push(e);
+        }
+        return true;
+    }
+
+    let filters = &mut parsed.filters;
+    let target = match (key.as_str(), negated) {
+        ("ext", false) => &mut filters.extensions,
+        ("ext", true) => &mut filters.exclude_extensions,
+        ("type", false) => &mut filters.file_types,
+        ("type", true) => &mut filters.exclude_file_types,
+        ("in", false) => &mut filters.locations,
+        ("in", true) => &mut filters.exclude_locations,
+        _ => return false,
+    };
+    target.push(value);
+    true
+}
+
+/// Char positions in `filename` covered by the first acceptable occurrence
+/// of each required term.
+fn required_term_indices(filename: &str, terms: &[RequiredTerm]) -> Vec<usize> {
+    let mut indices: Vec<usize> = terms
+        .iter()
+        .filter_map(|term| {
+            let start = term.find_in(filename)?;
+            Some(start..start + term.text.chars().count())
+        })
+        .flatten()
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// Split a leading comparison operator off a filter value.
+fn split_comparison(value: &str) -> (&str, &str) {
+    for op in [">=", "<=", ">", "<"] {
+        if let Some(rest) = value.strip_prefix(op) {
+            return (op, rest);
+        }
+    }
+    ("", value)
+}
+
+/// Parse a `size:` value such as ">100mb", "<=1.5gb" or "<1kb" into inclusive
+/// `(min, max)` byte bounds. Units are binary (1 KB = 1024 bytes), matching
+/// how sizes are displayed.
+fn parse_size_filter(value: &str) -> Result<(Option<i64>, Option<i64>), String> {
+    let invalid = || format!("Invalid size filter 'size:{}' (try size:>100mb or size:<1kb)", value);
+    let (op, amount) = split_comparison(value);
+    let number_end = amount
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(amount.len());
+    let number: f64 = amount[..number_end].parse().map_err(|_| invalid())?;
+    let multiplier: f64 = match &amount[number_end..] {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => 1024.0 * 1024.0,
+        "g" | "gb" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(invalid()),
+    };
+    let bytes = (number * multiplier).round() as i64;
+    match op {
+        ">" => Ok((Some(bytes + 1), None)),
+        ">=" => Ok((Some(bytes), None)),
+        "<" => Ok((None, Some(bytes - 1))),
+        "<=" => Ok((None, Some(bytes))),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a `modified:` value into a `[after, before)` timestamp window.
+///
+/// Accepts "today", "yesterday", a date ("2023-01-01", "jan 5 2024") meaning
+/// that whole day, a date with `<`/`>` for before/after it, or an age like
+/// "7d", "12h", "2w", "3m" (months), "1y" meaning "within the last ..."
+/// (`>7d` means older than that).
+fn parse_modified_filter(value: &str, now: i64, today: NaiveDate) -> Result<(Option<i64>, Option<i64>), String> {
+    let invalid = || {
+        format!(
+            "Invalid date filter 'modified:{}' (try modified:today, modified:7d or modified:<2023-01-01)",
+            value
+        )
+    };
+    let (op, rest) = split_comparison(value);
+
+    if let Some(age) = parse_age(rest) {
+        let cutoff = now - age;
+        return match op {
+            "" | "<" | "<=" => Ok((Some(cutoff), None)),
+            _ => Ok((None, Some(cutoff))),
+        };
+    }
+
+    let day = parse_date(rest, today).ok_or_else(invalid)?;
+    let start = local_midnight(day);
+    let end = local_midnight(day.succ_opt().ok_or_else(invalid)?);
+    match op {
+        "" => Ok((Some(start), Some(end))),
+        "<" => Ok((None, Some(start))),
+        "<=" => Ok((None, Some(end))),
+        ">" => Ok((Some(end), None)),
+        ">=" => Ok((Some(start), None)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Seconds in an age like "7d", "12h", "2w", "3m" or "1y".
+fn parse_age(value: &str) -> Option<i64> {
+    let unit = value.chars().last()?;
+    let count: i64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+    let seconds = match unit {
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        'm' => 30 * 86_400,
+        'y' => 365 * 86_400,
+        _ => return None,
+    };
+    count.checked_mul(seconds)
+}
+
+/// Unix timestamp of local midnight at the start of `day`.
+pub(crate) fn local_midnight(day: NaiveDate) -> i64 {
+    use chrono::TimeZone;
+    let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    chrono::Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+/// Split a folded query that ends in something extension-like ("report.pdf",
+/// "my.notes.v2.txt") into its stem and extension. The extension must be
+/// 1–5 alphanumeric chars with at least one letter, so "v2.0" isn't one.
+fn extension_hint(query_folded: &str) -> Option<(&str, &str)> {
+    let (stem, ext) = query_folded.rsplit_once('.')?;
+    let valid = !stem.is_empty()
+        && (1..=5).contains(&ext.len())
+        && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        && ext.chars().any(|c| c.is_ascii_alphabetic());
+    valid.then_some((stem, ext))
+}
+
+/// Search results together with the filters that were applied,
+/// so the UI can display them as chips.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    pub filters: QueryFilters,
+    /// Filter tokens that couldn't be understood, for the UI to show.
+    pub filter_errors: Vec<String>,
+    /// Stage timings, only present when the search was run with `metrics`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<SearchMeta>,
+}
+
+/// Where a file search spent its time. Times are in milliseconds; the fuzzy
+/// fields stay zero when SQL alone returned enough results.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchMeta {
+    pub sql_ms: f64,
+    /// Rows returned by the SQL stage, before scoring.
+    pub sql_candidates: usize,
+    pub fuzzy_ran: bool,
+    pub fuzzy_ms: f64,
+    /// Results added by the fuzzy stage.
+    pub fuzzy_candidates: usize,
+    pub deep_path_ran: bool,
+    pub deep_path_ms: f64,
+    pub total_ms: f64,
+    /// The SQL stage was answered from a search session's
+    /// [`CandidatePool`] that narrowed the previous query's candidates,
+    /// instead of the database or a full snapshot scan.
+    pub reused_candidates: bool,
+}
+
+/// Searches slower than this are logged as warnings.
+const SLOW_SEARCH_MS: f64 = 250.0;
+
+fn elapsed_ms(since: Instant) -> f64 {
+    since.elapsed().as_secs_f64() * 1000.0
+}
+
+/// In-memory copy of every indexed file used by the fuzzy stage, so rare
+/// queries don't re-read the whole table from SQLite on each keystroke.
+/// Rebuilt after each index pass and whenever files are added, hidden or
+/// renamed; click counts in it may lag the database until then.
+#[derive(Default)]
+pub struct FilenameSnapshot {
+    entries: RwLock<Arc<Vec<CompactEntry>>>,
+    /// Bumped on every rebuild so caches derived from the index can tell they're stale.
+    version: AtomicU64,
+    /// [`Database::files_generation`] the entries were read at, plus one;
+    /// 0 until the first rebuild.
+    built_from: AtomicU64,
+}
+
+impl FilenameSnapshot {
+    /// Rebuild the snapshot from the database. Returns the number of entries.
+    pub fn refresh(&self, db: &Database) -> Result<usize, String> {
+        // Read before loading: a write landing mid-load leaves the
+        // snapshot marked stale rather than looking current
+        let generation = db.files_generation();
+        let entries = db
+            .load_compact_entries()
+            .map_err(|e| format!("Failed to load snapshot: {}", e))?;
+        let count = entries.len();
+        *self.entries.write().unwrap() = Arc::new(entries);
+        self.built_from.store(generation + 1, Ordering::SeqCst);
+        self.version.fetch_add(1, Ordering::SeqCst);
+        Ok(count)
+    }
+
+    /// Number of rebuilds so far.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// Get the current snapshot, rebuilding it first if files were written
+    /// since it was built (or it never was), without querying SQLite.
+    pub fn load(&self, db: &Database) -> Result<Arc<Vec<CompactEntry>>, String> {
+        if self.built_from.load(Ordering::SeqCst) == db.files_generation() + 1 {
+            return Ok(self.entries.read().unwrap().clone());
+        }
+        self.refresh(db)?;
+        Ok(self.entries.read().unwrap().clone())
+    }
+}
+
+/// Snapshot entries that could match a query or any longer query starting
+/// with it, kept by a search session (see `search_sessions`) so the next
+/// keystroke only looks at these instead of querying SQL and scanning the
+/// whole snapshot again.
+///
+/// An entry is a candidate when its folded filename contains the query's
+/// chars in order (which every filename stage needs), when its path
+/// contains the query (the SQL stage's path match) or, in a pool built for
+/// deep path search, when its last folders and filename contain the chars
+/// in order. Typo matches needn't share the query's chars, so the fuzzy
+/// stage still runs its cheap typo pass over every entry.
+#[derive(Debug, Clone)]
+pub struct CandidatePool {
+    /// The snapshot the pool indexes into; a refreshed snapshot makes it stale.
+    entries: Arc<Vec<CompactEntry>>,
+    query_folded: String,
+    deep_path: bool,
+    /// Built by narrowing a parent pool rather than scanning `entries`.
+    narrowed: bool,
+    /// One bit per entry of `entries`.
+    bits: Vec<u64>,
+}
+
+impl CandidatePool {
+    /// The candidates for `query_folded` in `entries`. When `parent` was
+    /// built from the same snapshot for a prefix of the query, only its
+    /// candidates are checked, since anything matching the longer query
+    /// matched the prefix too.
+    pub fn build(
+        entries: Arc<Vec<CompactEntry>>,
+        query_folded: &str,
+        deep_path: bool,
+        parent: Option<&CandidatePool>,
+    ) -> Self {
+        let parent = parent.filter(|parent| {
+            Arc::ptr_eq(&parent.entries, &entries)
+                && query_folded.starts_with(parent.query_folded.as_str())
+                && (parent.deep_path || !deep_path)
+        });
+        let test = CandidateTest::new(query_folded, deep_path);
+        let matching: Vec<usize> = match parent {
+            Some(parent) => {
+                let checked: Vec<usize> = parent.indices().collect();
+                checked.into_par_iter().filter(|&i| test.matches(&entries[i])).collect()
+            }
+            None => (0..entries.len()).into_par_iter().filter(|&i| test.matches(&entries[i])).collect(),
+        };
+        let mut bits = vec![0u64; entries.len().div_ceil(64)];
+        for i in matching {
+            bits[i / 64] |= 1 << (i % 64);
+        }
+        CandidatePool {
+            entries,
+            query_folded: query_folded.to_string(),
+            deep_path,
+            narrowed: parent.is_some(),
+            bits,
+        }
+    }
+
+    /// Number of candidates.
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.bits.get(index / 64).is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(w, &word)| {
+            (0..64).filter(move |bit| word & (1 << bit) != 0).map(move |bit| w * 64 + bit)
+        })
+    }
+
+    /// Whether the pool was built for `query_folded` from `entries`.
+    fn applies_to(&self, query_folded: &str, entries: &Arc<Vec<CompactEntry>>) -> bool {
+        self.query_folded == query_folded && Arc::ptr_eq(&self.entries, entries)
+    }
+
+    /// The SQL stage's matches among the candidates: exact, prefix and
+    /// substring filename or display name matches and path matches that
+    /// pass `filters`, ranked and capped at `limit` like
+    /// [`Database::search_files`].
+    fn substring_matches(&self, query_folded: &str, filters: &QueryFilters, limit: usize) -> Vec<FileEntry> {
+        let type_rank = |file_type: &str| match file_type {
+            "app" => 5,
+            "shortcut" => 4,
+            "document" => 3,
+            "folder" => 2,
+            _ => 1,
+        };
+        let mut matches: Vec<(u8, u8, &CompactEntry)> = self
+            .indices()
+            .filter_map(|i| {
+                let entry = &self.entries[i];
+                let name_class = |name: &str| {
+                    if name == query_folded {
+                        4
+                    } else if name.starts_with(query_folded) {
+                        3
+                    } else if name.contains(query_folded) {
+                        2
+                    } else {
+                        0
+                    }
+                };
+                let class = name_class(&entry.filename_folded).max(name_class(&entry.display_name_folded));
+                let class = if class > 0 {
+                    class
+                } else if contains_ignore_ascii_case(&entry.filepath, query_folded) {
+                    1
+                } else {
+                    return None;
+                };
+                let passes = filters.matches(&entry.filename, &entry.filepath, entry.file_type)
+                    && filters.matches_metadata(entry.file_size, entry.modified_at)
+                    && filters.matches_required_terms(&entry.filename, &entry.display_name);
+                passes.then_some((class, type_rank(entry.file_type), entry))
+            })
+            .collect();
+        matches.sort_by(|(a_class, a_type, a), (b_class, b_type, b)| {
+            (b_class, b_type, b.click_count, b.last_accessed, b.modified_at).cmp(&(
+                a_class,
+                a_type,
+                a.click_count,
+                a.last_accessed,
+                a.modified_at,
+            ))
+        });
+        matches.truncate(limit);
+        matches
+            .into_iter()
+            .map(|(_, _, entry)| FileEntry {
+                id: entry.id,
+                filename: entry.filename.to_string(),
+                filepath: entry.filepath.to_string(),
+                extension: Path::new(&*entry.filename)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                file_size: entry.file_size,
+                modified_at: entry.modified_at,
+                file_type: entry.file_type.to_string(),
+                click_count: entry.click_count,
+                last_accessed: entry.last_accessed,
+                icon_path: None,
+                child_count: entry.child_count,
+                display_name: entry.display_name.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// The check behind [`CandidatePool::build`] for one query.
+struct CandidateTest<'a> {
+    query_folded: &'a str,
+    /// The query's chars without spaces and path separators, which the
+    /// in-order checks skip.
+    chars: Vec<char>,
+    deep_path: bool,
+}
+
+impl<'a> CandidateTest<'a> {
+    fn new(query_folded: &'a str, deep_path: bool) -> Self {
+        let chars = query_folded
+            .chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '/' | '\\'))
+            .collect();
+        CandidateTest {
+            query_folded,
+            chars,
+            deep_path,
+        }
+    }
+
+    fn matches(&self, entry: &CompactEntry) -> bool {
+        is_subsequence(&self.chars, entry.filename_folded.chars())
+            || (!entry.display_name_folded.is_empty() && is_subsequence(&self.chars, entry.display_name_folded.chars()))
+            || contains_ignore_ascii_case(&entry.filepath, self.query_folded)
+            || (self.deep_path && {
+                let (text, _, _) = deep_path_text(&parent_dir(&entry.filepath), &entry.filename);
+                is_subsequence(&self.chars, fold_text(&text).chars())
+            })
+    }
+}
+
+/// Whether `text` contains all of `chars` in order.
+fn is_subsequence(chars: &[char], mut text: impl Iterator<Item = char>) -> bool {
+    chars.iter().all(|&c| text.any(|t| t == c))
+}
+
+/// Whether `haystack` contains `needle`, ignoring ASCII case only, like
+/// SQLite's `LIKE`.
+fn contains_ignore_ascii_case(haystack: &str, needle: &str) -> bool {
+    needle.is_empty()
+        || haystack
+            .as_bytes()
+            .windows(needle.len())
+            .any(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// The candidates a search session's next search for `query` looks at, built
+/// from the current snapshot and narrowed from `parent` where it applies.
+/// `None` for queries that don't search the index by text: blank, clipboard,
+/// emoji and command queries, short ones and wildcard patterns.
+pub fn candidate_pool(
+    db: &Database,
+    snapshot: &FilenameSnapshot,
+    query: &str,
+    options: &SearchOptions,
+    parent: Option<&CandidatePool>,
+) -> Result<Option<CandidatePool>, String> {
+    if query.trim().is_empty()
+        || clipboard_query(query).is_some()
+        || emoji_query(query).is_some()
+        || query.trim_start().starts_with(COMMAND_PREFIX)
+    {
+        return Ok(None);
+    }
+    let text = parse_query(query).text;
+    let query_folded = fold_text(&text);
+    if query_folded.chars().count() <= SHORT_QUERY_CHARS || WildcardPattern::parse(&query_folded).is_some() {
+        return Ok(None);
+    }
+    let deep_path = options.deep_path || is_path_query(&text);
+    Ok(Some(CandidatePool::build(snapshot.load(db)?, &query_folded, deep_path, parent)))
+}
+
+/// Result limit used when neither the caller nor the `result_limit` setting provides one.
+pub const DEFAULT_RESULT_LIMIT: usize = 15;
+
+/// Upper bound for any result limit, and for how many ranked results a
+/// [`PageCache`] keeps per query.
+pub const MAX_RESULT_LIMIT: usize = 300;
+
+/// SQL candidates fetched per requested result, so ranking has some to
+/// choose from.
+const SQL_OVERFETCH: usize = 3;
+
+/// Queries up to this many chars are "short": a substring pattern would
+/// match much of the index, so SQL only looks at filename prefixes and
+/// entries launched before (see [`Database::search_files_short`]), without
+/// over-fetching, and the fuzzy and deep path stages are skipped.
+const SHORT_QUERY_CHARS: usize = 2;
+
+/// Meta key under which the default result limit is persisted.
+const RESULT_LIMIT_KEY: &str = "result_limit";
+/// Meta key under which the fuzzy stage is turned off.
+const FUZZY_KEY: &str = "enable_fuzzy";
+/// Meta key under which deep path search is turned on.
+const DEEP_PATH_KEY: &str = "deep_path_search";
+const CONTEXT_BOOST_KEY: &str = "context_boost";
+const HIDE_EMPTY_FILES_KEY: &str = "hide_empty_files";
+const LARGE_FILE_KEY: &str = "large_file_mb";
+
+/// Default size in MiB above which a regular file ranks a little lower.
+pub const DEFAULT_LARGE_FILE_MB: u64 = 2048;
+
+/// The persisted default result limit, or [`DEFAULT_RESULT_LIMIT`] if unset.
+pub fn default_result_limit(db: &Database) -> usize {
+    db.get_meta(RESULT_LIMIT_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .map(clamp_result_limit)
+        .unwrap_or(DEFAULT_RESULT_LIMIT)
+}
+
+/// Persist a new default result limit, clamped to `1..=MAX_RESULT_LIMIT`.
+/// Returns the value actually stored.
+pub fn set_default_result_limit(db: &Database, limit: usize) -> Result<usize, String> {
+    let limit = clamp_result_limit(limit);
+    db.set_meta(RESULT_LIMIT_KEY, &limit.to_string())
+        .map_err(|e| format!("Failed to save result limit: {}", e))?;
+    Ok(limit)
+}
+
+/// Whether searches run the fuzzy stage; on unless turned off.
+pub fn fuzzy_enabled(db: &Database) -> bool {
+    db.get_meta(FUZZY_KEY).ok().flatten().is_none_or(|value| value != "false")
+}
+
+pub fn set_fuzzy_enabled(db: &Database, enabled: bool) -> Result<(), String> {
+    db.set_meta(FUZZY_KEY, &enabled.to_string())
+        .map_err(|e| format!("Failed to save fuzzy search setting: {}", e))
+}
+
+/// Whether every search runs the deep path stage; off unless turned on.
+pub fn deep_path_enabled(db: &Database) -> bool {
+    db.get_meta(DEEP_PATH_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+pub fn set_deep_path_enabled(db: &Database, enabled: bool) -> Result<(), String> {
+    db.set_meta(DEEP_PATH_KEY, &enabled.to_string())
+        .map_err(|e| format!("Failed to save deep path search setting: {}", e))
+}
+
+/// Whether launches are remembered per foreground app and boosted when the
+/// launcher is opened from that app again. On unless turned off.
+pub fn context_boost_enabled(db: &Database) -> bool {
+    db.get_meta(CONTEXT_BOOST_KEY).ok().flatten().is_none_or(|value| value != "false")
+}
+
+pub fn set_context_boost_enabled(db: &Database, enabled: bool) -> Result<(), String> {
+    db.set_meta(CONTEXT_BOOST_KEY, &enabled.to_string())
+        .map_err(|e| format!("Failed to save context boost setting: {}", e))
+}
+
+/// Whether empty regular files are left out of results; off unless turned on.
+pub fn hide_empty_files(db: &Database) -> bool {
+    db.get_meta(HIDE_EMPTY_FILES_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+pub fn set_hide_empty_files(db: &Database, enabled: bool) -> Result<(), String> {
+    db.set_meta(HIDE_EMPTY_FILES_KEY, &enabled.to_string())
+        .map_err(|e| format!("Failed to save empty files setting: {}", e))
+}
+
+/// The persisted size in MiB above which regular files rank lower, or
+/// [`DEFAULT_LARGE_FILE_MB`] if unset; 0 means never.
+pub fn large_file_mb(db: &Database) -> u64 {
+    db.get_meta(LARGE_FILE_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_LARGE_FILE_MB)
+}
+
+pub fn set_large_file_mb(db: &Database, mb: u64) -> Result<(), String> {
+    db.set_meta(LARGE_FILE_KEY, &mb.to_string())
+        .map_err(|e| format!("Failed to save large file size: {}", e))
+}
+
+/// A [`large_file_mb`] setting in bytes, for [`SearchOptions::large_file_bytes`].
+pub fn large_file_bytes(mb: u64) -> i64 {
+    i64::try_from(mb).unwrap_or(i64::MAX).saturating_mul(1024 * 1024)
+}
+
+/// Keep a result limit within `1..=MAX_RESULT_LIMIT`.
+pub fn clamp_result_limit(limit: usize) -> usize {
+    limit.clamp(1, MAX_RESULT_LIMIT)
+}
+
+/// Default cutoff for the base match score. Every exact, prefix, substring
+/// and path match clears it (so single-character queries are unaffected);
+/// fuzzy hits need roughly half the quality of a consecutive match, which
+/// keeps abbreviations like "vsc" or "chrm" and drops letters scattered
+/// through unrelated names.
+pub const DEFAULT_MIN_SCORE: f64 = 280.0;
+const MIN_SCORE_KEY: &str = "min_match_score";
+
+/// The persisted minimum match score, or [`DEFAULT_MIN_SCORE`] if unset.
+pub fn default_min_score(db: &Database) -> f64 {
+    db.get_meta(MIN_SCORE_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .map(clamp_min_score)
+        .unwrap_or(DEFAULT_MIN_SCORE)
+}
+
+/// Persist a new minimum match score, clamped to `0..=SCORE_EXACT`.
+/// Returns the value actually stored.
+pub fn set_default_min_score(db: &Database, min_score: f64) -> Result<f64, String> {
+    let min_score = clamp_min_score(min_score);
+    db.set_meta(MIN_SCORE_KEY, &min_score.to_string())
+        .map_err(|e| format!("Failed to save minimum score: {}", e))?;
+    Ok(min_score)
+}
+
+fn clamp_min_score(min_score: f64) -> f64 {
+    if min_score.is_nan() {
+        return DEFAULT_MIN_SCORE;
+    }
+    min_score.clamp(0.0, SCORE_EXACT)
+}
+
+/// One page of a ranked result list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub filters: QueryFilters,
+    pub filter_errors: Vec<String>,
+    pub page: usize,
+    pub has_more: bool,
+}
+
+/// The full ranked list (up to [`MAX_RESULT_LIMIT`]) for the most recently
+/// paged query, so "load more" slices it instead of searching again.
+/// Entries are tied to a [`FilenameSnapshot`] version and recomputed after
+/// the index changes.
+#[derive(Default)]
+pub struct PageCache {
+    last: Mutex<Option<CachedQuery>>,
+}
+
+struct CachedQuery {
+    query: String,
+    snapshot_version: u64,
+    response: SearchResponse,
+}
+
+impl PageCache {
+    /// Forget the cached ranking, e.g. after a setting that affects it changed.
+    pub fn clear(&self) {
+        *self.last.lock().unwrap() = None;
+    }
+
+    /// Return page `page` (zero-based) of `page_size` results for `query`.
+    pub fn page(
+        &self,
+        db: &Arc<Database>,
+        snapshot: &FilenameSnapshot,
+        query: &str,
+        page: usize,
+        page_size: usize,
+    ) -> Result<SearchPage, String> {
+        let page_size = clamp_result_limit(page_size);
+        let version = snapshot.version();
+        let mut last = self.last.lock().unwrap();
+
+        let fresh = matches!(&*last, Some(cached) if cached.query == query && cached.snapshot_version == version);
+        if !fresh {
+            let options = SearchOptions {
+                max_results: MAX_RESULT_LIMIT,
+                explain: false,
+                min_score: default_min_score(db),
+                metrics: false,
+                // Windows come and go without the snapshot version changing
+                open_windows: Arc::default(),
+                fuzzy: fuzzy_enabled(db),
+                deep_path: deep_path_enabled(db),
+                // Pages slice one flat ranking
+                group_caps: None,
+                // The foreground app changes without invalidating the cache
+                context_app: None,
+                hide_empty_files: hide_empty_files(db),
+                large_file_bytes: large_file_bytes(large_file_mb(db)),
+            };
+            let response = search(db, snapshot, query, &options, &|| false)?;
+            *last = Some(CachedQuery {
+                query: query.to_string(),
+                // Loading the snapshot during the search may have rebuilt it
+                snapshot_version: snapshot.version(),
+                response,
+            });
+        }
+
+        let cached = last.as_ref().expect("page cache was just filled");
+        let results = &cached.response.results;
+        let start = page.saturating_mul(page_size).min(results.len());
+        let end = start.saturating_add(page_size).min(results.len());
+        Ok(SearchPage {
+            results: results[start..end].to_vec(),
+            filters: cached.response.filters.clone(),
+            filter_errors: cached.response.filter_errors.clone(),
+            page,
+            has_more: end < results.len(),
+        })
+    }
+}
+
+/// How many entries the "recent" and "frequent" sections of the empty-query
+/// view show.
+pub const HOME_SECTION_SIZE: usize = 5;
+const HOME_VIEW_KEY: &str = "show_home_view";
+
+/// Whether an empty query shows pinned, recent and frequent files (the
+/// default) rather than nothing.
+pub fn home_view_enabled(db: &Database) -> bool {
+    db.get_meta(HOME_VIEW_KEY)
+        .ok()
+        .flatten()
+        .is_none_or(|value| value != "false")
+}
+
+/// Persist whether an empty query shows the pinned/recent/frequent view.
+pub fn set_home_view_enabled(db: &Database, enabled: bool) -> Result<(), String> {
+    db.set_meta(HOME_VIEW_KEY, &enabled.to_string())
+        .map_err(|e| format!("Failed to save home view setting: {}", e))
+}
+
+/// Results for an empty query: pinned files, then the most recently and most
+/// frequently launched ones. A file appears only in its first section.
+fn home_results(db: &Database, max_results: usize) -> Result<Vec<SearchResult>, String> {
+    let map_err = |e: rusqlite::Error| format!("SQL search error: {}", e);
+    let pinned = db.pinned_entries().map_err(map_err)?;
+    let recent = db.recently_accessed(HOME_SECTION_SIZE + pinned.len()).map_err(map_err)?;
+    let frequent = db
+        .top_clicked(HOME_SECTION_SIZE * 2 + pinned.len())
+        .map_err(map_err)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let mut seen_ids: HashSet<i64> = HashSet::new();
+    let mut results = Vec::new();
+    for (section, entries, limit) in [
+        ("pinned", pinned, usize::MAX),
+        ("recent", recent, HOME_SECTION_SIZE),
+        ("frequent", frequent, HOME_SECTION_SIZE),
+    ] {
+        let fresh = entries.into_iter().filter(|entry| seen_ids.insert(entry.id));
+        results.extend(fresh.take(limit).map(|entry| SearchResult {
+            section: Some(section.to_string()),
+            ..home_result(&entry, now)
+        }));
+    }
+    results.truncate(max_results);
+    Ok(results)
+}
+
+fn home_result(entry: &FileEntry, now: i64) -> SearchResult {
+    SearchResult {
+        id: entry.id,
+        filename: entry.filename.clone(),
+        display_name: shown_display_name(&entry.display_name),
+        filepath: entry.filepath.clone(),
+        extension: entry.extension.clone(),
+        file_size: entry.file_size,
+        modified_at: entry.modified_at,
+        file_type: entry.file_type.clone(),
+        click_count: entry.click_count,
+        last_accessed: entry.last_accessed,
+        parent_dir: result_parent_dir(&entry.filepath, &entry.file_type),
+        file_size_display: format_size(entry.file_size),
+        modified_display: format_relative_time(entry.modified_at, now),
+        child_count: entry.child_count,
+        score: 0.0,
+        match_type: "home".to_string(),
+        matched_indices: Vec::new(),
+        path_indices: Vec::new(),
+        score_breakdown: None,
+        duplicates: Vec::new(),
+        section: None,
+        group: None,
+        is_running: false,
+        running_pids: Vec::new(),
     }
+}
+
+/// A web search offered when nothing in the index matches. `url` contains a
+/// `{query}` placeholder that is replaced with the URL-encoded query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchTemplate {
+    pub name: String,
+    pub url: String,
+}
+
+const WEB_SEARCH_KEY: &str = "web_search_templates";
 
-    let query_lower = query.to_lowercase();
+fn default_web_searches() -> Vec<WebSearchTemplate> {
+    vec![
+        WebSearchTemplate {
+            name: "Google".to_string(),
+            url: "https://www.google.com/search?q={query}".to_string(),
+        },
+        WebSearchTemplate {
+            name: "YouTube".to_string(),
+            url: "https://www.youtube.com/results?search_query={query}".to_string(),
+        },
+    ]
+}
+
+/// The persisted web-search fallbacks, or Google and YouTube if unset.
+pub fn web_search_templates(db: &Database) -> Vec<WebSearchTemplate> {
+    db.get_meta(WEB_SEARCH_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_else(default_web_searches)
+}
 
-    // Step 1: Get SQL-based results (prefix + substring matches)
-    let sql_results = db
-        .search_files(&query_lower, max_results * 3) // over-fetch for ranking
-        .map_err(|e| format!("SQL search error: {}", e))?;
+/// Persist the web-search fallbacks. Every template must be an http(s) URL
+/// containing `{query}`; an empty list disables the fallback.
+pub fn set_web_search_templates(db: &Database, templates: &[WebSearchTemplate]) -> Result<(), String> {
+    for template in templates {
+        if template.name.trim().is_empty() {
+            return Err("Web search name cannot be empty".to_string());
+        }
+        if !template.url.contains("{query}") {
+            return Err(format!("Web search URL for '{}' must contain {{query}}", template.name));
+        }
+        if !(template.url.starts_with("https://") || template.url.starts_with("http://")) {
+            return Err(format!("Web search URL for '{}' must start with http:// or https://", template.name));
+        }
+    }
+    let json = serde_json::to_string(templates).map_err(|e| e.to_string())?;
+    db.set_meta(WEB_SEARCH_KEY, &json)
+        .map_err(|e| format!("Failed to save web searches: {}", e))
+}
+
+/// Top-level domains accepted without a scheme, "www." or a path. Kept to
+/// ones that don't double as common file extensions ("readme.md", "main.rs").
+const KNOWN_TLDS: &[&str] = &[
+    "com", "org", "net", "io", "dev", "app", "edu", "gov", "co", "uk", "de", "fr", "nl", "eu", "ca", "au", "jp",
+    "ai", "info", "xyz", "tv", "gg", "me", "us",
+];
+
+/// Recognize a URL-like query ("github.com/rust-lang", "https://example.org",
+/// "localhost:3000") and return it normalized with a scheme.
+pub fn detect_url(query: &str) -> Option<String> {
+    let query = query.trim();
+    if query.is_empty() || query.chars().any(char::is_whitespace) {
+        return None;
+    }
+
+    let lower = query.to_ascii_lowercase();
+    let (has_scheme, rest) = match lower.split_once("://") {
+        Some(("http", rest)) | Some(("https", rest)) => (true, rest),
+        Some(_) => return None,
+        None => (false, lower.as_str()),
+    };
+
+    let (authority, path) = match rest.find(['/', '?', '#']) {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => host,
+        Some(_) => return None,
+        None => authority,
+    };
+
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid_labels = labels
+        .iter()
+        .all(|l| !l.is_empty() && !l.starts_with('-') && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    if !valid_labels {
+        return None;
+    }
+
+    let tld = labels[labels.len() - 1];
+    let looks_like_host = if host == "localhost" {
+        authority != host || has_scheme
+    } else {
+        labels.len() >= 2
+            && tld.len() >= 2
+            && tld.chars().all(|c| c.is_ascii_alphabetic())
+            && (has_scheme || host.starts_with("www.") || !path.is_empty() || KNOWN_TLDS.contains(&tld))
+    };
+    if !looks_like_host {
+        return None;
+    }
+
+    Some(if has_scheme {
+        query.to_string()
+    } else {
+        format!("https://{}", query)
+    })
+}
+
+/// Percent-encode `text` for use in a URL query string.
+pub fn url_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// A result that isn't backed by an index row (URLs, web searches).
+/// Synthetic results use negative ids so they never collide with real ones.
+fn synthetic_result(id: i64, title: String, target: String, match_type: &str, score: f64) -> SearchResult {
+    SearchResult {
+        id,
+        filename: title,
+        display_name: None,
+        filepath: target,
+        extension: String::new(),
+        file_size: 0,
+        modified_at: 0,
+        file_type: match_type.to_string(),
+        click_count: 0,
+        last_accessed: 0,
+        parent_dir: String::new(),
+        file_size_display: String::new(),
+        modified_display: String::new(),
+        child_count: None,
+        score,
+        match_type: match_type.to_string(),
+        matched_indices: Vec::new(),
+        path_indices: Vec::new(),
+        score_breakdown: None,
+        duplicates: Vec::new(),
+        section: None,
+        group: None,
+        is_running: false,
+        running_pids: Vec::new(),
+    }
+}
+
+/// Queries starting with this character search launcher commands instead of files.
+pub const COMMAND_PREFIX: char = '>';
+
+/// An internal launcher action reachable through the `>` prefix. `id` is what
+/// the frontend passes back to `run_launcher_command`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LauncherCommand {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const LAUNCHER_COMMANDS: &[LauncherCommand] = &[
+    LauncherCommand {
+        id: "rebuild",
+        name: "Rebuild index",
+        description: "Re-scan all indexed folders",
+    },
+    LauncherCommand {
+        id: "index_stats",
+        name: "Index stats",
+        description: "Show how many files are indexed and when",
+    },
+    LauncherCommand {
+        id: "settings",
+        name: "Settings",
+        description: "Open launcher settings",
+    },
+    LauncherCommand {
+        id: "check_updates",
+        name: "Check for updates",
+        description: "Look for a newer version of AnCheck",
+    },
+    LauncherCommand {
+        id: "install_update",
+        name: "Install update",
+        description: "Download the newest version and restart",
+    },
+    LauncherCommand {
+        id: "clear_clipboard",
+        name: "Clear clipboard history",
+        description: "Forget every text kept in clipboard history",
+    },
+    LauncherCommand {
+        id: "clear_usage",
+        name: "Clear usage data",
+        description: "Forget what you launched, picked and copied",
+    },
+    LauncherCommand {
+        id: "reset_index",
+        name: "Reset index",
+        description: "Delete the index and build it again from scratch",
+    },
+    LauncherCommand {
+        id: "quit",
+        name: "Quit",
+        description: "Exit the launcher",
+    },
+];
+
+/// Look up a launcher command by id.
+pub fn find_launcher_command(id: &str) -> Option<&'static LauncherCommand> {
+    LAUNCHER_COMMANDS.iter().find(|command| command.id == id)
+}
+
+/// Results for a `>` query: every command when nothing follows the prefix,
+/// otherwise the ones whose name fuzzy-matches, best first. An unmatched
+/// name yields a single "no such command" placeholder (`match_type =
+/// "no_command"`) rather than falling through to file search.
+///
+/// Command results carry the action id in `filepath` and the description in
+/// `parent_dir`.
+fn command_results(text: &str) -> Vec<SearchResult> {
+    let text = text.trim();
+    let query_folded = fold_text(text);
+    let matcher = SkimMatcherV2::default();
+    let query_chars = query_folded.chars().count();
+
+    let mut results: Vec<SearchResult> = LAUNCHER_COMMANDS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, command)| {
+            let (score, indices) = if query_folded.is_empty() {
+                (0.0, Vec::new())
+            } else {
+                let (score, indices) = matcher.fuzzy_indices(&fold_text(command.name), &query_folded)?;
+                (normalize_fuzzy(score, query_chars, (0.0, SCORE_EXACT)), indices)
+            };
+            Some(SearchResult {
+                matched_indices: indices,
+                path_indices: Vec::new(),
+                parent_dir: command.description.to_string(),
+                ..synthetic_result(
+                    -100 - i as i64,
+                    command.name.to_string(),
+                    command.id.to_string(),
+                    "command",
+                    score,
+                )
+            })
+        })
+        .collect();
+
+    if results.is_empty() {
+        let title = format!("No such command: ‘{}’", text);
+        results.push(synthetic_result(-99, title, String::new(), "no_command", 0.0));
+    }
+    // Stable sort keeps registry order for an empty query
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results
+}
+
+/// A power or session action offered alongside files when the query matches
+/// its name or a keyword ("lock", "reboot", "empty recycle bin"). `id` is what
+/// the frontend passes to `run_system_action`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SystemActionEntry {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub keywords: &'static [&'static str],
+}
+
+pub const SYSTEM_ACTIONS: &[SystemActionEntry] = &[
+    SystemActionEntry {
+        id: "lock",
+        name: "Lock",
+        description: "Lock this PC",
+        keywords: &["lock screen", "lock pc"],
+    },
+    SystemActionEntry {
+        id: "sleep",
+        name: "Sleep",
+        description: "Put this PC to sleep",
+        keywords: &["suspend"],
+    },
+    SystemActionEntry {
+        id: "sign_out",
+        name: "Sign out",
+        description: "Sign out of Windows",
+        keywords: &["log off", "logoff", "log out", "logout"],
+    },
+    SystemActionEntry {
+        id: "restart",
+        name: "Restart",
+        description: "Restart this PC",
+        keywords: &["reboot"],
+    },
+    SystemActionEntry {
+        id: "shutdown",
+        name: "Shut down",
+        description: "Turn off this PC",
+        keywords: &["shutdown", "power off", "turn off"],
+    },
+    SystemActionEntry {
+        id: "empty_recycle_bin",
+        name: "Empty Recycle Bin",
+        description: "Permanently delete everything in the Recycle Bin",
+        keywords: &["empty trash", "clear recycle bin"],
+    },
+];
+
+/// Lowest base score of a settings page result. With hundreds of names and
+/// keywords, weaker fuzzy hits ("notes" in "Notifications") would show up
+/// for almost any query; typos and abbreviations ("blutooth", "dsplay")
+/// still get through.
+const SETTINGS_PAGE_MIN_SCORE: f64 = 450.0;
+/// Shorter queries prefix-match too many pages to be worth listing them.
+const SETTINGS_PAGE_MIN_QUERY_CHARS: usize = 3;
+
+/// Settings pages and Control Panel applets (see [`SETTINGS_PAGES`]) whose
+/// name or a keyword matches `query_folded`, dropping those under
+/// `min_score` or [`SETTINGS_PAGE_MIN_SCORE`]. Nothing matches queries
+/// under [`SETTINGS_PAGE_MIN_QUERY_CHARS`]. Results carry the page's target in `filepath` and have
+/// `match_type = "setting"`, so launching them goes through `launch_file`.
+fn settings_page_results(query_folded: &str, min_score: f64) -> Vec<SearchResult> {
+    if query_folded.chars().count() < SETTINGS_PAGE_MIN_QUERY_CHARS {
+        return Vec::new();
+    }
+    let matcher = SkimMatcherV2::default();
+    let min_score = min_score.max(SETTINGS_PAGE_MIN_SCORE);
+
+    SETTINGS_PAGES
+        .iter()
+        .enumerate()
+        .filter_map(|(i, page)| {
+            let keywords = page.keywords.iter().copied();
+            let (score, indices) = score_name_or_keyword(&matcher, page.name, keywords, query_folded)?;
+            (score >= min_score).then(|| SearchResult {
+                matched_indices: indices,
+                path_indices: Vec::new(),
+                parent_dir: if page.target.starts_with(SETTINGS_URI_PREFIX) { "Settings" } else { "Control Panel" }
+                    .to_string(),
+                ..synthetic_result(
+                    -2000 - i as i64,
+                    page.name.to_string(),
+                    page.target.to_string(),
+                    "setting",
+                    score + file_type_boost("setting"),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Score a built-in label (action name, emoji keyword) against the query on
+/// the same scale as file names: exact, prefix, then a word starting with the
+/// query as a substring match, anything else through the fuzzy band.
+/// Returns the score and the matched char positions in `label`.
+fn score_label(matcher: &SkimMatcherV2, label: &str, query_folded: &str) -> Option<(f64, Vec<usize>)> {
+    let folded = fold_text(label);
+    let query_chars = query_folded.chars().count();
+    if folded == query_folded {
+        return Some((SCORE_EXACT, (0..query_chars).collect()));
+    }
+    if folded.starts_with(query_folded) {
+        return Some((SCORE_PREFIX, (0..query_chars).collect()));
+    }
+    let word_start = folded
+        .match_indices(query_folded)
+        .map(|(byte, _)| byte)
+        .find(|&byte| folded[..byte].ends_with(' '));
+    if let Some(byte) = word_start {
+        let start = folded[..byte].chars().count();
+        return Some((SCORE_SUBSTRING, (start..start + query_chars).collect()));
+    }
+    matcher
+        .fuzzy_indices(&folded, query_folded)
+        .map(|(score, indices)| (normalize_fuzzy(score, query_chars, FUZZY_NAME_BAND), indices))
+}
+
+/// The better of `name` and its best-matching keyword scored against the
+/// query (see [`score_label`]). Match positions are only kept when the name
+/// itself matched best, since the keywords aren't shown.
+fn score_name_or_keyword<'a>(
+    matcher: &SkimMatcherV2,
+    name: &str,
+    keywords: impl Iterator<Item = &'a str>,
+    query_folded: &str,
+) -> Option<(f64, Vec<usize>)> {
+    let by_name = score_label(matcher, name, query_folded);
+    let by_keyword = keywords
+        .filter_map(|keyword| score_label(matcher, keyword, query_folded))
+        .map(|(score, _)| (score, Vec::new()))
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+    match (by_name, by_keyword) {
+        (Some(name), Some(keyword)) if keyword.0 > name.0 => Some(keyword),
+        (Some(name), _) => Some(name),
+        (None, keyword) => keyword,
+    }
+}
+
+/// System actions whose name or a keyword matches `query_folded` (see
+/// [`score_label`]), dropping those under `min_score`. Results carry the
+/// action id in `filepath` and the description in `parent_dir`, with
+/// `match_type = "action"`.
+fn system_action_results(query_folded: &str, min_score: f64) -> Vec<SearchResult> {
+    let matcher = SkimMatcherV2::default();
+
+    SYSTEM_ACTIONS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, action)| {
+            let keywords = action.keywords.iter().copied();
+            let (score, indices) = score_name_or_keyword(&matcher, action.name, keywords, query_folded)?;
+            (score >= min_score).then(|| SearchResult {
+                matched_indices: indices,
+                path_indices: Vec::new(),
+                parent_dir: action.description.to_string(),
+                ..synthetic_result(
+                    -200 - i as i64,
+                    action.name.to_string(),
+                    action.id.to_string(),
+                    "action",
+                    score,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Characters of a snippet's text shown under its name.
+const SNIPPET_PREVIEW_CHARS: usize = 60;
+
+/// Snippets whose name or a keyword matches `query_folded` (see
+/// [`score_label`]), dropping those under `min_score`. Results carry the
+/// snippet id in `filepath` and the start of its text in `parent_dir`, with
+/// `match_type = "snippet"`.
+fn snippet_results(db: &Database, query_folded: &str, min_score: f64) -> Vec<SearchResult> {
+    let snippets = match db.snippets() {
+        Ok(snippets) => snippets,
+        Err(e) => {
+            warn!("Failed to load snippets: {}", e);
+            return Vec::new();
+        }
+    };
+    let matcher = SkimMatcherV2::default();
+
+    snippets
+        .iter()
+        .enumerate()
+        .filter_map(|(i, snippet)| {
+            let keywords = snippet.keywords.iter().map(String::as_str);
+            let (score, indices) = score_name_or_keyword(&matcher, &snippet.name, keywords, query_folded)?;
+            if score < min_score {
+                return None;
+            }
+            let first_line = snippet.body.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+            let mut preview: String = first_line.chars().take(SNIPPET_PREVIEW_CHARS).collect();
+            if preview.len() < first_line.len() || snippet.body.trim().lines().nth(1).is_some() {
+                preview.push('…');
+            }
+            Some(SearchResult {
+                matched_indices: indices,
+                path_indices: Vec::new(),
+                parent_dir: preview,
+                click_count: snippet.use_count,
+                ..synthetic_result(
+                    -400 - i as i64,
+                    snippet.name.clone(),
+                    snippet.id.to_string(),
+                    "snippet",
+                    score,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Open windows whose title or program name matches `query_folded` (see
+/// [`score_label`]), dropping those under `min_score`. Results carry the
+/// window handle in `filepath` and the program in `parent_dir`, with
+/// `match_type = "window"`. A window outranks `results` for launching its
+/// program again, since switching is usually what was meant.
+fn window_results(windows: &[OpenWindow], query_folded: &str, min_score: f64, results: &[SearchResult]) -> Vec<SearchResult> {
+    let matcher = SkimMatcherV2::default();
+
+    windows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, window)| {
+            let program = window.exe_name.rsplit_once('.').map_or(window.exe_name.as_str(), |(stem, _)| stem);
+            let (score, indices) =
+                score_name_or_keyword(&matcher, &window.title, std::iter::once(program), query_folded)?;
+            if score < min_score {
+                return None;
+            }
+            let app_score = results
+                .iter()
+                .filter(|r| {
+                    crate::processes::running_exe_name(r).is_some_and(|exe| exe.eq_ignore_ascii_case(&window.exe_name))
+                })
+                .map(|r| r.score)
+                .fold(f64::NEG_INFINITY, f64::max);
+            Some(SearchResult {
+                matched_indices: indices,
+                path_indices: Vec::new(),
+                parent_dir: window.exe_name.clone(),
+                is_running: true,
+                running_pids: vec![window.pid],
+                ..synthetic_result(
+                    -300 - i as i64,
+                    window.title.clone(),
+                    window.hwnd.to_string(),
+                    "window",
+                    score.max(app_score + 1.0),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Most emoji returned for one query.
+pub const EMOJI_RESULT_LIMIT: usize = 8;
+
+/// The search term of an emoji query (":fire", "emoji rocket"), or None if
+/// the query isn't one.
+fn emoji_query(query: &str) -> Option<&str> {
+    let query = query.trim_start();
+    if let Some(term) = query.strip_prefix(':') {
+        // "::" or ":" alone isn't a search
+        return (!term.trim().is_empty() && !term.starts_with(':')).then_some(term);
+    }
+    let (prefix, term) = query.split_at_checked(6)?;
+    (prefix.eq_ignore_ascii_case("emoji ") && !term.trim().is_empty()).then_some(term)
+}
+
+/// Emoji whose name or keywords match `term`, best first. Results have the
+/// emoji in `filename` and `filepath` (what gets copied) and its name in
+/// `parent_dir`, with `match_type = "emoji"`.
+fn emoji_results(term: &str, min_score: f64) -> Vec<SearchResult> {
+    let matcher = SkimMatcherV2::default();
+    let query_folded = fold_text(term.trim());
+
+    let mut results: Vec<SearchResult> = crate::emoji::EMOJI
+        .iter()
+        .enumerate()
+        .filter_map(|(i, emoji)| {
+            let score = std::iter::once(emoji.name)
+                .chain(emoji.keywords.split(' ').filter(|k| !k.is_empty()))
+                .filter_map(|label| score_label(&matcher, label, &query_folded))
+                .map(|(score, _)| score)
+                .fold(f64::NEG_INFINITY, f64::max);
+            (score >= min_score).then(|| SearchResult {
+                parent_dir: emoji.name.to_string(),
+                ..synthetic_result(
+                    -1000 - i as i64,
+                    emoji.emoji.to_string(),
+                    emoji.emoji.to_string(),
+                    "emoji",
+                    score,
+                )
+            })
+        })
+        .collect();
+    // Stable sort: equal scores keep the table's (popularity) order
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(EMOJI_RESULT_LIMIT);
+    results
+}
+
+/// Characters of a clipboard entry shown as its title.
+const CLIPBOARD_PREVIEW_CHARS: usize = 80;
+
+/// The search term of a clipboard history query ("clip invoice",
+/// ">clipboard", "clip " alone for everything), or None if the query isn't
+/// one. "clip" without a space is left to file search, for Clipchamp.
+fn clipboard_query(query: &str) -> Option<&str> {
+    let query = query.trim_start();
+    if let Some((prefix, term)) = query.split_at_checked(5) {
+        if prefix.eq_ignore_ascii_case("clip ") {
+            return Some(term);
+        }
+    }
+    let (prefix, term) = query.split_at_checked(10)?;
+    (prefix.eq_ignore_ascii_case(">clipboard") && (term.is_empty() || term.starts_with(char::is_whitespace)))
+        .then_some(term)
+}
+
+/// Clipboard history entries containing every word of `term`, newest
+/// first. Results carry the entry id in `filepath`, the start of its text
+/// as `filename` and when it was copied in `parent_dir`, with `match_type =
+/// "clipboard"`. With history off there's a single `no_command` result
+/// saying so.
+fn clipboard_results(db: &Database, term: &str) -> Vec<SearchResult> {
+    if !crate::clipboard_history::enabled(db) {
+        let title = "Clipboard history is off; turn it on in Settings".to_string();
+        return vec![synthetic_result(-99, title, String::new(), "no_command", 0.0)];
+    }
+    let entries = match db.clipboard_entries() {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to load clipboard history: {}", e);
+            return Vec::new();
+        }
+    };
+    let words: Vec<String> = fold_text(term).split_whitespace().map(str::to_string).collect();
+    let now = chrono::Utc::now().timestamp();
+
+    entries
+        .iter()
+        .filter(|entry| {
+            let folded = fold_text(&entry.text);
+            words.iter().all(|word| folded.contains(word.as_str()))
+        })
+        .enumerate()
+        .map(|(i, entry)| {
+            let text = entry.text.trim();
+            let flattened: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut preview: String = flattened.chars().take(CLIPBOARD_PREVIEW_CHARS).collect();
+            if preview.len() < flattened.len() {
+                preview.push('…');
+            }
+            SearchResult {
+                parent_dir: format_relative_time(entry.copied_at, now),
+                ..synthetic_result(-5000 - i as i64, preview, entry.id.to_string(), "clipboard", 0.0)
+            }
+        })
+        .collect()
+}
+
+/// Error returned by [`search`] when a newer search made this one obsolete.
+/// The frontend ignores it rather than treating it as a failure.
+pub const SEARCH_SUPERSEDED: &str = "superseded";
+
+/// Performs multi-strategy search combining SQL pre-filtering with in-memory fuzzy matching.
+///
+/// Strategy:
+/// 1. SQL LIKE query for prefix/substring matches (fast, uses indexes)
+/// 2. In-memory fuzzy matching over the [`FilenameSnapshot`] for fuzzy results
+/// 3. Combine, deduplicate, rank, and return top results
+///
+/// Filter tokens (see [`parse_query`]) are applied in both stages.
+///
+/// A URL-like query gets an "open in browser" result (`match_type = "url"`)
+/// ahead of the files; if nothing matches at all, the configured web
+/// searches are offered instead (`match_type = "web_search"`). Matching
+/// power/session actions (see [`SYSTEM_ACTIONS`]) are ranked in with the
+/// files when no filters are given.
+///
+/// A blank query returns the pinned/recent/frequent view (see
+/// [`home_view_enabled`]), with each result's `section` set. A query starting
+/// with [`COMMAND_PREFIX`] lists launcher commands instead of files, and an
+/// emoji query (":fire", "emoji rocket") lists matching emoji.
+///
+/// `is_superseded` is polled between stages and during the fuzzy scan; once it
+/// returns true the search is abandoned with [`SEARCH_SUPERSEDED`] as the error.
+///
+/// File searches slower than [`SLOW_SEARCH_MS`] are logged; the stage
+/// timings are returned as [`SearchResponse::meta`] when `options.metrics` is set.
+pub fn search(
+    db: &Arc<Database>,
+    snapshot: &FilenameSnapshot,
+    query: &str,
+    options: &SearchOptions,
+    is_superseded: &(dyn Fn() -> bool + Sync),
+) -> Result<SearchResponse, String> {
+    search_with_candidates(db, snapshot, query, options, None, is_superseded)
+}
+
+/// [`search`], looking only at `candidates` in the SQL, fuzzy and deep path
+/// stages when they were built for this query from the current snapshot
+/// (see [`candidate_pool`]). Otherwise they're ignored.
+pub fn search_with_candidates(
+    db: &Arc<Database>,
+    snapshot: &FilenameSnapshot,
+    query: &str,
+    options: &SearchOptions,
+    candidates: Option<&CandidatePool>,
+    is_superseded: &(dyn Fn() -> bool + Sync),
+) -> Result<SearchResponse, String> {
+    let max_results = options.max_results;
+    if query.trim().is_empty() {
+        let results = if home_view_enabled(db) {
+            home_results(db, max_results)?
+        } else {
+            Vec::new()
+        };
+        return Ok(SearchResponse {
+            results,
+            ..Default::default()
+        });
+    }
+    if let Some(term) = clipboard_query(query) {
+        let mut results = clipboard_results(db, term);
+        results.truncate(max_results);
+        return Ok(SearchResponse {
+            results,
+            ..Default::default()
+        });
+    }
+    if let Some(term) = emoji_query(query) {
+        let mut results = emoji_results(term, options.min_score);
+        results.truncate(max_results);
+        return Ok(SearchResponse {
+            results,
+            ..Default::default()
+        });
+    }
+    if let Some(command) = query.trim_start().strip_prefix(COMMAND_PREFIX) {
+        let mut results = command_results(command);
+        results.truncate(max_results);
+        return Ok(SearchResponse {
+            results,
+            ..Default::default()
+        });
+    }
+
+    let ParsedQuery {
+        text,
+        filters,
+        errors: filter_errors,
+        fully_strict,
+        ..
+    } = parse_query(query);
+    if text.is_empty() && filters.is_empty() {
+        return Ok(SearchResponse {
+            filter_errors,
+            ..Default::default()
+        });
+    }
+
+    let mut query_folded = fold_text(&text);
+    let mut wildcard = WildcardPattern::parse(&query_folded);
+    // A pattern of only wildcards matches every name: alongside filters it
+    // adds nothing to them, on its own it isn't worth running
+    if wildcard.as_ref().is_some_and(|pattern| !pattern.has_literals()) {
+        if filters.is_empty() {
+            let mut filter_errors = filter_errors;
+            filter_errors.push(format!("'{}' needs some text besides * and ?", text));
+            return Ok(SearchResponse {
+                filter_errors,
+                ..Default::default()
+            });
+        }
+        query_folded.clear();
+        wildcard = None;
+    }
+    let started = Instant::now();
+    let mut meta = SearchMeta::default();
+
+    // Step 1: Get SQL-based results (prefix + substring matches, or a GLOB
+    // for wildcard queries)
+    let short_query = wildcard.is_none() && (1..=SHORT_QUERY_CHARS).contains(&query_folded.chars().count());
+    let pool = match candidates {
+        Some(pool) if wildcard.is_none() && !short_query && pool.applies_to(&query_folded, &snapshot.load(db)?) => {
+            Some(pool)
+        }
+        _ => None,
+    };
+    meta.reused_candidates = pool.is_some_and(|pool| pool.narrowed);
+    let sql_results = if let Some(pool) = pool {
+        Ok(pool.substring_matches(&query_folded, &filters, max_results * SQL_OVERFETCH))
+    } else if wildcard.is_some() {
+        db.search_files_glob(&query_folded, &filters, max_results * SQL_OVERFETCH)
+    } else if short_query {
+        db.search_files_short(&query_folded, &filters, max_results)
+    } else {
+        db.search_files(&query_folded, &filters, max_results * SQL_OVERFETCH)
+    }
+    .map_err(|e| format!("SQL search error: {}", e))?;
+    meta.sql_ms = elapsed_ms(started);
+    meta.sql_candidates = sql_results.len();
+
+    if is_superseded() {
+        return Err(SEARCH_SUPERSEDED.to_string());
+    }
+
+    // Step 2: Score SQL results first
+    let matcher = SkimMatcherV2::default();
+    let mut scored_results: Vec<SearchResult> = Vec::new();
+    let mut seen_ids: HashSet<i64> = HashSet::new();
+
+    // Process SQL results first (these are prefix/substring matches)
+    let now = chrono::Utc::now().timestamp();
+    for entry in &sql_results {
+        seen_ids.insert(entry.id);
+        // SQL only checks case-sensitive containment, not word boundaries
+        if !filters.matches_required_terms(&entry.filename, &entry.display_name)
+            || (options.hide_empty_files && is_empty_file(&entry.file_type, entry.file_size))
+        {
+            continue;
+        }
+        let scored = match &wildcard {
+            Some(pattern) => score_wildcard_entry(entry, pattern, &query_folded, options.large_file_bytes),
+            None => Some(score_entry(entry, &query_folded, &matcher, options.large_file_bytes)),
+        };
+        let Some((breakdown, indices)) = scored else {
+            continue;
+        };
+        if !breakdown.passes(options.min_score) {
+            continue;
+        }
+        scored_results.push(SearchResult {
+            id: entry.id,
+            filename: entry.filename.clone(),
+            display_name: shown_display_name(&entry.display_name),
+            filepath: entry.filepath.clone(),
+            extension: entry.extension.clone(),
+            file_size: entry.file_size,
+            modified_at: entry.modified_at,
+            file_type: entry.file_type.clone(),
+            click_count: entry.click_count,
+            last_accessed: entry.last_accessed,
+            parent_dir: result_parent_dir(&entry.filepath, &entry.file_type),
+            file_size_display: format_size(entry.file_size),
+            modified_display: format_relative_time(entry.modified_at, now),
+            child_count: entry.child_count,
+            score: breakdown.total(),
+            match_type: breakdown.match_type().to_string(),
+            matched_indices: indices,
+            path_indices: Vec::new(),
+            score_breakdown: options.explain.then_some(breakdown),
+            duplicates: Vec::new(),
+            section: None,
+            group: None,
+            is_running: false,
+            running_pids: Vec::new(),
+        });
+    }
+
+    // Step 3: Only do the fuzzy scan if SQL didn't return enough good results.
+    // It runs in parallel over the in-memory snapshot rather than re-reading every row.
+    // A filter-only query has no text to fuzzy match against, and a wildcard
+    // query spells out exactly what it wants.
+    if options.fuzzy && wildcard.is_none() && !short_query && scored_results.len() < max_results && !query_folded.is_empty() {
+        let fuzzy_started = Instant::now();
+        let entries = match pool {
+            Some(pool) => pool.entries.clone(),
+            None => snapshot.load(db)?,
+        };
+        let query = FuzzyQuery::new(&query_folded, options.min_score);
+        let fuzzy_results = fuzzy_matches(&entries, &query, &filters, &seen_ids, options, pool, is_superseded);
+        if is_superseded() {
+            return Err(SEARCH_SUPERSEDED.to_string());
+        }
+        meta.fuzzy_ran = true;
+        meta.fuzzy_ms = elapsed_ms(fuzzy_started);
+        meta.fuzzy_candidates = fuzzy_results.len();
+        scored_results.extend(fuzzy_results);
+    }
+
+    // Step 4: Deep path search, matching folders as well as the filename, only
+    // while the filename stages leave the list short. A `/` in the query asks
+    // for it even when the setting is off.
+    let deep_query = deep_path_query(&query_folded);
+    let deep_path = options.deep_path || is_path_query(&text);
+    if deep_path && wildcard.is_none() && !short_query && scored_results.len() < max_results && !deep_query.is_empty() {
+        let deep_started = Instant::now();
+        // A pool built without deep path candidates can't narrow this stage
+        let pool = pool.filter(|pool| pool.deep_path);
+        let entries = match pool {
+            Some(pool) => pool.entries.clone(),
+            None => snapshot.load(db)?,
+        };
+        seen_ids.extend(scored_results.iter().map(|result| result.id));
+        let deep_results =
+            deep_path_matches(&entries, &deep_query, &filters, &seen_ids, options, pool, is_superseded);
+        if is_superseded() {
+            return Err(SEARCH_SUPERSEDED.to_string());
+        }
+        meta.deep_path_ran = true;
+        meta.deep_path_ms = elapsed_ms(deep_started);
+        scored_results.extend(deep_results);
+    }
+
+    if !query_folded.is_empty() {
+        let picks = db
+            .query_picks_for(&query_folded)
+            .map_err(|e| format!("SQL search error: {}", e))?;
+        let boosts = personal_boosts(&picks, &query_folded, chrono::Utc::now().timestamp());
+        apply_personal_boosts(&mut scored_results, &boosts);
+    }
+    if let Some(context_app) = options.context_app.as_deref() {
+        let picks = db
+            .context_picks_for(context_app)
+            .map_err(|e| format!("SQL search error: {}", e))?;
+        let boosts = context_boosts(&picks, chrono::Utc::now().timestamp());
+        apply_context_boosts(&mut scored_results, &boosts);
+    }
+
+    // Highlight where quoted / case-sensitive terms actually matched, which
+    // the case-insensitive scoring may have placed elsewhere
+    if !filters.required_terms.is_empty() {
+        for result in &mut scored_results {
+            let shown = result.display_name.as_deref().unwrap_or(&result.filename);
+            let mut indices = required_term_indices(shown, &filters.required_terms);
+            if !fully_strict {
+                indices.extend(result.matched_indices.iter().copied());
+                indices.sort_unstable();
+                indices.dedup();
+            }
+            result.matched_indices = indices;
+        }
+    }
+
+    if filters.is_empty() && !query_folded.is_empty() && wildcard.is_none() {
+        scored_results.extend(system_action_results(&query_folded, options.min_score));
+        scored_results.extend(settings_page_results(&query_folded, options.min_score));
+        let windows = window_results(&options.open_windows, &query_folded, options.min_score, &scored_results);
+        scored_results.extend(windows);
+        scored_results.extend(snippet_results(db, &query_folded, options.min_score));
+    }
+
+    // Sort by score descending
+    scored_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    let pinned: HashSet<String> = db
+        .pinned_entries()
+        .map_err(|e| format!("Failed to load pinned files: {}", e))?
+        .into_iter()
+        .map(|entry| entry.filepath)
+        .collect();
+    let mut scored_results = dedupe_results(scored_results, &pinned);
+
+    if let Some(url) = detect_url(query) {
+        let title = format!("Open {} in browser", query.trim());
+        scored_results.insert(0, synthetic_result(-1, title, url, "url", SCORE_EXACT));
+    } else if scored_results.is_empty() && !text.is_empty() {
+        let encoded = url_encode(&text);
+        for (i, template) in web_search_templates(db).into_iter().enumerate() {
+            scored_results.push(synthetic_result(
+                -2 - i as i64,
+                format!("Search {} for ‘{}’", template.name, text),
+                template.url.replace("{query}", &encoded),
+                "web_search",
+                0.0,
+            ));
+        }
+    }
+
+    // Return top N results, picked per group after ranking so the best
+    // of each group survives
+    match &options.group_caps {
+        Some(caps) => scored_results = group_results(scored_results, caps, max_results),
+        None => scored_results.truncate(max_results),
+    }
+
+    meta.total_ms = elapsed_ms(started);
+    if meta.total_ms > SLOW_SEARCH_MS {
+        warn!("Slow search for '{}': {:?}", query, meta);
+    }
+    Ok(SearchResponse {
+        results: scored_results,
+        filters,
+        filter_errors,
+        meta: options.metrics.then_some(meta),
+    })
+}
+
+/// Groups of a grouped search, in the order they are listed.
+const RESULT_GROUPS: [&str; 5] = ["apps", "actions", "folders", "documents", "other"];
+
+/// Index in [`RESULT_GROUPS`] of `result`'s group.
+fn result_group(result: &SearchResult) -> usize {
+    if result.id < 0 {
+        return 1;
+    }
+    match result.file_type.as_str() {
+        "app" | "shortcut" | "command" => 0,
+        "folder" => 2,
+        "document" => 3,
+        _ => 4,
+    }
+}
+
+/// Pick at most `max_results` of `results` (best first) without taking more
+/// of a group than its cap allows, then list them by group, best first
+/// within each. Slots the caps leave empty go to the best results left
+/// out, so a query matching only documents still fills the list.
+fn group_results(results: Vec<SearchResult>, caps: &GroupCaps, max_results: usize) -> Vec<SearchResult> {
+    let cap = |group: usize| match RESULT_GROUPS[group] {
+        "actions" => usize::MAX,
+        name => caps.each().iter().find(|(n, _)| *n == name).map_or(usize::MAX, |(_, cap)| *cap),
+    };
+    let mut counts = [0; RESULT_GROUPS.len()];
+    let mut picked = Vec::with_capacity(max_results);
+    let mut left_out = Vec::new();
+    for mut result in results {
+        let group = result_group(&result);
+        result.group = Some(RESULT_GROUPS[group].to_string());
+        if picked.len() < max_results && counts[group] < cap(group) {
+            counts[group] += 1;
+            picked.push(result);
+        } else {
+            left_out.push(result);
+        }
+    }
+    let room = max_results - picked.len();
+    picked.extend(left_out.into_iter().take(room));
+    // Stable, so each group stays in score order
+    picked.sort_by_key(result_group);
+    picked
+}
+
+/// Normalize a query for recording which result was picked for it: the
+/// folded free text without filter tokens, capped in length. None if empty.
+pub fn pick_key(query: &str) -> Option<String> {
+    const MAX_PICK_KEY_CHARS: usize = 64;
+    let folded = fold_text(parse_query(query).text.trim());
+    let key: String = folded.chars().take(MAX_PICK_KEY_CHARS).collect();
+    (!key.is_empty()).then_some(key)
+}
+
+/// Upper bound of the personalization boost, approached as picks add up.
+const PERSONAL_BOOST_MAX: f64 = 200.0;
+/// Days after which a pick counts half as much.
+const PICK_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Per-filepath boost from past picks. A pick for exactly this query counts
+/// fully, one for a shorter query the user kept typing past counts 3/4, and
+/// one for a longer query the user hasn't finished typing counts half. Each
+/// pick decays with age.
+fn personal_boosts(picks: &[QueryPick], query_folded: &str, now: i64) -> HashMap<String, f64> {
+    let mut weights: HashMap<String, f64> = HashMap::new();
+    for pick in picks {
+        let relation = if pick.query == query_folded {
+            1.0
+        } else if query_folded.starts_with(pick.query.as_str()) {
+            0.75
+        } else {
+            0.5
+        };
+        let age_days = (now - pick.last_used).max(0) as f64 / 86_400.0;
+        let decay = 0.5_f64.powf(age_days / PICK_HALF_LIFE_DAYS);
+        *weights.entry(pick.filepath.clone()).or_default() += pick.pick_count as f64 * relation * decay;
+    }
+    weights
+        .into_iter()
+        .map(|(filepath, weight)| (filepath, PERSONAL_BOOST_MAX * weight / (weight + 1.0)))
+        .collect()
+}
+
+/// Add personalization boosts to matching results. A boosted result never
+/// climbs past an exact filename match for a different file: its boost is
+/// capped to stay just below the weakest exact match.
+fn apply_personal_boosts(results: &mut [SearchResult], boosts: &HashMap<String, f64>) {
+    apply_boosts(results, boosts, |breakdown, boost| breakdown.personal_boost = boost);
+}
+
+/// Upper bound of the context boost, approached as launches from the same
+/// app add up. Well below [`PERSONAL_BOOST_MAX`]: the app in front says
+/// less about what's wanted than the query does.
+const CONTEXT_BOOST_MAX: f64 = 60.0;
+/// Days after which a launch from a context counts half as much.
+const CONTEXT_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// Per-filepath boost from launches recorded for the current context app,
+/// each decaying with age.
+fn context_boosts(picks: &[ContextPick], now: i64) -> HashMap<String, f64> {
+    picks
+        .iter()
+        .map(|pick| {
+            let age_days = (now - pick.last_used).max(0) as f64 / 86_400.0;
+            let weight = pick.pick_count as f64 * 0.5_f64.powf(age_days / CONTEXT_HALF_LIFE_DAYS);
+            (pick.filepath.clone(), CONTEXT_BOOST_MAX * weight / (weight + 1.0))
+        })
+        .collect()
+}
+
+/// Add context boosts to matching results, capped like
+/// [`apply_personal_boosts`].
+fn apply_context_boosts(results: &mut [SearchResult], boosts: &HashMap<String, f64>) {
+    apply_boosts(results, boosts, |breakdown, boost| breakdown.context_boost = boost);
+}
+
+/// Add each result's boost from `boosts` to its score, keeping it below the
+/// weakest exact match for another file, and note it in the breakdown with
+/// `record`.
+fn apply_boosts(
+    results: &mut [SearchResult],
+    boosts: &HashMap<String, f64>,
+    record: impl Fn(&mut ScoreBreakdown, f64),
+) {
+    if boosts.is_empty() {
+        return;
+    }
+    let exact_floor = results
+        .iter()
+        .filter(|r| r.match_type == "exact")
+        .map(|r| r.score)
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    for result in results.iter_mut() {
+        let Some(&boost) = boosts.get(&result.filepath) else {
+            continue;
+        };
+        let boost = match exact_floor {
+            Some(floor) if result.match_type != "exact" => boost.min(floor - 1.0 - result.score).max(0.0),
+            _ => boost,
+        };
+        result.score += boost;
+        if let Some(breakdown) = result.score_breakdown.as_mut() {
+            record(breakdown, boost);
+        }
+    }
+}
+
+/// Heuristic identity of a result's underlying file until shortcut targets
+/// are resolved: the same name, size and modification time. Folders and
+/// entries without metadata never merge, since the heuristic can't tell
+/// them apart.
+fn duplicate_key(result: &SearchResult) -> Option<(String, i64, i64)> {
+    if result.id < 0 || result.file_type == "folder" || (result.file_size == 0 && result.modified_at == 0) {
+        return None;
+    }
+    Some((fold_text(&result.filename), result.file_size, result.modified_at))
+}
+
+/// Collapse results that point at the same file (e.g. Desktop and Start Menu
+/// copies of one shortcut) into the best-scored one, listing the other paths
+/// in `duplicates`. On a score tie a pinned copy wins, then the one the user
+/// has launched more. `results` must already be sorted by score, descending.
+fn dedupe_results(results: Vec<SearchResult>, pinned: &HashSet<String>) -> Vec<SearchResult> {
+    let mut deduped: Vec<SearchResult> = Vec::with_capacity(results.len());
+    let mut groups: HashMap<(String, i64, i64), usize> = HashMap::new();
+
+    for result in results {
+        let Some(key) = duplicate_key(&result) else {
+            deduped.push(result);
+            continue;
+        };
+        match groups.get(&key) {
+            Some(&index) => {
+                let kept = &mut deduped[index];
+                let rank = |r: &SearchResult| (pinned.contains(&r.filepath), r.click_count);
+                if result.score == kept.score && rank(&result) > rank(kept) {
+                    let mut result = result;
+                    result.duplicates = std::mem::take(&mut kept.duplicates);
+                    result.duplicates.push(kept.filepath.clone());
+                    *kept = result;
+                } else {
+                    kept.duplicates.push(result.filepath);
+                }
+            }
+            None => {
+                groups.insert(key, deduped.len());
+                deduped.push(result);
+            }
+        }
+    }
+    deduped
+}
+
+/// Fuzzy-match every snapshot entry not already in `seen_ids` against the
+/// query, in parallel. Each rayon worker gets its own matcher; results keep
+/// snapshot order, so the final sort stays deterministic.
+///
+/// Work is split into chunks of [`CANCEL_CHECK_INTERVAL`] entries and
+/// `is_superseded` is checked before each one, so a stale search stops early.
+/// Skim only runs on entries whose [`BigramSignature`] the query admits.
+fn fuzzy_matches(
+    entries: &[CompactEntry],
+    query: &FuzzyQuery,
+    filters: &QueryFilters,
+    seen_ids: &HashSet<i64>,
+    options: &SearchOptions,
+    pool: Option<&CandidatePool>,
+    is_superseded: &(dyn Fn() -> bool + Sync),
+) -> Vec<SearchResult> {
+    entries
+        .par_chunks(CANCEL_CHECK_INTERVAL)
+        .enumerate()
+        .map_init(SkimMatcherV2::default, |matcher, (chunk_index, chunk)| {
+            if is_superseded() {
+                return Vec::new();
+            }
+            let first = chunk_index * CANCEL_CHECK_INTERVAL;
+            chunk
+                .iter()
+                .enumerate()
+                .filter_map(|(i, entry)| {
+                    // Entries outside the pool can't match skim, and ones the
+                    // pre-filter turns down can't match it well; only the typo
+                    // pass may find them
+                    let skim = pool.is_none_or(|pool| pool.contains(first + i))
+                        && query.bigrams.as_ref().is_none_or(|bigrams| bigrams.admits(entry.bigrams));
+                    fuzzy_match_entry(matcher, entry, query, filters, seen_ids, options, skim)
+                })
+                .collect::<Vec<_>>()
+        })
+        .flatten()
+        .collect()
+}
+
+/// Folders above the filename that deep path search matches against.
+const DEEP_PATH_FOLDERS: usize = 2;
+
+/// Whether the query's text names a path ("proj23/invoice"), which turns on
+/// deep path search for this query.
+fn is_path_query(text: &str) -> bool {
+    text.contains(['/', '\\'])
+}
+
+/// The folded query as deep path search matches it: separators become the
+/// spaces that join folder names in [`deep_path_text`].
+fn deep_path_query(query_folded: &str) -> String {
+    query_folded
+        .split(|c: char| c == '/' || c == '\\' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The text deep path search matches an entry against: the last
+/// [`DEEP_PATH_FOLDERS`] folder names of `parent` and the filename, joined by
+/// spaces ("clients proj23 invoice.pdf"). Also returns where that tail starts
+/// in `parent` and how many chars of the text it covers, in chars.
+fn deep_path_text(parent: &str, filename: &str) -> (String, usize, usize) {
+    let chars: Vec<char> = parent.chars().collect();
+    let mut start = chars.len();
+    let mut folders = 0;
+    while start > 0 {
+        if matches!(chars[start - 1], '\\' | '/') {
+            folders += 1;
+            if folders == DEEP_PATH_FOLDERS {
+                break;
+            }
+        }
+        start -= 1;
+    }
+    let tail = &chars[start..];
+    let mut text: String = tail.iter().map(|&c| if c == '\\' || c == '/' { ' ' } else { c }).collect();
+    text.push(' ');
+    text.push_str(filename);
+    (text, start, tail.len())
+}
+
+/// Deep-path-match every snapshot entry not already in `seen_ids`, in
+/// parallel and with the same cancellation checks as [`fuzzy_matches`].
+fn deep_path_matches(
+    entries: &[CompactEntry],
+    query: &str,
+    filters: &QueryFilters,
+    seen_ids: &HashSet<i64>,
+    options: &SearchOptions,
+    pool: Option<&CandidatePool>,
+    is_superseded: &(dyn Fn() -> bool + Sync),
+) -> Vec<SearchResult> {
+    let query_chars = query.chars().count();
+    entries
+        .par_chunks(CANCEL_CHECK_INTERVAL)
+        .enumerate()
+        .map_init(SkimMatcherV2::default, |matcher, (chunk_index, chunk)| {
+            if is_superseded() {
+                return Vec::new();
+            }
+            let first = chunk_index * CANCEL_CHECK_INTERVAL;
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| pool.is_none_or(|pool| pool.contains(first + i)))
+                .filter_map(|(_, entry)| {
+                    deep_path_match_entry(matcher, entry, query, query_chars, filters, seen_ids, options)
+                })
+                .collect::<Vec<_>>()
+        })
+        .flatten()
+        .collect()
+}
+
+/// Fuzzy-match a snapshot entry's folders and filename together, scored in
+/// [`FUZZY_DEEP_PATH_BAND`]. Matched chars are split between
+/// `matched_indices` (filename) and `path_indices` (`parent_dir`).
+fn deep_path_match_entry(
+    matcher: &SkimMatcherV2,
+    entry: &CompactEntry,
+    query: &str,
+    query_chars: usize,
+    filters: &QueryFilters,
+    seen_ids: &HashSet<i64>,
+    options: &SearchOptions,
+) -> Option<SearchResult> {
+    // Only filesystem entries have folders to match
+    if seen_ids.contains(&entry.id)
+        || matches!(entry.file_type, "url" | "command" | "bookmark")
+        || !filters.matches(&entry.filename, &entry.filepath, entry.file_type)
+        || !filters.matches_metadata(entry.file_size, entry.modified_at)
+        || !filters.matches_required_terms(&entry.filename, &entry.display_name)
+        || (options.hide_empty_files && is_empty_file(entry.file_type, entry.file_size))
+    {
+        return None;
+    }
+
+    let parent = parent_dir(&entry.filepath);
+    let (text, tail_start, tail_chars) = deep_path_text(&parent, &entry.filename);
+    let (text_folded, char_map) = fold_with_map(&text);
+    let match_score = matcher
+        .fuzzy_match(&text_folded, query)
+        .filter(|score| *score > 0)
+        .map(|score| normalize_fuzzy(score, query_chars, FUZZY_DEEP_PATH_BAND))?;
+    if match_score < options.min_score {
+        return None;
+    }
+
+    let (_, indices) = matcher.fuzzy_indices(&text_folded, query)?;
+    let mut path_indices = Vec::new();
+    let mut matched_indices = Vec::new();
+    for index in map_indices(indices, &char_map) {
+        match index.cmp(&tail_chars) {
+            std::cmp::Ordering::Less => path_indices.push(tail_start + index),
+            // The space joining the folders to the filename
+            std::cmp::Ordering::Equal => {}
+            // The filename, which a display name hides
+            std::cmp::Ordering::Greater if entry.display_name.is_empty() => {
+                matched_indices.push(index - tail_chars - 1)
+            }
+            std::cmp::Ordering::Greater => {}
+        }
+    }
+
+    let usage = usage_boost(entry.click_count, entry.last_accessed, entry.modified_at);
+    let breakdown = ScoreBreakdown {
+        branch: Cow::Borrowed("deep_path"),
+        match_score,
+        type_boost: entry_type_boost(entry.file_type, entry.child_count),
+        size_adjustment: size_adjustment(entry.file_type, entry.file_size, options.large_file_bytes),
+        click_boost: usage.click,
+        recency_boost: usage.recency,
+        ..Default::default()
+    };
+
+    Some(SearchResult {
+        id: entry.id,
+        filename: entry.filename.to_string(),
+        display_name: shown_display_name(&entry.display_name),
+        filepath: entry.filepath.to_string(),
+        extension: Path::new(&*entry.filename)
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_size: entry.file_size,
+        modified_at: entry.modified_at,
+        file_type: entry.file_type.to_string(),
+        click_count: entry.click_count,
+        last_accessed: entry.last_accessed,
+        parent_dir: parent,
+        file_size_display: format_size(entry.file_size),
+        modified_display: format_relative_time(entry.modified_at, chrono::Utc::now().timestamp()),
+        child_count: entry.child_count,
+        score: breakdown.total(),
+        match_type: breakdown.match_type().to_string(),
+        matched_indices,
+        path_indices,
+        score_breakdown: options.explain.then_some(breakdown),
+        duplicates: Vec::new(),
+        section: None,
+        group: None,
+        is_running: false,
+        running_pids: Vec::new(),
+    })
+}
+
+/// Number of snapshot entries fuzzy-matched between cancellation checks.
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// The folded query plus what the fuzzy stage precomputes from it once.
+struct FuzzyQuery<'a> {
+    folded: &'a str,
+    chars: usize,
+    typo: Option<TypoQuery>,
+    /// `None` when the pre-filter is off (see [`BIGRAM_FILTER_FLOOR`]).
+    bigrams: Option<BigramQuery>,
+}
+
+impl<'a> FuzzyQuery<'a> {
+    fn new(folded: &'a str, min_score: f64) -> Self {
+        FuzzyQuery {
+            folded,
+            chars: folded.chars().count(),
+            typo: TypoQuery::new(folded),
+            bigrams: (min_score >= BIGRAM_FILTER_FLOOR).then(|| BigramQuery::new(folded)).flatten(),
+        }
+    }
+}
+
+/// Share of the query's bigrams an entry's names must have for skim to run
+/// on it.
+const BIGRAM_MIN_SHARE: f64 = 0.6;
+/// The pre-filter only runs when results under this score are dropped
+/// anyway: a scattered skim match can share no bigram with the query, but
+/// scores below it.
+const BIGRAM_FILTER_FLOOR: f64 = DEFAULT_MIN_SCORE;
+/// Name chars up to this far apart make a pair in [`BigramSignature`].
+const MAX_BIGRAM_GAP: usize = 3;
+
+/// Which char pairs occur in an entry's folded names, one hashed bit of 256
+/// per pair, so the fuzzy stage can skip skim on names that share too few
+/// with the query (see [`BigramQuery`]). 32 bytes per snapshot entry.
+/// Besides adjacent chars it has chars up to [`MAX_BIGRAM_GAP`] apart and
+/// consecutive word initials, which is how skim's good matches skip chars:
+/// "stm" for "steam", "vsc" for "visual studio code". Whitespace is left
+/// out, as in the query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BigramSignature([u64; 4]);
+
+impl BigramSignature {
+    pub fn of(names_folded: &[&str]) -> Self {
+        let mut signature = BigramSignature::default();
+        for name in names_folded {
+            let chars: Vec<char> = name.chars().filter(|c| !c.is_whitespace()).collect();
+            for gap in 1..=MAX_BIGRAM_GAP {
+                for pair in chars.windows(gap + 1) {
+                    signature.insert(bigram_bit(pair[0], pair[gap]));
+                }
+            }
+            let initials: Vec<char> = word_initials(name).collect();
+            for pair in initials.windows(2) {
+                signature.insert(bigram_bit(pair[0], pair[1]));
+            }
+        }
+        signature
+    }
+
+    fn insert(&mut self, bit: u32) {
+        self.0[bit as usize / 64] |= 1 << (bit % 64);
+    }
+
+    fn contains(self, bit: u32) -> bool {
+        self.0[bit as usize / 64] & (1 << (bit % 64)) != 0
+    }
+}
+
+/// The first char of each word of `name`, words being split at anything
+/// that isn't alphanumeric.
+fn word_initials(name: &str) -> impl Iterator<Item = char> + '_ {
+    let mut previous: Option<char> = None;
+    name.chars().filter(move |&c| {
+        let initial = c.is_alphanumeric() && previous.is_none_or(|p| !p.is_alphanumeric());
+        previous = Some(c);
+        initial
+    })
+}
+
+/// The bit of [`BigramSignature`] for the pair `a`, `b`.
+fn bigram_bit(a: char, b: char) -> u32 {
+    let pair = (u64::from(a as u32) << 32) | u64::from(b as u32);
+    (pair.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 56) as u32
+}
+
+/// The query's adjacent char pairs, checked against each entry's
+/// [`BigramSignature`] before skim runs on it.
+struct BigramQuery {
+    bits: Vec<u32>,
+    /// How many of `bits` a signature needs.
+    required: usize,
+}
+
+impl BigramQuery {
+    /// `None` for a query with fewer than two chars besides whitespace.
+    fn new(query_folded: &str) -> Option<Self> {
+        let chars: Vec<char> = query_folded.chars().filter(|c| !c.is_whitespace()).collect();
+        let bits: Vec<u32> = chars.windows(2).map(|pair| bigram_bit(pair[0], pair[1])).collect();
+        if bits.is_empty() {
+            return None;
+        }
+        let required = (bits.len() as f64 * BIGRAM_MIN_SHARE).ceil() as usize;
+        Some(BigramQuery { bits, required })
+    }
+
+    fn admits(&self, signature: BigramSignature) -> bool {
+        self.bits.iter().filter(|&&bit| signature.contains(bit)).count() >= self.required
+    }
+}
+
+/// Fuzzy-match a single snapshot entry, returning a scored result on a hit.
+/// Names skim can't match (or matches poorly) get a typo-tolerant pass
+/// against their words when the query is eligible (see [`TypoQuery`]).
+/// Without `skim` only the typo pass runs. Both the filename and the display
+/// name are tried, like in [`score_entry`].
+fn fuzzy_match_entry(
+    matcher: &SkimMatcherV2,
+    entry: &CompactEntry,
+    query: &FuzzyQuery,
+    filters: &QueryFilters,
+    seen_ids: &HashSet<i64>,
+    options: &SearchOptions,
+    skim: bool,
+) -> Option<SearchResult> {
+    if !skim && query.typo.is_none() {
+        return None;
+    }
+    if seen_ids.contains(&entry.id)
+        || !filters.matches(&entry.filename, &entry.filepath, entry.file_type)
+        || !filters.matches_metadata(entry.file_size, entry.modified_at)
+        || !filters.matches_required_terms(&entry.filename, &entry.display_name)
+        || (options.hide_empty_files && is_empty_file(entry.file_type, entry.file_size))
+    {
+        return None;
+    }
+
+    // Fuzzy match against a pre-folded name, falling back to a typo match
+    let match_name = |name_folded: &str| {
+        let fuzzy_score = skim
+            .then(|| matcher.fuzzy_match(name_folded, query.folded))
+            .flatten()
+            .filter(|score| *score > 0)
+            .map(|score| normalize_fuzzy(score, query.chars, FUZZY_NAME_BAND))
+            .unwrap_or(0.0);
+        let typo = query
+            .typo
+            .as_ref()
+            .and_then(|typo| typo.best_word_match(name_folded))
+            .filter(|word| word.score() > fuzzy_score);
+        (typo.as_ref().map_or(fuzzy_score, TypoMatch::score), typo)
+    };
+    let has_display_name = !entry.display_name.is_empty();
+    let (mut match_score, mut typo) = match_name(&entry.filename_folded);
+    let mut on_display_name = false;
+    if has_display_name {
+        let (display_score, display_typo) = match_name(&entry.display_name_folded);
+        if display_score > 0.0 && display_score >= match_score {
+            (match_score, typo, on_display_name) = (display_score, display_typo, true);
+        }
+    }
+    if match_score <= 0.0 || match_score < options.min_score {
+        return None;
+    }
+
+    let matched_name: &str = if on_display_name { &entry.display_name } else { &entry.filename };
+    let (name_folded, char_map) = fold_with_map(matched_name);
+    let indices = if has_display_name && !on_display_name {
+        // A filename match has nothing to highlight in a display name
+        Vec::new()
+    } else {
+        match &typo {
+            Some(word) => map_indices(word.start..word.start + word.len, &char_map),
+            None => matcher
+                .fuzzy_indices(&name_folded, query.folded)
+                .map(|(_, indices)| map_indices(indices, &char_map))
+                .unwrap_or_default(),
+        }
+    };
+    let branch = if typo.is_some() { "typo" } else { "fuzzy" };
+
+    let usage = usage_boost(entry.click_count, entry.last_accessed, entry.modified_at);
+    let breakdown = ScoreBreakdown {
+        branch: Cow::Borrowed(branch),
+        match_score,
+        position_adjustment: 0.0,
+        length_bonus: name_length_bonus(query.chars, matched_name.chars().count()),
+        extension_adjustment: extension_adjustment(
+            &entry.filename_folded,
+            final_extension(&entry.filename_folded),
+            query.folded,
+        ),
+        type_boost: entry_type_boost(entry.file_type, entry.child_count),
+        size_adjustment: size_adjustment(entry.file_type, entry.file_size, options.large_file_bytes),
+        click_boost: usage.click,
+        recency_boost: usage.recency,
+        personal_boost: 0.0,
+        context_boost: 0.0,
+    };
+
+    Some(SearchResult {
+        id: entry.id,
+        filename: entry.filename.to_string(),
+        display_name: shown_display_name(&entry.display_name),
+        filepath: entry.filepath.to_string(),
+        extension: Path::new(&*entry.filename)
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_size: entry.file_size,
+        modified_at: entry.modified_at,
+        file_type: entry.file_type.to_string(),
+        click_count: entry.click_count,
+        last_accessed: entry.last_accessed,
+        parent_dir: result_parent_dir(&entry.filepath, entry.file_type),
+        file_size_display: format_size(entry.file_size),
+        modified_display: format_relative_time(entry.modified_at, chrono::Utc::now().timestamp()),
+        child_count: entry.child_count,
+        score: breakdown.total(),
+        match_type: branch.to_string(),
+        matched_indices: indices,
+        path_indices: Vec::new(),
+        score_breakdown: options.explain.then_some(breakdown),
+        duplicates: Vec::new(),
+        section: None,
+        group: None,
+        is_running: false,
+        running_pids: Vec::new(),
+    })
+}
+
+// Base scores shared by every match strategy, so SQL-backed and fuzzy
+// candidates land on one 0–1000 scale before boosts are added.
+const SCORE_EXACT: f64 = 1000.0;
+const SCORE_EXACT_STEM: f64 = 950.0;
+const SCORE_PREFIX: f64 = 800.0;
+const SCORE_SUBSTRING: f64 = 600.0;
+const SCORE_PATH: f64 = 300.0;
+/// A strong fuzzy filename hit outranks a path substring match, a scattered one doesn't.
+const FUZZY_NAME_BAND: (f64, f64) = (100.0, 550.0);
+/// Fuzzy path hits always rank below a path substring match. The top of the
+/// band clears [`DEFAULT_MIN_SCORE`], so a near-consecutive hit (one that
+/// only skips a folder separator) survives the default cutoff.
+const FUZZY_PATH_BAND: (f64, f64) = (150.0, 295.0);
+/// Deep path hits rank below an equally good fuzzy filename hit.
+const FUZZY_DEEP_PATH_BAND: (f64, f64) = (150.0, 450.0);
+
+/// Base score of a typo match one edit away; each further edit costs
+/// [`TYPO_EDIT_PENALTY`]. Both land between the fuzzy band and substring matches.
+const SCORE_TYPO: f64 = 590.0;
+const TYPO_EDIT_PENALTY: f64 = 20.0;
+
+/// Query lengths (in chars) eligible for the typo pass.
+const TYPO_QUERY_CHARS: std::ops::RangeInclusive<usize> = 3..=12;
+
+/// A single-word query prepared for typo-tolerant matching: "chorme" finds
+/// "Chrome" through a transposition, "notpad" finds "Notepad" through a
+/// missing letter.
+///
+/// Candidate words are compared with the optimal-string-alignment variant of
+/// Damerau–Levenshtein distance (a transposition counts as one edit), allowing
+/// one edit for queries up to 6 chars and two for longer ones. Words whose
+/// length is out of range, or whose set of characters differs from the
+/// query's by more than an edit could explain, are skipped before computing
+/// the distance, which keeps the pass cheap over a large snapshot.
+struct TypoQuery {
+    chars: Vec<char>,
+    char_set: u64,
+    max_distance: usize,
+}
+
+/// A filename word within typo distance of the query. `start`/`len` are char
+/// positions in the folded filename.
+struct TypoMatch {
+    distance: usize,
+    start: usize,
+    len: usize,
+}
+
+impl TypoMatch {
+    fn score(&self) -> f64 {
+        SCORE_TYPO - TYPO_EDIT_PENALTY * self.distance.saturating_sub(1) as f64
+    }
+}
+
+impl TypoQuery {
+    /// Prepare `query_folded`, or None if it isn't eligible (wrong length or
+    /// more than one word).
+    fn new(query_folded: &str) -> Option<Self> {
+        let chars: Vec<char> = query_folded.chars().collect();
+        if !TYPO_QUERY_CHARS.contains(&chars.len()) || chars.iter().any(|c| !c.is_alphanumeric()) {
+            return None;
+        }
+        let max_distance = if chars.len() <= 6 { 1 } else { 2 };
+        let char_set = chars.iter().fold(0, |set, &c| set | char_bit(c));
+        Some(TypoQuery {
+            chars,
+            char_set,
+            max_distance,
+        })
+    }
+
+    /// The closest word in the stem of `filename_folded`, if any is within
+    /// `max_distance` edits.
+    fn best_word_match(&self, filename_folded: &str) -> Option<TypoMatch> {
+        let stem = filename_folded.rsplit_once('.').map_or(filename_folded, |(stem, _)| stem);
+        let min_len = self.chars.len().saturating_sub(self.max_distance).max(*TYPO_QUERY_CHARS.start());
+        let max_len = self.chars.len() + self.max_distance;
+
+        let mut best: Option<TypoMatch> = None;
+        // Current word: its first char position, length, characters and character set
+        let (mut word_start, mut word_len, mut word_set) = (0, 0, 0u64);
+        let mut word = ['\0'; OSA_MAX_CHARS];
+        for (position, c) in stem.chars().chain(std::iter::once(' ')).enumerate() {
+            if c.is_alphanumeric() {
+                if word_len == 0 {
+                    (word_start, word_set) = (position, 0);
+                }
+                if let Some(slot) = word.get_mut(word_len) {
+                    *slot = c;
+                }
+                word_len += 1;
+                word_set |= char_bit(c);
+                continue;
+            }
+            if (min_len..=max_len).contains(&word_len) && self.plausible_char_set(word_set) {
+                let limit = best.as_ref().map_or(self.max_distance, |b| b.distance);
+                if let Some(distance) = osa_distance(&self.chars, &word[..word_len], limit) {
+                    if best.as_ref().is_none_or(|b| distance < b.distance) {
+                        best = Some(TypoMatch {
+                            distance,
+                            start: word_start,
+                            len: word_len,
+                        });
+                    }
+                }
+            }
+            word_len = 0;
+        }
+        best
+    }
+
+    /// Each edit adds at most one character the query lacks and drops at most
+    /// one it has, so sets differing by more than `max_distance` either way
+    /// can't be within range.
+    fn plausible_char_set(&self, word_set: u64) -> bool {
+        let extra = (word_set & !self.char_set).count_ones() as usize;
+        let missing = (self.char_set & !word_set).count_ones() as usize;
+        extra <= self.max_distance && missing <= self.max_distance
+    }
+}
+
+/// Bit for `c` in a 64-bit character set. Letters and digits get their own
+/// bit; anything else shares one by code point, which only weakens the
+/// filter, never wrongly rejects.
+fn char_bit(c: char) -> u64 {
+    let index = match c {
+        'a'..='z' => c as u32 - 'a' as u32,
+        '0'..='9' => 26 + c as u32 - '0' as u32,
+        _ => 36 + c as u32 % 28,
+    };
+    1 << index
+}
+
+/// Longest word [`osa_distance`] is ever asked to compare, plus one.
+const OSA_MAX_CHARS: usize = 15;
+
+/// Optimal-string-alignment distance between `a` and `b`, or None once it
+/// is certain to exceed `max`. `b` must be shorter than [`OSA_MAX_CHARS`].
+fn osa_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max || b.len() >= OSA_MAX_CHARS {
+        return None;
+    }
+    let mut before_previous = [0usize; OSA_MAX_CHARS];
+    let mut previous = [0usize; OSA_MAX_CHARS];
+    let mut current = [0usize; OSA_MAX_CHARS];
+    for (j, cell) in previous.iter_mut().enumerate().take(b.len() + 1) {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = current[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut value = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                value = value.min(before_previous[j - 2] + 1);
+            }
+            current[j] = value;
+            row_min = row_min.min(value);
+        }
+        if row_min > max {
+            return None;
+        }
+        before_previous = previous;
+        previous = current;
+    }
+    let distance = previous[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+/// Map a raw skim score into `band`, relative to the best score possible for a
+/// query of this length (a perfect consecutive match scores about 20 per char + 11).
+/// Quality is squared so scattered matches fall off quickly.
+fn normalize_fuzzy(skim_score: i64, query_chars: usize, band: (f64, f64)) -> f64 {
+    let ideal = (query_chars * 20 + 11) as f64;
+    let quality = (skim_score as f64 / ideal).clamp(0.0, 1.0);
+    band.0 + (band.1 - band.0) * quality * quality
+}
+
+/// How the query matched one of an entry's names, see [`score_entry`].
+struct NameMatch {
+    branch: &'static str,
+    match_score: f64,
+    position_adjustment: f64,
+    /// Char positions in the original name.
+    indices: Vec<usize>,
+    name_chars: usize,
+}
+
+impl NameMatch {
+    fn score(&self) -> f64 {
+        self.match_score + self.position_adjustment
+    }
+}
+
+/// Match `query_folded` literally against `name`: exact, exact without the
+/// final extension (only if `has_extension`), prefix or substring.
+fn literal_name_match(name: &str, query_folded: &str, has_extension: bool) -> Option<NameMatch> {
+    let (name_folded, char_map) = fold_with_map(name);
+    let query_chars = query_folded.chars().count();
+    let matched = |branch, match_score, position_adjustment, indices| NameMatch {
+        branch,
+        match_score,
+        position_adjustment,
+        indices,
+        name_chars: name.chars().count(),
+    };
+    // Exact match (highest priority)
+    if name_folded == *query_folded {
+        Some(matched("exact", SCORE_EXACT, 0.0, (0..name.chars().count()).collect()))
+    }
+    // Exact match without the final extension ("my.notes.v2" for "my.notes.v2.txt")
+    else if has_extension && name_folded.rsplit_once('.').is_some_and(|(stem, _)| stem == query_folded) {
+        Some(matched("exact_stem", SCORE_EXACT_STEM, 0.0, map_indices(0..query_chars, &char_map)))
+    }
+    // Prefix match
+    else if name_folded.starts_with(query_folded) {
+        Some(matched("prefix", SCORE_PREFIX, 0.0, map_indices(0..query_chars, &char_map)))
+    }
+    // Substring match, preferring an occurrence at a word boundary
+    else {
+        best_substring(&name_folded, query_folded).map(|(adjustment, start)| {
+            matched("substring", SCORE_SUBSTRING, adjustment, map_indices(start..start + query_chars, &char_map))
+        })
+    }
+}
+
+/// Fuzzy match `query_folded` against `name`.
+fn fuzzy_name_match(name: &str, query_folded: &str, matcher: &SkimMatcherV2) -> Option<NameMatch> {
+    let (name_folded, char_map) = fold_with_map(name);
+    let (skim_score, indices) = matcher.fuzzy_indices(&name_folded, query_folded)?;
+    Some(NameMatch {
+        branch: "fuzzy",
+        match_score: normalize_fuzzy(skim_score, query_folded.chars().count(), FUZZY_NAME_BAND),
+        position_adjustment: 0.0,
+        indices: map_indices(indices, &char_map),
+        name_chars: name.chars().count(),
+    })
+}
+
+/// The better of a match on the filename and one on the display name,
+/// preferring the display name on a tie since that's the name shown. The
+/// flag tells whether the display name matched.
+fn best_name_match(filename: Option<NameMatch>, display: Option<NameMatch>) -> Option<(NameMatch, bool)> {
+    match (filename, display) {
+        (Some(f), Some(d)) if f.score() > d.score() => Some((f, false)),
+        (_, Some(d)) => Some((d, true)),
+        (f, None) => f.map(|f| (f, false)),
+    }
+}
+
+/// An entry's display name as a result shows it: `None` when it has none.
+fn shown_display_name(display_name: &str) -> Option<String> {
+    (!display_name.is_empty()).then(|| display_name.to_string())
+}
+
+/// Compute a composite score for a FileEntry based on how well it matches the query.
+/// The query is matched against the filename and the display name, if any,
+/// and scores the same on either. Matched indices are char positions in the
+/// name shown for the entry: its display name if it has one, else its
+/// filename. `large_file_bytes` is [`SearchOptions::large_file_bytes`].
+fn score_entry(
+    entry: &FileEntry,
+    query_folded: &str,
+    matcher: &SkimMatcherV2,
+    large_file_bytes: i64,
+) -> (ScoreBreakdown, Vec<usize>) {
+    let filename_folded = fold_text(&entry.filename);
+    let filepath_folded = fold_text(&entry.filepath);
+    let query_chars = query_folded.chars().count();
+    let display_name = Some(entry.display_name.as_str()).filter(|name| !name.is_empty());
+
+    let mut breakdown = ScoreBreakdown {
+        branch: Cow::Borrowed("none"),
+        ..Default::default()
+    };
+    let mut name_match: Option<(NameMatch, bool)> = None;
+
+    // Filter-only query: every candidate matched the filters equally,
+    // so only the boosts below decide the order
+    if query_folded.is_empty() {
+        breakdown.branch = Cow::Borrowed("filter");
+    }
+    // Exact, prefix or substring match on either name
+    else if let Some(matched) = best_name_match(
+        literal_name_match(&entry.filename, query_folded, true),
+        display_name.and_then(|name| literal_name_match(name, query_folded, false)),
+    ) {
+        name_match = Some(matched);
+    }
+    // Path match (e.g., searching "docs/proj" matching path)
+    else if filepath_folded.contains(query_folded) {
+        breakdown.branch = Cow::Borrowed("path");
+        breakdown.match_score = SCORE_PATH;
+    }
+    // Fuzzy match on either name
+    else if let Some(matched) = best_name_match(
+        fuzzy_name_match(&entry.filename, query_folded, matcher),
+        display_name.and_then(|name| fuzzy_name_match(name, query_folded, matcher)),
+    ) {
+        name_match = Some(matched);
+    }
+    // Fuzzy match on filepath (indices point into the path, not the filename,
+    // so there is nothing to highlight)
+    else if let Some(fuzzy_result) = matcher.fuzzy_indices(&filepath_folded, query_folded) {
+        breakdown.branch = Cow::Borrowed("fuzzy_path");
+        breakdown.match_score = normalize_fuzzy(fuzzy_result.0, query_chars, FUZZY_PATH_BAND);
+    }
+
+    let mut matched_indices: Vec<usize> = Vec::new();
+    if let Some((matched, on_display_name)) = name_match {
+        breakdown.branch = Cow::Borrowed(matched.branch);
+        breakdown.match_score = matched.match_score;
+        breakdown.position_adjustment = matched.position_adjustment;
+        // Shorter names where the query covers more of the name feel like better hits
+        breakdown.length_bonus = name_length_bonus(query_chars, matched.name_chars);
+        // A filename match has nothing to highlight in a display name
+        if on_display_name || display_name.is_none() {
+            matched_indices = matched.indices;
+        }
+    }
+
+    breakdown.extension_adjustment =
+        extension_adjustment(&filename_folded, &entry.extension.to_lowercase(), query_folded);
+
+    // Apply boosts
+    let usage = usage_boost(entry.click_count, entry.last_accessed, entry.modified_at);
+    breakdown.type_boost = entry_type_boost(&entry.file_type, entry.child_count);
+    breakdown.size_adjustment = size_adjustment(&entry.file_type, entry.file_size, large_file_bytes);
+    breakdown.click_boost = usage.click;
+    breakdown.recency_boost = usage.recency;
+
+    (breakdown, matched_indices)
+}
+
+/// One element of a [`WildcardPattern`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GlobToken {
+    Literal(char),
+    /// `?`: exactly one char.
+    AnyChar,
+    /// `*`: any run of chars, including none.
+    AnyRun,
+}
+
+/// A folded query containing `*` or `?` wildcards, matched anywhere in a
+/// folded filename. Windows forbids both chars in filenames, so they are
+/// always wildcards and there is nothing to escape.
+#[derive(Debug, Clone, PartialEq)]
+struct WildcardPattern {
+    tokens: Vec<GlobToken>,
+}
+
+impl WildcardPattern {
+    /// Parse `query_folded`, or `None` when it has no wildcards.
+    fn parse(query_folded: &str) -> Option<Self> {
+        if !query_folded.contains(['*', '?']) {
+            return None;
+        }
+        let tokens = query_folded
+            .chars()
+            .map(|c| match c {
+                '*' => GlobToken::AnyRun,
+                '?' => GlobToken::AnyChar,
+                _ => GlobToken::Literal(c),
+            })
+            .collect();
+        Some(Self { tokens })
+    }
+
+    fn has_literals(&self) -> bool {
+        self.tokens.iter().any(|t| matches!(t, GlobToken::Literal(_)))
+    }
+
+    /// Find the leftmost match in `text`, each `*` taking as few chars as
+    /// possible. Returns where it starts and the positions matched by
+    /// literal chars, which are what gets highlighted.
+    fn find(&self, text: &[char]) -> Option<(usize, Vec<usize>)> {
+        (0..=text.len()).find_map(|start| self.match_at(text, start).map(|indices| (start, indices)))
+    }
+
+    /// Match the pattern against `text` from `start`, with anything allowed
+    /// after it. Backtracks only to the latest `*`, which is enough for glob
+    /// patterns and keeps this linear in practice.
+    fn match_at(&self, text: &[char], start: usize) -> Option<Vec<usize>> {
+        let mut indices = Vec::new();
+        let (mut p, mut t) = (0, start);
+        // Token after the latest `*`, the text position it resumes from, and
+        // how many indices were matched before it
+        let mut backtrack: Option<(usize, usize, usize)> = None;
+        while p < self.tokens.len() {
+            match self.tokens[p] {
+                GlobToken::AnyRun => {
+                    backtrack = Some((p + 1, t, indices.len()));
+                    p += 1;
+                    continue;
+                }
+                GlobToken::AnyChar if t < text.len() => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                GlobToken::Literal(c) if text.get(t) == Some(&c) => {
+                    indices.push(t);
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            // Let the latest `*` swallow one more char and retry from there
+            let (resume_p, resume_t, matched) = backtrack.filter(|&(_, resume_t, _)| resume_t < text.len())?;
+            backtrack = Some((resume_p, resume_t + 1, matched));
+            indices.truncate(matched);
+            p = resume_p;
+            t = resume_t + 1;
+        }
+        Some(indices)
+    }
+}
+
+/// Score `entry` against a wildcard query: prefix-level when the pattern
+/// matches from the start of the name, substring-level elsewhere. `None` when
+/// the pattern doesn't match the filename at all.
+fn score_wildcard_entry(
+    entry: &FileEntry,
+    pattern: &WildcardPattern,
+    query_folded: &str,
+    large_file_bytes: i64,
+) -> Option<(ScoreBreakdown, Vec<usize>)> {
+    let (filename_folded, char_map) = fold_with_map(&entry.filename);
+    let chars: Vec<char> = filename_folded.chars().collect();
+    let (start, indices) = pattern.find(&chars)?;
+    let anchored = start == 0 && pattern.tokens.first() != Some(&GlobToken::AnyRun);
+
+    let usage = usage_boost(entry.click_count, entry.last_accessed, entry.modified_at);
+    let breakdown = ScoreBreakdown {
+        branch: Cow::Borrowed("wildcard"),
+        match_score: if anchored { SCORE_PREFIX } else { SCORE_SUBSTRING },
+        length_bonus: name_length_bonus(indices.len(), entry.filename.chars().count()),
+        extension_adjustment: extension_adjustment(&filename_folded, &entry.extension.to_lowercase(), query_folded),
+        type_boost: entry_type_boost(&entry.file_type, entry.child_count),
+        size_adjustment: size_adjustment(&entry.file_type, entry.file_size, large_file_bytes),
+        click_boost: usage.click,
+        recency_boost: usage.recency,
+        ..Default::default()
+    };
+    // Wildcards only match filenames, which a display name hides
+    let indices = if entry.display_name.is_empty() { map_indices(indices, &char_map) } else { Vec::new() };
+    Some((breakdown, indices))
+}
+
+/// Bonus when a query like "report.pdf" names the entry's actual extension.
+const EXTENSION_MATCH_BONUS: f64 = 150.0;
+/// Penalty when the query's stem is in the name but the extension differs,
+/// e.g. a "report.pdf_old_versions" folder for "report.pdf".
+const EXTENSION_MISMATCH_PENALTY: f64 = 150.0;
+
+/// Score adjustment for a query ending in an extension (see [`extension_hint`]).
+/// `extension` is the entry's lowercase extension, without the dot.
+fn extension_adjustment(filename_folded: &str, extension: &str, query_folded: &str) -> f64 {
+    let Some((stem, wanted)) = extension_hint(query_folded) else {
+        return 0.0;
+    };
+    if extension == wanted {
+        EXTENSION_MATCH_BONUS
+    } else if filename_folded.contains(stem) {
+        -EXTENSION_MISMATCH_PENALTY
+    } else {
+        0.0
+    }
+}
+
+/// Text after the last dot of a folded filename, or "" if there is none.
+fn final_extension(filename_folded: &str) -> &str {
+    filename_folded.rsplit_once('.').map_or("", |(_, ext)| ext)
+}
+
+/// Bonus for a substring match that starts right after a separator.
+const WORD_BOUNDARY_BONUS: f64 = 100.0;
+/// Penalty per char of offset for a substring match inside a word, capped.
+const POSITION_PENALTY_PER_CHAR: f64 = 4.0;
+const MAX_POSITION_PENALTY: f64 = 80.0;
+/// Bonus when the query covers the whole filename, scaled down for longer names.
+const MAX_NAME_LENGTH_BONUS: f64 = 40.0;
+
+/// Find the best occurrence of `query_folded` inside `filename_folded`:
+/// matches at a word start (after space, `-`, `_` or `.`) earn a bonus, others
+/// lose a little per char of offset. Returns that adjustment and the match's char start.
+fn best_substring(filename_folded: &str, query_folded: &str) -> Option<(f64, usize)> {
+    filename_folded
+        .match_indices(query_folded)
+        .map(|(pos, _)| {
+            let start = filename_folded[..pos].chars().count();
+            let at_boundary = pos == 0
+                || filename_folded[..pos]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| matches!(c, ' ' | '-' | '_' | '.'));
+            let adjustment = if at_boundary {
+                WORD_BOUNDARY_BONUS
+            } else {
+                -(start as f64 * POSITION_PENALTY_PER_CHAR).min(MAX_POSITION_PENALTY)
+            };
+            (adjustment, start)
+        })
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// Bonus proportional to how much of the filename the query covers, so
+/// "Word.lnk" beats "Microsoft Word 2016 Tools Language Preferences.lnk" for "word".
+fn name_length_bonus(query_chars: usize, filename_chars: usize) -> f64 {
+    if filename_chars == 0 {
+        return 0.0;
+    }
+    MAX_NAME_LENGTH_BONUS * (query_chars as f64 / filename_chars as f64).min(1.0)
+}
+
+/// The `parent_dir` shown for a result; custom URL and command entries
+/// have none, and a bookmark shows its site.
+fn result_parent_dir(filepath: &str, file_type: &str) -> String {
+    match file_type {
+        "url" | "command" => String::new(),
+        "bookmark" => {
+            let rest = filepath.split_once("://").map_or(filepath, |(_, rest)| rest);
+            rest.split(['/', '?', '#']).next().unwrap_or_default().to_string()
+        }
+        _ => parent_dir(filepath),
+    }
+}
+
+/// The folder containing `filepath`, or "" for a bare name. Splits on both
+/// `\` and `/` so indexed Windows paths behave the same on every host;
+/// a drive root keeps its separator ("C:\").
+fn parent_dir(filepath: &str) -> String {
+    let Some(sep) = filepath.rfind(['\\', '/']) else {
+        return String::new();
+    };
+    let parent = &filepath[..sep];
+    if parent.is_empty() || parent.ends_with(':') {
+        filepath[..sep + 1].to_string()
+    } else {
+        parent.to_string()
+    }
+}
+
+/// Format a byte count like Explorer does: "512 B", "4.2 MB". Zero (folders,
+/// unknown sizes) gives "".
+pub fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    if bytes <= 0 {
+        return String::new();
+    }
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Describe a unix timestamp relative to `now`: "just now", "5 minutes ago",
+/// "yesterday", "3 weeks ago". Zero (unknown) gives "".
+pub fn format_relative_time(timestamp: i64, now: i64) -> String {
+    if timestamp <= 0 {
+        return String::new();
+    }
+    let seconds = (now - timestamp).max(0);
+    let (count, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (seconds / 60, "minute"),
+        3_600..=86_399 => (seconds / 3_600, "hour"),
+        86_400..=172_799 => return "yesterday".to_string(),
+        172_800..=1_209_599 => (seconds / 86_400, "day"),
+        1_209_600..=5_183_999 => (seconds / 604_800, "week"),
+        5_184_000..=31_535_999 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Fold `s` for case- and diacritic-insensitive matching: lowercase, NFKD
+/// decomposition with combining marks stripped, plus `ß` → "ss" and the
+/// Turkish dotless `ı` → "i" (dotted `İ` already folds to "i" via NFKD).
+pub fn fold_text(s: &str) -> String {
+    fold_with_map(s).0
+}
+
+/// Like [`fold_text`], also returning for each char of the result the index of
+/// the original char it came from, since folding can shrink or expand text.
+fn fold_with_map(s: &str) -> (String, Vec<usize>) {
+    let mut folded = String::with_capacity(s.len());
+    let mut map = Vec::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        for lc in c.to_lowercase() {
+            match lc {
+                'ß' => {
+                    folded.push_str("ss");
+                    map.extend([i, i]);
+                }
+                'ı' => {
+                    folded.push('i');
+                    map.push(i);
+                }
+                _ => decompose_compatible(lc, |d| {
+                    if !is_combining_mark(d) {
+                        folded.push(d);
+                        map.push(i);
+                    }
+                }),
+            }
+        }
+    }
+    (folded, map)
+}
+
+/// Translate char indices in a folded string back to char indices in the
+/// original, collapsing duplicates produced by expanded characters.
+fn map_indices(indices: impl IntoIterator<Item = usize>, map: &[usize]) -> Vec<usize> {
+    let mut mapped: Vec<usize> = indices.into_iter().filter_map(|i| map.get(i).copied()).collect();
+    mapped.dedup();
+    mapped
+}
+
+/// Boost score based on file type (apps rank higher than documents, etc.)
+fn file_type_boost(file_type: &str) -> f64 {
+    match file_type {
+        "app" => 50.0,
+        // Custom URL and command entries were added on purpose, like shortcuts
+        "shortcut" | "url" | "command" => 40.0,
+        // Under documents: a page matched by a keyword shouldn't beat a
+        // file named like the query
+        "setting" => 10.0,
+        "document" | "bookmark" => 20.0,
+        "folder" => 15.0,
+        "code" => 10.0,
+        "image" => 5.0,
+        _ => 0.0,
+    }
+}
+
+/// Taken off a folder's type boost when it's empty; it's rarely what the
+/// user is after.
+const EMPTY_FOLDER_PENALTY: f64 = 25.0;
+
+/// [`file_type_boost`], less [`EMPTY_FOLDER_PENALTY`] for a folder known to
+/// be empty.
+fn entry_type_boost(file_type: &str, child_count: Option<i64>) -> f64 {
+    let empty_folder = file_type == "folder" && child_count == Some(0);
+    file_type_boost(file_type) - if empty_folder { EMPTY_FOLDER_PENALTY } else { 0.0 }
+}
+
+/// Taken off an empty or very large regular file, which is rarely what the
+/// user is after. Kept well under the gap between an exact name match and
+/// the best fuzzy one, so it only reorders similar matches.
+const SIZE_EXTREME_PENALTY: f64 = 80.0;
+
+/// Whether `file_type` is a regular file whose size means something; apps,
+/// shortcuts, folders and custom entries are left alone.
+fn is_regular_file(file_type: &str) -> bool {
+    matches!(file_type, "document" | "image" | "code" | "other")
+}
+
+/// A regular file with nothing in it.
+fn is_empty_file(file_type: &str, file_size: i64) -> bool {
+    file_size == 0 && is_regular_file(file_type)
+}
+
+/// `-`[`SIZE_EXTREME_PENALTY`] for an empty regular file or one above
+/// `large_file_bytes` (unless that's 0), else 0.
+fn size_adjustment(file_type: &str, file_size: i64, large_file_bytes: i64) -> f64 {
+    let large = large_file_bytes > 0 && file_size > large_file_bytes;
+    if is_regular_file(file_type) && (file_size == 0 || large) {
+        -SIZE_EXTREME_PENALTY
+    } else {
+        0.0
+    }
+}
+
+/// Click-frequency and recency components of the usage boost.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct UsageBoost {
+    click: f64,
+    recency: f64,
+}
+
+/// Recency boost for a file modified just now, e.g. a finished download,
+/// fading out linearly over [`FRESH_FILE_WINDOW_SECS`].
+const FRESH_FILE_BOOST: f64 = 150.0;
+const FRESH_FILE_WINDOW_SECS: i64 = 10 * 60;
+
+/// Boost score based on usage frequency and recency.
+fn usage_boost(click_count: i64, last_accessed: i64, modified_at: i64) -> UsageBoost {
+    // Click count boost: logarithmic to prevent domination
+    let click_boost = if click_count > 0 {
+        (click_count as f64).ln() * 15.0
+    } else {
+        0.0
+    };
+
+    // Recency boost: higher for recently accessed items
+    let now = chrono::Utc::now().timestamp();
+    let recency_boost = if last_accessed > 0 {
+        let age_hours = ((now - last_accessed) as f64 / 3600.0).max(1.0);
+        // Decay over time: full boost if accessed in last hour, diminishing after
+        (100.0 / age_hours).min(30.0)
+    } else {
+        0.0
+    };
+    // Brand-new files float up even before they're ever opened
+    let fresh_boost = match now - modified_at {
+        age @ 0..FRESH_FILE_WINDOW_SECS => FRESH_FILE_BOOST * (1.0 - age as f64 / FRESH_FILE_WINDOW_SECS as f64),
+        _ => 0.0,
+    };
+
+    UsageBoost {
+        click: click_boost,
+        recency: recency_boost + fresh_boost,
+    }
+}
+
+/// Evaluate a math expression if the query looks like one.
+/// Supports basic arithmetic: +, -, *, /, parentheses.
+pub fn evaluate_math(query: &str) -> Option<String> {
+    let trimmed = query.trim();
+
+    // Must contain at least one operator and one digit
+    if !trimmed.chars().any(|c| c.is_ascii_digit())
+        || !trimmed.chars().any(|c| matches!(c, '+' | '-' | '*' | '/' | '%' | '^'))
+    {
+        return None;
+    }
+
+    // Only allow safe characters (hex digits and x/o for 0x/0b/0o literals)
+    if !trimmed.chars().all(|c| {
+        c.is_ascii_hexdigit()
+            || matches!(c, 'x' | 'X' | 'o' | 'O' | '+' | '-' | '*' | '/' | '.' | '(' | ')' | ' ' | '%' | '^')
+    }) {
+        return None;
+    }
+
+    // Simple recursive descent parser for basic math
+    match parse_expression(trimmed) {
+        Some((result, rest)) if rest.trim().is_empty() && result.is_finite() => Some(format_number(result)),
+        _ => None,
+    }
+}
+
+/// Format a computed answer: whole numbers without decimals, everything else
+/// rounded to 6 places with trailing zeros removed.
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.6}", value).trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Evaluate an inline answer for the query box: math, number bases, unit
+/// conversion, colors, then dates.
+pub fn evaluate_inline(query: &str) -> Option<String> {
+    evaluate_math(query)
+        .or_else(|| evaluate_base(query))
+        .or_else(|| evaluate_conversion(query))
+        .or_else(|| evaluate_color(query))
+        .or_else(|| evaluate_date(query))
+}
+
+// ---- Number bases ----
+
+/// Handle base conversions: bare prefixed literals ("0xff" → "255") and
+/// "<expr> to hex|dec|bin|oct" ("255 to hex" → "0xFF (255)"). Values that
+/// aren't integers or don't fit in 64 bits return None.
+pub fn evaluate_base(query: &str) -> Option<String> {
+    let lower = query.trim().to_lowercase();
+
+    let Some((source, target)) = lower.rsplit_once(" to ").or_else(|| lower.rsplit_once(" in ")) else {
+        // Bare literal: show its decimal value
+        let (value, rest) = parse_radix_literal(lower.strip_prefix('-').unwrap_or(&lower))?;
+        if !rest.is_empty() {
+            return None;
+        }
+        let value = value as i128;
+        return Some(if lower.starts_with('-') { -value } else { value }.to_string());
+    };
+
+    let (radix, prefix) = match target.trim() {
+        "hex" | "hexadecimal" => (16, "0x"),
+        "dec" | "decimal" => (10, ""),
+        "bin" | "binary" => (2, "0b"),
+        "oct" | "octal" => (8, "0o"),
+        _ => return None,
+    };
+    let value = integer_value(source.trim())?;
+    let digits = match radix {
+        16 => format!("{:X}", value.unsigned_abs()),
+        2 => format!("{:b}", value.unsigned_abs()),
+        8 => format!("{:o}", value.unsigned_abs()),
+        _ => value.unsigned_abs().to_string(),
+    };
+    let sign = if value < 0 { "-" } else { "" };
+    if radix == 10 {
+        Some(format!("{}{}", sign, digits))
+    } else {
+        Some(format!("{}{}{} ({})", sign, prefix, digits, value))
+    }
+}
+
+/// Integer value of a conversion source. Plain and prefixed literals are
+/// read exactly; anything else goes through the math parser and must come
+/// out as a whole number small enough to be exact in an f64.
+fn integer_value(source: &str) -> Option<i128> {
+    let (negative, unsigned) = match source.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, source),
+    };
+    let exact = match parse_radix_literal(unsigned) {
+        Some((value, "")) => Some(value),
+        _ if !unsigned.is_empty() && unsigned.chars().all(|c| c.is_ascii_digit()) => unsigned.parse().ok(),
+        _ => None,
+    };
+    if let Some(value) = exact {
+        let value = value as i128;
+        return Some(if negative { -value } else { value });
+    }
+
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0; // 2^53
+    match parse_expression(source) {
+        Some((value, rest)) if rest.trim().is_empty() && value.fract() == 0.0 && value.abs() <= MAX_EXACT => {
+            Some(value as i128)
+        }
+        _ => None,
+    }
+}
+
+/// Parse a "0x", "0b" or "0o" prefixed literal from the start of `input`.
+fn parse_radix_literal(input: &str) -> Option<(u64, &str)> {
+    let radix = match input.get(..2)?.to_ascii_lowercase().as_str() {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => return None,
+    };
+    let digits = &input[2..];
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    if end == 0 {
+        return None;
+    }
+    let value = u64::from_str_radix(&digits[..end], radix).ok()?;
+    Some((value, &digits[end..]))
+}
+
+// ---- Unit conversion ----
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnitKind {
+    Length,
+    Mass,
+    Temperature,
+    Data,
+    Speed,
+}
+
+/// A convertible unit. `factor` is its size in the kind's base unit
+/// (metre, gram, byte, metre/second); temperatures ignore it.
+struct Unit {
+    symbol: &'static str,
+    aliases: &'static [&'static str],
+    kind: UnitKind,
+    factor: f64,
+}
+
+const UNITS: &[Unit] = &[
+    // Length
+    Unit { symbol: "mm", aliases: &["mm", "millimeter", "millimeters", "millimetre", "millimetres"], kind: UnitKind::Length, factor: 0.001 },
+    Unit { symbol: "cm", aliases: &["cm", "centimeter", "centimeters", "centimetre", "centimetres"], kind: UnitKind::Length, factor: 0.01 },
+    Unit { symbol: "m", aliases: &["m", "meter", "meters", "metre", "metres"], kind: UnitKind::Length, factor: 1.0 },
+    Unit { symbol: "km", aliases: &["km", "kilometer", "kilometers", "kilometre", "kilometres"], kind: UnitKind::Length, factor: 1000.0 },
+    Unit { symbol: "in", aliases: &["in", "inch", "inches"], kind: UnitKind::Length, factor: 0.0254 },
+    Unit { symbol: "ft", aliases: &["ft", "foot", "feet"], kind: UnitKind::Length, factor: 0.3048 },
+    Unit { symbol: "yd", aliases: &["yd", "yard", "yards"], kind: UnitKind::Length, factor: 0.9144 },
+    Unit { symbol: "mi", aliases: &["mi", "mile", "miles"], kind: UnitKind::Length, factor: 1609.344 },
+    // Mass
+    Unit { symbol: "mg", aliases: &["mg", "milligram", "milligrams"], kind: UnitKind::Mass, factor: 0.001 },
+    Unit { symbol: "g", aliases: &["g", "gram", "grams"], kind: UnitKind::Mass, factor: 1.0 },
+    Unit { symbol: "kg", aliases: &["kg", "kilogram", "kilograms", "kilo", "kilos"], kind: UnitKind::Mass, factor: 1000.0 },
+    Unit { symbol: "t", aliases: &["t", "tonne", "tonnes"], kind: UnitKind::Mass, factor: 1_000_000.0 },
+    Unit { symbol: "oz", aliases: &["oz", "ounce", "ounces"], kind: UnitKind::Mass, factor: 28.349523125 },
+    Unit { symbol: "lb", aliases: &["lb", "lbs", "pound", "pounds"], kind: UnitKind::Mass, factor: 453.59237 },
+    // Temperature
+    Unit { symbol: "°C", aliases: &["c", "°c", "celsius"], kind: UnitKind::Temperature, factor: 1.0 },
+    Unit { symbol: "°F", aliases: &["f", "°f", "fahrenheit"], kind: UnitKind::Temperature, factor: 1.0 },
+    Unit { symbol: "K", aliases: &["k", "kelvin"], kind: UnitKind::Temperature, factor: 1.0 },
+    // Data sizes: KB/MB/... are decimal, KiB/MiB/... binary
+    Unit { symbol: "bit", aliases: &["bit", "bits"], kind: UnitKind::Data, factor: 0.125 },
+    Unit { symbol: "B", aliases: &["b", "byte", "bytes"], kind: UnitKind::Data, factor: 1.0 },
+    Unit { symbol: "KB", aliases: &["kb", "kilobyte", "kilobytes"], kind: UnitKind::Data, factor: 1e3 },
+    Unit { symbol: "MB", aliases: &["mb", "megabyte", "megabytes"], kind: UnitKind::Data, factor: 1e6 },
+    Unit { symbol: "GB", aliases: &["gb", "gigabyte", "gigabytes"], kind: UnitKind::Data, factor: 1e9 },
+    Unit { symbol: "TB", aliases: &["tb", "terabyte", "terabytes"], kind: UnitKind::Data, factor: 1e12 },
+    Unit { symbol: "KiB", aliases: &["kib", "kibibyte", "kibibytes"], kind: UnitKind::Data, factor: 1024.0 },
+    Unit { symbol: "MiB", aliases: &["mib", "mebibyte", "mebibytes"], kind: UnitKind::Data, factor: 1_048_576.0 },
+    Unit { symbol: "GiB", aliases: &["gib", "gibibyte", "gibibytes"], kind: UnitKind::Data, factor: 1_073_741_824.0 },
+    Unit { symbol: "TiB", aliases: &["tib", "tebibyte", "tebibytes"], kind: UnitKind::Data, factor: 1_099_511_627_776.0 },
+    // Speed
+    Unit { symbol: "m/s", aliases: &["m/s", "mps"], kind: UnitKind::Speed, factor: 1.0 },
+    Unit { symbol: "km/h", aliases: &["km/h", "kmh", "kph"], kind: UnitKind::Speed, factor: 1000.0 / 3600.0 },
+    Unit { symbol: "mph", aliases: &["mph"], kind: UnitKind::Speed, factor: 1609.344 / 3600.0 },
+    Unit { symbol: "kn", aliases: &["kn", "knot", "knots"], kind: UnitKind::Speed, factor: 1852.0 / 3600.0 },
+];
+
+/// Look up a unit by any of its aliases (case-insensitive).
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.trim().to_lowercase();
+    UNITS.iter().find(|unit| unit.aliases.contains(&name.as_str()))
+}
+
+/// Convert queries like "12 km to miles", "72 f to c" or "5 ft in cm".
+/// Returns None for anything that isn't a conversion between two known
+/// units of the same kind, so normal file search still happens.
+pub fn evaluate_conversion(query: &str) -> Option<String> {
+    let lower = query.trim().to_lowercase();
+    // rsplit so that "5 in in cm" keeps "in" (inches) on the left
+    let (left, target) = lower
+        .rsplit_once(" to ")
+        .or_else(|| lower.rsplit_once(" in "))?;
+
+    let number_end = left
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map(|(i, _)| i)
+        .unwrap_or(left.len());
+    let value: f64 = left[..number_end].parse().ok()?;
+    let from = find_unit(&left[number_end..])?;
+    let to = find_unit(target)?;
+    if from.kind != to.kind {
+        return None;
+    }
+
+    let converted = if from.kind == UnitKind::Temperature {
+        kelvin_to(to.symbol, kelvin_from(from.symbol, value))
+    } else {
+        value * from.factor / to.factor
+    };
+    if !converted.is_finite() {
+        return None;
+    }
+    Some(format!("{} {}", format_number(converted), to.symbol))
+}
+
+/// Temperature in kelvin from a value in the given unit.
+fn kelvin_from(symbol: &str, value: f64) -> f64 {
+    match symbol {
+        "°C" => value + 273.15,
+        "°F" => (value - 32.0) * 5.0 / 9.0 + 273.15,
+        _ => value,
+    }
+}
+
+/// Temperature in the given unit from a value in kelvin.
+fn kelvin_to(symbol: &str, kelvin: f64) -> f64 {
+    match symbol {
+        "°C" => kelvin - 273.15,
+        "°F" => (kelvin - 273.15) * 9.0 / 5.0 + 32.0,
+        _ => kelvin,
+    }
+}
+
+// ---- Colors ----
+
+/// An sRGB color with alpha in `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    alpha: f64,
+}
+
+/// Show a color code in hex, rgb() and hsl() form: "#ff8800" →
+/// "#FF8800 · rgb(255, 136, 0) · hsl(32, 100%, 50%)".
+/// Accepts `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()` and
+/// `hsl()`/`hsla()`. Anything malformed or out of range returns None, so a
+/// search for "#backup" still searches.
+pub fn evaluate_color(query: &str) -> Option<String> {
+    let lower = query.trim().to_lowercase();
+    let color = if let Some(hex) = lower.strip_prefix('#') {
+        parse_hex_color(hex)?
+    } else if let Some(args) = color_function_args(&lower, "rgb") {
+        parse_rgb_color(&args)?
+    } else if let Some(args) = color_function_args(&lower, "hsl") {
+        parse_hsl_color(&args)?
+    } else {
+        return None;
+    };
+    Some(format_color(color))
+}
+
+fn format_color(color: Color) -> String {
+    let Color { r, g, b, alpha } = color;
+    let (h, s, l) = rgb_to_hsl(r, g, b);
+    if alpha >= 1.0 {
+        format!(
+            "#{:02X}{:02X}{:02X} · rgb({}, {}, {}) · hsl({}, {}%, {}%)",
+            r, g, b, r, g, b, h, s, l
+        )
+    } else {
+        let a = format_number(alpha);
+        format!(
+            "#{:02X}{:02X}{:02X}{:02X} · rgba({}, {}, {}, {}) · hsla({}, {}%, {}%, {})",
+            r,
+            g,
+            b,
+            (alpha * 255.0).round() as u8,
+            r,
+            g,
+            b,
+            a,
+            h,
+            s,
+            l,
+            a
+        )
+    }
+}
+
+/// Parse the hex digits of `#rgb`, `#rrggbb` or `#rrggbbaa`.
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, width: usize| {
+        let value = u8::from_str_radix(&hex[i * width..(i + 1) * width], 16).ok()?;
+        Some(if width == 1 { value * 17 } else { value })
+    };
+    let (width, has_alpha) = match hex.len() {
+        3 => (1, false),
+        6 => (2, false),
+        8 => (2, true),
+        _ => return None,
+    };
+    Some(Color {
+        r: channel(0, width)?,
+        g: channel(1, width)?,
+        b: channel(2, width)?,
+        alpha: if has_alpha { channel(3, width)? as f64 / 255.0 } else { 1.0 },
+    })
+}
+
+/// Split "rgb(255, 136, 0)" / "rgba(...)" into its arguments. The `a`
+/// suffix requires a fourth (alpha) argument, and the plain form forbids it.
+fn color_function_args(lower: &str, name: &str) -> Option<Vec<String>> {
+    let rest = lower.strip_prefix(name)?;
+    let (with_alpha, rest) = match rest.strip_prefix('a') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let inner = rest.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+    let args: Vec<String> = inner.split(',').map(|arg| arg.trim().to_string()).collect();
+    (args.len() == if with_alpha { 4 } else { 3 }).then_some(args)
+}
+
+/// Parse an alpha argument: 0–1, or a percentage.
+fn parse_alpha(arg: Option<&String>) -> Option<f64> {
+    let Some(arg) = arg else {
+        return Some(1.0);
+    };
+    let alpha = match arg.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => arg.parse().ok()?,
+    };
+    (0.0..=1.0).contains(&alpha).then_some(alpha)
+}
+
+fn parse_rgb_color(args: &[String]) -> Option<Color> {
+    let channel = |arg: &String| arg.parse::<u8>().ok();
+    Some(Color {
+        r: channel(&args[0])?,
+        g: channel(&args[1])?,
+        b: channel(&args[2])?,
+        alpha: parse_alpha(args.get(3))?,
+    })
+}
+
+fn parse_hsl_color(args: &[String]) -> Option<Color> {
+    let hue: f64 = args[0].strip_suffix("deg").unwrap_or(&args[0]).trim().parse().ok()?;
+    let percent = |arg: &String| {
+        let value: f64 = arg.strip_suffix('%').unwrap_or(arg).trim().parse().ok()?;
+        (0.0..=100.0).contains(&value).then_some(value / 100.0)
+    };
+    if !(0.0..=360.0).contains(&hue) {
+        return None;
+    }
+    let (r, g, b) = hsl_to_rgb(hue, percent(&args[1])?, percent(&args[2])?);
+    Some(Color {
+        r,
+        g,
+        b,
+        alpha: parse_alpha(args.get(3))?,
+    })
+}
+
+/// Hue in degrees, saturation and lightness in percent, all rounded.
+fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (i64, i64, i64) {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0, 0, (lightness * 100.0).round() as i64);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (
+        hue.round() as i64 % 360,
+        (saturation * 100.0).round() as i64,
+        (lightness * 100.0).round() as i64,
+    )
+}
+
+/// Convert hue (degrees) and saturation/lightness (0–1) to RGB channels.
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = (hue % 360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |value: f64| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+// ---- Date calculations ----
+
+/// Evaluate date queries relative to the local date:
+/// "days until dec 25", "days since 2024-01-01", "today + 45 days",
+/// "2024-03-01 to 2024-09-15". A bare date is not an answer, and anything
+/// that doesn't parse cleanly returns None so file search is unaffected.
+pub fn evaluate_date(query: &str) -> Option<String> {
+    evaluate_date_at(query, chrono::Local::now().date_naive())
+}
+
+fn evaluate_date_at(query: &str, today: NaiveDate) -> Option<String> {
+    let lower = query.trim().to_lowercase();
+
+    if let Some(target) = lower.strip_prefix("days until ") {
+        let mut date = parse_date(target, today)?;
+        // "days until dec 25" on Dec 26 means next Christmas
+        if date < today && !has_explicit_year(target) {
+            date = date.checked_add_months(Months::new(12))?;
+        }
+        return Some(describe_days((date - today).num_days()));
+    }
+    if let Some(target) = lower.strip_prefix("days since ") {
+        let date = parse_date(target, today)?;
+        return Some(describe_days((today - date).num_days()));
+    }
+    if let Some((from, to)) = lower.split_once(" to ") {
+        let from = parse_date(from, today)?;
+        let to = parse_date(to, today)?;
+        return Some(describe_days((to - from).num_days()));
+    }
+    for (op, sign) in [(" + ", 1), (" - ", -1)] {
+        if let Some((date, duration)) = lower.split_once(op) {
+            let date = parse_date(date, today)?;
+            let result = add_duration(date, duration, sign)?;
+            return Some(result.format("%a, %d %b %Y").to_string());
+        }
+    }
+    None
+}
+
+/// "N days" for spans into the future, "N days ago" for the past.
+fn describe_days(days: i64) -> String {
+    let unit = if days.abs() == 1 { "day" } else { "days" };
+    if days < 0 {
+        format!("{} {} ago", -days, unit)
+    } else {
+        format!("{} {}", days, unit)
+    }
+}
+
+/// Apply a duration like "45 days", "2 weeks", "3 months" or "1 year".
+fn add_duration(date: NaiveDate, duration: &str, sign: i32) -> Option<NaiveDate> {
+    let mut parts = duration.split_whitespace();
+    let amount: u32 = parts.next()?.parse().ok()?;
+    let unit = parts.next()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let (days, months) = match unit {
+        "d" | "day" | "days" => (amount as u64, 0),
+        "w" | "wk" | "week" | "weeks" => (amount as u64 * 7, 0),
+        "mo" | "month" | "months" => (0, amount),
+        "y" | "yr" | "year" | "years" => (0, amount.checked_mul(12)?),
+        _ => return None,
+    };
+    if sign > 0 {
+        date.checked_add_days(Days::new(days))?.checked_add_months(Months::new(months))
+    } else {
+        date.checked_sub_days(Days::new(days))?.checked_sub_months(Months::new(months))
+    }
+}
+
+/// Parse a single date: "today"/"tomorrow"/"yesterday", ISO "2024-03-01",
+/// or month-name forms like "dec 25", "25 december", "march 1st 2025".
+/// Month-name dates without a year fall in the current year.
+fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim();
+    match text {
+        "today" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let tokens: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .collect();
+    let (month, day, year) = match tokens.as_slice() {
+        [a, b] | [a, b, _] => match (parse_month(a), parse_month(b)) {
+            (Some(month), None) => (month, parse_day(b)?, tokens.get(2)),
+            (None, Some(month)) => (month, parse_day(a)?, tokens.get(2)),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let year = match year {
+        Some(year) => year.parse().ok()?,
+        None => today.year(),
+    };
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+fn has_explicit_year(text: &str) -> bool {
+    text.split(|c: char| c.is_whitespace() || c == ',' || c == '-')
+        .any(|t| t.len() == 4 && t.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn parse_month(token: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june", "july", "august", "september", "october",
+        "november", "december",
+    ];
+    if token.len() < 3 {
+        return None;
+    }
+    let token = if token == "sept" { "sep" } else { token };
+    MONTHS
+        .iter()
+        .position(|name| name.starts_with(token))
+        .map(|i| i as u32 + 1)
+}
+
+/// Day of month with an optional ordinal suffix ("1st", "22nd", "25th").
+fn parse_day(token: &str) -> Option<u32> {
+    let digits = token.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &token[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+// ---- Simple math expression parser ----
+
+fn parse_expression(input: &str) -> Option<(f64, &str)> {
+    let (mut left, mut rest) = parse_term(input)?;
+    let mut rest_trimmed = rest.trim_start();
+    while rest_trimmed.starts_with('+') || rest_trimmed.starts_with('-') {
+        let op = rest_trimmed.chars().next()?;
+        let after_op = rest_trimmed[1..].trim_start();
+        let (right, new_rest) = parse_term(after_op)?;
+        left = match op {
+            '+' => left + right,
+            '-' => left - right,
+            _ => unreachable!(),
+        };
+        rest = new_rest;
+        rest_trimmed = rest.trim_start();
+    }
+    Some((left, rest))
+}
+
+fn parse_term(input: &str) -> Option<(f64, &str)> {
+    let (mut left, mut rest) = parse_factor(input)?;
+    let mut rest_trimmed = rest.trim_start();
+    while rest_trimmed.starts_with('*') || rest_trimmed.starts_with('/') || rest_trimmed.starts_with('%') {
+        let op = rest_trimmed.chars().next()?;
+        let after_op = rest_trimmed[1..].trim_start();
+        let (right, new_rest) = parse_factor(after_op)?;
+        left = match op {
+            '*' => left * right,
+            '/' => {
+                if right == 0.0 {
+                    return None; // division by zero
+                }
+                left / right
+            }
+            '%' => {
+                if right == 0.0 {
+                    return None;
+                }
+                left % right
+            }
+            _ => unreachable!(),
+        };
+        rest = new_rest;
+        rest_trimmed = rest.trim_start();
+    }
+    Some((left, rest))
+}
+
+fn parse_factor(input: &str) -> Option<(f64, &str)> {
+    let trimmed = input.trim_start();
+
+    // Handle negative numbers; "-2^2" is -(2^2)
+    if trimmed.starts_with('-') {
+        let (val, rest) = parse_factor(&trimmed[1..])?;
+        return Some((-val, rest));
+    }
+
+    let (base, rest) = parse_primary(trimmed)?;
+    let rest_trimmed = rest.trim_start();
+    if let Some(after_op) = rest_trimmed.strip_prefix('^') {
+        // Right-associative: the exponent is itself a (possibly negative) power
+        let (exponent, new_rest) = parse_factor(after_op)?;
+        let result = base.powf(exponent);
+        if !result.is_finite() {
+            return None; // overflow or a negative base with a fractional exponent
+        }
+        return Some((result, new_rest));
+    }
+    Some((base, rest))
+}
+
+fn parse_primary(input: &str) -> Option<(f64, &str)> {
+    let trimmed = input.trim_start();
+
+    // Handle parentheses
+    if trimmed.starts_with('(') {
+        let (val, rest) = parse_expression(&trimmed[1..])?;
+        let rest = rest.trim_start();
+        if rest.starts_with(')') {
+            return Some((val, &rest[1..]));
+        }
+        return None;
+    }
+
+    // Prefixed integer literals: 0xff, 0b1010, 0o17
+    if let Some((value, rest)) = parse_radix_literal(trimmed) {
+        return Some((value as f64, rest));
+    }
+
+    // Parse number
+    let mut end = 0;
+    let mut has_dot = false;
+    for (i, c) in trimmed.char_indices() {
+        if c.is_ascii_digit() {
+            end = i + 1;
+        } else if c == '.' && !has_dot {
+            has_dot = true;
+            end = i + 1;
+        } else {
+            break;
+        }
+    }
+
+    if end == 0 {
+        return None;
+    }
+
+    let num: f64 = trimmed[..end].parse().ok()?;
+    Some((num, &trimmed[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_math_eval_basic() {
+        assert_eq!(evaluate_math("2+2"), Some("4".to_string()));
+        assert_eq!(evaluate_math("10 * 5"), Some("50".to_string()));
+        assert_eq!(evaluate_math("100 / 4"), Some("25".to_string()));
+        assert_eq!(evaluate_math("3.14 * 2"), Some("6.28".to_string()));
+    }
+
+    #[test]
+    fn test_math_eval_complex() {
+        assert_eq!(evaluate_math("(2 + 3) * 4"), Some("20".to_string()));
+        assert_eq!(evaluate_math("10 + 5 * 2"), Some("20".to_string()));
+        assert_eq!(evaluate_math("100 / (2 + 3)"), Some("20".to_string()));
+    }
+
+    #[test]
+    fn test_math_eval_invalid() {
+        assert_eq!(evaluate_math("hello"), None);
+        assert_eq!(evaluate_math(""), None);
+        assert_eq!(evaluate_math("abc + 2"), None);
+    }
+
+    #[test]
+    fn test_math_division_by_zero() {
+        assert_eq!(evaluate_math("5 / 0"), None);
+    }
+
+    #[test]
+    fn test_math_eval_power() {
+        assert_eq!(evaluate_math("2^10"), Some("1024".to_string()));
+        assert_eq!(evaluate_math("2^-1"), Some("0.5".to_string()));
+        assert_eq!(evaluate_math("2^0.5"), Some("1.414214".to_string()));
+        assert_eq!(evaluate_math("-2^2"), Some("-4".to_string()));
+        assert_eq!(evaluate_math("(1+1)^8"), Some("256".to_string()));
+        assert_eq!(evaluate_math("2^3^2"), Some("512".to_string()));
+        assert_eq!(evaluate_math("3 * 2^2"), Some("12".to_string()));
+        assert_eq!(evaluate_math("10^400"), None);
+    }
+
+    #[test]
+    fn test_math_eval_prefixed_literals() {
+        assert_eq!(evaluate_math("0xff + 1"), Some("256".to_string()));
+        assert_eq!(evaluate_math("0b1010 * 2"), Some("20".to_string()));
+        assert_eq!(evaluate_math("0o17 - 0x0F"), Some("0".to_string()));
+        assert_eq!(evaluate_math("0xg + 1"), None);
+    }
+
+    #[test]
+    fn test_base_conversion() {
+        assert_eq!(evaluate_base("0xff"), Some("255".to_string()));
+        assert_eq!(evaluate_base("0b1010"), Some("10".to_string()));
+        assert_eq!(evaluate_base("255 to hex"), Some("0xFF (255)".to_string()));
+        assert_eq!(evaluate_base("0x1F4 to dec"), Some("500".to_string()));
+        assert_eq!(evaluate_base("0x1F4 to bin"), Some("0b111110100 (500)".to_string()));
+        assert_eq!(evaluate_base("0xff + 1 to hex"), Some("0x100 (256)".to_string()));
+        assert_eq!(evaluate_base("8 in octal"), Some("0o10 (8)".to_string()));
+    }
+
+    #[test]
+    fn test_base_conversion_degrades_gracefully() {
+        assert_eq!(evaluate_base("-255 to hex"), Some("-0xFF (-255)".to_string()));
+        assert_eq!(evaluate_base("-0x10"), Some("-16".to_string()));
+        assert_eq!(
+            evaluate_base("0xFFFFFFFFFFFFFFFF to dec"),
+            Some("18446744073709551615".to_string())
+        );
+        assert_eq!(evaluate_base("0x1FFFFFFFFFFFFFFFF"), None);
+        assert_eq!(evaluate_base("99999999999999999999 to hex"), None);
+        assert_eq!(evaluate_base("1.5 to hex"), None);
+        assert_eq!(evaluate_base("report to hex"), None);
+        assert_eq!(evaluate_base("0x"), None);
+    }
+
+    #[test]
+    fn test_conversion_length_and_mass() {
+        assert_eq!(evaluate_conversion("12 km to miles"), Some("7.456454 mi".to_string()));
+        assert_eq!(evaluate_conversion("5 ft in cm"), Some("152.4 cm".to_string()));
+        assert_eq!(evaluate_conversion("10 in in cm"), Some("25.4 cm".to_string()));
+        assert_eq!(evaluate_conversion("250 g to oz"), Some("8.81849 oz".to_string()));
+        assert_eq!(evaluate_conversion("2 Kilograms to lbs"), Some("4.409245 lb".to_string()));
+    }
+
+    #[test]
+    fn test_conversion_temperature() {
+        assert_eq!(evaluate_conversion("72 f to c"), Some("22.222222 °C".to_string()));
+        assert_eq!(evaluate_conversion("-40 c to f"), Some("-40 °F".to_string()));
+        assert_eq!(evaluate_conversion("100 celsius to kelvin"), Some("373.15 K".to_string()));
+        assert_eq!(evaluate_conversion("0 k to c"), Some("-273.15 °C".to_string()));
+    }
+
+    #[test]
+    fn test_conversion_data_and_speed() {
+        assert_eq!(evaluate_conversion("1 GiB to MB"), Some("1073.741824 MB".to_string()));
+        assert_eq!(evaluate_conversion("8 bits to bytes"), Some("1 B".to_string()));
+        assert_eq!(evaluate_conversion("100 km/h to mph"), Some("62.137119 mph".to_string()));
+    }
+
+    #[test]
+    fn test_conversion_rejects_unknown_or_mismatched_units() {
+        assert_eq!(evaluate_conversion("12 km to kg"), None);
+        assert_eq!(evaluate_conversion("12 parsecs to km"), None);
+        assert_eq!(evaluate_conversion("photos to backup"), None);
+        assert_eq!(evaluate_conversion("report in downloads"), None);
+    }
+
+    #[test]
+    fn test_color_conversion() {
+        let orange = "#FF8800 · rgb(255, 136, 0) · hsl(32, 100%, 50%)";
+        assert_eq!(evaluate_color("#ff8800").as_deref(), Some(orange));
+        assert_eq!(evaluate_color("#f80").as_deref(), Some(orange));
+        assert_eq!(evaluate_color("rgb(255,136,0)").as_deref(), Some(orange));
+        assert_eq!(evaluate_color("hsl(32, 100%, 50%)").as_deref(), Some(orange));
+        assert_eq!(
+            evaluate_color("rgba(0, 0, 255, 0.5)").as_deref(),
+            Some("#0000FF80 · rgba(0, 0, 255, 0.5) · hsla(240, 100%, 50%, 0.5)")
+        );
+        assert_eq!(
+            evaluate_color("#ffffff80").as_deref(),
+            Some("#FFFFFF80 · rgba(255, 255, 255, 0.501961) · hsla(0, 0%, 100%, 0.501961)")
+        );
+        assert_eq!(evaluate_inline("#000"), Some("#000000 · rgb(0, 0, 0) · hsl(0, 0%, 0%)".to_string()));
+    }
+
+    #[test]
+    fn test_color_rejects_invalid_codes() {
+        assert_eq!(evaluate_color("#backup"), None);
+        assert_eq!(evaluate_color("#12345"), None);
+        assert_eq!(evaluate_color("rgb(256, 0, 0)"), None);
+        assert_eq!(evaluate_color("rgb(1, 2)"), None);
+        assert_eq!(evaluate_color("rgb(1, 2, 3, 0.5)"), None);
+        assert_eq!(evaluate_color("rgba(1, 2, 3, 1.5)"), None);
+        assert_eq!(evaluate_color("hsl(400, 50%, 50%)"), None);
+        assert_eq!(evaluate_color("hsl(40, 150%, 50%)"), None);
+        assert_eq!(evaluate_color("rgb report"), None);
+    }
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_date_difference_and_until() {
+        let today = ymd(2024, 12, 1);
+        assert_eq!(evaluate_date_at("days until dec 25", today), Some("24 days".to_string()));
+        assert_eq!(evaluate_date_at("2024-03-01 to 2024-09-15", today), Some("198 days".to_string()));
+        assert_eq!(evaluate_date_at("Dec 25th 2023 to Jan 5 2024", today), Some("11 days".to_string()));
+        assert_eq!(evaluate_date_at("days since 2024-11-30", today), Some("1 day".to_string()));
+    }
+
+    #[test]
+    fn test_date_past_dates() {
+        let today = ymd(2024, 12, 26);
+        // Without a year, "until" rolls over to the next occurrence
+        assert_eq!(evaluate_date_at("days until dec 25", today), Some("364 days".to_string()));
+        assert_eq!(evaluate_date_at("days until 2024-12-20", today), Some("6 days ago".to_string()));
+        assert_eq!(evaluate_date_at("today to 2024-12-25", today), Some("1 day ago".to_string()));
+    }
+
+    #[test]
+    fn test_date_plus_duration() {
+        let today = ymd(2024, 1, 1);
+        assert_eq!(evaluate_date_at("today + 45 days", today), Some("Thu, 15 Feb 2024".to_string()));
+        // Leap years and year boundaries
+        assert_eq!(evaluate_date_at("2024-02-28 + 1 day", today), Some("Thu, 29 Feb 2024".to_string()));
+        assert_eq!(evaluate_date_at("2023-02-28 + 1 day", today), Some("Wed, 01 Mar 2023".to_string()));
+        assert_eq!(evaluate_date_at("2024-01-31 + 1 month", today), Some("Thu, 29 Feb 2024".to_string()));
+        assert_eq!(evaluate_date_at("2024-12-31 + 1 day", today), Some("Wed, 01 Jan 2025".to_string()));
+        assert_eq!(evaluate_date_at("2025-01-03 - 1 week", today), Some("Fri, 27 Dec 2024".to_string()));
+    }
+
+    #[test]
+    fn test_date_rejects_unparseable() {
+        let today = ymd(2024, 1, 1);
+        assert_eq!(evaluate_date_at("today", today), None);
+        assert_eq!(evaluate_date_at("2024-02-30 + 1 day", today), None);
+        assert_eq!(evaluate_date_at("03/04/2024 to today", today), None);
+        assert_eq!(evaluate_date_at("notes to self", today), None);
+        assert_eq!(evaluate_date_at("today + lots of days", today), None);
+    }
+
+    #[test]
+    fn test_fresh_files_get_a_fading_recency_boost() {
+        let now = chrono::Utc::now().timestamp();
+        let fresh = usage_boost(0, 0, now - 30).recency;
+        let fading = usage_boost(0, 0, now - 9 * 60).recency;
+        assert!(fresh > 100.0);
+        assert!(fading > 0.0 && fading < fresh / 5.0);
+        assert_eq!(usage_boost(0, 0, now - 86_400).recency, 0.0);
+        // A timestamp from the future isn't new, just wrong
+        assert_eq!(usage_boost(0, 0, now + 3_600).recency, 0.0);
+
+        let db = fixture_db_with_metadata(&[
+            ("invoices.lnk", r"C:\Users\me\Downloads\invoices.lnk", 1, now - 86_400),
+            ("invoice from acme.lnk", r"C:\Users\me\Downloads\invoice from acme.lnk", 1, now - 30),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "invoice", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].filename, "invoice from acme.lnk");
+    }
+
+    #[test]
+    fn test_file_type_boost_values() {
+        assert!(file_type_boost("app") > file_type_boost("document"));
+        assert!(file_type_boost("document") > file_type_boost("other"));
+    }
+
+    #[test]
+    fn test_entry_type_boost_only_penalizes_empty_folders() {
+        assert_eq!(
+            entry_type_boost("folder", Some(0)),
+            file_type_boost("folder") - EMPTY_FOLDER_PENALTY
+        );
+        assert_eq!(entry_type_boost("folder", Some(3)), file_type_boost("folder"));
+        assert_eq!(entry_type_boost("folder", None), file_type_boost("folder"));
+        assert_eq!(entry_type_boost("document", Some(0)), file_type_boost("document"));
+    }
+
+    #[test]
+    fn test_size_adjustment_spares_folders_and_apps() {
+        const GB: i64 = 1024 * 1024 * 1024;
+        assert_eq!(size_adjustment("document", 0, 2 * GB), -SIZE_EXTREME_PENALTY);
+        assert_eq!(size_adjustment("other", 5 * GB, 2 * GB), -SIZE_EXTREME_PENALTY);
+        assert_eq!(size_adjustment("other", 5 * GB, 0), 0.0);
+        assert_eq!(size_adjustment("image", 2 * GB, 2 * GB), 0.0);
+        for exempt in ["folder", "app", "shortcut", "url", "command", "bookmark"] {
+            assert_eq!(size_adjustment(exempt, 0, 2 * GB), 0.0, "{}", exempt);
+            assert_eq!(size_adjustment(exempt, 5 * GB, 2 * GB), 0.0, "{}", exempt);
+        }
+    }
+
+    #[test]
+    fn test_size_penalty_never_sinks_an_exact_name_below_a_fuzzy_match() {
+        // Worst case: the penalized file only matches the stem, the other
+        // is a perfect fuzzy hit with the biggest type boost and length bonus
+        let worst_exact = SCORE_EXACT_STEM - SIZE_EXTREME_PENALTY + file_type_boost("other");
+        let best_fuzzy = FUZZY_NAME_BAND.1 + MAX_NAME_LENGTH_BONUS + file_type_boost("app");
+        assert!(worst_exact > best_fuzzy);
+
+        const GB: i64 = 1024 * 1024 * 1024;
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        let row = |name: &str, size: i64, file_type: &str| {
+            let extension = Path::new(name).extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+            (name.to_string(), format!(r"C:\Files\{}", name), extension, size, 0, file_type.to_string())
+        };
+        db.upsert_files_batch(&[
+            row("budget", 0, "document"),
+            row("budget.iso", 5 * GB, "other"),
+            row("bud get.txt", 2048, "document"),
+            row("budget drafts", 0, "folder"),
+        ])
+        .unwrap();
+        let db = Arc::new(db);
+        let names = |options: &SearchOptions| -> Vec<String> {
+            search(&db, &FilenameSnapshot::default(), "budget", options, &|| false)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.filename)
+                .collect()
+        };
+
+        let ranked = names(&SearchOptions::default());
+        let position = |name: &str| ranked.iter().position(|n| n == name).unwrap();
+        assert!(position("budget") < position("bud get.txt"));
+        assert!(position("budget.iso") < position("bud get.txt"));
+
+        // Hiding empty files drops the empty document, not the empty folder
+        let hidden = names(&SearchOptions {
+            hide_empty_files: true,
+            ..SearchOptions::default()
+        });
+        assert!(!hidden.contains(&"budget".to_string()));
+        assert!(hidden.contains(&"budget drafts".to_string()));
+        assert!(hidden.contains(&"budget.iso".to_string()));
+    }
+
+    #[test]
+    fn test_parse_query_filters() {
+        let parsed = parse_query("ext:pdf invoice -type:shortcut in:Downloads");
+        assert_eq!(parsed.text, "invoice");
+        assert_eq!(parsed.filters.extensions, vec!["pdf"]);
+        assert_eq!(parsed.filters.exclude_file_types, vec!["shortcut"]);
+        assert_eq!(parsed.filters.locations, vec!["downloads"]);
+    }
+
+    #[test]
+    fn test_extension_hint() {
+        let hint = |query: &str| extension_hint(&fold_text(&parse_query(query).text)).map(|(_, ext)| ext.to_string());
+        assert_eq!(hint("report.PDF").as_deref(), Some("pdf"));
+        assert_eq!(hint("my.notes.v2.txt ext:txt").as_deref(), Some("txt"));
+        assert_eq!(hint("setup v2.0"), None);
+        assert_eq!(hint(".bashrc"), None);
+        assert_eq!(hint("notes"), None);
+    }
+
+    #[test]
+    fn test_parse_query_unknown_prefix_is_literal() {
+        let parsed = parse_query("foo:bar type: -note");
+        assert_eq!(parsed.text, "foo:bar type: -note");
+        assert!(parsed.filters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_size_filters() {
+        let filters = parse_query("size:>100mb size:<1gb").filters;
+        assert_eq!(filters.min_size, Some(100 * 1024 * 1024 + 1));
+        assert_eq!(filters.max_size, Some(1024 * 1024 * 1024 - 1));
+        assert_eq!(parse_query("size:<=1.5kb").filters.max_size, Some(1536));
+        assert_eq!(parse_query("size:>=10").filters.min_size, Some(10));
+
+        let parsed = parse_query("backup size:huge -size:>1mb");
+        assert_eq!(parsed.text, "backup");
+        assert!(parsed.filters.is_empty());
+        assert_eq!(parsed.errors.len(), 2);
+        assert!(parsed.errors[0].contains("size:huge"));
+    }
+
+    #[test]
+    fn test_parse_modified_filters() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let now = local_midnight(today) + 12 * 3_600;
+        let parse = |q: &str| parse_query_at(q, now, today);
+
+        let filters = parse("modified:today").filters;
+        assert_eq!(filters.modified_after, Some(local_midnight(today)));
+        assert_eq!(
+            filters.modified_before,
+            Some(local_midnight(NaiveDate::from_ymd_opt(2024, 6, 16).unwrap()))
+        );
+
+        let filters = parse("modified:<2023-01-01").filters;
+        assert_eq!(filters.modified_after, None);
+        assert_eq!(
+            filters.modified_before,
+            Some(local_midnight(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()))
+        );
+
+        assert_eq!(parse("modified:7d").filters.modified_after, Some(now - 7 * 86_400));
+        assert_eq!(parse("modified:>2w").filters.modified_before, Some(now - 14 * 86_400));
+        assert_eq!(parse("modified:soon").errors.len(), 1);
+    }
+
+    #[test]
+    fn test_size_and_modified_filters_apply_in_search() {
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        let mb = 1024 * 1024;
+        let row = |name: &str, size: i64, modified: i64| {
+            (name.to_string(), format!(r"C:\Videos\{}", name), "mp4".to_string(), size, modified, "other".to_string())
+        };
+        db.upsert_files_batch(&[
+            row("holiday big.mp4", 500 * mb, 1_700_000_000),
+            row("holiday small.mp4", 2 * mb, 1_700_000_000),
+            row("holiday old.mp4", 500 * mb, 1_500_000_000),
+        ])
+        .unwrap();
+        let db = Arc::new(db);
+        let snapshot = FilenameSnapshot::default();
+
+        let names = |query: &str| -> Vec<String> {
+            let mut names: Vec<String> = search(&db, &snapshot, query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.filename)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names("holiday size:>100mb"), vec!["holiday big.mp4", "holiday old.mp4"]);
+        assert_eq!(names("holiday size:>100mb modified:>2020-01-01"), vec!["holiday big.mp4"]);
+        // Fuzzy matches are filtered in memory the same way
+        assert_eq!(names("hldy size:<10mb"), vec!["holiday small.mp4"]);
+
+        let response = search(&db, &snapshot, "holiday size:lots", &SearchOptions::default(), &|| false).unwrap();
+        assert_eq!(response.filter_errors.len(), 1);
+        assert_eq!(response.results.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_quoted_and_case_sensitive_terms() {
+        let parsed = parse_query(r#"report "Q3 Budget" ext:xlsx"#);
+        assert_eq!(parsed.text, "report Q3 Budget");
+        assert_eq!(parsed.filters.extensions, vec!["xlsx"]);
+        assert_eq!(
+            parsed.filters.required_terms,
+            vec![RequiredTerm {
+                text: "Q3 Budget".to_string(),
+                whole_word: true
+            }]
+        );
+        assert!(!parsed.fully_strict);
+
+        let parsed = parse_query("cs:README notes");
+        assert_eq!(parsed.text, "README notes");
+        let terms: Vec<&str> = parsed.filters.required_terms.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(terms, vec!["README", "notes"]);
+        assert!(parsed.filters.required_terms.iter().all(|t| !t.whole_word));
+        assert!(parsed.fully_strict);
+
+        // Quote characters with nothing inside are ignored
+        assert!(parse_query(r#"notes """#).filters.required_terms.is_empty());
+    }
+
+    #[test]
+    fn test_required_term_find_in() {
+        let term = |text: &str, whole_word| RequiredTerm {
+            text: text.to_string(),
+            whole_word,
+        };
+        assert_eq!(term("Q3", true).find_in("Budget Q3.xlsx"), Some(7));
+        assert_eq!(term("Q3", true).find_in("Budget q3.xlsx"), None);
+        assert_eq!(term("Q3", true).find_in("Q3Q4 plan.txt"), None);
+        assert_eq!(term("Q3", false).find_in("Q3Q4 plan.txt"), Some(0));
+        // Skips an occurrence inside a word to find a whole-word one
+        assert_eq!(term("art", true).find_in("smart art.png"), Some(6));
+    }
+
+    #[test]
+    fn test_quoted_and_case_sensitive_search() {
+        let db = fixture_db(&[
+            ("report Report.txt", r"C:\Docs\report Report.txt"),
+            ("report.txt", r"C:\Docs\report.txt"),
+            ("Reporter.txt", r"C:\Docs\Reporter.txt"),
+            ("Reporter notes.txt", r"C:\Docs\Reporter notes.txt"),
+            ("reporter notes.txt", r"C:\Docs\reporter notes.txt"),
+        ]);
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+                .into_iter()
+                .filter(|r| r.id > 0)
+                .collect::<Vec<_>>()
+        };
+
+        let case_sensitive = run("cs:Report");
+        let names: HashSet<&str> = case_sensitive.iter().map(|r| r.filename.as_str()).collect();
+        assert_eq!(
+            names,
+            HashSet::from(["report Report.txt", "Reporter.txt", "Reporter notes.txt"])
+        );
+        let both = case_sensitive.iter().find(|r| r.filename == "report Report.txt").unwrap();
+        assert_eq!(both.matched_indices, vec![7, 8, 9, 10, 11, 12]);
+
+        let whole_word = run(r#""Report""#);
+        assert_eq!(whole_word.len(), 1);
+        assert_eq!(whole_word[0].filename, "report Report.txt");
+
+        // Only the quoted token is strict
+        let mixed = run(r#""Reporter" notes"#);
+        assert_eq!(mixed.len(), 1);
+        assert_eq!(mixed[0].filename, "Reporter notes.txt");
+    }
+
+    #[test]
+    fn test_case_sensitive_query_checks_each_word() {
+        let db = fixture_db(&[
+            ("README notes.txt", r"C:\Docs\README notes.txt"),
+            ("README draft notes.txt", r"C:\Docs\README draft notes.txt"),
+            ("README Notes.txt", r"C:\Docs\README Notes.txt"),
+            ("readme notes.txt", r"C:\Docs\readme notes.txt"),
+        ]);
+        set_web_search_templates(&db, &[]).unwrap();
+        let mut names: Vec<String> =
+            search(&db, &FilenameSnapshot::default(), "cs:README notes", &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.filename)
+                .collect();
+        names.sort();
+        // Words needn't be adjacent, but each keeps its case
+        assert_eq!(names, vec!["README draft notes.txt", "README notes.txt"]);
+        let filters = parse_query("cs:README notes").filters;
+        assert!(filters.matches_required_terms("notes README.md", ""));
+        assert!(!filters.matches_required_terms("Notes README.md", ""));
+    }
+
+    #[test]
+    fn test_filters_match_candidate() {
+        let filters = parse_query("ext:.PDF -in:archive").filters;
+        assert!(filters.matches("Invoice.pdf", r"C:\Users\me\Documents\Invoice.pdf", "document"));
+        assert!(!filters.matches("Invoice.docx", r"C:\Users\me\Documents\Invoice.docx", "document"));
+        assert!(!filters.matches("Old.pdf", r"C:\Users\me\Archive\Old.pdf", "document"));
+    }
+
+    fn indices_for(filename: &str, query: &str) -> Vec<usize> {
+        let entry = FileEntry {
+            id: 1,
+            filename: filename.to_string(),
+            filepath: format!(r"C:\Users\me\{}", filename),
+            extension: String::new(),
+            file_size: 0,
+            modified_at: 0,
+            file_type: "document".to_string(),
+            click_count: 0,
+            last_accessed: 0,
+            icon_path: None,
+            child_count: None,
+            display_name: String::new(),
+        };
+        score_entry(&entry, &fold_text(query), &SkimMatcherV2::default(), 0).1
+    }
+
+    #[test]
+    fn test_strong_fuzzy_path_hit_survives_default_cutoff() {
+        let entry = FileEntry {
+            id: 1,
+            filename: "return.pdf".to_string(),
+            filepath: r"C:\Users\me\Docs\Taxes 2023\return.pdf".to_string(),
+            extension: "pdf".to_string(),
+            file_size: 1024,
+            modified_at: 0,
+            file_type: "document".to_string(),
+            click_count: 0,
+            last_accessed: 0,
+            icon_path: None,
+            child_count: None,
+            display_name: String::new(),
+        };
+        let (breakdown, _) = score_entry(&entry, "docstaxes", &SkimMatcherV2::default(), 0);
+        assert_eq!(breakdown.branch, "fuzzy_path");
+        assert!(breakdown.passes(DEFAULT_MIN_SCORE), "scored {}", breakdown.base_match());
+        assert!(breakdown.match_score < SCORE_PATH);
+    }
+
+    #[test]
+    fn test_matched_indices_are_char_positions() {
+        assert_eq!(indices_for("Résumé.pdf", "résumé"), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(indices_for("Résumé.pdf", "sum"), vec![2, 3, 4]);
+        assert_eq!(indices_for("日本語メモ.txt", "メモ"), vec![3, 4]);
+        assert_eq!(indices_for("日本語メモ.txt", "日本語メモ.txt"), (0..9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_display_name_matches_like_filename() {
+        let db = fixture_db(&[
+            ("Code.exe", r"C:\Apps\VS Code\Code.exe"),
+            ("Visual Studio Code.lnk", r"C:\Start Menu\Visual Studio Code.lnk"),
+            ("setup.exe", r"C:\Downloads\setup.exe"),
+        ]);
+        db.set_display_names(&[(r"C:\Apps\VS Code\Code.exe".to_string(), "Visual Studio Code".to_string(), 0)])
+            .unwrap();
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+                .into_iter()
+                .filter(|r| r.id > 0)
+                .collect::<Vec<_>>()
+        };
+
+        // An exact display name match scores like an exact filename match
+        let exact = run("visual studio code");
+        let app = exact.iter().find(|r| r.filename == "Code.exe").unwrap();
+        assert_eq!(app.display_name.as_deref(), Some("Visual Studio Code"));
+        assert_eq!(app.match_type, "exact");
+        assert_eq!(app.matched_indices, (0..18).collect::<Vec<_>>());
+        let entry = db.search_files("code.exe", &QueryFilters::default(), 1).unwrap().remove(0);
+        let renamed = FileEntry {
+            filename: "Visual Studio Code".to_string(),
+            display_name: String::new(),
+            ..entry.clone()
+        };
+        let score = |entry: &FileEntry| score_entry(entry, "visual studio code", &SkimMatcherV2::default(), 0).0;
+        assert_eq!(score(&entry).total(), score(&renamed).total());
+
+        // The filename still finds it, with nothing to highlight in the
+        // display name shown instead
+        let by_filename = run("code.exe");
+        assert_eq!(by_filename[0].filename, "Code.exe");
+        assert!(by_filename[0].matched_indices.is_empty());
+        // Entries without a display name show their filename
+        assert_eq!(run("setup")[0].display_name, None);
+    }
+
+    #[test]
+    fn test_matched_indices_survive_expanding_lowercase() {
+        // 'İ' lowercases to two chars ("i̇"), shifting everything after it
+        assert_eq!(indices_for("İstanbul.txt", "stan"), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_fold_text_diacritics() {
+        assert_eq!(fold_text("Résumé.pdf"), "resume.pdf");
+        assert_eq!(fold_text("Über uns.docx"), "uber uns.docx");
+        assert_eq!(fold_text("Straße"), "strasse");
+        assert_eq!(fold_text("İstanbul ılık"), "istanbul ilik");
+    }
+
+    #[test]
+    fn test_folded_match_maps_to_original_indices() {
+        assert_eq!(indices_for("Über uns.docx", "uber"), vec![0, 1, 2, 3]);
+        // "ß" expands to "ss": both folded chars map back to the one original char
+        assert_eq!(indices_for("Straße.txt", "strasse"), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    fn fixture_db(files: &[(&str, &str)]) -> Arc<Database> {
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        let rows: Vec<(String, String, String, i64, i64, String)> = files
+            .iter()
+            .map(|(filename, filepath)| {
+                let extension = Path::new(filename)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                // Ordinary non-empty files, so no size penalty applies
+                (filename.to_string(), filepath.to_string(), extension, 1024, 0, "other".to_string())
+            })
+            .collect();
+        db.upsert_files_batch(&rows).unwrap();
+        Arc::new(db)
+    }
+
+    #[test]
+    fn test_kept_snapshot_matches_a_fresh_read_after_writes() {
+        let db = fixture_db(&[
+            ("quarterly report.pdf", r"C:\Docs\quarterly report.pdf"),
+            ("quiet retreat.txt", r"C:\Docs\quiet retreat.txt"),
+            ("notes.txt", r"C:\Docs\notes.txt"),
+        ]);
+        set_web_search_templates(&db, &[]).unwrap();
+        let options = SearchOptions {
+            min_score: 0.0,
+            ..Default::default()
+        };
+        let kept = FilenameSnapshot::default();
+        // A fresh snapshot re-reads every row, like the fuzzy pass did
+        // before it had one
+        let compare = |query: &str| {
+            let summary = |snapshot: &FilenameSnapshot| -> Vec<(String, String, i64)> {
+                search(&db, snapshot, query, &options, &|| false)
+                    .unwrap()
+                    .results
+                    .into_iter()
+                    .map(|r| (r.filename, r.filepath, r.score as i64))
+                    .collect()
+            };
+            let fresh = summary(&FilenameSnapshot::default());
+            assert_eq!(summary(&kept), fresh, "query {:?}", query);
+            fresh
+        };
+        assert!(!compare("qrtrt").is_empty());
+
+        // Hiding one file and adding another keeps the row count
+        db.set_hidden(r"C:\Docs\quiet retreat.txt", true).unwrap();
+        db.upsert_files_batch(&[(
+            "quota tracker.xlsx".to_string(),
+            r"C:\Docs\quota tracker.xlsx".to_string(),
+            "xlsx".to_string(),
+            1024,
+            0,
+            "other".to_string(),
+        )])
+        .unwrap();
+        let names: Vec<String> = compare("qtatrk").into_iter().map(|(name, _, _)| name).collect();
+        assert!(names.contains(&"quota tracker.xlsx".to_string()));
+        assert!(!names.contains(&"quiet retreat.txt".to_string()));
+
+        // So does a rename
+        db.remove_path(r"C:\Docs\quarterly report.pdf").unwrap();
+        db.upsert_files_batch(&[(
+            "annual report.pdf".to_string(),
+            r"C:\Docs\annual report.pdf".to_string(),
+            "pdf".to_string(),
+            1024,
+            0,
+            "other".to_string(),
+        )])
+        .unwrap();
+        let names: Vec<String> = compare("anlrpt").into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(names.first().map(String::as_str), Some("annual report.pdf"));
+        compare("qrtrt");
+    }
+
+    #[test]
+    fn test_path_matches_and_locations_are_folded() {
+        let db = fixture_db(&[
+            ("notes.txt", r"C:\Users\Zoë\Café\notes.txt"),
+            ("notes.txt", r"C:\Work\notes.txt"),
+        ]);
+        let paths = |query: &str| -> Vec<String> {
+            let parsed = parse_query(query);
+            let results = db.search_files(&fold_text(&parsed.text), &parsed.filters, 10).unwrap();
+            results.into_iter().map(|entry| entry.filepath).collect()
+        };
+        assert_eq!(paths("cafe"), vec![r"C:\Users\Zoë\Café\notes.txt"]);
+        assert_eq!(paths("notes in:zoe"), vec![r"C:\Users\Zoë\Café\notes.txt"]);
+        assert_eq!(paths(r"notes in:c:\users\zoë"), vec![r"C:\Users\Zoë\Café\notes.txt"]);
+        assert_eq!(paths("notes -in:CAFÉ"), vec![r"C:\Work\notes.txt"]);
+    }
+
+    #[test]
+    fn test_empty_query_returns_home_sections() {
+        let files: Vec<(String, String)> = (0..8)
+            .map(|i| (format!("App {}.lnk", i), format!(r"C:\Apps\App {}.lnk", i)))
+            .collect();
+        let refs: Vec<(&str, &str)> = files.iter().map(|(n, p)| (n.as_str(), p.as_str())).collect();
+        let db = fixture_db(&refs);
+        for (i, (_, path)) in files.iter().enumerate() {
+            for _ in 0..i {
+                db.record_click(path).unwrap();
+            }
+        }
+        db.set_pinned(r"C:\Apps\App 0.lnk", true).unwrap();
+        db.set_hidden(r"C:\Apps\App 7.lnk", true).unwrap();
+
+        let results = search(&db, &FilenameSnapshot::default(), "  ", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        let sections: Vec<&str> = results.iter().map(|r| r.section.as_deref().unwrap()).collect();
+        assert_eq!(results[0].filename, "App 0.lnk");
+        assert_eq!(sections.iter().filter(|s| **s == "pinned").count(), 1);
+        assert_eq!(sections.iter().filter(|s| **s == "recent").count(), HOME_SECTION_SIZE);
+        // Everything launched already shows under "recent" except the least used
+        assert_eq!(sections.iter().filter(|s| **s == "frequent").count(), 1);
+        assert!(results.iter().all(|r| r.filename != "App 7.lnk"));
+        let ids: HashSet<i64> = results.iter().map(|r| r.id).collect();
+        assert_eq!(ids.len(), results.len());
+
+        set_home_view_enabled(&db, false).unwrap();
+        let results = search(&db, &FilenameSnapshot::default(), "", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_command_prefix_lists_launcher_commands() {
+        let db = fixture_db(&[("rebuild notes.txt", r"C:\Users\me\rebuild notes.txt")]);
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+        };
+
+        let all = run(">");
+        assert_eq!(all.len(), LAUNCHER_COMMANDS.len());
+        assert!(all.iter().all(|r| r.match_type == "command" && r.id < 0));
+
+        let rebuild = run(">rebuild");
+        assert_eq!(rebuild.len(), 1);
+        assert_eq!(rebuild[0].filename, "Rebuild index");
+        assert_eq!(rebuild[0].filepath, "rebuild");
+        assert_eq!(rebuild[0].matched_indices, (0..7).collect::<Vec<_>>());
+        assert!(find_launcher_command(&rebuild[0].filepath).is_some());
+
+        assert_eq!(run("> idx stats")[0].filepath, "index_stats");
+
+        let missing = run(">frobnicate");
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].match_type, "no_command");
+        assert_eq!(missing[0].filename, "No such command: ‘frobnicate’");
+    }
+
+    #[test]
+    fn test_settings_pages_match_names_and_keywords() {
+        let db = fixture_db(&[("bluetooth driver.zip", r"C:\Users\me\bluetooth driver.zip")]);
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+        };
+
+        let bluetooth = run("bluetooth");
+        assert_eq!(bluetooth[0].match_type, "setting");
+        assert_eq!(bluetooth[0].filepath, "ms-settings:bluetooth");
+        assert_eq!(bluetooth[0].parent_dir, "Settings");
+        assert!(bluetooth.iter().any(|r| r.filename == "bluetooth driver.zip"));
+
+        assert_eq!(run("display settings")[0].filepath, "ms-settings:display");
+        let programs = run("add remove programs");
+        assert_eq!(programs[0].filepath, "control.exe /name Microsoft.ProgramsAndFeatures");
+        assert_eq!(programs[0].parent_dir, "Control Panel");
+        // Fuzzy, through the same scorer as file names
+        assert!(run("blutooth").iter().any(|r| r.filepath == "ms-settings:bluetooth"));
+        assert!(run("notes").iter().all(|r| r.match_type != "setting"));
+        assert!(run("bluetooth ext:zip").iter().all(|r| r.match_type != "setting"));
+    }
+
+    #[test]
+    fn test_system_actions_match_names_and_keywords() {
+        let db = fixture_db(&[
+            ("lockfile.txt", r"C:\Users\me\lockfile.txt"),
+            ("Reboot notes.txt", r"C:\Users\me\Reboot notes.txt"),
+        ]);
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+        };
+
+        let lock = run("lock");
+        assert_eq!(lock[0].match_type, "action");
+        assert_eq!(lock[0].filepath, "lock");
+        assert_eq!(lock[0].parent_dir, "Lock this PC");
+        assert_eq!(lock[0].matched_indices, vec![0, 1, 2, 3]);
+        assert!(lock.iter().any(|r| r.filename == "lockfile.txt"));
+
+        let reboot = run("reboot");
+        let restart = reboot.iter().find(|r| r.filepath == "restart").unwrap();
+        assert_eq!(restart.filename, "Restart");
+        assert!(restart.matched_indices.is_empty());
+
+        assert_eq!(run("recycle")[0].filepath, "empty_recycle_bin");
+        assert!(run("shut")
+            .iter()
+            .any(|r| r.match_type == "action" && r.filepath == "shutdown"));
+        // Filtered searches are about files
+        assert!(run("lock ext:txt").iter().all(|r| r.match_type != "action"));
+        assert!(run("quarterly").iter().all(|r| r.match_type != "action"));
+    }
+
+    #[test]
+    fn test_snippets_match_names_and_keywords() {
+        let db = fixture_db(&[("address book.xlsx", r"C:\Users\me\address book.xlsx")]);
+        let snippet = crate::snippets::prepare("Home address", &["street".to_string()], "1 Main St\nSpringfield").unwrap();
+        let id = db.add_snippet(&snippet).unwrap();
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+        };
+
+        let home = run("home");
+        assert_eq!(home[0].match_type, "snippet");
+        assert_eq!(home[0].filepath, id.to_string());
+        assert_eq!(home[0].parent_dir, "1 Main St…");
+        assert_eq!(home[0].matched_indices, vec![0, 1, 2, 3]);
+        assert!(run("address").iter().any(|r| r.match_type == "snippet"));
+        assert!(run("street").iter().any(|r| r.match_type == "snippet" && r.matched_indices.is_empty()));
+        assert!(run("home ext:txt").iter().all(|r| r.match_type != "snippet"));
+    }
+
+    #[test]
+    fn test_clipboard_queries() {
+        let db = fixture_db(&[("Clipchamp.lnk", r"C:\Apps\Clipchamp.lnk")]);
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+        };
+        assert_eq!(run("clip ")[0].match_type, "no_command");
+
+        crate::clipboard_history::set_enabled(&db, true).unwrap();
+        db.add_clipboard_entry("Invoice 2024-117\n  due Friday", 100).unwrap();
+        db.add_clipboard_entry("hunter2", 200).unwrap();
+        let all = run(">clipboard");
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].filename, "hunter2");
+        assert_eq!(all[1].filename, "Invoice 2024-117 due Friday");
+        assert!(all.iter().all(|r| r.match_type == "clipboard" && r.id < 0));
+
+        let invoice = run("clip friday invoice");
+        assert_eq!(invoice.len(), 1);
+        assert_eq!(db.clipboard_entry_text(invoice[0].filepath.parse().unwrap()).unwrap().unwrap(), "Invoice 2024-117\n  due Friday");
+        assert!(run("clip nothing").is_empty());
+        // Not clipboard queries
+        assert_eq!(run("clip")[0].filename, "Clipchamp.lnk");
+        assert!(run(">clipboardx").iter().all(|r| r.match_type != "clipboard"));
+    }
+
+    #[test]
+    fn test_open_windows_rank_above_their_app() {
+        let db = fixture_db(&[
+            ("chrome.exe", r"C:\Program Files\Google\Chrome\chrome.exe"),
+            ("Chrome Remote Desktop.lnk", r"C:\Users\me\Chrome Remote Desktop.lnk"),
+        ]);
+        for _ in 0..20 {
+            db.record_click(r"C:\Program Files\Google\Chrome\chrome.exe").unwrap();
+        }
+        let window = |hwnd: isize, title: &str, pid: u32, exe_name: &str| OpenWindow {
+            hwnd,
+            title: title.to_string(),
+            pid,
+            exe_name: exe_name.to_string(),
+        };
+        let options = SearchOptions {
+            open_windows: Arc::new(vec![
+                window(0x1a2b, "Inbox - Gmail - Google Chrome", 40, "chrome.exe"),
+                window(0x3c4d, "notes.txt - Notepad", 41, "notepad.exe"),
+            ]),
+            ..SearchOptions::default()
+        };
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &options, &|| false)
+                .unwrap()
+                .results
+        };
+
+        let chrome = run("chrome");
+        assert_eq!(chrome[0].match_type, "window");
+        assert_eq!(chrome[0].filepath, "6699");
+        assert_eq!(chrome[0].parent_dir, "chrome.exe");
+        assert_eq!(chrome[0].running_pids, vec![40]);
+        assert_eq!(chrome[1].filename, "chrome.exe");
+
+        // Matches on the title too, highlighting it
+        let gmail = run("gmail");
+        assert_eq!(gmail[0].filename, "Inbox - Gmail - Google Chrome");
+        assert_eq!(gmail[0].matched_indices, vec![8, 9, 10, 11, 12]);
+        assert_eq!(run("notepad")[0].filepath, "15437");
+
+        assert!(run("chrome ext:exe").iter().all(|r| r.match_type != "window"));
+        assert!(run("quarterly").iter().all(|r| r.match_type != "window"));
+    }
+
+    #[test]
+    fn test_emoji_queries() {
+        let db = fixture_db(&[("fire drill.pdf", r"C:\Users\me\fire drill.pdf")]);
+        let run = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+        };
+
+        let fire = run(":fire");
+        assert_eq!(fire[0].filename, "🔥");
+        assert_eq!(fire[0].match_type, "emoji");
+        assert_eq!(fire[0].filepath, "🔥");
+        assert!(fire.len() <= EMOJI_RESULT_LIMIT);
+        assert!(fire.iter().all(|r| r.match_type == "emoji"));
+
+        assert_eq!(run("emoji rocket")[0].filename, "🚀");
+        assert_eq!(run("Emoji thumbs up")[0].filename, "👍");
+        assert!(run(":lol").iter().any(|r| r.filename == "😂"));
+        assert!(run(":qqqzzz").is_empty());
+        // Skin-tone modifiers in the query don't break matching
+        assert!(run(":👍🏽").iter().all(|r| r.match_type == "emoji"));
+
+        // Not emoji queries
+        assert_eq!(emoji_query(":"), None);
+        assert_eq!(emoji_query("emoji"), None);
+        assert_eq!(emoji_query("emojis.txt"), None);
+        assert_eq!(run("fire")[0].filename, "fire drill.pdf");
+    }
+
+    #[test]
+    fn test_detect_url() {
+        assert_eq!(detect_url("github.com/rust-lang"), Some("https://github.com/rust-lang".to_string()));
+        assert_eq!(detect_url("https://example.org"), Some("https://example.org".to_string()));
+        assert_eq!(detect_url("www.example.xyz"), Some("https://www.example.xyz".to_string()));
+        assert_eq!(detect_url("localhost:3000"), Some("https://localhost:3000".to_string()));
+        assert_eq!(detect_url("docs.rs/serde"), Some("https://docs.rs/serde".to_string()));
+        // File names and plain words are not URLs
+        assert_eq!(detect_url("readme.md"), None);
+        assert_eq!(detect_url("main.rs"), None);
+        assert_eq!(detect_url("setup.exe"), None);
+        assert_eq!(detect_url("notepad"), None);
+        assert_eq!(detect_url("ftp://example.com"), None);
+        assert_eq!(detect_url("github.com rust"), None);
+    }
+
+    #[test]
+    fn test_url_encode() {
+        assert_eq!(url_encode("rust & go"), "rust%20%26%20go");
+        assert_eq!(url_encode("naïve"), "na%C3%AFve");
+        assert_eq!(url_encode("a-b_c.d~e"), "a-b_c.d~e");
+    }
+
+    #[test]
+    fn test_url_query_gets_browser_result_first() {
+        let db = fixture_db(&[("github.com.url", r"C:\Users\me\Favorites\github.com.url")]);
+        let results = search(&db, &FilenameSnapshot::default(), "github.com", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].match_type, "url");
+        assert_eq!(results[0].filepath, "https://github.com");
+        assert_eq!(results[1].filename, "github.com.url");
+    }
+
+    #[test]
+    fn test_web_search_fallback_when_nothing_matches() {
+        let db = fixture_db(&[("notes.txt", r"C:\notes.txt")]);
+        let results = search(&db, &FilenameSnapshot::default(), "zzq rust", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        let targets: Vec<&str> = results.iter().map(|r| r.filepath.as_str()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                "https://www.google.com/search?q=zzq%20rust",
+                "https://www.youtube.com/results?search_query=zzq%20rust",
+            ]
+        );
+        assert!(results.iter().all(|r| r.match_type == "web_search" && r.id < 0));
+
+        set_web_search_templates(
+            &db,
+            &[WebSearchTemplate {
+                name: "DuckDuckGo".to_string(),
+                url: "https://duckduckgo.com/?q={query}".to_string(),
+            }],
+        )
+        .unwrap();
+        let results = search(&db, &FilenameSnapshot::default(), "zzq", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].filename, "Search DuckDuckGo for ‘zzq’");
+    }
+
+    #[test]
+    fn test_web_search_templates_are_validated() {
+        let db = fixture_db(&[]);
+        let bad = WebSearchTemplate {
+            name: "Bad".to_string(),
+            url: "https://example.com/search".to_string(),
+        };
+        assert!(set_web_search_templates(&db, &[bad]).is_err());
+        assert_eq!(web_search_templates(&db), default_web_searches());
+    }
+
+    #[test]
+    fn test_min_score_drops_scattered_fuzzy_noise() {
+        let db = fixture_db(&[
+            ("jazz equinox.mp3", r"C:\Users\me\Music\jazz equinox.mp3"),
+            ("dizzy-requirements-export.docx", r"C:\Users\me\dizzy-requirements-export.docx"),
+            ("puzzles and frequent taxes.pdf", r"C:\Users\me\puzzles and frequent taxes.pdf"),
+            ("notes.txt", r"C:\Users\me\notes.txt"),
+            ("Google Chrome.lnk", r"C:\Apps\Google Chrome.lnk"),
+            ("Visual Studio Code.lnk", r"C:\Apps\Visual Studio Code.lnk"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let lenient = SearchOptions {
+            min_score: 0.0,
+            ..Default::default()
+        };
+        let noisy = search(&db, &snapshot, "zzqx", &lenient, &|| false).unwrap().results;
+        assert_eq!(noisy.len(), 3, "skim should weakly match the fixture");
+
+        // The web-search fallback is disabled so only indexed files are counted
+        set_web_search_templates(&db, &[]).unwrap();
+        let results = search(&db, &snapshot, "zzqx", &SearchOptions::default(), &|| false).unwrap().results;
+        assert!(results.is_empty());
+
+        // Single-character and strong fuzzy queries are unaffected
+        let results = search(&db, &snapshot, "n", &SearchOptions::default(), &|| false).unwrap().results;
+        assert_eq!(results[0].filename, "notes.txt");
+        let results = search(&db, &snapshot, "vsc", &SearchOptions::default(), &|| false).unwrap().results;
+        assert_eq!(results[0].filename, "Visual Studio Code.lnk");
+        let results = search(&db, &snapshot, "chrm", &SearchOptions::default(), &|| false).unwrap().results;
+        assert_eq!(results[0].filename, "Google Chrome.lnk");
+    }
+
+    #[test]
+    fn test_min_score_ignores_usage_boosts() {
+        let db = fixture_db(&[("jazz equinox.mp3", r"C:\Users\me\Music\jazz equinox.mp3")]);
+        set_web_search_templates(&db, &[]).unwrap();
+        for _ in 0..50 {
+            db.record_click(r"C:\Users\me\Music\jazz equinox.mp3").unwrap();
+        }
+        let results = search(&db, &FilenameSnapshot::default(), "zzqx", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_min_score_setting_round_trip() {
+        let db = fixture_db(&[]);
+        assert_eq!(default_min_score(&db), DEFAULT_MIN_SCORE);
+        assert_eq!(set_default_min_score(&db, 5000.0).unwrap(), SCORE_EXACT);
+        assert_eq!(set_default_min_score(&db, 220.0).unwrap(), 220.0);
+        assert_eq!(default_min_score(&db), 220.0);
+    }
+
+    #[test]
+    fn test_ranking_prefers_matching_extension() {
+        let db = fixture_db(&[
+            ("report.pdf_old_versions", r"C:\Users\me\report.pdf_old_versions"),
+            ("report.pdf.docx", r"C:\Users\me\report.pdf.docx"),
+            ("q3 report.pdf", r"C:\Users\me\q3 report.pdf"),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "report.pdf", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        // Without the extension adjustment both prefix matches would outrank the real PDF
+        assert_eq!(results[0].filename, "q3 report.pdf");
+    }
+
+    #[test]
+    fn test_exact_stem_strips_only_final_extension() {
+        let db = fixture_db(&[
+            ("my.notes.v2.txt", r"C:\Users\me\my.notes.v2.txt"),
+            ("my.notes.v2 backup.txt", r"C:\Users\me\my.notes.v2 backup.txt"),
+        ]);
+        let options = SearchOptions {
+            explain: true,
+            ..Default::default()
+        };
+        let results = search(&db, &FilenameSnapshot::default(), "my.notes.v2", &options, &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].filename, "my.notes.v2.txt");
+        assert_eq!(results[0].score_breakdown.as_ref().unwrap().branch, "exact_stem");
+
+        // "my" alone is no longer treated as the stem of "my.notes.v2.txt"
+        let results = search(&db, &FilenameSnapshot::default(), "my", &options, &|| false)
+            .unwrap()
+            .results;
+        assert!(results.iter().all(|r| r.score_breakdown.as_ref().unwrap().branch == "prefix"));
+    }
+
+    #[test]
+    fn test_parent_dir() {
+        assert_eq!(parent_dir(r"C:\Users\me\notes.txt"), r"C:\Users\me");
+        assert_eq!(parent_dir(r"C:\pagefile.sys"), r"C:\");
+        assert_eq!(parent_dir("notes.txt"), "");
+        assert_eq!(result_parent_dir("https://deploy.internal/runs?id=4", "bookmark"), "deploy.internal");
+        assert_eq!(result_parent_dir("https://example.com", "url"), "");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(4_404_019), "4.2 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = 1_700_000_000;
+        assert_eq!(format_relative_time(0, now), "");
+        assert_eq!(format_relative_time(now - 30, now), "just now");
+        assert_eq!(format_relative_time(now - 60, now), "1 minute ago");
+        assert_eq!(format_relative_time(now - 5 * 3_600, now), "5 hours ago");
+        assert_eq!(format_relative_time(now - 86_400, now), "yesterday");
+        assert_eq!(format_relative_time(now - 3 * 86_400, now), "3 days ago");
+        assert_eq!(format_relative_time(now - 21 * 86_400, now), "3 weeks ago");
+        assert_eq!(format_relative_time(now - 400 * 86_400, now), "1 year ago");
+        // Clock skew: a file "from the future" is just new
+        assert_eq!(format_relative_time(now + 100, now), "just now");
+    }
+
+    #[test]
+    fn test_fuzzy_results_carry_full_metadata() {
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        db.upsert_files_batch(&[(
+            "Visual Studio Code.lnk".to_string(),
+            r"C:\Apps\Visual Studio Code.lnk".to_string(),
+            "lnk".to_string(),
+            2_048,
+            1_600_000_000,
+            "shortcut".to_string(),
+        )])
+        .unwrap();
+        let db = Arc::new(db);
+        let results = search(&db, &FilenameSnapshot::default(), "vsc", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].match_type, "fuzzy");
+        assert_eq!(results[0].extension, "lnk");
+        assert_eq!(results[0].file_size, 2_048);
+        assert_eq!(results[0].file_size_display, "2.0 KB");
+        assert_eq!(results[0].parent_dir, r"C:\Apps");
+        assert!(results[0].modified_display.ends_with("ago"));
+
+        let exact_only = SearchOptions {
+            fuzzy: false,
+            metrics: true,
+            ..Default::default()
+        };
+        let response = search(&db, &FilenameSnapshot::default(), "vsc", &exact_only, &|| false).unwrap();
+        assert!(response.results.iter().all(|r| r.match_type != "fuzzy"));
+        assert!(!response.meta.unwrap().fuzzy_ran);
+    }
+
+    #[test]
+    fn test_search_result_serialization_shape() {
+        let db = fixture_db(&[("notes.txt", r"C:\Users\me\notes.txt")]);
+        let result = search(&db, &FilenameSnapshot::default(), "notes", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results
+            .remove(0);
+        let value = serde_json::to_value(&result).unwrap();
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec![
+                "click_count",
+                "extension",
+                "file_size",
+                "file_size_display",
+                "file_type",
+                "filename",
+                "filepath",
+                "id",
+                "last_accessed",
+                "match_type",
+                "matched_indices",
+                "modified_at",
+                "modified_display",
+                "parent_dir",
+                "score",
+            ]
+        );
+        assert_eq!(value["parent_dir"], r"C:\Users\me");
+    }
+
+    /// Index rows with explicit size and mtime, for the duplicate heuristic.
+    fn fixture_db_with_metadata(files: &[(&str, &str, i64, i64)]) -> Arc<Database> {
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        let rows: Vec<(String, String, String, i64, i64, String)> = files
+            .iter()
+            .map(|(filename, filepath, size, modified)| {
+                let extension = "lnk".to_string();
+                (filename.to_string(), filepath.to_string(), extension, *size, *modified, "shortcut".to_string())
+            })
+            .collect();
+        db.upsert_files_batch(&rows).unwrap();
+        Arc::new(db)
+    }
+
+    #[test]
+    fn test_dedupe_merges_copies_of_the_same_shortcut() {
+        let db = fixture_db_with_metadata(&[
+            ("Slack.lnk", r"C:\Users\me\Desktop\Slack.lnk", 2_100, 1_700_000_000),
+            ("Slack.lnk", r"C:\ProgramData\Microsoft\Windows\Start Menu\Programs\Slack.lnk", 2_100, 1_700_000_000),
+            ("Slack.lnk", r"C:\Users\me\AppData\Roaming\Microsoft\Windows\Start Menu\Programs\Slack.lnk", 2_100, 1_700_000_000),
+            ("Slack Helper.lnk", r"C:\Users\me\Desktop\Slack Helper.lnk", 900, 1_600_000_000),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "slack", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].filename, "Slack.lnk");
+        assert_eq!(results[0].duplicates.len(), 2);
+        assert!(!results[0].duplicates.contains(&results[0].filepath));
+        assert!(results[1].duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_dedupe_prefers_launched_copy_on_tie() {
+        let desktop = r"C:\Users\me\Desktop\Slack.lnk";
+        let start_menu = r"C:\ProgramData\Microsoft\Windows\Start Menu\Programs\Slack.lnk";
+        let db = fixture_db_with_metadata(&[
+            ("Slack.lnk", desktop, 2_100, 1_700_000_000),
+            ("Slack.lnk", start_menu, 2_100, 1_700_000_000),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "slack", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+
+        // Equal scores: the copy with more launches represents the group
+        let template = SearchResult {
+            duplicates: Vec::new(),
+            ..results[0].clone()
+        };
+        let unused = SearchResult {
+            filepath: desktop.to_string(),
+            click_count: 0,
+            ..template.clone()
+        };
+        let launched = SearchResult {
+            id: template.id + 1,
+            filepath: start_menu.to_string(),
+            click_count: 5,
+            ..template
+        };
+        let deduped = dedupe_results(vec![unused.clone(), launched.clone()], &HashSet::new());
+        assert_eq!(deduped.len(), 1);
+        assert_eq!(deduped[0].filepath, start_menu);
+        assert_eq!(deduped[0].duplicates, vec![desktop.to_string()]);
+
+        // A pinned copy wins the tie over launches, in either order
+        let pinned = HashSet::from([desktop.to_string()]);
+        for results in [vec![unused.clone(), launched.clone()], vec![launched, unused]] {
+            let deduped = dedupe_results(results, &pinned);
+            assert_eq!(deduped.len(), 1);
+            assert_eq!(deduped[0].filepath, desktop);
+            assert_eq!(deduped[0].duplicates, vec![start_menu.to_string()]);
+        }
+        // Same through search, whichever copy is pinned
+        for path in [desktop, start_menu] {
+            db.set_pinned(path, true).unwrap();
+            let results = search(&db, &FilenameSnapshot::default(), "slack", &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results;
+            assert_eq!(results[0].filepath, path);
+            db.set_pinned(path, false).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dedupe_keeps_different_files_with_the_same_name() {
+        let db = fixture_db_with_metadata(&[
+            ("notes.lnk", r"C:\Users\me\work\notes.lnk", 1_200, 1_700_000_000),
+            ("notes.lnk", r"C:\Users\me\home\notes.lnk", 3_400, 1_650_000_000),
+            ("notes.lnk", r"C:\Users\me\old\notes.lnk", 0, 0),
+            ("notes.lnk", r"C:\Users\me\older\notes.lnk", 0, 0),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "notes", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|r| r.duplicates.is_empty()));
+    }
+
+    #[test]
+    fn test_pick_key() {
+        assert_eq!(pick_key("  Ex "), Some("ex".to_string()));
+        assert_eq!(pick_key("Résumé ext:pdf"), Some("resume".to_string()));
+        assert_eq!(pick_key("type:app"), None);
+    }
+
+    #[test]
+    fn test_personalization_learns_picked_result() {
+        let db = fixture_db(&[
+            ("explorer_backup_notes.txt", r"C:\Users\me\explorer_backup_notes.txt"),
+            ("Microsoft Excel.lnk", r"C:\ProgramData\Start Menu\Microsoft Excel.lnk"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let excel = r"C:\ProgramData\Start Menu\Microsoft Excel.lnk";
+        let before = search(&db, &snapshot, "ex", &SearchOptions::default(), &|| false).unwrap().results;
+        assert_ne!(before[0].filepath, excel);
+
+        db.record_query_pick(&pick_key("ex").unwrap(), excel).unwrap();
+        db.record_query_pick(&pick_key("ex").unwrap(), excel).unwrap();
+        let options = SearchOptions {
+            explain: true,
+            ..Default::default()
+        };
+        let after = search(&db, &snapshot, "ex", &options, &|| false).unwrap().results;
+        assert_eq!(after[0].filepath, excel);
+        assert!(after[0].score_breakdown.as_ref().unwrap().personal_boost > 0.0);
+
+        // A pick for "ex" also helps the longer "exc" and the shorter "e"
+        for query in ["exc", "e"] {
+            let results = search(&db, &snapshot, query, &options, &|| false).unwrap().results;
+            let excel_result = results.iter().find(|r| r.filepath == excel).unwrap();
+            assert!(excel_result.score_breakdown.as_ref().unwrap().personal_boost > 0.0, "{}", query);
+        }
+    }
 
-    // Step 2: Score SQL results first
-    let matcher = SkimMatcherV2::default();
-    let mut scored_results: Vec<SearchResult> = Vec::new();
-    let mut seen_ids: std::collections::HashSet<i64> = std::collections::HashSet::new();
+    #[test]
+    fn test_personalization_never_beats_exact_match() {
+        let db = fixture_db(&[
+            ("code", r"C:\Apps\code"),
+            ("code review notes.txt", r"C:\Users\me\code review notes.txt"),
+        ]);
+        for _ in 0..20 {
+            db.record_query_pick("code", r"C:\Users\me\code review notes.txt").unwrap();
+        }
+        let results = search(&db, &FilenameSnapshot::default(), "code", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results[0].filename, "code");
+        assert!(results[1].score < results[0].score);
+    }
 
-    // Process SQL results first (these are prefix/substring matches)
-    for entry in &sql_results {
-        let (score, match_type, indices) = score_entry(entry, &query_lower, &matcher);
-        seen_ids.insert(entry.id);
-        scored_results.push(SearchResult {
-            id: entry.id,
-            filename: entry.filename.clone(),
-            filepath: entry.filepath.clone(),
-            extension: entry.extension.clone(),
-            file_size: entry.file_size,
-            modified_at: entry.modified_at,
-            file_type: entry.file_type.clone(),
-            click_count: entry.click_count,
-            last_accessed: entry.last_accessed,
-            score,
-            match_type,
-            matched_indices: indices,
-        });
+    #[test]
+    fn test_personal_boost_decays_with_age() {
+        let now = 1_700_000_000;
+        let pick = |last_used| QueryPick {
+            query: "ex".to_string(),
+            filepath: "excel".to_string(),
+            pick_count: 3,
+            last_used,
+        };
+        let fresh = personal_boosts(&[pick(now)], "ex", now)["excel"];
+        let old = personal_boosts(&[pick(now - 90 * 86_400)], "ex", now)["excel"];
+        assert!(fresh > old * 2.0);
+        assert!(fresh < PERSONAL_BOOST_MAX);
     }
 
-    // Step 3: Only do expensive fuzzy scan if SQL didn't return enough good results
-    // This avoids loading 100K+ filenames into memory on every keystroke
-    if scored_results.len() < max_results {
-    let all_files = db
-        .get_all_filenames()
-        .map_err(|e| format!("Failed to get filenames: {}", e))?;
+    #[test]
+    fn test_candidate_pool_narrows_to_the_same_results() {
+        let db = fixture_db(&[
+            ("Chrome.lnk", r"C:\Start Menu\Chrome.lnk"),
+            ("Chromebook notes.txt", r"C:\Docs\Chromebook notes.txt"),
+            ("chrome_setup.exe", r"C:\Downloads\chrome_setup.exe"),
+            ("Notes.txt", r"C:\Projects\chromium\Notes.txt"),
+            // Only the typo pass finds this one for "chrome"
+            ("Chrmoe typo.txt", r"C:\Docs\Chrmoe typo.txt"),
+            ("Calendar.exe", r"C:\Apps\Calendar.exe"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let options = SearchOptions {
+            metrics: true,
+            ..SearchOptions::default()
+        };
+        let ranked = |response: SearchResponse| -> Vec<(String, f64)> {
+            response.results.into_iter().map(|r| (r.filename, r.score)).collect()
+        };
 
-    for (id, filename, filepath, file_type, click_count, last_accessed, modified_at) in &all_files {
-        if seen_ids.contains(id) {
-            continue;
+        let mut parent: Option<CandidatePool> = None;
+        let mut sizes = Vec::new();
+        for query in ["chr", "chro", "chrom", "chrome"] {
+            let pool = candidate_pool(&db, &snapshot, query, &options, parent.as_ref()).unwrap().unwrap();
+            sizes.push(pool.len());
+            let pooled = search_with_candidates(&db, &snapshot, query, &options, Some(&pool), &|| false).unwrap();
+            // Only a pool narrowed from the previous query's counts as reused
+            assert_eq!(pooled.meta.as_ref().unwrap().reused_candidates, parent.is_some(), "{}", query);
+            let fresh = search(&db, &snapshot, query, &options, &|| false).unwrap();
+            assert_eq!(ranked(pooled), ranked(fresh), "{}", query);
+            parent = Some(pool);
         }
+        assert!(sizes.windows(2).all(|pair| pair[1] <= pair[0]));
+        // The three Chrome files; "chromium" no longer matches
+        assert_eq!(sizes.last(), Some(&3));
 
-        // Fuzzy match against filename
-        if let Some(fuzzy_score) = matcher.fuzzy_match(&filename.to_lowercase(), &query_lower) {
-            if fuzzy_score > 0 {
-                let indices = matcher
-                    .fuzzy_indices(&filename.to_lowercase(), &query_lower)
-                    .map(|(_, indices)| indices)
-                    .unwrap_or_default();
-
-                let base_score = fuzzy_score as f64;
-                let type_boost = file_type_boost(file_type);
-                let usage_boost = usage_boost(*click_count, *last_accessed);
-                let final_score = base_score * 0.5 + type_boost + usage_boost; // fuzzy gets 0.5x weight
-
-                seen_ids.insert(*id);
-                scored_results.push(SearchResult {
-                    id: *id,
-                    filename: filename.clone(),
-                    filepath: filepath.clone(),
-                    extension: String::new(),
-                    file_size: 0,
-                    modified_at: *modified_at,
-                    file_type: file_type.clone(),
-                    click_count: *click_count,
-                    last_accessed: *last_accessed,
-                    score: final_score,
-                    match_type: "fuzzy".to_string(),
-                    matched_indices: indices,
-                });
-            }
+        // Queries that don't search by text get no pool
+        for query in ["ch", "chr*", ">clipboard", ""] {
+            assert!(candidate_pool(&db, &snapshot, query, &options, None).unwrap().is_none(), "{}", query);
         }
+
+        // A refreshed snapshot makes the pool stale
+        let pool = parent.unwrap();
+        snapshot.refresh(&db).unwrap();
+        let stale = search_with_candidates(&db, &snapshot, "chrome", &options, Some(&pool), &|| false).unwrap();
+        assert!(!stale.meta.unwrap().reused_candidates);
     }
-    } // end fuzzy scan conditional
 
-    // Sort by score descending
-    scored_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    #[test]
+    fn test_pool_substring_matches_agree_with_sql() {
+        const KB: i64 = 1024;
+        const MB: i64 = 1024 * KB;
+        let db = Database::open(&std::path::PathBuf::from(":memory:")).unwrap();
+        let rows: Vec<(String, String, String, i64, i64, String)> = [
+            ("report.pdf", r"C:\Docs\report.pdf", 2 * MB, 1_700_000_000, "document"),
+            ("Report Q3.docx", r"C:\Docs\Work\Report Q3.docx", 50 * KB, 1_690_000_000, "document"),
+            ("report tool.exe", r"C:\Apps\report tool.exe", 5 * MB, 1_650_000_000, "app"),
+            ("reporting.lnk", r"C:\Start Menu\reporting.lnk", 2 * KB, 1_600_000_000, "shortcut"),
+            ("notes.txt", r"C:\Reports\notes.txt", KB, 1_710_000_000, "document"),
+            ("old report.pdf", r"C:\Archive\old report.pdf", 10 * MB, 1_500_000_000, "document"),
+            ("report", r"C:\Docs\report", 0, 1_680_000_000, "folder"),
+            ("myreport.txt", r"C:\Docs\myreport.txt", 300, 1_705_000_000, "other"),
+            ("REPORT draft.txt", r"C:\Docs\REPORT draft.txt", 4 * KB, 1_660_000_000, "document"),
+        ]
+        .iter()
+        .map(|&(name, path, size, modified, file_type)| {
+            let extension = Path::new(name).extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+            (name.to_string(), path.to_string(), extension, size, modified, file_type.to_string())
+        })
+        .collect();
+        db.upsert_files_batch(&rows).unwrap();
+        db.record_click_at(r"C:\Docs\Work\Report Q3.docx", 1_700_000_000).unwrap();
+        let snapshot = FilenameSnapshot::default();
 
-    // Return top N results
-    scored_results.truncate(max_results);
-    Ok(scored_results)
-}
+        for query in [
+            "report",
+            "report ext:pdf",
+            "report -ext:pdf ext:txt",
+            "report type:document",
+            "report -type:app -type:folder",
+            "report size:>1mb",
+            "report size:<10kb",
+            "report modified:<2023-06-01",
+            "report modified:>2023-06-01",
+            "report in:docs",
+            "report -in:archive",
+            "cs:report",
+            "cs:REPORT",
+            "report \"Q3\"",
+        ] {
+            let parsed = parse_query(query);
+            assert!(parsed.errors.is_empty(), "{}: {:?}", query, parsed.errors);
+            let query_folded = fold_text(&parsed.text);
+            let pool = CandidatePool::build(snapshot.load(&db).unwrap(), &query_folded, false, None);
+            for limit in [100, 3] {
+                let pooled: Vec<String> = pool
+                    .substring_matches(&query_folded, &parsed.filters, limit)
+                    .into_iter()
+                    .map(|entry| entry.filepath)
+                    .collect();
+                // SQL only checks required terms by containment; search
+                // drops the rest afterwards, as here
+                let from_sql: Vec<String> = db
+                    .search_files(&query_folded, &parsed.filters, limit)
+                    .unwrap()
+                    .into_iter()
+                    .filter(|entry| parsed.filters.matches_required_terms(&entry.filename, &entry.display_name))
+                    .map(|entry| entry.filepath)
+                    .collect();
+                assert!(!pooled.is_empty(), "{}", query);
+                assert_eq!(pooled, from_sql, "{} (limit {})", query, limit);
+            }
+        }
+    }
 
-/// Compute a composite score for a FileEntry based on how well it matches the query.
-fn score_entry(
-    entry: &FileEntry,
-    query_lower: &str,
-    matcher: &SkimMatcherV2,
-) -> (f64, String, Vec<usize>) {
-    let filename_lower = entry.filename.to_lowercase();
-    let filepath_lower = entry.filepath.to_lowercase();
+    #[test]
+    fn test_context_boost_applies_only_in_its_app() {
+        let db = fixture_db(&[
+            ("report draft.txt", r"C:\Users\me\report draft.txt"),
+            ("report final.docx", r"C:\Users\me\report final.docx"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let final_report = r"C:\Users\me\report final.docx";
+        let before = search(&db, &snapshot, "report", &SearchOptions::default(), &|| false).unwrap().results;
+        assert_ne!(before[0].filepath, final_report);
 
-    let mut best_score: f64 = 0.0;
-    let mut match_type = "none".to_string();
-    let mut matched_indices: Vec<usize> = Vec::new();
+        db.record_context_pick("slack.exe", final_report).unwrap();
+        db.record_context_pick("slack.exe", final_report).unwrap();
+        let in_app = |app: &str| SearchOptions {
+            explain: true,
+            context_app: Some(app.to_string()),
+            ..Default::default()
+        };
+        let slack = search(&db, &snapshot, "report", &in_app("slack.exe"), &|| false).unwrap().results;
+        assert_eq!(slack[0].filepath, final_report);
+        assert!(slack[0].score_breakdown.as_ref().unwrap().context_boost > 0.0);
 
-    // Exact match (highest priority)
-    if filename_lower == *query_lower {
-        best_score = 1000.0;
-        match_type = "exact".to_string();
-        matched_indices = (0..entry.filename.len()).collect();
+        // Other apps, and searches without a context, rank as before
+        let code = search(&db, &snapshot, "report", &in_app("code.exe"), &|| false).unwrap().results;
+        assert_eq!(code[0].filepath, before[0].filepath);
+        let none = search(&db, &snapshot, "report", &SearchOptions::default(), &|| false).unwrap().results;
+        assert_eq!(none[0].filepath, before[0].filepath);
     }
-    // Exact match without extension
-    else if filename_lower.split('.').next().unwrap_or("") == query_lower {
-        best_score = 950.0;
-        match_type = "exact".to_string();
-        matched_indices = (0..query_lower.len()).collect();
+
+    #[test]
+    fn test_query_picks_table_is_capped() {
+        let db = fixture_db(&[]);
+        for i in 0..2010 {
+            db.record_query_pick("q", &format!(r"C:\file{}.txt", i)).unwrap();
+        }
+        assert_eq!(db.query_picks_for("q").unwrap().len(), 2000);
     }
-    // Prefix match
-    else if filename_lower.starts_with(query_lower) {
-        best_score = 800.0;
-        match_type = "prefix".to_string();
-        matched_indices = (0..query_lower.len()).collect();
+
+    #[test]
+    fn test_wildcard_query_matches_glob() {
+        let db = fixture_db(&[
+            ("Report_2024_final.xlsx", r"C:\Users\me\Report_2024_final.xlsx"),
+            ("quarterly report 2024 notes.xlsx", r"C:\Users\me\quarterly report 2024 notes.xlsx"),
+            ("report 2023.xlsx", r"C:\Users\me\report 2023.xlsx"),
+            ("Report_2024_final.docx", r"C:\Users\me\Report_2024_final.docx"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let results = search(&db, &snapshot, "rep*2024*.xlsx", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        let names: Vec<&str> = results.iter().map(|r| r.filename.as_str()).collect();
+        // Matching from the start of the name ranks above matching inside it
+        assert_eq!(names, ["Report_2024_final.xlsx", "quarterly report 2024 notes.xlsx"]);
+        assert_eq!(results[0].match_type, "wildcard");
+        assert_eq!(results[0].matched_indices, [0, 1, 2, 7, 8, 9, 10, 17, 18, 19, 20, 21]);
+
+        // `?` is exactly one char
+        let results = search(&db, &snapshot, "report 202?.xlsx", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].filename, "report 2023.xlsx");
+
+        // No fuzzy fallback: "rpt" would fuzzy match every report
+        let results = search(&db, &snapshot, "rpt*.xlsx", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert!(results.iter().all(|r| r.id < 0), "{:?}", results);
     }
-    // Substring match in filename
-    else if let Some(pos) = filename_lower.find(query_lower) {
-        best_score = 600.0;
-        match_type = "substring".to_string();
-        matched_indices = (pos..pos + query_lower.len()).collect();
+
+    #[test]
+    fn test_wildcard_brackets_are_literal() {
+        let db = fixture_db(&[
+            ("notes [draft].txt", r"C:\Users\me\notes [draft].txt"),
+            ("notes d.txt", r"C:\Users\me\notes d.txt"),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "notes [d*t].txt", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].filename, "notes [draft].txt");
     }
-    // Path match (e.g., searching "docs/proj" matching path)
-    else if filepath_lower.contains(query_lower) {
-        best_score = 300.0;
-        match_type = "path".to_string();
+
+    #[test]
+    fn test_wildcard_only_query() {
+        let db = fixture_db(&[
+            ("a.pdf", r"C:\Users\me\a.pdf"),
+            ("b.pdf", r"C:\Users\me\b.pdf"),
+            ("c.txt", r"C:\Users\me\c.txt"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        // On its own it would list the whole index, so it is refused
+        let response = search(&db, &snapshot, "*?", &SearchOptions::default(), &|| false).unwrap();
+        assert!(response.results.is_empty());
+        assert_eq!(response.filter_errors.len(), 1);
+
+        // Next to filters it just means "anything"
+        let response = search(&db, &snapshot, "* ext:pdf", &SearchOptions::default(), &|| false).unwrap();
+        assert_eq!(response.results.len(), 2);
+        assert!(response.filter_errors.is_empty());
     }
-    // Fuzzy match on filename
-    else if let Some(fuzzy_result) = matcher.fuzzy_indices(&filename_lower, query_lower) {
-        best_score = (fuzzy_result.0 as f64).max(10.0);
-        match_type = "fuzzy".to_string();
-        matched_indices = fuzzy_result.1;
+
+    #[test]
+    fn test_wildcard_pattern_backtracks() {
+        let pattern = WildcardPattern::parse("a*b?d").unwrap();
+        let text: Vec<char> = "xxabbcabxd".chars().collect();
+        assert_eq!(pattern.find(&text), Some((2, vec![2, 7, 9])));
+        assert_eq!(pattern.find(&"abd".chars().collect::<Vec<_>>()), None);
+        assert_eq!(WildcardPattern::parse("plain"), None);
+        assert!(!WildcardPattern::parse("*?*").unwrap().has_literals());
     }
-    // Fuzzy match on filepath
-    else if let Some(fuzzy_result) = matcher.fuzzy_indices(&filepath_lower, query_lower) {
-        best_score = (fuzzy_result.0 as f64 * 0.5).max(5.0);
-        match_type = "path".to_string();
-        matched_indices = fuzzy_result.1;
+
+    #[test]
+    fn test_ranking_exact_prefix_fuzzy_path() {
+        let db = fixture_db(&[
+            ("readme.txt", r"C:\tools\vscode\readme.txt"),
+            ("VS Code.lnk", r"C:\Apps\VS Code.lnk"),
+            ("vscode-insiders.exe", r"C:\Apps\vscode-insiders.exe"),
+            ("vscode", r"C:\Apps\vscode"),
+        ]);
+        let results = search(&db, &FilenameSnapshot::default(), "vscode", &SearchOptions::default(), &|| false).unwrap().results;
+        let names: Vec<&str> = results.iter().map(|r| r.filename.as_str()).collect();
+        assert_eq!(names, vec!["vscode", "vscode-insiders.exe", "VS Code.lnk", "readme.txt"]);
+        assert_eq!(results[2].match_type, "fuzzy");
+        assert_eq!(results[3].match_type, "path");
     }
 
-    // Apply boosts
-    let type_boost = file_type_boost(&entry.file_type);
-    let usage_boost = usage_boost(entry.click_count, entry.last_accessed);
+    #[test]
+    fn test_scattered_fuzzy_ranks_below_path_match() {
+        let scattered = normalize_fuzzy(65, 6, FUZZY_NAME_BAND);
+        let strong = normalize_fuzzy(121, 6, FUZZY_NAME_BAND);
+        assert!(scattered < SCORE_PATH);
+        assert!(strong > SCORE_PATH && strong < SCORE_SUBSTRING);
+    }
 
-    let final_score = best_score + type_boost + usage_boost;
+    #[test]
+    fn test_snapshot_mirrors_database_rows() {
+        let db = fixture_db(&[
+            ("Résumé.pdf", r"C:\Users\me\Résumé.pdf"),
+            ("VS Code.lnk", r"C:\Apps\VS Code.lnk"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let entries = snapshot.load(&db).unwrap();
+        assert_eq!(entries.len(), 2);
+        for entry in entries.iter() {
+            let row = db.get_file_by_id(entry.id).unwrap().unwrap();
+            assert_eq!(&*entry.filename, row.filename);
+            assert_eq!(&*entry.filepath, row.filepath);
+            assert_eq!(&*entry.filename_folded, fold_text(&row.filename));
+            assert_eq!(entry.file_type, row.file_type);
+        }
+    }
 
-    (final_score, match_type, matched_indices)
-}
+    #[test]
+    fn test_snapshot_refreshes_when_row_count_changes() {
+        let db = fixture_db(&[("VS Code.lnk", r"C:\Apps\VS Code.lnk")]);
+        let snapshot = FilenameSnapshot::default();
+        assert_eq!(search(&db, &snapshot, "vscd", &SearchOptions::default(), &|| false).unwrap().results.len(), 1);
 
-/// Boost score based on file type (apps rank higher than documents, etc.)
-fn file_type_boost(file_type: &str) -> f64 {
-    match file_type {
-        "app" => 50.0,
-        "shortcut" => 40.0,
-        "document" => 20.0,
-        "folder" => 15.0,
-        "code" => 10.0,
-        "image" => 5.0,
-        _ => 0.0,
+        db.upsert_file("VS Codium.lnk", r"C:\Apps\VS Codium.lnk", "lnk", 0, 0, "other")
+            .unwrap();
+        let names: Vec<String> = search(&db, &snapshot, "vscd", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|r| r.filename)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"VS Codium.lnk".to_string()));
     }
-}
-
-/// Boost score based on usage frequency and recency.
-fn usage_boost(click_count: i64, last_accessed: i64) -> f64 {
-    // Click count boost: logarithmic to prevent domination
-    let click_boost = if click_count > 0 {
-        (click_count as f64).ln() * 15.0
-    } else {
-        0.0
-    };
 
-    // Recency boost: higher for recently accessed items
-    let recency_boost = if last_accessed > 0 {
-        let now = chrono::Utc::now().timestamp();
-        let age_hours = ((now - last_accessed) as f64 / 3600.0).max(1.0);
-        // Decay over time: full boost if accessed in last hour, diminishing after
-        (100.0 / age_hours).min(30.0)
-    } else {
-        0.0
-    };
+    /// Timed comparison of the fuzzy stage on one thread vs. the rayon pool.
+    /// Run with `cargo test --release bench_fuzzy_matches -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_fuzzy_matches() {
+        const WORDS: &[&str] = &[
+            "report", "setup", "photo", "invoice", "project", "backup", "notes", "draft",
+            "studio", "player", "driver", "update", "config", "manual", "budget", "slides",
+        ];
+        let entries: Vec<CompactEntry> = (0..300_000i64)
+            .map(|i| {
+                let n = i as usize;
+                let filename = format!(
+                    "{} {} {}.{}",
+                    WORDS[n % 16],
+                    WORDS[(n / 16) % 16],
+                    i,
+                    ["pdf", "exe", "lnk", "docx"][n % 4]
+                );
+                CompactEntry {
+                    id: i,
+                    filename_folded: fold_text(&filename).into_boxed_str(),
+                    filepath: format!(r"C:\Users\me\Documents\{}", filename).into_boxed_str(),
+                    filename: filename.into_boxed_str(),
+                    file_size: 0,
+                    file_type: "document",
+                    click_count: 0,
+                    last_accessed: 0,
+                    modified_at: 0,
+                    child_count: None,
+                    display_name: "".into(),
+                    display_name_folded: "".into(),
+                    bigrams: BigramSignature::default(),
+                }
+            })
+            .collect();
+        let filters = QueryFilters::default();
+        let seen = HashSet::new();
+        let options = SearchOptions {
+            min_score: 0.0,
+            ..Default::default()
+        };
+        let query = FuzzyQuery::new("phtupd", options.min_score);
 
-    click_boost + recency_boost
-}
+        let single = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let start = std::time::Instant::now();
+        let sequential = single.install(|| fuzzy_matches(&entries, &query, &filters, &seen, &options, None, &|| false));
+        let sequential_time = start.elapsed();
 
-/// Evaluate a math expression if the query looks like one.
-/// Supports basic arithmetic: +, -, *, /, parentheses.
-pub fn evaluate_math(query: &str) -> Option<String> {
-    let trimmed = query.trim();
+        let start = std::time::Instant::now();
+        let parallel = fuzzy_matches(&entries, &query, &filters, &seen, &options, None, &|| false);
+        let parallel_time = start.elapsed();
 
-    // Must contain at least one operator and one digit
-    if !trimmed.chars().any(|c| c.is_ascii_digit())
-        || !trimmed.chars().any(|c| matches!(c, '+' | '-' | '*' | '/' | '%' | '^'))
-    {
-        return None;
+        println!(
+            "300k entries, {} hits: 1 thread {:?}, {} threads {:?}",
+            parallel.len(),
+            sequential_time,
+            rayon::current_num_threads(),
+            parallel_time
+        );
+        let ids = |results: &[SearchResult]| results.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(&sequential), ids(&parallel));
     }
 
-    // Only allow safe characters
-    if !trimmed
-        .chars()
-        .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '*' | '/' | '.' | '(' | ')' | ' ' | '%' | '^'))
-    {
-        return None;
+    fn compact_entry(id: i64, filename: &str) -> CompactEntry {
+        let filename_folded = fold_text(filename);
+        CompactEntry {
+            id,
+            bigrams: BigramSignature::of(&[&filename_folded]),
+            filename_folded: filename_folded.into_boxed_str(),
+            filepath: format!(r"C:\Users\me\Documents\{}", filename).into_boxed_str(),
+            filename: filename.into(),
+            file_size: 1024,
+            file_type: "document",
+            click_count: 0,
+            last_accessed: 0,
+            modified_at: 0,
+            child_count: None,
+            display_name: "".into(),
+            display_name_folded: "".into(),
+        }
     }
 
-    // Simple recursive descent parser for basic math
-    match parse_expression(trimmed) {
-        Some((result, rest)) if rest.trim().is_empty() => {
-            // Format nicely: remove trailing zeros for whole numbers
-            if result.fract() == 0.0 && result.abs() < 1e15 {
-                Some(format!("{}", result as i64))
-            } else {
-                Some(format!("{:.6}", result).trim_end_matches('0').trim_end_matches('.').to_string())
-            }
+    #[test]
+    fn test_bigram_prefilter_keeps_matches_above_the_floor() {
+        const NAMES: &[&str] = &[
+            "Visual Studio Code.lnk", "Notepad.exe", "notepad++.exe", "Google Chrome.lnk", "Mozilla Firefox.lnk",
+            "Adobe Photoshop 2024.lnk", "Microsoft Excel.lnk", "Microsoft Word.lnk", "PowerPoint.lnk",
+            "Spotify.exe", "Discord.lnk", "Windows PowerShell.lnk", "Command Prompt.lnk", "Task Manager.lnk",
+            "Control Panel.lnk", "invoice budget 2024.pdf", "Invoice-March.pdf", "project_plan_v2.docx",
+            "photo_2023-08-14.jpg", "backup notes.txt", "setup.exe", "draft report final.docx",
+            "Quarterly Report Q3.xlsx", "resume.pdf", "README.md", "Cargo.toml", "main.rs", "screenshot 42.png",
+            "VLC media player.lnk", "7-Zip File Manager.lnk", "OBS Studio.lnk", "Paint.NET.lnk", "GIMP 2.10.lnk",
+            "Steam.lnk", "Epic Games Launcher.lnk", "Zoom.lnk", "Microsoft Teams.lnk", "Slack.lnk",
+            "Sublime Text.lnk", "IntelliJ IDEA Community Edition.lnk", "Android Studio.lnk", "Postman.lnk",
+            "Docker Desktop.lnk", "Git Bash.lnk", "WinRAR.lnk", "Calculator.lnk", "Snipping Tool.lnk",
+            "budget slides update.pptx", "manual config driver.pdf", "player studio 12.exe",
+        ];
+        const QUERIES: &[&str] = &[
+            "vsc", "vscode", "ntpd", "notepd", "chrm", "gchrome", "frfx", "firefx", "phtshp", "adbps", "excl",
+            "msword", "pwrpnt", "sptfy", "dscrd", "pwrshl", "cmdprmt", "tskmgr", "ctrlpnl", "invbdg", "invmar",
+            "prjpln", "bkpnts", "drftrpt", "qrtrpt", "rdme", "crgtml", "scrnsht", "vlcmp", "7zip", "obsst",
+            "pntnet", "stm", "epcgm", "msteams", "sublm", "intlj", "andrst", "pstmn", "dckr", "gitbsh", "calc",
+            "snptl", "bdgsld", "mnlcfg", "plyrstd", "phtupd", "chorme", "notpad", "xyzzy",
+        ];
+        let entries: Vec<CompactEntry> =
+            NAMES.iter().enumerate().map(|(i, name)| compact_entry(i as i64 + 1, name)).collect();
+        let (filters, seen) = (QueryFilters::default(), HashSet::new());
+        let ids = |query: &FuzzyQuery, options: &SearchOptions| {
+            fuzzy_matches(&entries, query, &filters, &seen, options, None, &|| false)
+                .into_iter()
+                .filter(|r| r.score_breakdown.as_ref().unwrap().match_score >= BIGRAM_FILTER_FLOOR)
+                .map(|r| r.id)
+                .collect::<Vec<_>>()
+        };
+        let unfiltered = SearchOptions {
+            min_score: 0.0,
+            explain: true,
+            ..SearchOptions::default()
+        };
+        let filtered = SearchOptions {
+            min_score: BIGRAM_FILTER_FLOOR,
+            ..unfiltered.clone()
+        };
+
+        let mut skipped = 0;
+        for query in QUERIES {
+            let without = FuzzyQuery::new(query, unfiltered.min_score);
+            let with = FuzzyQuery::new(query, filtered.min_score);
+            assert!(without.bigrams.is_none());
+            let bigrams = with.bigrams.as_ref().unwrap();
+            skipped += entries.iter().filter(|entry| !bigrams.admits(entry.bigrams)).count();
+            assert_eq!(ids(&with, &filtered), ids(&without, &unfiltered), "lost a match for '{}'", query);
         }
-        _ => None,
+        // Most names share little with any one query
+        assert!(skipped > QUERIES.len() * NAMES.len() / 2, "only {} skipped", skipped);
     }
-}
 
-// ---- Simple math expression parser ----
-
-fn parse_expression(input: &str) -> Option<(f64, &str)> {
-    let (mut left, mut rest) = parse_term(input)?;
-    let mut rest_trimmed = rest.trim_start();
-    while rest_trimmed.starts_with('+') || rest_trimmed.starts_with('-') {
-        let op = rest_trimmed.chars().next()?;
-        let after_op = rest_trimmed[1..].trim_start();
-        let (right, new_rest) = parse_term(after_op)?;
-        left = match op {
-            '+' => left + right,
-            '-' => left - right,
-            _ => unreachable!(),
+    /// Timed comparison of the fuzzy stage with and without the bigram
+    /// pre-filter. Run with
+    /// `cargo test --release bench_bigram_prefilter -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_bigram_prefilter() {
+        const WORDS: &[&str] = &[
+            "report", "setup", "photo", "invoice", "project", "backup", "notes", "draft",
+            "studio", "player", "driver", "update", "config", "manual", "budget", "slides",
+        ];
+        let entries: Vec<CompactEntry> = (0..300_000i64)
+            .map(|i| {
+                let n = i as usize;
+                let filename = format!("{} {} {}.{}", WORDS[n % 16], WORDS[(n / 16) % 16], i, ["pdf", "exe"][n % 2]);
+                compact_entry(i, &filename)
+            })
+            .collect();
+        let (filters, seen) = (QueryFilters::default(), HashSet::new());
+        let options = SearchOptions::default();
+        let time = |min_score: f64| {
+            let query = FuzzyQuery::new("invbdg", min_score);
+            let start = std::time::Instant::now();
+            let hits = fuzzy_matches(&entries, &query, &filters, &seen, &options, None, &|| false).len();
+            (hits, start.elapsed())
         };
-        rest = new_rest;
-        rest_trimmed = rest.trim_start();
+        let bigrams = BigramQuery::new("invbdg").unwrap();
+        let admitted = entries.iter().filter(|entry| bigrams.admits(entry.bigrams)).count();
+        let (unfiltered_hits, unfiltered_time) = time(0.0);
+        let (filtered_hits, filtered_time) = time(BIGRAM_FILTER_FLOOR);
+        println!(
+            "300k entries: without pre-filter {} hits in {:?}, with it {} hits in {:?} ({} entries skimmed)",
+            unfiltered_hits, unfiltered_time, filtered_hits, filtered_time, admitted
+        );
+        assert_eq!(unfiltered_hits, filtered_hits);
     }
-    Some((left, rest))
-}
 
-fn parse_term(input: &str) -> Option<(f64, &str)> {
-    let (mut left, mut rest) = parse_factor(input)?;
-    let mut rest_trimmed = rest.trim_start();
-    while rest_trimmed.starts_with('*') || rest_trimmed.starts_with('/') || rest_trimmed.starts_with('%') {
-        let op = rest_trimmed.chars().next()?;
-        let after_op = rest_trimmed[1..].trim_start();
-        let (right, new_rest) = parse_factor(after_op)?;
-        left = match op {
-            '*' => left * right,
-            '/' => {
-                if right == 0.0 {
-                    return None; // division by zero
-                }
-                left / right
-            }
-            '%' => {
-                if right == 0.0 {
-                    return None;
-                }
-                left % right
-            }
-            _ => unreachable!(),
+    #[test]
+    fn test_osa_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(osa_distance(&chars("chorme"), &chars("chrome"), 1), Some(1));
+        assert_eq!(osa_distance(&chars("notpad"), &chars("notepad"), 1), Some(1));
+        assert_eq!(osa_distance(&chars("spotfy"), &chars("spotify"), 2), Some(1));
+        assert_eq!(osa_distance(&chars("exel"), &chars("excel"), 1), Some(1));
+        assert_eq!(osa_distance(&chars("firfeox"), &chars("firefox"), 2), Some(1));
+        assert_eq!(osa_distance(&chars("frefx"), &chars("firefox"), 2), Some(2));
+        assert_eq!(osa_distance(&chars("chorme"), &chars("chrome"), 0), None);
+        assert_eq!(osa_distance(&chars("abc"), &chars("xyz"), 2), None);
+    }
+
+    #[test]
+    fn test_typo_tolerant_matches() {
+        let db = fixture_db(&[
+            ("Google Chrome.lnk", r"C:\ProgramData\Start Menu\Google Chrome.lnk"),
+            ("Notepad.lnk", r"C:\ProgramData\Start Menu\Notepad.lnk"),
+            ("Photoshop.lnk", r"C:\ProgramData\Start Menu\Photoshop.lnk"),
+        ]);
+        let top = |query: &str| {
+            search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+                .unwrap()
+                .results
+                .into_iter()
+                .next()
         };
-        rest = new_rest;
-        rest_trimmed = rest.trim_start();
+
+        let chrome = top("chorme").unwrap();
+        assert_eq!(chrome.filename, "Google Chrome.lnk");
+        assert_eq!(chrome.match_type, "typo");
+        assert_eq!(chrome.matched_indices, (7..13).collect::<Vec<_>>());
+        assert!(chrome.score < SCORE_SUBSTRING + 100.0);
+
+        assert_eq!(top("notpad").unwrap().filename, "Notepad.lnk");
+        assert_eq!(top("photsohp").unwrap().filename, "Photoshop.lnk");
+        // Too many edits for a short query
+        assert!(top("nptpda").is_none_or(|r| r.filename != "Notepad.lnk"));
+        // Multi-word queries skip the typo pass
+        assert!(TypoQuery::new("chorme beta").is_none());
+        assert!(TypoQuery::new("ab").is_none());
     }
-    Some((left, rest))
-}
 
-fn parse_factor(input: &str) -> Option<(f64, &str)> {
-    let trimmed = input.trim_start();
+    /// Cost of the typo pass on its own over a 200k-name corpus where every
+    /// name has two candidate words.
+    /// Run with `cargo test --release bench_typo_pass -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_typo_pass() {
+        const WORDS: &[&str] = &[
+            "report", "setup", "photo", "invoice", "project", "backup", "notes", "draft",
+            "studio", "player", "driver", "update", "config", "manual", "budget", "slides",
+        ];
+        let names: Vec<String> = (0..200_000usize)
+            .map(|n| fold_text(&format!("{} {} {}.pdf", WORDS[n % 16], WORDS[(n / 16) % 16], n)))
+            .collect();
+        let typo = TypoQuery::new("invocie").unwrap();
 
-    // Handle parentheses
-    if trimmed.starts_with('(') {
-        let (val, rest) = parse_expression(&trimmed[1..])?;
-        let rest = rest.trim_start();
-        if rest.starts_with(')') {
-            return Some((val, &rest[1..]));
-        }
-        return None;
+        let start = std::time::Instant::now();
+        let typo_hits = names.par_iter().filter(|name| typo.best_word_match(name).is_some()).count();
+        let typo_time = start.elapsed();
+
+        // The skim pass over the same names, for scale
+        let matcher = SkimMatcherV2::default();
+        let start = std::time::Instant::now();
+        let skim_hits = names.par_iter().filter(|name| matcher.fuzzy_match(name, "invocie").is_some()).count();
+        let skim_time = start.elapsed();
+
+        println!(
+            "200k names: typo pass {} hits in {:?}, skim {} hits in {:?}",
+            typo_hits, typo_time, skim_hits, skim_time
+        );
+        assert!(typo_hits > 0);
     }
 
-    // Handle negative numbers
-    if trimmed.starts_with('-') {
-        let (val, rest) = parse_factor(&trimmed[1..])?;
-        return Some((-val, rest));
+    #[test]
+    fn test_superseded_search_bails_out() {
+        let db = fixture_db(&[("VS Code.lnk", r"C:\Apps\VS Code.lnk")]);
+        let result = search(&db, &FilenameSnapshot::default(), "vscd", &SearchOptions::default(), &|| true);
+        assert_eq!(result.unwrap_err(), SEARCH_SUPERSEDED);
     }
 
-    // Parse number
-    let mut end = 0;
-    let mut has_dot = false;
-    for (i, c) in trimmed.char_indices() {
-        if c.is_ascii_digit() {
-            end = i + 1;
-        } else if c == '.' && !has_dot {
-            has_dot = true;
-            end = i + 1;
-        } else {
-            break;
-        }
+    #[test]
+    fn test_grouping_keeps_room_for_every_group() {
+        let result = |id: i64, file_type: &str, score: f64| {
+            synthetic_result(id, format!("{} {}", file_type, id), format!(r"C:\{}", id), file_type, score)
+        };
+        // A flood of documents outranking everything else
+        let mut results: Vec<SearchResult> = (1..=20).map(|id| result(id, "document", 200.0 - id as f64)).collect();
+        results.push(result(21, "app", 10.0));
+        results.push(result(22, "folder", 6.0));
+        results.push(result(23, "folder", 5.0));
+        results.push(result(-1, "window", 1.0));
+
+        let grouped = group_results(results.clone(), &GroupCaps::default(), 15);
+        assert_eq!(grouped.len(), 15);
+        let groups: Vec<&str> = grouped.iter().map(|r| r.group.as_deref().unwrap()).collect();
+        assert_eq!(&groups[..4], ["apps", "actions", "folders", "folders"]);
+        // Five documents by their cap, six more filling the free slots, best first
+        assert!(groups[4..].iter().all(|g| *g == "documents"));
+        let ids: Vec<i64> = grouped[4..].iter().map(|r| r.id).collect();
+        assert_eq!(ids, (1..=11).collect::<Vec<i64>>());
+
+        // Caps bind while the list is full of other groups
+        let tight = GroupCaps {
+            documents: 2,
+            ..GroupCaps::default()
+        };
+        let grouped = group_results(results, &tight, 5);
+        let ids: Vec<i64> = grouped.iter().map(|r| r.id).collect();
+        assert_eq!(ids, [21, 22, 23, 1, 2]);
     }
 
-    if end == 0 {
-        return None;
+    #[test]
+    fn test_page_cache_slices_and_invalidates() {
+        let files: Vec<(String, String)> = (0..25)
+            .map(|i| (format!("report {:02}.pdf", i), format!(r"C:\Docs\report {:02}.pdf", i)))
+            .collect();
+        let refs: Vec<(&str, &str)> = files.iter().map(|(n, p)| (n.as_str(), p.as_str())).collect();
+        let db = fixture_db(&refs);
+        let snapshot = FilenameSnapshot::default();
+        let cache = PageCache::default();
+
+        let first = cache.page(&db, &snapshot, "report", 0, 10).unwrap();
+        let third = cache.page(&db, &snapshot, "report", 2, 10).unwrap();
+        assert_eq!(first.results.len(), 10);
+        assert!(first.has_more);
+        assert_eq!(third.results.len(), 5);
+        assert!(!third.has_more);
+
+        db.upsert_file("report 99.pdf", r"C:\Docs\report 99.pdf", "pdf", 0, 0, "other")
+            .unwrap();
+        snapshot.refresh(&db).unwrap();
+        let third = cache.page(&db, &snapshot, "report", 2, 10).unwrap();
+        assert_eq!(third.results.len(), 6);
     }
 
-    let num: f64 = trimmed[..end].parse().ok()?;
-    Some((num, &trimmed[end..]))
-}
+    fn ranked_names(files: &[(&str, &str)], query: &str) -> Vec<String> {
+        let db = fixture_db(files);
+        search(&db, &FilenameSnapshot::default(), query, &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|r| r.filename)
+            .collect()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_ranking_word_boundary_beats_inner_substring() {
+        let names = ranked_names(
+            &[
+                ("chromenotes_backup.txt", r"C:\Docs\chromenotes_backup.txt"),
+                ("my-notes.txt", r"C:\Docs\my-notes.txt"),
+                ("Notes.txt", r"C:\Docs\Notes.txt"),
+            ],
+            "note",
+        );
+        assert_eq!(names, vec!["Notes.txt", "my-notes.txt", "chromenotes_backup.txt"]);
+    }
 
     #[test]
-    fn test_math_eval_basic() {
-        assert_eq!(evaluate_math("2+2"), Some("4".to_string()));
-        assert_eq!(evaluate_math("10 * 5"), Some("50".to_string()));
-        assert_eq!(evaluate_math("100 / 4"), Some("25".to_string()));
-        assert_eq!(evaluate_math("3.14 * 2"), Some("6.28".to_string()));
+    fn test_ranking_prefers_shorter_names() {
+        let names = ranked_names(
+            &[
+                (
+                    "Microsoft Word 2016 Tools Language Preferences.lnk",
+                    r"C:\Start Menu\Microsoft Word 2016 Tools Language Preferences.lnk",
+                ),
+                ("Word 2016 Tools Language Preferences.lnk", r"C:\Start Menu\Word 2016 Tools Language Preferences.lnk"),
+                ("WordPad.lnk", r"C:\Start Menu\WordPad.lnk"),
+                ("Word.lnk", r"C:\Start Menu\Word.lnk"),
+            ],
+            "word",
+        );
+        assert_eq!(
+            names,
+            vec![
+                "Word.lnk",
+                "WordPad.lnk",
+                "Word 2016 Tools Language Preferences.lnk",
+                "Microsoft Word 2016 Tools Language Preferences.lnk",
+            ]
+        );
     }
 
     #[test]
-    fn test_math_eval_complex() {
-        assert_eq!(evaluate_math("(2 + 3) * 4"), Some("20".to_string()));
-        assert_eq!(evaluate_math("10 + 5 * 2"), Some("20".to_string()));
-        assert_eq!(evaluate_math("100 / (2 + 3)"), Some("20".to_string()));
+    fn test_search_meta_only_when_requested() {
+        let db = fixture_db(&[("notes.txt", r"C:\Docs\notes.txt"), ("nodes.txt", r"C:\Docs\nodes.txt")]);
+        let snapshot = FilenameSnapshot::default();
+        let plain = search(&db, &snapshot, "notes", &SearchOptions::default(), &|| false).unwrap();
+        assert!(plain.meta.is_none());
+
+        let options = SearchOptions {
+            metrics: true,
+            ..Default::default()
+        };
+        let meta = search(&db, &snapshot, "notes", &options, &|| false).unwrap().meta.unwrap();
+        assert_eq!(meta.sql_candidates, 1);
+        // One SQL hit is fewer than the result limit, so the fuzzy stage ran too
+        assert!(meta.fuzzy_ran);
+        assert!(meta.total_ms >= meta.sql_ms + meta.fuzzy_ms);
     }
 
     #[test]
-    fn test_math_eval_invalid() {
-        assert_eq!(evaluate_math("hello"), None);
-        assert_eq!(evaluate_math(""), None);
-        assert_eq!(evaluate_math("abc + 2"), None);
+    fn test_short_query_finds_prefixes_and_launched_apps() {
+        let mut files = vec![
+            ("Chrome.lnk".to_string(), r"C:\Start Menu\Chrome.lnk".to_string()),
+            ("Calculator.lnk".to_string(), r"C:\Start Menu\Calculator.lnk".to_string()),
+            ("Visual Studio Code.lnk".to_string(), r"C:\Start Menu\Visual Studio Code.lnk".to_string()),
+        ];
+        files.extend((0..40).map(|i| (format!("notes {}.doc", i), format!(r"C:\Docs\notes {}.doc", i))));
+        let refs: Vec<(&str, &str)> = files.iter().map(|(n, p)| (n.as_str(), p.as_str())).collect();
+        let db = fixture_db(&refs);
+        db.record_click(r"C:\Start Menu\Visual Studio Code.lnk").unwrap();
+
+        let options = SearchOptions {
+            metrics: true,
+            ..Default::default()
+        };
+        let response = search(&db, &FilenameSnapshot::default(), "c", &options, &|| false).unwrap();
+        let files = response.results.iter().filter(|r| r.id >= 0);
+        let mut names: Vec<&str> = files.map(|r| r.filename.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["Calculator.lnk", "Chrome.lnk", "Visual Studio Code.lnk"]);
+        assert!(!response.meta.unwrap().fuzzy_ran);
+
+        // Longer queries still match anywhere in the name
+        let results = search(&db, &FilenameSnapshot::default(), "doc", &options, &|| false).unwrap().results;
+        assert_eq!(results.len(), SearchOptions::default().max_results);
     }
 
     #[test]
-    fn test_math_division_by_zero() {
-        assert_eq!(evaluate_math("5 / 0"), None);
+    fn test_deep_path_search_matches_folders() {
+        let db = fixture_db(&[
+            ("invoice_march.pdf", r"C:\Users\me\clients\proj23\invoice_march.pdf"),
+            ("invoice_april.pdf", r"C:\Users\me\other\invoice_april.pdf"),
+        ]);
+        let snapshot = FilenameSnapshot::default();
+        let plain = search(&db, &snapshot, "proj23 invoice", &SearchOptions::default(), &|| false).unwrap();
+        assert!(plain.results.iter().all(|r| r.filename != "invoice_march.pdf"));
+
+        let deep = SearchOptions {
+            deep_path: true,
+            ..Default::default()
+        };
+        for (query, options) in [("proj23 invoice", &deep), ("proj23/invoice", &SearchOptions::default())] {
+            let results = search(&db, &snapshot, query, options, &|| false).unwrap().results;
+            assert_eq!(results.len(), 1, "{}", query);
+            let result = &results[0];
+            assert_eq!(result.filename, "invoice_march.pdf");
+            assert_eq!(result.match_type, "path");
+            assert!(result.score < SCORE_SUBSTRING);
+            let parent: Vec<char> = result.parent_dir.chars().collect();
+            let highlighted: String = result.path_indices.iter().map(|&i| parent[i]).collect();
+            assert_eq!(highlighted, "proj23");
+            assert_eq!(result.matched_indices, (0..7).collect::<Vec<_>>());
+        }
+
+        // A filename hit that fills the result list skips the deep stage
+        let one = SearchOptions {
+            max_results: 1,
+            deep_path: true,
+            metrics: true,
+            ..Default::default()
+        };
+        let response = search(&db, &snapshot, "invoice", &one, &|| false).unwrap();
+        assert!(!response.meta.unwrap().deep_path_ran);
     }
 
     #[test]
-    fn test_file_type_boost_values() {
-        assert!(file_type_boost("app") > file_type_boost("document"));
-        assert!(file_type_boost("document") > file_type_boost("other"));
+    fn test_explain_breakdown_sums_to_score() {
+        let db = fixture_db(&[
+            ("my-notes.txt", r"C:\Docs\my-notes.txt"),
+            ("Note Pad.lnk", r"C:\Apps\Note Pad.lnk"),
+        ]);
+        let options = SearchOptions {
+            explain: true,
+            ..Default::default()
+        };
+        let results = search(&db, &FilenameSnapshot::default(), "notes", &options, &|| false)
+            .unwrap()
+            .results;
+        let substring = &results[0];
+        let breakdown = substring.score_breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.branch, "substring");
+        assert_eq!(breakdown.position_adjustment, WORD_BOUNDARY_BONUS);
+        assert!((breakdown.total() - substring.score).abs() < 1e-9);
+
+        let plain = search(&db, &FilenameSnapshot::default(), "notes", &SearchOptions::default(), &|| false)
+            .unwrap()
+            .results;
+        assert!(plain.iter().all(|r| r.score_breakdown.is_none()));
     }
 }
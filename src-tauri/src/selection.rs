@@ -0,0 +1,255 @@
+//! Searching the text selected in another app: the selection hotkey copies
+//! it with a synthetic Ctrl+C, and the launcher opens with it as the query.
+//!
+//! The user's clipboard is put back afterwards, every format of it that
+//! lives in plain memory (text, HTML, images as DIBs, file lists, app
+//! formats); bitmaps and metafiles held as GDI handles are rebuilt by
+//! Windows from those or lost. Opt-in, as synthetic keystrokes land in
+//! whatever has focus.
+
+use crate::clipboard::ClipboardHolder;
+use crate::clipboard_history::sequence_number;
+use std::time::{Duration, Instant};
+
+/// Longest the whole capture may take; a hung app that never answers the
+/// copy, or never renders its clipboard data, is given up on.
+pub const CAPTURE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Time for the user to let go of the hotkey before Ctrl+C is sent, so it
+/// isn't Ctrl+Shift+C.
+const MODIFIER_RELEASE_WAIT: Duration = Duration::from_millis(500);
+/// Time for the foreground app to put its selection on the clipboard.
+const COPY_WAIT: Duration = Duration::from_millis(400);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const OPEN_ATTEMPTS: u32 = 5;
+/// Longer selections are cut to this many characters.
+const MAX_QUERY_CHARS: usize = 200;
+
+/// Copy the foreground app's selection and return it as a query, or `None`
+/// if nothing was selected. The clipboard is left as it was.
+pub fn capture(clipboard: &ClipboardHolder) -> Result<Option<String>, String> {
+    let saved = save_clipboard()?;
+    let before = sequence_number();
+    wait_for_modifiers_released();
+    send_copy()?;
+
+    let deadline = Instant::now() + COPY_WAIT;
+    while sequence_number() == before && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    if sequence_number() == before {
+        // Nothing was selected; the clipboard is untouched
+        return Ok(None);
+    }
+    let text = clipboard.get_text();
+    restore_clipboard(&saved)?;
+    Ok(text.as_deref().and_then(query_from_selection))
+}
+
+/// A selection as a query: whitespace and line breaks collapsed into
+/// single spaces and cut to [`MAX_QUERY_CHARS`].
+fn query_from_selection(text: &str) -> Option<String> {
+    let query: Vec<&str> = text.split_whitespace().collect();
+    let query: String = query.join(" ").chars().take(MAX_QUERY_CHARS).collect();
+    let query = query.trim_end();
+    (!query.is_empty()).then(|| query.to_string())
+}
+
+/// Whether clipboard `format` is stored as a memory block that can be
+/// copied out and back. Bitmaps, palettes and metafiles are GDI handles,
+/// owner-display and private formats belong to their app.
+fn is_memory_format(format: u32) -> bool {
+    const CF_BITMAP: u32 = 2;
+    const CF_METAFILEPICT: u32 = 3;
+    const CF_PALETTE: u32 = 9;
+    const CF_ENHMETAFILE: u32 = 14;
+    !matches!(format, CF_BITMAP | CF_METAFILEPICT | CF_PALETTE | CF_ENHMETAFILE | 0x80..=0x3FF)
+}
+
+/// Every memory format on the clipboard with its data.
+#[cfg(windows)]
+fn save_clipboard() -> Result<Vec<(u32, Vec<u8>)>, String> {
+    use windows::Win32::Foundation::HGLOBAL;
+    use windows::Win32::System::DataExchange::{EnumClipboardFormats, GetClipboardData};
+    use windows::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+
+    let _open = OpenClipboard::open()?;
+    let mut saved = Vec::new();
+    // SAFETY: the clipboard is open for the whole loop, and each block is
+    // read only between locking and unlocking it, within its size.
+    unsafe {
+        let mut format = EnumClipboardFormats(0);
+        while format != 0 {
+            if is_memory_format(format) {
+                if let Ok(handle) = GetClipboardData(format) {
+                    let memory = HGLOBAL(handle.0);
+                    let data = GlobalLock(memory) as *const u8;
+                    if !data.is_null() {
+                        saved.push((format, std::slice::from_raw_parts(data, GlobalSize(memory)).to_vec()));
+                        let _ = GlobalUnlock(memory);
+                    }
+                }
+            }
+            format = EnumClipboardFormats(format);
+        }
+    }
+    Ok(saved)
+}
+
+/// Replace the clipboard's contents with `saved`. Formats that can't be
+/// put back are skipped.
+#[cfg(windows)]
+fn restore_clipboard(saved: &[(u32, Vec<u8>)]) -> Result<(), String> {
+    use windows::Win32::Foundation::{GlobalFree, HANDLE};
+    use windows::Win32::System::DataExchange::{EmptyClipboard, SetClipboardData};
+    use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+
+    let _open = OpenClipboard::open()?;
+    // SAFETY: the clipboard is open; each block is written within its size
+    // while locked, and freed here only if the clipboard didn't take it.
+    unsafe {
+        EmptyClipboard().map_err(|e| format!("Failed to restore the clipboard: {}", e))?;
+        for (format, data) in saved {
+            let Ok(memory) = GlobalAlloc(GMEM_MOVEABLE, data.len().max(1)) else {
+                continue;
+            };
+            let target = GlobalLock(memory) as *mut u8;
+            if target.is_null() {
+                let _ = GlobalFree(memory);
+                continue;
+            }
+            std::ptr::copy_nonoverlapping(data.as_ptr(), target, data.len());
+            let _ = GlobalUnlock(memory);
+            if SetClipboardData(*format, HANDLE(memory.0)).is_err() {
+                let _ = GlobalFree(memory);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The clipboard opened by this thread, closed again on drop.
+#[cfg(windows)]
+struct OpenClipboard;
+
+#[cfg(windows)]
+impl OpenClipboard {
+    /// Open the clipboard, retrying briefly while another app holds it.
+    fn open() -> Result<OpenClipboard, String> {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::System::DataExchange::OpenClipboard as open_clipboard;
+
+        let mut last_error = String::new();
+        for _ in 0..OPEN_ATTEMPTS {
+            // SAFETY: a null owner window is allowed; the clipboard is
+            // closed in `drop`.
+            match unsafe { open_clipboard(HWND::default()) } {
+                Ok(()) => return Ok(OpenClipboard),
+                Err(e) => last_error = e.to_string(),
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        Err(format!("The clipboard is in use by another app: {}", last_error))
+    }
+}
+
+#[cfg(windows)]
+impl Drop for OpenClipboard {
+    fn drop(&mut self) {
+        // SAFETY: this thread opened the clipboard in `open`.
+        let _ = unsafe { windows::Win32::System::DataExchange::CloseClipboard() };
+    }
+}
+
+/// Wait up to [`MODIFIER_RELEASE_WAIT`] for Shift, Ctrl, Alt and the
+/// Windows keys to be up.
+#[cfg(windows)]
+fn wait_for_modifiers_released() {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+    };
+
+    let deadline = Instant::now() + MODIFIER_RELEASE_WAIT;
+    let any_down = || {
+        [VK_SHIFT, VK_CONTROL, VK_MENU, VK_LWIN, VK_RWIN]
+            .iter()
+            // SAFETY: only reads key state; the high bit means down.
+            .any(|vk| unsafe { GetAsyncKeyState(vk.0 as i32) } < 0)
+    };
+    while any_down() && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Send Ctrl+C to whichever window has keyboard focus.
+#[cfg(windows)]
+fn send_copy() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, VIRTUAL_KEY,
+        VK_C, VK_CONTROL,
+    };
+
+    let key = |vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    let inputs = [
+        key(VK_CONTROL, KEYBD_EVENT_FLAGS(0)),
+        key(VK_C, KEYBD_EVENT_FLAGS(0)),
+        key(VK_C, KEYEVENTF_KEYUP),
+        key(VK_CONTROL, KEYEVENTF_KEYUP),
+    ];
+    // SAFETY: `inputs` are fully initialized keyboard events and the size
+    // passed is that of one INPUT, as the API requires.
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        // Blocked by UIPI when the target runs elevated and we don't
+        return Err("Couldn't copy the selection from that window".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn save_clipboard() -> Result<Vec<(u32, Vec<u8>)>, String> {
+    Err("Searching the selection is only supported on Windows".to_string())
+}
+
+#[cfg(not(windows))]
+fn restore_clipboard(_saved: &[(u32, Vec<u8>)]) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn wait_for_modifiers_released() {}
+
+#[cfg(not(windows))]
+fn send_copy() -> Result<(), String> {
+    Err("Searching the selection is only supported on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_from_selection() {
+        assert_eq!(query_from_selection("  quarterly\r\n  report\t2024 ").as_deref(), Some("quarterly report 2024"));
+        assert_eq!(query_from_selection(" \n "), None);
+        assert_eq!(query_from_selection(&"é".repeat(500)).unwrap().chars().count(), MAX_QUERY_CHARS);
+    }
+
+    #[test]
+    fn test_is_memory_format() {
+        // CF_UNICODETEXT, CF_DIB, CF_HDROP and a registered format
+        assert!([13, 8, 15, 0xC0A1].into_iter().all(is_memory_format));
+        // CF_BITMAP, CF_ENHMETAFILE, CF_OWNERDISPLAY, a private and a GDI object format
+        assert!(![2, 14, 0x80, 0x200, 0x300].into_iter().any(is_memory_format));
+    }
+}
@@ -0,0 +1,699 @@
+//! User settings, gathered into one struct for the settings page.
+//!
+//! Each setting is still stored under its own `index_meta` key, next to the
+//! code that uses it; [`Settings`] loads and saves them together.
+//! `lib.rs` keeps the loaded settings in `AppState` and applies a
+//! [`SettingsPatch`] to them, re-registering hotkeys and waking the
+//! background indexer as needed.
+
+use crate::bookmarks::{self, BookmarkBrowser};
+use crate::db::Database;
+use crate::i18n::Locale;
+use crate::logging::LogLevel;
+use crate::placement::WindowPlacement;
+use crate::searcher::GroupCaps;
+use crate::{clipboard_history, hotkeys, indexer, launcher, quick_slots, searcher};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Minutes between background index passes when unset.
+pub const DEFAULT_INDEX_INTERVAL_MINUTES: u64 = 5;
+const MAX_INDEX_INTERVAL_MINUTES: u64 = 24 * 60;
+const INDEX_INTERVAL_KEY: &str = "index_interval_minutes";
+const EXCLUDED_EXTENSIONS_KEY: &str = "excluded_extensions";
+const INDEX_PROVIDERS_KEY: &str = "index_providers";
+const INDEX_ROOTS_KEY: &str = "index_roots";
+const GROUP_RESULTS_KEY: &str = "group_results";
+const GROUP_CAPS_KEY: &str = "group_caps";
+const WINDOW_PLACEMENT_KEY: &str = "window_placement";
+const LOG_LEVEL_KEY: &str = "log_level";
+const LOCALE_KEY: &str = "locale";
+const AUTO_PASTE_SNIPPETS_KEY: &str = "auto_paste_snippets";
+const PIN_BY_DEFAULT_KEY: &str = "pin_by_default";
+/// Days between background update checks when unset.
+pub const DEFAULT_UPDATE_CHECK_INTERVAL_DAYS: u64 = 7;
+const MAX_UPDATE_CHECK_INTERVAL_DAYS: u64 = 365;
+const UPDATE_CHECK_INTERVAL_KEY: &str = "update_check_interval_days";
+const INDEX_NOTIFICATIONS_KEY: &str = "index_notifications";
+const RELAUNCH_HOTKEY_KEY: &str = "relaunch_hotkey";
+const SELECTION_HOTKEY_KEY: &str = "selection_hotkey";
+/// Seconds without input before a background index pass may run, when unset.
+pub const DEFAULT_INDEX_IDLE_SECONDS: u64 = 60;
+const MAX_INDEX_IDLE_SECONDS: u64 = 60 * 60;
+const INDEX_IDLE_SECONDS_KEY: &str = "index_idle_seconds";
+const INDEX_ON_BATTERY_KEY: &str = "index_on_battery";
+/// Minutes a background index pass may take before a toast says so, when unset.
+pub const DEFAULT_NOTIFY_SLOW_INDEX_MINUTES: u64 = 10;
+const NOTIFY_SLOW_INDEX_KEY: &str = "notify_slow_index_minutes";
+/// Files a background index pass may remove before a toast says so, when unset.
+pub const DEFAULT_NOTIFY_REMOVED_FILES: usize = 500;
+const MAX_NOTIFY_REMOVED_FILES: usize = 1_000_000;
+const NOTIFY_REMOVED_FILES_KEY: &str = "notify_removed_files";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Settings {
+    /// Activation hotkeys, primary first.
+    pub hotkeys: Vec<String>,
+    /// Hotkey that launches the last launched item again without showing
+    /// the window; empty when off, as it is by default.
+    pub relaunch_hotkey: String,
+    /// Hotkey that copies the text selected in the foreground app and
+    /// opens the launcher searching for it; empty when off, as it is by
+    /// default since it sends a synthetic Ctrl+C.
+    pub selection_hotkey: String,
+    /// Results returned by a search that doesn't pass its own limit.
+    pub result_limit: usize,
+    /// Fall back to fuzzy matching when a search finds few results. On by
+    /// default; turning it off makes searches cheaper on slow machines.
+    pub enable_fuzzy: bool,
+    /// Fuzzy-match the last folders of each path too when a search finds
+    /// few results. Off by default; a `/` in the query turns it on for
+    /// that query.
+    pub deep_path_search: bool,
+    /// Remember what's launched from which app and rank it a little higher
+    /// when the launcher is opened from that app again. On by default.
+    pub context_boost: bool,
+    /// Leave empty documents and other empty files out of results. Off by
+    /// default.
+    pub hide_empty_files: bool,
+    /// Size in MiB above which a file (not a folder or app) ranks a little
+    /// lower; 0 turns that off.
+    pub large_file_mb: u64,
+    /// List results by category (apps first), each capped by
+    /// `group_caps`, instead of one ranking. Off by default.
+    pub group_results: bool,
+    /// Most results per category when `group_results` is on.
+    pub group_caps: GroupCaps,
+    /// Minutes between background index passes.
+    pub index_interval_minutes: u64,
+    /// File extensions left out of the index, lowercase without the dot.
+    pub excluded_extensions: Vec<String>,
+    /// Whether each index provider (see `indexer::PROVIDER_NAMES`) runs,
+    /// by name; one that isn't listed runs.
+    pub index_providers: BTreeMap<String, bool>,
+    /// Folders the filesystem provider walks; empty for the default ones
+    /// (see `indexer::default_index_directories`).
+    pub index_roots: Vec<String>,
+    /// File manager command template; empty for Explorer.
+    pub file_manager: String,
+    /// Which browser opens a bookmark: the default one, or the one it was
+    /// bookmarked in.
+    pub bookmark_browser: BookmarkBrowser,
+    /// Which monitor the window opens on.
+    pub window_placement: WindowPlacement,
+    /// How much is written to the log file.
+    pub log_level: LogLevel,
+    /// Language of the tray menu, notifications and launch errors.
+    pub locale: Locale,
+    /// Paste a picked snippet or clipboard history entry into the
+    /// previously focused window, not just copy it.
+    pub auto_paste_snippets: bool,
+    /// Keep copied text for `clip ` queries. Off by default.
+    pub clipboard_history: bool,
+    /// Days between background update checks; 0 turns them off.
+    pub update_check_interval_days: u64,
+    /// Start with the window pinned, so it stays open when it loses focus.
+    pub pin_by_default: bool,
+    /// Show a toast when a background index pass fails, runs long or
+    /// removes many files. On by default.
+    pub index_notifications: bool,
+    /// Minutes after which a background index pass counts as long.
+    pub notify_slow_index_minutes: u64,
+    /// Removed files from which a background index pass is reported.
+    pub notify_removed_files: usize,
+    /// Seconds without keyboard or mouse input before a background index
+    /// pass runs; 0 runs it regardless.
+    pub index_idle_seconds: u64,
+    /// Run background index passes on battery too. Off by default.
+    pub index_on_battery: bool,
+    /// Items launched by Ctrl+Alt+1 through Ctrl+Alt+9, by slot (see
+    /// `quick_slots`).
+    pub quick_slots: BTreeMap<u8, String>,
+}
+
+/// A partial update from the frontend: missing fields keep their current
+/// value, and unknown ones are ignored so an older build accepts a newer
+/// frontend's settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SettingsPatch {
+    pub hotkeys: Option<Vec<String>>,
+    pub relaunch_hotkey: Option<String>,
+    pub selection_hotkey: Option<String>,
+    pub result_limit: Option<usize>,
+    pub enable_fuzzy: Option<bool>,
+    pub deep_path_search: Option<bool>,
+    pub context_boost: Option<bool>,
+    pub hide_empty_files: Option<bool>,
+    pub large_file_mb: Option<u64>,
+    pub group_results: Option<bool>,
+    pub group_caps: Option<GroupCaps>,
+    pub index_interval_minutes: Option<u64>,
+    pub excluded_extensions: Option<Vec<String>>,
+    pub index_providers: Option<BTreeMap<String, bool>>,
+    pub index_roots: Option<Vec<String>>,
+    pub file_manager: Option<String>,
+    pub bookmark_browser: Option<BookmarkBrowser>,
+    pub window_placement: Option<WindowPlacement>,
+    pub log_level: Option<LogLevel>,
+    pub locale: Option<Locale>,
+    pub auto_paste_snippets: Option<bool>,
+    pub clipboard_history: Option<bool>,
+    pub update_check_interval_days: Option<u64>,
+    pub pin_by_default: Option<bool>,
+    pub index_notifications: Option<bool>,
+    pub notify_slow_index_minutes: Option<u64>,
+    pub notify_removed_files: Option<usize>,
+    pub index_idle_seconds: Option<u64>,
+    pub index_on_battery: Option<bool>,
+    pub quick_slots: Option<BTreeMap<u8, String>>,
+}
+
+impl Settings {
+    /// Load every setting. Values that are unset or unreadable fall back to
+    /// their defaults.
+    pub fn load(db: &Database) -> Self {
+        Settings {
+            hotkeys: hotkeys::stored_hotkeys(db),
+            relaunch_hotkey: db.get_meta(RELAUNCH_HOTKEY_KEY).ok().flatten().unwrap_or_default(),
+            selection_hotkey: db.get_meta(SELECTION_HOTKEY_KEY).ok().flatten().unwrap_or_default(),
+            result_limit: searcher::default_result_limit(db),
+            enable_fuzzy: searcher::fuzzy_enabled(db),
+            deep_path_search: searcher::deep_path_enabled(db),
+            context_boost: searcher::context_boost_enabled(db),
+            hide_empty_files: searcher::hide_empty_files(db),
+            large_file_mb: searcher::large_file_mb(db),
+            group_results: group_results(db),
+            group_caps: group_caps(db),
+            index_interval_minutes: index_interval_minutes(db),
+            excluded_extensions: excluded_extensions(db),
+            index_providers: index_providers(db),
+            index_roots: index_roots(db),
+            file_manager: launcher::file_manager_command(db).unwrap_or_default(),
+            bookmark_browser: bookmarks::bookmark_browser(db),
+            window_placement: window_placement(db),
+            log_level: log_level(db),
+            locale: locale(db),
+            auto_paste_snippets: auto_paste_snippets(db),
+            clipboard_history: clipboard_history::enabled(db),
+            update_check_interval_days: update_check_interval_days(db),
+            pin_by_default: pin_by_default(db),
+            index_notifications: index_notifications(db),
+            notify_slow_index_minutes: notify_slow_index_minutes(db),
+            notify_removed_files: notify_removed_files(db),
+            index_idle_seconds: index_idle_seconds(db),
+            index_on_battery: index_on_battery(db),
+            quick_slots: quick_slots::bindings(db),
+        }
+    }
+
+    /// These settings with `patch` applied, after validating it.
+    pub fn with_patch(&self, patch: SettingsPatch) -> Result<Settings, String> {
+        let mut settings = self.clone();
+        if let Some(list) = patch.hotkeys {
+            settings.hotkeys = hotkeys::normalize_hotkeys(&list)?;
+        }
+        if let Some(hotkey) = patch.relaunch_hotkey {
+            settings.relaunch_hotkey = action_hotkey(&hotkey)?;
+        }
+        if let Some(hotkey) = patch.selection_hotkey {
+            settings.selection_hotkey = action_hotkey(&hotkey)?;
+        }
+        for hotkey in [&settings.relaunch_hotkey, &settings.selection_hotkey] {
+            if settings.hotkeys.iter().any(|h| h.eq_ignore_ascii_case(hotkey)) {
+                return Err(format!("{} already shows the launcher", hotkey));
+            }
+        }
+        if !settings.relaunch_hotkey.is_empty() && settings.relaunch_hotkey.eq_ignore_ascii_case(&settings.selection_hotkey) {
+            return Err("The relaunch and selection hotkeys must be different".to_string());
+        }
+        if let Some(limit) = patch.result_limit {
+            if !(1..=searcher::MAX_RESULT_LIMIT).contains(&limit) {
+                return Err(format!("Result limit must be between 1 and {}", searcher::MAX_RESULT_LIMIT));
+            }
+            settings.result_limit = limit;
+        }
+        if let Some(enabled) = patch.enable_fuzzy {
+            settings.enable_fuzzy = enabled;
+        }
+        if let Some(enabled) = patch.deep_path_search {
+            settings.deep_path_search = enabled;
+        }
+        if let Some(enabled) = patch.context_boost {
+            settings.context_boost = enabled;
+        }
+        if let Some(enabled) = patch.hide_empty_files {
+            settings.hide_empty_files = enabled;
+        }
+        if let Some(mb) = patch.large_file_mb {
+            settings.large_file_mb = mb;
+        }
+        if let Some(enabled) = patch.group_results {
+            settings.group_results = enabled;
+        }
+        if let Some(caps) = patch.group_caps {
+            for (group, cap) in caps.each() {
+                if !(1..=searcher::MAX_RESULT_LIMIT).contains(&cap) {
+                    return Err(format!(
+                        "The cap for {} must be between 1 and {}",
+                        group,
+                        searcher::MAX_RESULT_LIMIT
+                    ));
+                }
+            }
+            settings.group_caps = caps;
+        }
+        if let Some(minutes) = patch.index_interval_minutes {
+            if !(1..=MAX_INDEX_INTERVAL_MINUTES).contains(&minutes) {
+                return Err(format!("Index interval must be between 1 and {} minutes", MAX_INDEX_INTERVAL_MINUTES));
+            }
+            settings.index_interval_minutes = minutes;
+        }
+        if let Some(extensions) = patch.excluded_extensions {
+            settings.excluded_extensions = normalize_extensions(&extensions)?;
+        }
+        if let Some(providers) = patch.index_providers {
+            if let Some(name) = providers.keys().find(|name| !indexer::PROVIDER_NAMES.contains(&name.as_str())) {
+                return Err(format!("Unknown index provider '{}'", name));
+            }
+            settings.index_providers = providers;
+        }
+        if let Some(roots) = patch.index_roots {
+            settings.index_roots = normalize_roots(&roots);
+        }
+        if let Some(command) = patch.file_manager {
+            settings.file_manager = command.trim().to_string();
+        }
+        if let Some(browser) = patch.bookmark_browser {
+            settings.bookmark_browser = browser;
+        }
+        if let Some(placement) = patch.window_placement {
+            settings.window_placement = placement;
+        }
+        if let Some(level) = patch.log_level {
+            settings.log_level = level;
+        }
+        if let Some(locale) = patch.locale {
+            settings.locale = locale;
+        }
+        if let Some(enabled) = patch.auto_paste_snippets {
+            settings.auto_paste_snippets = enabled;
+        }
+        if let Some(enabled) = patch.clipboard_history {
+            settings.clipboard_history = enabled;
+        }
+        if let Some(days) = patch.update_check_interval_days {
+            if days > MAX_UPDATE_CHECK_INTERVAL_DAYS {
+                return Err(format!(
+                    "Update check interval must be at most {} days (0 to turn checks off)",
+                    MAX_UPDATE_CHECK_INTERVAL_DAYS
+                ));
+            }
+            settings.update_check_interval_days = days;
+        }
+        if let Some(pinned) = patch.pin_by_default {
+            settings.pin_by_default = pinned;
+        }
+        if let Some(enabled) = patch.index_notifications {
+            settings.index_notifications = enabled;
+        }
+        if let Some(minutes) = patch.notify_slow_index_minutes {
+            if !(1..=MAX_INDEX_INTERVAL_MINUTES).contains(&minutes) {
+                return Err(format!(
+                    "Slow index notification must be between 1 and {} minutes",
+                    MAX_INDEX_INTERVAL_MINUTES
+                ));
+            }
+            settings.notify_slow_index_minutes = minutes;
+        }
+        if let Some(files) = patch.notify_removed_files {
+            if !(1..=MAX_NOTIFY_REMOVED_FILES).contains(&files) {
+                return Err(format!(
+                    "Removed files notification must be between 1 and {} files",
+                    MAX_NOTIFY_REMOVED_FILES
+                ));
+            }
+            settings.notify_removed_files = files;
+        }
+        if let Some(seconds) = patch.index_idle_seconds {
+            if seconds > MAX_INDEX_IDLE_SECONDS {
+                return Err(format!(
+                    "Idle time before indexing must be at most {} seconds",
+                    MAX_INDEX_IDLE_SECONDS
+                ));
+            }
+            settings.index_idle_seconds = seconds;
+        }
+        if let Some(enabled) = patch.index_on_battery {
+            settings.index_on_battery = enabled;
+        }
+        if let Some(slots) = patch.quick_slots {
+            settings.quick_slots = quick_slots::normalize_slots(&slots)?;
+        }
+        Ok(settings)
+    }
+
+    /// Persist every setting.
+    pub fn save(&self, db: &Database) -> Result<(), String> {
+        hotkeys::save_hotkeys(db, &self.hotkeys)?;
+        db.set_meta(RELAUNCH_HOTKEY_KEY, &self.relaunch_hotkey)
+            .map_err(|e| format!("Failed to save relaunch hotkey: {}", e))?;
+        db.set_meta(SELECTION_HOTKEY_KEY, &self.selection_hotkey)
+            .map_err(|e| format!("Failed to save selection hotkey: {}", e))?;
+        searcher::set_default_result_limit(db, self.result_limit)?;
+        searcher::set_fuzzy_enabled(db, self.enable_fuzzy)?;
+        searcher::set_deep_path_enabled(db, self.deep_path_search)?;
+        searcher::set_context_boost_enabled(db, self.context_boost)?;
+        searcher::set_hide_empty_files(db, self.hide_empty_files)?;
+        searcher::set_large_file_mb(db, self.large_file_mb)?;
+        db.set_meta(GROUP_RESULTS_KEY, &self.group_results.to_string())
+            .map_err(|e| format!("Failed to save grouping setting: {}", e))?;
+        let caps = serde_json::to_string(&self.group_caps).map_err(|e| e.to_string())?;
+        db.set_meta(GROUP_CAPS_KEY, &caps)
+            .map_err(|e| format!("Failed to save group caps: {}", e))?;
+        db.set_meta(INDEX_INTERVAL_KEY, &self.index_interval_minutes.to_string())
+            .map_err(|e| format!("Failed to save index interval: {}", e))?;
+        let extensions = serde_json::to_string(&self.excluded_extensions).map_err(|e| e.to_string())?;
+        db.set_meta(EXCLUDED_EXTENSIONS_KEY, &extensions)
+            .map_err(|e| format!("Failed to save excluded extensions: {}", e))?;
+        let providers = serde_json::to_string(&self.index_providers).map_err(|e| e.to_string())?;
+        db.set_meta(INDEX_PROVIDERS_KEY, &providers)
+            .map_err(|e| format!("Failed to save index providers: {}", e))?;
+        let roots = serde_json::to_string(&self.index_roots).map_err(|e| e.to_string())?;
+        db.set_meta(INDEX_ROOTS_KEY, &roots)
+            .map_err(|e| format!("Failed to save index folders: {}", e))?;
+        db.set_meta(WINDOW_PLACEMENT_KEY, self.window_placement.as_str())
+            .map_err(|e| format!("Failed to save window placement: {}", e))?;
+        db.set_meta(LOG_LEVEL_KEY, self.log_level.as_str())
+            .map_err(|e| format!("Failed to save log level: {}", e))?;
+        db.set_meta(LOCALE_KEY, self.locale.as_str())
+            .map_err(|e| format!("Failed to save language: {}", e))?;
+        db.set_meta(AUTO_PASTE_SNIPPETS_KEY, &self.auto_paste_snippets.to_string())
+            .map_err(|e| format!("Failed to save auto-paste setting: {}", e))?;
+        clipboard_history::set_enabled(db, self.clipboard_history)?;
+        bookmarks::set_bookmark_browser(db, self.bookmark_browser)?;
+        db.set_meta(UPDATE_CHECK_INTERVAL_KEY, &self.update_check_interval_days.to_string())
+            .map_err(|e| format!("Failed to save update check interval: {}", e))?;
+        db.set_meta(PIN_BY_DEFAULT_KEY, &self.pin_by_default.to_string())
+            .map_err(|e| format!("Failed to save pin setting: {}", e))?;
+        db.set_meta(INDEX_NOTIFICATIONS_KEY, &self.index_notifications.to_string())
+            .map_err(|e| format!("Failed to save notification setting: {}", e))?;
+        db.set_meta(NOTIFY_SLOW_INDEX_KEY, &self.notify_slow_index_minutes.to_string())
+            .map_err(|e| format!("Failed to save notification setting: {}", e))?;
+        db.set_meta(NOTIFY_REMOVED_FILES_KEY, &self.notify_removed_files.to_string())
+            .map_err(|e| format!("Failed to save notification setting: {}", e))?;
+        db.set_meta(INDEX_IDLE_SECONDS_KEY, &self.index_idle_seconds.to_string())
+            .map_err(|e| format!("Failed to save idle time setting: {}", e))?;
+        db.set_meta(INDEX_ON_BATTERY_KEY, &self.index_on_battery.to_string())
+            .map_err(|e| format!("Failed to save battery setting: {}", e))?;
+        db.replace_quick_slots(&self.quick_slots)
+            .map_err(|e| format!("Failed to save quick slots: {}", e))?;
+        launcher::set_file_manager_command(db, &self.file_manager)
+    }
+}
+
+fn index_interval_minutes(db: &Database) -> u64 {
+    db.get_meta(INDEX_INTERVAL_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .filter(|minutes| (1..=MAX_INDEX_INTERVAL_MINUTES).contains(minutes))
+        .unwrap_or(DEFAULT_INDEX_INTERVAL_MINUTES)
+}
+
+fn update_check_interval_days(db: &Database) -> u64 {
+    db.get_meta(UPDATE_CHECK_INTERVAL_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .filter(|days| *days <= MAX_UPDATE_CHECK_INTERVAL_DAYS)
+        .unwrap_or(DEFAULT_UPDATE_CHECK_INTERVAL_DAYS)
+}
+
+fn window_placement(db: &Database) -> WindowPlacement {
+    db.get_meta(WINDOW_PLACEMENT_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| WindowPlacement::parse(&value))
+        .unwrap_or_default()
+}
+
+fn log_level(db: &Database) -> LogLevel {
+    db.get_meta(LOG_LEVEL_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| LogLevel::parse(&value))
+        .unwrap_or_default()
+}
+
+fn locale(db: &Database) -> Locale {
+    db.get_meta(LOCALE_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| Locale::parse(&value))
+        .unwrap_or_default()
+}
+
+fn auto_paste_snippets(db: &Database) -> bool {
+    db.get_meta(AUTO_PASTE_SNIPPETS_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+fn pin_by_default(db: &Database) -> bool {
+    db.get_meta(PIN_BY_DEFAULT_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+/// A trimmed relaunch or selection hotkey, which can't be a double-tap;
+/// empty turns it off.
+fn action_hotkey(hotkey: &str) -> Result<String, String> {
+    let hotkey = hotkey.trim();
+    if hotkeys::parse_double_tap(hotkey).is_some() {
+        return Err("Only the launcher hotkeys can be double-taps".to_string());
+    }
+    Ok(hotkey.to_string())
+}
+
+fn index_notifications(db: &Database) -> bool {
+    db.get_meta(INDEX_NOTIFICATIONS_KEY).ok().flatten().is_none_or(|value| value != "false")
+}
+
+fn notify_slow_index_minutes(db: &Database) -> u64 {
+    db.get_meta(NOTIFY_SLOW_INDEX_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .filter(|minutes| (1..=MAX_INDEX_INTERVAL_MINUTES).contains(minutes))
+        .unwrap_or(DEFAULT_NOTIFY_SLOW_INDEX_MINUTES)
+}
+
+fn index_idle_seconds(db: &Database) -> u64 {
+    db.get_meta(INDEX_IDLE_SECONDS_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .filter(|seconds| *seconds <= MAX_INDEX_IDLE_SECONDS)
+        .unwrap_or(DEFAULT_INDEX_IDLE_SECONDS)
+}
+
+fn index_on_battery(db: &Database) -> bool {
+    db.get_meta(INDEX_ON_BATTERY_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+fn notify_removed_files(db: &Database) -> usize {
+    db.get_meta(NOTIFY_REMOVED_FILES_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .filter(|files| (1..=MAX_NOTIFY_REMOVED_FILES).contains(files))
+        .unwrap_or(DEFAULT_NOTIFY_REMOVED_FILES)
+}
+
+/// The persisted excluded extensions; the indexer reads these on every pass.
+pub fn excluded_extensions(db: &Database) -> Vec<String> {
+    let Some(json) = db.get_meta(EXCLUDED_EXTENSIONS_KEY).ok().flatten() else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        warn!("Ignoring invalid excluded extensions: {}", e);
+        Vec::new()
+    })
+}
+
+/// Which index providers are turned on or off; the indexer reads this on
+/// every pass.
+pub fn index_providers(db: &Database) -> BTreeMap<String, bool> {
+    let Some(json) = db.get_meta(INDEX_PROVIDERS_KEY).ok().flatten() else {
+        return BTreeMap::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        warn!("Ignoring invalid index providers: {}", e);
+        BTreeMap::new()
+    })
+}
+
+/// The folders chosen to be indexed, empty for the default ones; the
+/// indexer reads this on every pass.
+pub fn index_roots(db: &Database) -> Vec<String> {
+    let Some(json) = db.get_meta(INDEX_ROOTS_KEY).ok().flatten() else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        warn!("Ignoring invalid index folders: {}", e);
+        Vec::new()
+    })
+}
+
+fn group_results(db: &Database) -> bool {
+    db.get_meta(GROUP_RESULTS_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
+
+fn group_caps(db: &Database) -> GroupCaps {
+    let Some(json) = db.get_meta(GROUP_CAPS_KEY).ok().flatten() else {
+        return GroupCaps::default();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        warn!("Ignoring invalid group caps: {}", e);
+        GroupCaps::default()
+    })
+}
+
+/// Lowercase, strip leading dots ("*.TMP" becomes "tmp") and drop
+/// duplicates and blanks.
+fn normalize_extensions(extensions: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for extension in extensions {
+        let extension = extension.trim().trim_start_matches('*').trim_start_matches('.').to_lowercase();
+        if extension.is_empty() {
+            continue;
+        }
+        if extension.contains(|c: char| c.is_whitespace() || matches!(c, '.' | '/' | '\\' | '*' | '?')) {
+            return Err(format!("Invalid extension '{}'", extension));
+        }
+        if !normalized.contains(&extension) {
+            normalized.push(extension);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Trim folders and drop blanks and duplicates, which differ only in case
+/// on Windows.
+fn normalize_roots(roots: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for root in roots {
+        let root = root.trim();
+        if !root.is_empty() && !normalized.iter().any(|seen| seen.eq_ignore_ascii_case(root)) {
+            normalized.push(root.to_string());
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn patch(json: &str) -> SettingsPatch {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let defaults = Settings::load(&db);
+        assert_eq!(defaults.hotkeys, [hotkeys::DEFAULT_HOTKEY]);
+        assert_eq!(defaults.result_limit, searcher::DEFAULT_RESULT_LIMIT);
+        assert_eq!(defaults.index_interval_minutes, DEFAULT_INDEX_INTERVAL_MINUTES);
+        assert!(!defaults.clipboard_history);
+        assert_eq!(defaults.update_check_interval_days, DEFAULT_UPDATE_CHECK_INTERVAL_DAYS);
+        assert!(defaults.index_notifications);
+        assert!(defaults.enable_fuzzy);
+        assert!(!defaults.deep_path_search);
+        assert!(defaults.context_boost);
+        assert!(!defaults.hide_empty_files);
+        assert_eq!(defaults.large_file_mb, searcher::DEFAULT_LARGE_FILE_MB);
+        assert!(defaults.index_providers.is_empty());
+        assert!(defaults.index_roots.is_empty());
+        assert!(!defaults.group_results);
+        assert_eq!(defaults.group_caps, GroupCaps::default());
+        assert_eq!(defaults.notify_removed_files, DEFAULT_NOTIFY_REMOVED_FILES);
+        assert_eq!(defaults.index_idle_seconds, DEFAULT_INDEX_IDLE_SECONDS);
+        assert!(!defaults.index_on_battery);
+        assert_eq!(defaults.locale, Locale::En);
+        assert_eq!((defaults.relaunch_hotkey.as_str(), defaults.selection_hotkey.as_str()), ("", ""));
+        assert!(defaults.quick_slots.is_empty());
+
+        let updated = defaults
+            .with_patch(patch(
+                r#"{"result_limit": 40, "enable_fuzzy": false, "deep_path_search": true, "context_boost": false, "hide_empty_files": true, "large_file_mb": 0, "group_results": true, "group_caps": {"documents": 3},
+                    "excluded_extensions": ["*.TMP", ".log", "tmp"], "index_providers": {"filesystem": false, "bookmarks": true},
+                    "index_roots": [" C:\\Tools ", "", "c:\\tools", "D:\\Projects"],
+                    "bookmark_browser": "source",
+                    "window_placement": "focused_window", "log_level": "debug", "locale": "de", "auto_paste_snippets": true, "update_check_interval_days": 0,
+                    "pin_by_default": true, "index_notifications": false, "notify_slow_index_minutes": 30,
+                    "index_idle_seconds": 0, "index_on_battery": true, "relaunch_hotkey": " Ctrl+Shift+Space ",
+                    "quick_slots": {"2": " C:\\Apps\\Code.exe ", "9": "D:\\Notes"},
+                    "added_in_v9": true}"#,
+            ))
+            .unwrap();
+        assert_eq!(updated.result_limit, 40);
+        assert!(!updated.enable_fuzzy);
+        assert!(updated.deep_path_search);
+        assert!(!updated.context_boost);
+        assert!(updated.hide_empty_files);
+        assert_eq!(updated.large_file_mb, 0);
+        assert!(updated.group_results);
+        assert_eq!((updated.group_caps.documents, updated.group_caps.apps), (3, 5));
+        assert_eq!(updated.window_placement, WindowPlacement::FocusedWindow);
+        assert_eq!(updated.log_level, LogLevel::Debug);
+        assert_eq!(updated.locale, Locale::De);
+        assert!(updated.auto_paste_snippets);
+        assert_eq!(updated.update_check_interval_days, 0);
+        assert!(updated.pin_by_default);
+        assert!(!updated.index_notifications);
+        assert_eq!(updated.notify_slow_index_minutes, 30);
+        assert_eq!(updated.index_idle_seconds, 0);
+        assert!(updated.index_on_battery);
+        assert_eq!(updated.relaunch_hotkey, "Ctrl+Shift+Space");
+        assert_eq!(updated.excluded_extensions, ["tmp", "log"]);
+        assert_eq!(updated.index_providers.get("filesystem"), Some(&false));
+        assert_eq!(updated.index_roots, [r"C:\Tools", r"D:\Projects"]);
+        assert_eq!(updated.bookmark_browser, BookmarkBrowser::Source);
+        assert_eq!(updated.hotkeys, defaults.hotkeys);
+        assert_eq!(updated.quick_slots.get(&2).map(String::as_str), Some(r"C:\Apps\Code.exe"));
+        assert_eq!(updated.quick_slots.len(), 2);
+
+        updated.save(&db).unwrap();
+        assert_eq!(Settings::load(&db), updated);
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let settings = Settings::load(&Database::open(&PathBuf::from(":memory:")).unwrap());
+        assert!(settings.with_patch(patch(r#"{"result_limit": 0}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"index_interval_minutes": 0}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"group_caps": {"apps": 0}}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"update_check_interval_days": 400}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"index_idle_seconds": 7200}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"excluded_extensions": ["tar.gz"]}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"index_providers": {"gopher": true}}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"hotkeys": [" ", ""]}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"quick_slots": {"0": "C:\\a.txt"}}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"relaunch_hotkey": "ctrl+space"}"#)).is_err());
+        assert!(settings.with_patch(patch(r#"{"relaunch_hotkey": "Double Shift"}"#)).is_err());
+        assert!(settings
+            .with_patch(patch(r#"{"relaunch_hotkey": "Alt+R", "selection_hotkey": "alt+r"}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_corrupt_values_fall_back_to_defaults() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        db.set_meta(INDEX_INTERVAL_KEY, "soon").unwrap();
+        db.set_meta(EXCLUDED_EXTENSIONS_KEY, "{not json").unwrap();
+        let settings = Settings::load(&db);
+        assert_eq!(settings.index_interval_minutes, DEFAULT_INDEX_INTERVAL_MINUTES);
+        assert!(settings.excluded_extensions.is_empty());
+    }
+}
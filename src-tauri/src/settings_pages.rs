@@ -0,0 +1,181 @@
+//! Windows Settings pages and classic Control Panel applets, offered as
+//! results for queries like "bluetooth", "display settings" or "add remove
+//! programs". They aren't files, so they come from this table rather than
+//! the index.
+//!
+//! A page's `target` is either an `ms-settings:` URI or
+//! `control.exe /name <canonical name>`; `launcher::launch` starts both.
+
+/// A Settings page or Control Panel applet, found by its name or a keyword.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SettingsPage {
+    pub name: &'static str,
+    pub target: &'static str,
+    pub keywords: &'static [&'static str],
+}
+
+/// Prefix of a Windows Settings page URI.
+pub const SETTINGS_URI_PREFIX: &str = "ms-settings:";
+/// Prefix of a classic Control Panel target, followed by the applet's
+/// canonical name.
+pub const CONTROL_PANEL_PREFIX: &str = "control.exe /name ";
+
+const fn page(name: &'static str, target: &'static str, keywords: &'static [&'static str]) -> SettingsPage {
+    SettingsPage { name, target, keywords }
+}
+
+pub const SETTINGS_PAGES: &[SettingsPage] = &[
+    // System
+    page(
+        "Display settings",
+        "ms-settings:display",
+        &["screen resolution", "brightness", "scale", "monitor", "night light"],
+    ),
+    page("Sound settings", "ms-settings:sound", &["audio", "volume", "speakers", "microphone", "output device"]),
+    page("Notifications settings", "ms-settings:notifications", &["focus assist", "do not disturb", "alerts"]),
+    page("Power & sleep settings", "ms-settings:powersleep", &["power", "sleep", "screen timeout", "battery saver"]),
+    page("Battery settings", "ms-settings:batterysaver", &["battery", "battery saver", "battery usage"]),
+    page(
+        "Storage settings",
+        "ms-settings:storagesense",
+        &["disk space", "storage sense", "free up space", "disk cleanup"],
+    ),
+    page("Multitasking settings", "ms-settings:multitasking", &["snap windows", "virtual desktops", "alt tab"]),
+    page("Clipboard settings", "ms-settings:clipboard", &["clipboard history"]),
+    page("Remote Desktop settings", "ms-settings:remotedesktop", &["rdp", "remote desktop"]),
+    page("About this PC", "ms-settings:about", &["system info", "pc name", "rename pc", "windows version", "specs"]),
+    // Devices
+    page("Bluetooth & devices", "ms-settings:bluetooth", &["bluetooth", "pair", "headphones", "devices"]),
+    page("Printers & scanners", "ms-settings:printers", &["printer", "scanner", "print"]),
+    page("Mouse settings", "ms-settings:mousetouchpad", &["mouse", "pointer speed", "scroll"]),
+    page("Touchpad settings", "ms-settings:devices-touchpad", &["touchpad", "trackpad", "gestures"]),
+    page("AutoPlay settings", "ms-settings:autoplay", &["autoplay", "usb drive"]),
+    // Network
+    page("Network & internet", "ms-settings:network-status", &["network", "internet", "connection status"]),
+    page("Wi-Fi settings", "ms-settings:network-wifi", &["wifi", "wireless", "wlan", "hotspot networks"]),
+    page("Ethernet settings", "ms-settings:network-ethernet", &["ethernet", "lan", "cable"]),
+    page("VPN settings", "ms-settings:network-vpn", &["vpn"]),
+    page("Airplane mode", "ms-settings:network-airplanemode", &["flight mode", "airplane"]),
+    page("Mobile hotspot", "ms-settings:network-mobilehotspot", &["hotspot", "tethering"]),
+    page("Proxy settings", "ms-settings:network-proxy", &["proxy"]),
+    // Personalization
+    page("Background settings", "ms-settings:personalization-background", &["wallpaper", "desktop background"]),
+    page("Colors settings", "ms-settings:colors", &["dark mode", "light mode", "accent color", "theme color"]),
+    page("Lock screen settings", "ms-settings:lockscreen", &["lock screen", "screen saver"]),
+    page("Themes settings", "ms-settings:themes", &["themes", "desktop icons"]),
+    page("Taskbar settings", "ms-settings:taskbar", &["taskbar", "system tray", "notification area"]),
+    page("Start settings", "ms-settings:personalization-start", &["start menu"]),
+    page("Fonts settings", "ms-settings:fonts", &["fonts", "install font"]),
+    // Apps
+    page("Installed apps", "ms-settings:appsfeatures", &["apps and features", "uninstall", "remove app"]),
+    page("Default apps", "ms-settings:defaultapps", &["default browser", "file associations", "open with"]),
+    page("Startup apps", "ms-settings:startupapps", &["startup", "autostart", "run at login"]),
+    page("Optional features", "ms-settings:optionalfeatures", &["windows features", "add feature"]),
+    // Accounts
+    page("Your info", "ms-settings:yourinfo", &["account", "profile picture", "microsoft account"]),
+    page("Sign-in options", "ms-settings:signinoptions", &["password", "pin", "windows hello", "fingerprint"]),
+    page("Family & other users", "ms-settings:otherusers", &["add user", "other users", "family"]),
+    // Time & language
+    page("Date & time settings", "ms-settings:dateandtime", &["clock", "time zone", "date"]),
+    page("Language & region", "ms-settings:regionlanguage", &["language", "region", "keyboard layout", "input"]),
+    page("Speech settings", "ms-settings:speech", &["speech", "voice"]),
+    // Gaming & accessibility
+    page("Game Mode", "ms-settings:gaming-gamemode", &["gaming", "game bar"]),
+    page("Accessibility", "ms-settings:easeofaccess", &["ease of access", "magnifier", "narrator", "high contrast"]),
+    // Privacy & update
+    page("Privacy & security", "ms-settings:privacy", &["privacy", "permissions"]),
+    page("Camera privacy", "ms-settings:privacy-webcam", &["camera", "webcam"]),
+    page("Microphone privacy", "ms-settings:privacy-microphone", &["microphone access"]),
+    page("Location settings", "ms-settings:privacy-location", &["location", "gps"]),
+    page("Windows Security", "ms-settings:windowsdefender", &["defender", "antivirus", "firewall", "virus"]),
+    page("Windows Update", "ms-settings:windowsupdate", &["update", "updates", "check for updates", "patch"]),
+    page("Recovery", "ms-settings:recovery", &["reset this pc", "reset pc", "advanced startup"]),
+    page("Activation", "ms-settings:activation", &["product key", "activate windows", "license"]),
+    page("Troubleshoot", "ms-settings:troubleshoot", &["troubleshooter", "fix problems"]),
+    // Classic Control Panel
+    page("Control Panel", "control.exe /name Microsoft.AllControlPanelItems", &["control panel"]),
+    page(
+        "Programs and Features",
+        "control.exe /name Microsoft.ProgramsAndFeatures",
+        &["add remove programs", "add or remove programs", "uninstall a program", "appwiz"],
+    ),
+    page("Device Manager", "control.exe /name Microsoft.DeviceManager", &["drivers", "hardware", "devmgmt"]),
+    page(
+        "Network and Sharing Center",
+        "control.exe /name Microsoft.NetworkAndSharingCenter",
+        &["network adapter", "sharing center"],
+    ),
+    page("Power Options", "control.exe /name Microsoft.PowerOptions", &["power plan", "high performance"]),
+    page("Sound (classic)", "control.exe /name Microsoft.Sound", &["playback devices", "recording devices", "mmsys"]),
+    page("Mouse Properties", "control.exe /name Microsoft.Mouse", &["double click speed", "cursor"]),
+    page(
+        "Administrative Tools",
+        "control.exe /name Microsoft.AdministrativeTools",
+        &["windows tools", "services", "event viewer"],
+    ),
+    page("Credential Manager", "control.exe /name Microsoft.CredentialManager", &["saved passwords", "credentials"]),
+    page("User Accounts", "control.exe /name Microsoft.UserAccounts", &["user account control", "uac"]),
+    page("Windows Defender Firewall", "control.exe /name Microsoft.WindowsFirewall", &["firewall"]),
+    page(
+        "System Properties",
+        "control.exe /name Microsoft.System",
+        &["environment variables", "advanced system settings", "computer name"],
+    ),
+    page(
+        "File Explorer Options",
+        "control.exe /name Microsoft.FolderOptions",
+        &["folder options", "show hidden files", "file extensions"],
+    ),
+    page("Internet Options", "control.exe /name Microsoft.InternetOptions", &["inetcpl", "internet properties"]),
+    page(
+        "Region (classic)",
+        "control.exe /name Microsoft.RegionAndLanguage",
+        &["date format", "number format", "intl"],
+    ),
+    page("Backup and Restore", "control.exe /name Microsoft.BackupAndRestore", &["backup", "system image"]),
+];
+
+/// Whether `target` is a Settings page or Control Panel applet rather than
+/// a file.
+pub fn is_settings_target(target: &str) -> bool {
+    target.starts_with(SETTINGS_URI_PREFIX) || target.starts_with(CONTROL_PANEL_PREFIX)
+}
+
+/// The canonical applet name of a Control Panel target.
+pub fn control_panel_name(target: &str) -> Option<&str> {
+    target.strip_prefix(CONTROL_PANEL_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_target_is_well_formed() {
+        for page in SETTINGS_PAGES {
+            assert!(is_settings_target(page.target), "{}", page.name);
+            if let Some(page_id) = page.target.strip_prefix(SETTINGS_URI_PREFIX) {
+                assert!(
+                    !page_id.is_empty() && page_id.chars().all(|c| c.is_ascii_lowercase() || c == '-'),
+                    "bad URI for {}: {}",
+                    page.name,
+                    page.target
+                );
+            } else {
+                let applet = control_panel_name(page.target).unwrap();
+                let name = applet.strip_prefix("Microsoft.").unwrap_or_default();
+                assert!(
+                    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()),
+                    "bad canonical name for {}: {}",
+                    page.name,
+                    page.target
+                );
+            }
+            assert!(page.keywords.iter().all(|k| *k == k.to_lowercase()), "{}", page.name);
+        }
+        let mut targets: Vec<&str> = SETTINGS_PAGES.iter().map(|page| page.target).collect();
+        targets.sort();
+        targets.dedup();
+        assert_eq!(targets.len(), SETTINGS_PAGES.len(), "duplicate target");
+    }
+}
@@ -0,0 +1,131 @@
+//! Text snippets: an address, a code block or an email template, found by
+//! name or keyword and copied to the clipboard when picked.
+//!
+//! With auto-paste on, picking one also pastes it where the user was
+//! typing: the launcher hides, focus goes back to the window that had it
+//! before the launcher opened (remembered by
+//! [`WindowList::remember_foreground`](crate::open_windows::WindowList::remember_foreground)),
+//! and Ctrl+V is sent to it.
+
+use crate::db::NewSnippet;
+use crate::open_windows;
+use std::time::Duration;
+
+/// Snippets longer than this are refused; they're meant to be small.
+const MAX_BODY_CHARS: usize = 100_000;
+/// Time for the restored window to take keyboard focus before the paste.
+const PASTE_DELAY: Duration = Duration::from_millis(80);
+
+/// Validate a snippet from the frontend. Keywords may be separated by
+/// spaces or commas; they're lowercased and deduplicated.
+pub fn prepare(name: &str, keywords: &[String], body: &str) -> Result<NewSnippet, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    if body.trim().is_empty() {
+        return Err("Snippet text cannot be empty".to_string());
+    }
+    if body.chars().count() > MAX_BODY_CHARS {
+        return Err(format!("Snippets are limited to {} characters", MAX_BODY_CHARS));
+    }
+
+    let mut normalized: Vec<String> = Vec::new();
+    for keyword in keywords.iter().flat_map(|k| k.split(|c: char| c == ',' || c.is_whitespace())) {
+        let keyword = keyword.to_lowercase();
+        if !keyword.is_empty() && !normalized.contains(&keyword) {
+            normalized.push(keyword);
+        }
+    }
+    Ok(NewSnippet {
+        name: name.to_string(),
+        keywords: normalized,
+        body: body.to_string(),
+    })
+}
+
+/// Switch back to `hwnd` and press Ctrl+V in it. The snippet must already
+/// be on the clipboard.
+pub fn paste_into(hwnd: isize) -> Result<(), String> {
+    open_windows::focus_window(hwnd)?;
+    std::thread::sleep(PASTE_DELAY);
+    send_paste()
+}
+
+/// Send Ctrl+V to whichever window has keyboard focus.
+#[cfg(windows)]
+fn send_paste() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, VIRTUAL_KEY,
+        VK_CONTROL, VK_V,
+    };
+
+    let key = |vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    let inputs = [
+        key(VK_CONTROL, KEYBD_EVENT_FLAGS(0)),
+        key(VK_V, KEYBD_EVENT_FLAGS(0)),
+        key(VK_V, KEYEVENTF_KEYUP),
+        key(VK_CONTROL, KEYEVENTF_KEYUP),
+    ];
+    // SAFETY: `inputs` are fully initialized keyboard events and the size
+    // passed is that of one INPUT, as the API requires.
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        // Blocked by UIPI when the target runs elevated and we don't
+        return Err("Couldn't paste into that window; the snippet is on the clipboard".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn send_paste() -> Result<(), String> {
+    Err("Pasting is only supported on Windows; the snippet is on the clipboard".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_prepare_normalizes_keywords() {
+        let keywords = ["Home, ADDRESS".to_string(), "  post  home".to_string()];
+        let snippet = prepare(" My address ", &keywords, "1 Main St\nSpringfield").unwrap();
+        assert_eq!(snippet.name, "My address");
+        assert_eq!(snippet.keywords, ["home", "address", "post"]);
+        assert_eq!(snippet.body, "1 Main St\nSpringfield");
+
+        assert!(prepare(" ", &[], "text").is_err());
+        assert!(prepare("Empty", &[], " \n ").is_err());
+        assert!(prepare("Huge", &[], &"x".repeat(MAX_BODY_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_snippet_crud() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        let address = prepare("My address", &["home".to_string()], "1 Main St").unwrap();
+        let id = db.add_snippet(&address).unwrap();
+        db.record_snippet_use(id).unwrap();
+        let stored = db.snippet(id).unwrap().unwrap();
+        assert_eq!((stored.keywords.as_slice(), stored.use_count), (["home".to_string()].as_slice(), 1));
+
+        let moved = prepare("My address", &[], "2 Elm St").unwrap();
+        assert!(db.update_snippet(id, &moved).unwrap());
+        assert_eq!(db.snippets().unwrap()[0].body, "2 Elm St");
+        assert!(db.remove_snippet(id).unwrap());
+        assert!(!db.update_snippet(id, &moved).unwrap());
+        assert_eq!(db.snippet(id).unwrap(), None);
+    }
+}
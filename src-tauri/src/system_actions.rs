@@ -0,0 +1,98 @@
+use log::{error, info};
+use std::process::Command;
+
+/// Error returned by [`run`] when a destructive action is requested without
+/// confirmation. The frontend asks the user and retries with `confirmed`.
+pub const CONFIRMATION_REQUIRED: &str = "confirmation_required";
+
+/// A power or session action offered as a search result
+/// (see `searcher::SYSTEM_ACTIONS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemAction {
+    Lock,
+    Sleep,
+    SignOut,
+    Restart,
+    Shutdown,
+    EmptyRecycleBin,
+}
+
+impl SystemAction {
+    /// Parse the action id used in search results.
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "lock" => Some(SystemAction::Lock),
+            "sleep" => Some(SystemAction::Sleep),
+            "sign_out" => Some(SystemAction::SignOut),
+            "restart" => Some(SystemAction::Restart),
+            "shutdown" => Some(SystemAction::Shutdown),
+            "empty_recycle_bin" => Some(SystemAction::EmptyRecycleBin),
+            _ => None,
+        }
+    }
+
+    /// Actions that lose unsaved work or data can't run without confirmation.
+    pub fn is_destructive(self) -> bool {
+        matches!(
+            self,
+            SystemAction::SignOut | SystemAction::Restart | SystemAction::Shutdown | SystemAction::EmptyRecycleBin
+        )
+    }
+}
+
+/// Run `action`. Destructive actions fail with [`CONFIRMATION_REQUIRED`]
+/// unless `confirmed` is set.
+pub fn run(action: SystemAction, confirmed: bool) -> Result<(), String> {
+    if action.is_destructive() && !confirmed {
+        return Err(CONFIRMATION_REQUIRED.to_string());
+    }
+    info!("Running system action: {:?}", action);
+
+    match action {
+        SystemAction::Lock => spawn("rundll32", &["user32.dll,LockWorkStation"]),
+        // Suspends to RAM; Windows hibernates instead when hibernation is enabled
+        SystemAction::Sleep => spawn("rundll32", &["powrprof.dll,SetSuspendState", "0,1,0"]),
+        SystemAction::SignOut => spawn("shutdown", &["/l"]),
+        SystemAction::Restart => spawn("shutdown", &["/r", "/t", "0"]),
+        SystemAction::Shutdown => spawn("shutdown", &["/s", "/t", "0"]),
+        SystemAction::EmptyRecycleBin => empty_recycle_bin(),
+    }
+}
+
+fn spawn(program: &str, args: &[&str]) -> Result<(), String> {
+    crate::launcher::no_window(&mut Command::new(program)).args(args).spawn().map_err(|e| {
+        error!("Failed to run {} {:?}: {}", program, args, e);
+        format!("Failed to run {}: {}", program, e)
+    })?;
+    Ok(())
+}
+
+/// Empty the Recycle Bin on every drive without Explorer's own prompt
+/// (the frontend has already confirmed).
+#[cfg(windows)]
+fn empty_recycle_bin() -> Result<(), String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::{
+        SHEmptyRecycleBinW, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI, SHERB_NOSOUND,
+    };
+
+    // SAFETY: a null window and root path are documented to mean "no owner"
+    // and "all drives".
+    unsafe {
+        SHEmptyRecycleBinW(
+            HWND::default(),
+            PCWSTR::null(),
+            SHERB_NOCONFIRMATION | SHERB_NOPROGRESSUI | SHERB_NOSOUND,
+        )
+    }
+    .map_err(|e| {
+        error!("Failed to empty Recycle Bin: {}", e);
+        format!("Failed to empty Recycle Bin: {}", e)
+    })
+}
+
+#[cfg(not(windows))]
+fn empty_recycle_bin() -> Result<(), String> {
+    Err("Emptying the Recycle Bin is only supported on Windows".to_string())
+}
@@ -0,0 +1,124 @@
+//! Whether now is a good time for a background index pass: not while the
+//! user is typing or presenting, and not on battery unless they said so.
+//! Manual rebuilds ignore all of this.
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// How long a skipped background pass waits before checking again.
+pub const DEFER_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Why a background pass didn't run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// There was keyboard or mouse input within the idle threshold.
+    UserActive,
+    /// The machine is running on battery.
+    OnBattery,
+}
+
+impl SkipReason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            SkipReason::UserActive => "the computer is in use",
+            SkipReason::OnBattery => "the computer is on battery",
+        }
+    }
+}
+
+/// The conditions a background pass waits for, from settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackgroundPolicy {
+    /// Input this recent defers the pass; zero never does.
+    pub idle_threshold: Duration,
+    pub index_on_battery: bool,
+}
+
+/// Why a background pass shouldn't run now, if it shouldn't.
+pub fn background_skip_reason(policy: &BackgroundPolicy) -> Option<SkipReason> {
+    skip_reason(policy, idle_time(), on_battery())
+}
+
+/// [`background_skip_reason`] for a given idle time and power source.
+/// Whatever can't be determined doesn't hold the pass back.
+fn skip_reason(policy: &BackgroundPolicy, idle: Option<Duration>, on_battery: Option<bool>) -> Option<SkipReason> {
+    if !policy.index_on_battery && on_battery == Some(true) {
+        return Some(SkipReason::OnBattery);
+    }
+    if idle.is_some_and(|idle| idle < policy.idle_threshold) {
+        return Some(SkipReason::UserActive);
+    }
+    None
+}
+
+/// Time since the last keyboard or mouse input in this session.
+#[cfg(windows)]
+fn idle_time() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: `cbSize` is set as the API requires.
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both tick counts wrap after 49.7 days
+        Some(Duration::from_millis(GetTickCount().wrapping_sub(info.dwTime) as u64))
+    }
+}
+
+#[cfg(not(windows))]
+fn idle_time() -> Option<Duration> {
+    None
+}
+
+/// Whether the machine runs on battery; `None` when Windows doesn't know.
+#[cfg(windows)]
+fn on_battery() -> Option<bool> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // SAFETY: `status` is a valid out pointer for the call.
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+fn on_battery() -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_reason() {
+        let policy = BackgroundPolicy {
+            idle_threshold: Duration::from_secs(60),
+            index_on_battery: false,
+        };
+        let idle = Some(Duration::from_secs(600));
+        let active = Some(Duration::from_secs(5));
+
+        assert_eq!(skip_reason(&policy, idle, Some(false)), None);
+        assert_eq!(skip_reason(&policy, active, Some(false)), Some(SkipReason::UserActive));
+        assert_eq!(skip_reason(&policy, idle, Some(true)), Some(SkipReason::OnBattery));
+        assert_eq!(skip_reason(&policy, None, None), None);
+
+        let relaxed = BackgroundPolicy {
+            idle_threshold: Duration::ZERO,
+            index_on_battery: true,
+        };
+        assert_eq!(skip_reason(&relaxed, active, Some(true)), None);
+    }
+}
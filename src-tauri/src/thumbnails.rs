@@ -0,0 +1,346 @@
+//! Small thumbnails for image and video results in the result list.
+//!
+//! Windows' thumbnail cache (`IShellItemImageFactory`) makes them where it
+//! can, which covers videos and any image format with a codec installed;
+//! common image formats are decoded directly when it can't. Each thumbnail
+//! is saved as a PNG named after the file's path, modification time and
+//! size, so the same results typed again cost a file read. The list asks
+//! for a dozen at once while the user types, so only a few are made at a
+//! time and the rest wait their turn.
+
+use crate::preview;
+use base64::Engine;
+use log::{debug, warn};
+use serde::Serialize;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Largest thumbnail made, in pixels on the longest side.
+pub const MAX_THUMBNAIL_PX: u32 = 256;
+const MIN_THUMBNAIL_PX: u32 = 16;
+/// Thumbnails made at the same time.
+const CONCURRENT_THUMBNAILS: usize = 2;
+/// How long making one may take before giving up (slow network paths).
+const THUMBNAIL_TIMEOUT: Duration = Duration::from_secs(3);
+/// Thumbnails kept on disk by [`Thumbnails::prune`].
+const MAX_CACHED_THUMBNAILS: usize = 2000;
+
+/// Formats only the shell can thumbnail.
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "m4v", "mkv", "avi", "mov", "wmv", "webm"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Thumbnail {
+    /// PNG, at most the requested size on its longest side.
+    Ready { data_base64: String },
+    /// There's no thumbnail to show: the file is gone or online-only, its
+    /// format has no codec, it's corrupt, or it took too long. Expected for
+    /// plenty of files, so it isn't an error.
+    Unavailable,
+}
+
+/// Makes thumbnails and keeps them in a cache folder.
+pub struct Thumbnails {
+    dir: PathBuf,
+    permits: Semaphore,
+}
+
+impl Thumbnails {
+    pub fn new(dir: PathBuf) -> Self {
+        Thumbnails {
+            dir,
+            permits: Semaphore::new(CONCURRENT_THUMBNAILS),
+        }
+    }
+
+    /// The thumbnail of `filepath`, at most `max_px` pixels (clamped to
+    /// 16–[`MAX_THUMBNAIL_PX`]) on its longest side, from the cache or
+    /// made now. Failed attempts are remembered until the file changes,
+    /// except timeouts, which are retried next time.
+    pub async fn get(&self, filepath: String, max_px: u32) -> Thumbnail {
+        let max_px = max_px.clamp(MIN_THUMBNAIL_PX, MAX_THUMBNAIL_PX);
+        let dir = self.dir.clone();
+        let path = PathBuf::from(&filepath);
+        let lookup = tokio::task::spawn_blocking(move || cache_lookup(&dir, &path, max_px));
+        let cache_file = match tokio::time::timeout(THUMBNAIL_TIMEOUT, lookup).await {
+            Ok(Ok(Lookup::Cached(thumbnail))) => return thumbnail,
+            Ok(Ok(Lookup::Missing(cache_file))) => cache_file,
+            _ => return Thumbnail::Unavailable,
+        };
+
+        let Ok(_permit) = self.permits.acquire().await else {
+            return Thumbnail::Unavailable;
+        };
+        let task = tokio::task::spawn_blocking(move || {
+            // Made meanwhile by a request that was ahead in the queue
+            if let Some(thumbnail) = read_cached(&cache_file) {
+                return thumbnail;
+            }
+            make_and_store(Path::new(&filepath), max_px, &cache_file)
+        });
+        match tokio::time::timeout(THUMBNAIL_TIMEOUT, task).await {
+            Ok(Ok(thumbnail)) => thumbnail,
+            _ => Thumbnail::Unavailable,
+        }
+    }
+
+    /// Remove all but the [`MAX_CACHED_THUMBNAILS`] most recently made
+    /// thumbnails. Returns how many were removed.
+    pub fn prune(&self) -> usize {
+        prune_dir(&self.dir, MAX_CACHED_THUMBNAILS)
+    }
+}
+
+/// Whether a result of `file_type` with `extension` can have a thumbnail
+/// at all, to turn the rest away without touching the disk.
+pub fn supports(file_type: &str, extension: &str) -> bool {
+    let extension = extension.to_lowercase();
+    file_type == "image" || VIDEO_EXTENSIONS.contains(&extension.as_str())
+}
+
+enum Lookup {
+    Cached(Thumbnail),
+    /// Not made yet; this is where it goes.
+    Missing(PathBuf),
+}
+
+/// Find the cached thumbnail of `path` in `dir`. Files that can't have one
+/// (missing, folders, online-only) count as cached as unavailable.
+fn cache_lookup(dir: &Path, path: &Path, max_px: u32) -> Lookup {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return Lookup::Cached(Thumbnail::Unavailable);
+    };
+    if !metadata.is_file() || preview::is_cloud_placeholder(&metadata) {
+        return Lookup::Cached(Thumbnail::Unavailable);
+    }
+    let mut hasher = DefaultHasher::new();
+    (path, metadata.modified().ok(), metadata.len(), max_px).hash(&mut hasher);
+    let cache_file = dir.join(format!("{:016x}.png", hasher.finish()));
+    match read_cached(&cache_file) {
+        Some(thumbnail) => Lookup::Cached(thumbnail),
+        None => Lookup::Missing(cache_file),
+    }
+}
+
+/// A cached thumbnail; an empty file records that there is none.
+fn read_cached(cache_file: &Path) -> Option<Thumbnail> {
+    let png = std::fs::read(cache_file).ok()?;
+    Some(thumbnail_from(png))
+}
+
+fn thumbnail_from(png: Vec<u8>) -> Thumbnail {
+    if png.is_empty() {
+        Thumbnail::Unavailable
+    } else {
+        Thumbnail::Ready {
+            data_base64: base64::engine::general_purpose::STANDARD.encode(png),
+        }
+    }
+}
+
+/// Make the thumbnail of `path` and save it to `cache_file`, or save that
+/// there is none.
+fn make_and_store(path: &Path, max_px: u32, cache_file: &Path) -> Thumbnail {
+    let png = make(path, max_px).unwrap_or_else(|e| {
+        debug!("No thumbnail for '{}': {}", path.display(), e);
+        Vec::new()
+    });
+    let stored = cache_file
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(cache_file, &png));
+    if let Err(e) = stored {
+        warn!("Failed to cache thumbnail of '{}': {}", path.display(), e);
+    }
+    thumbnail_from(png)
+}
+
+/// A PNG thumbnail of `path`: the shell's, or else one decoded directly.
+fn make(path: &Path, max_px: u32) -> Result<Vec<u8>, String> {
+    let image = match shell_thumbnail(path, max_px) {
+        Ok(image) => image,
+        Err(shell_error) => decode_thumbnail(path).map_err(|e| format!("{}; {}", shell_error, e))?,
+    };
+    preview::encode_png(preview::shrink_to(image, max_px))
+}
+
+/// Decode an image in one of the formats the `image` crate reads.
+fn decode_thumbnail(path: &Path) -> Result<image::DynamicImage, String> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !preview::IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("can't decode .{} files", extension));
+    }
+    let bytes = preview::read_capped(path, preview::MAX_IMAGE_BYTES + 1)?;
+    if bytes.len() as u64 > preview::MAX_IMAGE_BYTES {
+        return Err("too large to decode".to_string());
+    }
+    preview::decode_image(&bytes)
+}
+
+/// Ask Windows' thumbnail cache for a thumbnail of `path`, never settling
+/// for the file type's icon. COM is initialized single-threaded here and
+/// released afterwards, unless the thread already joined another apartment.
+#[cfg(windows)]
+fn shell_thumbnail(path: &Path, max_px: u32) -> Result<image::DynamicImage, String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::SIZE;
+    use windows::Win32::Graphics::Gdi::{
+        CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+        DIB_RGB_COLORS, HDC,
+    };
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+    use windows::Win32::UI::Shell::{IShellItemImageFactory, SHCreateItemFromParsingName, SIIGBF_THUMBNAILONLY};
+
+    let wide = HSTRING::from(path);
+    let size = SIZE {
+        cx: max_px as i32,
+        cy: max_px as i32,
+    };
+    // SAFETY: the path string outlives the calls using it, the pixel buffer
+    // is sized from the header GetDIBits filled in, the DC and bitmap are
+    // released exactly once, and COM is only uninitialized if we
+    // initialized it.
+    let pixels = unsafe {
+        let com = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let pixels = (|| -> Result<(u32, u32, Vec<u8>), String> {
+            let factory: IShellItemImageFactory =
+                SHCreateItemFromParsingName(&wide, None).map_err(|e| e.to_string())?;
+            let bitmap = factory.GetImage(size, SIIGBF_THUMBNAILONLY).map_err(|e| e.to_string())?;
+            let dc = CreateCompatibleDC(HDC::default());
+            let mut info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            // The first call only fills in the dimensions
+            let mut read = GetDIBits(dc, bitmap, 0, 0, None, &mut info, DIB_RGB_COLORS) != 0;
+            let (width, height) = (info.bmiHeader.biWidth.unsigned_abs(), info.bmiHeader.biHeight.unsigned_abs());
+            // Ask for top-down 32-bit BGRA rows
+            info.bmiHeader.biBitCount = 32;
+            info.bmiHeader.biCompression = BI_RGB.0;
+            info.bmiHeader.biHeight = -(height as i32);
+            let mut pixels = vec![0u8; width as usize * height as usize * 4];
+            if read {
+                read = GetDIBits(
+                    dc,
+                    bitmap,
+                    0,
+                    height,
+                    Some(pixels.as_mut_ptr().cast()),
+                    &mut info,
+                    DIB_RGB_COLORS,
+                ) != 0;
+            }
+            let _ = DeleteDC(dc);
+            let _ = DeleteObject(bitmap);
+            if !read || width == 0 || height == 0 {
+                return Err("couldn't read the thumbnail bitmap".to_string());
+            }
+            Ok((width, height, pixels))
+        })();
+        if com.is_ok() {
+            CoUninitialize();
+        }
+        pixels
+    };
+    let (width, height, mut pixels) = pixels?;
+
+    // Opaque thumbnails often come back with the alpha channel left at zero
+    let opaque = pixels.chunks_exact(4).all(|pixel| pixel[3] == 0);
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        if opaque {
+            pixel[3] = 255;
+        }
+    }
+    image::RgbaImage::from_raw(width, height, pixels)
+        .map(image::DynamicImage::ImageRgba8)
+        .ok_or_else(|| "thumbnail bitmap has the wrong size".to_string())
+}
+
+#[cfg(not(windows))]
+fn shell_thumbnail(_path: &Path, _max_px: u32) -> Result<image::DynamicImage, String> {
+    Err("the thumbnail cache is only available on Windows".to_string())
+}
+
+/// Remove all but the `keep` most recently written files in `dir`.
+fn prune_dir(dir: &Path, keep: usize) -> usize {
+    let Ok(read) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = read
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if files.len() <= keep {
+        return 0;
+    }
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    files[keep..]
+        .iter()
+        .filter(|(_, path)| std::fs::remove_file(path).is_ok())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnails_are_made_once_and_cached() {
+        let dir = std::env::temp_dir().join(format!("ancheck-thumbnails-{}", std::process::id()));
+        let cache = dir.join("cache");
+        std::fs::create_dir_all(&dir).unwrap();
+        let picture = dir.join("wide.png");
+        image::RgbImage::new(1024, 512).save(&picture).unwrap();
+
+        let Lookup::Missing(cache_file) = cache_lookup(&cache, &picture, 64) else {
+            panic!("nothing should be cached yet");
+        };
+        let Thumbnail::Ready { data_base64 } = make_and_store(&picture, 64, &cache_file) else {
+            panic!("expected a thumbnail");
+        };
+        let png = base64::engine::general_purpose::STANDARD.decode(&data_base64).unwrap();
+        let thumbnail = image::load_from_memory(&png).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (64, 32));
+        assert!(matches!(
+            cache_lookup(&cache, &picture, 64),
+            Lookup::Cached(Thumbnail::Ready { data_base64: cached }) if cached == data_base64
+        ));
+        // Another size is another thumbnail
+        assert!(matches!(cache_lookup(&cache, &picture, 32), Lookup::Missing(_)));
+
+        // A corrupt image is remembered as having none
+        let corrupt = dir.join("corrupt.jpg");
+        std::fs::write(&corrupt, b"\xFF\xD8\xFF not really a jpeg").unwrap();
+        let Lookup::Missing(cache_file) = cache_lookup(&cache, &corrupt, 64) else {
+            panic!("nothing should be cached yet");
+        };
+        assert_eq!(make_and_store(&corrupt, 64, &cache_file), Thumbnail::Unavailable);
+        assert!(matches!(cache_lookup(&cache, &corrupt, 64), Lookup::Cached(Thumbnail::Unavailable)));
+
+        assert!(matches!(
+            cache_lookup(&cache, &dir.join("missing.png"), 64),
+            Lookup::Cached(Thumbnail::Unavailable)
+        ));
+
+        assert_eq!(prune_dir(&cache, 1), 1);
+        assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_thumbnail_support() {
+        assert!(supports("image", "png"));
+        assert!(supports("other", "MP4"));
+        assert!(!supports("document", "pdf"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Checking for and installing new versions with the updater plugin, from
+//! the tray, the `>Check for updates` command or a background check every
+//! `update_check_interval_days` (weekly by default).
+//!
+//! A check keeps the update it found, so installing right after doesn't
+//! fetch the release manifest again. Installing emits
+//! `update-download-progress` events and restarts into the new version.
+
+use crate::db::Database;
+use log::{info, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_updater::{Error as UpdaterError, Update, UpdaterExt};
+
+/// Unix seconds of the last background check.
+const LAST_CHECK_KEY: &str = "last_update_check";
+/// Wait after startup before the first background check, to stay out of
+/// the initial index's way.
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+/// How often the background task looks whether a check is due.
+const DUE_POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Result of `check_for_updates`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub current_version: String,
+    /// The new version, when one is available.
+    pub version: Option<String>,
+    /// Release notes.
+    pub notes: Option<String>,
+    /// When the new version was published (Unix seconds).
+    pub date: Option<i64>,
+}
+
+/// Payload of `update-download-progress`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    /// Size of the download, if the server sent it.
+    pub total: Option<u64>,
+}
+
+/// The update found by the last check, and whether one is installing.
+#[derive(Default)]
+pub struct UpdateState {
+    pending: Mutex<Option<Update>>,
+    installing: AtomicBool,
+}
+
+/// Ask the update server for a newer version.
+pub async fn check(app: &AppHandle, state: &UpdateState) -> Result<UpdateInfo, String> {
+    let updater = app.updater().map_err(|e| describe_error(&e))?;
+    let update = updater.check().await.map_err(|e| describe_error(&e))?;
+    let info = match &update {
+        Some(update) => {
+            info!("Update available: {} -> {}", update.current_version, update.version);
+            UpdateInfo {
+                available: true,
+                current_version: update.current_version.clone(),
+                version: Some(update.version.clone()),
+                notes: update.body.clone(),
+                date: update.date.map(|date| date.unix_timestamp()),
+            }
+        }
+        None => UpdateInfo {
+            available: false,
+            current_version: app.package_info().version.to_string(),
+            version: None,
+            notes: None,
+            date: None,
+        },
+    };
+    *state.pending.lock().unwrap() = update;
+    Ok(info)
+}
+
+/// One line about a check's outcome, for the tray and `>Check for updates`.
+pub fn describe(info: &UpdateInfo) -> String {
+    match &info.version {
+        Some(version) if info.available => format!(
+            "AnCheck {} is available (you have {}); run >Install update to get it",
+            version, info.current_version
+        ),
+        _ => format!("AnCheck {} is up to date", info.current_version),
+    }
+}
+
+/// Download and install the update found by the last check (checking again
+/// if there wasn't one), then restart. Only returns on failure, or when
+/// there's nothing to install.
+pub async fn install(app: &AppHandle, state: &UpdateState) -> Result<(), String> {
+    if state.installing.swap(true, Ordering::SeqCst) {
+        return Err("An update is already being installed".to_string());
+    }
+    let result = download_and_install(app, state).await;
+    state.installing.store(false, Ordering::SeqCst);
+    result?;
+    info!("Update installed; restarting");
+    app.restart()
+}
+
+async fn download_and_install(app: &AppHandle, state: &UpdateState) -> Result<(), String> {
+    let pending = state.pending.lock().unwrap().take();
+    let update = match pending {
+        Some(update) => update,
+        None => {
+            check(app, state).await?;
+            state
+                .pending
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or("AnCheck is already up to date")?
+        }
+    };
+
+    info!("Downloading update {}", update.version);
+    let mut downloaded = 0u64;
+    update
+        .download_and_install(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = app.emit("update-download-progress", DownloadProgress { downloaded, total });
+            },
+            || info!("Update downloaded"),
+        )
+        .await
+        .map_err(|e| describe_error(&e))
+}
+
+/// A message for the user instead of the plugin's, which assume the reader
+/// knows how updates are served.
+fn describe_error(error: &UpdaterError) -> String {
+    warn!("Updater error: {:?}", error);
+    match error {
+        UpdaterError::Reqwest(_) | UpdaterError::Network(_) => {
+            "Couldn't reach the update server; check your internet connection".to_string()
+        }
+        UpdaterError::Minisign(_) | UpdaterError::Base64(_) | UpdaterError::SignatureUtf8(_) => {
+            "The update's signature didn't match, so it wasn't installed".to_string()
+        }
+        UpdaterError::ReleaseNotFound | UpdaterError::TargetNotFound(_) | UpdaterError::TargetsNotFound(_) => {
+            "The update server has no release for this version of Windows".to_string()
+        }
+        UpdaterError::EmptyEndpoints => "Updates aren't configured in this build".to_string(),
+        other => format!("Update failed: {}", other),
+    }
+}
+
+/// Whether a background check is due, `interval_days` after the last one.
+/// An interval of 0 turns background checks off.
+fn check_due(last_check: i64, interval_days: u64, now: i64) -> bool {
+    interval_days > 0 && now - last_check >= (interval_days * 24 * 60 * 60) as i64
+}
+
+/// Check in the background whenever `interval_days` (read each time, so a
+/// settings change applies) says it's due, emitting `update-available`
+/// with an [`UpdateInfo`] when there's a new version.
+pub fn spawn_background_checks(
+    app: AppHandle,
+    db: Arc<Database>,
+    state: Arc<UpdateState>,
+    interval_days: impl Fn() -> u64 + Send + 'static,
+) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+        loop {
+            let last_check = db
+                .get_meta(LAST_CHECK_KEY)
+                .ok()
+                .flatten()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0);
+            let now = chrono::Utc::now().timestamp();
+            if check_due(last_check, interval_days(), now) {
+                match check(&app, &state).await {
+                    Ok(info) => {
+                        if let Err(e) = db.set_meta(LAST_CHECK_KEY, &now.to_string()) {
+                            warn!("Failed to record update check: {}", e);
+                        }
+                        if info.available {
+                            let _ = app.emit("update-available", info);
+                        }
+                    }
+                    // Offline now; try again next time round
+                    Err(e) => info!("Background update check failed: {}", e),
+                }
+            }
+            tokio::time::sleep(DUE_POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_due() {
+        let day = 24 * 60 * 60;
+        assert!(check_due(0, 7, 100));
+        assert!(!check_due(100, 7, 100 + 6 * day));
+        assert!(check_due(100, 7, 100 + 7 * day));
+        assert!(!check_due(0, 0, 100 * day));
+    }
+}
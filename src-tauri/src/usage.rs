@@ -0,0 +1,188 @@
+//! The usage leaderboard for the stats page: the most-launched entries of
+//! all time, this month or this week.
+//!
+//! All time comes straight from each entry's `click_count`. The other
+//! periods count rows in the launch log, which only keeps the last two
+//! months (see [`Database::record_click_at`]). Weeks start on Monday and
+//! months on the 1st, at local midnight.
+
+use crate::db::{Database, FileEntry};
+use crate::searcher::local_midnight;
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Most entries a leaderboard can list.
+pub const MAX_LEADERBOARD_ENTRIES: usize = 100;
+
+/// Which launches a leaderboard counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsagePeriod {
+    #[default]
+    AllTime,
+    ThisMonth,
+    ThisWeek,
+}
+
+/// One place on the leaderboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    /// 1 for the most launched.
+    pub rank: usize,
+    /// Launches in the period.
+    pub launches: i64,
+    /// Places gained on this week's ranking compared with last week's
+    /// (negative when it dropped). `None` when the entry wasn't launched in
+    /// one of the two weeks.
+    pub rank_change: Option<i64>,
+    pub entry: FileEntry,
+}
+
+/// The `limit` most-launched entries in `period`, ranked from today.
+pub fn leaderboard(db: &Database, limit: usize, period: UsagePeriod) -> Result<Vec<LeaderboardEntry>, String> {
+    leaderboard_on(db, limit, period, chrono::Local::now().date_naive())
+}
+
+fn leaderboard_on(
+    db: &Database,
+    limit: usize,
+    period: UsagePeriod,
+    today: NaiveDate,
+) -> Result<Vec<LeaderboardEntry>, String> {
+    let limit = limit.clamp(1, MAX_LEADERBOARD_ENTRIES) as i64;
+    let this_week = week_start(today);
+    let (this_week_start, last_week_start) = (local_midnight(this_week), local_midnight(this_week - Days::new(7)));
+    let window = match period {
+        UsagePeriod::AllTime => None,
+        UsagePeriod::ThisMonth => Some((local_midnight(today.with_day(1).unwrap_or(today)), i64::MAX)),
+        UsagePeriod::ThisWeek => Some((this_week_start, i64::MAX)),
+    };
+    let ranked = db.most_launched(window, limit).map_err(|e| e.to_string())?;
+    let this_week_ranks = ranks(db, (this_week_start, i64::MAX))?;
+    let last_week_ranks = ranks(db, (last_week_start, this_week_start))?;
+
+    Ok(ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, (entry, launches))| {
+            let rank_change = this_week_ranks
+                .get(&entry.filepath)
+                .zip(last_week_ranks.get(&entry.filepath))
+                .map(|(&now, &before)| before as i64 - now as i64);
+            LeaderboardEntry {
+                rank: i + 1,
+                launches,
+                rank_change,
+                entry,
+            }
+        })
+        .collect())
+}
+
+/// Every entry launched in `window`, mapped to its 1-based rank.
+fn ranks(db: &Database, window: (i64, i64)) -> Result<HashMap<String, usize>, String> {
+    let ranked = db.most_launched(Some(window), -1).map_err(|e| e.to_string())?;
+    Ok(ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, (entry, _))| (entry.filepath, i + 1))
+        .collect())
+}
+
+/// The Monday on or before `day`.
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Days::new(u64::from(day.weekday().num_days_from_monday()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_week_start() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        // 2026-10-12 is a Monday
+        assert_eq!(week_start(date(12)), date(12));
+        assert_eq!(week_start(date(18)), date(12));
+        assert_eq!(week_start(date(19)), date(19));
+    }
+
+    #[test]
+    fn test_leaderboard_periods_and_rank_change() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        for name in ["editor.exe", "browser.exe", "notes.txt", "old.doc"] {
+            db.upsert_file(name, &format!(r"C:\Apps\{}", name), "", 1, 1, "other").unwrap();
+        }
+        let path = |name: &str| format!(r"C:\Apps\{}", name);
+        // Sunday 2026-10-18; this week started Monday the 12th
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let at = |day: u32| local_midnight(NaiveDate::from_ymd_opt(2026, 10, day).unwrap()) + 3600;
+        let launch = |name: &str, day: u32, times: usize| {
+            for _ in 0..times {
+                db.record_click_at(&path(name), at(day)).unwrap();
+            }
+        };
+        // Last week: browser, then editor, then notes
+        launch("browser.exe", 6, 3);
+        launch("editor.exe", 7, 2);
+        launch("notes.txt", 8, 1);
+        // Earlier this month, before last week
+        launch("old.doc", 1, 8);
+        // This week: editor overtakes browser, notes isn't used
+        launch("editor.exe", 13, 4);
+        launch("browser.exe", 14, 1);
+
+        let week = leaderboard_on(&db, 10, UsagePeriod::ThisWeek, today).unwrap();
+        let summary: Vec<_> = week
+            .iter()
+            .map(|e| (e.rank, e.entry.filename.as_str(), e.launches, e.rank_change))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(1, "editor.exe", 4, Some(1)), (2, "browser.exe", 1, Some(-1))]
+        );
+
+        let month = leaderboard_on(&db, 10, UsagePeriod::ThisMonth, today).unwrap();
+        let summary: Vec<_> = month
+            .iter()
+            .map(|e| (e.entry.filename.as_str(), e.launches, e.rank_change))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("old.doc", 8, None),
+                ("editor.exe", 6, Some(1)),
+                ("browser.exe", 4, Some(-1)),
+                ("notes.txt", 1, None),
+            ]
+        );
+
+        // All time counts launches the log no longer has
+        db.set_hidden(&path("old.doc"), true).unwrap();
+        let all_time = leaderboard_on(&db, 1, UsagePeriod::AllTime, today).unwrap();
+        assert_eq!(all_time.len(), 1);
+        assert_eq!(all_time[0].entry.filename, "editor.exe");
+        assert_eq!(all_time[0].launches, 6);
+    }
+
+    #[test]
+    fn test_launch_log_is_trimmed_and_cleared() {
+        let db = Database::open(&PathBuf::from(":memory:")).unwrap();
+        db.upsert_file("a.txt", r"C:\a.txt", "txt", 1, 1, "document").unwrap();
+        let now = 100 * 86_400;
+        db.record_click_at(r"C:\a.txt", now - 90 * 86_400).unwrap();
+        db.record_click_at(r"C:\a.txt", now).unwrap();
+        // Launches of files that aren't indexed aren't logged
+        db.record_click_at(r"C:\missing.txt", now).unwrap();
+
+        let logged = db.most_launched(Some((0, i64::MAX)), -1).unwrap();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].1, 1);
+        assert_eq!(db.most_launched(None, -1).unwrap()[0].1, 2);
+
+        db.clear_usage_data().unwrap();
+        assert!(db.most_launched(Some((0, i64::MAX)), -1).unwrap().is_empty());
+    }
+}
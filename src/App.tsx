@@ -4,23 +4,138 @@ import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import SearchInput from "./components/SearchInput";
 import ResultsList from "./components/ResultsList";
-import { useSearch } from "./hooks/useSearch";
+import { splitLaunchArgs, useSearch } from "./hooks/useSearch";
+import type {
+  AppError,
+  DownloadProgress,
+  SearchResult,
+  UpdateInfo,
+} from "./hooks/useSearch";
 import { useKeyboardNav } from "./hooks/useKeyboardNav";
 
+/**
+ * Run a launcher command, asking first when the backend says it deletes
+ * data. Returns the message to show, or undefined if the user cancelled.
+ */
+async function runLauncherCommand(
+  result: SearchResult,
+): Promise<string | null | undefined> {
+  try {
+    return await invoke<string | null>("run_launcher_command", {
+      actionId: result.filepath,
+    });
+  } catch (error) {
+    if (error !== "confirmation_required") throw error;
+    if (!window.confirm(`${result.filename}? ${result.parent_dir}.`)) {
+      return undefined;
+    }
+    return await invoke<string | null>("run_launcher_command", {
+      actionId: result.filepath,
+      confirmed: true,
+    });
+  }
+}
+
+/**
+ * Run a power/session action, asking first when the backend says it is
+ * destructive. Returns false if the user cancelled.
+ */
+async function runSystemAction(result: SearchResult): Promise<boolean> {
+  try {
+    await invoke("run_system_action", { action: result.filepath });
+  } catch (error) {
+    if (error !== "confirmation_required") throw error;
+    if (!window.confirm(`${result.filename}? ${result.parent_dir}.`)) {
+      return false;
+    }
+    await invoke("run_system_action", {
+      action: result.filepath,
+      confirmed: true,
+    });
+  }
+  return true;
+}
+
+/**
+ * End every process of a running app result, retrying as administrator
+ * (after asking) when the backend says access was denied. Returns false if
+ * the user cancelled.
+ */
+async function endTask(result: SearchResult): Promise<boolean> {
+  for (const pid of result.running_pids ?? []) {
+    try {
+      await invoke("kill_process", { pid });
+    } catch (error) {
+      if (error !== "elevation_required") throw error;
+      const name = result.display_name ?? result.filename;
+      if (!window.confirm(`Ending ${name} needs administrator rights. Try as administrator?`)) {
+        return false;
+      }
+      await invoke("kill_process", { pid, elevated: true });
+    }
+  }
+  return true;
+}
+
 function App() {
-  const { query, setQuery, results, mathResult, isLoading, clearSearch } =
-    useSearch(50);
+  const {
+    query,
+    setQuery,
+    results,
+    filterErrors,
+    mathResult,
+    isLoading,
+    clearSearch,
+    refresh,
+  } = useSearch(50);
   const [indexCount, setIndexCount] = useState<number>(0);
   const [isIndexing, setIsIndexing] = useState(false);
+  // A pinned window stays open when it loses focus
+  const [isPinned, setIsPinned] = useState(false);
+  const [commandMessage, setCommandMessage] = useState<string | null>(null);
+
+  // Any new query replaces the last command's message
+  useEffect(() => setCommandMessage(null), [query]);
 
   // Launch the selected result
   const handleSelect = useCallback(
     async (index: number) => {
       const result = results[index];
-      if (!result) return;
+      if (!result || result.match_type === "no_command") return;
 
       try {
-        await invoke("launch_file", { filepath: result.filepath });
+        if (result.match_type === "command") {
+          // Commands with a message keep the window open to show it
+          const message = await runLauncherCommand(result);
+          if (message === undefined) return;
+          if (message) {
+            setCommandMessage(message);
+            return;
+          }
+        } else if (result.match_type === "emoji") {
+          await invoke("copy_to_clipboard", { text: result.filepath });
+        } else if (result.match_type === "action") {
+          if (!(await runSystemAction(result))) return;
+        } else if (result.match_type === "snippet") {
+          // Copies, and pastes into the previous window if enabled
+          await invoke("use_snippet", { id: Number(result.filepath) });
+        } else if (result.match_type === "clipboard") {
+          await invoke("use_clipboard_entry", { id: Number(result.filepath) });
+        } else if (result.match_type === "window") {
+          await invoke("focus_window", { hwnd: Number(result.filepath) });
+        } else {
+          // Text after `--` is passed to the program as arguments
+          const { search, args } = splitLaunchArgs(query);
+          if (args.length > 0) {
+            await invoke("launch_file_with_args", {
+              filepath: result.filepath,
+              args,
+              query: search,
+            });
+          } else {
+            await invoke("launch_file", { filepath: result.filepath, query });
+          }
+        }
         // Hide window after launching
         const win = getCurrentWindow();
         await win.hide();
@@ -29,7 +144,29 @@ function App() {
         console.error("Launch error:", error);
       }
     },
-    [results, clearSearch],
+    [results, query, clearSearch],
+  );
+
+  // Run the selected file as administrator; only real files can be elevated
+  const handleSelectElevated = useCallback(
+    async (index: number) => {
+      const result = results[index];
+      if (!result || result.id < 0) return;
+
+      try {
+        await invoke("launch_file_elevated", {
+          filepath: result.filepath,
+          query: splitLaunchArgs(query).search,
+        });
+        const win = getCurrentWindow();
+        await win.hide();
+        clearSearch();
+      } catch (error) {
+        // Includes a declined UAC prompt; keep the window open to say so
+        setCommandMessage(String(error));
+      }
+    },
+    [results, query, clearSearch],
   );
 
   // Handle Escape: hide window and clear search
@@ -47,6 +184,90 @@ function App() {
     results.length,
     handleSelect,
     handleEscape,
+    handleSelectElevated,
+  );
+
+  // Copy the inline answer (calculator, conversions) and hide the window
+  const handleCopyAnswer = useCallback(async () => {
+    if (!mathResult) return;
+
+    try {
+      await invoke("copy_to_clipboard", { text: mathResult });
+      const win = getCurrentWindow();
+      await win.hide();
+      clearSearch();
+    } catch (error) {
+      console.error("Copy error:", error);
+    }
+  }, [mathResult, clearSearch]);
+
+  // End the processes of a running app, keeping the window open to say so
+  const handleEndTask = useCallback(
+    async (index: number) => {
+      const result = results[index];
+      if (!result?.is_running) return;
+
+      try {
+        if (!(await endTask(result))) return;
+        setCommandMessage(`Ended ${result.display_name ?? result.filename}`);
+        refresh();
+      } catch (error) {
+        setCommandMessage(String(error));
+      }
+    },
+    [results, refresh],
+  );
+
+  // Move the selected file or folder to the Recycle Bin after asking
+  const handleDelete = useCallback(
+    async (index: number) => {
+      const result = results[index];
+      if (result?.match_type === "clipboard") {
+        // Forgetting a history entry needs no confirmation
+        try {
+          await invoke("remove_clipboard_entry", { id: Number(result.filepath) });
+          refresh();
+        } catch (error) {
+          setCommandMessage(String(error));
+        }
+        return;
+      }
+      if (!result || result.id < 0) return;
+      if (!window.confirm(`Move "${result.filename}" to the Recycle Bin?`)) {
+        return;
+      }
+
+      try {
+        await invoke("delete_file", {
+          target: String(result.id),
+          confirm: true,
+        });
+        setCommandMessage(`Moved ${result.filename} to the Recycle Bin`);
+        refresh();
+      } catch (error) {
+        setCommandMessage(String(error));
+      }
+    },
+    [results, refresh],
+  );
+
+  // Enter copies the inline answer when one is shown; Ctrl+1-9 still launches.
+  // Shift+Delete recycles the selected result (or forgets a clipboard entry).
+  const handleInputKeyDown = useCallback(
+    (e: React.KeyboardEvent) => {
+      if (e.key === "Enter" && mathResult && !e.ctrlKey) {
+        e.preventDefault();
+        handleCopyAnswer();
+        return;
+      }
+      if (e.key === "Delete" && e.shiftKey) {
+        e.preventDefault();
+        handleDelete(selectedIndex);
+        return;
+      }
+      handleKeyDown(e);
+    },
+    [mathResult, handleCopyAnswer, handleDelete, selectedIndex, handleKeyDown],
   );
 
   // Listen for backend events
@@ -65,6 +286,67 @@ function App() {
         .catch(console.error);
     }).then((fn) => unlisteners.push(fn));
 
+    listen<string>("indexing-failed", (event) => {
+      setIsIndexing(false);
+      setCommandMessage(`Indexing failed: ${event.payload}`);
+    }).then((fn) => unlisteners.push(fn));
+
+    listen<AppError>("app-error", (event) => {
+      if (event.payload.severity !== "transient") {
+        setCommandMessage(event.payload.message);
+      }
+    }).then((fn) => unlisteners.push(fn));
+
+    // A launch failed because the file was deleted since indexing
+    listen<string>("stale-entry-removed", (event) => {
+      const name = event.payload.split(/[\\/]/).pop();
+      setCommandMessage(`${name} no longer exists — removed from index`);
+      refresh();
+    }).then((fn) => unlisteners.push(fn));
+
+    listen<UpdateInfo>("update-available", (event) => {
+      const { version, current_version } = event.payload;
+      setCommandMessage(
+        `AnCheck ${version} is available (you have ${current_version}); run >Install update to get it`,
+      );
+    }).then((fn) => unlisteners.push(fn));
+
+    // Outcome of a tray "Check for Updates" with nothing to install
+    listen<string>("update-status", (event) => {
+      setCommandMessage(event.payload);
+    }).then((fn) => unlisteners.push(fn));
+
+    listen<DownloadProgress>("update-download-progress", (event) => {
+      const { downloaded, total } = event.payload;
+      const percent = total ? ` ${Math.floor((downloaded / total) * 100)}%` : "";
+      setCommandMessage(`Downloading update…${percent}`);
+    }).then((fn) => unlisteners.push(fn));
+
+    listen<boolean>("window-pinned", (event) => {
+      setIsPinned(event.payload);
+    }).then((fn) => unlisteners.push(fn));
+
+    // Cleared usage and a reset index change what searches return
+    listen("usage-data-cleared", () => {
+      refresh();
+    }).then((fn) => unlisteners.push(fn));
+
+    listen<number>("index-reset", () => {
+      setIndexCount(0);
+      refresh();
+    }).then((fn) => unlisteners.push(fn));
+
+    // An ancheck://search link, or text selected when the selection hotkey was pressed
+    listen<string>("prefill-query", (event) => {
+      setQuery(event.payload);
+    }).then((fn) => unlisteners.push(fn));
+
+    invoke<string | null>("take_startup_query")
+      .then((startupQuery) => {
+        if (startupQuery !== null) setQuery(startupQuery);
+      })
+      .catch(console.error);
+
     // Get initial count
     invoke<number>("get_index_count")
       .then(setIndexCount)
@@ -75,10 +357,14 @@ function App() {
       .then(setIsIndexing)
       .catch(console.error);
 
+    invoke<boolean>("is_window_pinned")
+      .then(setIsPinned)
+      .catch(console.error);
+
     return () => {
       unlisteners.forEach((fn) => fn());
     };
-  }, []);
+  }, [refresh, setQuery]);
 
   return (
     <div className="app-container">
@@ -86,21 +372,36 @@ function App() {
         query={query}
         onQueryChange={setQuery}
         onClear={clearSearch}
-        onKeyDown={handleKeyDown}
+        onKeyDown={handleInputKeyDown}
         isLoading={isLoading}
       />
 
       {/* Status bar */}
       <div className="status-bar">
-        <span>
-          {indexCount > 0 ? `${indexCount.toLocaleString()} files indexed` : ""}
-        </span>
+        {filterErrors.length > 0 ? (
+          <span className="filter-error">{filterErrors[0]}</span>
+        ) : commandMessage ? (
+          <span>{commandMessage}</span>
+        ) : (
+          <span>
+            {indexCount > 0 ? `${indexCount.toLocaleString()} files indexed` : ""}
+          </span>
+        )}
         {isIndexing && (
           <span className="indexing">
             <span className="spinner" />
             Indexing...
           </span>
         )}
+        <button
+          className={`pin-toggle${isPinned ? " pinned" : ""}`}
+          title={isPinned ? "Unpin: hide when focus is lost" : "Pin: stay open when focus is lost"}
+          onClick={() =>
+            invoke("set_window_pinned", { pinned: !isPinned }).catch(console.error)
+          }
+        >
+          📌
+        </button>
       </div>
 
       <ResultsList
@@ -110,6 +411,7 @@ function App() {
         selectedIndex={selectedIndex}
         onSelect={handleSelect}
         onHover={setSelectedIndex}
+        onEndTask={handleEndTask}
         isLoading={isLoading}
       />
 
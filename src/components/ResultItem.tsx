@@ -1,6 +1,38 @@
-import React, { useCallback } from "react";
+import React, { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { SearchResult } from "../hooks/useSearch";
+import type { SearchResult, Thumbnail } from "../hooks/useSearch";
+
+/** Extensions the backend can thumbnail besides `file_type` "image". */
+const VIDEO_EXTENSIONS = ["mp4", "m4v", "mkv", "avi", "mov", "wmv", "webm"];
+/** Thumbnail size asked for: the icon box at 2x for high-DPI screens. */
+const THUMBNAIL_PX = 72;
+
+/** Load the thumbnail of an image or video result, or null while there is none. */
+function useThumbnail(result: SearchResult): string | null {
+  const [src, setSrc] = useState<string | null>(null);
+  const wanted =
+    result.id > 0 &&
+    (result.file_type === "image" ||
+      VIDEO_EXTENSIONS.includes(result.extension.toLowerCase()));
+
+  useEffect(() => {
+    setSrc(null);
+    if (!wanted) return;
+    let cancelled = false;
+    invoke<Thumbnail>("get_thumbnail", { id: result.id, maxPx: THUMBNAIL_PX })
+      .then((thumbnail) => {
+        if (!cancelled && thumbnail.kind === "ready") {
+          setSrc(`data:image/png;base64,${thumbnail.data_base64}`);
+        }
+      })
+      .catch((err) => console.error("Failed to load thumbnail:", err));
+    return () => {
+      cancelled = true;
+    };
+  }, [result.id, wanted]);
+
+  return src;
+}
 
 /** Map file_type to an emoji icon and badge style. */
 function getFileIcon(fileType: string, extension: string): string {
@@ -17,6 +49,24 @@ function getFileIcon(fileType: string, extension: string): string {
       return "🖼️";
     case "code":
       return "💻";
+    case "url":
+      return "🌐";
+    case "bookmark":
+      return "🔖";
+    case "web_search":
+      return "🔍";
+    case "command":
+      return "⚡";
+    case "action":
+      return "⏻";
+    case "window":
+      return "🪟";
+    case "snippet":
+      return "📋";
+    case "clipboard":
+      return "📎";
+    case "no_command":
+      return "❔";
     default:
       return "📄";
   }
@@ -43,7 +93,11 @@ function getDocIcon(ext: string): string {
   }
 }
 
-/** Render the filename with matched characters highlighted. */
+/**
+ * Render the filename with matched characters highlighted.
+ * `matchedIndices` are code point (not UTF-16) positions, so iterate with
+ * `Array.from` to keep surrogate pairs such as emoji intact.
+ */
 function highlightName(
   name: string,
   matchedIndices: number[],
@@ -52,22 +106,23 @@ function highlightName(
     return name;
   }
 
+  const chars = Array.from(name);
   const indexSet = new Set(matchedIndices);
   const parts: React.ReactNode[] = [];
   let currentRun = "";
   let isHighlightRun = false;
 
-  for (let i = 0; i < name.length; i++) {
+  for (let i = 0; i < chars.length; i++) {
     const shouldHighlight = indexSet.has(i);
 
     if (i === 0) {
       isHighlightRun = shouldHighlight;
-      currentRun = name[i];
+      currentRun = chars[i];
       continue;
     }
 
     if (shouldHighlight === isHighlightRun) {
-      currentRun += name[i];
+      currentRun += chars[i];
     } else {
       // Flush current run
       if (isHighlightRun) {
@@ -79,7 +134,7 @@ function highlightName(
       } else {
         parts.push(<span key={`n-${i}`}>{currentRun}</span>);
       }
-      currentRun = name[i];
+      currentRun = chars[i];
       isHighlightRun = shouldHighlight;
     }
   }
@@ -100,17 +155,19 @@ function highlightName(
   return parts;
 }
 
-/** Format file size in human-readable form. */
-function formatSize(bytes: number): string {
-  if (bytes === 0) return "";
-  const units = ["B", "KB", "MB", "GB"];
-  let idx = 0;
-  let size = bytes;
-  while (size >= 1024 && idx < units.length - 1) {
-    size /= 1024;
-    idx++;
+/** Largest child count the indexer records (`CHILD_COUNT_CAP`). */
+const CHILD_COUNT_CAP = 10000;
+
+/** "214 items" for a counted folder, "Empty" for an empty one. */
+function childCountLabel(count: number | undefined): string {
+  if (count === undefined) {
+    return "";
+  }
+  if (count === 0) {
+    return "Empty";
   }
-  return `${size.toFixed(idx === 0 ? 0 : 1)} ${units[idx]}`;
+  const shown = count.toLocaleString() + (count >= CHILD_COUNT_CAP ? "+" : "");
+  return `${shown} ${count === 1 ? "item" : "items"}`;
 }
 
 interface ResultItemProps {
@@ -119,6 +176,7 @@ interface ResultItemProps {
   isSelected: boolean;
   onSelect: (index: number) => void;
   onHover: (index: number) => void;
+  onEndTask: (index: number) => void;
 }
 
 const ResultItem: React.FC<ResultItemProps> = ({
@@ -127,19 +185,26 @@ const ResultItem: React.FC<ResultItemProps> = ({
   isSelected,
   onSelect,
   onHover,
+  onEndTask,
 }) => {
-  const icon = getFileIcon(result.file_type, result.extension);
+  const icon =
+    result.match_type === "emoji"
+      ? result.filename
+      : getFileIcon(result.file_type, result.extension);
+  const thumbnail = useThumbnail(result);
 
   const handleContextMenu = useCallback(
     async (e: React.MouseEvent) => {
       e.preventDefault();
+      // URLs, bookmarks and web searches have no folder to open
+      if (result.id < 0 || result.file_type === "bookmark") return;
       try {
         await invoke("open_containing_folder", { filepath: result.filepath });
       } catch (err) {
         console.error("Failed to open folder:", err);
       }
     },
-    [result.filepath],
+    [result.id, result.filepath],
   );
 
   return (
@@ -153,23 +218,45 @@ const ResultItem: React.FC<ResultItemProps> = ({
       title="Right-click to open containing folder"
     >
       {/* Icon */}
-      <div className="result-icon">{icon}</div>
+      <div className="result-icon">
+        {thumbnail ? <img className="result-thumbnail" src={thumbnail} alt="" /> : icon}
+      </div>
 
       {/* File info */}
       <div className="result-info">
-        <div className="result-name">
-          {highlightName(result.filename, result.matched_indices)}
+        <div className="result-name" title={result.display_name ? result.filename : undefined}>
+          {highlightName(result.display_name ?? result.filename, result.matched_indices)}
         </div>
         <div className="result-path" title={result.filepath}>
-          {result.filepath}
+          {result.path_indices?.length
+            ? highlightName(result.parent_dir, result.path_indices)
+            : result.parent_dir || result.filepath}
         </div>
       </div>
 
       {/* Meta info */}
       <div className="result-meta">
-        {result.file_size > 0 && (
+        {(result.file_size_display || result.modified_display || result.child_count !== undefined) && (
           <span className="result-path" style={{ fontSize: "10px" }}>
-            {formatSize(result.file_size)}
+            {[childCountLabel(result.child_count), result.file_size_display, result.modified_display]
+              .filter(Boolean)
+              .join(" · ")}
+          </span>
+        )}
+        {result.is_running && (
+          <span className="result-running">
+            Running
+            <button
+              className="end-task"
+              title="End every process of this app"
+              onClick={(e) => {
+                // Ending the app mustn't also launch it
+                e.stopPropagation();
+                onEndTask(index);
+              }}
+            >
+              End task
+            </button>
           </span>
         )}
         <span className={`result-badge ${result.file_type}`}>
@@ -2,6 +2,17 @@ import React, { useRef, useEffect } from "react";
 import type { SearchResult } from "../hooks/useSearch";
 import ResultItem from "./ResultItem";
 
+const SECTION_TITLES: Record<string, string> = {
+  pinned: "Pinned",
+  recent: "Recent",
+  frequent: "Frequently used",
+  apps: "Apps",
+  actions: "Actions",
+  folders: "Folders",
+  documents: "Documents",
+  other: "Other files",
+};
+
 interface ResultsListProps {
   results: SearchResult[];
   mathResult: string | null;
@@ -9,6 +20,7 @@ interface ResultsListProps {
   selectedIndex: number;
   onSelect: (index: number) => void;
   onHover: (index: number) => void;
+  onEndTask: (index: number) => void;
   isLoading: boolean;
 }
 
@@ -19,6 +31,7 @@ const ResultsList: React.FC<ResultsListProps> = ({
   selectedIndex,
   onSelect,
   onHover,
+  onEndTask,
   isLoading,
 }) => {
   const containerRef = useRef<HTMLDivElement>(null);
@@ -40,8 +53,8 @@ const ResultsList: React.FC<ResultsListProps> = ({
     }
   }, [selectedIndex]);
 
-  // Show nothing if no query
-  if (!query.trim()) {
+  // Show the hint if there is no query and nothing pinned or recent
+  if (!query.trim() && results.length === 0) {
     return (
       <div className="results-container">
         <div className="no-results">
@@ -94,25 +107,37 @@ const ResultsList: React.FC<ResultsListProps> = ({
         <div className="math-result">
           <span className="equals">=</span>
           <span className="value">{mathResult}</span>
-          <span className="label">Calculator</span>
+          <span className="label">Calculator · Enter to copy</span>
         </div>
       )}
 
-      {/* File results */}
-      {results.map((result, idx) => (
-        <div
-          key={result.id}
-          ref={idx === selectedIndex ? selectedRef : undefined}
-        >
-          <ResultItem
-            result={result}
-            index={idx}
-            isSelected={idx === selectedIndex}
-            onSelect={onSelect}
-            onHover={onHover}
-          />
-        </div>
-      ))}
+      {/* File results, with headers for the empty-query sections and the
+          groups of a grouped search */}
+      {results.map((result, idx) => {
+        const heading = result.section ?? result.group;
+        const previous = results[idx - 1];
+        return (
+          <div
+            key={result.id}
+            ref={idx === selectedIndex ? selectedRef : undefined}
+          >
+            {heading &&
+              heading !== (previous?.section ?? previous?.group) && (
+                <div className="section-header">
+                  {SECTION_TITLES[heading] ?? heading}
+                </div>
+              )}
+            <ResultItem
+              result={result}
+              index={idx}
+              isSelected={idx === selectedIndex}
+              onSelect={onSelect}
+              onHover={onHover}
+              onEndTask={onEndTask}
+            />
+          </div>
+        );
+      })}
     </div>
   );
 };
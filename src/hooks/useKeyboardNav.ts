@@ -2,12 +2,14 @@ import { useState, useCallback, useEffect } from "react";
 
 /**
  * Keyboard navigation hook for the results list.
- * Handles ↑/↓ arrows, Enter, Esc, Tab, Ctrl+1-9 quick-launch.
+ * Handles ↑/↓ arrows, Enter, Ctrl+Shift+Enter (run as administrator), Esc,
+ * Tab, Ctrl+1-9 quick-launch.
  */
 export function useKeyboardNav(
   resultCount: number,
   onSelect: (index: number) => void,
   onEscape: () => void,
+  onSelectElevated?: (index: number) => void,
 ) {
   const [selectedIndex, setSelectedIndex] = useState(0);
 
@@ -54,7 +56,10 @@ export function useKeyboardNav(
 
         case "Enter":
           e.preventDefault();
-          if (resultCount > 0) {
+          if (resultCount === 0) break;
+          if (e.ctrlKey && e.shiftKey && onSelectElevated) {
+            onSelectElevated(selectedIndex);
+          } else {
             onSelect(selectedIndex);
           }
           break;
@@ -68,7 +73,7 @@ export function useKeyboardNav(
           break;
       }
     },
-    [resultCount, selectedIndex, onSelect, onEscape],
+    [resultCount, selectedIndex, onSelect, onEscape, onSelectElevated],
   );
 
   return {
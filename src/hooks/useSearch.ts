@@ -1,10 +1,13 @@
 import { useState, useEffect, useRef, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 /** Shape of a search result from the Rust backend. */
 export interface SearchResult {
   id: number;
   filename: string;
+  /** App name read from a shortcut or program, shown instead of `filename`. */
+  display_name?: string;
   filepath: string;
   extension: string;
   file_size: number;
@@ -12,74 +15,529 @@ export interface SearchResult {
   file_type: string;
   click_count: number;
   last_accessed: number;
+  /**
+   * Containing folder; empty for URLs and web searches. For open windows
+   * (`match_type` "window") the program, with the window handle as `filepath`;
+   * for snippets the start of the text, with the snippet id as `filepath`;
+   * for clipboard history entries when they were copied, with the entry id
+   * as `filepath`.
+   */
+  parent_dir: string;
+  /** Human-readable size ("4.2 MB"); empty when unknown. */
+  file_size_display: string;
+  /** Relative modification time ("3 days ago"); empty when unknown. */
+  modified_display: string;
+  /** Items directly inside a folder result, capped at 10,000; absent when not counted. */
+  child_count?: number;
   score: number;
   match_type: string;
+  /** Positions in the shown name (`display_name ?? filename`) that matched. */
   matched_indices: number[];
+  /** Positions in `parent_dir` a deep path search matched. */
+  path_indices?: number[];
+  /** Only present when the search was run with `explain: true`. */
+  score_breakdown?: ScoreBreakdown;
+  /** Other locations of the same file folded into this result. */
+  duplicates?: string[];
+  /** Empty-query section: "pinned", "recent" or "frequent". */
+  section?: string;
+  /** Category in a grouped search: "apps", "actions", "folders", "documents" or "other". */
+  group?: string;
+  /** Apps and shortcuts whose program currently has a process. */
+  is_running?: boolean;
+  running_pids?: number[];
+}
+
+/** How a result's score was computed; the numeric fields sum to `score`. */
+export interface ScoreBreakdown {
+  branch: string;
+  match_score: number;
+  position_adjustment: number;
+  length_bonus: number;
+  extension_adjustment: number;
+  type_boost: number;
+  size_adjustment: number;
+  click_boost: number;
+  recency_boost: number;
+  personal_boost: number;
+  context_boost: number;
+}
+
+/**
+ * Filters parsed from `ext:`, `type:` and `in:` tokens (prefixed with `-` to exclude),
+ * plus `size:` and `modified:` bounds.
+ */
+export interface QueryFilters {
+  extensions: string[];
+  file_types: string[];
+  locations: string[];
+  exclude_extensions: string[];
+  exclude_file_types: string[];
+  exclude_locations: string[];
+  min_size: number | null;
+  max_size: number | null;
+  modified_after: number | null;
+  modified_before: number | null;
+  /** Case-sensitive text from quoted phrases and `cs:` queries. */
+  required_terms: { text: string; whole_word: boolean }[];
+}
+
+/** Shape of the `search` command response. */
+export interface SearchResponse {
+  results: SearchResult[];
+  filters: QueryFilters;
+  filter_errors: string[];
+  /** Only present when the search was run with `metrics: true`. */
+  meta?: SearchMeta;
+}
+
+/** A context menu action from `get_actions`, run with `run_action`. */
+export interface ResultAction {
+  id: string;
+  label: string;
+  /** `run_action` fails with "confirmation_required" unless `confirmed`. */
+  needs_confirm: boolean;
+}
+
+/** Details pane preview from `get_file_preview`. */
+export type FilePreview =
+  | { kind: "text"; content: string; truncated: boolean }
+  | { kind: "image"; data_base64: string; width: number; height: number }
+  | { kind: "none" };
+
+/** A result row's thumbnail from `get_thumbnail`, a PNG when there is one. */
+export type Thumbnail =
+  | { kind: "ready"; data_base64: string }
+  | { kind: "unavailable" };
+
+/** Details pane information from `get_file_details`; times are Unix seconds. */
+export interface FileDetails {
+  filepath: string;
+  /** Exact size in bytes; null for folders. */
+  size: number | null;
+  created_at: number | null;
+  modified_at: number | null;
+  accessed_at: number | null;
+  /** What a shortcut points at. */
+  target_path: string | null;
+  version: {
+    product_name: string | null;
+    product_version: string | null;
+    file_description: string | null;
+    file_version: string | null;
+    company_name: string | null;
+  } | null;
+  /** Null for anything that isn't a program. */
+  is_running: boolean | null;
+}
+
+/** One file's result from `launch_files`. */
+export interface LaunchOutcome {
+  filepath: string;
+  ok: boolean;
+  error?: string;
+}
+
+/** Whether one hotkey passed to `set_hotkeys` could be registered. */
+export interface HotkeyStatus {
+  hotkey: string;
+  registered: boolean;
+  error?: string;
+}
+
+/** Payload of the `app-error` event and entries of `get_recent_errors`. */
+export interface AppError {
+  source: string;
+  message: string;
+  severity: "transient" | "error" | "fatal";
+  /** Unix seconds. */
+  timestamp: number;
+}
+
+/** What a custom entry's target is. */
+export type EntryKind = "file" | "url" | "command";
+
+/** A user-added entry from `get_custom_entries`. */
+export interface CustomEntry {
+  id: number;
+  name: string;
+  target: string;
+  kind: EntryKind;
+  /** When a command entry was confirmed (Unix seconds); 0 if never. */
+  confirmed_at: number;
+}
+
+/** Result of `check_for_updates` and payload of `update-available`. */
+export interface UpdateInfo {
+  available: boolean;
+  current_version: string;
+  version: string | null;
+  /** Release notes. */
+  notes: string | null;
+  /** When the new version was published (Unix seconds). */
+  date: number | null;
+}
+
+/** Payload of `update-download-progress`; sizes in bytes. */
+export interface DownloadProgress {
+  downloaded: number;
+  total: number | null;
+}
+
+/** Result of `clear_usage_data` and payload of `usage-data-cleared`. */
+export interface UsageCleared {
+  /** Files and custom entries whose launch count and time were reset. */
+  launches: number;
+  query_picks: number;
+  context_picks: number;
+  snippets: number;
+  clipboard_entries: number;
+}
+
+/** What the first-run index covers. */
+export type IndexScope = "apps" | "apps_and_documents" | "everything";
+
+/** From `get_onboarding_state`, for the first-run chooser. */
+export interface OnboardingState {
+  /** Nothing is indexed until a choice is passed to `complete_onboarding`. */
+  first_run: boolean;
+  choices: { scope: IndexScope; roots: string[] }[];
+  /** Seconds until apps only is picked automatically; null once done. */
+  seconds_left: number | null;
+}
+
+/** Which launches `get_usage_leaderboard` counts. */
+export type UsagePeriod = "all_time" | "this_month" | "this_week";
+
+/** One place from `get_usage_leaderboard`. */
+export interface LeaderboardEntry {
+  /** 1 for the most launched. */
+  rank: number;
+  /** Launches in the period. */
+  launches: number;
+  /**
+   * Places gained from last week's ranking to this week's (negative when it
+   * dropped); null when it wasn't launched in one of the two weeks.
+   */
+  rank_change: number | null;
+  entry: {
+    id: number;
+    filename: string;
+    filepath: string;
+    extension: string;
+    file_size: number;
+    modified_at: number;
+    file_type: string;
+    click_count: number;
+    last_accessed: number;
+    icon_path: string | null;
+    child_count: number | null;
+    /** Empty when the entry has no display name. */
+    display_name: string;
+  };
+}
+
+/** A text snippet from `get_snippets`, used with `use_snippet`. */
+export interface Snippet {
+  id: number;
+  name: string;
+  keywords: string[];
+  body: string;
+  use_count: number;
+}
+
+export type IndexTrigger = "startup" | "manual" | "background";
+
+/** Summary of the last finished index pass. */
+export interface IndexRunSummary {
+  trigger: IndexTrigger;
+  started_at: number;
+  finished_at: number;
+  files_indexed: number;
+  files_removed: number;
+  error: string | null;
+  /** Each index provider that ran, in order. */
+  providers: ProviderSummary[];
+  /** Each index root and whether it was scanned; empty when the file system walk didn't run. */
+  roots: RootCoverage[];
+}
+
+/** How one index root fared in a pass. */
+export interface RootCoverage {
+  root: string;
+  status: { status: "scanned" | "skipped_missing" } | { status: "error"; error: string };
+  /** Scanned before but not this time; its entries are kept until it's back. */
+  stale: boolean;
+  newly_stale: boolean;
+}
+
+/** How one index provider (the file system walk, …) did in a pass. */
+export interface ProviderSummary {
+  name: string;
+  entries: number;
+  /** Items it couldn't read, such as folders it has no access to. */
+  skipped: number;
+  /** Its own entries (bookmarks, …) it no longer found, now removed. */
+  removed: number;
+  duration_ms: number;
+  error: string | null;
+  /** What it read from, e.g. the folders the file system walk scanned. */
+  sources: string[];
+}
+
+/** Result of `get_indexing_status`. */
+export interface IndexingStatus {
+  running: boolean;
+  phase: "idle" | "removing_missing" | "scanning" | "finishing";
+  trigger: IndexTrigger | null;
+  files_processed: number;
+  current_root: string | null;
+  started_at: number | null;
+  /** Rough seconds left, if there was a previous pass to compare with. */
+  eta_hint: number | null;
+  last_run: IndexRunSummary | null;
+  /** Why background passes are being put off, which leaves the index stale. */
+  skipped: { reason: "user_active" | "on_battery"; skipped_at: number } | null;
+}
+
+/** All settings, as returned by `get_settings`. */
+export interface Settings {
+  hotkeys: string[];
+  /** Global hotkey relaunching the last launched item; empty when off. */
+  relaunch_hotkey: string;
+  /** Global hotkey searching the text selected in another app; empty when off. */
+  selection_hotkey: string;
+  result_limit: number;
+  /** Fall back to fuzzy matching when few results are found; on by default. */
+  enable_fuzzy: boolean;
+  deep_path_search: boolean;
+  /** Rank what was launched from the app the launcher opened over a little higher; on by default. */
+  context_boost: boolean;
+  /** Leave empty files (not folders or apps) out of results; off by default. */
+  hide_empty_files: boolean;
+  /** Size in MiB above which files rank a little lower; 0 turns that off. */
+  large_file_mb: number;
+  /** List results by category, each capped by `group_caps`; off by default. */
+  group_results: boolean;
+  group_caps: { apps: number; folders: number; documents: number; other: number };
+  index_interval_minutes: number;
+  excluded_extensions: string[];
+  /** Index providers turned on or off by name; one left out runs. */
+  index_providers: Record<string, boolean>;
+  /** Folders to index; empty for the default ones. */
+  index_roots: string[];
+  file_manager: string;
+  /** Open bookmarks in the default browser or the one they came from. */
+  bookmark_browser: "default" | "source";
+  window_placement: "cursor" | "focused_window" | "primary";
+  log_level: "error" | "warn" | "info" | "debug" | "trace";
+  /** Language of the tray menu, notifications and launch errors. */
+  locale: "en" | "de" | "es" | "hi";
+  /** Paste a picked snippet or clipboard entry into the previous window. */
+  auto_paste_snippets: boolean;
+  /** Keep copied text for `clip ` queries; off by default. */
+  clipboard_history: boolean;
+  /** Days between background update checks; 0 turns them off. */
+  update_check_interval_days: number;
+  /** Start with the window pinned open. */
+  pin_by_default: boolean;
+  /** Toast when a background index pass fails, runs long or removes many files. */
+  index_notifications: boolean;
+  /** Minutes after which a background index pass counts as long. */
+  notify_slow_index_minutes: number;
+  /** Removed files from which a background index pass is reported. */
+  notify_removed_files: number;
+  /** Seconds without input before a background index pass runs; 0 doesn't wait. */
+  index_idle_seconds: number;
+  /** Run background index passes on battery too. */
+  index_on_battery: boolean;
+  /** Paths launched by Ctrl+Alt+1 through Ctrl+Alt+9, keyed by slot number. */
+  quick_slots: Record<string, string>;
+}
+
+/** A bound quick slot, as returned by `get_quick_slots`. */
+export interface QuickSlot {
+  slot: number;
+  hotkey: string;
+  filepath: string;
+  /** Display name or filename if indexed, else the last part of the path. */
+  name: string;
+  indexed: boolean;
+  /** Why the slot's hotkey couldn't be registered, if it couldn't. */
+  hotkey_error: string | null;
+}
+
+/** Result of `update_settings`; `hotkeys` is set when the update changed them. */
+export interface SettingsUpdate {
+  settings: Settings;
+  hotkeys?: HotkeyStatus[];
+  relaunch_hotkey?: HotkeyStatus;
+  selection_hotkey?: HotkeyStatus;
+  /** Set when the update changed quick slots; one status per bound slot. */
+  quick_slot_hotkeys?: HotkeyStatus[];
+}
+
+/** Stage timings (milliseconds) and candidate counts for one search. */
+export interface SearchMeta {
+  sql_ms: number;
+  sql_candidates: number;
+  fuzzy_ran: boolean;
+  fuzzy_ms: number;
+  fuzzy_candidates: number;
+  deep_path_ran: boolean;
+  deep_path_ms: number;
+  total_ms: number;
+  reused_candidates: boolean;
+}
+
+/** Payload of the `search-results` event sent for `update_query`. */
+export interface SessionResults {
+  session_id: number;
+  query: string;
+  response: SearchResponse;
+}
+
+/**
+ * Split launch arguments off a query: everything after a standalone `--` is
+ * passed to the launched program (`code -- .`). Double quotes group an
+ * argument containing spaces.
+ */
+export function splitLaunchArgs(query: string): {
+  search: string;
+  args: string[];
+} {
+  const separator = /(^|\s)--(\s|$)/.exec(query);
+  if (!separator) return { search: query, args: [] };
+
+  const rest = query.slice(separator.index + separator[0].length);
+  const args = Array.from(rest.matchAll(/"([^"]*)"|(\S+)/g), (m) =>
+    m[1] !== undefined ? m[1] : m[2],
+  );
+  return { search: query.slice(0, separator.index), args };
 }
 
 /**
  * Custom hook that manages search state:
- * - Debounced query dispatch to Rust backend
+ * - Debounced query dispatch to Rust backend, through a search-as-you-type
+ *   session: each query goes to `update_query` and results come back as
+ *   `search-results` events
  * - Math expression evaluation
  * - Loading state
  */
 export function useSearch(debounceMs: number = 50) {
   const [query, setQuery] = useState("");
   const [results, setResults] = useState<SearchResult[]>([]);
+  const [filters, setFilters] = useState<QueryFilters | null>(null);
+  const [filterErrors, setFilterErrors] = useState<string[]>([]);
   const [mathResult, setMathResult] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState(false);
+  // Bumped to re-run the current query after the index changed under it
+  const [refreshToken, setRefreshToken] = useState(0);
   const timerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const abortRef = useRef(0); // generation counter to ignore stale results
+  const sessionRef = useRef<Promise<number> | null>(null);
+  // The query whose `search-results` event is still wanted
+  const pendingQueryRef = useRef<string | null>(null);
+
+  const startSession = useCallback(() => {
+    sessionRef.current = invoke<number>("start_search_session");
+    return sessionRef.current;
+  }, []);
 
-  const performSearch = useCallback(async (q: string, generation: number) => {
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+    listen<SessionResults>("search-results", async (event) => {
+      const { session_id, query: resultQuery, response } = event.payload;
+      const current = await sessionRef.current?.catch(() => null);
+      if (session_id !== current || resultQuery !== pendingQueryRef.current) return;
+      pendingQueryRef.current = null;
+      setResults(response.results);
+      setFilters(response.filters);
+      setFilterErrors(response.filter_errors);
+      setIsLoading(false);
+    }).then((fn) => {
+      if (cancelled) fn();
+      else unlisten = fn;
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  const performSearch = useCallback(async (input: string, generation: number) => {
+    // Launch arguments aren't part of the search
+    const q = splitLaunchArgs(input).search;
     if (!q.trim()) {
-      setResults([]);
+      // An empty query shows pinned, recent and frequent files
+      pendingQueryRef.current = null;
+      setFilters(null);
+      setFilterErrors([]);
       setMathResult(null);
       setIsLoading(false);
+      try {
+        const homeResponse = await invoke<SearchResponse>("search", { query: "" });
+        if (generation === abortRef.current) {
+          setResults(homeResponse.results);
+        }
+      } catch (error) {
+        console.error("Search error:", error);
+        if (generation === abortRef.current) {
+          setResults([]);
+        }
+      }
       return;
     }
 
     setIsLoading(true);
+    pendingQueryRef.current = q;
+
+    const updateQuery = async (sessionId: number) =>
+      invoke("update_query", { sessionId, query: q });
 
     try {
-      // Run search and math eval in parallel
-      const [searchResults, mathEval] = await Promise.all([
-        invoke<SearchResult[]>("search", { query: q }),
+      // Send the query and run math eval in parallel; the results arrive
+      // as a `search-results` event, and none if a newer query replaced it
+      const [, mathEval] = await Promise.all([
+        (sessionRef.current ?? startSession())
+          .then(updateQuery)
+          .catch((error) => {
+            // The session was dropped after sitting idle: start another
+            if (!String(error).startsWith("No search session")) throw error;
+            return startSession().then(updateQuery);
+          }),
         invoke<string | null>("eval_math", { query: q }),
       ]);
 
-      // Only update if this is still the latest generation
       if (generation === abortRef.current) {
-        setResults(searchResults);
         setMathResult(mathEval);
       }
     } catch (error) {
       console.error("Search error:", error);
       if (generation === abortRef.current) {
+        pendingQueryRef.current = null;
         setResults([]);
+        setFilters(null);
+        setFilterErrors([]);
         setMathResult(null);
-      }
-    } finally {
-      if (generation === abortRef.current) {
         setIsLoading(false);
       }
     }
-  }, []);
+  }, [startSession]);
 
   useEffect(() => {
     if (timerRef.current !== null) {
       clearTimeout(timerRef.current);
     }
 
+    const generation = ++abortRef.current;
+
     if (!query.trim()) {
-      setResults([]);
-      setMathResult(null);
-      setIsLoading(false);
+      performSearch(query, generation);
       return;
     }
 
-    const generation = ++abortRef.current;
-
     timerRef.current = setTimeout(() => {
       performSearch(query, generation);
     }, debounceMs);
@@ -89,11 +547,16 @@ export function useSearch(debounceMs: number = 50) {
         clearTimeout(timerRef.current);
       }
     };
-  }, [query, debounceMs, performSearch]);
+  }, [query, debounceMs, performSearch, refreshToken]);
+
+  const refresh = useCallback(() => setRefreshToken((t) => t + 1), []);
 
   const clearSearch = useCallback(() => {
+    pendingQueryRef.current = null;
     setQuery("");
     setResults([]);
+    setFilters(null);
+    setFilterErrors([]);
     setMathResult(null);
     setIsLoading(false);
   }, []);
@@ -102,8 +565,11 @@ export function useSearch(debounceMs: number = 50) {
     query,
     setQuery,
     results,
+    filters,
+    filterErrors,
     mathResult,
     isLoading,
     clearSearch,
+    refresh,
   };
 }